@@ -0,0 +1,288 @@
+//! Procedural macros for the [`amx`] crate. Use them through the re-exports
+//! in `amx`.
+//!
+//! [`amx`]: https://docs.rs/amx/
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+use std::fmt::Write;
+
+/// Generate a fully unrolled GEMM micro-kernel. See `amx::gemm_microkernel`
+/// for the documentation.
+#[proc_macro]
+pub fn gemm_microkernel(input: TokenStream) -> TokenStream {
+    match parse(input).and_then(|spec| spec.generate()) {
+        Ok(output) => output.parse().unwrap(),
+        Err(msg) => format!("::core::compile_error!({msg:?});").parse().unwrap(),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Elem {
+    I16,
+    F32,
+}
+
+impl Elem {
+    fn name(self) -> &'static str {
+        match self {
+            Self::I16 => "i16",
+            Self::F32 => "f32",
+        }
+    }
+
+    /// The number of elements in a register row.
+    fn lanes(self) -> usize {
+        match self {
+            Self::I16 => 32,
+            Self::F32 => 16,
+        }
+    }
+
+    /// The number of independent accumulator tiles in Z.
+    fn tiles(self) -> usize {
+        64 / self.lanes()
+    }
+
+    fn method(self) -> &'static str {
+        match self {
+            Self::I16 => "outer_product_i16_xy_to_z",
+            Self::F32 => "outer_product_f32_xy_to_z",
+        }
+    }
+}
+
+struct Spec {
+    attrs: String,
+    vis: String,
+    name: String,
+    elem: Elem,
+    mr: usize,
+    nr: usize,
+    unroll: usize,
+}
+
+fn parse(input: TokenStream) -> Result<Spec, String> {
+    let mut tokens = input.into_iter().peekable();
+    let mut attrs = String::new();
+    let mut vis = String::new();
+
+    // `#[...]` (including doc comments)
+    while let Some(TokenTree::Punct(p)) = tokens.peek() {
+        if p.as_char() != '#' {
+            break;
+        }
+        tokens.next();
+        match tokens.next() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => {
+                writeln!(attrs, "#{g}").unwrap();
+            }
+            _ => return Err("expected an attribute after `#`".into()),
+        }
+    }
+
+    // `pub`, `pub(...)`
+    if let Some(TokenTree::Ident(i)) = tokens.peek()
+        && i.to_string() == "pub"
+    {
+        vis.push_str("pub");
+        tokens.next();
+        if let Some(TokenTree::Group(g)) = tokens.peek()
+            && g.delimiter() == Delimiter::Parenthesis
+        {
+            write!(vis, "{g}").unwrap();
+            tokens.next();
+        }
+    }
+
+    match tokens.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "fn" => {}
+        _ => return Err("expected `fn`".into()),
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected a function name".into()),
+    };
+    let params = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g.stream(),
+        _ => return Err("expected `(ELEMENT_TYPE, mr = ..., nr = ...)`".into()),
+    };
+    match tokens.next() {
+        None => {}
+        Some(TokenTree::Punct(p)) if p.as_char() == ';' && tokens.next().is_none() => {}
+        Some(t) => return Err(format!("unexpected token `{t}`")),
+    }
+
+    // Split the parameters by commas
+    let mut items: Vec<Vec<TokenTree>> = vec![Vec::new()];
+    for t in params {
+        match &t {
+            TokenTree::Punct(p) if p.as_char() == ',' => items.push(Vec::new()),
+            _ => items.last_mut().unwrap().push(t),
+        }
+    }
+    if items.last().is_some_and(|i| i.is_empty()) {
+        items.pop();
+    }
+
+    let mut items = items.into_iter();
+    let elem = match items.next().as_deref() {
+        Some([TokenTree::Ident(i)]) => match i.to_string().as_str() {
+            "i16" => Elem::I16,
+            "f32" => Elem::F32,
+            other => return Err(format!("unsupported element type `{other}`")),
+        },
+        _ => return Err("expected an element type (`i16` or `f32`)".into()),
+    };
+
+    let (mut mr, mut nr, mut unroll) = (None, None, None);
+    for item in items {
+        let (key, value) = match &item[..] {
+            [
+                TokenTree::Ident(k),
+                TokenTree::Punct(eq),
+                TokenTree::Literal(v),
+            ] if eq.as_char() == '=' && eq.spacing() == Spacing::Alone => {
+                (k.to_string(), v.to_string())
+            }
+            _ => return Err("expected `KEY = INTEGER`".into()),
+        };
+        let value: usize = value
+            .parse()
+            .map_err(|_| format!("`{key}` must be an unsuffixed integer literal"))?;
+        let slot = match key.as_str() {
+            "mr" => &mut mr,
+            "nr" => &mut nr,
+            "unroll" => &mut unroll,
+            _ => return Err(format!("unknown parameter `{key}`")),
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("duplicate parameter `{key}`"));
+        }
+    }
+
+    Ok(Spec {
+        attrs,
+        vis,
+        name,
+        elem,
+        mr: mr.ok_or("missing parameter `mr`")?,
+        nr: nr.ok_or("missing parameter `nr`")?,
+        unroll: unroll.unwrap_or(1),
+    })
+}
+
+impl Spec {
+    fn generate(&self) -> Result<String, String> {
+        let Self {
+            ref attrs,
+            ref vis,
+            ref name,
+            elem,
+            mr,
+            nr,
+            unroll,
+        } = *self;
+        let (ty, lanes) = (elem.name(), elem.lanes());
+
+        if mr == 0 || nr == 0 || unroll == 0 {
+            return Err("`mr`, `nr`, and `unroll` must be non-zero".into());
+        }
+        if mr * nr > elem.tiles() {
+            return Err(format!(
+                "`mr * nr` must not exceed {} for `{ty}` (the number of Z tiles)",
+                elem.tiles()
+            ));
+        }
+        if unroll * nr > 8 || unroll * mr > 8 {
+            return Err(
+                "`unroll * nr` and `unroll * mr` must not exceed 8 (the number of X/Y rows)".into(),
+            );
+        }
+
+        let mut out = String::new();
+        writeln!(out, "{attrs}").unwrap();
+        writeln!(
+            out,
+            "#[inline(always)] {vis} unsafe fn {name}<A: ::amx::Amx + ?::core::marker::Sized>(\
+                ctx: &mut A, k: usize, a: *const {ty}, b: *const {ty}, accumulate: bool) {{"
+        )
+        .unwrap();
+        out.push_str("if k == 0 { return; }\n");
+        out.push_str("let (mut a, mut b) = (a, b);\n");
+        out.push_str("unsafe {\n");
+
+        // The first step determines whether the existing contents of Z are
+        // kept
+        self.emit_steps(&mut out, 1, "accumulate");
+        writeln!(out, "a = a.add({}); b = b.add({});", mr * lanes, nr * lanes).unwrap();
+        out.push_str("let mut rest = k - 1;\n");
+        if unroll > 1 {
+            writeln!(out, "while rest >= {unroll} {{").unwrap();
+            self.emit_steps(&mut out, unroll, "true");
+            writeln!(
+                out,
+                "a = a.add({}); b = b.add({}); rest -= {unroll};",
+                unroll * mr * lanes,
+                unroll * nr * lanes
+            )
+            .unwrap();
+            out.push_str("}\n");
+        }
+        out.push_str("while rest > 0 {\n");
+        self.emit_steps(&mut out, 1, "true");
+        writeln!(
+            out,
+            "a = a.add({}); b = b.add({}); rest -= 1;",
+            mr * lanes,
+            nr * lanes
+        )
+        .unwrap();
+        out.push_str("}\n");
+
+        out.push_str("}\n}\n");
+        Ok(out)
+    }
+
+    /// Emit `count` consecutive k-steps. All loads are issued before the
+    /// outer products so that they don't stall on each other.
+    fn emit_steps(&self, out: &mut String, count: usize, accumulate: &str) {
+        let Self { elem, mr, nr, .. } = *self;
+        let lanes = elem.lanes();
+        for u in 0..count {
+            for j in 0..nr {
+                writeln!(
+                    out,
+                    "ctx.load512(b.add({}), ::amx::XRow({}));",
+                    (u * nr + j) * lanes,
+                    u * nr + j
+                )
+                .unwrap();
+            }
+            for i in 0..mr {
+                writeln!(
+                    out,
+                    "ctx.load512(a.add({}), ::amx::YRow({}));",
+                    (u * mr + i) * lanes,
+                    u * mr + i
+                )
+                .unwrap();
+            }
+        }
+        for u in 0..count {
+            for i in 0..mr {
+                for j in 0..nr {
+                    writeln!(
+                        out,
+                        "ctx.{}(::core::option::Option::Some(::amx::XBytes({})), \
+                            ::core::option::Option::Some(::amx::YBytes({})), \
+                            ::amx::ZRow({}), {accumulate});",
+                        elem.method(),
+                        (u * nr + j) * 64,
+                        (u * mr + i) * 64,
+                        i * nr + j,
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Measure the throughput and latency of each wrapped instruction.
+//!
+//! Run with `cargo bench --bench instructions`. The throughput is measured by
+//! rotating through independent Z rows, and the latency by a chain of
+//! instructions accumulating to the same Z row. A drop in throughput points
+//! to extra moves or a missed `inline(always)` in the wrappers.
+//!
+//! The results can be checked for regressions with [`amx::bench::Baseline`]:
+//!
+//!  - `AMX_BENCH_SAVE=<path>` saves the measured throughputs to `<path>`.
+//!  - `AMX_BENCH_BASELINE=<path>` compares them against `<path>` and exits
+//!    with a non-zero status if any instruction regressed by more than 10%.
+//!
+//! The numbers are only meaningful on Apple Silicon. Elsewhere, the
+//! emulator is measured with fewer iterations to check the harness.
+use amx::{
+    Amx, AmxFallbackCtx, Index4, Normal, StoreHint, VecFpAluOp, VecFpTy, VecIntOp, VecIntTy, X16,
+    XBytes, XRow, YBytes, YRow, ZRow,
+    bench::{self, Baseline, Status},
+};
+use std::{process::ExitCode, time::Instant};
+
+/// The tolerance of the baseline comparison
+const TOLERANCE: f64 = 0.1;
+
+/// An instruction to measure
+struct Instr<C> {
+    name: &'static str,
+    /// Arithmetic operations per instruction, or zero for loads and stores
+    ops: usize,
+    /// Bytes moved per instruction, or zero for computations
+    bytes: usize,
+    /// The number of independent Z rows to rotate through
+    z_rows: usize,
+    issue: fn(&mut C, &mut [u8; 64], ZRow),
+}
+
+fn instrs<C: Amx>() -> Vec<Instr<C>> {
+    vec![
+        Instr {
+            name: "mac16",
+            ops: 32 * 32 * 2,
+            bytes: 0,
+            z_rows: 2,
+            issue: |ctx, _, z| {
+                ctx.outer_product_i16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), z, true)
+            },
+        },
+        Instr {
+            name: "fma16",
+            ops: 32 * 32 * 2,
+            bytes: 0,
+            z_rows: 2,
+            issue: |ctx, _, z| {
+                ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), z, true)
+            },
+        },
+        Instr {
+            name: "fma32",
+            ops: 16 * 16 * 2,
+            bytes: 0,
+            z_rows: 4,
+            issue: |ctx, _, z| {
+                ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), z, true)
+            },
+        },
+        Instr {
+            name: "fma64",
+            ops: 8 * 8 * 2,
+            bytes: 0,
+            z_rows: 8,
+            issue: |ctx, _, z| {
+                ctx.outer_product_f64_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), z, true)
+            },
+        },
+        Instr {
+            name: "fma32_vector",
+            ops: 16 * 2,
+            bytes: 0,
+            z_rows: 64,
+            issue: |ctx, _, z| ctx.fma_vector_f32(Some(XBytes(0)), Some(YBytes(0)), z, true),
+        },
+        Instr {
+            name: "vecint.i16",
+            ops: 32 * 2,
+            bytes: 0,
+            z_rows: 64,
+            issue: |ctx, _, z| {
+                ctx.vector_int(VecIntOp::new(VecIntTy::I16), XBytes(0), YBytes(0), z)
+            },
+        },
+        Instr {
+            name: "vecfp.f32",
+            ops: 16 * 2,
+            bytes: 0,
+            z_rows: 64,
+            issue: |ctx, _, z| {
+                ctx.vector_fp(VecFpTy::F32, VecFpAluOp::MulAdd, XBytes(0), YBytes(0), z)
+            },
+        },
+        Instr {
+            name: "genlut",
+            ops: 0,
+            bytes: 0,
+            z_rows: 64,
+            issue: |ctx, _, z| ctx.lut(YBytes(0), XRow(1), z, (Normal, Index4, X16)),
+        },
+        Instr {
+            name: "ldx",
+            ops: 0,
+            bytes: 64,
+            z_rows: 8,
+            issue: |ctx, buf, z| ctx.load512_slice(&buf[..], XRow(z.0)),
+        },
+        Instr {
+            name: "ldy",
+            ops: 0,
+            bytes: 64,
+            z_rows: 8,
+            issue: |ctx, buf, z| ctx.load512_slice(&buf[..], YRow(z.0)),
+        },
+        Instr {
+            name: "stz",
+            ops: 0,
+            bytes: 64,
+            z_rows: 64,
+            issue: |ctx, buf, z| ctx.store512_slice(&mut buf[..], z),
+        },
+        Instr {
+            name: "stz.nt",
+            ops: 0,
+            bytes: 64,
+            z_rows: 64,
+            issue: |ctx, buf, z| unsafe {
+                ctx.store512_hinted(buf.as_mut_ptr(), z, StoreHint::NonTemporal)
+            },
+        },
+    ]
+}
+
+/// Measure every instruction and return the throughputs in instructions per
+/// second.
+fn run<C: Amx>(ctx: &mut C, iterations: usize) -> Vec<(&'static str, f64)> {
+    let mut buf = [0u8; 64];
+    println!(
+        "{:>14} {:>12} {:>10} {:>10}",
+        "instruction", "Minstr/s", "latency", "GOPS/GB/s"
+    );
+    instrs::<C>()
+        .into_iter()
+        .map(|instr| {
+            let mut i = 0;
+            let rate = bench::measure_throughput(iterations, 1.0, || {
+                (instr.issue)(ctx, &mut buf, ZRow(i % instr.z_rows));
+                i += 1;
+            });
+
+            let start = Instant::now();
+            for _ in 0..iterations {
+                (instr.issue)(ctx, &mut buf, ZRow(0));
+            }
+            let latency = start.elapsed().as_secs_f64() / iterations as f64;
+
+            let (work, unit) = match (instr.ops, instr.bytes) {
+                (0, 0) => (String::new(), ""),
+                (0, bytes) => (format!("{:.1}", rate * bytes as f64 * 1e-9), " GB/s"),
+                (ops, _) => (format!("{:.1}", rate * ops as f64 * 1e-9), " GOPS"),
+            };
+            println!(
+                "{:>14} {:>12.1} {:>8.2}ns {:>10}{unit}",
+                instr.name,
+                rate * 1e-6,
+                latency * 1e9,
+                work,
+            );
+            (instr.name, rate)
+        })
+        .collect()
+}
+
+fn main() -> ExitCode {
+    // Collected before enabling AMX because `AmxReport::collect` uses its
+    // own context
+    #[cfg(target_arch = "aarch64")]
+    let chip = amx::AmxReport::collect().chip;
+    #[cfg(not(target_arch = "aarch64"))]
+    let chip = None;
+
+    let mut ctx = AmxFallbackCtx::try_new().unwrap();
+    let results = match &mut ctx {
+        #[cfg(target_arch = "aarch64")]
+        AmxFallbackCtx::Native(ctx) => run(ctx, 1 << 22),
+        AmxFallbackCtx::Emu(ctx) => {
+            println!("AMX is not available; measuring the emulator");
+            run(ctx, 1 << 10)
+        }
+    };
+    let chip = match chip {
+        Some(chip) => chip,
+        None if ctx.is_native() => "unknown".to_owned(),
+        None => "emulator".to_owned(),
+    };
+
+    let mut current = Baseline::new();
+    for (name, rate) in results {
+        current.record(&chip, name, rate);
+    }
+    if let Some(path) = std::env::var_os("AMX_BENCH_SAVE") {
+        current.save(path).expect("failed to save the baseline");
+    }
+    if let Some(path) = std::env::var_os("AMX_BENCH_BASELINE") {
+        let baseline = Baseline::load(path).expect("failed to load the baseline");
+        let mut regressed = false;
+        for delta in baseline.compare(&current, TOLERANCE) {
+            if delta.status == Status::Regressed {
+                println!(
+                    "{}: regressed to {:.0}% of the baseline",
+                    delta.kernel,
+                    delta.ratio().unwrap() * 100.0
+                );
+                regressed = true;
+            }
+        }
+        if regressed {
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,5 @@
+//! Print a report of the system's AMX capabilities for attaching to issues.
+fn main() {
+    let report = amx::AmxReport::collect();
+    println!("{}", report.to_json());
+}
@@ -0,0 +1,75 @@
+//! Functions whose generated assembly is checked by `tests/asm.rs`
+//!
+//! Each `audit_*` function issues one AMX instruction, either by a free
+//! function of `amx::nativeops` or by an [`AmxOps`] method called on
+//! [`AmxCtx`] (through the `Deref` chain to `nativeops::AmxOps`). Inspect the
+//! output of
+//!
+//! ```text
+//! cargo rustc --release --example asm_audit -- --emit asm
+//! ```
+//!
+//! to check that no call or extra move is inserted around the instruction.
+#[cfg(target_arch = "aarch64")]
+mod audit {
+    use amx::{AmxCtx, AmxOps, nativeops};
+
+    macro_rules! audit_free {
+        ($($name:ident => $op:ident),* $(,)?) => {$(
+            #[unsafe(no_mangle)]
+            #[inline(never)]
+            pub unsafe fn $name(x: u64) {
+                unsafe { nativeops::$op(x) }
+            }
+        )*};
+    }
+
+    macro_rules! audit_ctx_mem {
+        ($($name:ident => $op:ident),* $(,)?) => {$(
+            #[unsafe(no_mangle)]
+            #[inline(never)]
+            pub unsafe fn $name(ctx: &mut AmxCtx, x: u64, ptr: *mut ()) {
+                unsafe { ctx.$op(x, ptr) }
+            }
+        )*};
+    }
+
+    macro_rules! audit_ctx {
+        ($($name:ident => $op:ident),* $(,)?) => {$(
+            #[unsafe(no_mangle)]
+            #[inline(never)]
+            pub fn $name(ctx: &mut AmxCtx, x: u64) {
+                ctx.$op(x)
+            }
+        )*};
+    }
+
+    audit_free! {
+        audit_ldx => ldx, audit_ldy => ldy, audit_stx => stx, audit_sty => sty,
+        audit_ldz => ldz, audit_stz => stz, audit_ldzi => ldzi, audit_stzi => stzi,
+        audit_extrx => extrx, audit_extry => extry,
+        audit_fma64 => fma64, audit_fms64 => fms64, audit_fma32 => fma32, audit_fms32 => fms32,
+        audit_mac16 => mac16, audit_fma16 => fma16, audit_fms16 => fms16,
+        audit_vecint => vecint, audit_vecfp => vecfp, audit_matint => matint,
+        audit_matfp => matfp, audit_genlut => genlut,
+    }
+
+    audit_ctx_mem! {
+        audit_ctx_ldx => ldx, audit_ctx_ldy => ldy, audit_ctx_stx => stx, audit_ctx_sty => sty,
+        audit_ctx_ldz => ldz, audit_ctx_stz => stz, audit_ctx_ldzi => ldzi,
+        audit_ctx_stzi => stzi,
+    }
+
+    audit_ctx! {
+        audit_ctx_extrx => extrx, audit_ctx_extry => extry,
+        audit_ctx_fma64 => fma64, audit_ctx_fms64 => fms64,
+        audit_ctx_fma32 => fma32, audit_ctx_fms32 => fms32,
+        audit_ctx_mac16 => mac16, audit_ctx_fma16 => fma16, audit_ctx_fms16 => fms16,
+        audit_ctx_vecint => vecint, audit_ctx_vecfp => vecfp,
+        audit_ctx_matint => matint, audit_ctx_matfp => matfp, audit_ctx_genlut => genlut,
+    }
+}
+
+fn main() {
+    println!("See the generated assembly of the `audit_*` functions");
+}
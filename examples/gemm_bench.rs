@@ -0,0 +1,178 @@
+//! Compare the throughput of the `linalg` GEMM kernels against Accelerate's
+//! BLAS.
+//!
+//! Accelerate has no `f16` GEMM, so `hgemm` is compared against `sgemm` on
+//! the same inputs widened to `f32`.
+use amx::{
+    fp16::F16Bits,
+    linalg::{self, MatMut, MatRef},
+};
+use clap::Parser;
+use std::time::Instant;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Opts {
+    /// The size of the square matrices
+    #[arg(short, long, default_value_t = 512)]
+    size: usize,
+    /// The number of repetitions of each kernel
+    #[arg(short, long, default_value_t = 10)]
+    iterations: usize,
+}
+
+#[cfg(target_os = "macos")]
+mod accelerate {
+    const ROW_MAJOR: i32 = 101;
+    const NO_TRANS: i32 = 111;
+
+    #[link(name = "Accelerate", kind = "framework")]
+    unsafe extern "C" {
+        fn cblas_sgemm(
+            order: i32,
+            trans_a: i32,
+            trans_b: i32,
+            m: i32,
+            n: i32,
+            k: i32,
+            alpha: f32,
+            a: *const f32,
+            lda: i32,
+            b: *const f32,
+            ldb: i32,
+            beta: f32,
+            c: *mut f32,
+            ldc: i32,
+        );
+        fn cblas_dgemm(
+            order: i32,
+            trans_a: i32,
+            trans_b: i32,
+            m: i32,
+            n: i32,
+            k: i32,
+            alpha: f64,
+            a: *const f64,
+            lda: i32,
+            b: *const f64,
+            ldb: i32,
+            beta: f64,
+            c: *mut f64,
+            ldc: i32,
+        );
+    }
+
+    /// Calculate `C = A * B` for square matrices of size `n`.
+    pub fn sgemm(n: usize, a: &[f32], b: &[f32], c: &mut [f32]) {
+        assert!(a.len() >= n * n && b.len() >= n * n && c.len() >= n * n);
+        let n = n as i32;
+        // Safety: The buffers hold `n * n` elements each
+        unsafe {
+            cblas_sgemm(
+                ROW_MAJOR,
+                NO_TRANS,
+                NO_TRANS,
+                n,
+                n,
+                n,
+                1.0,
+                a.as_ptr(),
+                n,
+                b.as_ptr(),
+                n,
+                0.0,
+                c.as_mut_ptr(),
+                n,
+            )
+        }
+    }
+
+    /// Calculate `C = A * B` for square matrices of size `n`.
+    pub fn dgemm(n: usize, a: &[f64], b: &[f64], c: &mut [f64]) {
+        assert!(a.len() >= n * n && b.len() >= n * n && c.len() >= n * n);
+        let n = n as i32;
+        // Safety: The buffers hold `n * n` elements each
+        unsafe {
+            cblas_dgemm(
+                ROW_MAJOR,
+                NO_TRANS,
+                NO_TRANS,
+                n,
+                n,
+                n,
+                1.0,
+                a.as_ptr(),
+                n,
+                b.as_ptr(),
+                n,
+                0.0,
+                c.as_mut_ptr(),
+                n,
+            )
+        }
+    }
+}
+
+/// Run `f` `iterations` times and print the throughput in GFLOPS.
+fn measure(name: &str, n: usize, iterations: usize, mut f: impl FnMut()) {
+    f(); // warm up
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let secs = start.elapsed().as_secs_f64();
+    let flops = 2.0 * (n as f64).powi(3) * iterations as f64;
+    println!("{name:>20}: {:8.2} GFLOPS", flops / secs * 1e-9);
+}
+
+fn main() {
+    let opts = Opts::parse();
+    let n = opts.size;
+    let mut ctx = amx::AmxCtx::new().unwrap();
+
+    let a32: Vec<f32> = (0..n * n).map(|i| (i % 7) as f32 * 0.25 - 0.75).collect();
+    let b32: Vec<f32> = (0..n * n).map(|i| (i % 5) as f32 * 0.5 - 1.0).collect();
+    let a16: Vec<F16Bits> = a32.iter().copied().map(F16Bits::from_f32).collect();
+    let b16: Vec<F16Bits> = b32.iter().copied().map(F16Bits::from_f32).collect();
+    let a64: Vec<f64> = a32.iter().map(|&x| x as f64).collect();
+    let b64: Vec<f64> = b32.iter().map(|&x| x as f64).collect();
+    let mut c32 = vec![0f32; n * n];
+    let mut c64 = vec![0f64; n * n];
+
+    println!("{n}×{n}×{n}, {} iterations", opts.iterations);
+    measure("amx hgemm", n, opts.iterations, || {
+        linalg::hgemm(
+            &mut ctx,
+            MatRef::new(&a16, n, n),
+            MatRef::new(&b16, n, n),
+            MatMut::new(&mut c32, n, n),
+            false,
+        )
+    });
+    measure("amx sgemm", n, opts.iterations, || {
+        linalg::sgemm(
+            &mut ctx,
+            MatRef::new(&a32, n, n),
+            MatRef::new(&b32, n, n),
+            MatMut::new(&mut c32, n, n),
+            false,
+        )
+    });
+    #[cfg(target_os = "macos")]
+    measure("accelerate sgemm", n, opts.iterations, || {
+        accelerate::sgemm(n, &a32, &b32, &mut c32)
+    });
+    measure("amx dgemm", n, opts.iterations, || {
+        linalg::dgemm(
+            &mut ctx,
+            MatRef::new(&a64, n, n),
+            MatRef::new(&b64, n, n),
+            MatMut::new(&mut c64, n, n),
+            false,
+        )
+    });
+    #[cfg(target_os = "macos")]
+    measure("accelerate dgemm", n, opts.iterations, || {
+        accelerate::dgemm(n, &a64, &b64, &mut c64)
+    });
+}
@@ -0,0 +1,94 @@
+#ifndef AMX_H
+#define AMX_H
+
+/* Generated by cbindgen from `src/capi.rs`. Do not edit manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The status code returned by the C API functions
+ */
+typedef enum AmxStatus {
+  /**
+   * The operation succeeded.
+   */
+  AMX_STATUS_OK = 0,
+  /**
+   * AMX could not be enabled for the calling thread.
+   */
+  AMX_STATUS_UNAVAILABLE = -1,
+  /**
+   * A pointer is null or the dimensions are inconsistent.
+   */
+  AMX_STATUS_INVALID_ARGUMENT = -2,
+} AmxStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Calculate `C = A * B` (or `C += A * B` if `accumulate` is set) where `A`
+ * is `m`×`k`, `B` is `k`×`n`, and `C` is `m`×`n`.
+ *
+ * # Safety
+ *
+ * The matrices must be valid for the specified dimensions and strides, and
+ * `c` must not overlap with `a` or `b`.
+ */
+AmxStatus amx_sgemm(size_t m,
+                    size_t n,
+                    size_t k,
+                    const float *a,
+                    size_t lda,
+                    const float *b,
+                    size_t ldb,
+                    float *c,
+                    size_t ldc,
+                    bool accumulate);
+
+/**
+ * Calculate the dot product of `x[0..n]` and `y[0..n]` and store it to
+ * `*out`.
+ *
+ * # Safety
+ *
+ * `x` and `y` must be valid for reading `n` elements, and `out` must be
+ * valid for writing.
+ */
+AmxStatus amx_sdot(size_t n, const float *x, const float *y, float *out);
+
+/**
+ * Calculate the `nx - nh + 1` outputs of the linear convolution of
+ * `x[0..nx]` and `h[0..nh]` for which `h` fully overlaps `x`. See
+ * `amx::dsp::conv_f32` for details.
+ *
+ * `nh` must be in range `1..=nx`.
+ *
+ * # Safety
+ *
+ * `x` and `h` must be valid for reading `nx` and `nh` elements,
+ * respectively, and `out` must be valid for writing `nx - nh + 1` elements.
+ */
+AmxStatus amx_sconv(const float *x, size_t nx, const float *h, size_t nh, float *out);
+
+/**
+ * Quantize `x[0..n]` to 16-bit integers: `out[i] = round(x[i] / scale)`.
+ * See `amx::dsp::quantize_i16` for details.
+ *
+ * # Safety
+ *
+ * `x` must be valid for reading `n` elements, and `out` must be valid for
+ * writing `n` elements.
+ */
+AmxStatus amx_quantize_i16(const float *x, size_t n, float scale, int16_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AMX_H */
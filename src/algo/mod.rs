@@ -0,0 +1,6 @@
+//! Data-parallel primitives (selection, sorting, scans) built on top of
+//! [`Amx`](crate::Amx)
+mod scan;
+mod sort;
+mod topk;
+pub use self::{scan::*, sort::*, topk::*};
@@ -0,0 +1,92 @@
+//! Prefix sums
+use crate::{
+    Amx, Index4, Normal, X32, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, MatMut, fma32_vector},
+};
+
+/// Load the constants used by [`scan`]: `x[0] = 0`, `x[2] = 0` (the carry),
+/// `y[0] = 1`, and the `genlut` indices broadcasting lane 15 in `y[1]`.
+fn load_constants(ctx: &mut (impl Amx + ?Sized)) {
+    const ZEROS: [f32; F32_LANES] = [0.0; F32_LANES];
+    const ONES: [f32; F32_LANES] = [1.0; F32_LANES];
+    const BROADCAST_LAST: [u8; 64] = [0xff; 64];
+    // Safety: Reading 64 bytes from each `[f32; 16]` and `[u8; 64]`
+    unsafe {
+        ctx.load512(ZEROS.as_ptr(), XRow(0));
+        ctx.load512(ZEROS.as_ptr(), XRow(2));
+        ctx.load512(ONES.as_ptr(), YRow(0));
+        ctx.load512(BROADCAST_LAST.as_ptr(), YRow(1));
+    }
+}
+
+/// Calculate the prefix sum of `data` in place, continuing from the carry
+/// broadcast in `x[2]`. The constants must have been loaded by
+/// [`load_constants`].
+///
+/// Each row of 16 values is scanned by `log2(16)` shifted adds, where the
+/// shifted operand is read from X at a byte offset reaching into the zero
+/// row `x[0]`. The carry is then added, and the new carry is broadcast from
+/// the last lane by `genlut`, so it never leaves the register file.
+///
+/// This overwrites `x[1..3]` and `z[0..2]`.
+fn scan(ctx: &mut (impl Amx + ?Sized), data: &mut [f32], exclusive: bool) {
+    let shifted = |s: usize| XBytes(64 - s * 4);
+    for chunk in data.chunks_mut(F32_LANES) {
+        let mut row = [0f32; F32_LANES];
+        row[..chunk.len()].copy_from_slice(chunk);
+        // Safety: Reading 64 bytes from `[f32; 16]`
+        unsafe { ctx.load512(row.as_ptr(), XRow(1)) };
+
+        fma32_vector(ctx, XBytes(64), YBytes(0), ZRow(0), false, false);
+        for s in [1, 2, 4, 8] {
+            fma32_vector(ctx, shifted(s), YBytes(0), ZRow(0), true, false);
+            ctx.extract_z_to_x(ZRow(0), XBytes(64));
+        }
+
+        let out = if exclusive {
+            fma32_vector(ctx, shifted(1), YBytes(0), ZRow(1), false, false);
+            fma32_vector(ctx, XBytes(2 * 64), YBytes(0), ZRow(1), true, false);
+            ZRow(1)
+        } else {
+            ZRow(0)
+        };
+        fma32_vector(ctx, XBytes(2 * 64), YBytes(0), ZRow(0), true, false);
+        ctx.extract_z_to_x(ZRow(0), XBytes(64));
+        ctx.lut(YBytes(64), XRow(1), XRow(2), (Normal, Index4, X32));
+
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(row.as_mut_ptr(), out) };
+        chunk.copy_from_slice(&row[..chunk.len()]);
+    }
+}
+
+/// Calculate the prefix sum of `data` in place, i.e., replace `data[i]` with
+/// `data[0] + … + data[i]` (inclusive) or `data[0] + … + data[i - 1]`
+/// (exclusive, if `exclusive` is set).
+///
+/// The values are summed in a tree within each row of 16 values, so the
+/// rounding errors differ from those of a sequential sum.
+///
+/// This overwrites `x[0..3]`, `y[0..2]`, and `z[0..2]`.
+pub fn prefix_sum_f32(ctx: &mut (impl Amx + ?Sized), data: &mut [f32], exclusive: bool) {
+    load_constants(ctx);
+    scan(ctx, data, exclusive);
+}
+
+/// Calculate the prefix sum of each row of `mat` in place. See
+/// [`prefix_sum_f32`] for details.
+///
+/// This overwrites `x[0..3]`, `y[0..2]`, and `z[0..2]`.
+pub fn prefix_sum_rows_f32(
+    ctx: &mut (impl Amx + ?Sized),
+    mut mat: MatMut<'_, f32>,
+    exclusive: bool,
+) {
+    const ZEROS: [f32; F32_LANES] = [0.0; F32_LANES];
+    load_constants(ctx);
+    for i in 0..mat.rows() {
+        // Safety: Reading 64 bytes from `[f32; 16]`
+        unsafe { ctx.load512(ZEROS.as_ptr(), XRow(2)) };
+        scan(ctx, mat.row_mut(i), exclusive);
+    }
+}
@@ -0,0 +1,144 @@
+//! In-register sorting networks
+use crate::{
+    Amx, Index4, Normal, VecFpAluOp, VecFpTy, X32, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, fma32_vector},
+};
+
+/// Load the `genlut` indices exchanging the lanes `i` and `i ^ j` for
+/// `j = 1, 2, 4, 8` into `y[0][j.ilog2() * 8..][..8]`.
+fn load_partners(ctx: &mut (impl Amx + ?Sized)) {
+    let mut indices = [0u8; 64];
+    for t in 0..4 {
+        for i in 0..F32_LANES {
+            indices[t * 8 + i / 2] |= ((i ^ (1 << t)) as u8) << (i % 2 * 4);
+        }
+    }
+    // Safety: Reading 64 bytes from `[u8; 64]`
+    unsafe { ctx.load512(indices.as_ptr(), YRow(0)) };
+}
+
+/// Sort the `16 * num_rows` values in `x[0]` (and `x[2]` if `num_rows` is
+/// `2`) in ascending order by a bitonic sorting network. The partner indices
+/// must have been loaded by [`load_partners`].
+///
+/// A compare-exchange step between lanes `i` and `i ^ j` in the same row
+/// gathers the partners by `genlut` and calculates `s * min(s * a, s * p)`,
+/// where `s` is `-1` for the lanes taking the maximum and `1` otherwise.
+/// The values are moved between Z and X by `extrx`.
+///
+/// This overwrites `x[0..4]`, `y[1]`, and `z[0..2]`.
+fn bitonic(ctx: &mut (impl Amx + ?Sized), num_rows: usize) {
+    let n = F32_LANES * num_rows;
+    let mut k = 2;
+    while k <= n {
+        let mut j = k / 2;
+        while j > 0 {
+            if j == F32_LANES {
+                // Exchange between the rows. This only happens in the last
+                // merge, which is ascending.
+                const ONES: [f32; F32_LANES] = [1.0; F32_LANES];
+                // Safety: Reading 64 bytes from `[f32; 16]`
+                unsafe { ctx.load512(ONES.as_ptr(), YRow(1)) };
+                for z in 0..2 {
+                    fma32_vector(ctx, XBytes(0), YBytes(64), ZRow(z), false, false);
+                    let op = if z == 1 {
+                        VecFpAluOp::Max
+                    } else {
+                        VecFpAluOp::Min
+                    };
+                    ctx.vector_fp(VecFpTy::F32, op, XBytes(2 * 64), YBytes(0), ZRow(z));
+                }
+                ctx.extract_z_to_x(ZRow(0), XBytes(0));
+                ctx.extract_z_to_x(ZRow(1), XBytes(2 * 64));
+            } else {
+                for r in 0..num_rows {
+                    let (data, scratch) = (r * 2, r * 2 + 1);
+                    let signs: [f32; F32_LANES] = std::array::from_fn(|i| {
+                        let g = r * F32_LANES + i;
+                        if (g & j == 0) == (g & k == 0) {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    });
+                    // Safety: Reading 64 bytes from `[f32; 16]`
+                    unsafe { ctx.load512(signs.as_ptr(), YRow(1)) };
+
+                    ctx.lut(
+                        YBytes(j.ilog2() as usize * 8),
+                        XRow(data),
+                        XRow(scratch),
+                        (Normal, Index4, X32),
+                    );
+                    fma32_vector(ctx, XBytes(data * 64), YBytes(64), ZRow(0), false, false);
+                    fma32_vector(ctx, XBytes(scratch * 64), YBytes(64), ZRow(1), false, false);
+                    ctx.extract_z_to_x(ZRow(1), XBytes(scratch * 64));
+                    ctx.vector_fp(
+                        VecFpTy::F32,
+                        VecFpAluOp::Min,
+                        XBytes(scratch * 64),
+                        YBytes(0),
+                        ZRow(0),
+                    );
+                    ctx.extract_z_to_x(ZRow(0), XBytes(scratch * 64));
+                    fma32_vector(ctx, XBytes(scratch * 64), YBytes(64), ZRow(0), false, false);
+                    ctx.extract_z_to_x(ZRow(0), XBytes(data * 64));
+                }
+            }
+            j /= 2;
+        }
+        k *= 2;
+    }
+}
+
+/// Sort each row of 16 `f32` values in ascending order.
+///
+/// Each row is sorted by a 10-stage bitonic network without leaving the
+/// register file, using `genlut` to gather the partner lanes and `vecfp` to
+/// calculate their minima and maxima. This is useful for small per-row sorts
+/// such as median filters.
+///
+/// The order of the results is unspecified if a row contains `NaN`, and
+/// negative zeros may be turned into positive zeros.
+///
+/// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..2]`.
+pub fn sort_rows_f32x16(ctx: &mut (impl Amx + ?Sized), rows: &mut [[f32; F32_LANES]]) {
+    load_partners(ctx);
+    for row in rows {
+        // Safety: Reading 64 bytes from `[f32; 16]`
+        unsafe { ctx.load512(row.as_ptr(), XRow(0)) };
+        bitonic(ctx, 1);
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(row.as_mut_ptr(), XRow(0)) };
+    }
+}
+
+/// Sort each row of 32 `i16` values in ascending order.
+///
+/// The values are converted to `f32` (which represents them exactly) and
+/// sorted as two registers by a 15-stage bitonic network, as described in
+/// [`sort_rows_f32x16`].
+///
+/// This overwrites `x[0..4]`, `y[0..2]`, and `z[0..2]`.
+pub fn sort_rows_i16x32(ctx: &mut (impl Amx + ?Sized), rows: &mut [[i16; 2 * F32_LANES]]) {
+    load_partners(ctx);
+    for row in rows {
+        let mut values = [[0f32; F32_LANES]; 2];
+        for (r, values) in values.iter_mut().enumerate() {
+            *values = std::array::from_fn(|i| row[r * F32_LANES + i] as f32);
+            // Safety: Reading 64 bytes from `[f32; 16]`
+            unsafe { ctx.load512(values.as_ptr(), XRow(r * 2)) };
+        }
+        bitonic(ctx, 2);
+        for (r, values) in values.iter_mut().enumerate() {
+            // Safety: Writing 64 bytes to `[f32; 16]`
+            unsafe { ctx.store512(values.as_mut_ptr(), XRow(r * 2)) };
+            for (dst, &src) in row[r * F32_LANES..][..F32_LANES]
+                .iter_mut()
+                .zip(values.iter())
+            {
+                *dst = src as i16;
+            }
+        }
+    }
+}
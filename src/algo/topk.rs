@@ -0,0 +1,156 @@
+//! Top-k selection
+use crate::{
+    Amx, F32, Index4, Reverse, XBytes, XRow, YRow,
+    linalg::{F32_LANES, MatMut, MatRef},
+};
+
+/// Maintains the `k` largest scores and their indices over a stream of
+/// score rows, e.g., the rows of a similarity matrix produced by a GEMM
+/// kernel in a retrieval workload.
+///
+/// Each row is compared against the current `k`-th largest score 16 lanes
+/// at a time by a reverse `genlut` lookup, so only the scores that can
+/// enter the top `k` are passed to the CPU for insertion. `NaN` scores are
+/// ignored. Ties are broken in favor of smaller indices.
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize,
+    /// `(score, index)` sorted in descending order of scores
+    entries: Vec<(f32, usize)>,
+    /// The index of the next score to be pushed
+    next_index: usize,
+}
+
+impl TopK {
+    /// Construct an empty `TopK` retaining the `k` largest scores.
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            entries: Vec::with_capacity(k),
+            next_index: 0,
+        }
+    }
+
+    /// Get `k`.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Get the retained `(score, index)` pairs in descending order of
+    /// scores. This contains fewer than `k` elements if fewer than `k`
+    /// (non-`NaN`) scores have been pushed.
+    #[inline]
+    pub fn as_slice(&self) -> &[(f32, usize)] {
+        &self.entries
+    }
+
+    /// Forget all scores, and restart the indices from zero.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.next_index = 0;
+    }
+
+    /// The score a new score must exceed to be retained
+    fn threshold(&self) -> f32 {
+        if self.entries.len() < self.k {
+            f32::NEG_INFINITY
+        } else {
+            self.entries[self.k - 1].0
+        }
+    }
+
+    /// Push a row of scores. The scores are assigned consecutive indices
+    /// continuing from the previous row.
+    ///
+    /// This overwrites `x[0..2]` and `y[0]`.
+    pub fn push_row(&mut self, ctx: &mut (impl Amx + ?Sized), scores: &[f32]) {
+        let base = self.next_index;
+        self.next_index += scores.len();
+        if self.k == 0 {
+            return;
+        }
+
+        // The segment index of a score is `0` iff it's below the threshold
+        let mut table_threshold = None;
+        for (c, chunk) in scores.chunks(F32_LANES).enumerate() {
+            let threshold = self.threshold();
+            if table_threshold != Some(threshold) {
+                let mut table = [f32::INFINITY; F32_LANES];
+                table[0] = f32::NEG_INFINITY;
+                table[1] = threshold;
+                // Safety: Reading 64 bytes from `[f32; 16]`
+                unsafe { ctx.load512(table.as_ptr(), XRow(0)) };
+                table_threshold = Some(threshold);
+            }
+
+            let mut row = [f32::NEG_INFINITY; F32_LANES];
+            row[..chunk.len()].copy_from_slice(chunk);
+            // Safety: Reading 64 bytes from `[f32; 16]`
+            unsafe { ctx.load512(row.as_ptr(), XRow(1)) };
+            ctx.lut(XBytes(64), XRow(0), YRow(0), (Reverse, Index4, F32));
+            let mut segments = [0u8; 64];
+            // Safety: Writing 64 bytes to `[u8; 64]`
+            unsafe { ctx.store512(segments.as_mut_ptr(), YRow(0)) };
+
+            for (i, &score) in chunk.iter().enumerate() {
+                if segments[i / 2] >> (i % 2 * 4) & 0xf != 0 {
+                    self.insert(score, base + c * F32_LANES + i);
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, score: f32, index: usize) {
+        if score.is_nan() || (self.entries.len() == self.k && score <= self.threshold()) {
+            return;
+        }
+        // `index` is larger than all retained indices
+        let pos = self.entries.partition_point(|&(s, _)| s >= score);
+        self.entries.insert(pos, (score, index));
+        self.entries.truncate(self.k);
+    }
+}
+
+/// Find the `k` largest scores in each row of `scores` (`M`×`N`), where
+/// `k` is the number of columns of `values` and `indices` (`M`×`k`).
+///
+/// Each row of `values` receives the scores in descending order, and the
+/// same row of `indices` receives their column indices. If a row has fewer
+/// than `k` non-`NaN` scores, the remaining elements are filled with `NaN`
+/// and `usize::MAX`. See [`TopK`] for details.
+///
+/// This overwrites `x[0..2]` and `y[0]`.
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent.
+pub fn topk_rows(
+    ctx: &mut (impl Amx + ?Sized),
+    scores: MatRef<'_, f32>,
+    mut values: MatMut<'_, f32>,
+    mut indices: MatMut<'_, usize>,
+) {
+    let k = values.cols();
+    assert_eq!(values.rows(), scores.rows(), "shape mismatch in `values`");
+    assert_eq!(
+        (indices.rows(), indices.cols()),
+        (scores.rows(), k),
+        "shape mismatch in `indices`"
+    );
+
+    let mut topk = TopK::new(k);
+    for i in 0..scores.rows() {
+        topk.clear();
+        topk.push_row(ctx, scores.row(i));
+        let entries = topk.as_slice();
+        for (j, (value, index)) in values
+            .row_mut(i)
+            .iter_mut()
+            .zip(indices.row_mut(i))
+            .enumerate()
+        {
+            (*value, *index) = entries.get(j).copied().unwrap_or((f32::NAN, usize::MAX));
+        }
+    }
+}
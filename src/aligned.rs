@@ -0,0 +1,95 @@
+//! Buffers with the alignment required by AMX's load and store instructions
+use std::ops::{Deref, DerefMut};
+
+macro_rules! define_aligned {
+    ($(#[$meta:meta])* $name:ident, $align:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+        #[repr(C, align($align))]
+        pub struct $name<T: ?Sized>(pub T);
+
+        impl<T> $name<T> {
+            /// Wrap `value`.
+            #[inline]
+            pub const fn new(value: T) -> Self {
+                Self(value)
+            }
+
+            /// Unwrap the contained value.
+            #[inline]
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T: ?Sized> Deref for $name<T> {
+            type Target = T;
+
+            #[inline]
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T: ?Sized> DerefMut for $name<T> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            #[inline]
+            fn from(value: T) -> Self {
+                Self(value)
+            }
+        }
+    };
+}
+
+define_aligned!(
+    /// A value aligned to 64-byte boundaries, accepted by
+    /// [`Amx::load512_aligned`] and [`Amx::store512_aligned`]
+    ///
+    /// [`Amx::load512_aligned`]: crate::Amx::load512_aligned
+    /// [`Amx::store512_aligned`]: crate::Amx::store512_aligned
+    Aligned64,
+    64
+);
+
+define_aligned!(
+    /// A value aligned to 128-byte boundaries, accepted by
+    /// [`Amx::load1024`] and [`Amx::store1024`]
+    ///
+    /// The 1024-bit loads and stores require 128-byte alignment. Passing a
+    /// misaligned pointer to [`Amx::load1024_aligned`] is undefined behavior,
+    /// whereas passing anything but an `Aligned128` to [`Amx::load1024`] is
+    /// a type error.
+    ///
+    /// ```rust
+    /// use amx::{Aligned128, Amx, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let src = Aligned128([1.5f32; 32]);
+    /// ctx.load1024(&src, ZRow(4));
+    ///
+    /// let mut dst = Aligned128([0.0f32; 32]);
+    /// ctx.store1024(&mut dst, ZRow(4));
+    /// assert_eq!(dst, src);
+    /// ```
+    ///
+    /// A plain array, which may be misaligned, is rejected:
+    ///
+    /// ```rust,compile_fail
+    /// use amx::{Amx, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.load1024(&[1.5f32; 32], ZRow(4));
+    /// ```
+    ///
+    /// [`Amx::load1024`]: crate::Amx::load1024
+    /// [`Amx::store1024`]: crate::Amx::store1024
+    /// [`Amx::load1024_aligned`]: crate::Amx::load1024_aligned
+    Aligned128,
+    128
+);
@@ -0,0 +1,426 @@
+//! Benchmark result persistence and regression detection
+//!
+//! A [`Baseline`] maps `(chip, kernel)` pairs to measured throughputs. It
+//! can be saved to a file after a reference run, and subsequent runs can be
+//! compared against it by [`Baseline::compare`] to fail a CI job on
+//! performance regressions.
+//!
+//! ```rust
+//! use amx::bench::{Baseline, Status};
+//!
+//! let mut baseline = Baseline::new();
+//! baseline.record("Apple M1", "sgemm_256", 1200.0);
+//!
+//! let mut current = Baseline::new();
+//! current.record("Apple M1", "sgemm_256", 1000.0);
+//!
+//! let deltas = baseline.compare(&current, 0.05);
+//! assert_eq!(deltas[0].status, Status::Regressed);
+//! ```
+//!
+//! # File format
+//!
+//! The file is UTF-8 text with one measurement per line, consisting of the
+//! chip name, the kernel name, and the throughput separated by tabs. Empty
+//! lines and lines starting with `#` are ignored.
+//!
+//! # Memory bandwidth
+//!
+//! [`measure_bandwidth`] measures the throughput of streaming `ldx`, `ldy`,
+//! or `stz` operations over a buffer with a given [`Pattern`]. Comparing it
+//! against the bytes a kernel moves per second tells whether the kernel is
+//! bound by loads and stores or by computation.
+//!
+//! ```rust
+//! use amx::bench::{self, Access, Pattern};
+//!
+//! let mut ctx = amx::AmxEmuCtx::default();
+//! let bw = bench::measure_bandwidth(&mut ctx, Access::LoadX, Pattern::Pair, 4096, 2);
+//! assert_eq!(bw.bytes, 8192);
+//! println!("{bw}");
+//! ```
+use crate::{Amx, XRow, YRow, ZRow};
+use std::{collections::BTreeMap, fmt, fmt::Write as _, io, path::Path, time::Instant};
+
+/// Measured throughputs keyed by chip and kernel names. See the
+/// [module-level documentation](self) for details.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    entries: BTreeMap<(String, String), f64>,
+}
+
+/// The result of comparing a measurement against a [`Baseline`], returned by
+/// [`Baseline::compare`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub chip: String,
+    pub kernel: String,
+    /// The throughput in the baseline, or `None` if the kernel is new
+    pub baseline: Option<f64>,
+    /// The throughput in the current run
+    pub current: f64,
+    pub status: Status,
+}
+
+/// Classifies a [`Delta`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The throughput is within the tolerance of the baseline.
+    Unchanged,
+    /// The throughput is above the baseline by more than the tolerance.
+    Improved,
+    /// The throughput is below the baseline by more than the tolerance.
+    Regressed,
+    /// The baseline has no measurement for the chip and kernel.
+    New,
+}
+
+impl Baseline {
+    /// Construct an empty `Baseline`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the throughput of `kernel` on `chip`, replacing an existing
+    /// measurement. The unit is up to the caller (e.g., GFLOPS) but must be
+    /// consistent across runs, with larger values being better.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chip` or `kernel` contains a tab or a line break.
+    #[track_caller]
+    pub fn record(&mut self, chip: &str, kernel: &str, throughput: f64) {
+        for name in [chip, kernel] {
+            assert!(
+                !name.contains(['\t', '\n', '\r']),
+                "{name:?} contains a tab or a line break"
+            );
+        }
+        self.entries
+            .insert((chip.to_owned(), kernel.to_owned()), throughput);
+    }
+
+    /// Get the throughput of `kernel` on `chip`.
+    pub fn get(&self, chip: &str, kernel: &str) -> Option<f64> {
+        self.entries
+            .get(&(chip.to_owned(), kernel.to_owned()))
+            .copied()
+    }
+
+    /// Iterate over the `(chip, kernel, throughput)` triples in ascending
+    /// order of chip and kernel names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, f64)> + '_ {
+        self.entries
+            .iter()
+            .map(|((chip, kernel), &t)| (chip.as_str(), kernel.as_str(), t))
+    }
+
+    /// Get the number of measurements.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if there are no measurements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Copy all measurements from `other`, replacing existing ones. This can
+    /// be used to accept the current run as the new baseline.
+    pub fn merge(&mut self, other: &Baseline) {
+        self.entries
+            .extend(other.entries.iter().map(|(k, &v)| (k.clone(), v)));
+    }
+
+    /// Compare each measurement in `current` against `self`.
+    ///
+    /// A measurement is considered [`Status::Regressed`] if it's below
+    /// `baseline * (1 - tolerance)` and [`Status::Improved`] if it's above
+    /// `baseline * (1 + tolerance)`. Measurements only found in `self` are
+    /// not reported.
+    pub fn compare(&self, current: &Baseline, tolerance: f64) -> Vec<Delta> {
+        current
+            .entries
+            .iter()
+            .map(|(key, &current)| {
+                let baseline = self.entries.get(key).copied();
+                let status = match baseline {
+                    None => Status::New,
+                    Some(b) if current < b * (1.0 - tolerance) => Status::Regressed,
+                    Some(b) if current > b * (1.0 + tolerance) => Status::Improved,
+                    Some(_) => Status::Unchanged,
+                };
+                Delta {
+                    chip: key.0.clone(),
+                    kernel: key.1.clone(),
+                    baseline,
+                    current,
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    /// Parse a baseline in the [file format](self#file-format).
+    pub fn parse(s: &str) -> io::Result<Self> {
+        let mut this = Self::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |what: &str| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {what}", i + 1),
+                )
+            };
+            let mut fields = line.split('\t');
+            let (Some(chip), Some(kernel), Some(throughput), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid("expected three tab-separated fields"));
+            };
+            let throughput = throughput
+                .parse()
+                .map_err(|_| invalid("invalid throughput"))?;
+            this.entries
+                .insert((chip.to_owned(), kernel.to_owned()), throughput);
+        }
+        Ok(this)
+    }
+
+    /// Render the baseline in the [file format](self#file-format).
+    pub fn to_text(&self) -> String {
+        let mut out = String::from("# chip\tkernel\tthroughput\n");
+        for (chip, kernel, throughput) in self.iter() {
+            writeln!(out, "{chip}\t{kernel}\t{throughput}").unwrap();
+        }
+        out
+    }
+
+    /// Load a baseline from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Save the baseline to the file at `path`, replacing its contents.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+}
+
+impl Delta {
+    /// Get `current / baseline`, or `None` if the kernel is new.
+    pub fn ratio(&self) -> Option<f64> {
+        self.baseline.map(|b| self.current / b)
+    }
+}
+
+/// Call `f` `iterations` times and return the throughput in units of `work`
+/// per second, where `work` is the amount of work done by each call (e.g.,
+/// the number of floating-point operations).
+pub fn measure_throughput(iterations: usize, work: f64, mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    iterations as f64 * work / elapsed
+}
+
+/// The operation streamed by [`measure_bandwidth`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Load to X (`ldx`)
+    LoadX,
+    /// Load to Y (`ldy`)
+    LoadY,
+    /// Store from Z (`stz`)
+    StoreZ,
+}
+
+/// The memory access pattern used by [`measure_bandwidth`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// Consecutive 64-byte operations
+    Sequential,
+    /// Consecutive 128-byte operations on pairs of registers
+    Pair,
+    /// 64-byte operations advancing by the specified number of bytes, which
+    /// must be a non-zero multiple of 64. The buffer is swept once for each
+    /// 64-byte offset within the stride, so every byte is still accessed
+    /// once per pass.
+    Strided(usize),
+    /// Consecutive 64-byte interleaved Z stores (`stzi`). Only valid with
+    /// [`Access::StoreZ`].
+    InterleavedZ,
+}
+
+/// The result of [`measure_bandwidth`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bandwidth {
+    pub access: Access,
+    pub pattern: Pattern,
+    /// The total number of bytes transferred
+    pub bytes: usize,
+    /// The elapsed time in seconds
+    pub seconds: f64,
+}
+
+impl Bandwidth {
+    /// Get the throughput in gigabytes (10⁹ bytes) per second.
+    pub fn gbps(&self) -> f64 {
+        self.bytes as f64 / self.seconds / 1e9
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Access::LoadX => "ldx",
+            Access::LoadY => "ldy",
+            Access::StoreZ => "stz",
+        })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Sequential => f.write_str("sequential"),
+            Pattern::Pair => f.write_str("pair"),
+            Pattern::Strided(stride) => write!(f, "strided({stride})"),
+            Pattern::InterleavedZ => f.write_str("interleaved"),
+        }
+    }
+}
+
+impl fmt::Display for Bandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {:.2} GB/s",
+            self.access,
+            self.pattern,
+            self.gbps()
+        )
+    }
+}
+
+/// Measure the throughput of `passes` sweeps of `access` over a
+/// `buffer_size`-byte buffer aligned to 128-byte boundaries.
+///
+/// The register rows are rotated between operations (`x[0..8]`, `y[0..8]`,
+/// or `z[0..64]`), so this overwrites the whole register file of the accessed
+/// kind. Choose `buffer_size` to target a specific level of the memory
+/// hierarchy, e.g., 16 KiB for L1 and 64 MiB for DRAM.
+///
+/// # Panics
+///
+/// Panics if `buffer_size` is not a non-zero multiple of 128, the stride of
+/// [`Pattern::Strided`] is not a non-zero multiple of 64, or
+/// [`Pattern::InterleavedZ`] is used with an access other than
+/// [`Access::StoreZ`].
+#[track_caller]
+pub fn measure_bandwidth(
+    ctx: &mut (impl Amx + ?Sized),
+    access: Access,
+    pattern: Pattern,
+    buffer_size: usize,
+    passes: usize,
+) -> Bandwidth {
+    assert!(
+        buffer_size != 0 && buffer_size.is_multiple_of(128),
+        "`buffer_size` must be a non-zero multiple of 128"
+    );
+    match pattern {
+        Pattern::Strided(stride) => assert!(
+            stride != 0 && stride.is_multiple_of(64),
+            "the stride must be a non-zero multiple of 64"
+        ),
+        Pattern::InterleavedZ => assert_eq!(
+            access,
+            Access::StoreZ,
+            "`Pattern::InterleavedZ` requires `Access::StoreZ`"
+        ),
+        Pattern::Sequential | Pattern::Pair => {}
+    }
+
+    let mut storage = vec![0u8; buffer_size + 128];
+    let start = storage.as_ptr().align_offset(128);
+    let buf = &mut storage[start..][..buffer_size];
+
+    let (width, offsets): (usize, Vec<usize>) = match pattern {
+        Pattern::Sequential | Pattern::InterleavedZ => (64, (0..buffer_size).step_by(64).collect()),
+        Pattern::Pair => (128, (0..buffer_size).step_by(128).collect()),
+        Pattern::Strided(stride) => (
+            64,
+            (0..stride.min(buffer_size))
+                .step_by(64)
+                .flat_map(|s| (s..buffer_size).step_by(stride))
+                .collect(),
+        ),
+    };
+    let num_rows = match access {
+        Access::LoadX | Access::LoadY => 8,
+        Access::StoreZ => 64,
+    };
+
+    let ptr = buf.as_mut_ptr();
+    let start = Instant::now();
+    for _ in 0..passes {
+        for (i, &offset) in offsets.iter().enumerate() {
+            let row = i * (width / 64) % num_rows;
+            // Safety: `offset + width <= buffer_size`, and `offset` is a
+            // multiple of 128 if `width` is 128
+            unsafe {
+                let p = ptr.add(offset);
+                match (access, pattern) {
+                    (Access::LoadX, Pattern::Pair) => ctx.load1024_aligned(p, XRow(row)),
+                    (Access::LoadY, Pattern::Pair) => ctx.load1024_aligned(p, YRow(row)),
+                    (Access::StoreZ, Pattern::Pair) => ctx.store1024_aligned(p, ZRow(row)),
+                    (Access::StoreZ, Pattern::InterleavedZ) => {
+                        ctx.store512_interleaved(p, ZRow(row))
+                    }
+                    (Access::LoadX, _) => ctx.load512(p, XRow(row)),
+                    (Access::LoadY, _) => ctx.load512(p, YRow(row)),
+                    (Access::StoreZ, _) => ctx.store512(p, ZRow(row)),
+                }
+            }
+        }
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    std::hint::black_box(&storage);
+
+    Bandwidth {
+        access,
+        pattern,
+        bytes: passes * offsets.len() * width,
+        seconds,
+    }
+}
+
+/// Measure the bandwidth of every [`Access`] with the sequential, pair,
+/// and 4096-byte strided patterns, and [`Pattern::InterleavedZ`] stores,
+/// using [`measure_bandwidth`].
+#[track_caller]
+pub fn probe_bandwidth(
+    ctx: &mut (impl Amx + ?Sized),
+    buffer_size: usize,
+    passes: usize,
+) -> Vec<Bandwidth> {
+    let mut out = Vec::new();
+    for access in [Access::LoadX, Access::LoadY, Access::StoreZ] {
+        for pattern in [Pattern::Sequential, Pattern::Pair, Pattern::Strided(4096)] {
+            out.push(measure_bandwidth(ctx, access, pattern, buffer_size, passes));
+        }
+    }
+    out.push(measure_bandwidth(
+        ctx,
+        Access::StoreZ,
+        Pattern::InterleavedZ,
+        buffer_size,
+        passes,
+    ));
+    out
+}
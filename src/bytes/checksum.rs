@@ -0,0 +1,162 @@
+//! Fletcher and Adler checksums
+use crate::{Amx, ZRow, pipeline::Pipeline};
+
+/// The number of `i16` lanes in a register row
+const LANES: usize = 32;
+
+/// The number of bytes summed by each block
+const BLOCK: usize = 64;
+
+/// The modulus of Adler-32
+const ADLER_MOD: u64 = 65521;
+
+/// The modulus of Fletcher-64
+const FLETCHER_MOD: u64 = 0xffff_ffff;
+
+/// Calculate the `C` weighted sums `sums[b][c] = sum(weights[i][c] *
+/// block[b][i] for i in 0..64)` of each 64-byte block of `data`, passing
+/// them to `f(first_block, sums)` in batches of up to 32 blocks. The last
+/// block is padded with zeros.
+///
+/// The bytes at each position of 32 blocks are gathered into one X row, so
+/// each block is accumulated by 64 16-bit outer products with 32-bit
+/// accumulation against the weight rows, which cannot overflow for
+/// weights up to `64`.
+///
+/// This overwrites `x`, `y`, and `z`.
+fn block_sums<const C: usize>(
+    ctx: &mut (impl Amx + ?Sized),
+    data: &[u8],
+    weights: &[[i16; LANES]; BLOCK],
+    mut f: impl FnMut(usize, &[[i32; C]]),
+) {
+    let num_blocks = data.len().div_ceil(BLOCK);
+    let mut sums = [[0i32; C]; LANES];
+
+    for b0 in (0..num_blocks).step_by(LANES) {
+        let nb = (num_blocks - b0).min(LANES);
+
+        // `x[i][bb] = data[(b0 + bb) * 64 + i]`
+        let mut x = [[0i16; LANES]; BLOCK];
+        for (bb, block) in data[b0 * BLOCK..].chunks(BLOCK).take(nb).enumerate() {
+            for (x, &byte) in x.iter_mut().zip(block) {
+                x[bb] = byte as i16;
+            }
+        }
+
+        Pipeline::deepest(1, 1).run(
+            ctx,
+            BLOCK,
+            |ctx, i, slot| {
+                // Safety: Reading 64 bytes from each `[i16; 32]`
+                unsafe {
+                    ctx.load512(x[i].as_ptr(), slot.x_row(0));
+                    ctx.load512(weights[i].as_ptr(), slot.y_row(0));
+                }
+            },
+            |ctx, i, slot| {
+                ctx.outer_product_i16_xy_to_z_i32(
+                    Some(slot.x_bytes(0)),
+                    Some(slot.y_bytes(0)),
+                    ZRow(0),
+                    i != 0,
+                );
+            },
+        );
+
+        // `sums[bb][c]` is in `z[c * 2 + bb % 2][bb / 2]`
+        for c in 0..C {
+            let mut z = [[0i32; LANES / 2]; 2];
+            for (k, z) in z.iter_mut().enumerate() {
+                // Safety: Writing 64 bytes to `[i32; 16]`
+                unsafe { ctx.store512(z.as_mut_ptr(), ZRow(c * 2 + k)) };
+            }
+            for (bb, sums) in sums[..nb].iter_mut().enumerate() {
+                sums[c] = z[bb % 2][bb / 2];
+            }
+        }
+
+        f(b0, &sums[..nb]);
+    }
+}
+
+/// Update the Adler-32 checksum `adler` with `data` and return the new
+/// checksum. Pass `1` to start a new checksum.
+///
+/// Each 64-byte block's plain sum `Σ d[i]` and weighted sum
+/// `Σ (64 - i) d[i]` are calculated on the coprocessor, and only the
+/// per-block recurrence and the modular reductions are done on the CPU.
+///
+/// This overwrites `x`, `y`, and `z`.
+pub fn adler32(ctx: &mut (impl Amx + ?Sized), adler: u32, data: &[u8]) -> u32 {
+    let weights: [[i16; LANES]; BLOCK] = std::array::from_fn(|i| {
+        let mut row = [0; LANES];
+        row[0] = 1;
+        row[1] = (BLOCK - i) as i16;
+        row
+    });
+
+    let (mut a, mut b) = ((adler & 0xffff) as u64, (adler >> 16) as u64);
+    block_sums::<2>(ctx, data, &weights, |b0, sums| {
+        for (bb, &[s, t]) in sums.iter().enumerate() {
+            let len = (data.len() - (b0 + bb) * BLOCK).min(BLOCK) as u64;
+            let (s, t) = (s as u64, t as u64);
+            // Discount the weights of the zero padding
+            let t = t - (BLOCK as u64 - len) * s;
+            b = (b + len * a + t) % ADLER_MOD;
+            a = (a + s) % ADLER_MOD;
+        }
+    });
+
+    (b << 16 | a) as u32
+}
+
+/// Update the Fletcher-64 checksum `sum` with `data` and return the new
+/// checksum. Pass `0` to start a new checksum.
+///
+/// `data` is interpreted as a sequence of little-endian 32-bit words. The
+/// lower half of the checksum holds the sum of the words, and the upper half
+/// holds the sum of the running sums, both modulo `2³² - 1`.
+///
+/// The plain and weighted sums of each of the four byte positions in a
+/// 64-byte block are calculated on the coprocessor, and only the per-block
+/// recurrence and the modular reductions are done on the CPU.
+///
+/// This overwrites `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if `data.len()` is not a multiple of `4`.
+pub fn fletcher64(ctx: &mut (impl Amx + ?Sized), sum: u64, data: &[u8]) -> u64 {
+    assert_eq!(data.len() % 4, 0, "`data.len()` must be a multiple of `4`");
+
+    // Byte `p` of word `k` is weighted by `1` in lane `2p` and `16 - k` in
+    // lane `2p + 1`
+    let words_per_block = BLOCK / 4;
+    let weights: [[i16; LANES]; BLOCK] = std::array::from_fn(|i| {
+        let (k, p) = (i / 4, i % 4);
+        let mut row = [0; LANES];
+        row[2 * p] = 1;
+        row[2 * p + 1] = (words_per_block - k) as i16;
+        row
+    });
+
+    let (mut sum1, mut sum2) = (sum & 0xffff_ffff, sum >> 32);
+    block_sums::<8>(ctx, data, &weights, |b0, sums| {
+        for (bb, sums) in sums.iter().enumerate() {
+            let len = ((data.len() - (b0 + bb) * BLOCK).min(BLOCK) / 4) as u64;
+            let (mut s, mut t) = (0u64, 0u64);
+            for p in 0..4 {
+                let (s_p, t_p) = (sums[2 * p] as u64, sums[2 * p + 1] as u64);
+                // Discount the weights of the zero padding
+                let t_p = t_p - (words_per_block as u64 - len) * s_p;
+                s += s_p << (8 * p);
+                t += t_p << (8 * p);
+            }
+            sum2 = (sum2 + len * sum1 + t) % FLETCHER_MOD;
+            sum1 = (sum1 + s) % FLETCHER_MOD;
+        }
+    });
+
+    sum2 << 32 | sum1
+}
@@ -0,0 +1,272 @@
+//! Assembling register rows from non-contiguous bytes
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow, linalg::mac16_vector};
+use std::ops::Range;
+
+/// The number of bytes in a register row
+const ROW: usize = 64;
+
+/// The number of `i16` lanes in a register row
+const LANES: usize = 32;
+
+/// A plan assembling a 64-byte row from ranges of several source slices
+/// and fill bytes, built by [`GatherPlan::copy`] and [`GatherPlan::fill`].
+/// This suits data that never arrives contiguous, such as packet payloads
+/// or sparse features.
+///
+/// ```rust
+/// use amx::bytes::GatherPlan;
+///
+/// let header = b"GET /index.html";
+/// let payload = [7u8; 100];
+/// let mut plan = GatherPlan::new();
+/// plan.copy(0, 4..15).fill(0, 5).copy(1, 90..100);
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let row = plan.gather(&mut ctx, &[header, &payload]);
+/// assert_eq!(&row[..11], b"/index.html");
+/// assert_eq!(&row[11..16], [0; 5]);
+/// assert_eq!(&row[16..26], [7; 10]);
+/// ```
+///
+/// # Execution
+///
+/// The copied ranges are grouped into 64-byte windows of their sources,
+/// and each window is loaded to X only once. Every range is then moved into
+/// place by vector-mode `mac16` reading X at the byte offset aligning the
+/// window with the destination, with a per-lane multiplier in Y keeping only
+/// the destination bytes:
+///
+///  - `1` keeps both bytes of an `i16` lane.
+///  - `256`, applied to X read one byte later, keeps only the high byte.
+///  - `1` and `-256` in the same manner keep only the low byte.
+///
+/// Since the ranges are disjoint, the results are simply summed in Z.
+#[derive(Debug, Clone)]
+pub struct GatherPlan {
+    len: usize,
+    /// The fill bytes at their destinations, and zero elsewhere
+    fill: [u8; ROW],
+    segments: Vec<Segment>,
+    windows: Vec<Window>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    source: usize,
+    src: Range<usize>,
+    dest: usize,
+    /// The index into [`GatherPlan::windows`]
+    window: usize,
+    /// The multipliers applied to X read at the aligning offset and the
+    /// next byte, respectively
+    masks: [[i16; LANES]; 2],
+}
+
+#[derive(Debug, Clone)]
+struct Window {
+    source: usize,
+    src: Range<usize>,
+}
+
+impl Default for GatherPlan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GatherPlan {
+    /// Construct an empty `GatherPlan`.
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            fill: [0; ROW],
+            segments: Vec::new(),
+            windows: Vec::new(),
+        }
+    }
+
+    /// Get the number of bytes assembled so far. The rest of the row is
+    /// filled with zeros.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if no bytes have been added.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of 64-byte loads from the sources performed by
+    /// [`Self::gather_to_z`].
+    pub fn num_loads(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Append `sources[source][range]` to the row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row would exceed 64 bytes.
+    #[track_caller]
+    pub fn copy(&mut self, source: usize, range: Range<usize>) -> &mut Self {
+        let dest = self.reserve(range.len());
+        if range.is_empty() {
+            return self;
+        }
+        match self.segments.last_mut() {
+            Some(last) if last.source == source && last.src.end == range.start => {
+                last.src.end = range.end;
+            }
+            _ => self.segments.push(Segment {
+                source,
+                src: range,
+                dest,
+                window: 0,
+                masks: [[0; LANES]; 2],
+            }),
+        }
+        self.plan();
+        self
+    }
+
+    /// Append `len` copies of `byte` to the row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row would exceed 64 bytes.
+    #[track_caller]
+    pub fn fill(&mut self, byte: u8, len: usize) -> &mut Self {
+        let dest = self.reserve(len);
+        self.fill[dest..][..len].fill(byte);
+        self
+    }
+
+    #[track_caller]
+    fn reserve(&mut self, len: usize) -> usize {
+        assert!(self.len + len <= ROW, "the row would exceed 64 bytes");
+        self.len += len;
+        self.len - len
+    }
+
+    /// Recalculate the windows and the masks.
+    fn plan(&mut self) {
+        let mut order: Vec<usize> = (0..self.segments.len()).collect();
+        order.sort_by_key(|&i| (self.segments[i].source, self.segments[i].src.start));
+
+        self.windows.clear();
+        for i in order {
+            let seg = &mut self.segments[i];
+            match self.windows.last_mut() {
+                Some(w)
+                    if w.source == seg.source
+                        && seg.src.end.max(w.src.end) - w.src.start <= ROW =>
+                {
+                    w.src.end = w.src.end.max(seg.src.end);
+                }
+                _ => self.windows.push(Window {
+                    source: seg.source,
+                    src: seg.src.clone(),
+                }),
+            }
+            seg.window = self.windows.len() - 1;
+        }
+
+        for seg in &mut self.segments {
+            let dest = seg.dest..seg.dest + seg.src.len();
+            seg.masks = [[0; LANES]; 2];
+            for k in 0..LANES {
+                let (lo, hi) = (dest.contains(&(k * 2)), dest.contains(&(k * 2 + 1)));
+                let (m0, m1) = match (lo, hi) {
+                    (false, false) => (0, 0),
+                    (true, true) => (1, 0),
+                    (false, true) => (0, 256),
+                    (true, false) => (1, -256),
+                };
+                seg.masks[0][k] = m0;
+                seg.masks[1][k] = m1;
+            }
+        }
+    }
+
+    /// Assemble the row in `z[z_row]` (viewed as `[u8; 64]`).
+    ///
+    /// A window is loaded directly from its source if the source has at
+    /// least 64 bytes, and otherwise copied to a zero-padded buffer first.
+    ///
+    /// This overwrites `x` and `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a source referenced by the plan is missing from `sources`
+    /// or shorter than a range copied from it.
+    #[track_caller]
+    pub fn gather_to_z(&self, ctx: &mut (impl Amx + ?Sized), sources: &[&[u8]], z_row: ZRow) {
+        for seg in &self.segments {
+            let source = sources.get(seg.source).expect("missing source");
+            assert!(
+                seg.src.end <= source.len(),
+                "range {:?} is out of bounds of source {}",
+                seg.src,
+                seg.source
+            );
+        }
+
+        const ONES: [i16; LANES] = [1; LANES];
+        // Safety: Reading 64 bytes from `[u8; 64]` and `[i16; 32]`
+        unsafe {
+            ctx.load512(self.fill.as_ptr(), XRow(0));
+            ctx.load512(ONES.as_ptr(), YRow(0));
+        }
+        mac16_vector(ctx, XBytes(0), YBytes(0), z_row, false, false, 0);
+
+        // The start of each loaded window in its source
+        let mut starts = vec![0; self.windows.len()];
+        for (batch, windows) in self.windows.chunks(8).enumerate() {
+            for (r, window) in windows.iter().enumerate() {
+                let source = sources[window.source];
+                let start = if source.len() >= ROW {
+                    window.src.start.min(source.len() - ROW)
+                } else {
+                    0
+                };
+                starts[batch * 8 + r] = start;
+                if source.len() >= ROW {
+                    // Safety: `start + 64 <= source.len()`
+                    unsafe { ctx.load512(source[start..].as_ptr(), XRow(r)) };
+                } else {
+                    let mut padded = [0u8; ROW];
+                    padded[..source.len()].copy_from_slice(source);
+                    // Safety: Reading 64 bytes from `[u8; 64]`
+                    unsafe { ctx.load512(padded.as_ptr(), XRow(r)) };
+                }
+            }
+
+            let segments = self.segments.iter().filter(|seg| seg.window / 8 == batch);
+            for seg in segments {
+                // Safety: Reading 64 bytes from each `[i16; 32]`
+                unsafe {
+                    ctx.load512(seg.masks[0].as_ptr(), YRow(0));
+                    ctx.load512(seg.masks[1].as_ptr(), YRow(1));
+                }
+                let r = seg.window % 8;
+                let offset = (r * ROW + seg.src.start - starts[seg.window] + 512 - seg.dest) % 512;
+                mac16_vector(ctx, XBytes(offset), YBytes(0), z_row, true, false, 0);
+                let offset = (offset + 1) % 512;
+                mac16_vector(ctx, XBytes(offset), YBytes(ROW), z_row, true, false, 0);
+            }
+        }
+    }
+
+    /// Assemble the row and return it. See [`Self::gather_to_z`] for
+    /// details.
+    ///
+    /// This overwrites `x`, `y`, and `z[0]`.
+    #[track_caller]
+    pub fn gather(&self, ctx: &mut (impl Amx + ?Sized), sources: &[&[u8]]) -> [u8; ROW] {
+        self.gather_to_z(ctx, sources, ZRow(0));
+        let mut out = [0u8; ROW];
+        // Safety: Writing 64 bytes to `[u8; 64]`
+        unsafe { ctx.store512(out.as_mut_ptr(), ZRow(0)) };
+        out
+    }
+}
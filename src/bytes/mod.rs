@@ -0,0 +1,7 @@
+//! Byte stream and columnar data kernels built on top of
+//! [`Amx`](crate::Amx)
+mod checksum;
+mod gather;
+mod search;
+mod unpack;
+pub use self::{checksum::*, gather::*, search::*, unpack::*};
@@ -0,0 +1,184 @@
+//! Multi-pattern byte search
+use crate::{Amx, Index4, Normal, X8, XRow, YBytes, YRow, ZRow};
+
+/// The number of bytes scanned by each chunk
+const CHUNK: usize = 64;
+
+/// The number of pattern buckets, i.e., the bit width of a transition mask
+const NUM_BUCKETS: usize = 8;
+
+/// The maximum number of leading pattern bytes compared on the coprocessor
+const MAX_PREFIX: usize = 4;
+
+/// A match found by [`ShiftOr::find_all`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Match {
+    /// The byte offset of the match in the haystack
+    pub start: usize,
+    /// The index of the matched pattern
+    pub pattern: usize,
+}
+
+/// A multi-pattern matcher based on shift-or (bitap) filtering, suitable
+/// for scanning logs or pre-filtering tokenizer input for a set of
+/// literals.
+///
+/// The patterns are distributed among eight buckets, each represented by a
+/// bit in an 8-bit transition mask. For each of the first `L` positions of
+/// the patterns (`L` = the length of the shortest pattern, up to `4`), the
+/// mask of a byte has the bit of a bucket cleared if the byte's low and high
+/// nibbles both occur at that position in one of the bucket's patterns.
+/// These masks are looked up by `genlut` from per-nibble tables, 64 bytes at
+/// a time, and a candidate is reported for every start position where the
+/// shifted masks ORed together have a cleared bit. Candidates are then
+/// verified against the bucket's patterns on the CPU.
+#[derive(Debug, Clone)]
+pub struct ShiftOr {
+    patterns: Vec<Vec<u8>>,
+    /// The indices of the patterns in each bucket
+    buckets: [Vec<usize>; NUM_BUCKETS],
+    /// The number of leading bytes filtered by the tables
+    prefix: usize,
+    /// `tables[k * 2]` and `tables[k * 2 + 1]` hold the masks for the low
+    /// and high nibbles, respectively, of the `k`-th byte of a pattern
+    tables: Vec<[u8; 64]>,
+}
+
+impl ShiftOr {
+    /// Construct a `ShiftOr` matching any of `patterns`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `patterns` is empty or contains an empty pattern.
+    #[track_caller]
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        assert!(!patterns.is_empty(), "`patterns` must not be empty");
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|p| p.as_ref().to_vec()).collect();
+        assert!(
+            patterns.iter().all(|p| !p.is_empty()),
+            "`patterns` must not contain an empty pattern"
+        );
+
+        let prefix = patterns.iter().map(Vec::len).min().unwrap().min(MAX_PREFIX);
+
+        let mut buckets: [Vec<usize>; NUM_BUCKETS] = Default::default();
+        for i in 0..patterns.len() {
+            buckets[i % NUM_BUCKETS].push(i);
+        }
+
+        // A set bit indicates a mismatch
+        let mut tables = vec![[0u8; 64]; prefix * 2];
+        for (k, tables) in tables.chunks_exact_mut(2).enumerate() {
+            for table in tables.iter_mut() {
+                table[..16].fill(!0);
+            }
+            for (b, bucket) in buckets.iter().enumerate() {
+                for &i in bucket {
+                    let byte = patterns[i][k] as usize;
+                    tables[0][byte & 0xf] &= !(1 << b);
+                    tables[1][byte >> 4] &= !(1 << b);
+                }
+            }
+        }
+
+        Self {
+            patterns,
+            buckets,
+            prefix,
+            tables,
+        }
+    }
+
+    /// Get the number of patterns.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Return `false`. A `ShiftOr` always has at least one pattern.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Find all (possibly overlapping) occurrences of the patterns in
+    /// `haystack`, sorted by their start positions and then by pattern
+    /// indices.
+    ///
+    /// This overwrites `x[0..2L]`, `y[0]`, and `z[0..4L]`, where `L` is the
+    /// length of the filtered prefix (at most `4`).
+    pub fn find_all(&self, ctx: &mut (impl Amx + ?Sized), haystack: &[u8]) -> Vec<Match> {
+        let prefix = self.prefix;
+        let mut matches = Vec::new();
+        if haystack.len() < prefix {
+            return matches;
+        }
+        let num_starts = haystack.len() - prefix + 1;
+
+        for (k, table) in self.tables.iter().enumerate() {
+            // Safety: Reading 64 bytes from `[u8; 64]`
+            unsafe { ctx.load512(table.as_ptr(), XRow(k)) };
+        }
+
+        // Consecutive chunks overlap by `prefix - 1` bytes so that every
+        // start position has all of its prefix in one chunk
+        let stride = CHUNK - (prefix - 1);
+        for c0 in (0..num_starts).step_by(stride) {
+            let mut chunk = [0u8; CHUNK];
+            let len = (haystack.len() - c0).min(CHUNK);
+            chunk[..len].copy_from_slice(&haystack[c0..c0 + len]);
+            // Safety: Reading 64 bytes from `[u8; 64]`
+            unsafe { ctx.load512(chunk.as_ptr(), YRow(0)) };
+
+            // Each `genlut` consumes 64 nibbles, i.e., 32 bytes of the
+            // chunk. `z[k * 4 + h * 2]` holds the low-nibble masks in the
+            // even bytes, and `z[k * 4 + h * 2 + 1]` holds the high-nibble
+            // masks in the odd bytes.
+            for k in 0..prefix {
+                for h in 0..2 {
+                    for n in 0..2 {
+                        ctx.lut(
+                            YBytes(h * 32),
+                            XRow(k * 2 + n),
+                            ZRow(k * 4 + h * 2 + n),
+                            (Normal, Index4, X8),
+                        );
+                    }
+                }
+            }
+
+            // `masks[k][i]` = the mask of `chunk[i]` for the `k`-th byte
+            let mut masks = [[0u8; CHUNK]; MAX_PREFIX];
+            for (k, masks) in masks[..prefix].iter_mut().enumerate() {
+                let mut z = [[0u8; 64]; 4];
+                for (r, z) in z.iter_mut().enumerate() {
+                    // Safety: Writing 64 bytes to `[u8; 64]`
+                    unsafe { ctx.store512(z.as_mut_ptr(), ZRow(k * 4 + r)) };
+                }
+                for (i, mask) in masks.iter_mut().enumerate() {
+                    let (h, j) = (i / 32, i % 32);
+                    *mask = z[h * 2][j * 2] | z[h * 2 + 1][j * 2 + 1];
+                }
+            }
+
+            for s in 0..stride.min(num_starts - c0) {
+                let state = (0..prefix).fold(0, |state, k| state | masks[k][s + k]);
+                let start = c0 + s;
+                let first = matches.len();
+                for (b, bucket) in self.buckets.iter().enumerate() {
+                    if state & (1 << b) != 0 {
+                        continue;
+                    }
+                    for &i in bucket {
+                        if haystack[start..].starts_with(&self.patterns[i]) {
+                            matches.push(Match { start, pattern: i });
+                        }
+                    }
+                }
+                matches[first..].sort_unstable();
+            }
+        }
+
+        matches
+    }
+}
@@ -0,0 +1,205 @@
+//! Bit-unpacking of integer columns
+use crate::{
+    Amx, Index2, Index4, Index5, Normal, X16, X32, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::mac16_vector,
+};
+
+/// The number of values unpacked by each batch
+const LANES: usize = 32;
+
+/// An output element type of [`unpack`]
+trait Lane: Copy + Default {
+    /// Whether the lanes are 32-bit
+    const WIDE: bool;
+
+    fn from_index(i: usize) -> Self;
+}
+
+impl Lane for i16 {
+    const WIDE: bool = false;
+
+    #[inline]
+    fn from_index(i: usize) -> Self {
+        i as i16
+    }
+}
+
+impl Lane for i32 {
+    const WIDE: bool = true;
+
+    #[inline]
+    fn from_index(i: usize) -> Self {
+        i as i32
+    }
+}
+
+/// The implementation of [`unpack_bits_i16`] and [`unpack_bits_i32`]
+#[track_caller]
+fn unpack<T: Lane>(ctx: &mut (impl Amx + ?Sized), packed: &[u8], bits: u32, out: &mut [T]) {
+    assert!((1..=16).contains(&bits), "`bits` must be in range `1..=16`");
+    let w = bits as usize;
+    assert!(
+        packed.len() * 8 >= out.len() * w,
+        "`packed` is too short to contain {} values",
+        out.len()
+    );
+
+    match (w, T::WIDE) {
+        (2 | 4, _) | (5, false) => unpack_lut(ctx, packed, w, out),
+        _ => unpack_mac(ctx, packed, w, out),
+    }
+}
+
+/// The `genlut` path of [`unpack`]
+fn unpack_lut<T: Lane>(ctx: &mut (impl Amx + ?Sized), packed: &[u8], w: usize, out: &mut [T]) {
+    // Each `genlut` produces one row of `T`
+    let values_per_lut = 64 / size_of::<T>();
+    let bytes_per_lut = values_per_lut * w / 8;
+    let luts_per_load = 64 / bytes_per_lut;
+
+    let table: [T; LANES] = std::array::from_fn(T::from_index);
+    // Safety: Reading 64 bytes from `[T; 32]`
+    unsafe { ctx.load512(table.as_ptr(), XRow(0)) };
+
+    for (out, packed) in out
+        .chunks_mut(values_per_lut * luts_per_load)
+        .zip(packed.chunks(bytes_per_lut * luts_per_load))
+    {
+        let mut chunk = [0u8; 64];
+        chunk[..packed.len()].copy_from_slice(packed);
+        // Safety: Reading 64 bytes from `[u8; 64]`
+        unsafe { ctx.load512(chunk.as_ptr(), YRow(0)) };
+
+        for (i, out) in out.chunks_mut(values_per_lut).enumerate() {
+            let (input, output) = (YBytes(i * bytes_per_lut), ZRow(i));
+            match (w, T::WIDE) {
+                (2, false) => ctx.lut(input, XRow(0), output, (Normal, Index2, X16)),
+                (4, false) => ctx.lut(input, XRow(0), output, (Normal, Index4, X16)),
+                (5, false) => ctx.lut(input, XRow(0), output, (Normal, Index5, X16)),
+                (2, true) => ctx.lut(input, XRow(0), output, (Normal, Index2, X32)),
+                (4, true) => ctx.lut(input, XRow(0), output, (Normal, Index4, X32)),
+                _ => unreachable!(),
+            }
+
+            let mut values = [T::default(); LANES];
+            // Safety: Writing 64 bytes to `[T; 32]`
+            unsafe { ctx.store512(values.as_mut_ptr(), output) };
+            out.copy_from_slice(&values[..out.len()]);
+        }
+    }
+}
+
+/// The `mac16` path of [`unpack`]
+fn unpack_mac<T: Lane>(ctx: &mut (impl Amx + ?Sized), packed: &[u8], w: usize, out: &mut [T]) {
+    // A batch of 32 values always starts at a byte boundary, so the bit
+    // offset `r` of each lane is the same in every batch. Lane `m` is
+    // `(b0 >> r) + (b1 << (8 - r)) + (b2 << (16 - r))`, where `b0..b2` are
+    // the bytes spanned by the value with the bits above it cleared.
+    let offset = |m: usize| (m * w / 8, m * w % 8);
+    let num_bytes = |r: usize| (r + w).div_ceil(8);
+
+    // `y[0][m] = 1 << (8 - r)` (used with a shift amount of `8` for `b0`),
+    // `y[1][m] = 1 << min(16 - r, 14)`
+    let y: [[i16; LANES]; 2] = [
+        std::array::from_fn(|m| 1 << (8 - offset(m).1)),
+        std::array::from_fn(|m| 1 << (16 - offset(m).1).min(14)),
+    ];
+    // Safety: Reading 64 bytes from each `[i16; 32]`
+    unsafe {
+        ctx.load512(y[0].as_ptr(), YRow(0));
+        ctx.load512(y[1].as_ptr(), YRow(1));
+    }
+    let num_x_rows = if w > 9 {
+        3
+    } else if w > 1 {
+        2
+    } else {
+        1
+    };
+
+    for (batch, out) in out.chunks_mut(LANES).enumerate() {
+        let base = batch * LANES * w / 8;
+        let byte = |i: usize| *packed.get(base + i).unwrap_or(&0) as i16;
+
+        let mut x = [[0i16; LANES]; 3];
+        for m in 0..out.len() {
+            let (start, r) = offset(m);
+            let n = num_bytes(r);
+            for (k, x) in x[..n].iter_mut().enumerate() {
+                x[m] = byte(start + k);
+            }
+            // Clear the bits above the value
+            x[n - 1][m] &= (1 << (r + w - (n - 1) * 8)) - 1;
+            // `b2 << (16 - r)` doesn't fit in `y` if `r < 2`
+            x[2][m] <<= 16 - r - (16 - r).min(14);
+        }
+        // Safety: Reading 64 bytes from each `[i16; 32]`
+        unsafe {
+            for (k, x) in x[..num_x_rows].iter().enumerate() {
+                ctx.load512(x.as_ptr(), XRow(k));
+            }
+        }
+
+        mac16_vector(ctx, XBytes(0), YBytes(0), ZRow(0), false, T::WIDE, 8);
+        if num_x_rows > 1 {
+            mac16_vector(ctx, XBytes(64), YBytes(0), ZRow(0), true, T::WIDE, 0);
+        }
+        if num_x_rows > 2 {
+            mac16_vector(ctx, XBytes(128), YBytes(64), ZRow(0), true, T::WIDE, 0);
+        }
+
+        let mut values = [T::default(); LANES];
+        // Safety: Writing 64 bytes to `[i16; 32]` or 128 bytes to
+        //         `[i32; 32]`
+        unsafe {
+            if T::WIDE {
+                ctx.store512_interleaved(values.as_mut_ptr(), ZRow(0));
+                ctx.store512_interleaved(values.as_mut_ptr().add(LANES / 2), ZRow(1));
+            } else {
+                ctx.store512(values.as_mut_ptr(), ZRow(0));
+            }
+        }
+        out.copy_from_slice(&values[..out.len()]);
+    }
+}
+
+/// Unpack `out.len()` unsigned `bits`-bit integers packed LSB-first in
+/// `packed` (the bit-packed encoding of Parquet and Arrow) into `out`.
+///
+/// Widths supported by `genlut` (`2`, `4`, and `5`) are extracted 32
+/// values per instruction from an identity table. Other widths are handled
+/// by gathering the bytes spanned by each value on the CPU and shifting and
+/// combining them on the coprocessor with vector-mode `mac16`.
+///
+/// 16-bit values not representable as `i16` are wrapped around.
+///
+/// This overwrites `x[0..3]`, `y[0..2]`, and `z[0..16]`.
+///
+/// # Panics
+///
+/// Panics if `bits` is not in range `1..=16` or if `packed` is too short
+/// to contain `out.len()` values.
+#[track_caller]
+pub fn unpack_bits_i16(ctx: &mut (impl Amx + ?Sized), packed: &[u8], bits: u32, out: &mut [i16]) {
+    unpack(ctx, packed, bits, out);
+}
+
+/// Unpack `out.len()` unsigned `bits`-bit integers packed LSB-first in
+/// `packed` (the bit-packed encoding of Parquet and Arrow) into `out`.
+///
+/// Widths supported by `genlut` (`2` and `4`) are extracted 16 values per
+/// instruction from an identity table. Other widths are handled by
+/// gathering the bytes spanned by each value on the CPU and shifting and
+/// combining them on the coprocessor with vector-mode `mac16` with 32-bit
+/// accumulation.
+///
+/// This overwrites `x[0..3]`, `y[0..2]`, and `z[0..16]`.
+///
+/// # Panics
+///
+/// Panics if `bits` is not in range `1..=16` or if `packed` is too short
+/// to contain `out.len()` values.
+#[track_caller]
+pub fn unpack_bits_i32(ctx: &mut (impl Amx + ?Sized), packed: &[u8], bits: u32, out: &mut [i32]) {
+    unpack(ctx, packed, bits, out);
+}
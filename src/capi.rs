@@ -0,0 +1,222 @@
+//! A stable C API for the kernels (requires the `capi` feature)
+//!
+//! The functions in this module are exported with unmangled names so that
+//! the crate can be built as a static or dynamic library and called from C
+//! or C++:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+//!
+//! The corresponding declarations are found in `include/amx.h`, which is
+//! generated by [cbindgen](https://github.com/mozilla/cbindgen):
+//!
+//! ```text
+//! cbindgen --config cbindgen.toml --output include/amx.h
+//! ```
+//!
+//! Each function enables AMX for the duration of the call, so it must not be
+//! called while the calling thread already has an active
+//! [`AmxCtx`](crate::AmxCtx). Matrices are stored in row-major order, and
+//! `ld*` specify the distance between rows in elements. On targets other
+//! than AArch64, the functions run on [`AmxEmuCtx`](crate::AmxEmuCtx).
+use crate::{
+    DynAmx, dsp,
+    linalg::{self, MatMut, MatRef},
+};
+
+/// The status code returned by the C API functions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub enum AmxStatus {
+    /// The operation succeeded.
+    Ok = 0,
+    /// AMX could not be enabled for the calling thread.
+    Unavailable = -1,
+    /// A pointer is null or the dimensions are inconsistent.
+    InvalidArgument = -2,
+}
+
+/// Run `f` with an AMX context for the current thread.
+fn with_ctx(f: impl FnOnce(&mut dyn DynAmx)) -> AmxStatus {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "aarch64")] {
+            let Ok(mut ctx) = crate::AmxCtx::new() else {
+                return AmxStatus::Unavailable;
+            };
+            f(&mut ctx);
+        } else {
+            f(&mut crate::AmxEmuCtx::default());
+        }
+    }
+    AmxStatus::Ok
+}
+
+/// Construct a slice from a C array, returning `None` if `ptr` is null and
+/// `len` is non-zero.
+///
+/// # Safety
+///
+/// If `ptr` is not null, it must be valid for reading `len` elements.
+unsafe fn slice<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        // Safety: Upheld by the caller
+        Some(unsafe { std::slice::from_raw_parts(ptr, len) })
+    }
+}
+
+/// The mutable version of [`slice`].
+///
+/// # Safety
+///
+/// If `ptr` is not null, it must be valid for reading and writing `len`
+/// elements.
+unsafe fn slice_mut<'a, T>(ptr: *mut T, len: usize) -> Option<&'a mut [T]> {
+    if len == 0 {
+        Some(&mut [])
+    } else if ptr.is_null() {
+        None
+    } else {
+        // Safety: Upheld by the caller
+        Some(unsafe { std::slice::from_raw_parts_mut(ptr, len) })
+    }
+}
+
+/// Get the number of elements spanned by a `rows`×`cols` matrix with the
+/// specified row stride, or `None` if `ld < cols` or it overflows.
+fn extent(rows: usize, cols: usize, ld: usize) -> Option<usize> {
+    if ld < cols {
+        None
+    } else if rows == 0 || cols == 0 {
+        Some(0)
+    } else {
+        (rows - 1).checked_mul(ld)?.checked_add(cols)
+    }
+}
+
+/// Calculate `C = A * B` (or `C += A * B` if `accumulate` is set) where `A`
+/// is `m`×`k`, `B` is `k`×`n`, and `C` is `m`×`n`.
+///
+/// # Safety
+///
+/// The matrices must be valid for the specified dimensions and strides, and
+/// `c` must not overlap with `a` or `b`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn amx_sgemm(
+    m: usize,
+    n: usize,
+    k: usize,
+    a: *const f32,
+    lda: usize,
+    b: *const f32,
+    ldb: usize,
+    c: *mut f32,
+    ldc: usize,
+    accumulate: bool,
+) -> AmxStatus {
+    let (Some(a_len), Some(b_len), Some(c_len)) =
+        (extent(m, k, lda), extent(k, n, ldb), extent(m, n, ldc))
+    else {
+        return AmxStatus::InvalidArgument;
+    };
+    // Safety: Upheld by the caller
+    let (Some(a), Some(b), Some(c)) = (
+        unsafe { slice(a, a_len) },
+        unsafe { slice(b, b_len) },
+        unsafe { slice_mut(c, c_len) },
+    ) else {
+        return AmxStatus::InvalidArgument;
+    };
+    with_ctx(|ctx| {
+        linalg::sgemm(
+            ctx,
+            MatRef::with_stride(a, m, k, lda),
+            MatRef::with_stride(b, k, n, ldb),
+            MatMut::with_stride(c, m, n, ldc),
+            accumulate,
+        )
+    })
+}
+
+/// Calculate the dot product of `x[0..n]` and `y[0..n]` and store it to
+/// `*out`.
+///
+/// # Safety
+///
+/// `x` and `y` must be valid for reading `n` elements, and `out` must be
+/// valid for writing.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn amx_sdot(
+    n: usize,
+    x: *const f32,
+    y: *const f32,
+    out: *mut f32,
+) -> AmxStatus {
+    // Safety: Upheld by the caller
+    let (Some(x), Some(y)) = (unsafe { slice(x, n) }, unsafe { slice(y, n) }) else {
+        return AmxStatus::InvalidArgument;
+    };
+    if out.is_null() {
+        return AmxStatus::InvalidArgument;
+    }
+    // Safety: Upheld by the caller
+    with_ctx(|ctx| unsafe { out.write(linalg::sdot(ctx, x, y)) })
+}
+
+/// Calculate the `nx - nh + 1` outputs of the linear convolution of
+/// `x[0..nx]` and `h[0..nh]` for which `h` fully overlaps `x`. See
+/// `amx::dsp::conv_f32` for details.
+///
+/// `nh` must be in range `1..=nx`.
+///
+/// # Safety
+///
+/// `x` and `h` must be valid for reading `nx` and `nh` elements,
+/// respectively, and `out` must be valid for writing `nx - nh + 1` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn amx_sconv(
+    x: *const f32,
+    nx: usize,
+    h: *const f32,
+    nh: usize,
+    out: *mut f32,
+) -> AmxStatus {
+    if nh == 0 || nh > nx {
+        return AmxStatus::InvalidArgument;
+    }
+    // Safety: Upheld by the caller
+    let (Some(x), Some(h), Some(out)) =
+        (unsafe { slice(x, nx) }, unsafe { slice(h, nh) }, unsafe {
+            slice_mut(out, nx - nh + 1)
+        })
+    else {
+        return AmxStatus::InvalidArgument;
+    };
+    with_ctx(|ctx| dsp::conv_f32(ctx, x, h, out))
+}
+
+/// Quantize `x[0..n]` to 16-bit integers: `out[i] = round(x[i] / scale)`.
+/// See `amx::dsp::quantize_i16` for details.
+///
+/// # Safety
+///
+/// `x` must be valid for reading `n` elements, and `out` must be valid for
+/// writing `n` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn amx_quantize_i16(
+    x: *const f32,
+    n: usize,
+    scale: f32,
+    out: *mut i16,
+) -> AmxStatus {
+    // Safety: Upheld by the caller
+    let (Some(x), Some(out)) = (unsafe { slice(x, n) }, unsafe { slice_mut(out, n) }) else {
+        return AmxStatus::InvalidArgument;
+    };
+    dsp::quantize_i16(x, scale, out);
+    AmxStatus::Ok
+}
@@ -0,0 +1,77 @@
+//! Runtime validation of register rows and offsets
+use std::fmt;
+
+use crate::{
+    AmxVersion,
+    regs::{XBytes, XRow, YBytes, YRow, ZRow},
+};
+
+/// The error type for the `try_*` methods of [`Amx`](crate::Amx)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AmxArgError {
+    /// A register row index is out of range `0..len`.
+    RowOutOfRange { index: usize, len: usize },
+    /// A byte offset into a register file is out of range `0..len`.
+    OffsetOutOfRange { offset: usize, len: usize },
+    /// The operation requires a newer AMX revision than
+    /// [`AmxOps::version`](crate::AmxOps::version).
+    Unsupported { required: AmxVersion },
+}
+
+impl fmt::Display for AmxArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::RowOutOfRange { index, len } => {
+                write!(f, "register row index {index} is out of range 0..{len}")
+            }
+            Self::OffsetOutOfRange { offset, len } => {
+                write!(f, "register byte offset {offset} is out of range 0..{len}")
+            }
+            Self::Unsupported { required } => {
+                write!(
+                    f,
+                    "the operation requires AMX revision {required:?} or later"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AmxArgError {}
+
+/// Register rows and offsets that can be validated at runtime.
+pub trait CheckArg {
+    /// Check if `self` is in range.
+    fn check(&self) -> Result<(), AmxArgError>;
+}
+
+macro_rules! impl_check_index {
+    ($($ty:ty),*) => {$(
+        impl CheckArg for $ty {
+            #[inline]
+            fn check(&self) -> Result<(), AmxArgError> {
+                <$ty>::try_new(self.0).map(drop)
+            }
+        }
+    )*};
+}
+
+impl_check_index!(XRow, YRow, ZRow, XBytes, YBytes);
+
+impl<T: CheckArg> CheckArg for Option<T> {
+    #[inline]
+    fn check(&self) -> Result<(), AmxArgError> {
+        self.as_ref().map_or(Ok(()), T::check)
+    }
+}
+
+#[cfg(feature = "either")]
+impl<Left: CheckArg, Right: CheckArg> CheckArg for either::Either<Left, Right> {
+    #[inline]
+    fn check(&self) -> Result<(), AmxArgError> {
+        match self {
+            either::Left(x) => x.check(),
+            either::Right(x) => x.check(),
+        }
+    }
+}
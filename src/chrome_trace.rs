@@ -0,0 +1,135 @@
+//! Export of instruction traces in the Chrome tracing JSON format
+use std::{fmt::Write as _, io};
+
+use crate::emu::Instr;
+
+/// An instruction trace with timing information, which can be exported in
+/// the Chrome Trace Event Format (JSON) accepted by Perfetto and
+/// `chrome://tracing`.
+///
+/// Each instruction becomes a complete (`"ph": "X"`) event named after its
+/// mnemonic, with the pseudo-assembly (see [`Instr`]'s `Display` impl) in
+/// its `args`. JSON has no representation of NaN and infinities, so
+/// non-finite times are written as `null`.
+///
+/// # Example
+///
+/// ```rust
+/// use amx::{ChromeTrace, Instr, jit::JitOp};
+///
+/// let trace = [
+///     Instr { op: JitOp::Ldx, operand: 0, addr: Some(0x1000) },
+///     Instr { op: JitOp::Fma32, operand: 0, addr: None },
+/// ];
+/// // Assume every instruction takes 1 ns
+/// let json = ChromeTrace::from_cost_model(&trace, |_| 0.001).to_json();
+/// assert!(json.starts_with(r#"{"traceEvents":[{"name":"ldx","#));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ChromeTrace {
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Clone)]
+struct Event {
+    instr: Instr,
+    /// The start time in microseconds
+    ts: f64,
+    /// The duration in microseconds
+    dur: f64,
+    tid: u32,
+}
+
+impl ChromeTrace {
+    /// Construct an empty `ChromeTrace`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a `ChromeTrace` by laying out `instrs` back to back on a
+    /// single thread, where `cost(instr)` gives the duration of each
+    /// instruction in microseconds.
+    pub fn from_cost_model(instrs: &[Instr], mut cost: impl FnMut(&Instr) -> f64) -> Self {
+        let mut this = Self::new();
+        let mut ts = 0.0;
+        for instr in instrs {
+            let dur = cost(instr);
+            this.push(instr, 0, ts, dur);
+            ts += dur;
+        }
+        this
+    }
+
+    /// Append an instruction executed by thread `tid` at time `ts` for
+    /// `dur` (both in microseconds), e.g., as measured on real hardware.
+    pub fn push(&mut self, instr: &Instr, tid: u32, ts: f64, dur: f64) -> &mut Self {
+        self.events.push(Event {
+            instr: *instr,
+            ts,
+            dur,
+            tid,
+        });
+        self
+    }
+
+    /// Get the number of events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Get a flag indicating whether there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Render the trace as a JSON string.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from(r#"{"traceEvents":["#);
+        for (i, event) in self.events.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                r#"{{"name":"{}","cat":"amx","ph":"X","ts":"#,
+                event.instr.op.mnemonic()
+            )
+            .unwrap();
+            write_json_f64(event.ts, &mut out);
+            out.push_str(r#","dur":"#);
+            write_json_f64(event.dur, &mut out);
+            write!(out, r#","pid":0,"tid":{},"args":{{"asm":""#, event.tid).unwrap();
+            escape_json(&event.instr.to_string(), &mut out);
+            out.push_str(r#""}}"#);
+        }
+        out.push_str(r#"],"displayTimeUnit":"ns"}"#);
+        out
+    }
+
+    /// Write the trace as JSON to `writer`.
+    pub fn write_json(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(self.to_json().as_bytes())
+    }
+}
+
+/// Append `s` to `out` with the characters not allowed in a JSON string
+/// escaped.
+pub(crate) fn escape_json(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str(r#"\""#),
+            '\\' => out.push_str(r"\\"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Append `x` to `out` as a JSON number, or `null` if it's NaN or infinite.
+pub(crate) fn write_json_f64(x: f64, out: &mut String) {
+    if x.is_finite() {
+        write!(out, "{x}").unwrap();
+    } else {
+        out.push_str("null");
+    }
+}
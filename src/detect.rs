@@ -0,0 +1,138 @@
+//! Runtime detection of AMX support
+use std::sync::OnceLock;
+
+/// An AMX revision, named after the chip generation that introduced it
+///
+/// The revisions are ordered by age, so `version >= AmxVersion::M2` checks
+/// for M2 or later.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum AmxVersion {
+    /// The first revision, found in M1
+    M1,
+    /// Found in M2
+    M2,
+    /// Found in M3
+    M3,
+}
+
+impl AmxVersion {
+    /// Get the generation number (e.g., `2` for M2).
+    pub const fn generation(self) -> u32 {
+        match self {
+            Self::M1 => 1,
+            Self::M2 => 2,
+            Self::M3 => 3,
+        }
+    }
+
+    /// Derive the AMX revision from a brand string like `Apple M2 Pro`.
+    /// A generation newer than the ones known to this crate (e.g.,
+    /// `Apple M4`) maps to the newest known revision. Returns `None` if the
+    /// chip is not recognized.
+    pub fn from_brand_string(chip: &str) -> Option<Self> {
+        let generation = chip.strip_prefix("Apple M")?;
+        let digits = generation.split(|c: char| !c.is_ascii_digit()).next()?;
+        match digits.parse::<u32>().ok()? {
+            0 => None,
+            1 => Some(Self::M1),
+            2 => Some(Self::M2),
+            _ => Some(Self::M3),
+        }
+    }
+}
+
+/// The error type for [`AmxCtx::new`](crate::AmxCtx::new) and
+/// [`AmxFallbackCtx::try_new`](crate::AmxFallbackCtx::try_new)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NewAmxCtxError {
+    /// The current thread already has an active `AmxCtx`.
+    AlreadyActive,
+    /// AMX is not supported by the target system (see [`is_available`]).
+    Unsupported,
+}
+
+/// Check if the current system has AMX, i.e., it has an Apple-designed
+/// AArch64 processor. This covers Apple Silicon Macs and iOS devices as well
+/// as other operating systems running on them, such as Asahi Linux.
+///
+/// This only queries the operating system and never executes an AMX
+/// instruction, so it's safe to call anywhere. The result is cached.
+///
+/// ```rust
+/// if let Some(version) = amx::version() {
+///     assert!(amx::is_available());
+///     println!("AMX revision: {version:?}");
+/// }
+/// ```
+pub fn is_available() -> bool {
+    detect().0
+}
+
+/// Get the AMX revision of the current system, derived from the processor's
+/// brand string (see [`AmxVersion::from_brand_string`]). Returns `None` if
+/// AMX is not available or the brand string is unavailable or not
+/// recognized (e.g., on an A-series chip or outside Apple's operating
+/// systems).
+pub fn version() -> Option<AmxVersion> {
+    detect().1
+}
+
+/// Detect `(is_available(), version())`.
+fn detect() -> (bool, Option<AmxVersion>) {
+    static DETECTED: OnceLock<(bool, Option<AmxVersion>)> = OnceLock::new();
+    *DETECTED.get_or_init(|| {
+        if !cfg!(target_arch = "aarch64") || !is_apple_cpu() {
+            return (false, None);
+        }
+        let version = chip_name().and_then(|chip| AmxVersion::from_brand_string(&chip));
+        (true, version)
+    })
+}
+
+/// Check if the processor is designed by Apple.
+fn is_apple_cpu() -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(target_vendor = "apple")] {
+            true
+        } else if #[cfg(target_os = "linux")] {
+            // Apple's implementer code in `MIDR_EL1` is `0x61`
+            std::fs::read_to_string("/proc/cpuinfo").is_ok_and(|info| {
+                info.lines().any(|line| {
+                    let (key, value) = line.split_once(':').unwrap_or_default();
+                    key.trim() == "CPU implementer" && value.trim() == "0x61"
+                })
+            })
+        } else {
+            false
+        }
+    }
+}
+
+/// Get the processor's brand string.
+pub(crate) fn chip_name() -> Option<String> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_vendor = "apple")] {
+            let name = c"machdep.cpu.brand_string";
+            let mut buf = [0u8; 256];
+            let mut len = buf.len();
+            // Safety: `buf` is valid for writing `len` bytes
+            let ret = unsafe {
+                libc::sysctlbyname(
+                    name.as_ptr(),
+                    buf.as_mut_ptr().cast(),
+                    &mut len,
+                    std::ptr::null_mut(),
+                    0,
+                )
+            };
+            if ret != 0 {
+                return None;
+            }
+            let s = std::ffi::CStr::from_bytes_until_nul(&buf[..len]).ok()?;
+            Some(s.to_string_lossy().into_owned())
+        } else {
+            None
+        }
+    }
+}
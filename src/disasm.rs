@@ -0,0 +1,559 @@
+//! Operand decoding and pseudo-assembly rendering of instruction traces
+//!
+//! [`decode`] splits the operand of any AMX instruction into its fields,
+//! which is handy when porting assembly kernels or comparing against traces
+//! of other libraries. [`Disassembly`] renders whole traces recorded by
+//! [`AmxEmuCtx`](crate::AmxEmuCtx).
+use std::fmt;
+
+use crate::{
+    Fma16Operand, Fma32Operand, Fma64Operand, Mac16Operand, MatFpOperand, MatFpTy, MatIntOperand,
+    MatIntTy, RegFile, VecFpAluOp, VecFpOperand, VecFpTy, VecIntOperand, VecIntTy, XBytes, XRow,
+    YBytes, ZRow, emu::Instr, jit::JitOp, matop::MatOptions,
+};
+
+/// Renders a sequence of [`Instr`]s as pseudo-assembly, one instruction per
+/// line.
+///
+/// Memory addresses are displayed relative to the closest preceding base
+/// address registered by [`Self::base`], e.g., `ldx x3, [p+0xc0]`.
+///
+/// # Example
+///
+/// ```rust
+/// use amx::{Disassembly, Instr, jit::JitOp};
+///
+/// let trace = [
+///     Instr { op: JitOp::Ldx, operand: 3 << 56, addr: Some(0x10c0) },
+///     Instr { op: JitOp::Fma32, operand: 0, addr: None },
+/// ];
+/// let text = Disassembly::new(&trace).base("p", 0x1000).to_string();
+/// assert_eq!(text, "ldx x3, [p+0xc0]\nfma32 z0 += x[0..64] * y[0..64]\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Disassembly<'a> {
+    instrs: &'a [Instr],
+    bases: Vec<(&'a str, usize)>,
+}
+
+impl<'a> Disassembly<'a> {
+    /// Construct a `Disassembly` of `instrs`.
+    pub fn new(instrs: &'a [Instr]) -> Self {
+        Self {
+            instrs,
+            bases: Vec::new(),
+        }
+    }
+
+    /// Display the addresses at or after `addr` (and before the next base
+    /// address) as offsets from `name`.
+    pub fn base(mut self, name: &'a str, addr: usize) -> Self {
+        self.bases.push((name, addr));
+        self
+    }
+}
+
+impl fmt::Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instr in self.instrs {
+            fmt_instr(instr, &self.bases, f)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_instr(self, &[], f)
+    }
+}
+
+/// Format a memory address, using the closest preceding base address if any.
+fn fmt_addr(addr: usize, bases: &[(&str, usize)], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match bases
+        .iter()
+        .filter(|&&(_, base)| base <= addr)
+        .max_by_key(|&&(_, base)| base)
+    {
+        Some((name, base)) => write!(f, "[{name}+{:#x}]", addr - base),
+        None => write!(f, "[{addr:#x}]"),
+    }
+}
+
+/// Format a 64-byte window of X or Y starting at `offset`.
+fn fmt_window(name: &str, offset: u64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{name}[{offset}..{}]", offset + 64)
+}
+
+fn fmt_instr(instr: &Instr, bases: &[(&str, usize)], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let decoded = decode(instr.op as u8, instr.operand);
+    fmt_decoded(&decoded, instr.addr.unwrap_or_default(), bases, f)
+}
+
+/// The fields of an instruction decoded by [`decode`]
+///
+/// The `Display` implementation renders the instruction as pseudo-assembly
+/// like [`Disassembly`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodedInstr {
+    /// `ldx`, `ldy`, `stx`, `sty`, `ldz`, or `stz`, transferring row `row`
+    /// (and `row + 1` if `pair` is set) from or to `addr`
+    Mem {
+        /// The opcode
+        op: JitOp,
+        /// The first register row
+        row: usize,
+        /// Whether two rows (128 bytes) are transferred
+        pair: bool,
+        /// The memory address
+        addr: usize,
+    },
+    /// `ldzi` or `stzi`, transferring the interleaved halves of rows
+    /// `row & !1` and `row | 1` from or to `addr`
+    MemInterleaved {
+        /// The opcode
+        op: JitOp,
+        /// The register row
+        row: usize,
+        /// The memory address
+        addr: usize,
+    },
+    /// `extrx` (copying `z[z_row]` to X at `offset`) or `extry` (to Y)
+    Extract {
+        /// The opcode
+        op: JitOp,
+        /// The byte offset in X or Y
+        offset: usize,
+        /// The source row of Z
+        z_row: ZRow,
+    },
+    /// `mac16`, the integer multiply-accumulate
+    Mac16(Mac16Operand),
+    /// `fma16` or, if `subtract` is set, `fms16`
+    Fma16 {
+        /// Whether the product is subtracted
+        subtract: bool,
+        /// The decoded operand
+        operand: Fma16Operand,
+    },
+    /// `fma32` or, if `subtract` is set, `fms32`
+    Fma32 {
+        /// Whether the product is subtracted
+        subtract: bool,
+        /// The decoded operand
+        operand: Fma32Operand,
+    },
+    /// `fma64` or, if `subtract` is set, `fms64`
+    Fma64 {
+        /// Whether the product is subtracted
+        subtract: bool,
+        /// The decoded operand
+        operand: Fma64Operand,
+    },
+    /// `vecint`, the integer vector operation
+    VecInt(VecIntOperand),
+    /// `vecfp`, the floating-point vector operation
+    VecFp(VecFpOperand),
+    /// `matint`, the integer outer product
+    MatInt(MatIntOperand),
+    /// `matfp`, the floating-point outer product
+    MatFp(MatFpOperand),
+    /// `genlut`, looking up the indices in `input` (X or Y) at byte offset
+    /// `input_offset` in the table `x[table_row]` and writing the values
+    /// to row `output_row` of `output`. `mode` is the raw LUT mode (see
+    /// [`LutTy`](crate::LutTy)).
+    Genlut {
+        /// The register file holding the indices
+        input: RegFile,
+        /// The byte offset of the indices
+        input_offset: usize,
+        /// The register file written
+        output: RegFile,
+        /// The row written
+        output_row: usize,
+        /// The raw LUT mode
+        mode: u8,
+        /// The row of X holding the table
+        table_row: XRow,
+    },
+    /// An unknown opcode, or an operand whose lane type or ALU mode is not
+    /// supported by this crate
+    Unknown {
+        /// The raw opcode
+        opcode: u8,
+        /// The raw operand
+        operand: u64,
+    },
+}
+
+/// Decode the operand `operand` of the AMX instruction with the opcode
+/// `opcode` (the value of [`JitOp`]).
+///
+/// The memory instructions take the address from the low 56 bits of the
+/// operand, as the hardware does.
+///
+/// ```rust
+/// use amx::disasm::{DecodedInstr, decode};
+///
+/// let operand = (1 << 63) | (3 << 20) | (128 << 10);
+/// assert!(matches!(decode(12, operand), DecodedInstr::Fma32 { subtract: false, .. }));
+/// assert_eq!(
+///     decode(12, operand).to_string(),
+///     "fma32 z3 += x[128..192] * y[0..64] (vector)",
+/// );
+/// assert_eq!(
+///     decode(18, (4 << 42) | (1 << 47) | (1 << 53) | (2 << 58)).to_string(),
+///     "vecint.i32 z0 -= (x[0..64] * y[0..64]) >> 2 (saturate)",
+/// );
+/// assert_eq!(decode(17, 0).to_string(), "amx17 0x0");
+/// ```
+pub fn decode(opcode: u8, operand: u64) -> DecodedInstr {
+    let x = operand;
+    let Some(op) = JitOp::from_opcode(opcode) else {
+        return DecodedInstr::Unknown { opcode, operand };
+    };
+    let addr = (x & ((1 << 56) - 1)) as usize;
+    let row = ((x >> 56) & 0x3f) as usize;
+    let decoded = match op {
+        JitOp::Ldx | JitOp::Ldy | JitOp::Stx | JitOp::Sty | JitOp::Ldz | JitOp::Stz => {
+            Some(DecodedInstr::Mem {
+                op,
+                row,
+                pair: x & (1 << 62) != 0,
+                addr,
+            })
+        }
+        JitOp::Ldzi | JitOp::Stzi => Some(DecodedInstr::MemInterleaved { op, row, addr }),
+        JitOp::Extrx | JitOp::Extry => Some(DecodedInstr::Extract {
+            op,
+            offset: if op == JitOp::Extrx {
+                ((x >> 10) & 0x1ff) as usize
+            } else {
+                (x & 0x1ff) as usize
+            },
+            z_row: ZRow(((x >> 20) & 0x3f) as usize),
+        }),
+        JitOp::Mac16 => Some(DecodedInstr::Mac16(Mac16Operand::decode(x))),
+        JitOp::Fma16 | JitOp::Fms16 => Some(DecodedInstr::Fma16 {
+            subtract: op == JitOp::Fms16,
+            operand: Fma16Operand::decode(x),
+        }),
+        JitOp::Fma32 | JitOp::Fms32 => Some(DecodedInstr::Fma32 {
+            subtract: op == JitOp::Fms32,
+            operand: Fma32Operand::decode(x),
+        }),
+        JitOp::Fma64 | JitOp::Fms64 => Some(DecodedInstr::Fma64 {
+            subtract: op == JitOp::Fms64,
+            operand: Fma64Operand::decode(x),
+        }),
+        JitOp::Vecint => VecIntOperand::decode(x).map(DecodedInstr::VecInt),
+        JitOp::Vecfp => VecFpOperand::decode(x).map(DecodedInstr::VecFp),
+        JitOp::Matint => MatIntOperand::decode(x).map(DecodedInstr::MatInt),
+        JitOp::Matfp => MatFpOperand::decode(x).map(DecodedInstr::MatFp),
+        JitOp::Genlut => {
+            let output = if x & (1 << 26) != 0 {
+                RegFile::Z
+            } else if x & (1 << 25) != 0 {
+                RegFile::Y
+            } else {
+                RegFile::X
+            };
+            let row_bits = if output == RegFile::Z { 0x3f } else { 0x1f };
+            Some(DecodedInstr::Genlut {
+                input: if x & (1 << 10) != 0 {
+                    RegFile::Y
+                } else {
+                    RegFile::X
+                },
+                input_offset: (x & 0x1ff) as usize,
+                output,
+                output_row: ((x >> 20) & row_bits) as usize,
+                mode: ((x >> 53) & 0xf) as u8,
+                table_row: XRow(((x >> 60) & 7) as usize),
+            })
+        }
+    };
+    decoded.unwrap_or(DecodedInstr::Unknown { opcode, operand })
+}
+
+impl fmt::Display for DecodedInstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let addr = match *self {
+            Self::Mem { addr, .. } | Self::MemInterleaved { addr, .. } => addr,
+            _ => 0,
+        };
+        fmt_decoded(self, addr, &[], f)
+    }
+}
+
+/// The lowercase name of a register file
+fn reg_name(file: RegFile) -> &'static str {
+    match file {
+        RegFile::X => "x",
+        RegFile::Y => "y",
+        RegFile::Z => "z",
+    }
+}
+
+/// The assignment operator of an accumulating operation
+fn assign(accumulate: bool, subtract: bool) -> &'static str {
+    match (accumulate, subtract) {
+        (false, _) => "=",
+        (true, false) => "+=",
+        (true, true) => "-=",
+    }
+}
+
+/// Format `x * y`, omitting the excluded operands.
+fn fmt_product(
+    x_offset: Option<XBytes>,
+    y_offset: Option<YBytes>,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    match (x_offset, y_offset) {
+        (Some(x), Some(y)) => {
+            fmt_window("x", x.0 as u64, f)?;
+            f.write_str(" * ")?;
+            fmt_window("y", y.0 as u64, f)
+        }
+        (Some(x), None) => fmt_window("x", x.0 as u64, f),
+        (None, Some(y)) => fmt_window("y", y.0 as u64, f),
+        (None, None) => f.write_str("1"),
+    }
+}
+
+/// Format an instruction of the `fma`/`fms`/`mac16` family.
+fn fmt_mul(
+    mnemonic: &str,
+    subtract: bool,
+    (x_offset, y_offset, z_row, accumulate): (Option<XBytes>, Option<YBytes>, ZRow, bool),
+    (shift, wide, vector): (u32, bool, bool),
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(
+        f,
+        "{mnemonic} z{} {} ",
+        z_row.0,
+        assign(accumulate, subtract)
+    )?;
+    fmt_product(x_offset, y_offset, f)?;
+    if shift != 0 {
+        write!(f, " >> {shift}")?;
+    }
+    if wide {
+        f.write_str(" (wide)")?;
+    }
+    if vector {
+        f.write_str(" (vector)")?;
+    }
+    Ok(())
+}
+
+fn fmt_decoded(
+    decoded: &DecodedInstr,
+    addr: usize,
+    bases: &[(&str, usize)],
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    match *decoded {
+        DecodedInstr::Mem { op, row, pair, .. } => {
+            let reg = match op {
+                JitOp::Ldx | JitOp::Stx => "x",
+                JitOp::Ldy | JitOp::Sty => "y",
+                _ => "z",
+            };
+            write!(f, "{} {reg}{row}, ", op.mnemonic())?;
+            if pair {
+                write!(f, "{reg}{}, ", row + 1)?;
+            }
+            fmt_addr(addr, bases, f)
+        }
+        DecodedInstr::MemInterleaved { op, row, .. } => {
+            write!(f, "{} z{row}, ", op.mnemonic())?;
+            fmt_addr(addr, bases, f)
+        }
+        DecodedInstr::Extract { op, offset, z_row } => {
+            if op == JitOp::Extrx {
+                fmt_window("extrx x", offset as u64, f)?;
+            } else {
+                fmt_window("extry y", offset as u64, f)?;
+            }
+            write!(f, " = z{}", z_row.0)
+        }
+        DecodedInstr::Mac16(o) => fmt_mul(
+            "mac16",
+            false,
+            (o.x_offset, o.y_offset, o.z_row, o.accumulate),
+            (o.shift, o.widen, o.vector),
+            f,
+        ),
+        DecodedInstr::Fma16 {
+            subtract,
+            operand: o,
+        } => fmt_mul(
+            if subtract { "fms16" } else { "fma16" },
+            subtract,
+            (o.x_offset, o.y_offset, o.z_row, o.accumulate),
+            (0, o.widen, o.vector),
+            f,
+        ),
+        DecodedInstr::Fma32 {
+            subtract,
+            operand: o,
+        } => fmt_mul(
+            if subtract { "fms32" } else { "fma32" },
+            subtract,
+            (o.x_offset, o.y_offset, o.z_row, o.accumulate),
+            (0, false, o.vector),
+            f,
+        ),
+        DecodedInstr::Fma64 {
+            subtract,
+            operand: o,
+        } => fmt_mul(
+            if subtract { "fms64" } else { "fma64" },
+            subtract,
+            (o.x_offset, o.y_offset, o.z_row, o.accumulate),
+            (0, false, o.vector),
+            f,
+        ),
+        DecodedInstr::VecInt(o) => {
+            let op = o.op;
+            let ty = match op.ty() {
+                VecIntTy::I8 => "i8",
+                VecIntTy::I16 => "i16",
+                VecIntTy::I32 => "i32",
+            };
+            write!(
+                f,
+                "vecint.{ty} z{} {} ",
+                o.z_row.0,
+                assign(op.accumulate, op.subtract)
+            )?;
+            let (x, y) = (Some(o.x_offset), Some(o.y_offset));
+            if op.shift != 0 {
+                f.write_str("(")?;
+                fmt_product(x, y, f)?;
+                write!(f, ") >> {}", op.shift)?;
+            } else {
+                fmt_product(x, y, f)?;
+            }
+            if op.saturate {
+                f.write_str(" (saturate)")?;
+            }
+            Ok(())
+        }
+        DecodedInstr::VecFp(o) => {
+            let ty = match o.ty {
+                VecFpTy::F16 => "f16",
+                VecFpTy::F32 => "f32",
+                VecFpTy::F64 => "f64",
+            };
+            write!(f, "vecfp.{ty} z{} ", o.z_row.0)?;
+            let (x, y) = (Some(o.x_offset), Some(o.y_offset));
+            match o.op {
+                VecFpAluOp::MulAdd | VecFpAluOp::MulSub => {
+                    f.write_str(assign(true, o.op == VecFpAluOp::MulSub))?;
+                    f.write_str(" ")?;
+                    fmt_product(x, y, f)
+                }
+                VecFpAluOp::Add | VecFpAluOp::Sub => {
+                    f.write_str(assign(true, o.op == VecFpAluOp::Sub))?;
+                    f.write_str(" ")?;
+                    fmt_product(x, None, f)
+                }
+                VecFpAluOp::Min | VecFpAluOp::Max => {
+                    let name = if o.op == VecFpAluOp::Min {
+                        "min"
+                    } else {
+                        "max"
+                    };
+                    write!(f, "= {name}(z{}, ", o.z_row.0)?;
+                    fmt_product(x, None, f)?;
+                    f.write_str(")")
+                }
+            }
+        }
+        DecodedInstr::MatInt(o) => {
+            let ty = match o.op.ty() {
+                MatIntTy::I8 => "i8",
+                MatIntTy::U8 => "u8",
+                MatIntTy::I16 => "i16",
+                MatIntTy::U16 => "u16",
+            };
+            fmt_mat(
+                "matint",
+                ty,
+                &o.op.options,
+                (o.x_offset, o.y_offset, o.z_row),
+                f,
+            )
+        }
+        DecodedInstr::MatFp(o) => {
+            let ty = match o.op.ty() {
+                MatFpTy::F16 => "f16",
+                MatFpTy::F32 => "f32",
+                MatFpTy::F64 => "f64",
+                #[cfg(feature = "amx2")]
+                MatFpTy::Bf16 => "bf16",
+            };
+            fmt_mat(
+                "matfp",
+                ty,
+                &o.op.options,
+                (o.x_offset, o.y_offset, o.z_row),
+                f,
+            )
+        }
+        DecodedInstr::Genlut {
+            input,
+            input_offset,
+            output,
+            output_row,
+            mode,
+            table_row,
+        } => {
+            write!(
+                f,
+                "genlut {}{output_row} = lut(x{}, ",
+                reg_name(output),
+                table_row.0
+            )?;
+            fmt_window(reg_name(input), input_offset as u64, f)?;
+            write!(f, ", mode {mode})")
+        }
+        DecodedInstr::Unknown { opcode, operand } => match JitOp::from_opcode(opcode) {
+            Some(op) => write!(f, "{} {operand:#x}", op.mnemonic()),
+            None => write!(f, "amx{opcode} {operand:#x}"),
+        },
+    }
+}
+
+/// Format a `matint` or `matfp` instruction.
+fn fmt_mat(
+    mnemonic: &str,
+    ty: &str,
+    options: &MatOptions,
+    (x_offset, y_offset, z_row): (XBytes, YBytes, ZRow),
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(
+        f,
+        "{mnemonic}.{ty} z{} {} ",
+        z_row.0,
+        assign(options.accumulate, options.subtract)
+    )?;
+    fmt_product(Some(x_offset), Some(y_offset), f)?;
+    if options.widen {
+        f.write_str(" (wide)")?;
+    }
+    if options.x_lanes != 0 {
+        write!(f, " (x_lanes={})", options.x_lanes)?;
+    }
+    if options.y_lanes != 0 {
+        write!(f, " (y_lanes={})", options.y_lanes)?;
+    }
+    Ok(())
+}
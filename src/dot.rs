@@ -0,0 +1,78 @@
+//! Dot products with the horizontal reduction performed on AMX
+use crate::{Amx, VecFpAluOp, VecFpTy, XBytes, XRow, YBytes, YRow, ZRow, linalg::F32_LANES};
+
+/// See [`Amx::dot_f32_batch`].
+pub(crate) fn dot_f32_batch(
+    ctx: &mut (impl Amx + ?Sized),
+    pairs: &[(&[f32], &[f32])],
+    out: &mut [f32],
+) {
+    assert_eq!(pairs.len(), out.len(), "length mismatch in `out`");
+    for (i, (x, y)) in pairs.iter().enumerate() {
+        assert_eq!(x.len(), y.len(), "length mismatch in pair {i}");
+    }
+
+    let mut xb = [0f32; F32_LANES];
+    let mut yb = [0f32; F32_LANES];
+    // Each pair is accumulated in its own row of Z
+    for (pairs, out) in pairs.chunks(64).zip(out.chunks_mut(64)) {
+        for (p, (x, y)) in pairs.iter().enumerate() {
+            if x.is_empty() {
+                // Safety: Reading 64 bytes from `[f32; 16]`
+                unsafe { ctx.load512([0f32; F32_LANES].as_ptr(), ZRow(p)) };
+                continue;
+            }
+            let (x_row, y_row) = (XRow(p % 8), YRow(p % 8));
+            for (c, (x, y)) in x.chunks(F32_LANES).zip(y.chunks(F32_LANES)).enumerate() {
+                // Safety: Reading 64 bytes from each `[f32; 16]` or full chunk
+                unsafe {
+                    if x.len() == F32_LANES {
+                        ctx.load512(x.as_ptr(), x_row);
+                        ctx.load512(y.as_ptr(), y_row);
+                    } else {
+                        xb[..x.len()].copy_from_slice(x);
+                        xb[x.len()..].fill(0.0);
+                        yb[..y.len()].copy_from_slice(y);
+                        yb[y.len()..].fill(0.0);
+                        ctx.load512(xb.as_ptr(), x_row);
+                        ctx.load512(yb.as_ptr(), y_row);
+                    }
+                }
+                ctx.fma_vector_f32(
+                    Some(XBytes(x_row.0 * 64)),
+                    Some(YBytes(y_row.0 * 64)),
+                    ZRow(p),
+                    c != 0,
+                );
+            }
+        }
+
+        for (p, out) in out.iter_mut().enumerate() {
+            *out = reduce_row_f32(ctx, ZRow(p));
+        }
+    }
+}
+
+/// Sum the lanes of `z[z_index]: [f32; 16]` by folding the row in half four
+/// times. Each fold copies the row to X and adds its upper half to the lower
+/// half. Clobbers `x[0]` and `z[z_index]`.
+fn reduce_row_f32(ctx: &mut (impl Amx + ?Sized), z_index: ZRow) -> f32 {
+    let mut width = F32_LANES;
+    while width > 1 {
+        width /= 2;
+        ctx.extract_z_to_x(z_index, XBytes(0));
+        // `z[i] += x[i + width]`. The upper lanes are discarded.
+        ctx.vector_fp(
+            VecFpTy::F32,
+            VecFpAluOp::Add,
+            XBytes(width * 4),
+            YBytes(0),
+            z_index,
+        );
+    }
+
+    let mut out = [0f32; F32_LANES];
+    // Safety: Writing 64 bytes to `[f32; 16]`
+    unsafe { ctx.store512(out.as_mut_ptr(), z_index) };
+    out[0]
+}
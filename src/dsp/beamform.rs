@@ -0,0 +1,154 @@
+//! Array processing (beamforming) kernels
+use crate::{
+    Amx, ZRow,
+    linalg::{self, F32_LANES, MatMut, MatRef, SplitComplex, pack_row, read_tile_row, write_row},
+    pipeline::Pipeline,
+};
+
+/// Estimate the spatial covariance matrix
+/// `R = sum(x[t] * x[t]ᴴ for t in 0..T)` (or `R += ...` if `accumulate` is
+/// set) of `T` snapshots of an `M`-channel array.
+///
+/// `snapshots` is `T`×`M` (one snapshot per row), and `r` is `M`×`M`. Only
+/// the tiles on or above the diagonal are calculated on AMX; the rest is
+/// filled in by Hermitian symmetry. The result is not normalized by `T`.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn spatial_covariance(
+    ctx: &mut (impl Amx + ?Sized),
+    snapshots: SplitComplex<MatRef<'_, f32>>,
+    mut r: SplitComplex<MatMut<'_, f32>>,
+    accumulate: bool,
+) {
+    let (t, m) = (snapshots.re.rows(), snapshots.re.cols());
+    assert_eq!(
+        (snapshots.im.rows(), snapshots.im.cols()),
+        (t, m),
+        "shape mismatch in `snapshots`"
+    );
+    assert_eq!((r.re.rows(), r.re.cols()), (m, m), "shape mismatch in `r`");
+    assert_eq!((r.im.rows(), r.im.cols()), (m, m), "shape mismatch in `r`");
+    if t == 0 {
+        if !accumulate {
+            for i in 0..m {
+                r.re.row_mut(i).fill(0.0);
+                r.im.row_mut(i).fill(0.0);
+            }
+        }
+        return;
+    }
+
+    // `a_pack[tt]` = `[Xr[tt, i0..], Xi[tt, i0..], -Xr[tt, i0..]]`
+    let mut a_pack = vec![[[0f32; F32_LANES]; 3]; t];
+    // `b_pack[tt]` = `[Xr[tt, j0..], Xi[tt, j0..]]`
+    let mut b_pack = vec![[[0f32; F32_LANES]; 2]; t];
+
+    for i0 in (0..m).step_by(F32_LANES) {
+        let mr = (m - i0).min(F32_LANES);
+        for (tt, a_pack) in a_pack.iter_mut().enumerate() {
+            pack_row(&mut a_pack[0], &snapshots.re.row(tt)[i0..i0 + mr]);
+            pack_row(&mut a_pack[1], &snapshots.im.row(tt)[i0..i0 + mr]);
+            a_pack[2] = a_pack[0].map(|x| -x);
+        }
+
+        for j0 in (i0..m).step_by(F32_LANES) {
+            let nr = (m - j0).min(F32_LANES);
+            for (tt, b_pack) in b_pack.iter_mut().enumerate() {
+                pack_row(&mut b_pack[0], &snapshots.re.row(tt)[j0..j0 + nr]);
+                pack_row(&mut b_pack[1], &snapshots.im.row(tt)[j0..j0 + nr]);
+            }
+
+            Pipeline::new(2, 2, 3).run(
+                ctx,
+                t,
+                |ctx, tt, slot| {
+                    // Safety: Reading 64 bytes from each `[f32; 16]`
+                    unsafe {
+                        for (k, a_pack) in a_pack[tt].iter().enumerate() {
+                            ctx.load512(a_pack.as_ptr(), slot.y_row(k));
+                        }
+                        for (k, b_pack) in b_pack[tt].iter().enumerate() {
+                            ctx.load512(b_pack.as_ptr(), slot.x_row(k));
+                        }
+                    }
+                },
+                |ctx, tt, slot| {
+                    let (br, bi) = (slot.x_bytes(0), slot.x_bytes(1));
+                    let (ar, ai, neg_ar) = (slot.y_bytes(0), slot.y_bytes(1), slot.y_bytes(2));
+                    let first = tt == 0;
+                    // re += Ar * Br + Ai * Bi
+                    ctx.outer_product_f32_xy_to_z(Some(br), Some(ar), ZRow(0), !first);
+                    ctx.outer_product_f32_xy_to_z(Some(bi), Some(ai), ZRow(0), true);
+                    // im += Ai * Br - Ar * Bi
+                    ctx.outer_product_f32_xy_to_z(Some(br), Some(ai), ZRow(1), !first);
+                    ctx.outer_product_f32_xy_to_z(Some(bi), Some(neg_ar), ZRow(1), true);
+                },
+            );
+
+            for ii in 0..mr {
+                let re = read_tile_row(ctx, 0, ii);
+                let im = read_tile_row(ctx, 1, ii);
+                write_row(&mut r.re.row_mut(i0 + ii)[j0..j0 + nr], &re, accumulate);
+                write_row(&mut r.im.row_mut(i0 + ii)[j0..j0 + nr], &im, accumulate);
+                if j0 == i0 {
+                    continue;
+                }
+                // Mirror the tile below the diagonal
+                for jj in 0..nr {
+                    let (dst_re, dst_im) = (
+                        &mut r.re.row_mut(j0 + jj)[i0 + ii],
+                        &mut r.im.row_mut(j0 + jj)[i0 + ii],
+                    );
+                    if accumulate {
+                        *dst_re += re[jj];
+                        *dst_im -= im[jj];
+                    } else {
+                        (*dst_re, *dst_im) = (re[jj], -im[jj]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Apply `B` beamforming weight vectors to `T` snapshots of an `M`-channel
+/// array: `out[t][b] = weights[.., b]ᴴ * x[t]` (or `+=` if `accumulate` is
+/// set).
+///
+/// `snapshots` is `T`×`M` (one snapshot per row), `weights` is `M`×`B` (one
+/// weight vector per column), and `out` is `T`×`B`. This is a complex
+/// matrix multiplication by the conjugated weights, calculated by
+/// [`cgemm`](linalg::cgemm).
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn apply_beamforming_weights(
+    ctx: &mut (impl Amx + ?Sized),
+    snapshots: SplitComplex<MatRef<'_, f32>>,
+    weights: SplitComplex<MatRef<'_, f32>>,
+    out: SplitComplex<MatMut<'_, f32>>,
+    accumulate: bool,
+) {
+    let (m, b) = (weights.re.rows(), weights.re.cols());
+    assert_eq!(
+        (weights.im.rows(), weights.im.cols()),
+        (m, b),
+        "shape mismatch in `weights`"
+    );
+    let neg_im: Vec<f32> = (0..m)
+        .flat_map(|i| weights.im.row(i).iter().map(|&x| -x))
+        .collect();
+    linalg::cgemm(
+        ctx,
+        snapshots,
+        SplitComplex {
+            re: weights.re,
+            im: MatRef::new(&neg_im, m, b),
+        },
+        out,
+        accumulate,
+    );
+}
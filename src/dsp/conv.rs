@@ -0,0 +1,51 @@
+//! Real single-precision convolution
+use crate::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, fma32_vector},
+};
+
+/// Calculate the linear convolution
+/// `out[l] = sum(x[l + k] * h[h.len() - 1 - k] for k in 0..h.len())` of
+/// real signals, keeping only the outputs for which `h` fully overlaps `x`.
+///
+/// `x.len()` must be at least `out.len() + h.len() - 1`, and `h` must not be
+/// empty.
+///
+/// This overwrites `x[0]`, `y[0]`, and `z[0]`.
+pub fn conv_f32(ctx: &mut (impl Amx + ?Sized), x: &[f32], h: &[f32], out: &mut [f32]) {
+    assert!(!h.is_empty(), "`h` must not be empty");
+    assert!(
+        x.len() + 1 >= out.len() + h.len(),
+        "`x` is too short for the requested number of outputs"
+    );
+    if out.is_empty() {
+        return;
+    }
+
+    // `x`, padded so that every 16-sample window is readable
+    let padded_len = out.len() + h.len() - 1 + F32_LANES;
+    let mut xs = vec![0f32; padded_len];
+    let len = x.len().min(padded_len);
+    xs[..len].copy_from_slice(&x[..len]);
+
+    // `[h[h.len() - 1 - k]; 16]` for each `k`
+    let hb: Vec<[f32; F32_LANES]> = h.iter().rev().map(|&h| [h; F32_LANES]).collect();
+
+    for l0 in (0..out.len()).step_by(F32_LANES) {
+        let nr = (out.len() - l0).min(F32_LANES);
+        for (k, hb) in hb.iter().enumerate() {
+            // Safety: `xs` is padded to allow reading 16 samples at any
+            // offset; `[f32; 16]` is 64 bytes long
+            unsafe {
+                ctx.load512(xs[l0 + k..].as_ptr(), XRow(0));
+                ctx.load512(hb.as_ptr(), YRow(0));
+            }
+            fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(0), k != 0, false);
+        }
+
+        let mut z = [0f32; F32_LANES];
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(z.as_mut_ptr(), ZRow(0)) };
+        out[l0..l0 + nr].copy_from_slice(&z[..nr]);
+    }
+}
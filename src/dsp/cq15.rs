@@ -0,0 +1,227 @@
+//! Complex Q15 (interleaved 16-bit I/Q) kernels
+use super::Rounding;
+use crate::{Amx, Mac16Operand, XBytes, XRow, YBytes, YRow, ZRow, linalg::MatMut};
+
+/// A complex number in Q15 fixed-point format, stored as an interleaved I/Q
+/// pair.
+///
+/// This matches the sample format produced by most SDR front-ends.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Cq15 {
+    /// The in-phase (real) component
+    pub re: i16,
+    /// The quadrature (imaginary) component
+    pub im: i16,
+}
+
+impl Cq15 {
+    /// Construct a `Cq15`.
+    #[inline]
+    pub const fn new(re: i16, im: i16) -> Self {
+        Self { re, im }
+    }
+
+    /// Convert a Q30 (or Q`30 - headroom`) complex accumulator to Q15.
+    #[inline]
+    fn from_wide(re: i64, im: i64, shift: u32, rounding: Rounding) -> Self {
+        let narrow = |x: i64| {
+            rounding
+                .shift_right(x, shift)
+                .clamp(i16::MIN as i64, i16::MAX as i64) as i16
+        };
+        Self::new(narrow(re), narrow(im))
+    }
+}
+
+/// The number of complex samples in a register row
+const LANES: usize = 16;
+
+/// Issue `mac16` with 32-bit accumulators in `z`.
+///
+/// The products are shifted right by `shift` bits before accumulation.
+#[inline(always)]
+fn mac16_wide(
+    ctx: &mut (impl Amx + ?Sized),
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+    shift: u32,
+    vector: bool,
+    accumulate: bool,
+) {
+    let operand = Mac16Operand::new()
+        .x_offset(Some(x_offset_bytes))
+        .y_offset(Some(y_offset_bytes))
+        .z_row(z_index)
+        .accumulate(accumulate)
+        .widen(true) // 32-bit `z`
+        .shift(shift)
+        .vector(vector);
+    ctx.mac16(operand.encode());
+}
+
+/// Calculate the complex outer product `out[j][i] = x[i] * y[j]` (or
+/// `x[i] * conj(y[j])` if `conj_y` is set) of Q15 vectors.
+///
+/// The four partial products of each complex multiplication are accumulated
+/// exactly in 32-bit `z` lanes, so the only rounding step is the final
+/// narrowing to Q15 specified by `rounding`. `out` must be a
+/// `y.len()`×`x.len()` matrix.
+///
+/// This overwrites `x[0]`, `y[0]`, and the entire contents of `z`.
+pub fn cq15_outer_product(
+    ctx: &mut (impl Amx + ?Sized),
+    x: &[Cq15],
+    y: &[Cq15],
+    mut out: MatMut<'_, Cq15>,
+    conj_y: bool,
+    rounding: Rounding,
+) {
+    assert_eq!(
+        (out.rows(), out.cols()),
+        (y.len(), x.len()),
+        "output shape mismatch"
+    );
+
+    for j0 in (0..y.len()).step_by(LANES) {
+        let nr = (y.len() - j0).min(LANES);
+        let mut y_row = [Cq15::default(); LANES];
+        y_row[..nr].copy_from_slice(&y[j0..j0 + nr]);
+        // Safety: Reading 64 bytes from `[Cq15; 16]`
+        unsafe { ctx.load512(y_row.as_ptr(), YRow(0)) };
+
+        for i0 in (0..x.len()).step_by(LANES) {
+            let mr = (x.len() - i0).min(LANES);
+            let mut x_row = [Cq15::default(); LANES];
+            x_row[..mr].copy_from_slice(&x[i0..i0 + mr]);
+            // Safety: Reading 64 bytes from `[Cq15; 16]`
+            unsafe { ctx.load512(x_row.as_ptr(), XRow(0)) };
+
+            // Treating the rows as `[i16; 32]`, this computes every product
+            // of a real or imaginary part of `x` and that of `y`. The
+            // product of `x[i]` and `y[j]` lands in `z[j * 4..][..4][i]`
+            // as `[xr*yr, xi*yr, xr*yi, xi*yi]`.
+            mac16_wide(ctx, XBytes(0), YBytes(0), ZRow(0), 0, false, false);
+
+            for jj in 0..nr {
+                let mut z = [[0i32; LANES]; 4];
+                for (k, z) in z.iter_mut().enumerate() {
+                    // Safety: Writing 64 bytes to `[i32; 16]`
+                    unsafe { ctx.store512(z.as_mut_ptr(), ZRow(jj * 4 + k)) };
+                }
+                let [rr, ir, ri, ii] = z;
+                let out_row = &mut out.row_mut(j0 + jj)[i0..i0 + mr];
+                for (p, out) in out_row.iter_mut().enumerate() {
+                    let (rr, ir, ri, ii) = (rr[p] as i64, ir[p] as i64, ri[p] as i64, ii[p] as i64);
+                    let (re, im) = if conj_y {
+                        (rr + ii, ir - ri)
+                    } else {
+                        (rr - ii, ir + ri)
+                    };
+                    *out = Cq15::from_wide(re, im, 15, rounding);
+                }
+            }
+        }
+    }
+}
+
+/// Calculate the complex cross-correlation
+/// `out[l] = sum(x[l + k] * h[k] for k in 0..h.len())` (with `h[k]` replaced
+/// by `conj(h[k])` if `conj_h` is set) of Q15 signals.
+///
+/// The products are accumulated in 32-bit `z` lanes after being shifted
+/// right by `ceil(log2(h.len()))` bits, which guarantees the accumulation
+/// never overflows. The result is then narrowed to Q15 as specified by
+/// `rounding`.
+///
+/// `x.len()` must be at least `out.len() + h.len() - 1`, and `h.len()` must
+/// be in range `1..=32768`.
+///
+/// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..4]`.
+pub fn cq15_xcorr(
+    ctx: &mut (impl Amx + ?Sized),
+    x: &[Cq15],
+    h: &[Cq15],
+    out: &mut [Cq15],
+    conj_h: bool,
+    rounding: Rounding,
+) {
+    assert!(
+        (1..=32768).contains(&h.len()),
+        "`h.len()` must be in range `1..=32768`"
+    );
+    assert!(
+        x.len() + 1 >= out.len() + h.len(),
+        "`x` is too short for the requested number of lags"
+    );
+    if out.is_empty() {
+        return;
+    }
+
+    let headroom = h.len().next_power_of_two().trailing_zeros();
+
+    // `x` and the same with its real and imaginary parts swapped, padded so
+    // that every 16-sample window is readable
+    let padded_len = out.len() + h.len() - 1 + LANES;
+    let mut xs = vec![Cq15::default(); padded_len];
+    xs[..x.len().min(padded_len)].copy_from_slice(&x[..x.len().min(padded_len)]);
+    let xs_swapped: Vec<Cq15> = xs.iter().map(|c| Cq15::new(c.im, c.re)).collect();
+
+    // `[re; 32]` and `[im; 32]` for each tap
+    let hb: Vec<[[i16; LANES * 2]; 2]> = h
+        .iter()
+        .map(|c| [[c.re; LANES * 2], [c.im; LANES * 2]])
+        .collect();
+
+    for l0 in (0..out.len()).step_by(LANES) {
+        let nr = (out.len() - l0).min(LANES);
+        for (k, hb) in hb.iter().enumerate() {
+            // Safety: `xs` and `xs_swapped` are padded to allow reading 16
+            // samples at any lag; each `[i16; 32]` is 64 bytes long
+            unsafe {
+                ctx.load512(xs[l0 + k..].as_ptr(), XRow(0));
+                ctx.load512(xs_swapped[l0 + k..].as_ptr(), XRow(1));
+                ctx.load512(hb[0].as_ptr(), YRow(0));
+                ctx.load512(hb[1].as_ptr(), YRow(1));
+            }
+            let accumulate = k != 0;
+            // z[0] += [xr*hr], z[1] += [xi*hr]
+            mac16_wide(
+                ctx,
+                XBytes(0),
+                YBytes(0),
+                ZRow(0),
+                headroom,
+                true,
+                accumulate,
+            );
+            // z[2] += [xi*hi], z[3] += [xr*hi]
+            mac16_wide(
+                ctx,
+                XBytes(64),
+                YBytes(64),
+                ZRow(2),
+                headroom,
+                true,
+                accumulate,
+            );
+        }
+
+        let mut z = [[0i32; LANES]; 4];
+        for (k, z) in z.iter_mut().enumerate() {
+            // Safety: Writing 64 bytes to `[i32; 16]`
+            unsafe { ctx.store512(z.as_mut_ptr(), ZRow(k)) };
+        }
+        let [rr, ir, ii, ri] = z;
+        for (p, out) in out[l0..l0 + nr].iter_mut().enumerate() {
+            let (rr, ir, ii, ri) = (rr[p] as i64, ir[p] as i64, ii[p] as i64, ri[p] as i64);
+            let (re, im) = if conj_h {
+                (rr + ii, ir - ri)
+            } else {
+                (rr - ii, ir + ri)
+            };
+            *out = Cq15::from_wide(re, im, 15 - headroom, rounding);
+        }
+    }
+}
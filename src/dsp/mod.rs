@@ -0,0 +1,35 @@
+//! Signal processing kernels built on top of [`Amx`](crate::Amx)
+mod beamform;
+mod conv;
+mod cq15;
+mod ofdm;
+mod partitioned;
+mod quantize;
+mod resample;
+mod viterbi;
+pub use self::{
+    beamform::*, conv::*, cq15::*, ofdm::*, partitioned::*, quantize::*, resample::*, viterbi::*,
+};
+
+/// Specifies how a fixed-point result is narrowed to its output precision.
+///
+/// In both cases, values outside the output type's range saturate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rounding {
+    /// Discard the fractional bits (round toward negative infinity).
+    Truncate,
+    /// Round to the nearest representable value, with ties rounded up.
+    Nearest,
+}
+
+impl Rounding {
+    /// Shift `value` right by `shift` bits according to `self`.
+    #[inline]
+    pub(crate) fn shift_right(self, value: i64, shift: u32) -> i64 {
+        match self {
+            Self::Truncate => value >> shift,
+            Self::Nearest if shift > 0 => (value + (1 << (shift - 1))) >> shift,
+            Self::Nearest => value,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! OFDM frequency-domain equalization
+use crate::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, MatMut, MatRef, SplitComplex, fma32_vector, pack_row},
+};
+
+/// Apply per-subcarrier complex equalizer taps to a block of OFDM symbols:
+/// `out[s][k] = symbols[s][k] * taps[k]`.
+///
+/// `symbols` and `out` are `S`×`K` (one symbol per row, after the FFT), and
+/// `taps` has `K` elements. The equalizer taps are loaded once per 16
+/// subcarriers and applied to every symbol by vector-mode `fma32`/`fms32`.
+///
+/// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..16]`.
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent.
+pub fn ofdm_equalize(
+    ctx: &mut (impl Amx + ?Sized),
+    symbols: SplitComplex<MatRef<'_, f32>>,
+    taps: SplitComplex<&[f32]>,
+    mut out: SplitComplex<MatMut<'_, f32>>,
+) {
+    let (s, k) = (symbols.re.rows(), symbols.re.cols());
+    assert_eq!(
+        (symbols.im.rows(), symbols.im.cols()),
+        (s, k),
+        "shape mismatch in `symbols`"
+    );
+    assert_eq!(
+        (taps.re.len(), taps.im.len()),
+        (k, k),
+        "shape mismatch in `taps`"
+    );
+    assert_eq!(
+        (out.re.rows(), out.re.cols()),
+        (s, k),
+        "shape mismatch in `out`"
+    );
+    assert_eq!(
+        (out.im.rows(), out.im.cols()),
+        (s, k),
+        "shape mismatch in `out`"
+    );
+
+    let mut pack = [[0f32; F32_LANES]; 2];
+    for k0 in (0..k).step_by(F32_LANES) {
+        let nr = (k - k0).min(F32_LANES);
+        pack_row(&mut pack[0], &taps.re[k0..k0 + nr]);
+        pack_row(&mut pack[1], &taps.im[k0..k0 + nr]);
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            ctx.load512(pack[0].as_ptr(), YRow(0));
+            ctx.load512(pack[1].as_ptr(), YRow(1));
+        }
+
+        for si in 0..s {
+            pack_row(&mut pack[0], &symbols.re.row(si)[k0..k0 + nr]);
+            pack_row(&mut pack[1], &symbols.im.row(si)[k0..k0 + nr]);
+            // Safety: Reading 64 bytes from each `[f32; 16]`
+            unsafe {
+                ctx.load512(pack[0].as_ptr(), XRow(0));
+                ctx.load512(pack[1].as_ptr(), XRow(1));
+            }
+
+            // Rotate through the Z rows to avoid stalling on the dependency
+            // between consecutive symbols
+            let (z_re, z_im) = (ZRow(si % 8 * 2), ZRow(si % 8 * 2 + 1));
+            // re = xr * hr - xi * hi; im = xr * hi + xi * hr
+            fma32_vector(ctx, XBytes(0), YBytes(0), z_re, false, false);
+            fma32_vector(ctx, XBytes(64), YBytes(64), z_re, true, true);
+            fma32_vector(ctx, XBytes(0), YBytes(64), z_im, false, false);
+            fma32_vector(ctx, XBytes(64), YBytes(0), z_im, true, false);
+
+            // Safety: Writing 64 bytes to each `[f32; 16]`
+            unsafe {
+                ctx.store512(pack[0].as_mut_ptr(), z_re);
+                ctx.store512(pack[1].as_mut_ptr(), z_im);
+            }
+            out.re.row_mut(si)[k0..k0 + nr].copy_from_slice(&pack[0][..nr]);
+            out.im.row_mut(si)[k0..k0 + nr].copy_from_slice(&pack[1][..nr]);
+        }
+    }
+}
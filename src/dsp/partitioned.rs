@@ -0,0 +1,286 @@
+//! Uniformly partitioned convolution for long impulse responses
+use std::f64::consts::PI;
+
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow, linalg::F32_LANES, linalg::fma32_vector};
+
+/// A streaming convolution engine for long real impulse responses (e.g.,
+/// convolution reverb or room correction) using uniformly partitioned
+/// overlap-save.
+///
+/// The impulse response is split into partitions of `block_size` samples,
+/// each transformed to the frequency domain once. For every block of input,
+/// the spectra of the last `num_partitions` input blocks are multiplied by
+/// the partitions' spectra and summed. These complex multiply-accumulates,
+/// which dominate the cost for long impulse responses, run on AMX using
+/// vector-mode `fma32`/`fms32`; the FFTs are calculated on the CPU.
+///
+/// The output is delayed by `block_size` samples relative to the exact
+/// convolution.
+#[derive(Debug, Clone)]
+pub struct PartitionedConvolver {
+    block: usize,
+    fft: Fft,
+    /// The number of stored frequency bins (`block + 1` rounded up to a
+    /// multiple of 16)
+    bins: usize,
+    /// The spectra of the partitions, each stored as `[re; bins]` followed
+    /// by `[im; bins]`
+    filter: Vec<f32>,
+    /// The spectra of the last `num_partitions` input blocks in the same
+    /// layout, used as a ring buffer
+    history: Vec<f32>,
+    /// The index of the newest spectrum in `history`
+    head: usize,
+    /// The previous input block followed by the current one
+    input: Vec<f32>,
+    /// The output block being read out
+    output: Vec<f32>,
+    /// The number of samples in the current input block
+    fill: usize,
+    /// Scratch buffers for the FFT
+    scratch: SplitBuf,
+}
+
+#[derive(Debug, Clone)]
+struct SplitBuf {
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl PartitionedConvolver {
+    /// Construct a `PartitionedConvolver` for the impulse response `ir`
+    /// with the specified block size.
+    ///
+    /// Smaller blocks reduce the latency at the cost of more partitions to
+    /// process per output sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ir` is empty or `block_size` is not a power of two.
+    #[track_caller]
+    pub fn new(ir: &[f32], block_size: usize) -> Self {
+        assert!(!ir.is_empty(), "`ir` must not be empty");
+        assert!(
+            block_size.is_power_of_two(),
+            "`block_size` must be a power of two"
+        );
+        let block = block_size;
+        let bins = (block + 1).div_ceil(F32_LANES) * F32_LANES;
+        let num_partitions = ir.len().div_ceil(block);
+        let fft = Fft::new(2 * block);
+        let mut scratch = SplitBuf {
+            re: vec![0.0; 2 * block],
+            im: vec![0.0; 2 * block],
+        };
+
+        let mut filter = vec![0.0; num_partitions * 2 * bins];
+        for (part, spectrum) in ir.chunks(block).zip(filter.chunks_exact_mut(2 * bins)) {
+            scratch.re.fill(0.0);
+            scratch.re[..part.len()].copy_from_slice(part);
+            fft.forward_real(&mut scratch, spectrum);
+        }
+
+        Self {
+            block,
+            fft,
+            bins,
+            filter,
+            history: vec![0.0; num_partitions * 2 * bins],
+            head: 0,
+            input: vec![0.0; 2 * block],
+            output: vec![0.0; block],
+            fill: 0,
+            scratch,
+        }
+    }
+
+    /// Get the block size.
+    pub fn block_size(&self) -> usize {
+        self.block
+    }
+
+    /// Get the number of partitions of the impulse response.
+    pub fn num_partitions(&self) -> usize {
+        self.history.len() / (2 * self.bins)
+    }
+
+    /// Get the delay of the output in samples, which equals the block size.
+    pub fn latency(&self) -> usize {
+        self.block
+    }
+
+    /// Clear the internal state as if no samples have been processed.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.input.fill(0.0);
+        self.output.fill(0.0);
+        self.head = 0;
+        self.fill = 0;
+    }
+
+    /// Feed `input` to the convolver and write the same number of output
+    /// samples to `output`:
+    /// `output[n] = sum(ir[k] * x[n - latency - k] for k in 0..ir.len())`,
+    /// where `x` is the concatenation of all input fed so far.
+    ///
+    /// The input can be split into arbitrary chunks without affecting the
+    /// output.
+    ///
+    /// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..2]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` and `output` have different lengths.
+    #[track_caller]
+    pub fn process(&mut self, ctx: &mut (impl Amx + ?Sized), input: &[f32], output: &mut [f32]) {
+        assert_eq!(input.len(), output.len(), "length mismatch");
+        let (mut input, mut output) = (input, output);
+        while !input.is_empty() {
+            let n = (self.block - self.fill).min(input.len());
+            let range = self.fill..self.fill + n;
+            output[..n].copy_from_slice(&self.output[range.clone()]);
+            self.input[self.block..][range].copy_from_slice(&input[..n]);
+            self.fill += n;
+            (input, output) = (&input[n..], &mut output[n..]);
+
+            if self.fill == self.block {
+                self.process_block(ctx);
+                self.fill = 0;
+            }
+        }
+    }
+
+    fn process_block(&mut self, ctx: &mut (impl Amx + ?Sized)) {
+        let (block, bins) = (self.block, self.bins);
+        let num_partitions = self.num_partitions();
+
+        // Transform the last two input blocks into the ring buffer
+        self.head = (self.head + 1) % num_partitions;
+        self.scratch.re.copy_from_slice(&self.input);
+        self.fft.forward_real(
+            &mut self.scratch,
+            &mut self.history[self.head * 2 * bins..][..2 * bins],
+        );
+        self.input.copy_within(block.., 0);
+
+        // Multiply-accumulate the spectra 16 bins at a time
+        let mut acc = vec![0f32; 2 * bins];
+        for c in (0..bins).step_by(F32_LANES) {
+            for p in 0..num_partitions {
+                let x =
+                    &self.history[(self.head + num_partitions - p) % num_partitions * 2 * bins..];
+                let h = &self.filter[p * 2 * bins..];
+                // Safety: Reading 64 bytes from each 16-element slice
+                unsafe {
+                    ctx.load512(x[c..][..F32_LANES].as_ptr(), XRow(0));
+                    ctx.load512(x[bins + c..][..F32_LANES].as_ptr(), XRow(1));
+                    ctx.load512(h[c..][..F32_LANES].as_ptr(), YRow(0));
+                    ctx.load512(h[bins + c..][..F32_LANES].as_ptr(), YRow(1));
+                }
+                // re += xr * hr - xi * hi; im += xr * hi + xi * hr
+                let first = p == 0;
+                fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(0), !first, false);
+                fma32_vector(ctx, XBytes(64), YBytes(64), ZRow(0), true, true);
+                fma32_vector(ctx, XBytes(0), YBytes(64), ZRow(1), !first, false);
+                fma32_vector(ctx, XBytes(64), YBytes(0), ZRow(1), true, false);
+            }
+            // Safety: Writing 64 bytes to each 16-element slice
+            unsafe {
+                ctx.store512(acc[c..][..F32_LANES].as_mut_ptr(), ZRow(0));
+                ctx.store512(acc[bins + c..][..F32_LANES].as_mut_ptr(), ZRow(1));
+            }
+        }
+
+        // The second half of the inverse transform is free of circular
+        // aliasing
+        self.fft.inverse_real(&mut self.scratch, &acc);
+        self.output.copy_from_slice(&self.scratch.re[block..]);
+    }
+}
+
+/// An iterative radix-2 complex FFT
+#[derive(Debug, Clone)]
+struct Fft {
+    /// `exp(-2πik/n)` for `k` in `0..n/2`
+    twiddles: Vec<(f32, f32)>,
+}
+
+impl Fft {
+    fn new(n: usize) -> Self {
+        debug_assert!(n.is_power_of_two());
+        let twiddles = (0..n / 2)
+            .map(|k| {
+                let (sin, cos) = (-2.0 * PI * k as f64 / n as f64).sin_cos();
+                (cos as f32, sin as f32)
+            })
+            .collect();
+        Self { twiddles }
+    }
+
+    fn len(&self) -> usize {
+        self.twiddles.len() * 2
+    }
+
+    /// Transform `buf` in place, conjugating the twiddle factors if `inverse`
+    /// is set. The result is not normalized.
+    fn transform(&self, buf: &mut SplitBuf, inverse: bool) {
+        let n = self.len();
+        if n < 2 {
+            return;
+        }
+        let bits = n.trailing_zeros();
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if i < j {
+                buf.re.swap(i, j);
+                buf.im.swap(i, j);
+            }
+        }
+
+        let mut half = 1;
+        while half < n {
+            let stride = n / (2 * half);
+            for start in (0..n).step_by(2 * half) {
+                for k in 0..half {
+                    let (wr, wi) = self.twiddles[k * stride];
+                    let wi = if inverse { -wi } else { wi };
+                    let (a, b) = (start + k, start + k + half);
+                    let tr = buf.re[b] * wr - buf.im[b] * wi;
+                    let ti = buf.re[b] * wi + buf.im[b] * wr;
+                    buf.re[b] = buf.re[a] - tr;
+                    buf.im[b] = buf.im[a] - ti;
+                    buf.re[a] += tr;
+                    buf.im[a] += ti;
+                }
+            }
+            half *= 2;
+        }
+    }
+
+    /// Transform the real signal in `buf.re` and store the non-negative
+    /// frequency bins to `out` as `[re; bins]` followed by `[im; bins]`.
+    fn forward_real(&self, buf: &mut SplitBuf, out: &mut [f32]) {
+        let bins = out.len() / 2;
+        buf.im.fill(0.0);
+        self.transform(buf, false);
+        let num = self.len() / 2 + 1;
+        out.fill(0.0);
+        out[..num].copy_from_slice(&buf.re[..num]);
+        out[bins..][..num].copy_from_slice(&buf.im[..num]);
+    }
+
+    /// Inverse-transform the spectrum of a real signal stored by
+    /// [`Self::forward_real`] and store the result to `buf.re`.
+    fn inverse_real(&self, buf: &mut SplitBuf, spectrum: &[f32]) {
+        let (n, bins) = (self.len(), spectrum.len() / 2);
+        for k in 0..n {
+            // Reconstruct the negative frequencies by Hermitian symmetry
+            let (kk, sign) = if k <= n / 2 { (k, 1.0) } else { (n - k, -1.0) };
+            buf.re[k] = spectrum[kk];
+            buf.im[k] = spectrum[bins + kk] * sign;
+        }
+        self.transform(buf, true);
+        let scale = (n as f32).recip();
+        buf.re.iter_mut().for_each(|x| *x *= scale);
+    }
+}
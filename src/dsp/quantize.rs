@@ -0,0 +1,20 @@
+//! Conversion from floating-point to fixed-point samples
+
+/// Quantize `x` to 16-bit integers: `out[i] = round(x[i] / scale)`.
+///
+/// Ties are rounded away from zero, values outside the range of `i16`
+/// saturate, and NaN is converted to zero. The result is suitable as input
+/// to the `i16` kernels, e.g., [`cq15_xcorr`](super::cq15_xcorr) with
+/// `scale = 2^-15`.
+///
+/// # Panics
+///
+/// Panics if `x` and `out` have different lengths.
+pub fn quantize_i16(x: &[f32], scale: f32, out: &mut [i16]) {
+    assert_eq!(x.len(), out.len(), "length mismatch");
+    let inv_scale = scale.recip();
+    for (out, &x) in out.iter_mut().zip(x) {
+        // `as` saturates and maps NaN to zero
+        *out = (x * inv_scale).round() as i16;
+    }
+}
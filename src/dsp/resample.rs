@@ -0,0 +1,231 @@
+//! Polyphase sample-rate conversion
+use std::f64::consts::PI;
+
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow, linalg::F32_LANES, linalg::fma32_vector};
+
+/// The maximum number of output samples computed concurrently, each in its
+/// own Z row to avoid stalling on the accumulation dependency
+const BATCH: usize = 16;
+
+/// The Kaiser window's `β` parameter of the prototype filter, giving a
+/// stopband attenuation of about 90 dB
+const KAISER_BETA: f64 = 8.6;
+
+/// The passband edge of the prototype filter relative to the lower of the
+/// two Nyquist frequencies
+const PASSBAND: f64 = 0.9;
+
+/// A streaming polyphase resampler converting real `f32` signals by a
+/// rational factor, e.g., from 44.1 kHz to 48 kHz.
+///
+/// Each output sample is the dot product of a window of the input with one
+/// phase of a Kaiser-windowed sinc lowpass filter, calculated by vector-mode
+/// `fma32`. Up to 16 output samples are computed at once.
+///
+/// # Example
+///
+/// ```rust
+/// use amx::{AmxEmuCtx, dsp::Resampler};
+/// let mut ctx = AmxEmuCtx::default();
+/// let mut resampler = Resampler::new(44_100, 48_000, 32);
+/// let mut out = Vec::new();
+/// resampler.process(&mut ctx, &[0.0; 441], &mut out);
+/// assert_eq!(out.len(), 480);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    up: usize,
+    down: usize,
+    /// The number of taps per phase (a multiple of 16)
+    taps: usize,
+    /// `bank[p * taps + j] = h[(taps - 1 - j) * up + p]` where `h` is the
+    /// prototype filter, so that each phase is applied as a forward dot
+    /// product
+    bank: Vec<f32>,
+    /// The input samples not yet consumed, preceded by the last `taps - 1`
+    /// consumed ones
+    history: Vec<f32>,
+    /// The phase of the next output sample
+    phase: usize,
+    /// The number of input samples to drop before the next window, which
+    /// exceeded `history` when the last call stepped past its end
+    skip: usize,
+}
+
+impl Resampler {
+    /// Construct a `Resampler` converting from `from_rate` to `to_rate`
+    /// (e.g., in Hz) using `taps_per_phase` filter taps per output sample,
+    /// rounded up to a multiple of 16.
+    ///
+    /// More taps give a sharper transition band at a proportional cost. 32
+    /// is adequate for most audio applications.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the parameters is zero.
+    #[track_caller]
+    pub fn new(from_rate: usize, to_rate: usize, taps_per_phase: usize) -> Self {
+        assert!(
+            from_rate > 0 && to_rate > 0,
+            "sample rates must be positive"
+        );
+        assert!(taps_per_phase > 0, "`taps_per_phase` must be positive");
+        let g = gcd(from_rate, to_rate);
+        let (up, down) = (to_rate / g, from_rate / g);
+        let taps = taps_per_phase.div_ceil(F32_LANES) * F32_LANES;
+
+        // Design the prototype filter at `up` times the input rate
+        let len = up * taps;
+        let cutoff = PASSBAND * 0.5 / up.max(down) as f64;
+        let center = (len - 1) as f64 / 2.0;
+        let i0_beta = bessel_i0(KAISER_BETA);
+        let h: Vec<f64> = (0..len)
+            .map(|i| {
+                let t = i as f64 - center;
+                let r = if len > 1 { t / center } else { 0.0 };
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).max(0.0).sqrt()) / i0_beta;
+                // Scaled by `up` to compensate for the zero-stuffing
+                2.0 * cutoff * sinc(2.0 * cutoff * t) * window * up as f64
+            })
+            .collect();
+
+        let bank = (0..up)
+            .flat_map(|p| (0..taps).map(move |j| (taps - 1 - j) * up + p))
+            .map(|i| h[i] as f32)
+            .collect();
+
+        Self {
+            up,
+            down,
+            taps,
+            bank,
+            history: vec![0.0; taps - 1],
+            phase: 0,
+            skip: 0,
+        }
+    }
+
+    /// Get the conversion ratio `(up, down)` in lowest terms. `up` output
+    /// samples are produced for every `down` input samples.
+    pub fn ratio(&self) -> (usize, usize) {
+        (self.up, self.down)
+    }
+
+    /// Get the number of filter taps per output sample.
+    pub fn taps_per_phase(&self) -> usize {
+        self.taps
+    }
+
+    /// Get the group delay of the filter in input samples.
+    pub fn delay(&self) -> f64 {
+        (self.up * self.taps - 1) as f64 / (2 * self.up) as f64
+    }
+
+    /// Clear the internal state as if no samples have been processed.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.history.resize(self.taps - 1, 0.0);
+        self.phase = 0;
+        self.skip = 0;
+    }
+
+    /// Feed `input` to the resampler and append the resulting output
+    /// samples to `output`.
+    ///
+    /// The output sample `o` (counted from the construction or the last
+    /// [`reset`](Self::reset)) corresponds to the input time
+    /// `o * down / up - self.delay()`. The input can be split into
+    /// arbitrary chunks without affecting the output.
+    ///
+    /// This overwrites `x[0]`, `y[0]`, and `z[0..16]`.
+    pub fn process(&mut self, ctx: &mut (impl Amx + ?Sized), input: &[f32], output: &mut Vec<f32>) {
+        self.history.extend_from_slice(input);
+        let skip = self.skip.min(self.history.len());
+        self.history.drain(..skip);
+        self.skip -= skip;
+        if self.skip > 0 {
+            return;
+        }
+
+        // The window start and phase of each output sample in the batch
+        let mut batch = [(0usize, 0usize); BATCH];
+        let mut pos = 0;
+        loop {
+            let mut len = 0;
+            while len < BATCH && pos + self.taps <= self.history.len() {
+                batch[len] = (pos, self.phase);
+                self.phase += self.down;
+                pos += self.phase / self.up;
+                self.phase %= self.up;
+                len += 1;
+            }
+            if len == 0 {
+                break;
+            }
+            self.compute_batch(ctx, &batch[..len], output);
+        }
+
+        // With a large `down / up`, the next window may start past the end
+        // of the input
+        let consumed = pos.min(self.history.len());
+        self.history.drain(..consumed);
+        self.skip = pos - consumed;
+    }
+
+    fn compute_batch(
+        &self,
+        ctx: &mut (impl Amx + ?Sized),
+        batch: &[(usize, usize)],
+        output: &mut Vec<f32>,
+    ) {
+        for c in (0..self.taps).step_by(F32_LANES) {
+            for (i, &(pos, phase)) in batch.iter().enumerate() {
+                let x = &self.history[pos + c..pos + c + F32_LANES];
+                let h = &self.bank[phase * self.taps + c..][..F32_LANES];
+                // Safety: Reading 64 bytes from each 16-element slice
+                unsafe {
+                    ctx.load512(x.as_ptr(), XRow(0));
+                    ctx.load512(h.as_ptr(), YRow(0));
+                }
+                fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(i), c != 0, false);
+            }
+        }
+
+        let mut z = [0f32; F32_LANES];
+        for i in 0..batch.len() {
+            // Safety: Writing 64 bytes to `[f32; 16]`
+            unsafe { ctx.store512(z.as_mut_ptr(), ZRow(i)) };
+            output.push(z.iter().sum());
+        }
+    }
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The normalized sinc function `sin(πx) / (πx)`
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// The zeroth-order modified Bessel function of the first kind
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for k in 1..50 {
+        term *= (x / (2 * k) as f64).powi(2);
+        sum += term;
+        if term < sum * 1e-17 {
+            break;
+        }
+    }
+    sum
+}
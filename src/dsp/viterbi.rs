@@ -0,0 +1,96 @@
+//! Branch metrics for Viterbi decoding
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow, linalg::MatMut};
+
+/// The number of `i16` lanes in a register row
+const LANES: usize = 32;
+
+/// Calculate the branch metrics of a rate-`1/rate` convolutional code for
+/// every possible code symbol: `metrics[t][c] = sum(soft[t * rate + j] *
+/// s(c, j) for j in 0..rate)`, where `s(c, j)` is `-1` if bit `j` of `c` is
+/// set and `+1` otherwise.
+///
+/// `soft` holds `rate` soft bits per trellis step (positive values favoring
+/// bit `0`), and `metrics` must be `T`×`2^rate` where
+/// `T = soft.len() / rate`. A larger metric indicates a more likely code
+/// symbol, so the result can be fed directly to an add-compare-select
+/// stage.
+///
+/// The correlations against all code symbols are calculated for 32 trellis
+/// steps at once by `rate` 16-bit outer products with 32-bit accumulation,
+/// which cannot overflow.
+///
+/// This overwrites `x[0..rate]`, `y[0..rate]`, and the entire contents of
+/// `z`.
+///
+/// # Panics
+///
+/// Panics if `rate` is not in range `1..=5`, `soft.len()` is not a multiple
+/// of `rate`, or `metrics` has a wrong shape.
+pub fn branch_metrics(
+    ctx: &mut (impl Amx + ?Sized),
+    soft: &[i16],
+    rate: usize,
+    mut metrics: MatMut<'_, i32>,
+) {
+    assert!((1..=5).contains(&rate), "`rate` must be in range `1..=5`");
+    assert_eq!(
+        soft.len() % rate,
+        0,
+        "`soft.len()` must be a multiple of `rate`"
+    );
+    let (steps, num_symbols) = (soft.len() / rate, 1 << rate);
+    assert_eq!(
+        (metrics.rows(), metrics.cols()),
+        (steps, num_symbols),
+        "output shape mismatch"
+    );
+
+    // `y[j][c] = s(c, j)`
+    for j in 0..rate {
+        let y_row: [i16; LANES] = std::array::from_fn(|c| {
+            if c >= num_symbols {
+                0
+            } else if c >> j & 1 != 0 {
+                -1
+            } else {
+                1
+            }
+        });
+        // Safety: Reading 64 bytes from `[i16; 32]`
+        unsafe { ctx.load512(y_row.as_ptr(), YRow(j)) };
+    }
+
+    for t0 in (0..steps).step_by(LANES) {
+        let mr = (steps - t0).min(LANES);
+        // `x[j][tt] = soft[(t0 + tt) * rate + j]`
+        for j in 0..rate {
+            let mut x_row = [0i16; LANES];
+            for (tt, x) in x_row[..mr].iter_mut().enumerate() {
+                *x = soft[(t0 + tt) * rate + j];
+            }
+            // Safety: Reading 64 bytes from `[i16; 32]`
+            unsafe { ctx.load512(x_row.as_ptr(), XRow(j)) };
+        }
+
+        for j in 0..rate {
+            ctx.outer_product_i16_xy_to_z_i32(
+                Some(XBytes(j * 64)),
+                Some(YBytes(j * 64)),
+                ZRow(0),
+                j != 0,
+            );
+        }
+
+        // `metrics[t0 + tt][c]` is in `z[c * 2 + tt % 2][tt / 2]`
+        for c in 0..num_symbols {
+            let mut z = [[0i32; LANES / 2]; 2];
+            for (k, z) in z.iter_mut().enumerate() {
+                // Safety: Writing 64 bytes to `[i32; 16]`
+                unsafe { ctx.store512(z.as_mut_ptr(), ZRow(c * 2 + k)) };
+            }
+            for tt in 0..mr {
+                metrics.row_mut(t0 + tt)[c] = z[tt % 2][tt / 2];
+            }
+        }
+    }
+}
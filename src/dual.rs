@@ -0,0 +1,214 @@
+//! Differential execution on two backends (requires the `dual` feature)
+use std::fmt;
+
+use crate::{
+    Amx, AmxEmuCtx, AmxVersion, Instr, RowDiff, jit::JitOp, minimize::diff_registers, ops::AmxOps,
+};
+
+/// What [`DualCtx`] does when the backends' register contents diverge
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OnDivergence {
+    /// Panic with a description of the divergence.
+    Panic,
+    /// Record the divergence, to be retrieved by
+    /// [`DualCtx::take_divergences`], and copy the first backend's register
+    /// contents to the second backend so that the subsequent instructions
+    /// start from the same state.
+    Record,
+}
+
+/// A divergence detected by [`DualCtx`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DualDivergence {
+    /// The instruction after which the register contents differ
+    pub instr: Instr,
+    /// The register rows that differ. [`RowDiff::left`] is the first
+    /// backend's contents.
+    pub diffs: Vec<RowDiff>,
+}
+
+impl fmt::Display for DualDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` diverged in", self.instr)?;
+        for diff in &self.diffs {
+            let reg = format!("{:?}", diff.file).to_lowercase();
+            let bytes: Vec<usize> = diff.differing_bytes().collect();
+            write!(f, " {reg}{} (bytes {bytes:?})", diff.row)?;
+        }
+        Ok(())
+    }
+}
+
+/// An AMX context executing every instruction on two backends and comparing
+/// their register contents after each instruction, e.g., to find emulator
+/// bugs and undocumented hardware behavior.
+///
+/// On a supported system, [`DualCtx::new`] pairs [`AmxCtx`](crate::AmxCtx)
+/// with [`AmxEmuCtx`]. Any two backends can be paired by
+/// [`DualCtx::with_backends`].
+///
+/// Store instructions are executed only on the first backend, whose output
+/// is what the memory receives. They don't modify the registers, so the
+/// comparison still covers them.
+///
+/// ```rust
+/// use amx::{Amx, AmxEmuCtx, DualCtx, OnDivergence, XRow, jit::JitOp};
+///
+/// let mut ctx = DualCtx::with_backends(AmxEmuCtx::default(), AmxEmuCtx::default())
+///     .on_divergence(OnDivergence::Record);
+/// ctx.load512_slice(&[1u8; 64], XRow(0));
+/// ctx.right_mut().x_mut()[0] = 2; // Make the backends diverge
+/// ctx.load512_slice(&[3u8; 64], XRow(1));
+///
+/// let divergences = ctx.take_divergences();
+/// assert_eq!(divergences.len(), 1);
+/// assert_eq!(divergences[0].instr.op, JitOp::Ldx);
+/// assert_eq!(divergences[0].diffs[0].row, 0);
+/// ```
+///
+/// Comparing the register contents takes 80 stores on each backend, so
+/// every instruction is much slower than usual.
+#[derive(Debug)]
+pub struct DualCtx<A, B = AmxEmuCtx> {
+    left: A,
+    right: B,
+    on_divergence: OnDivergence,
+    divergences: Vec<DualDivergence>,
+}
+
+#[cfg(any(doc, target_arch = "aarch64"))]
+impl DualCtx<crate::AmxCtx, AmxEmuCtx> {
+    /// Construct a `DualCtx` pairing a new [`AmxCtx`](crate::AmxCtx) with a
+    /// new [`AmxEmuCtx`].
+    pub fn new() -> Result<Self, crate::NewAmxCtxError> {
+        Ok(Self::with_backends(
+            crate::AmxCtx::new()?,
+            AmxEmuCtx::default(),
+        ))
+    }
+}
+
+impl<A: AmxOps, B: AmxOps> DualCtx<A, B> {
+    /// Construct a `DualCtx` pairing `left` and `right`. `left`'s register
+    /// contents are copied to `right`.
+    ///
+    /// Divergences cause a panic by default. See [`Self::on_divergence`].
+    pub fn with_backends(mut left: A, mut right: B) -> Self {
+        right.restore(&left.snapshot());
+        Self {
+            left,
+            right,
+            on_divergence: OnDivergence::Panic,
+            divergences: Vec::new(),
+        }
+    }
+
+    /// Set what to do when the backends diverge.
+    pub fn on_divergence(self, on_divergence: OnDivergence) -> Self {
+        Self {
+            on_divergence,
+            ..self
+        }
+    }
+
+    /// Get the first backend.
+    pub fn left(&self) -> &A {
+        &self.left
+    }
+
+    /// Get the second backend.
+    pub fn right(&self) -> &B {
+        &self.right
+    }
+
+    /// Get the first backend mutably. Instructions executed on it directly
+    /// are not compared.
+    pub fn left_mut(&mut self) -> &mut A {
+        &mut self.left
+    }
+
+    /// Get the second backend mutably. Instructions executed on it directly
+    /// are not compared.
+    pub fn right_mut(&mut self) -> &mut B {
+        &mut self.right
+    }
+
+    /// Remove and return the divergences recorded so far.
+    pub fn take_divergences(&mut self) -> Vec<DualDivergence> {
+        std::mem::take(&mut self.divergences)
+    }
+
+    /// Get the backends.
+    pub fn into_inner(self) -> (A, B) {
+        (self.left, self.right)
+    }
+
+    /// Compare the register contents after executing an instruction.
+    fn check(&mut self, op: JitOp, operand: u64, ptr: *mut ()) {
+        let diffs = diff_registers(&mut self.left, &mut self.right);
+        if diffs.is_empty() {
+            return;
+        }
+        let addr = op.is_mem().then_some(ptr as usize);
+        let divergence = DualDivergence {
+            instr: Instr { op, operand, addr },
+            diffs,
+        };
+        match self.on_divergence {
+            OnDivergence::Panic => panic!("{divergence}"),
+            OnDivergence::Record => {
+                self.divergences.push(divergence);
+                self.right.restore(&self.left.snapshot());
+            }
+        }
+    }
+}
+
+/// Implement [`AmxOps`] for [`DualCtx`] by executing each instruction on
+/// both backends, except stores.
+macro_rules! impl_dual_ops {
+    (
+        load: $($load_name:ident => $load_op:ident),*;
+        store: $($store_name:ident),*;
+        $($name:ident => $op:ident),* $(,)?
+    ) => {
+        unsafe impl<A: AmxOps, B: AmxOps> AmxOps for DualCtx<A, B> {
+            $(
+                unsafe fn $load_name(&mut self, x: u64, ptr: *mut ()) {
+                    // Safety: Upheld by the caller
+                    unsafe {
+                        self.left.$load_name(x, ptr);
+                        self.right.$load_name(x, ptr);
+                    }
+                    self.check(JitOp::$load_op, x, ptr);
+                }
+            )*
+            $(
+                unsafe fn $store_name(&mut self, x: u64, ptr: *mut ()) {
+                    // Safety: Upheld by the caller
+                    unsafe { self.left.$store_name(x, ptr) }
+                }
+            )*
+            $(
+                fn $name(&mut self, x: u64) {
+                    self.left.$name(x);
+                    self.right.$name(x);
+                    self.check(JitOp::$op, x, std::ptr::null_mut());
+                }
+            )*
+
+            /// Get the older of the backends' AMX revisions.
+            fn version(&self) -> AmxVersion {
+                self.left.version().min(self.right.version())
+            }
+        }
+    };
+}
+
+impl_dual_ops! {
+    load: ldx => Ldx, ldy => Ldy, ldz => Ldz, ldzi => Ldzi;
+    store: stx, sty, stz, stzi;
+    extrx => Extrx, extry => Extry, fma64 => Fma64, fms64 => Fms64, fma32 => Fma32,
+    fms32 => Fms32, mac16 => Mac16, fma16 => Fma16, fms16 => Fms16, vecint => Vecint,
+    vecfp => Vecfp, matint => Matint, matfp => Matfp, genlut => Genlut,
+}
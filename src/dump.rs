@@ -0,0 +1,147 @@
+//! Formatted register dumps for debugging
+use std::fmt;
+
+use crate::{AmxElement, AmxState, RegFile, fp16::F16Bits};
+
+/// The element type in which [`AmxDump`] renders register rows
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DumpFormat {
+    /// Bytes in hexadecimal
+    #[default]
+    Hex,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    /// IEEE 754 binary16
+    F16,
+    /// bfloat16
+    BF16,
+    F32,
+    F64,
+}
+
+impl DumpFormat {
+    /// Render the elements of `row`.
+    fn cells(self, row: &[u8; 64]) -> Vec<String> {
+        macro_rules! cells {
+            ($ty:ty, $f:expr) => {
+                <$ty>::row_from_bytes(row).into_iter().map($f).collect()
+            };
+        }
+        match self {
+            Self::Hex => cells!(u8, |x| format!("{x:02x}")),
+            Self::U8 => cells!(u8, |x| x.to_string()),
+            Self::I8 => cells!(i8, |x| x.to_string()),
+            Self::U16 => cells!(u16, |x| x.to_string()),
+            Self::I16 => cells!(i16, |x| x.to_string()),
+            Self::U32 => cells!(u32, |x| x.to_string()),
+            Self::I32 => cells!(i32, |x| x.to_string()),
+            Self::U64 => cells!(u64, |x| x.to_string()),
+            Self::I64 => cells!(i64, |x| x.to_string()),
+            Self::F16 => cells!(F16Bits, |x| format!("{:?}", x.to_f32())),
+            Self::BF16 => cells!(u16, |x| format!("{:?}", f32::from_bits(u32::from(x) << 16))),
+            Self::F32 => cells!(f32, |x| format!("{x:?}")),
+            Self::F64 => cells!(f64, |x| format!("{x:?}")),
+        }
+    }
+}
+
+/// A copy of the register contents rendered as matrices by its
+/// [`Display`](fmt::Display) implementation, returned by
+/// [`Amx::dump`](crate::Amx::dump)
+///
+/// Each line shows one register row, with the elements right-aligned in
+/// columns of the same width within a register file.
+///
+/// ```rust
+/// use amx::{Amx, DumpFormat, RegFile, YRow};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// ctx.load512_slice(&[-1i16; 32], YRow(1));
+/// let dump = ctx.dump().format(DumpFormat::I16);
+/// let y = dump.file(RegFile::Y).to_string();
+/// assert!(y.starts_with("y0:  0  0"));
+/// assert_eq!(y.lines().nth(1).unwrap(), format!("y1:{}", " -1".repeat(32)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmxDump {
+    state: AmxState,
+    format: DumpFormat,
+}
+
+impl AmxDump {
+    /// Construct an `AmxDump` of `state`, rendered in [`DumpFormat::Hex`].
+    pub fn new(state: AmxState) -> Self {
+        Self {
+            state,
+            format: DumpFormat::Hex,
+        }
+    }
+
+    /// Render the rows as elements of `format`.
+    pub fn format(mut self, format: DumpFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Get a [`Display`](fmt::Display)able view of the register file `file`
+    /// only.
+    pub fn file(&self, file: RegFile) -> RegFileDump<'_> {
+        RegFileDump { dump: self, file }
+    }
+
+    /// Get the dumped register contents.
+    pub fn state(&self) -> &AmxState {
+        &self.state
+    }
+}
+
+impl fmt::Display for AmxDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, file) in [RegFile::X, RegFile::Y, RegFile::Z].into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.file(file))?;
+        }
+        Ok(())
+    }
+}
+
+/// A register file of an [`AmxDump`], returned by [`AmxDump::file`]
+#[derive(Debug, Copy, Clone)]
+pub struct RegFileDump<'a> {
+    dump: &'a AmxDump,
+    file: RegFile,
+}
+
+impl fmt::Display for RegFileDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = &self.dump.state;
+        let (name, bytes) = match self.file {
+            RegFile::X => ('x', state.x()),
+            RegFile::Y => ('y', state.y()),
+            RegFile::Z => ('z', state.z().as_flattened()),
+        };
+        let rows: Vec<Vec<String>> = bytes
+            .chunks_exact(64)
+            .map(|row| self.dump.format.cells(row.try_into().unwrap()))
+            .collect();
+        let width = rows.iter().flatten().map(String::len).max().unwrap_or(0);
+        let label_width = format!("{name}{}", rows.len().saturating_sub(1)).len() + 1;
+
+        for (i, row) in rows.iter().enumerate() {
+            write!(f, "{:<label_width$}", format!("{name}{i}:"))?;
+            for cell in row {
+                write!(f, " {cell:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
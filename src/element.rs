@@ -0,0 +1,80 @@
+//! Typed views of the register sets
+use crate::fp16::F16Bits;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An element type of the register rows, which are viewed as
+/// [`Self::Row`] (`[Self; 64 / size_of::<Self>()]`) by
+/// [`Amx::read_x_as`](crate::Amx::read_x_as),
+/// [`Amx::read_y_as`](crate::Amx::read_y_as), and
+/// [`Amx::read_z_as`](crate::Amx::read_z_as).
+///
+/// Every bit pattern of the element types is a valid value, so register rows
+/// can also be stored to slices of them (see
+/// [`Amx::store512_slice`](crate::Amx::store512_slice)).
+///
+/// This trait is sealed and implemented for `u8`, `i8`, `u16`, `i16`, `u32`,
+/// `i32`, `u64`, `i64`, [`F16Bits`], `f32`, and `f64`. With the `half`
+/// feature, it's also implemented for `half::f16` and `half::bf16`, which
+/// saves bit-casting `u16` buffers in half-precision code.
+pub trait AmxElement: sealed::Sealed + Copy {
+    /// A register row viewed as an array of `Self`
+    type Row: Copy;
+
+    /// Convert a 64-byte register row to [`Self::Row`].
+    fn row_from_bytes(bytes: &[u8; 64]) -> Self::Row;
+}
+
+/// The `x` register set viewed as rows of `T`
+pub type XRegs<T> = [<T as AmxElement>::Row; 8];
+
+/// The `y` register set viewed as rows of `T`
+pub type YRegs<T> = [<T as AmxElement>::Row; 8];
+
+/// The `z` register set viewed as rows of `T`
+pub type ZRegs<T> = [<T as AmxElement>::Row; 64];
+
+macro_rules! impl_element {
+    ($($ty:ty => $size:literal, $from_bytes:expr;)*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl AmxElement for $ty {
+            type Row = [$ty; 64 / $size];
+
+            #[inline]
+            fn row_from_bytes(bytes: &[u8; 64]) -> Self::Row {
+                std::array::from_fn(|i| {
+                    $from_bytes(bytes[i * $size..][..$size].try_into().unwrap())
+                })
+            }
+        }
+    )*};
+}
+
+impl_element! {
+    u8 => 1, u8::from_ne_bytes;
+    i8 => 1, i8::from_ne_bytes;
+    u16 => 2, u16::from_ne_bytes;
+    i16 => 2, i16::from_ne_bytes;
+    u32 => 4, u32::from_ne_bytes;
+    i32 => 4, i32::from_ne_bytes;
+    u64 => 8, u64::from_ne_bytes;
+    i64 => 8, i64::from_ne_bytes;
+    F16Bits => 2, |b| F16Bits(u16::from_ne_bytes(b));
+    f32 => 4, f32::from_ne_bytes;
+    f64 => 8, f64::from_ne_bytes;
+}
+
+#[cfg(feature = "half")]
+impl_element! {
+    half::f16 => 2, half::f16::from_ne_bytes;
+    half::bf16 => 2, half::bf16::from_ne_bytes;
+}
+
+/// Convert the rows of a register set in `bytes` to `[T::Row; N]`.
+#[inline]
+pub(crate) fn regs_from_bytes<T: AmxElement, const N: usize>(bytes: &[u8]) -> [T::Row; N] {
+    std::array::from_fn(|i| T::row_from_bytes(bytes[i * 64..][..64].try_into().unwrap()))
+}
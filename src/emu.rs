@@ -0,0 +1,1296 @@
+//! Software emulation of AMX instructions
+//!
+//! The emulated register file can be resized via [`AmxEmuGeometry`] to
+//! model hypothetical AMX revisions. Row indices and byte offsets taken from
+//! instruction operands wrap around the configured register file, and the
+//! interleaved Z layout produced by outer products spreads over all Z rows
+//! (e.g., a `f32` outer product writes every eighth row if Z has 128 rows).
+//!
+//! Floating-point multiply-adds are fused (rounded once), as on the
+//! hardware, so that the results are bit-identical to those of
+//! [`AmxCtx`](crate::AmxCtx). The handling of subnormals and NaNs is
+//! configured by [`FpSemantics`]. The operand modes introduced by later AMX
+//! revisions are enabled by [`AmxEmuCtx::set_version`].
+//!
+//! Every instruction is emulated, which covers all operands produced by the
+//! methods of [`Amx`](crate::Amx), and the integration tests run on
+//! `AmxEmuCtx` on targets other than AArch64. The ALU modes (bits 47–52) of
+//! `vecint`, `vecfp`, `matint`, and `matfp`, as described in
+//! <https://github.com/corsix/amx>, are emulated as follows, where the
+//! integer products and the addends of modes 11 and 12 are shifted right by
+//! the shift field of `vecint`:
+//!
+//! | Mode | Operation          | Instructions          | Since |
+//! |------|--------------------|-----------------------|-------|
+//! | 0    | `z + x * y`        | all                   | M1    |
+//! | 1    | `z - x * y`        | all                   | M1    |
+//! | 4    | `x > 0 ? y : 0`    | all                   | M1    |
+//! | 5    | `min(x, z)`        | `vecint`, `vecfp`     | M1    |
+//! | 7    | `max(x, z)`        | `vecint`, `vecfp`     | M1    |
+//! | 10   | `x * y`            | all                   | M2    |
+//! | 11   | `z + x`            | all                   | M2    |
+//! | 12   | `z + y`            | all                   | M2    |
+//!
+//! The floating-point modes 5 and 7 behave like AArch64's `FMIN` and `FMAX`,
+//! so NaN operands are handled as configured by [`FpSemantics`].
+//!
+//! Only modes 0 and 1 are used by the methods of [`Amx`](crate::Amx), and
+//! the others haven't been verified on the hardware. The remaining modes are
+//! reserved and panic when executed, as do the M2 modes unless enabled by
+//! [`AmxEmuCtx::set_version`].
+use crate::fp16::{f16_to_f32, f64_to_bf16, f64_to_f16};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut, Range},
+};
+
+use crate::{AmxVersion, jit::JitOp, ops::AmxOps};
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod trace;
+pub use self::trace::{RowChange, TraceEntry};
+
+/// The size of the register file emulated by [`AmxEmuCtx`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmxEmuGeometry {
+    /// The number of 64-byte rows in `x`
+    pub x_rows: usize,
+    /// The number of 64-byte rows in `y`
+    pub y_rows: usize,
+    /// The number of 64-byte rows in `z`
+    pub z_rows: usize,
+}
+
+impl AmxEmuGeometry {
+    /// The register file found in the existing processors (8 X rows, 8 Y
+    /// rows, and 64 Z rows).
+    pub const M1: Self = Self {
+        x_rows: 8,
+        y_rows: 8,
+        z_rows: 64,
+    };
+}
+
+impl Default for AmxEmuGeometry {
+    #[inline]
+    fn default() -> Self {
+        Self::M1
+    }
+}
+
+/// The register file of an emulated AMX context
+///
+/// With the `serde` feature, `AmxState` implements `Serialize` and
+/// `Deserialize`, e.g., to save [`Amx::snapshot`](crate::Amx::snapshot)'s
+/// output as a golden test vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmxState {
+    geometry: AmxEmuGeometry,
+    x: Vec<u8>,
+    y: Vec<u8>,
+    z: Vec<[u8; 64]>,
+}
+
+impl AmxState {
+    /// Construct an `AmxState` with the specified register file size. All
+    /// registers are initialized to zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x_rows` or `y_rows` is zero or `z_rows` is not a non-zero
+    /// multiple of 64.
+    #[track_caller]
+    pub fn new(geometry: AmxEmuGeometry) -> Self {
+        assert!(
+            geometry.x_rows > 0 && geometry.y_rows > 0,
+            "`x_rows` and `y_rows` must be non-zero"
+        );
+        assert!(
+            geometry.z_rows > 0 && geometry.z_rows.is_multiple_of(64),
+            "`z_rows` must be a non-zero multiple of 64"
+        );
+        Self {
+            geometry,
+            x: vec![0; geometry.x_rows * 64],
+            y: vec![0; geometry.y_rows * 64],
+            z: vec![[0; 64]; geometry.z_rows],
+        }
+    }
+
+    /// Get the size of the register file.
+    #[inline]
+    pub fn geometry(&self) -> AmxEmuGeometry {
+        self.geometry
+    }
+
+    /// Get the contents of `x`.
+    #[inline]
+    pub fn x(&self) -> &[u8] {
+        &self.x
+    }
+
+    /// Get the contents of `y`.
+    #[inline]
+    pub fn y(&self) -> &[u8] {
+        &self.y
+    }
+
+    /// Get the contents of `z`.
+    #[inline]
+    pub fn z(&self) -> &[[u8; 64]] {
+        &self.z
+    }
+
+    /// Get the mutable contents of `x`.
+    #[inline]
+    pub fn x_mut(&mut self) -> &mut [u8] {
+        &mut self.x
+    }
+
+    /// Get the mutable contents of `y`.
+    #[inline]
+    pub fn y_mut(&mut self) -> &mut [u8] {
+        &mut self.y
+    }
+
+    /// Get the mutable contents of `z`.
+    #[inline]
+    pub fn z_mut(&mut self) -> &mut [[u8; 64]] {
+        &mut self.z
+    }
+}
+
+/// The floating-point semantics of [`AmxEmuCtx`], corresponding to the
+/// `FZ` and `DN` bits of the AArch64 `FPCR` register
+///
+/// The default is IEEE 754 arithmetic with subnormals, where a NaN result
+/// propagates an input NaN as on AArch64: the first signaling NaN, or
+/// otherwise the first quiet NaN, in the order Z, X, Y, quietened. A NaN
+/// produced by an invalid operation (e.g., `0 * inf`) is the positive
+/// default NaN.
+///
+/// Which semantics a given processor implements for AMX instructions can be
+/// checked by running the same program on it and on the emulator with
+/// `DualCtx` (requires the `dual` feature).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FpSemantics {
+    /// Treat subnormal inputs as zero and replace subnormal results with
+    /// zero of the same sign.
+    pub flush_subnormals: bool,
+    /// Replace every NaN result with the default NaN instead of propagating
+    /// an input NaN.
+    pub default_nan: bool,
+}
+
+impl FpSemantics {
+    /// IEEE 754 arithmetic with subnormals and NaN propagation
+    pub const IEEE: Self = Self {
+        flush_subnormals: false,
+        default_nan: false,
+    };
+}
+
+/// An instruction executed by [`AmxEmuCtx`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Instr {
+    /// The opcode
+    pub op: JitOp,
+    /// The operand register's value, excluding the pointer of a memory
+    /// operation
+    pub operand: u64,
+    /// The address accessed by a memory operation
+    pub addr: Option<usize>,
+}
+
+/// A function called by [`AmxEmuCtx`] after executing each instruction.
+pub type AmxEmuHook = Box<dyn FnMut(&Instr, &AmxState) + Send>;
+
+/// An emulated AMX context. Implements [`AmxOps`] and, by extension,
+/// [`Amx`](crate::Amx).
+///
+/// `AmxEmuCtx` derefs to [`AmxState`], through which the registers can be
+/// inspected and modified.
+pub struct AmxEmuCtx {
+    state: AmxState,
+    hook: Option<AmxEmuHook>,
+    /// The memory regions accessible by load/store instructions. `None`
+    /// disables the check.
+    sandbox: Option<Vec<Range<usize>>>,
+    /// The recorded instructions. `None` disables tracing.
+    trace: Option<Vec<TraceEntry>>,
+    fp: FpSemantics,
+    version: AmxVersion,
+}
+
+impl Default for AmxEmuCtx {
+    fn default() -> Self {
+        Self::new(AmxEmuGeometry::M1)
+    }
+}
+
+impl fmt::Debug for AmxEmuCtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AmxEmuCtx")
+            .field("state", &self.state)
+            .field("hook", &self.hook.as_ref().map(|_| ..))
+            .field("sandbox", &self.sandbox)
+            .field("trace", &self.trace)
+            .field("fp", &self.fp)
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+impl AmxEmuCtx {
+    /// Construct an `AmxEmuCtx` with the specified register file size. All
+    /// registers are initialized to zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x_rows` or `y_rows` is zero or `z_rows` is not a non-zero
+    /// multiple of 64.
+    #[track_caller]
+    pub fn new(geometry: AmxEmuGeometry) -> Self {
+        Self {
+            state: AmxState::new(geometry),
+            hook: None,
+            sandbox: None,
+            trace: None,
+            fp: FpSemantics::IEEE,
+            version: AmxVersion::M1,
+        }
+    }
+
+    /// Set the emulated AMX revision, which is reported by
+    /// [`AmxOps::version`] and enables the operand modes introduced by it.
+    /// The default is [`AmxVersion::M1`].
+    #[inline]
+    pub fn set_version(&mut self, version: AmxVersion) {
+        self.version = version;
+    }
+
+    /// Get the floating-point semantics.
+    #[inline]
+    pub fn fp_semantics(&self) -> FpSemantics {
+        self.fp
+    }
+
+    /// Set the floating-point semantics of the subsequently executed
+    /// instructions.
+    ///
+    /// ```rust
+    /// use amx::{Amx, FpSemantics, XBytes, XRow, YBytes, YRow, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let x = [f32::MIN_POSITIVE; 16];
+    /// ctx.load512_slice(&x, XRow(0));
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), None, ZRow(0), false);
+    /// assert_eq!(ctx.read_z_f32()[0], x);
+    ///
+    /// // `MIN_POSITIVE - MIN_POSITIVE * 0.5` is subnormal
+    /// ctx.load512_slice(&[0.5f32; 16], YRow(0));
+    /// ctx.set_fp_semantics(FpSemantics {
+    ///     flush_subnormals: true,
+    ///     ..FpSemantics::IEEE
+    /// });
+    /// ctx.vector_fp(
+    ///     amx::VecFpTy::F32,
+    ///     amx::VecFpAluOp::MulSub,
+    ///     XBytes(0),
+    ///     YBytes(0),
+    ///     ZRow(0),
+    /// );
+    /// assert_eq!(ctx.read_z_f32()[0], [0.0; 16]);
+    /// ```
+    #[inline]
+    pub fn set_fp_semantics(&mut self, fp: FpSemantics) {
+        self.fp = fp;
+    }
+
+    /// Set a function to be called after executing each instruction,
+    /// replacing the existing one.
+    ///
+    /// The hook receives the executed instruction and the resulting register
+    /// contents, which is useful for, e.g., coverage tracking and invariant
+    /// checking.
+    pub fn set_hook(&mut self, hook: impl FnMut(&Instr, &AmxState) + Send + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Remove and return the hook set by [`Self::set_hook`].
+    pub fn take_hook(&mut self) -> Option<AmxEmuHook> {
+        self.hook.take()
+    }
+
+    /// Allow load/store instructions to access `buffer`, enabling the
+    /// address sandbox if it's not enabled yet.
+    ///
+    /// While the address sandbox is enabled, a load/store instruction
+    /// accessing memory outside of the registered buffers causes a panic
+    /// before the access takes place.
+    pub fn register_buffer<T>(&mut self, buffer: &[T]) {
+        let range = buffer.as_ptr_range();
+        self.register_region(range.start as usize..range.end as usize);
+    }
+
+    /// Like [`Self::register_buffer`], but takes a range of addresses.
+    pub fn register_region(&mut self, region: Range<usize>) {
+        self.sandbox.get_or_insert_with(Vec::new).push(region);
+    }
+
+    /// Disable the address sandbox and forget the registered buffers.
+    pub fn disable_sandbox(&mut self) {
+        self.sandbox = None;
+    }
+
+    /// Start recording every executed instruction, its decoded operand, and
+    /// the register rows it modifies. The recorded [`TraceEntry`]s are
+    /// retrieved by [`Self::take_trace`].
+    ///
+    /// ```rust
+    /// use amx::{Amx, XBytes, YBytes, ZRow, jit::JitOp};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.load512_slice(&[1.0f32; 16], amx::XRow(0));
+    /// ctx.enable_trace();
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), None, ZRow(0), false);
+    ///
+    /// let trace = ctx.take_trace();
+    /// assert_eq!(trace.len(), 1);
+    /// assert_eq!(trace[0].instr.op, JitOp::Fma32);
+    /// assert!(trace[0].fields.contains(&("skip_y", 1)));
+    /// // Writes every fourth row of Z
+    /// assert_eq!(trace[0].changes.len(), 16);
+    /// ```
+    ///
+    /// Each instruction copies the register file while tracing is
+    /// enabled, so tracing slows down the emulation considerably.
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    /// Stop recording instructions and discard the recorded ones.
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    /// Remove and return the instructions recorded since tracing was enabled
+    /// or this method was last called. Tracing stays enabled.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Check the memory access by a load/store instruction against the
+    /// address sandbox.
+    #[inline]
+    fn check_access(&self, op: JitOp, operand: u64, ptr: *mut ()) {
+        let Some(sandbox) = &self.sandbox else { return };
+        let len = match op {
+            JitOp::Ldzi | JitOp::Stzi => 64,
+            _ => mem_operand(operand).1 * 64,
+        };
+        let access = ptr as usize..(ptr as usize).wrapping_add(len);
+        if !sandbox
+            .iter()
+            .any(|r| r.start <= access.start && access.start <= access.end && access.end <= r.end)
+        {
+            panic!(
+                "`{op:?}` accesses {len} bytes at {:#x}, which is outside of the registered buffers",
+                access.start
+            );
+        }
+    }
+
+    /// Execute an instruction and call the hook.
+    ///
+    /// # Safety
+    ///
+    /// See [`AmxState::execute`].
+    #[inline]
+    unsafe fn step(&mut self, op: JitOp, operand: u64, ptr: *mut ()) {
+        if op.is_mem() {
+            self.check_access(op, operand, ptr);
+        }
+        let before = self.trace.is_some().then(|| self.state.clone());
+        // Safety: Upheld by the caller
+        unsafe { self.state.execute(op, operand, ptr, self.fp, self.version) };
+        let addr = op.is_mem().then_some(ptr as usize);
+        let instr = Instr { op, operand, addr };
+        if let (Some(trace), Some(before)) = (&mut self.trace, before) {
+            trace.push(TraceEntry::new(instr, &before, &self.state));
+        }
+        if let Some(hook) = &mut self.hook {
+            hook(&instr, &self.state);
+        }
+    }
+}
+
+impl Deref for AmxEmuCtx {
+    type Target = AmxState;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl DerefMut for AmxEmuCtx {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.state
+    }
+}
+
+/// Decode the first register row and the number of rows of a load/store
+/// operand.
+#[inline]
+fn mem_operand(x: u64) -> (usize, usize) {
+    (
+        ((x >> 56) & 0x3f) as usize,
+        if x & (1 << 62) != 0 { 2 } else { 1 },
+    )
+}
+
+/// Read 64 bytes from `regs` starting at `offset`, wrapping around the end.
+fn read_wrapping(regs: &[u8], offset: usize) -> [u8; 64] {
+    std::array::from_fn(|i| regs[(offset + i) % regs.len()])
+}
+
+/// Write 64 bytes to `regs` starting at `offset`, wrapping around the end.
+fn write_wrapping(regs: &mut [u8], offset: usize, data: &[u8; 64]) {
+    let len = regs.len();
+    for (i, &b) in data.iter().enumerate() {
+        regs[(offset + i) % len] = b;
+    }
+}
+
+/// Load `n` rows from `ptr` to the register file `regs`.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reading `n * 64` bytes.
+unsafe fn load_rows(regs: &mut [u8], row: usize, n: usize, ptr: *mut ()) {
+    for r in 0..n {
+        // Safety: Upheld by the caller
+        let src = unsafe {
+            ptr.cast::<u8>()
+                .add(r * 64)
+                .cast::<[u8; 64]>()
+                .read_unaligned()
+        };
+        write_wrapping(regs, (row + r) * 64, &src);
+    }
+}
+
+/// Store `n` rows from the register file `regs` to `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writing `n * 64` bytes.
+unsafe fn store_rows(regs: &[u8], row: usize, n: usize, ptr: *mut ()) {
+    for r in 0..n {
+        let data = read_wrapping(regs, (row + r) * 64);
+        // Safety: Upheld by the caller
+        unsafe {
+            ptr.cast::<u8>()
+                .add(r * 64)
+                .cast::<[u8; 64]>()
+                .write_unaligned(data)
+        };
+    }
+}
+
+/// Calculate `x * y + z` for `f16` or `bf16` inputs (converted to `f64`)
+/// with a single rounding to the input type, as done by the hardware.
+///
+/// The product is exact in `f64`, but the sum might not be, so it's rounded
+/// to odd, which makes the subsequent rounding to `f16` or `bf16` correct.
+fn fma_narrow(x: f64, y: f64, z: f64) -> f64 {
+    let p = x * y;
+    let s = p + z;
+    // Two-sum: `s + err == p + z` exactly
+    let b = s - p;
+    let err = (p - (s - b)) + (z - b);
+    if err == 0.0 || s.to_bits() & 1 != 0 {
+        s
+    } else if (err > 0.0) == (s > 0.0) {
+        f64::from_bits(s.to_bits() + 1)
+    } else {
+        f64::from_bits(s.to_bits() - 1)
+    }
+}
+
+/// Decode the numbers of enabled X and Y lanes of a `matint`/`matfp`
+/// operand.
+fn mat_lanes(x: u64, lanes: usize) -> (usize, usize) {
+    let decode = |n: u64| if n == 0 { lanes } else { n as usize };
+    (decode((x >> 32) & 0x3f), decode((x >> 53) & 0x3f))
+}
+
+/// The element type of a floating-point `fma`/`fms`/`vecfp`/`matfp`
+/// instruction
+#[derive(Clone, Copy)]
+enum Fp {
+    F16,
+    /// `bfloat16`, supported by `matfp` since M2
+    Bf16,
+    F32,
+    F64,
+}
+
+impl Fp {
+    #[inline]
+    fn size(self) -> usize {
+        match self {
+            Self::F16 | Self::Bf16 => 2,
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Get the number of explicit mantissa bits.
+    #[inline]
+    fn mantissa_bits(self) -> u32 {
+        match self {
+            Self::F16 => 10,
+            Self::Bf16 => 7,
+            Self::F32 => 23,
+            Self::F64 => 52,
+        }
+    }
+
+    /// Split `bits` into the sign bit, the biased exponent, and the
+    /// mantissa, and get the maximum exponent.
+    #[inline]
+    fn split(self, bits: u64) -> (u64, u64, u64, u64) {
+        let width = self.size() as u32 * 8;
+        let m = self.mantissa_bits();
+        let exp_max = (1 << (width - 1 - m)) - 1;
+        (
+            bits >> (width - 1),
+            (bits >> m) & exp_max,
+            bits & ((1 << m) - 1),
+            exp_max,
+        )
+    }
+
+    #[inline]
+    fn read(self, b: &[u8], fp: FpSemantics) -> f64 {
+        let mut buf = [0; 8];
+        buf[..b.len()].copy_from_slice(b);
+        let bits = u64::from_le_bytes(buf);
+        let (sign, exp, man, exp_max) = self.split(bits);
+        if exp == exp_max && man != 0 {
+            // Keep the payload and the quiet bit, which `as` doesn't
+            return f64::from_bits(sign << 63 | 0x7ff << 52 | man << (52 - self.mantissa_bits()));
+        }
+        if exp == 0 && man != 0 && fp.flush_subnormals {
+            return f64::from_bits(sign << 63);
+        }
+        match self {
+            Self::F16 => f16_to_f32(bits as u16) as f64,
+            Self::Bf16 => f32::from_bits((bits as u32) << 16) as f64,
+            Self::F32 => f32::from_bits(bits as u32) as f64,
+            Self::F64 => f64::from_bits(bits),
+        }
+    }
+
+    /// Calculate `x * y + z` with a single rounding to `self`.
+    #[inline]
+    fn mul_add(self, x: f64, y: f64, z: f64, fp: FpSemantics) -> f64 {
+        if x.is_nan() || y.is_nan() || z.is_nan() {
+            let invalid = (x.is_infinite() && y == 0.0) || (x == 0.0 && y.is_infinite());
+            // A quiet NaN in Z doesn't hide an invalid product on AArch64
+            return if fp.default_nan || (invalid && is_quiet_nan(z)) {
+                f64::NAN
+            } else {
+                propagate_nan([z, x, y])
+            };
+        }
+        let value = match self {
+            Self::F64 => x.mul_add(y, z),
+            Self::F32 => (x as f32).mul_add(y as f32, z as f32) as f64,
+            Self::F16 | Self::Bf16 => fma_narrow(x, y, z),
+        };
+        // Invalid operations produce the default NaN
+        if value.is_nan() { f64::NAN } else { value }
+    }
+
+    #[inline]
+    fn write(self, b: &mut [u8], value: f64, fp: FpSemantics) {
+        let m = self.mantissa_bits();
+        let bits = if value.is_nan() {
+            let (_, _, _, exp_max) = self.split(0);
+            let v = value.to_bits();
+            let width = self.size() as u32 * 8;
+            (v >> 63 << (width - 1))
+                | (exp_max << m)
+                | ((v & ((1 << 52) - 1)) >> (52 - m))
+                | (1 << (m - 1))
+        } else {
+            let bits = match self {
+                Self::F16 => f64_to_f16(value) as u64,
+                Self::Bf16 => f64_to_bf16(value) as u64,
+                Self::F32 => (value as f32).to_bits() as u64,
+                Self::F64 => value.to_bits(),
+            };
+            let (sign, exp, man, _) = self.split(bits);
+            if exp == 0 && man != 0 && fp.flush_subnormals {
+                sign << (self.size() * 8 - 1)
+            } else {
+                bits
+            }
+        };
+        b.copy_from_slice(&bits.to_le_bytes()[..b.len()]);
+    }
+}
+
+/// Check if `x` is a quiet NaN.
+#[inline]
+fn is_quiet_nan(x: f64) -> bool {
+    x.is_nan() && x.to_bits() & (1 << 51) != 0
+}
+
+/// Select the NaN propagated by an arithmetic operation on `operands` (in
+/// the AArch64 order) and quieten it. This is the first signaling NaN, or
+/// the first quiet NaN if there's none.
+fn propagate_nan(operands: [f64; 3]) -> f64 {
+    let nan = (operands.iter())
+        .find(|x| x.is_nan() && !is_quiet_nan(**x))
+        .or_else(|| operands.iter().find(|x| x.is_nan()))
+        .expect("no NaN operand");
+    f64::from_bits(nan.to_bits() | (1 << 51))
+}
+
+/// Calculate the minimum (or maximum if `max`) of `x` and `z` as AArch64's
+/// `FMIN`/`FMAX` do: a NaN operand produces a NaN, selected as by
+/// [`FpSemantics`], and `-0` is less than `+0`.
+#[inline]
+fn min_max(x: f64, z: f64, max: bool, fp: FpSemantics) -> f64 {
+    if x.is_nan() || z.is_nan() {
+        return if fp.default_nan {
+            f64::NAN
+        } else {
+            propagate_nan([z, x, 0.0])
+        };
+    }
+    if x == z {
+        // Only differs for zeros of opposite signs
+        let bits = if max {
+            x.to_bits() & z.to_bits()
+        } else {
+            x.to_bits() | z.to_bits()
+        };
+        return f64::from_bits(bits);
+    }
+    if (x > z) == max { x } else { z }
+}
+
+/// The ALU mode of a `vecint`/`vecfp`/`matint`/`matfp` instruction, or the
+/// operation of a `fma`/`fms` instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Alu {
+    /// `z + x * y`
+    MulAdd,
+    /// `z - x * y`
+    MulSub,
+    /// `x > 0 ? y : 0`
+    Select,
+    /// `min(x, z)`
+    Min,
+    /// `max(x, z)`
+    Max,
+    /// `x * y`
+    Mul,
+    /// `z + x`
+    AddX,
+    /// `z + y`
+    AddY,
+}
+
+impl Alu {
+    /// Decode the ALU mode field (bits 47–52) of `instr`'s operand `x`.
+    /// `vector` enables the modes only available to `vecint` and `vecfp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mode is reserved in `version`.
+    #[track_caller]
+    fn decode(instr: &str, x: u64, vector: bool, version: AmxVersion) -> Self {
+        let m2 = version >= AmxVersion::M2;
+        match (x >> 47) & 0x3f {
+            0 => Self::MulAdd,
+            1 => Self::MulSub,
+            4 => Self::Select,
+            5 if vector => Self::Min,
+            7 if vector => Self::Max,
+            10 if m2 => Self::Mul,
+            11 if m2 => Self::AddX,
+            12 if m2 => Self::AddY,
+            mode => panic!("`{instr}` ALU mode {mode} is reserved on {version:?}"),
+        }
+    }
+
+    /// Calculate the result of an integer operation, where the products and
+    /// the addends `x` and `y` are shifted right by `shift` bits.
+    #[inline]
+    fn apply_int(self, x: i64, y: i64, z: i64, shift: u32) -> i64 {
+        match self {
+            Self::MulAdd => z.wrapping_add(x.wrapping_mul(y) >> shift),
+            Self::MulSub => z.wrapping_sub(x.wrapping_mul(y) >> shift),
+            Self::Select => {
+                if x > 0 {
+                    y
+                } else {
+                    0
+                }
+            }
+            Self::Min => x.min(z),
+            Self::Max => x.max(z),
+            Self::Mul => x.wrapping_mul(y) >> shift,
+            Self::AddX => z.wrapping_add(x >> shift),
+            Self::AddY => z.wrapping_add(y >> shift),
+        }
+    }
+
+    /// Calculate the result of a floating-point operation on `ty`, rounded
+    /// once.
+    #[inline]
+    fn apply_fp(self, ty: Fp, x: f64, y: f64, z: f64, fp: FpSemantics) -> f64 {
+        match self {
+            Self::MulAdd => ty.mul_add(x, y, z, fp),
+            Self::MulSub => ty.mul_add(-x, y, z, fp),
+            Self::Select => {
+                if x > 0.0 {
+                    y
+                } else {
+                    0.0
+                }
+            }
+            Self::Min => min_max(x, z, false, fp),
+            Self::Max => min_max(x, z, true, fp),
+            // Adding `-0.0` preserves the sign of a zero product
+            Self::Mul => ty.mul_add(x, y, -0.0, fp),
+            Self::AddX => ty.mul_add(x, 1.0, z, fp),
+            Self::AddY => ty.mul_add(y, 1.0, z, fp),
+        }
+    }
+}
+
+/// The decoded operand of a `fma`/`fms`/`mac16` instruction
+struct MulOperand {
+    x: [u8; 64],
+    y: [u8; 64],
+    z_row: usize,
+    skip_z: bool,
+    skip_x: bool,
+    skip_y: bool,
+    wide: bool,
+    vector: bool,
+}
+
+impl AmxState {
+    fn mul_operand(&self, x: u64) -> MulOperand {
+        MulOperand {
+            y: read_wrapping(&self.y, (x & 0x1ff) as usize),
+            x: read_wrapping(&self.x, ((x >> 10) & 0x1ff) as usize),
+            z_row: ((x >> 20) & 0x3f) as usize,
+            skip_z: x & (1 << 27) != 0,
+            skip_x: x & (1 << 28) != 0,
+            skip_y: x & (1 << 29) != 0,
+            wide: x & (1 << 62) != 0,
+            vector: x & (1 << 63) != 0,
+        }
+    }
+
+    /// Call `f(row, lane, i, j)` for each output element of a multiplication
+    /// on `lanes`-element vectors, where `i` and `j` are the indices into
+    /// `x` and `y`, respectively.
+    ///
+    /// In matrix mode, Z is divided into `z_rows / lanes` interleaved tiles.
+    /// If `widen` is set, the output elements are twice as large, and each
+    /// row of a tile is split into a pair of rows holding the even and odd
+    /// lanes.
+    fn for_each_output(
+        &self,
+        op: &MulOperand,
+        lanes: usize,
+        widen: bool,
+        mut f: impl FnMut(usize, usize, usize, usize),
+    ) {
+        let z_rows = self.geometry.z_rows;
+        let stride = z_rows / lanes;
+        if op.vector {
+            for i in 0..lanes {
+                if widen {
+                    f((op.z_row + (i & 1)) % z_rows, i >> 1, i, i);
+                } else {
+                    f(op.z_row % z_rows, i, i, i);
+                }
+            }
+        } else {
+            for j in 0..lanes {
+                for i in 0..lanes {
+                    if widen {
+                        let tile = op.z_row % (stride / 2);
+                        f(j * stride + (i & 1) * (stride / 2) + tile, i >> 1, i, j);
+                    } else {
+                        f(j * stride + op.z_row % stride, i, i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    fn fma(&mut self, x: u64, ty: Fp, alu: Alu, fp: FpSemantics) {
+        self.fma_masked(x, ty, alu, usize::MAX, usize::MAX, fp);
+    }
+
+    /// Like [`Self::fma`], but only the first `x_lanes` and `y_lanes` lanes
+    /// of X and Y, respectively, are enabled.
+    fn fma_masked(
+        &mut self,
+        x: u64,
+        ty: Fp,
+        alu: Alu,
+        x_lanes: usize,
+        y_lanes: usize,
+        fp: FpSemantics,
+    ) {
+        let op = self.mul_operand(x);
+        let size = ty.size();
+        let lanes = 64 / size;
+        let widen = matches!(ty, Fp::F16 | Fp::Bf16) && op.wide;
+        let (out_ty, out_size) = if widen { (Fp::F32, 4) } else { (ty, size) };
+
+        let mut z = std::mem::take(&mut self.z);
+        self.for_each_output(&op, lanes, widen, |row, lane, i, j| {
+            if i >= x_lanes || j >= y_lanes {
+                return;
+            }
+            let xv = if op.skip_x {
+                1.0
+            } else {
+                ty.read(&op.x[i * size..][..size], fp)
+            };
+            let yv = if op.skip_y {
+                1.0
+            } else {
+                ty.read(&op.y[j * size..][..size], fp)
+            };
+            let out = &mut z[row][lane * out_size..][..out_size];
+            let zv = if op.skip_z { 0.0 } else { out_ty.read(out, fp) };
+            out_ty.write(out, alu.apply_fp(out_ty, xv, yv, zv, fp), fp);
+        });
+        self.z = z;
+    }
+}
+
+impl AmxState {
+    unsafe fn ldx(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { load_rows(&mut self.x, row, n, ptr) };
+    }
+
+    unsafe fn ldy(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { load_rows(&mut self.y, row, n, ptr) };
+    }
+
+    unsafe fn stx(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { store_rows(&self.x, row, n, ptr) };
+    }
+
+    unsafe fn sty(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { store_rows(&self.y, row, n, ptr) };
+    }
+
+    unsafe fn ldz(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { load_rows(self.z.as_flattened_mut(), row, n, ptr) };
+    }
+
+    unsafe fn stz(&mut self, x: u64, ptr: *mut ()) {
+        let (row, n) = mem_operand(x);
+        // Safety: Upheld by the caller
+        unsafe { store_rows(self.z.as_flattened(), row, n, ptr) };
+    }
+
+    unsafe fn ldzi(&mut self, x: u64, ptr: *mut ()) {
+        let (row, _) = mem_operand(x);
+        // Safety: Upheld by the caller
+        let src = unsafe { ptr.cast::<[u8; 64]>().read_unaligned() };
+        // The even (odd) row index selects the left (right) half of a pair of
+        // rows, whose 32-bit elements are interleaved in memory
+        let (base, half) = (row & !1, row & 1);
+        for (k, src) in src.chunks_exact(4).enumerate() {
+            let z_row = (base + (k & 1)) % self.geometry.z_rows;
+            let lane = half * 8 + k / 2;
+            self.z[z_row][lane * 4..][..4].copy_from_slice(src);
+        }
+    }
+
+    unsafe fn stzi(&mut self, x: u64, ptr: *mut ()) {
+        let (row, _) = mem_operand(x);
+        let (base, half) = (row & !1, row & 1);
+        let mut dst = [0u8; 64];
+        for (k, dst) in dst.chunks_exact_mut(4).enumerate() {
+            let z_row = (base + (k & 1)) % self.geometry.z_rows;
+            let lane = half * 8 + k / 2;
+            dst.copy_from_slice(&self.z[z_row][lane * 4..][..4]);
+        }
+        // Safety: Upheld by the caller
+        unsafe { ptr.cast::<[u8; 64]>().write_unaligned(dst) };
+    }
+
+    fn extrx(&mut self, x: u64) {
+        let row = self.z[((x >> 20) & 0x3f) as usize % self.geometry.z_rows];
+        write_wrapping(&mut self.x, ((x >> 10) & 0x1ff) as usize, &row);
+    }
+
+    fn extry(&mut self, x: u64) {
+        let row = self.z[((x >> 20) & 0x3f) as usize % self.geometry.z_rows];
+        write_wrapping(&mut self.y, (x & 0x1ff) as usize, &row);
+    }
+
+    fn fma64(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F64, Alu::MulAdd, fp);
+    }
+
+    fn fms64(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F64, Alu::MulSub, fp);
+    }
+
+    fn fma32(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F32, Alu::MulAdd, fp);
+    }
+
+    fn fms32(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F32, Alu::MulSub, fp);
+    }
+
+    fn mac16(&mut self, x: u64) {
+        let op = self.mul_operand(x);
+        let shift = ((x >> 55) & 0x1f) as u32;
+        let get = |b: &[u8; 64], i: usize| i16::from_le_bytes([b[i * 2], b[i * 2 + 1]]) as i32;
+
+        let mut z = std::mem::take(&mut self.z);
+        self.for_each_output(&op, 32, op.wide, |row, lane, i, j| {
+            let xv = if op.skip_x { 1 } else { get(&op.x, i) };
+            let yv = if op.skip_y { 1 } else { get(&op.y, j) };
+            let p = xv.wrapping_mul(yv) >> shift;
+            let zr = &mut z[row];
+            if op.wide {
+                let out = &mut zr[lane * 4..][..4];
+                let zv = if op.skip_z {
+                    0
+                } else {
+                    i32::from_le_bytes((&*out).try_into().unwrap())
+                };
+                out.copy_from_slice(&zv.wrapping_add(p).to_le_bytes());
+            } else {
+                let out = &mut zr[lane * 2..][..2];
+                let zv = if op.skip_z {
+                    0
+                } else {
+                    i16::from_le_bytes((&*out).try_into().unwrap())
+                };
+                out.copy_from_slice(&zv.wrapping_add(p as i16).to_le_bytes());
+            }
+        });
+        self.z = z;
+    }
+
+    fn fma16(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F16, Alu::MulAdd, fp);
+    }
+
+    fn fms16(&mut self, x: u64, fp: FpSemantics) {
+        self.fma(x, Fp::F16, Alu::MulSub, fp);
+    }
+
+    fn vecint(&mut self, x: u64, version: AmxVersion) {
+        let xs = read_wrapping(&self.x, ((x >> 10) & 0x1ff) as usize);
+        let ys = read_wrapping(&self.y, (x & 0x1ff) as usize);
+        let z_row = ((x >> 20) & 0x3f) as usize % self.geometry.z_rows;
+        let skip_z = x & (1 << 27) != 0;
+        let size = match (x >> 42) & 0xf {
+            4 => 4,
+            10 => 1,
+            _ => 2,
+        };
+        let alu = Alu::decode("vecint", x, true, version);
+        let saturate = x & (1 << 53) != 0;
+        let shift = ((x >> 58) & 0x1f) as u32;
+
+        let read = |b: &[u8]| -> i64 {
+            match size {
+                1 => b[0] as i8 as i64,
+                2 => i16::from_le_bytes(b.try_into().unwrap()) as i64,
+                _ => i32::from_le_bytes(b.try_into().unwrap()) as i64,
+            }
+        };
+        let bits = size as u32 * 8;
+        let (min, max) = (-1i64 << (bits - 1), (1i64 << (bits - 1)) - 1);
+
+        for i in 0..64 / size {
+            let (xv, yv) = (read(&xs[i * size..][..size]), read(&ys[i * size..][..size]));
+            let out = &mut self.z[z_row][i * size..][..size];
+            let zv = if skip_z { 0 } else { read(out) };
+            let value = alu.apply_int(xv, yv, zv, shift);
+            let value = if saturate {
+                value.clamp(min, max)
+            } else {
+                value
+            };
+            out.copy_from_slice(&value.to_le_bytes()[..size]);
+        }
+    }
+
+    fn vecfp(&mut self, x: u64, fp: FpSemantics, version: AmxVersion) {
+        let xs = read_wrapping(&self.x, ((x >> 10) & 0x1ff) as usize);
+        let ys = read_wrapping(&self.y, (x & 0x1ff) as usize);
+        let z_row = ((x >> 20) & 0x3f) as usize % self.geometry.z_rows;
+        let ty = match (x >> 42) & 0xf {
+            4 => Fp::F32,
+            7 => Fp::F64,
+            _ => Fp::F16,
+        };
+        let alu = Alu::decode("vecfp", x, true, version);
+        let skip_z = x & (1 << 27) != 0;
+        let skip_y = x & (1 << 29) != 0;
+        let size = ty.size();
+
+        for i in 0..64 / size {
+            let xv = ty.read(&xs[i * size..][..size], fp);
+            let yv = if skip_y {
+                1.0
+            } else {
+                ty.read(&ys[i * size..][..size], fp)
+            };
+            let out = &mut self.z[z_row][i * size..][..size];
+            let zv = if skip_z { 0.0 } else { ty.read(out, fp) };
+            ty.write(out, alu.apply_fp(ty, xv, yv, zv, fp), fp);
+        }
+    }
+
+    fn matint(&mut self, x: u64, version: AmxVersion) {
+        let op = self.mul_operand(x);
+        let (x_lanes, y_lanes) = mat_lanes(x, 32);
+        let alu = Alu::decode("matint", x, false, version);
+        let get = match (x >> 42) & 0xf {
+            0 => |b: &[u8; 64], i: usize| i16::from_le_bytes([b[i * 2], b[i * 2 + 1]]) as i32,
+            1 => |b: &[u8; 64], i: usize| u16::from_le_bytes([b[i * 2], b[i * 2 + 1]]) as i32,
+            2 => |b: &[u8; 64], i: usize| b[i] as i8 as i32,
+            3 => |b: &[u8; 64], i: usize| b[i] as i32,
+            // The lane types added by M2 aren't known well enough to emulate
+            ty => panic!("`matint` lane type {ty} is not emulated"),
+        };
+
+        let mut z = std::mem::take(&mut self.z);
+        self.for_each_output(&op, 32, op.wide, |row, lane, i, j| {
+            if i >= x_lanes || j >= y_lanes {
+                return;
+            }
+            let (xv, yv) = (get(&op.x, i) as i64, get(&op.y, j) as i64);
+            let size = if op.wide { 4 } else { 2 };
+            let out = &mut z[row][lane * size..][..size];
+            let zv = match (op.skip_z, op.wide) {
+                (true, _) => 0,
+                (false, true) => i32::from_le_bytes((&*out).try_into().unwrap()) as i64,
+                (false, false) => i16::from_le_bytes((&*out).try_into().unwrap()) as i64,
+            };
+            // The sums wrap around to the output lane type
+            let value = alu.apply_int(xv, yv, zv, 0);
+            out.copy_from_slice(&value.to_le_bytes()[..size]);
+        });
+        self.z = z;
+    }
+
+    fn matfp(&mut self, x: u64, fp: FpSemantics, version: AmxVersion) {
+        let ty = match (x >> 42) & 0xf {
+            1 if version >= AmxVersion::M2 => Fp::Bf16,
+            4 => Fp::F32,
+            7 => Fp::F64,
+            _ => Fp::F16,
+        };
+        let alu = Alu::decode("matfp", x, false, version);
+        let (x_lanes, y_lanes) = mat_lanes(x, 64 / ty.size());
+        // Bits 28 and 29 don't skip X and Y in `matfp`
+        let x = x & !(0b11 << 28);
+        self.fma_masked(x, ty, alu, x_lanes, y_lanes, fp);
+    }
+
+    fn genlut(&mut self, x: u64) {
+        let in_offset = (x & 0x1ff) as usize;
+        let input = if x & (1 << 10) != 0 {
+            read_wrapping(&self.y, in_offset)
+        } else {
+            read_wrapping(&self.x, in_offset)
+        };
+        let table_row = ((x >> 60) & 7) as usize % self.geometry.x_rows;
+        let table: [u8; 64] = self.x[table_row * 64..][..64].try_into().unwrap();
+        let mode = (x >> 53) & 0xf;
+
+        // Extract the `i`-th `bits`-bit field from `input`
+        let field = |bits: usize, i: usize| -> usize {
+            let bit = i * bits;
+            let word = u32::from_le_bytes(std::array::from_fn(|k| {
+                *input.get(bit / 8 + k).unwrap_or(&0)
+            }));
+            ((word >> (bit % 8)) & ((1 << bits) - 1)) as usize
+        };
+
+        let mut out = [0u8; 64];
+        if mode >= 7 {
+            // Look up the table using the indices
+            let (index_bits, esize) = match mode {
+                7 => (2, 4),
+                8 => (2, 2),
+                9 => (2, 1),
+                10 => (4, 8),
+                11 => (4, 4),
+                12 => (4, 2),
+                13 => (4, 1),
+                14 => (5, 2),
+                _ => (5, 1),
+            };
+            let entries = 64 / esize;
+            for (i, out) in out.chunks_exact_mut(esize).enumerate() {
+                let index = field(index_bits, i) % entries;
+                out.copy_from_slice(&table[index * esize..][..esize]);
+            }
+        } else {
+            // Find the interval of the table containing each input element
+            let (index_bits, esize) = match mode {
+                0 | 3 | 5 => (4, 4),
+                2 => (4, 8),
+                _ => (5, 2),
+            };
+            let entries = (64 / esize).min(1 << index_bits);
+            let key = |b: &[u8], i: usize| -> f64 {
+                let e = &b[i * esize..][..esize];
+                match mode {
+                    0 => f32::from_le_bytes(e.try_into().unwrap()) as f64,
+                    1 => f16_to_f32(u16::from_le_bytes(e.try_into().unwrap())) as f64,
+                    2 => f64::from_le_bytes(e.try_into().unwrap()),
+                    3 => i32::from_le_bytes(e.try_into().unwrap()) as f64,
+                    4 => i16::from_le_bytes(e.try_into().unwrap()) as f64,
+                    5 => u32::from_le_bytes(e.try_into().unwrap()) as f64,
+                    _ => u16::from_le_bytes(e.try_into().unwrap()) as f64,
+                }
+            };
+            let mut packed = [0u8; 32];
+            for i in 0..64 / esize {
+                let value = key(&input, i);
+                let first_gt = (0..entries)
+                    .find(|&k| key(&table, k) > value)
+                    .unwrap_or(entries);
+                let index = first_gt.wrapping_sub(1) & ((1 << index_bits) - 1);
+                for b in 0..index_bits {
+                    let bit = i * index_bits + b;
+                    packed[bit / 8] |= (((index >> b) & 1) as u8) << (bit % 8);
+                }
+            }
+            out[..32].copy_from_slice(&packed);
+        }
+
+        let dst = ((x >> 20) & 0x3f) as usize;
+        if x & (1 << 26) != 0 {
+            self.z[dst % self.geometry.z_rows] = out;
+        } else if x & (1 << 25) != 0 {
+            write_wrapping(&mut self.y, (dst & 7) * 64, &out);
+        } else {
+            write_wrapping(&mut self.x, (dst & 7) * 64, &out);
+        }
+    }
+}
+
+impl AmxState {
+    /// Execute an instruction.
+    ///
+    /// # Safety
+    ///
+    /// If `op` is a memory operation, `ptr` must be valid for the access
+    /// specified by `operand`.
+    unsafe fn execute(
+        &mut self,
+        op: JitOp,
+        operand: u64,
+        ptr: *mut (),
+        fp: FpSemantics,
+        version: AmxVersion,
+    ) {
+        // Safety: Upheld by the caller
+        unsafe {
+            match op {
+                JitOp::Ldx => self.ldx(operand, ptr),
+                JitOp::Ldy => self.ldy(operand, ptr),
+                JitOp::Stx => self.stx(operand, ptr),
+                JitOp::Sty => self.sty(operand, ptr),
+                JitOp::Ldz => self.ldz(operand, ptr),
+                JitOp::Stz => self.stz(operand, ptr),
+                JitOp::Ldzi => self.ldzi(operand, ptr),
+                JitOp::Stzi => self.stzi(operand, ptr),
+                JitOp::Extrx => self.extrx(operand),
+                JitOp::Extry => self.extry(operand),
+                JitOp::Fma64 => self.fma64(operand, fp),
+                JitOp::Fms64 => self.fms64(operand, fp),
+                JitOp::Fma32 => self.fma32(operand, fp),
+                JitOp::Fms32 => self.fms32(operand, fp),
+                JitOp::Mac16 => self.mac16(operand),
+                JitOp::Fma16 => self.fma16(operand, fp),
+                JitOp::Fms16 => self.fms16(operand, fp),
+                JitOp::Vecint => self.vecint(operand, version),
+                JitOp::Vecfp => self.vecfp(operand, fp, version),
+                JitOp::Matint => self.matint(operand, version),
+                JitOp::Matfp => self.matfp(operand, fp, version),
+                JitOp::Genlut => self.genlut(operand),
+            }
+        }
+    }
+}
+
+/// Implement [`AmxOps`] for [`AmxEmuCtx`] by calling [`AmxEmuCtx::step`].
+macro_rules! impl_emu_ops {
+    (
+        mem: $($mem_name:ident => $mem_op:ident),*;
+        $($name:ident => $op:ident),* $(,)?
+    ) => {
+        unsafe impl AmxOps for AmxEmuCtx {
+            $(
+                unsafe fn $mem_name(&mut self, x: u64, ptr: *mut ()) {
+                    // Safety: Upheld by the caller
+                    unsafe { self.step(JitOp::$mem_op, x, ptr) }
+                }
+            )*
+            $(
+                fn $name(&mut self, x: u64) {
+                    // Safety: Not a memory operation
+                    unsafe { self.step(JitOp::$op, x, std::ptr::null_mut()) }
+                }
+            )*
+
+            fn version(&self) -> AmxVersion {
+                self.version
+            }
+        }
+    };
+}
+
+impl_emu_ops! {
+    mem: ldx => Ldx, ldy => Ldy, stx => Stx, sty => Sty, ldz => Ldz, stz => Stz,
+        ldzi => Ldzi, stzi => Stzi;
+    extrx => Extrx, extry => Extry, fma64 => Fma64, fms64 => Fms64, fma32 => Fma32,
+    fms32 => Fms32, mac16 => Mac16, fma16 => Fma16, fms16 => Fms16, vecint => Vecint,
+    vecfp => Vecfp, matint => Matint, matfp => Matfp, genlut => Genlut,
+}
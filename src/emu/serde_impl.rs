@@ -0,0 +1,68 @@
+//! `serde` support for [`AmxState`] (requires the `serde` feature)
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+use super::{AmxEmuGeometry, AmxState};
+
+/// The serialized form of [`AmxState`]. Each register set is a flat byte
+/// sequence.
+#[derive(Serialize)]
+struct StateRef<'a> {
+    geometry: AmxEmuGeometry,
+    x: &'a [u8],
+    y: &'a [u8],
+    z: &'a [u8],
+}
+
+#[derive(Deserialize)]
+struct StateOwned {
+    geometry: AmxEmuGeometry,
+    x: Vec<u8>,
+    y: Vec<u8>,
+    z: Vec<u8>,
+}
+
+impl Serialize for AmxState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StateRef {
+            geometry: self.geometry,
+            x: &self.x,
+            y: &self.y,
+            z: self.z.as_flattened(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AmxState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let StateOwned { geometry, x, y, z } = StateOwned::deserialize(deserializer)?;
+        if geometry.x_rows == 0
+            || geometry.y_rows == 0
+            || geometry.z_rows == 0
+            || !geometry.z_rows.is_multiple_of(64)
+        {
+            return Err(D::Error::custom(format_args!(
+                "invalid register file size: {geometry:?}"
+            )));
+        }
+        for (name, regs, rows) in [
+            ("x", &x, geometry.x_rows),
+            ("y", &y, geometry.y_rows),
+            ("z", &z, geometry.z_rows),
+        ] {
+            if Some(regs.len()) != rows.checked_mul(64) {
+                return Err(D::Error::custom(format_args!(
+                    "`{name}` has {} bytes, but {rows} rows were expected",
+                    regs.len()
+                )));
+            }
+        }
+        let (z, _) = z.as_chunks::<64>();
+        Ok(Self {
+            geometry,
+            x,
+            y,
+            z: z.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,182 @@
+//! Instruction traces recorded by [`AmxEmuCtx`](super::AmxEmuCtx)
+use std::fmt;
+
+use super::{AmxState, Instr};
+use crate::{jit::JitOp, minimize::RegFile};
+
+/// An instruction executed by [`AmxEmuCtx`](super::AmxEmuCtx) and its
+/// effect on the registers, recorded while tracing is enabled by
+/// [`AmxEmuCtx::enable_trace`](super::AmxEmuCtx::enable_trace)
+///
+/// The `Display` implementation renders the instruction as pseudo-assembly
+/// (see [`Disassembly`](crate::Disassembly)), followed by the decoded
+/// operand fields and the modified rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub instr: Instr,
+    /// The decoded operand fields. See [`Instr::fields`].
+    pub fields: Vec<(&'static str, u64)>,
+    /// The register rows modified by the instruction
+    pub changes: Vec<RowChange>,
+}
+
+/// A register row modified by an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowChange {
+    pub file: RegFile,
+    pub row: usize,
+    /// The row's contents before the instruction
+    pub before: [u8; 64],
+    /// The row's contents after the instruction
+    pub after: [u8; 64],
+}
+
+impl RowChange {
+    /// Get the byte offsets within the row at which the contents changed.
+    pub fn changed_bytes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..64).filter(|&i| self.before[i] != self.after[i])
+    }
+}
+
+impl TraceEntry {
+    /// Construct a `TraceEntry` from the register contents before and after
+    /// executing `instr`.
+    pub(super) fn new(instr: Instr, before: &AmxState, after: &AmxState) -> Self {
+        let mut changes = Vec::new();
+        for (file, before, after) in [
+            (RegFile::X, &before.x[..], &after.x[..]),
+            (RegFile::Y, &before.y[..], &after.y[..]),
+            (RegFile::Z, before.z.as_flattened(), after.z.as_flattened()),
+        ] {
+            let rows = before.chunks_exact(64).zip(after.chunks_exact(64));
+            for (row, (before, after)) in rows.enumerate() {
+                if before != after {
+                    changes.push(RowChange {
+                        file,
+                        row,
+                        before: before.try_into().unwrap(),
+                        after: after.try_into().unwrap(),
+                    });
+                }
+            }
+        }
+        Self {
+            instr,
+            fields: instr.fields(),
+            changes,
+        }
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n   ", self.instr)?;
+        for (name, value) in &self.fields {
+            write!(f, " {name}={value}")?;
+        }
+        for change in &self.changes {
+            let reg = match change.file {
+                RegFile::X => "x",
+                RegFile::Y => "y",
+                RegFile::Z => "z",
+            };
+            let bytes = change.changed_bytes().count();
+            write!(f, "\n    {reg}{}: {bytes} bytes changed", change.row)?;
+        }
+        Ok(())
+    }
+}
+
+impl Instr {
+    /// Decode the operand into named fields, e.g., `("z_row", 3)`. Flags
+    /// are decoded as `0` or `1`.
+    ///
+    /// ```rust
+    /// use amx::{Instr, jit::JitOp};
+    ///
+    /// let instr = Instr { op: JitOp::Fma32, operand: (5 << 20) | (1 << 27), addr: None };
+    /// let fields = instr.fields();
+    /// assert!(fields.contains(&("z_row", 5)));
+    /// assert!(fields.contains(&("skip_z", 1)));
+    /// ```
+    pub fn fields(&self) -> Vec<(&'static str, u64)> {
+        let x = self.operand;
+        let bits = |start: u32, len: u32| (x >> start) & ((1 << len) - 1);
+        let offsets = [
+            ("y_offset", bits(0, 9)),
+            ("x_offset", bits(10, 9)),
+            ("z_row", bits(20, 6)),
+        ];
+        let mut fields = Vec::new();
+        match self.op {
+            JitOp::Ldx | JitOp::Ldy | JitOp::Stx | JitOp::Sty | JitOp::Ldz | JitOp::Stz => {
+                fields.extend([("row", bits(56, 6)), ("pair", bits(62, 1))]);
+            }
+            JitOp::Ldzi | JitOp::Stzi => fields.push(("row", bits(56, 6))),
+            JitOp::Extrx => fields.extend([offsets[1], offsets[2]]),
+            JitOp::Extry => fields.extend([offsets[0], offsets[2]]),
+            JitOp::Fma64
+            | JitOp::Fms64
+            | JitOp::Fma32
+            | JitOp::Fms32
+            | JitOp::Mac16
+            | JitOp::Fma16
+            | JitOp::Fms16 => {
+                fields.extend(offsets);
+                fields.extend([
+                    ("skip_z", bits(27, 1)),
+                    ("skip_x", bits(28, 1)),
+                    ("skip_y", bits(29, 1)),
+                ]);
+                if self.op == JitOp::Mac16 {
+                    fields.push(("shift", bits(55, 5)));
+                }
+                fields.extend([("wide", bits(62, 1)), ("vector", bits(63, 1))]);
+            }
+            JitOp::Vecint => {
+                fields.extend(offsets);
+                fields.extend([
+                    ("skip_z", bits(27, 1)),
+                    ("lane_width", bits(42, 4)),
+                    ("alu", bits(47, 6)),
+                    ("saturate", bits(53, 1)),
+                    ("shift", bits(58, 5)),
+                ]);
+            }
+            JitOp::Vecfp => {
+                fields.extend(offsets);
+                fields.extend([
+                    ("skip_y", bits(29, 1)),
+                    ("lane_width", bits(42, 4)),
+                    ("alu", bits(47, 6)),
+                ]);
+            }
+            JitOp::Matint | JitOp::Matfp => {
+                fields.extend(offsets);
+                fields.extend([
+                    ("skip_z", bits(27, 1)),
+                    ("x_lanes", bits(32, 6)),
+                    ("lane_type", bits(42, 4)),
+                    ("alu", bits(47, 6)),
+                    ("y_lanes", bits(53, 6)),
+                    ("wide", bits(62, 1)),
+                ]);
+            }
+            JitOp::Genlut => {
+                fields.extend([
+                    ("input_offset", bits(0, 9)),
+                    ("input_in_y", bits(10, 1)),
+                    (
+                        "output_row",
+                        bits(20, if x & (1 << 26) != 0 { 6 } else { 5 }),
+                    ),
+                    ("output_in_y", bits(25, 1)),
+                    ("output_in_z", bits(26, 1)),
+                    ("mode", bits(53, 4)),
+                    ("table_row", bits(60, 3)),
+                ]);
+            }
+        }
+        fields
+    }
+}
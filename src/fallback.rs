@@ -0,0 +1,147 @@
+//! A context that uses AMX if available and the emulator otherwise
+use std::ops::{Deref, DerefMut};
+
+use crate::{AmxEmuCtx, AmxOps, AmxVersion, DynAmx, NewAmxCtxError};
+
+/// Either a native [`AmxCtx`](crate::AmxCtx) or an [`AmxEmuCtx`], chosen at
+/// runtime by [`Self::try_new`]
+///
+/// This allows one code path to run on every machine: the instructions are
+/// executed natively on Apple Silicon and emulated elsewhere. The context
+/// implements [`AmxOps`] by matching on the variant, so it can be passed to
+/// any function taking `impl Amx` without dynamic dispatch. For
+/// convenience, it also derefs to [`dyn DynAmx`](DynAmx).
+///
+/// ```rust
+/// use amx::{Amx, AmxFallbackCtx, XBytes, XRow, YBytes, YRow, ZRow};
+///
+/// let mut ctx = AmxFallbackCtx::try_new().unwrap();
+/// assert_eq!(ctx.is_native(), amx::is_available());
+///
+/// ctx.load512_slice(&[1.5f32; 16], XRow(0));
+/// ctx.load512_slice(&[2.0f32; 16], YRow(0));
+/// ctx.fma_vector_f32(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+/// assert_eq!(ctx.read_z_f32()[0], [3.0; 16]);
+/// ```
+pub enum AmxFallbackCtx {
+    /// The native context
+    #[cfg(any(doc, target_arch = "aarch64"))]
+    Native(crate::AmxCtx),
+    /// The emulator, used if AMX is not supported by the system
+    Emu(AmxEmuCtx),
+}
+
+impl AmxFallbackCtx {
+    /// Construct an [`AmxCtx`](crate::AmxCtx) if AMX is available and a
+    /// default [`AmxEmuCtx`] otherwise.
+    ///
+    /// Fails with [`NewAmxCtxError::AlreadyActive`] if the current thread
+    /// already has an active `AmxCtx`, instead of silently running the
+    /// emulator on a system with AMX.
+    pub fn try_new() -> Result<Self, NewAmxCtxError> {
+        #[cfg(target_arch = "aarch64")]
+        match crate::AmxCtx::new() {
+            Ok(ctx) => return Ok(Self::Native(ctx)),
+            Err(NewAmxCtxError::Unsupported) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Self::Emu(AmxEmuCtx::default()))
+    }
+
+    /// Construct a context by [`Self::try_new`], call `f` with it, and drop
+    /// the context, even if `f` panics. Returns the result of `f`.
+    ///
+    /// See [`AmxCtx::with`](crate::AmxCtx::with).
+    ///
+    /// ```rust
+    /// use amx::{Amx, AmxFallbackCtx, XRow};
+    ///
+    /// let x = AmxFallbackCtx::with(|ctx| {
+    ///     ctx.load512_slice(&[7u8; 64], XRow(0));
+    ///     ctx.read_x()[0]
+    /// });
+    /// assert_eq!(x.unwrap(), 7);
+    /// ```
+    pub fn with<R>(f: impl FnOnce(&mut Self) -> R) -> Result<R, NewAmxCtxError> {
+        let mut ctx = Self::try_new()?;
+        Ok(f(&mut ctx))
+    }
+
+    /// Get a flag indicating whether the instructions are executed natively.
+    pub fn is_native(&self) -> bool {
+        !matches!(self, Self::Emu(_))
+    }
+}
+
+impl Deref for AmxFallbackCtx {
+    type Target = dyn DynAmx;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            #[cfg(any(doc, target_arch = "aarch64"))]
+            Self::Native(ctx) => ctx,
+            Self::Emu(ctx) => ctx,
+        }
+    }
+}
+
+impl DerefMut for AmxFallbackCtx {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            #[cfg(any(doc, target_arch = "aarch64"))]
+            Self::Native(ctx) => ctx,
+            Self::Emu(ctx) => ctx,
+        }
+    }
+}
+
+/// Implement the [`AmxOps`] methods by matching on the variant, so that the
+/// calls are dispatched statically instead of through the vtable of
+/// [`dyn DynAmx`](DynAmx).
+macro_rules! dispatch_amx_ops {
+    (
+        unsafe { $($unsafe_op:ident),* }
+        safe { $($op:ident),* }
+    ) => {
+        $(
+            #[inline(always)]
+            unsafe fn $unsafe_op(&mut self, x: u64, ptr: *mut ()) {
+                match self {
+                    #[cfg(any(doc, target_arch = "aarch64"))]
+                    Self::Native(ctx) => unsafe { AmxOps::$unsafe_op(ctx, x, ptr) },
+                    Self::Emu(ctx) => unsafe { AmxOps::$unsafe_op(ctx, x, ptr) },
+                }
+            }
+        )*
+        $(
+            #[inline(always)]
+            fn $op(&mut self, x: u64) {
+                match self {
+                    #[cfg(any(doc, target_arch = "aarch64"))]
+                    Self::Native(ctx) => AmxOps::$op(ctx, x),
+                    Self::Emu(ctx) => AmxOps::$op(ctx, x),
+                }
+            }
+        )*
+    };
+}
+
+// Safety: Just forwarding the calls
+unsafe impl AmxOps for AmxFallbackCtx {
+    dispatch_amx_ops! {
+        unsafe { ldx, ldy, stx, sty, ldz, stz, ldzi, stzi }
+        safe {
+            extrx, extry, fma64, fms64, fma32, fms32, mac16, fma16, fms16, vecint, vecfp,
+            matint, matfp, genlut
+        }
+    }
+
+    #[inline(always)]
+    fn version(&self) -> AmxVersion {
+        match self {
+            #[cfg(any(doc, target_arch = "aarch64"))]
+            Self::Native(ctx) => AmxOps::version(ctx),
+            Self::Emu(ctx) => AmxOps::version(ctx),
+        }
+    }
+}
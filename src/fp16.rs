@@ -0,0 +1,132 @@
+//! Conversions between `f16` bit patterns and native floating-point types
+//!
+//! The inputs and outputs of the `f16` instructions (e.g.,
+//! [`Amx::outer_product_f16_xy_to_z`](crate::Amx::outer_product_f16_xy_to_z))
+//! are IEEE 754 half-precision values, which Rust has no stable type for.
+//! They are represented as `u16` bit patterns here, as are the `bf16`
+//! values used by AMX revisions since M2. With the `half` feature,
+//! `half::f16` and `half::bf16` can be used instead.
+/// Convert the `f16` bit pattern `x` to `f32`. This is exact.
+pub fn f16_to_f32(x: u16) -> f32 {
+    let sign = (x as u32 & 0x8000) << 16;
+    let exp = ((x >> 10) & 0x1f) as u32;
+    let man = x as u32 & 0x3ff;
+    let bits = if exp == 0 {
+        if man == 0 {
+            sign
+        } else {
+            // Subnormal
+            let shift = man.leading_zeros() - 21;
+            let man = (man << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (man << 13)
+        }
+    } else if exp == 0x1f {
+        sign | 0x7f80_0000 | (man << 13)
+    } else {
+        sign | ((exp + 127 - 15) << 23) | (man << 13)
+    };
+    f32::from_bits(bits)
+}
+
+/// Convert `x` to `f16`, rounding to nearest, ties to even.
+pub(crate) fn f64_to_f16(x: f64) -> u16 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    let man = bits & 0xf_ffff_ffff_ffff;
+    if exp == 0x7ff {
+        return sign | 0x7c00 | if man != 0 { 0x200 } else { 0 };
+    }
+    let e = exp - 1023 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let round =
+        |half: u64, rem: u64, mid: u64| half + (rem > mid || (rem == mid && half & 1 != 0)) as u64;
+    if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        let man = man | (1 << 52);
+        let shift = (43 - e) as u32;
+        let rem = man & ((1 << shift) - 1);
+        return sign | round(man >> shift, rem, 1 << (shift - 1)) as u16;
+    }
+    sign | round(
+        ((e as u64) << 10) | (man >> 42),
+        man & 0x3ff_ffff_ffff,
+        1 << 41,
+    ) as u16
+}
+
+/// Convert `x` to the `f16` bit pattern nearest to it (ties to even).
+/// Values too large for `f16` become infinity.
+pub fn f32_to_f16(x: f32) -> u16 {
+    // `f32` to `f64` is exact, so this rounds only once
+    f64_to_f16(x as f64)
+}
+
+/// Convert the `bf16` bit pattern `x` to `f32`. This is exact.
+pub fn bf16_to_f32(x: u16) -> f32 {
+    f32::from_bits((x as u32) << 16)
+}
+
+/// Convert `x` to the `bf16` bit pattern nearest to it (ties to even). NaNs
+/// are quietened.
+pub fn f32_to_bf16(x: f32) -> u16 {
+    let bits = x.to_bits();
+    if x.is_nan() {
+        return (bits >> 16) as u16 | 0x40;
+    }
+    ((bits + 0x7fff + ((bits >> 16) & 1)) >> 16) as u16
+}
+
+/// Convert `x` to `bf16`, rounding to nearest, ties to even.
+pub(crate) fn f64_to_bf16(x: f64) -> u16 {
+    // Round to odd in `f32`, which has enough extra bits to make the
+    // subsequent rounding to `bf16` correct
+    let f = x as f32;
+    if f as f64 == x || f.to_bits() & 1 != 0 || !f.is_finite() {
+        return f32_to_bf16(f);
+    }
+    let bits = if (f as f64).abs() < x.abs() {
+        f.to_bits() + 1
+    } else {
+        f.to_bits() - 1
+    };
+    f32_to_bf16(f32::from_bits(bits))
+}
+
+/// An `f16` value stored as its bit pattern, used as the `f16` element type
+/// of [`Amx::read_z_as`](crate::Amx::read_z_as) and its siblings
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct F16Bits(pub u16);
+
+impl F16Bits {
+    /// Convert `x` to the nearest `f16` value. See [`f32_to_f16`].
+    pub fn from_f32(x: f32) -> Self {
+        Self(f32_to_f16(x))
+    }
+
+    /// Convert the value to `f32`. This is exact.
+    pub fn to_f32(self) -> f32 {
+        f16_to_f32(self.0)
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<half::f16> for F16Bits {
+    #[inline]
+    fn from(x: half::f16) -> Self {
+        Self(x.to_bits())
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<F16Bits> for half::f16 {
+    #[inline]
+    fn from(x: F16Bits) -> Self {
+        half::f16::from_bits(x.0)
+    }
+}
@@ -13,6 +13,22 @@ use crate::{
 ///  - `index` is the data type for indices.
 ///  - `value` is the data type for looked-up values.
 ///
+/// In the normal direction, each index selects an element of the table,
+/// which is the row of X specified to [`Amx::lut`](crate::Amx::lut). In the
+/// reverse direction, the table is a sorted list of interval boundaries, and
+/// each input element is converted to the index of the last boundary not
+/// greater than it (all ones if there's none). The indices are packed in the
+/// low bytes of the output.
+///
+/// The following combinations are supported:
+///
+/// | direction   | index      | value                                  |
+/// | ----------- | ---------- | -------------------------------------- |
+/// | [`Normal`]  | [`Index2`] | [`X8`], [`X16`], [`X32`]               |
+/// | [`Normal`]  | [`Index4`] | [`X8`], [`X16`], [`X32`], [`X64`]      |
+/// | [`Normal`]  | [`Index5`] | [`X8`], [`X16`]                        |
+/// | [`Reverse`] | [`Index4`] | [`F32`], [`F64`], [`I32`], [`U32`]     |
+/// | [`Reverse`] | [`Index5`] | [`F16`], [`I16`], [`U16`]              |
 pub trait LutTy {
     /// The raw LUT mode number for `genlut` instruction.
     fn genlut_mode(&self) -> u64;
@@ -103,6 +119,64 @@ define_lut_ty! {
     (Normal, Index5, X8) => 15,
 }
 
+/// A raw `genlut` mode number, used by wrappers choosing the mode at runtime
+pub(crate) struct RawLutMode(pub u64);
+
+impl LutTy for RawLutMode {
+    #[inline(always)]
+    fn genlut_mode(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The subset of [`LutTy`] performing reverse lookups, accepted by
+/// [`Amx::lut_reverse`](crate::Amx::lut_reverse)
+///
+/// The types implementing this trait are `(Reverse, index, value)`. See
+/// [`LutTy`] for the supported combinations.
+pub trait LutReverseTy: LutTy {
+    /// The width of each output index in bits
+    const INDEX_BITS: usize;
+    /// The size of each input element in bytes
+    const ELEMENT_SIZE: usize;
+}
+
+macro_rules! define_lut_reverse_ty {
+    ($(
+        $ty:ty => ($index_bits:expr, $element_size:expr)
+    ),*$(,)*) => {$(
+        impl LutReverseTy for $ty {
+            const INDEX_BITS: usize = $index_bits;
+            const ELEMENT_SIZE: usize = $element_size;
+        }
+    )*};
+}
+
+define_lut_reverse_ty! {
+    (Reverse, Index4, F32) => (4, 4),
+    (Reverse, Index5, F16) => (5, 2),
+    (Reverse, Index4, F64) => (4, 8),
+    (Reverse, Index4, I32) => (4, 4),
+    (Reverse, Index5, I16) => (5, 2),
+    (Reverse, Index4, U32) => (4, 4),
+    (Reverse, Index5, U16) => (5, 2),
+}
+
+/// Get the `i`-th index of the output of a reverse lookup, where each index
+/// is `index_bits` wide (see [`LutReverseTy::INDEX_BITS`]).
+///
+/// # Panics
+///
+/// Panics if the index is outside `packed`.
+#[inline]
+#[track_caller]
+pub fn lut_index(packed: &[u8], index_bits: usize, i: usize) -> u8 {
+    let bit = i * index_bits;
+    let lo = packed[bit / 8] as u16;
+    let hi = packed.get(bit / 8 + 1).copied().unwrap_or(0) as u16;
+    (((lo | (hi << 8)) >> (bit % 8)) & ((1 << index_bits) - 1)) as u8
+}
+
 #[cfg(feature = "either")]
 impl<Left: LutTy, Right: LutTy> LutTy for either::Either<Left, Right> {
     #[inline]
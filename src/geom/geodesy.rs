@@ -0,0 +1,124 @@
+//! Coordinate reference system transformations
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// The number of `f64` lanes in a register row
+const LANES: usize = 8;
+
+/// The number of `f64` output tiles in `z`
+const TILES: usize = 8;
+
+/// An affine transformation `p ↦ matrix * p + offset` in double precision
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AffineTransform {
+    pub matrix: [[f64; 3]; 3],
+    pub offset: [f64; 3],
+}
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            offset: [0.0; 3],
+        }
+    }
+}
+
+impl AffineTransform {
+    /// Construct a 7-parameter Helmert transformation in the position vector
+    /// convention:
+    ///
+    /// ```text
+    /// p' = translation + (1 + scale_ppm * 1e-6) * R * p
+    ///
+    ///     ┌   1  -rz   ry ┐
+    /// R = │  rz    1  -rx │
+    ///     └ -ry   rx    1 ┘
+    /// ```
+    ///
+    /// `rotation` is `[rx, ry, rz]` in radians. For parameters published in
+    /// the coordinate frame convention, negate `rotation`.
+    pub fn helmert(translation: [f64; 3], rotation: [f64; 3], scale_ppm: f64) -> Self {
+        let [rx, ry, rz] = rotation;
+        let m = 1.0 + scale_ppm * 1e-6;
+        Self {
+            matrix: [
+                [m, -rz * m, ry * m],
+                [rz * m, m, -rx * m],
+                [-ry * m, rx * m, m],
+            ],
+            offset: translation,
+        }
+    }
+}
+
+/// Apply `transform` to each point: `out[i] = transform.matrix * points[i] +
+/// transform.offset`.
+///
+/// The matrix product is calculated by `f64` outer products of 8 points'
+/// coordinates and the columns of `transform.matrix`, and the offset by an
+/// outer product with a row of ones, handling 64 points per step across
+/// the 8 tiles of `z`.
+///
+/// This overwrites `x[0..2]`, `y[0..4]`, and the entire contents of `z`.
+///
+/// # Panics
+///
+/// Panics if `points` and `out` have different lengths.
+pub fn transform_coords(
+    ctx: &mut (impl Amx + ?Sized),
+    transform: &AffineTransform,
+    points: &[[f64; 3]],
+    out: &mut [[f64; 3]],
+) {
+    assert_eq!(points.len(), out.len(), "length mismatch");
+    const ONES: [f64; LANES] = [1.0; LANES];
+
+    // `y[b][j]` = `matrix[j][b]` (`b < 3`) or `offset[j]` (`b = 3`)
+    let mut y = [[0f64; LANES]; 4];
+    for (j, (row, &offset)) in transform.matrix.iter().zip(&transform.offset).enumerate() {
+        for (b, &m) in row.iter().enumerate() {
+            y[b][j] = m;
+        }
+        y[3][j] = offset;
+    }
+    // Safety: Reading 64 bytes from each `[f64; 8]`
+    unsafe {
+        for (b, y) in y.iter().enumerate() {
+            ctx.load512(y.as_ptr(), YRow(b));
+        }
+        ctx.load512(ONES.as_ptr(), XRow(1));
+    }
+
+    for (points, out) in points
+        .chunks(LANES * TILES)
+        .zip(out.chunks_mut(LANES * TILES))
+    {
+        for (tile, (points, out)) in points.chunks(LANES).zip(out.chunks_mut(LANES)).enumerate() {
+            for b in 0..3 {
+                let mut x = [0f64; LANES];
+                for (x, p) in x.iter_mut().zip(points) {
+                    *x = p[b];
+                }
+                // Safety: Reading 64 bytes from `[f64; 8]`
+                unsafe { ctx.load512(x.as_ptr(), XRow(0)) };
+                ctx.outer_product_f64_xy_to_z(
+                    Some(XBytes(0)),
+                    Some(YBytes(b * 64)),
+                    ZRow(tile),
+                    b != 0,
+                );
+            }
+            ctx.outer_product_f64_xy_to_z(Some(XBytes(64)), Some(YBytes(3 * 64)), ZRow(tile), true);
+
+            // `z[j * 8 + tile][i]` = coordinate `j` of point `i`
+            let mut z = [[0f64; LANES]; 3];
+            for (j, z) in z.iter_mut().enumerate() {
+                // Safety: Writing 64 bytes to `[f64; 8]`
+                unsafe { ctx.store512(z.as_mut_ptr(), ZRow(j * TILES + tile)) };
+            }
+            for (i, out) in out.iter_mut().enumerate() {
+                *out = [z[0][i], z[1][i], z[2][i]];
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Geometry and physics kernels built on top of [`Amx`](crate::Amx)
+mod geodesy;
+mod ray;
+mod rigid;
+pub use self::{geodesy::*, ray::*, rigid::*};
@@ -0,0 +1,261 @@
+//! Ray intersection tests
+use crate::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, MatMut, fma32_vector, read_tile_row},
+    pipeline::Pipeline,
+};
+
+/// A ray `origin + t * dir`
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Ray {
+    pub origin: [f32; 3],
+    pub dir: [f32; 3],
+}
+
+/// An axis-aligned bounding box
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+/// A triangle given by its three vertices
+pub type Triangle = [[f32; 3]; 3];
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+// The Möller–Trumbore test computes `det`, `u * det`, `v * det`, and
+// `t * det` as triple products of the ray and the triangle's edges. Expanding
+// them, each becomes a sum of products of a per-triangle quantity and a
+// per-ray quantity, so they can be evaluated for 16×16 ray-triangle pairs by
+// outer products:
+//
+//     det     = -d · n
+//     u * det =  e2 · c - d · m2
+//     v * det = -e1 · c - d · m1
+//     t * det =  o · n - v0 · n
+//
+// where `e1 = v1 - v0`, `e2 = v2 - v0`, `n = e1 × e2`, `m1 = v0 × e1`,
+// `m2 = e2 × v0`, and `c = o × d`.
+
+/// The per-triangle quantities: `[n, e1, e2, m1, m2]` (3 vectors each)
+/// followed by `v0 · n`
+const TRI_VECS: usize = 16;
+
+/// The per-ray quantities: `[-d, c, -c, o]` (3 vectors each) followed by
+/// `-1`
+const RAY_VECS: usize = 13;
+
+/// The terms of each output tile as `(tile, triangle vector, ray vector)`
+const TERMS: [(usize, usize, usize); 19] = [
+    // det
+    (0, 0, 0),
+    (0, 1, 1),
+    (0, 2, 2),
+    // u * det
+    (1, 6, 3),
+    (1, 7, 4),
+    (1, 8, 5),
+    (1, 12, 0),
+    (1, 13, 1),
+    (1, 14, 2),
+    // v * det
+    (2, 3, 6),
+    (2, 4, 7),
+    (2, 5, 8),
+    (2, 9, 0),
+    (2, 10, 1),
+    (2, 11, 2),
+    // t * det
+    (3, 0, 9),
+    (3, 1, 10),
+    (3, 2, 11),
+    (3, 15, 12),
+];
+
+/// Calculate the ray parameter `t` of the intersection of every ray and
+/// every triangle: `t[r][k]` is the distance (in units of `rays[r].dir`)
+/// to `triangles[k]`, or `f32::INFINITY` if the ray misses it. Hits behind
+/// the origin (`t <= 0`) and rays parallel to the triangle count as
+/// misses.
+///
+/// This implements the Möller–Trumbore test, with the products for 16 rays
+/// and 16 triangles issued as `f32` outer products per step.
+///
+/// This overwrites the entire contents of `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if `t` is not `rays.len()`×`triangles.len()`.
+pub fn ray_triangle_intersect(
+    ctx: &mut (impl Amx + ?Sized),
+    rays: &[Ray],
+    triangles: &[Triangle],
+    mut t: MatMut<'_, f32>,
+) {
+    assert_eq!(
+        (t.rows(), t.cols()),
+        (rays.len(), triangles.len()),
+        "output shape mismatch"
+    );
+
+    // `tri_pack[k0 / 16][v][kk]` is the `v`-th quantity of `triangles[k0 + kk]`
+    let tri_pack: Vec<[[f32; F32_LANES]; TRI_VECS]> = triangles
+        .chunks(F32_LANES)
+        .map(|chunk| {
+            let mut pack = [[0f32; F32_LANES]; TRI_VECS];
+            for (kk, &[v0, v1, v2]) in chunk.iter().enumerate() {
+                let (e1, e2) = (sub(v1, v0), sub(v2, v0));
+                let n = cross(e1, e2);
+                let vecs = [n, e1, e2, cross(v0, e1), cross(e2, v0)];
+                for (v, value) in vecs.iter().flatten().enumerate() {
+                    pack[v][kk] = *value;
+                }
+                pack[15][kk] = dot(v0, n);
+            }
+            pack
+        })
+        .collect();
+
+    let mut ray_pack = [[0f32; F32_LANES]; RAY_VECS];
+    for r0 in (0..rays.len()).step_by(F32_LANES) {
+        let nr = (rays.len() - r0).min(F32_LANES);
+        for row in &mut ray_pack {
+            row.fill(0.0);
+        }
+        for (rr, ray) in rays[r0..r0 + nr].iter().enumerate() {
+            let c = cross(ray.origin, ray.dir);
+            for a in 0..3 {
+                ray_pack[a][rr] = -ray.dir[a];
+                ray_pack[3 + a][rr] = c[a];
+                ray_pack[6 + a][rr] = -c[a];
+                ray_pack[9 + a][rr] = ray.origin[a];
+            }
+            ray_pack[12][rr] = -1.0;
+        }
+
+        for (k0, tri_pack) in (0..triangles.len()).step_by(F32_LANES).zip(&tri_pack) {
+            let nk = (triangles.len() - k0).min(F32_LANES);
+            Pipeline::deepest(1, 1).run(
+                ctx,
+                TERMS.len(),
+                |ctx, i, slot| {
+                    let (_, tv, rv) = TERMS[i];
+                    // Safety: Reading 64 bytes from each `[f32; 16]`
+                    unsafe {
+                        ctx.load512(tri_pack[tv].as_ptr(), slot.x_row(0));
+                        ctx.load512(ray_pack[rv].as_ptr(), slot.y_row(0));
+                    }
+                },
+                |ctx, i, slot| {
+                    let (tile, _, _) = TERMS[i];
+                    let first = i == 0 || TERMS[i - 1].0 != tile;
+                    ctx.outer_product_f32_xy_to_z(
+                        Some(slot.x_bytes(0)),
+                        Some(slot.y_bytes(0)),
+                        ZRow(tile),
+                        !first,
+                    );
+                },
+            );
+
+            for rr in 0..nr {
+                let det = read_tile_row(ctx, 0, rr);
+                let u = read_tile_row(ctx, 1, rr);
+                let v = read_tile_row(ctx, 2, rr);
+                let dist = read_tile_row(ctx, 3, rr);
+                let out = &mut t.row_mut(r0 + rr)[k0..k0 + nk];
+                for (kk, out) in out.iter_mut().enumerate() {
+                    let inv_det = det[kk].recip();
+                    let (u, v) = (u[kk] * inv_det, v[kk] * inv_det);
+                    let dist = dist[kk] * inv_det;
+                    let hit = det[kk] != 0.0 && u >= 0.0 && v >= 0.0 && u + v <= 1.0 && dist > 0.0;
+                    *out = if hit { dist } else { f32::INFINITY };
+                }
+            }
+        }
+    }
+}
+
+/// Calculate the ray parameter at which each ray enters `aabb`:
+/// `t[r]` is `max(t_enter, 0)` if `rays[r]` hits the box at a non-negative
+/// `t`, and `f32::INFINITY` otherwise.
+///
+/// This implements the slab test with 16 rays per step, mapping rays to the
+/// lanes of vector-mode `fma32`. Zero direction components are replaced by
+/// `±1e-30` to avoid indeterminate forms.
+///
+/// This overwrites `x[0..2]`, `y[0..3]`, and `z[0..6]`.
+///
+/// # Panics
+///
+/// Panics if `rays` and `t` have different lengths.
+pub fn ray_aabb_intersect(ctx: &mut (impl Amx + ?Sized), rays: &[Ray], aabb: &Aabb, t: &mut [f32]) {
+    assert_eq!(rays.len(), t.len(), "length mismatch");
+    const ONES: [f32; F32_LANES] = [1.0; F32_LANES];
+    // Safety: Reading 64 bytes from `[f32; 16]`
+    unsafe { ctx.load512(ONES.as_ptr(), YRow(2)) };
+
+    for (rays, t) in rays.chunks(F32_LANES).zip(t.chunks_mut(F32_LANES)) {
+        // `pack[a]` = `[1 / d[a], -o[a] / d[a]]`
+        let mut pack = [[[0f32; F32_LANES]; 2]; 3];
+        for (rr, ray) in rays.iter().enumerate() {
+            for (a, pack) in pack.iter_mut().enumerate() {
+                let d = if ray.dir[a] == 0.0 {
+                    1e-30f32.copysign(ray.dir[a])
+                } else {
+                    ray.dir[a]
+                };
+                pack[0][rr] = d.recip();
+                pack[1][rr] = -ray.origin[a] / d;
+            }
+        }
+
+        // `z[a * 2 + b]` = `(bound - o[a]) / d[a]` where `bound` is
+        // `aabb.min[a]` (`b = 0`) or `aabb.max[a]` (`b = 1`)
+        for (a, pack) in pack.iter().enumerate() {
+            let bounds = [[aabb.min[a]; F32_LANES], [aabb.max[a]; F32_LANES]];
+            // Safety: Reading 64 bytes from each `[f32; 16]`
+            unsafe {
+                ctx.load512(pack[0].as_ptr(), XRow(0));
+                ctx.load512(pack[1].as_ptr(), XRow(1));
+                ctx.load512(bounds[0].as_ptr(), YRow(0));
+                ctx.load512(bounds[1].as_ptr(), YRow(1));
+            }
+            for b in 0..2 {
+                let z = ZRow(a * 2 + b);
+                fma32_vector(ctx, XBytes(0), YBytes(b * 64), z, false, false);
+                fma32_vector(ctx, XBytes(64), YBytes(2 * 64), z, true, false);
+            }
+        }
+
+        let mut slabs = [[0f32; F32_LANES]; 6];
+        for (i, slab) in slabs.iter_mut().enumerate() {
+            // Safety: Writing 64 bytes to `[f32; 16]`
+            unsafe { ctx.store512(slab.as_mut_ptr(), ZRow(i)) };
+        }
+        for (rr, t) in t.iter_mut().enumerate() {
+            let (mut enter, mut exit) = (0f32, f32::INFINITY);
+            for a in 0..3 {
+                let (t0, t1) = (slabs[a * 2][rr], slabs[a * 2 + 1][rr]);
+                enter = enter.max(t0.min(t1));
+                exit = exit.min(t0.max(t1));
+            }
+            *t = if enter <= exit { enter } else { f32::INFINITY };
+        }
+    }
+}
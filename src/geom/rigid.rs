@@ -0,0 +1,231 @@
+//! Rigid-body kinematics
+use crate::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, fma32_vector},
+};
+
+/// A 3×3 matrix in row-major order
+pub type Mat3 = [[f32; 3]; 3];
+
+/// A rigid transformation `p ↦ rotation * p + translation`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RigidTransform {
+    pub rotation: Mat3,
+    pub translation: [f32; 3],
+}
+
+impl Default for RigidTransform {
+    fn default() -> Self {
+        Self {
+            rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            translation: [0.0; 3],
+        }
+    }
+}
+
+/// 16 3×3 matrices in structure-of-arrays form: `m[i * 3 + j][lane]`
+type Mat3x16 = [[f32; F32_LANES]; 9];
+
+/// 16 3D vectors in structure-of-arrays form: `v[i][lane]`
+type Vec3x16 = [[f32; F32_LANES]; 3];
+
+fn pack_mat3<'a>(src: impl Iterator<Item = &'a Mat3>) -> Mat3x16 {
+    let mut out = [[0f32; F32_LANES]; 9];
+    for (lane, m) in src.enumerate() {
+        for (i, &x) in m.iter().flatten().enumerate() {
+            out[i][lane] = x;
+        }
+    }
+    out
+}
+
+fn pack_vec3<'a>(src: impl Iterator<Item = &'a [f32; 3]>) -> Vec3x16 {
+    let mut out = [[0f32; F32_LANES]; 3];
+    for (lane, v) in src.enumerate() {
+        for (i, &x) in v.iter().enumerate() {
+            out[i][lane] = x;
+        }
+    }
+    out
+}
+
+/// Calculate `m * v (+ t)` for each lane.
+///
+/// This overwrites `x[0..4]`, `y[0..4]`, and `z[0..3]`.
+fn mat_vec(
+    ctx: &mut (impl Amx + ?Sized),
+    m: &Mat3x16,
+    v: &Vec3x16,
+    t: Option<&Vec3x16>,
+) -> Vec3x16 {
+    const ONES: [f32; F32_LANES] = [1.0; F32_LANES];
+    // Safety: Reading 64 bytes from each `[f32; 16]`
+    unsafe {
+        for (j, v) in v.iter().enumerate() {
+            ctx.load512(v.as_ptr(), YRow(j));
+        }
+        ctx.load512(ONES.as_ptr(), YRow(3));
+    }
+
+    let mut out = [[0f32; F32_LANES]; 3];
+    for (i, out) in out.iter_mut().enumerate() {
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            for j in 0..3 {
+                ctx.load512(m[i * 3 + j].as_ptr(), XRow(j));
+            }
+            if let Some(t) = t {
+                ctx.load512(t[i].as_ptr(), XRow(3));
+            }
+        }
+        for j in 0..3 {
+            fma32_vector(ctx, XBytes(j * 64), YBytes(j * 64), ZRow(i), j != 0, false);
+        }
+        if t.is_some() {
+            fma32_vector(ctx, XBytes(3 * 64), YBytes(3 * 64), ZRow(i), true, false);
+        }
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(out.as_mut_ptr(), ZRow(i)) };
+    }
+    out
+}
+
+/// Calculate `a * b` (or `a * bᵀ` if `transpose_b` is set) for each lane.
+///
+/// This overwrites `x[0..3]`, `y[0..3]`, and `z[0..9]`.
+fn mat_mul(ctx: &mut (impl Amx + ?Sized), a: &Mat3x16, b: &Mat3x16, transpose_b: bool) -> Mat3x16 {
+    for i in 0..3 {
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            for k in 0..3 {
+                ctx.load512(a[i * 3 + k].as_ptr(), XRow(k));
+            }
+        }
+        for j in 0..3 {
+            // Safety: Reading 64 bytes from each `[f32; 16]`
+            unsafe {
+                for k in 0..3 {
+                    let b_kj = if transpose_b { j * 3 + k } else { k * 3 + j };
+                    ctx.load512(b[b_kj].as_ptr(), YRow(k));
+                }
+            }
+            for k in 0..3 {
+                fma32_vector(
+                    ctx,
+                    XBytes(k * 64),
+                    YBytes(k * 64),
+                    ZRow(i * 3 + j),
+                    k != 0,
+                    false,
+                );
+            }
+        }
+    }
+
+    let mut out = [[0f32; F32_LANES]; 9];
+    for (i, out) in out.iter_mut().enumerate() {
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(out.as_mut_ptr(), ZRow(i)) };
+    }
+    out
+}
+
+fn unpack_vec3(src: &Vec3x16, out: &mut [[f32; 3]]) {
+    for (lane, out) in out.iter_mut().enumerate() {
+        *out = std::array::from_fn(|i| src[i][lane]);
+    }
+}
+
+fn unpack_mat3(src: &Mat3x16, out: &mut [Mat3]) {
+    for (lane, out) in out.iter_mut().enumerate() {
+        *out = std::array::from_fn(|i| std::array::from_fn(|j| src[i * 3 + j][lane]));
+    }
+}
+
+/// Transform each point by the corresponding rigid transformation:
+/// `out[i] = transforms[i].rotation * points[i] + transforms[i].translation`.
+///
+/// Points are mapped to the lanes of vector-mode `fma32`, 16 at a time. To
+/// transform several contact points of the same body, repeat its transform.
+///
+/// This overwrites `x[0..4]`, `y[0..4]`, and `z[0..3]`.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn transform_points(
+    ctx: &mut (impl Amx + ?Sized),
+    transforms: &[RigidTransform],
+    points: &[[f32; 3]],
+    out: &mut [[f32; 3]],
+) {
+    assert_eq!(transforms.len(), points.len(), "length mismatch");
+    assert_eq!(points.len(), out.len(), "length mismatch");
+    for ((transforms, points), out) in transforms
+        .chunks(F32_LANES)
+        .zip(points.chunks(F32_LANES))
+        .zip(out.chunks_mut(F32_LANES))
+    {
+        let r = pack_mat3(transforms.iter().map(|t| &t.rotation));
+        let t = pack_vec3(transforms.iter().map(|t| &t.translation));
+        let p = pack_vec3(points.iter());
+        unpack_vec3(&mat_vec(ctx, &r, &p, Some(&t)), out);
+    }
+}
+
+/// Multiply each vector by the corresponding 3×3 matrix:
+/// `out[i] = matrices[i] * v[i]`, e.g., to convert angular momenta to
+/// angular velocities with inverse inertia tensors.
+///
+/// This overwrites `x[0..3]`, `y[0..4]`, and `z[0..3]`.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn apply_mat3(
+    ctx: &mut (impl Amx + ?Sized),
+    matrices: &[Mat3],
+    v: &[[f32; 3]],
+    out: &mut [[f32; 3]],
+) {
+    assert_eq!(matrices.len(), v.len(), "length mismatch");
+    assert_eq!(v.len(), out.len(), "length mismatch");
+    for ((matrices, v), out) in matrices
+        .chunks(F32_LANES)
+        .zip(v.chunks(F32_LANES))
+        .zip(out.chunks_mut(F32_LANES))
+    {
+        let m = pack_mat3(matrices.iter());
+        let v = pack_vec3(v.iter());
+        unpack_vec3(&mat_vec(ctx, &m, &v, None), out);
+    }
+}
+
+/// Rotate each body-space inertia tensor (or its inverse) to world space:
+/// `out[i] = rotations[i] * inertia[i] * rotations[i]ᵀ`.
+///
+/// Bodies are mapped to the lanes of vector-mode `fma32`, 16 at a time.
+///
+/// This overwrites `x[0..3]`, `y[0..3]`, and `z[0..9]`.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn world_inertia(
+    ctx: &mut (impl Amx + ?Sized),
+    rotations: &[Mat3],
+    inertia: &[Mat3],
+    out: &mut [Mat3],
+) {
+    assert_eq!(rotations.len(), inertia.len(), "length mismatch");
+    assert_eq!(inertia.len(), out.len(), "length mismatch");
+    for ((rotations, inertia), out) in rotations
+        .chunks(F32_LANES)
+        .zip(inertia.chunks(F32_LANES))
+        .zip(out.chunks_mut(F32_LANES))
+    {
+        let r = pack_mat3(rotations.iter());
+        let ri = mat_mul(ctx, &r, &pack_mat3(inertia.iter()), false);
+        unpack_mat3(&mat_mul(ctx, &ri, &r, true), out);
+    }
+}
@@ -0,0 +1,97 @@
+//! Moving register contents between threads
+use crate::{Amx, AmxEmuCtx, AmxFallbackCtx, AmxState, NewAmxCtxError};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An owned AMX context accepted by [`SendableAmxState::take`]
+///
+/// This trait is sealed and implemented for [`AmxCtx`](crate::AmxCtx),
+/// [`AmxFallbackCtx`], and [`AmxEmuCtx`]. Borrowed contexts are excluded
+/// because `take` must drop the context to disable AMX.
+pub trait OwnedAmxCtx: sealed::Sealed + Amx {}
+
+#[cfg(any(doc, target_arch = "aarch64"))]
+impl sealed::Sealed for crate::AmxCtx {}
+#[cfg(any(doc, target_arch = "aarch64"))]
+impl OwnedAmxCtx for crate::AmxCtx {}
+
+impl sealed::Sealed for AmxFallbackCtx {}
+impl OwnedAmxCtx for AmxFallbackCtx {}
+
+impl sealed::Sealed for AmxEmuCtx {}
+impl OwnedAmxCtx for AmxEmuCtx {}
+
+/// The register contents of a released AMX context, which can be sent to
+/// another thread and loaded into a new context there
+///
+/// AMX is enabled per thread, so [`AmxCtx`](crate::AmxCtx) is neither `Send`
+/// nor `Sync`. To continue a computation on another thread, convert the
+/// context to `SendableAmxState` by [`Self::take`] (which disables AMX on the
+/// source thread), move it, and rehydrate it by [`Self::into_ctx`] or
+/// [`Self::into_fallback_ctx`].
+///
+/// ```rust
+/// use amx::{Amx, AmxFallbackCtx, SendableAmxState, XRow};
+///
+/// let mut ctx = AmxFallbackCtx::try_new().unwrap();
+/// ctx.load512_slice(&[3u8; 64], XRow(1));
+/// let state = SendableAmxState::take(ctx);
+///
+/// std::thread::spawn(move || {
+///     let mut ctx = state.into_fallback_ctx().unwrap();
+///     assert_eq!(ctx.read_x()[64], 3);
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+///
+/// The context is consumed, so a borrowed one is rejected:
+///
+/// ```rust,compile_fail
+/// use amx::{AmxFallbackCtx, SendableAmxState};
+///
+/// let mut ctx = AmxFallbackCtx::try_new().unwrap();
+/// let state = SendableAmxState::take(&mut ctx);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendableAmxState(AmxState);
+
+impl SendableAmxState {
+    /// Capture the registers of `ctx` by [`Amx::snapshot`] and drop `ctx`,
+    /// releasing the current thread's AMX context.
+    pub fn take(mut ctx: impl OwnedAmxCtx) -> Self {
+        Self(ctx.snapshot())
+    }
+
+    /// Enable AMX for the current thread and load the captured registers.
+    ///
+    /// Fails if [`AmxCtx::new`](crate::AmxCtx::new) fails.
+    #[cfg(any(doc, target_arch = "aarch64"))]
+    pub fn into_ctx(self) -> Result<crate::AmxCtx, NewAmxCtxError> {
+        let mut ctx = crate::AmxCtx::new()?;
+        ctx.restore(&self.0);
+        Ok(ctx)
+    }
+
+    /// Like [`Self::into_ctx`], but falls back to the emulator if AMX is not
+    /// supported. See [`AmxFallbackCtx::try_new`].
+    pub fn into_fallback_ctx(self) -> Result<AmxFallbackCtx, NewAmxCtxError> {
+        let mut ctx = AmxFallbackCtx::try_new()?;
+        ctx.restore(&self.0);
+        Ok(ctx)
+    }
+
+    /// Get the captured registers.
+    #[inline]
+    pub fn state(&self) -> &AmxState {
+        &self.0
+    }
+
+    /// Unwrap the captured registers.
+    #[inline]
+    pub fn into_state(self) -> AmxState {
+        self.0
+    }
+}
@@ -0,0 +1,152 @@
+//! Executable memory management
+use std::{io, ptr::NonNull};
+
+use super::JitBuilder;
+use crate::nativeops::AmxOps;
+
+/// Assembled AMX code placed in executable memory. Created by
+/// [`JitBuilder::finish`].
+#[derive(Debug)]
+pub struct JitKernel {
+    ptr: NonNull<u8>,
+    len: usize,
+    num_args: usize,
+}
+
+// Safety: The mapping is immutable after construction
+unsafe impl Send for JitKernel {}
+unsafe impl Sync for JitKernel {}
+
+impl JitBuilder {
+    /// Assemble the recorded instructions and place them in executable
+    /// memory.
+    ///
+    /// On macOS, the memory is mapped with `MAP_JIT` and written while the
+    /// current thread's JIT write protection is disabled. Elsewhere, the
+    /// memory is mapped writable and then remapped read-only and executable.
+    pub fn finish(&self) -> io::Result<JitKernel> {
+        let code = self.assemble();
+        let num_args = self
+            .insts
+            .iter()
+            .filter_map(|inst| inst.arg)
+            .map(|arg| arg as usize + 1)
+            .max()
+            .unwrap_or(0);
+        let len = code.len() * 4;
+
+        // Safety: Creating a new mapping doesn't affect existing ones
+        unsafe {
+            let ptr = map(len)?;
+            std::ptr::copy_nonoverlapping(code.as_ptr().cast::<u8>(), ptr.as_ptr(), len);
+            if let Err(e) = seal(ptr, len) {
+                libc::munmap(ptr.as_ptr().cast(), len);
+                return Err(e);
+            }
+            Ok(JitKernel { ptr, len, num_args })
+        }
+    }
+}
+
+impl JitKernel {
+    /// Get the number of arguments the code expects.
+    pub fn num_args(&self) -> usize {
+        self.num_args
+    }
+
+    /// Execute the code.
+    ///
+    /// # Safety
+    ///
+    /// The memory operations recorded by [`JitBuilder::mem`] must access
+    /// valid memory when `args` is given.
+    #[track_caller]
+    pub unsafe fn call(&self, _ops: &mut AmxOps<'_>, args: &[u64]) {
+        assert!(args.len() >= self.num_args, "not enough arguments");
+        // Safety: `ptr` points to a function assembled by `JitBuilder`, and
+        //         AMX is enabled as proven by `_ops`
+        unsafe {
+            let f: unsafe extern "C" fn(*const u64) = std::mem::transmute(self.ptr.as_ptr());
+            f(args.as_ptr());
+        }
+    }
+}
+
+impl Drop for JitKernel {
+    fn drop(&mut self) {
+        // Safety: We own the mapping
+        unsafe { libc::munmap(self.ptr.as_ptr().cast(), self.len) };
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "macos")] {
+        unsafe extern "C" {
+            fn sys_icache_invalidate(start: *mut libc::c_void, len: libc::size_t);
+        }
+
+        unsafe fn map(len: usize) -> io::Result<NonNull<u8>> {
+            // Safety: Upheld by the caller
+            unsafe {
+                let ptr = libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC,
+                    libc::MAP_PRIVATE | libc::MAP_ANON | libc::MAP_JIT,
+                    -1,
+                    0,
+                );
+                if ptr == libc::MAP_FAILED {
+                    return Err(io::Error::last_os_error());
+                }
+                // Make the mapping writable for the current thread
+                libc::pthread_jit_write_protect_np(0);
+                Ok(NonNull::new_unchecked(ptr.cast()))
+            }
+        }
+
+        unsafe fn seal(ptr: NonNull<u8>, len: usize) -> io::Result<()> {
+            // Safety: Upheld by the caller
+            unsafe {
+                libc::pthread_jit_write_protect_np(1);
+                sys_icache_invalidate(ptr.as_ptr().cast(), len);
+            }
+            Ok(())
+        }
+    } else {
+        unsafe extern "C" {
+            fn __clear_cache(start: *mut libc::c_char, end: *mut libc::c_char);
+        }
+
+        unsafe fn map(len: usize) -> io::Result<NonNull<u8>> {
+            // Safety: Upheld by the caller
+            unsafe {
+                let ptr = libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_PRIVATE | libc::MAP_ANON,
+                    -1,
+                    0,
+                );
+                if ptr == libc::MAP_FAILED {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(NonNull::new_unchecked(ptr.cast()))
+            }
+        }
+
+        unsafe fn seal(ptr: NonNull<u8>, len: usize) -> io::Result<()> {
+            // Safety: Upheld by the caller
+            unsafe {
+                if libc::mprotect(ptr.as_ptr().cast(), len, libc::PROT_READ | libc::PROT_EXEC) != 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                let start = ptr.as_ptr().cast::<libc::c_char>();
+                __clear_cache(start, start.add(len));
+            }
+            Ok(())
+        }
+    }
+}
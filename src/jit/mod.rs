@@ -0,0 +1,284 @@
+//! Runtime assembler for AMX instruction sequences
+//!
+//! [`JitBuilder`] records a sequence of AMX instructions whose operands are
+//! either constants or derived from pointers supplied at call time, and
+//! assembles them into straight-line machine code. This is useful when the
+//! shape of a kernel (e.g., its unroll factor) is only known at runtime, in
+//! which case the per-instruction overhead of going through [`AmxOps`] can be
+//! avoided by generating the code on the fly.
+//!
+//! The generated function has the signature
+//! `unsafe extern "C" fn(args: *const u64)`. A memory operation's address is
+//! computed as `args[arg] + offset`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use amx::jit::{JitBuilder, JitOp};
+//!
+//! let mut b = JitBuilder::new();
+//! for i in 0..4 {
+//!     // Load `x[i]` from `args[0] + i * 64`
+//!     b.mem(JitOp::Ldx, 0, i * 64, (i as u64) << 56);
+//! }
+//! b.op(JitOp::Fma32, 0);
+//! assert_eq!(b.len(), 5);
+//! let code = b.assemble();
+//! assert_eq!(code.last(), Some(&0xd65f03c0)); // `ret`
+//! ```
+use crate::ops::AmxOps;
+
+/// An AMX instruction that can be emitted by [`JitBuilder`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum JitOp {
+    Ldx = 0,
+    Ldy = 1,
+    Stx = 2,
+    Sty = 3,
+    Ldz = 4,
+    Stz = 5,
+    Ldzi = 6,
+    Stzi = 7,
+    Extrx = 8,
+    Extry = 9,
+    Fma64 = 10,
+    Fms64 = 11,
+    Fma32 = 12,
+    Fms32 = 13,
+    Mac16 = 14,
+    Fma16 = 15,
+    Fms16 = 16,
+    Vecint = 18,
+    Vecfp = 19,
+    Matint = 20,
+    Matfp = 21,
+    Genlut = 22,
+}
+
+impl JitOp {
+    /// Get the instruction with the opcode `opcode`, the 5-bit field of the
+    /// instruction word that selects the operation. Returns `None` for the
+    /// opcodes without a corresponding `JitOp`.
+    pub fn from_opcode(opcode: u8) -> Option<Self> {
+        Some(match opcode {
+            0 => Self::Ldx,
+            1 => Self::Ldy,
+            2 => Self::Stx,
+            3 => Self::Sty,
+            4 => Self::Ldz,
+            5 => Self::Stz,
+            6 => Self::Ldzi,
+            7 => Self::Stzi,
+            8 => Self::Extrx,
+            9 => Self::Extry,
+            10 => Self::Fma64,
+            11 => Self::Fms64,
+            12 => Self::Fma32,
+            13 => Self::Fms32,
+            14 => Self::Mac16,
+            15 => Self::Fma16,
+            16 => Self::Fms16,
+            18 => Self::Vecint,
+            19 => Self::Vecfp,
+            20 => Self::Matint,
+            21 => Self::Matfp,
+            22 => Self::Genlut,
+            _ => return None,
+        })
+    }
+
+    /// Get a flag indicating whether this instruction accesses memory.
+    #[inline]
+    pub fn is_mem(self) -> bool {
+        (self as u8) < 8
+    }
+
+    /// Get the instruction's mnemonic, e.g., `"fma32"`.
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Self::Ldx => "ldx",
+            Self::Ldy => "ldy",
+            Self::Stx => "stx",
+            Self::Sty => "sty",
+            Self::Ldz => "ldz",
+            Self::Stz => "stz",
+            Self::Ldzi => "ldzi",
+            Self::Stzi => "stzi",
+            Self::Extrx => "extrx",
+            Self::Extry => "extry",
+            Self::Fma64 => "fma64",
+            Self::Fms64 => "fms64",
+            Self::Fma32 => "fma32",
+            Self::Fms32 => "fms32",
+            Self::Mac16 => "mac16",
+            Self::Fma16 => "fma16",
+            Self::Fms16 => "fms16",
+            Self::Vecint => "vecint",
+            Self::Vecfp => "vecfp",
+            Self::Matint => "matint",
+            Self::Matfp => "matfp",
+            Self::Genlut => "genlut",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Inst {
+    op: JitOp,
+    /// The index into `args` for a memory operation
+    arg: Option<u16>,
+    operand: u64,
+}
+
+/// Records AMX instructions to be assembled by [`Self::assemble`] or
+/// [`Self::finish`].
+#[derive(Debug, Default, Clone)]
+pub struct JitBuilder {
+    insts: Vec<Inst>,
+}
+
+/// The register holding `args`
+const REG_ARGS: u32 = 0;
+/// The register holding an operand being constructed
+const REG_OPERAND: u32 = 9;
+/// The register holding a loaded pointer
+const REG_PTR: u32 = 10;
+
+impl JitBuilder {
+    /// Construct an empty `JitBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of recorded AMX instructions.
+    pub fn len(&self) -> usize {
+        self.insts.len()
+    }
+
+    /// Get a flag indicating whether no instructions have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.insts.is_empty()
+    }
+
+    /// Record a non-memory instruction with a constant operand.
+    #[track_caller]
+    pub fn op(&mut self, op: JitOp, operand: u64) -> &mut Self {
+        assert!(!op.is_mem(), "{op:?} accesses memory; use `mem` instead");
+        self.insts.push(Inst {
+            op,
+            arg: None,
+            operand,
+        });
+        self
+    }
+
+    /// Record a memory instruction accessing `args[arg] + offset`. `operand`
+    /// provides the remaining operand bits (e.g., the register row in bits
+    /// 56–61) and must not overlap with the address bits.
+    ///
+    /// `arg` must be in range `0..4096`.
+    #[track_caller]
+    pub fn mem(&mut self, op: JitOp, arg: usize, offset: usize, operand: u64) -> &mut Self {
+        assert!(
+            op.is_mem(),
+            "{op:?} does not access memory; use `op` instead"
+        );
+        assert!(arg < 4096, "argument index out of range");
+        debug_assert_eq!(operand & 0x00ff_ffff_ffff_ffff, 0);
+        self.insts.push(Inst {
+            op,
+            arg: Some(arg as u16),
+            operand: operand.wrapping_add(offset as u64),
+        });
+        self
+    }
+
+    /// Assemble the recorded instructions into AArch64 machine code.
+    pub fn assemble(&self) -> Vec<u32> {
+        let mut code = Vec::with_capacity(self.insts.len() * 4 + 1);
+        for inst in &self.insts {
+            // `mov{z,k} x9, ...`
+            let mut first = true;
+            for hw in 0..4 {
+                let imm = (inst.operand >> (hw * 16)) as u16 as u32;
+                if imm != 0 || (hw == 3 && first) {
+                    let base = if first { 0xd280_0000 } else { 0xf280_0000 };
+                    code.push(base | (hw << 21) | (imm << 5) | REG_OPERAND);
+                    first = false;
+                }
+            }
+
+            if let Some(arg) = inst.arg {
+                // `ldr x10, [x0, #arg * 8]`
+                code.push(0xf940_0000 | ((arg as u32) << 10) | (REG_ARGS << 5) | REG_PTR);
+                // `add x9, x9, x10`
+                code.push(0x8b00_0000 | (REG_PTR << 16) | (REG_OPERAND << 5) | REG_OPERAND);
+            }
+
+            code.push(0x0020_1000 | ((inst.op as u32) << 5) | REG_OPERAND);
+        }
+        // `ret`
+        code.push(0xd65f_03c0);
+        code
+    }
+
+    /// Execute the recorded instructions through [`AmxOps`]. This produces
+    /// the same effects as calling the assembled code, but works with any
+    /// implementation of `AmxOps`, including the emulator.
+    ///
+    /// # Safety
+    ///
+    /// `args` must contain all referenced arguments, and the memory
+    /// operations must access valid memory.
+    pub unsafe fn replay(&self, ops: &mut (impl AmxOps + ?Sized), args: &[u64]) {
+        for inst in &self.insts {
+            let Some(arg) = inst.arg else {
+                let x = inst.operand;
+                match inst.op {
+                    JitOp::Extrx => ops.extrx(x),
+                    JitOp::Extry => ops.extry(x),
+                    JitOp::Fma64 => ops.fma64(x),
+                    JitOp::Fms64 => ops.fms64(x),
+                    JitOp::Fma32 => ops.fma32(x),
+                    JitOp::Fms32 => ops.fms32(x),
+                    JitOp::Mac16 => ops.mac16(x),
+                    JitOp::Fma16 => ops.fma16(x),
+                    JitOp::Fms16 => ops.fms16(x),
+                    JitOp::Vecint => ops.vecint(x),
+                    JitOp::Vecfp => ops.vecfp(x),
+                    JitOp::Matint => ops.matint(x),
+                    JitOp::Matfp => ops.matfp(x),
+                    JitOp::Genlut => ops.genlut(x),
+                    _ => unreachable!(),
+                }
+                continue;
+            };
+
+            let x = inst.operand & !0x00ff_ffff_ffff_ffff;
+            let ptr = args[arg as usize].wrapping_add(inst.operand & 0x00ff_ffff_ffff_ffff) as usize
+                as *mut ();
+            // Safety: Upheld by the caller
+            unsafe {
+                match inst.op {
+                    JitOp::Ldx => ops.ldx(x, ptr),
+                    JitOp::Ldy => ops.ldy(x, ptr),
+                    JitOp::Stx => ops.stx(x, ptr),
+                    JitOp::Sty => ops.sty(x, ptr),
+                    JitOp::Ldz => ops.ldz(x, ptr),
+                    JitOp::Stz => ops.stz(x, ptr),
+                    JitOp::Ldzi => ops.ldzi(x, ptr),
+                    JitOp::Stzi => ops.stzi(x, ptr),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(doc, target_arch = "aarch64"))] {
+        mod exec;
+        pub use self::exec::JitKernel;
+    }
+}
@@ -0,0 +1,122 @@
+//! A declarative mini-language for straight-line AMX kernels
+
+/// Expand a compact kernel description into straight-line [`Amx`] method
+/// calls.
+///
+/// The first token names the context, which must be a variable on which the
+/// [`Amx`] methods can be called (`amx::prelude::*` must be in scope). It is
+/// followed by a block of statements:
+///
+/// | Statement                          | Expansion                                                  |
+/// | ---------------------------------- | ---------------------------------------------------------- |
+/// | `load x[ROW] <- PTR;`              | `ctx.load512(PTR, XRow(ROW))` (also `y`, `z`)              |
+/// | `store z[ROW] -> PTR;`             | `ctx.store512(PTR, ZRow(ROW))` (also `x`, `y`)             |
+/// | `i16 z[ROW] += x(XOFF) * y(YOFF);` | `ctx.outer_product_i16_xy_to_z(Some(XBytes(XOFF)), Some(YBytes(YOFF)), ZRow(ROW), true)` |
+/// | `i16 z[ROW] = x(XOFF) * y(YOFF);`  | Same as above, but with `accumulate = false`               |
+/// | `f32 z[ROW] += x(XOFF) * y(YOFF);` | Same as above, but with `outer_product_f32_xy_to_z`        |
+/// | `repeat N { ... }`                 | The enclosed statements, repeated `N` times                |
+///
+/// Note that `x[...]`/`y[...]` specify a register row, while `x(...)`/
+/// `y(...)` specify a byte offset. `N` must be an integer literal in range
+/// `1..=16` or one of `32` and `64`.
+///
+/// Loads and stores expand to calls to `unsafe` methods, so a kernel
+/// including them must be placed in an `unsafe` block.
+///
+/// # Example
+///
+/// ```rust
+/// use amx::prelude::*;
+///
+/// fn kernel(ctx: &mut impl amx::Amx, x: &[i16; 32], y: &[i16; 32]) {
+///     unsafe {
+///         amx::amx_kernel!(ctx => {
+///             load x[0] <- x.as_ptr();
+///             load y[0] <- y.as_ptr();
+///             i16 z[0] = x(0) * y(0);
+///             repeat 8 {
+///                 i16 z[1] += x(0) * y(0);
+///                 i16 z[0] += x(0) * y(0);
+///             }
+///         });
+///     }
+/// }
+/// ```
+///
+/// [`Amx`]: crate::Amx
+#[macro_export]
+macro_rules! amx_kernel {
+    ($ctx:ident => { $($body:tt)* }) => {
+        $crate::amx_kernel!(@stmts $ctx; $($body)*)
+    };
+
+    // Statements
+    (@stmts $ctx:ident;) => {};
+    (@stmts $ctx:ident; repeat $n:tt { $($inner:tt)* } $($rest:tt)*) => {
+        $crate::amx_kernel!(@repeat $n $ctx; $($inner)*);
+        $crate::amx_kernel!(@stmts $ctx; $($rest)*);
+    };
+    (@stmts $ctx:ident; load $reg:ident [$row:expr] <- $ptr:expr; $($rest:tt)*) => {
+        $ctx.load512($ptr, $crate::amx_kernel!(@row $reg $row));
+        $crate::amx_kernel!(@stmts $ctx; $($rest)*);
+    };
+    (@stmts $ctx:ident; store $reg:ident [$row:expr] -> $ptr:expr; $($rest:tt)*) => {
+        $ctx.store512($ptr, $crate::amx_kernel!(@row $reg $row));
+        $crate::amx_kernel!(@stmts $ctx; $($rest)*);
+    };
+    (@stmts $ctx:ident; $ty:ident z[$z:expr] += x($x:expr) * y($y:expr); $($rest:tt)*) => {
+        $crate::amx_kernel!(@outer $ty $ctx, $x, $y, $z, true);
+        $crate::amx_kernel!(@stmts $ctx; $($rest)*);
+    };
+    (@stmts $ctx:ident; $ty:ident z[$z:expr] = x($x:expr) * y($y:expr); $($rest:tt)*) => {
+        $crate::amx_kernel!(@outer $ty $ctx, $x, $y, $z, false);
+        $crate::amx_kernel!(@stmts $ctx; $($rest)*);
+    };
+
+    // Register rows
+    (@row x $row:expr) => { $crate::XRow($row) };
+    (@row y $row:expr) => { $crate::YRow($row) };
+    (@row z $row:expr) => { $crate::ZRow($row) };
+
+    // Outer products
+    (@outer i16 $ctx:ident, $x:expr, $y:expr, $z:expr, $acc:expr) => {
+        $ctx.outer_product_i16_xy_to_z(
+            Some($crate::XBytes($x)),
+            Some($crate::YBytes($y)),
+            $crate::ZRow($z),
+            $acc,
+        )
+    };
+    (@outer f32 $ctx:ident, $x:expr, $y:expr, $z:expr, $acc:expr) => {
+        $ctx.outer_product_f32_xy_to_z(
+            Some($crate::XBytes($x)),
+            Some($crate::YBytes($y)),
+            $crate::ZRow($z),
+            $acc,
+        )
+    };
+
+    // Unrolling
+    (@repeat 1 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@stmts $ctx; $($b)*); };
+    (@repeat 2 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 1 1 $ctx; $($b)*); };
+    (@repeat 3 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 2 1 $ctx; $($b)*); };
+    (@repeat 4 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 2 2 $ctx; $($b)*); };
+    (@repeat 5 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 4 1 $ctx; $($b)*); };
+    (@repeat 6 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 4 2 $ctx; $($b)*); };
+    (@repeat 7 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 4 3 $ctx; $($b)*); };
+    (@repeat 8 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 4 4 $ctx; $($b)*); };
+    (@repeat 9 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 1 $ctx; $($b)*); };
+    (@repeat 10 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 2 $ctx; $($b)*); };
+    (@repeat 11 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 3 $ctx; $($b)*); };
+    (@repeat 12 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 4 $ctx; $($b)*); };
+    (@repeat 13 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 5 $ctx; $($b)*); };
+    (@repeat 14 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 6 $ctx; $($b)*); };
+    (@repeat 15 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 7 $ctx; $($b)*); };
+    (@repeat 16 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 8 8 $ctx; $($b)*); };
+    (@repeat 32 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 16 16 $ctx; $($b)*); };
+    (@repeat 64 $ctx:ident; $($b:tt)*) => { $crate::amx_kernel!(@rep2 32 32 $ctx; $($b)*); };
+    (@rep2 $n1:tt $n2:tt $ctx:ident; $($b:tt)*) => {
+        $crate::amx_kernel!(@repeat $n1 $ctx; $($b)*);
+        $crate::amx_kernel!(@repeat $n2 $ctx; $($b)*);
+    };
+}
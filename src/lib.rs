@@ -6,6 +6,8 @@
 //! # Resources
 //!
 //!  - <https://gist.github.com/dougallj/7a75a3be1ec69ca550e7c36dc75e0d6f>
+//!  - <https://github.com/corsix/amx> (operand encodings, including the lane
+//!    types and ALU modes of `vecint`, `vecfp`, `matint`, and `matfp`)
 //!  - <https://www.realworldtech.com/forum/?threadid=187087&curpostid=187120>
 //!
 //! # Example
@@ -25,7 +27,7 @@
 //!     ZRow(0),            // output to Z starting from row offset 0
 //!     false,              // don't accumulate
 //! );
-//! let z: [[i16; 32]; 64] = unsafe { std::mem::transmute(ctx.read_z()) };
+//! let z = ctx.read_z_as::<i16>();
 //! for (x_i, &x) in x.iter().enumerate() {
 //!     for (y_i, &y) in y.iter().enumerate() {
 //!         assert_eq!(z[y_i * 2][x_i], x * y);
@@ -46,24 +48,164 @@
 //! }
 //! ```
 
+pub mod algo;
+mod aligned;
+pub mod bench;
+pub mod bytes;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod check;
+mod chrome_trace;
+mod detect;
+pub mod disasm;
+mod dot;
+pub mod dsp;
+#[cfg(feature = "dual")]
+mod dual;
+mod dump;
+mod element;
+mod emu;
+pub mod fp16;
 mod genlut;
+pub mod geom;
+mod handoff;
+pub mod jit;
+mod kernel;
+pub mod linalg;
 mod load_store;
+mod matop;
+pub mod minimize;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra_ext;
+#[cfg(feature = "ndarray")]
+pub mod ndarray_ext;
+pub mod nn;
+#[macro_use]
 mod ops;
+// Declared after `ops` to use `forward_amx_ops!`
+mod fallback;
+mod operand;
+#[macro_use]
+mod outer_product;
+pub mod parallel;
+pub mod pipeline;
 mod regs;
-pub use crate::{genlut::*, load_store::*, ops::AmxOps, regs::*};
+mod shuffle;
+mod vecfp;
+mod vecint;
+pub use crate::{
+    aligned::{Aligned64, Aligned128},
+    check::*,
+    chrome_trace::ChromeTrace,
+    detect::{AmxVersion, NewAmxCtxError, is_available, version},
+    disasm::Disassembly,
+    dump::{AmxDump, DumpFormat, RegFileDump},
+    element::{AmxElement, XRegs, YRegs, ZRegs},
+    emu::{
+        AmxEmuCtx, AmxEmuGeometry, AmxEmuHook, AmxState, FpSemantics, Instr, RowChange, TraceEntry,
+    },
+    fallback::AmxFallbackCtx,
+    genlut::*,
+    handoff::{OwnedAmxCtx, SendableAmxState},
+    load_store::*,
+    matop::{MatFpOp, MatFpTy, MatIntOp, MatIntTy},
+    minimize::{Divergence, RegFile, RowDiff, find_divergence},
+    operand::{
+        Fma16Operand, Fma32Operand, Fma64Operand, Mac16Operand, MatFpOperand, MatIntOperand,
+        VecFpOperand, VecIntOperand,
+    },
+    ops::AmxOps,
+    regs::*,
+    shuffle::Shuffle512,
+    vecfp::{VecFpAluOp, VecFpTy},
+    vecint::{VecIntOp, VecIntTy},
+};
+
+#[cfg(feature = "dual")]
+pub use crate::dual::{DualCtx, DualDivergence, OnDivergence};
 
 cfg_if::cfg_if! {
     if #[cfg(any(doc, target_arch = "aarch64"))] {
         mod nativectx;
         pub mod nativeops;
-        pub use crate::nativectx::{AmxCtx, NewAmxCtxError};
+        mod report;
+        pub use crate::{
+            nativectx::AmxCtx,
+            report::{AmxReport, Throughput},
+        };
     }
 }
 
+/// Define a fully unrolled GEMM micro-kernel.
+///
+/// ```text
+/// gemm_microkernel!(VIS fn NAME(ELEM, mr = MR, nr = NR, unroll = UNROLL));
+/// ```
+///
+/// This expands to the following function:
+///
+/// ```text
+/// VIS unsafe fn NAME<A: Amx + ?Sized>(
+///     ctx: &mut A, k: usize, a: *const ELEM, b: *const ELEM, accumulate: bool);
+/// ```
+///
+/// which computes the product of an `(MR * L) × k` panel `a` and a
+/// `k × (NR * L)` panel `b` in Z, where `L` is the number of elements in a
+/// register row (`ELEM` = `i16`: 32, `f32`: 16). Both panels are packed
+/// k-major: `a` contains `k` columns of `MR * L` elements each, and `b`
+/// contains `k` rows of `NR * L` elements each. Block `(i, j)` of the result
+/// is placed in the Z tile `i * NR + j`, i.e., element `(r, c)` of the block
+/// is found at `z[r * T + i * NR + j][c]`, where `T` = `64 / L`.
+///
+/// The loop over `k` is unrolled by `UNROLL` (default: 1), with each of
+/// the unrolled steps assigned its own X and Y rows. The parameters are
+/// checked at compile time: `MR * NR` must not exceed `T`, and neither
+/// `UNROLL * MR` nor `UNROLL * NR` may exceed 8.
+///
+/// If `accumulate` is `false`, the existing contents of the tiles are
+/// discarded. If `k` is zero, Z is left unchanged.
+///
+/// # Safety
+///
+/// The generated function reads `k * MR * L` elements from `a` and
+/// `k * NR * L` elements from `b`.
+///
+/// # Example
+///
+/// ```rust
+/// amx::gemm_microkernel!(pub fn kernel_32x32(f32, mr = 2, nr = 2, unroll = 4));
+/// ```
+#[cfg(feature = "macros")]
+pub use amx_macros::gemm_microkernel;
+
 /// The prelude.
+///
+/// This includes the register types, the named register row constants
+/// ([`X0`]–[`X7`], [`Y0`]–[`Y7`], [`Z0`]–[`Z63`]), and the marker types for
+/// [`Amx::lut`], so a kernel can be written like this:
+///
+/// ```rust
+/// use amx::prelude::*;
+///
+/// fn kernel(ctx: &mut impl amx::Amx, x: &[i16; 32], y: &[i16; 32]) {
+///     unsafe { ctx.load512(x.as_ptr(), X3) };
+///     unsafe { ctx.load512(y.as_ptr(), Y5) };
+///     ctx.outer_product_i16_xy_to_z(Some(XBytes(3 * 64)), Some(YBytes(5 * 64)), Z1, false);
+/// }
+/// ```
+///
+/// [`X0`]: crate::consts::X0
+/// [`X7`]: crate::consts::X7
+/// [`Y0`]: crate::consts::Y0
+/// [`Y7`]: crate::consts::Y7
+/// [`Z0`]: crate::consts::Z0
+/// [`Z63`]: crate::consts::Z63
 pub mod prelude {
     #[doc(no_inline)]
-    pub use crate::{Amx as _, ops::AmxOps as _};
+    pub use crate::{
+        Amx as _, Index2, Index4, Index5, Normal, Reverse, X8, X16, X32, X64, XBytes, XRow, YBytes,
+        YRow, ZRow, consts::*, ops::AmxOps as _,
+    };
 }
 
 /// A high-level wrapper for AMX instructions.
@@ -73,7 +215,8 @@ pub mod prelude {
 ///  - On a supported system, construct an [`AmxCtx`] by calling
 ///    [`AmxCtx::new`]. `AmxCtx` derefs to [`amx::nativeops::AmxOps`], which
 ///    implements [`AmxOps`]​ (the low-level wrapper), which has a
-///    blanket impl of `Amx`.
+///    blanket impl of `Amx`. `AmxCtx` also implements `AmxOps` itself so
+///    that it can be used as [`dyn DynAmx`](DynAmx).
 ///
 ///  - On a supported system, construct [`amx::nativeops::AmxOps`] directly by
 ///    calling [`amx::nativeops::AmxOps::new`]. This is unsafe because it does
@@ -127,149 +270,1762 @@ pub trait Amx: crate::ops::AmxOps {
         }
     }
 
-    /// Load 512 bits (64 bytes) from memory to `z[index][0..64]` with interleaving.
+    /// Like [`Self::store512`], but with a cache behavior hint. See
+    /// [`StoreHint`] for its current effect.
+    ///
+    /// ```rust
+    /// use amx::{Amx, StoreHint, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.load512_slice(&[7u32; 16], ZRow(3));
+    /// let mut out = [0u32; 16];
+    /// unsafe { ctx.store512_hinted(out.as_mut_ptr(), ZRow(3), StoreHint::NonTemporal) };
+    /// assert_eq!(out, [7; 16]);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The same as [`Self::store512`].
+    #[inline(always)]
+    #[track_caller]
+    unsafe fn store512_hinted<T>(&mut self, ptr: *mut T, row: impl LoadStore, hint: StoreHint) {
+        match hint {
+            // No encoding of a non-temporal store is known
+            StoreHint::Normal | StoreHint::NonTemporal => unsafe { row.store512(self, ptr) },
+        }
+    }
+
+    /// Like [`Self::store1024_aligned`], but with a cache behavior hint. See
+    /// [`StoreHint`] for its current effect.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`Self::store1024_aligned`].
+    #[inline(always)]
+    #[track_caller]
+    unsafe fn store1024_aligned_hinted<T>(
+        &mut self,
+        ptr: *mut T,
+        row: impl LoadStore,
+        hint: StoreHint,
+    ) {
+        match hint {
+            // No encoding of a non-temporal store is known
+            StoreHint::Normal | StoreHint::NonTemporal => unsafe {
+                row.store1024_aligned(self, ptr)
+            },
+        }
+    }
+
+    /// Load 512 bits (64 bytes) from each of `ptrs` to the corresponding row
+    /// of `rows` (an array or tuple of rows).
+    ///
+    /// Pairs of adjacent rows are loaded by one 1024-bit operation if their
+    /// pointers are adjacent and aligned to 128-byte boundaries. See
+    /// [`LoadStoreMulti`].
+    ///
+    /// # Safety
     ///
-    /// `index` must be in range `0..64`.
+    /// Each of `ptrs` must be valid for reading 64 bytes.
     #[inline(always)]
     #[track_caller]
-    unsafe fn load512_interleaved<T>(&mut self, ptr: *const T, row: ZRow) {
+    unsafe fn load512_multi<T, const N: usize>(
+        &mut self,
+        ptrs: [*const T; N],
+        rows: impl LoadStoreMulti<N>,
+    ) {
         unsafe {
-            load512_z_interleaved(self, ptr, row);
+            rows.load512_multi(self, ptrs);
         }
     }
 
-    /// Store 512 bits (64 bytes) `z[index][0..64]` to memory with interleaving.
+    /// Store 512 bits (64 bytes) from each row of `rows` (an array or tuple
+    /// of rows) to the corresponding pointer of `ptrs`.
+    ///
+    /// Pairs of adjacent rows are stored by one 1024-bit operation if their
+    /// pointers are adjacent and aligned to 128-byte boundaries. See
+    /// [`LoadStoreMulti`].
+    ///
+    /// # Safety
     ///
-    /// `index` must be in range `0..64`.
+    /// Each of `ptrs` must be valid for writing 64 bytes.
     #[inline(always)]
     #[track_caller]
-    unsafe fn store512_interleaved<T>(&mut self, ptr: *mut T, row: ZRow) {
+    unsafe fn store512_multi<T, const N: usize>(
+        &mut self,
+        ptrs: [*mut T; N],
+        rows: impl LoadStoreMulti<N>,
+    ) {
         unsafe {
-            store512_z_interleaved(self, ptr, row);
+            rows.store512_multi(self, ptrs);
         }
     }
 
-    /// Read the whole contents of `x`.
-    fn read_x(&mut self) -> [u8; 512] {
-        let mut ret = std::mem::MaybeUninit::uninit();
-        for i in 0..8 {
-            // Safety: Writing in a memory region within `ret`
+    /// Load the first 512 bits (64 bytes) of `src` to the specified register
+    /// row. This is a safe version of [`Self::load512`].
+    ///
+    /// ```rust
+    /// use amx::{Amx, XRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.load512_slice(&[1u8; 64], XRow(0));
+    /// ctx.load512_slice(&[2.0f32; 20], XRow(1));
+    ///
+    /// let mut out = [0.0f32; 16];
+    /// ctx.store512_slice(&mut out, XRow(1));
+    /// assert_eq!(out, [2.0; 16]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is shorter than 64 bytes or the register index is out
+    /// of range.
+    #[inline(always)]
+    #[track_caller]
+    fn load512_slice<T: AmxElement>(&mut self, src: &[T], row: impl LoadStore) {
+        assert!(
+            std::mem::size_of_val(src) >= 64,
+            "`src` is shorter than 64 bytes"
+        );
+        // Safety: `src` is valid for reading 64 bytes
+        unsafe { self.load512(src.as_ptr(), row) }
+    }
+
+    /// Store the specified register row's contents to the first 512 bits (64
+    /// bytes) of `dst`. This is a safe version of [`Self::store512`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than 64 bytes or the register index is out
+    /// of range.
+    #[inline(always)]
+    #[track_caller]
+    fn store512_slice<T: AmxElement>(&mut self, dst: &mut [T], row: impl LoadStore) {
+        assert!(
+            std::mem::size_of_val(dst) >= 64,
+            "`dst` is shorter than 64 bytes"
+        );
+        // Safety: `dst` is valid for writing 64 bytes, and any bit pattern
+        // is a valid `AmxElement`
+        unsafe { self.store512(dst.as_mut_ptr(), row) }
+    }
+
+    /// Load the first 512 bits (64 bytes) of the 64-byte aligned array `src`
+    /// to the specified register row.
+    ///
+    /// An array shorter than 64 bytes is rejected at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    fn load512_aligned<T: AmxElement, const N: usize>(
+        &mut self,
+        src: &Aligned64<[T; N]>,
+        row: impl LoadStore,
+    ) {
+        const { assert!(size_of::<[T; N]>() >= 64, "`src` is shorter than 64 bytes") };
+        // Safety: `src` is valid for reading 64 bytes
+        unsafe { self.load512(src.as_ptr(), row) }
+    }
+
+    /// Store the specified register row's contents to the first 512 bits (64
+    /// bytes) of the 64-byte aligned array `dst`.
+    ///
+    /// An array shorter than 64 bytes is rejected at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    fn store512_aligned<T: AmxElement, const N: usize>(
+        &mut self,
+        dst: &mut Aligned64<[T; N]>,
+        row: impl LoadStore,
+    ) {
+        const { assert!(size_of::<[T; N]>() >= 64, "`dst` is shorter than 64 bytes") };
+        // Safety: `dst` is valid for writing 64 bytes, and any bit pattern
+        // is a valid `AmxElement`
+        unsafe { self.store512(dst.as_mut_ptr(), row) }
+    }
+
+    /// Load the first 1024 bits (128 bytes) of the 128-byte aligned array
+    /// `src` to the specified register row and the subsequent one. This is a
+    /// safe version of [`Self::load1024_aligned`].
+    ///
+    /// An array shorter than 128 bytes is rejected at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    fn load1024<T: AmxElement, const N: usize>(
+        &mut self,
+        src: &Aligned128<[T; N]>,
+        row: impl LoadStore,
+    ) {
+        const {
+            assert!(
+                size_of::<[T; N]>() >= 128,
+                "`src` is shorter than 128 bytes"
+            )
+        };
+        // Safety: `src` is valid for reading 128 bytes and aligned to
+        // 128-byte boundaries
+        unsafe { self.load1024_aligned(src.as_ptr(), row) }
+    }
+
+    /// Store the specified register row and the subsequent one's contents to
+    /// the first 1024 bits (128 bytes) of the 128-byte aligned array `dst`.
+    /// This is a safe version of [`Self::store1024_aligned`].
+    ///
+    /// An array shorter than 128 bytes is rejected at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    fn store1024<T: AmxElement, const N: usize>(
+        &mut self,
+        dst: &mut Aligned128<[T; N]>,
+        row: impl LoadStore,
+    ) {
+        const {
+            assert!(
+                size_of::<[T; N]>() >= 128,
+                "`dst` is shorter than 128 bytes"
+            )
+        };
+        // Safety: `dst` is valid for writing 128 bytes and aligned to
+        // 128-byte boundaries, and any bit pattern is a valid `AmxElement`
+        unsafe { self.store1024_aligned(dst.as_mut_ptr(), row) }
+    }
+
+    /// Load `base[indices[i]]` to the `i`-th `f32` lane of the specified
+    /// register row.
+    ///
+    /// This is not hardware-accelerated: the elements are read by scalar
+    /// loads into a staging buffer, which is then loaded by one
+    /// [`Self::load512`].
+    ///
+    /// # Safety
+    ///
+    /// `base.add(indices[i] as usize)` must be valid for reading an `f32` for
+    /// every `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline]
+    #[track_caller]
+    unsafe fn gather_f32(&mut self, base: *const f32, indices: &[u32; 16], row: impl LoadStore) {
+        // Safety: Upheld by the caller
+        let staging = indices.map(|i| unsafe { base.add(i as usize).read() });
+        self.load512_slice(&staging, row);
+    }
+
+    /// Store the `i`-th `f32` lane of the specified register row to
+    /// `base[indices[i]]`. If an index appears more than once, the last lane
+    /// with the index is stored.
+    ///
+    /// Like [`Self::gather_f32`], this is done by one [`Self::store512`] to a
+    /// staging buffer followed by scalar stores.
+    ///
+    /// # Safety
+    ///
+    /// `base.add(indices[i] as usize)` must be valid for writing an `f32` for
+    /// every `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline]
+    #[track_caller]
+    unsafe fn scatter_f32(&mut self, row: impl LoadStore, base: *mut f32, indices: &[u32; 16]) {
+        let mut staging = [0f32; 16];
+        self.store512_slice(&mut staging, row);
+        for (&i, &x) in indices.iter().zip(&staging) {
+            // Safety: Upheld by the caller
+            unsafe { base.add(i as usize).write(x) };
+        }
+    }
+
+    /// Load `src[indices[i]]` to the `i`-th `f32` lane of the specified
+    /// register row. This is a safe version of [`Self::gather_f32`].
+    ///
+    /// ```rust
+    /// use amx::{Amx, XRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let table: Vec<f32> = (0..100).map(|i| i as f32).collect();
+    /// let indices = [99, 0, 42, 7, 7, 7, 1, 2, 3, 4, 5, 6, 50, 60, 70, 80];
+    /// ctx.gather_f32_slice(&table, &indices, XRow(2));
+    ///
+    /// let mut out = vec![0.0; 100];
+    /// ctx.scatter_f32_slice(XRow(2), &mut out, &indices);
+    /// assert_eq!(out[99], 99.0);
+    /// assert_eq!(out[42], 42.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bounds of `src` or the register index
+    /// is out of range.
+    #[inline]
+    #[track_caller]
+    fn gather_f32_slice(&mut self, src: &[f32], indices: &[u32; 16], row: impl LoadStore) {
+        let staging = indices.map(|i| src[i as usize]);
+        self.load512_slice(&staging, row);
+    }
+
+    /// Store the `i`-th `f32` lane of the specified register row to
+    /// `dst[indices[i]]`. This is a safe version of [`Self::scatter_f32`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bounds of `dst` or the register index
+    /// is out of range. `dst` is left unmodified in that case.
+    #[inline]
+    #[track_caller]
+    fn scatter_f32_slice(&mut self, row: impl LoadStore, dst: &mut [f32], indices: &[u32; 16]) {
+        assert!(
+            indices.iter().all(|&i| (i as usize) < dst.len()),
+            "index out of bounds of `dst`"
+        );
+        // Safety: All indices are in bounds
+        unsafe { self.scatter_f32(row, dst.as_mut_ptr(), indices) }
+    }
+
+    /// Load `src` (up to 16×16) to the `tile`-th 16×16 `f32` tile of Z in
+    /// the layout of [`Self::outer_product_f32_xy_to_z`], filling the
+    /// elements outside `src` with zeros. `src` can have any memory layout.
+    ///
+    /// ```rust
+    /// use amx::Amx;
+    /// use ndarray::{Array2, array};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let a = array![[1.0f32, 2.0], [3.0, 4.0]];
+    /// ctx.load_tile(&a.t(), 1);
+    ///
+    /// let mut out = Array2::zeros((3, 3));
+    /// ctx.store_tile(1, &mut out.view_mut());
+    /// assert_eq!(out, array![[1.0, 3.0, 0.0], [2.0, 4.0, 0.0], [0.0; 3]]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is larger than 16×16 or `tile` is not in range
+    /// `0..4`.
+    #[cfg(feature = "ndarray")]
+    #[track_caller]
+    fn load_tile(&mut self, src: &ndarray::ArrayView2<'_, f32>, tile: usize) {
+        ndarray_ext::load_tile(self, src, tile);
+    }
+
+    /// Store the top-left corner of the `tile`-th 16×16 `f32` tile of Z, in
+    /// the layout of [`Self::outer_product_f32_xy_to_z`], to `dst` (up to
+    /// 16×16). `dst` can have any memory layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is larger than 16×16 or `tile` is not in range
+    /// `0..4`.
+    #[cfg(feature = "ndarray")]
+    #[track_caller]
+    fn store_tile(&mut self, tile: usize, dst: &mut ndarray::ArrayViewMut2<'_, f32>) {
+        ndarray_ext::store_tile(self, tile, dst);
+    }
+
+    /// Load `rows` rows of a matrix, each 512 bits (64 bytes) long and
+    /// `stride_bytes` bytes apart in memory starting at `ptr`, to `rows`
+    /// consecutive register rows starting at `first_row`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// // A 4×32 `f32` matrix; load the right half of the middle two rows
+    /// let a: Vec<f32> = (0..128).map(|i| i as f32).collect();
+    /// unsafe { ctx.load_matrix(a[32 + 16..].as_ptr(), 32 * 4, 2, XRow(6)) };
+    /// let x = ctx.read_x_as::<f32>();
+    /// assert_eq!(x[6][0], 48.0);
+    /// assert_eq!(x[7][15], 95.0);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// For each `i` in `0..rows`, `ptr.byte_add(i * stride_bytes)` must be
+    /// valid for reading 64 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register rows exceed the register file.
+    #[inline]
+    #[track_caller]
+    unsafe fn load_matrix<T, R: LoadStore>(
+        &mut self,
+        ptr: *const T,
+        stride_bytes: usize,
+        rows: usize,
+        first_row: R,
+    ) {
+        load_store::check_matrix_rows(&first_row, rows);
+        for i in 0..rows {
+            // Safety: Upheld by the caller
             unsafe {
-                self.store512(
-                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
-                    XRow(i),
-                )
+                first_row
+                    .nth_row(i)
+                    .load512(self, ptr.byte_add(i * stride_bytes))
             };
         }
-        // Safety: All elements are initialized
-        unsafe { ret.assume_init() }
     }
 
-    /// Read the whole contents of `y`.
-    fn read_y(&mut self) -> [u8; 512] {
-        let mut ret = std::mem::MaybeUninit::uninit();
-        for i in 0..8 {
-            // Safety: Writing in a memory region within `ret`
+    /// Store `rows` consecutive register rows starting at `first_row` to the
+    /// rows of a matrix, each 512 bits (64 bytes) long and `stride_bytes`
+    /// bytes apart in memory starting at `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// For each `i` in `0..rows`, `ptr.byte_add(i * stride_bytes)` must be
+    /// valid for writing 64 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register rows exceed the register file.
+    #[inline]
+    #[track_caller]
+    unsafe fn store_matrix<T, R: LoadStore>(
+        &mut self,
+        ptr: *mut T,
+        stride_bytes: usize,
+        rows: usize,
+        first_row: R,
+    ) {
+        load_store::check_matrix_rows(&first_row, rows);
+        for i in 0..rows {
+            // Safety: Upheld by the caller
             unsafe {
-                self.store512(
-                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
-                    YRow(i),
-                )
+                first_row
+                    .nth_row(i)
+                    .store512(self, ptr.byte_add(i * stride_bytes))
             };
         }
-        // Safety: All elements are initialized
-        unsafe { ret.assume_init() }
     }
 
-    /// Read the whole contents of `z`.
-    fn read_z(&mut self) -> [u8; 4096] {
-        let mut ret = std::mem::MaybeUninit::uninit();
-        for i in 0..64 {
-            // Safety: Writing in a memory region within `ret`
+    /// Load consecutive register rows `rows` from a contiguous buffer at
+    /// `ptr`, using 1024-bit loads for the 128-byte aligned pairs of rows and
+    /// 512-bit loads for the rest.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XRow, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let a: Vec<u8> = (0..=255).cycle().take(512).collect();
+    /// unsafe { ctx.load_rows(a.as_ptr(), XRow(0)..XRow(8)) };
+    /// assert_eq!(ctx.read_x()[..], a[..]);
+    ///
+    /// ctx.load_rows_slice(&[1.0f32; 48], ZRow(10)..ZRow(13));
+    /// assert_eq!(ctx.read_z_f32()[12], [1.0; 16]);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reading 64 bytes per row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ends of `rows` are in different register files, `rows`
+    /// is decreasing, or the rows exceed the register file.
+    #[inline]
+    #[track_caller]
+    unsafe fn load_rows<T, R: LoadStore>(&mut self, ptr: *const T, rows: std::ops::Range<R>) {
+        let (first, n) = load_store::row_range(&rows);
+        let mut i = 0;
+        while i < n {
+            let row = first.nth_row(i);
+            // Safety: Upheld by the caller
             unsafe {
-                self.store512(
-                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
-                    ZRow(i),
-                )
-            };
+                let ptr = ptr.byte_add(i * 64);
+                if i + 1 < n && (ptr as usize).is_multiple_of(128) {
+                    row.load1024_aligned(self, ptr);
+                    i += 2;
+                } else {
+                    row.load512(self, ptr);
+                    i += 1;
+                }
+            }
         }
-        // Safety: All elements are initialized
-        unsafe { ret.assume_init() }
     }
 
-    /// Calculate the outer product of `x: [i16; 32]` and `y: [i16; 32]` and write
-    /// the output to every second row of `z: [[i16; 32]; 64]`.
+    /// Store consecutive register rows `rows` to a contiguous buffer at
+    /// `ptr`, using 1024-bit stores for the 128-byte aligned pairs of rows
+    /// and 512-bit stores for the rest.
     ///
-    /// If `x_offset_bytes` and/or `y_offset_bytes` are `None`, the respective
-    /// registers will be excluded from the operation (not performing
-    /// multiplication).
+    /// # Safety
     ///
-    /// `z_index` must be in range `0..64`. Only the least significant bit of
-    /// `z_index` will be taken into consideration.
-    #[inline(always)]
-    fn outer_product_i16_xy_to_z(
+    /// `ptr` must be valid for writing 64 bytes per row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ends of `rows` are in different register files, `rows`
+    /// is decreasing, or the rows exceed the register file.
+    #[inline]
+    #[track_caller]
+    unsafe fn store_rows<T, R: LoadStore>(&mut self, ptr: *mut T, rows: std::ops::Range<R>) {
+        let (first, n) = load_store::row_range(&rows);
+        let mut i = 0;
+        while i < n {
+            let row = first.nth_row(i);
+            // Safety: Upheld by the caller
+            unsafe {
+                let ptr = ptr.byte_add(i * 64);
+                if i + 1 < n && (ptr as usize).is_multiple_of(128) {
+                    row.store1024_aligned(self, ptr);
+                    i += 2;
+                } else {
+                    row.store512(self, ptr);
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// Load consecutive register rows `rows` from the beginning of `src`.
+    /// This is a safe version of [`Self::load_rows`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is shorter than 64 bytes per row or under the
+    /// conditions described in [`Self::load_rows`].
+    #[inline]
+    #[track_caller]
+    fn load_rows_slice<T: AmxElement, R: LoadStore>(
         &mut self,
-        x_offset_bytes: Option<XBytes>,
-        y_offset_bytes: Option<YBytes>,
-        z_index: ZRow,
-        accumulate: bool,
+        src: &[T],
+        rows: std::ops::Range<R>,
     ) {
-        // FIXME: rustfmt doesn't like patterns in provided trait methods
-        let z_index = z_index.0;
-        debug_assert!(x_offset_bytes.unwrap_or_default().0 < 0x200);
-        debug_assert!(y_offset_bytes.unwrap_or_default().0 < 0x200);
-        debug_assert!(z_index < 64);
-        // TODO: widening (i32 output)
-        // TODO: vector output (reducing)
-        self.mac16(
-            (y_offset_bytes.unwrap_or_default().0
-                | (x_offset_bytes.unwrap_or_default().0 << 10)
-                | (z_index << 20)
-                | (((!accumulate) as usize) << 27)
-                | ((x_offset_bytes.is_none() as usize) << 28)
-                | ((y_offset_bytes.is_none() as usize) << 29)) as u64,
+        let (_, n) = load_store::row_range(&rows);
+        assert!(
+            std::mem::size_of_val(src) >= n * 64,
+            "`src` is shorter than {n} rows"
         );
+        // Safety: `src` is valid for reading `n * 64` bytes
+        unsafe { self.load_rows(src.as_ptr(), rows) }
     }
 
-    /// Calculate the outer product of `x: [f32; 16]` and `y: [f32; 16]` and write
-    /// the output to every fourth row of `z: [[f32; 16]; 64]`.
+    /// Store consecutive register rows `rows` to the beginning of `dst`.
+    /// This is a safe version of [`Self::store_rows`].
     ///
-    /// If `x_offset_bytes` and/or `y_offset_bytes` are `None`, the respective
-    /// registers will be excluded from the operation (not performing
-    /// multiplication).
+    /// # Panics
     ///
-    /// `z_index` must be in range `0..64`. Only the 2 least significant bits
-    /// of `z_index` will be taken into consideration.
-    #[inline(always)]
-    fn outer_product_f32_xy_to_z(
+    /// Panics if `dst` is shorter than 64 bytes per row or under the
+    /// conditions described in [`Self::store_rows`].
+    #[inline]
+    #[track_caller]
+    fn store_rows_slice<T: AmxElement, R: LoadStore>(
         &mut self,
-        x_offset_bytes: Option<XBytes>,
-        y_offset_bytes: Option<YBytes>,
-        z_index: ZRow,
-        accumulate: bool,
+        dst: &mut [T],
+        rows: std::ops::Range<R>,
     ) {
-        // FIXME: rustfmt doesn't like patterns in provided trait methods
-        let z_index = z_index.0;
-        debug_assert!(x_offset_bytes.unwrap_or_default().0 < 0x200);
-        debug_assert!(y_offset_bytes.unwrap_or_default().0 < 0x200);
-        debug_assert!(z_index < 64);
-        // TODO: widening (f64 output)
-        // TODO: vector output (reducing)
-        self.fma32(
-            (y_offset_bytes.unwrap_or_default().0
-                | (x_offset_bytes.unwrap_or_default().0 << 10)
-                | (z_index << 20)
-                | (((!accumulate) as usize) << 27)
-                | ((x_offset_bytes.is_none() as usize) << 28)
-                | ((y_offset_bytes.is_none() as usize) << 29)) as u64,
+        let (_, n) = load_store::row_range(&rows);
+        assert!(
+            std::mem::size_of_val(dst) >= n * 64,
+            "`dst` is shorter than {n} rows"
         );
+        // Safety: `dst` is valid for writing `n * 64` bytes, and any bit
+        // pattern is a valid `AmxElement`
+        unsafe { self.store_rows(dst.as_mut_ptr(), rows) }
     }
 
-    /// Perform (reverse) table lookup.
+    /// Like [`Self::load512`], but without checking the register index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `load512`'s requirements, the register index must be
+    /// in range (`0..8` for X and Y, `0..64` for Z).
     #[inline(always)]
-    fn lut(&mut self, input: impl LutIn, table: XRow, output: impl LutOut, ty: impl LutTy) {
-        genlut::lut(self, input, table, output, ty);
+    unsafe fn load512_unchecked<T>(&mut self, ptr: *const T, row: impl LoadStore) {
+        unsafe {
+            row.load512_unchecked(self, ptr);
+        }
     }
-}
 
-impl<T: AmxOps + ?Sized> Amx for T {}
+    /// Like [`Self::load1024_aligned`], but without checking the register
+    /// index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `load1024_aligned`'s requirements, the register index
+    /// must be in range (`0..8` for X and Y, `0..64` for Z).
+    #[inline(always)]
+    unsafe fn load1024_aligned_unchecked<T>(&mut self, ptr: *const T, row: impl LoadStore) {
+        unsafe {
+            row.load1024_aligned_unchecked(self, ptr);
+        }
+    }
+
+    /// Like [`Self::store512`], but without checking the register index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `store512`'s requirements, the register index must be
+    /// in range (`0..8` for X and Y, `0..64` for Z).
+    #[inline(always)]
+    unsafe fn store512_unchecked<T>(&mut self, ptr: *mut T, row: impl LoadStore) {
+        unsafe {
+            row.store512_unchecked(self, ptr);
+        }
+    }
+
+    /// Like [`Self::store1024_aligned`], but without checking the register
+    /// index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `store1024_aligned`'s requirements, the register index
+    /// must be in range (`0..8` for X and Y, `0..64` for Z).
+    #[inline(always)]
+    unsafe fn store1024_aligned_unchecked<T>(&mut self, ptr: *mut T, row: impl LoadStore) {
+        unsafe {
+            row.store1024_aligned_unchecked(self, ptr);
+        }
+    }
+
+    /// Load 512 bits (64 bytes) from memory to half of the specified
+    /// register row and its neighbor with interleaving. See
+    /// [`LoadStoreInterleaved`] for the layout.
+    ///
+    /// Only Z has the instruction for this. For X and Y, this is emulated by
+    /// several loads and stores.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reading 64 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    unsafe fn load512_interleaved<T>(&mut self, ptr: *const T, row: impl LoadStoreInterleaved) {
+        unsafe {
+            row.load512_interleaved(self, ptr);
+        }
+    }
+
+    /// Store 512 bits (64 bytes) from half of the specified register row and
+    /// its neighbor to memory with interleaving. See
+    /// [`LoadStoreInterleaved`] for the layout.
+    ///
+    /// Only Z has the instruction for this. For X and Y, this is emulated by
+    /// several stores.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writing 64 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register index is out of range.
+    #[inline(always)]
+    #[track_caller]
+    unsafe fn store512_interleaved<T>(&mut self, ptr: *mut T, row: impl LoadStoreInterleaved) {
+        unsafe {
+            row.store512_interleaved(self, ptr);
+        }
+    }
+
+    /// Like [`Self::load512`], but returns an error instead of panicking if
+    /// the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::load512`].
+    #[inline]
+    unsafe fn try_load512<T>(
+        &mut self,
+        ptr: *const T,
+        row: impl LoadStore + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: The index is in range, and the rest is upheld by the caller
+        unsafe { row.load512_unchecked(self, ptr) };
+        Ok(())
+    }
+
+    /// Like [`Self::load1024_aligned`], but returns an error instead of panicking if
+    /// the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::load1024_aligned`].
+    #[inline]
+    unsafe fn try_load1024_aligned<T>(
+        &mut self,
+        ptr: *const T,
+        row: impl LoadStore + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: The index is in range, and the rest is upheld by the caller
+        unsafe { row.load1024_aligned_unchecked(self, ptr) };
+        Ok(())
+    }
+
+    /// Like [`Self::store512`], but returns an error instead of panicking if
+    /// the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::store512`].
+    #[inline]
+    unsafe fn try_store512<T>(
+        &mut self,
+        ptr: *mut T,
+        row: impl LoadStore + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: The index is in range, and the rest is upheld by the caller
+        unsafe { row.store512_unchecked(self, ptr) };
+        Ok(())
+    }
+
+    /// Like [`Self::store1024_aligned`], but returns an error instead of panicking if
+    /// the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::store1024_aligned`].
+    #[inline]
+    unsafe fn try_store1024_aligned<T>(
+        &mut self,
+        ptr: *mut T,
+        row: impl LoadStore + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: The index is in range, and the rest is upheld by the caller
+        unsafe { row.store1024_aligned_unchecked(self, ptr) };
+        Ok(())
+    }
+
+    /// Like [`Self::load512_interleaved`], but returns an error instead of
+    /// panicking if the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::load512_interleaved`].
+    #[inline]
+    unsafe fn try_load512_interleaved<T>(
+        &mut self,
+        ptr: *const T,
+        row: impl LoadStoreInterleaved + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: Upheld by the caller
+        unsafe { self.load512_interleaved(ptr, row) };
+        Ok(())
+    }
+
+    /// Like [`Self::store512_interleaved`], but returns an error instead of
+    /// panicking if the register index is out of range.
+    ///
+    /// # Safety
+    ///
+    /// The memory access must be valid as in [`Self::store512_interleaved`].
+    #[inline]
+    unsafe fn try_store512_interleaved<T>(
+        &mut self,
+        ptr: *mut T,
+        row: impl LoadStoreInterleaved + CheckArg,
+    ) -> Result<(), AmxArgError> {
+        row.check()?;
+        // Safety: Upheld by the caller
+        unsafe { self.store512_interleaved(ptr, row) };
+        Ok(())
+    }
+
+    /// Read the whole contents of `x`.
+    fn read_x(&mut self) -> [u8; 512] {
+        let mut ret = std::mem::MaybeUninit::uninit();
+        for i in 0..8 {
+            // Safety: Writing in a memory region within `ret`
+            unsafe {
+                self.store512(
+                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
+                    XRow(i),
+                )
+            };
+        }
+        // Safety: All elements are initialized
+        unsafe { ret.assume_init() }
+    }
+
+    /// Read the whole contents of `y`.
+    fn read_y(&mut self) -> [u8; 512] {
+        let mut ret = std::mem::MaybeUninit::uninit();
+        for i in 0..8 {
+            // Safety: Writing in a memory region within `ret`
+            unsafe {
+                self.store512(
+                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
+                    YRow(i),
+                )
+            };
+        }
+        // Safety: All elements are initialized
+        unsafe { ret.assume_init() }
+    }
+
+    /// Read the whole contents of `z`.
+    fn read_z(&mut self) -> [u8; 4096] {
+        let mut ret = std::mem::MaybeUninit::uninit();
+        for i in 0..64 {
+            // Safety: Writing in a memory region within `ret`
+            unsafe {
+                self.store512(
+                    (ret.as_mut_ptr() as *mut u8).offset(i as isize * 64),
+                    ZRow(i),
+                )
+            };
+        }
+        // Safety: All elements are initialized
+        unsafe { ret.assume_init() }
+    }
+
+    /// Overwrite the whole contents of `x`.
+    fn write_x(&mut self, x: &[u8; 512]) {
+        // Safety: Reading 8 rows from `[u8; 512]`
+        unsafe { self.load_matrix(x.as_ptr(), 64, 8, XRow(0)) };
+    }
+
+    /// Overwrite the whole contents of `y`.
+    fn write_y(&mut self, y: &[u8; 512]) {
+        // Safety: Reading 8 rows from `[u8; 512]`
+        unsafe { self.load_matrix(y.as_ptr(), 64, 8, YRow(0)) };
+    }
+
+    /// Overwrite the whole contents of `z`.
+    ///
+    /// ```rust
+    /// use amx::Amx;
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let z: [u8; 4096] = std::array::from_fn(|i| i as u8);
+    /// ctx.write_z(&z);
+    /// assert_eq!(ctx.read_z(), z);
+    /// ```
+    fn write_z(&mut self, z: &[u8; 4096]) {
+        // Safety: Reading 64 rows from `[u8; 4096]`
+        unsafe { self.load_matrix(z.as_ptr(), 64, 64, ZRow(0)) };
+    }
+
+    /// Set the whole contents of `x` to zero.
+    ///
+    /// This loads every row from a shared 64-byte zero buffer, which stays
+    /// in the L1 cache.
+    fn clear_x(&mut self) {
+        for i in 0..8 {
+            // Safety: Reading 64 bytes from `[u8; 64]`
+            unsafe { self.load512(load_store::ZEROS.as_ptr(), XRow(i)) };
+        }
+    }
+
+    /// Set the whole contents of `y` to zero. See [`Self::clear_x`].
+    fn clear_y(&mut self) {
+        for i in 0..8 {
+            // Safety: Reading 64 bytes from `[u8; 64]`
+            unsafe { self.load512(load_store::ZEROS.as_ptr(), YRow(i)) };
+        }
+    }
+
+    /// Set the whole contents of `z` to zero without accessing memory.
+    ///
+    /// ```rust
+    /// use amx::Amx;
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.write_z(&[0xff; 4096]);
+    /// ctx.clear_z();
+    /// assert_eq!(ctx.read_z(), [0; 4096]);
+    /// ```
+    ///
+    /// # Execution
+    ///
+    /// Four matrix-mode `f16` operations with X and Y treated as ones
+    /// calculate `z = -(1 * 1)` and then `z += 1 * 1`, which is `+0.0` (all
+    /// bits zero), in each half of Z. This is much cheaper than loading
+    /// 4 KiB of zeros, so a kernel can start with this instead of a
+    /// non-accumulating first step.
+    fn clear_z(&mut self) {
+        for z_index in 0..2 {
+            // X and Y are treated as ones
+            let operand = Fma16Operand::new()
+                .x_offset(None)
+                .y_offset(None)
+                .z_row(ZRow(z_index));
+            // Z is treated as zero
+            self.fms16(operand.accumulate(false).encode());
+            self.fma16(operand.encode());
+        }
+    }
+
+    /// Set the whole contents of `x`, `y`, and `z` to zero.
+    fn clear_all(&mut self) {
+        self.clear_x();
+        self.clear_y();
+        self.clear_z();
+    }
+
+    /// Set every lane of the `x` register row `row` to `value`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.fill_x_row(2.0f32, XRow(1));
+    /// ctx.fill_y_row(-0.5f32, YRow(0));
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(64)), Some(YBytes(0)), ZRow(0), false);
+    /// assert_eq!(ctx.read_z_f32()[0], [-1.0; 16]);
+    /// ```
+    #[inline]
+    fn fill_x_row<T: AmxElement>(&mut self, value: T, row: XRow) {
+        self.load512_slice(&[value; 64], row);
+    }
+
+    /// Set every lane of the `y` register row `row` to `value`.
+    #[inline]
+    fn fill_y_row<T: AmxElement>(&mut self, value: T, row: YRow) {
+        self.load512_slice(&[value; 64], row);
+    }
+
+    /// Set every lane of the `z` register row `row` to `value`, e.g., to
+    /// initialize accumulators to a bias.
+    #[inline]
+    fn fill_z_row<T: AmxElement>(&mut self, value: T, row: ZRow) {
+        self.load512_slice(&[value; 64], row);
+    }
+
+    /// Copy the register row `src` to all rows of `x`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let bias: [f32; 16] = std::array::from_fn(|i| i as f32);
+    /// ctx.load512_slice(&bias, ZRow(5));
+    /// ctx.broadcast_to_x(ZRow(5));
+    /// assert_eq!(ctx.read_x_as::<f32>(), [bias; 8]);
+    /// ```
+    #[inline]
+    fn broadcast_to_x(&mut self, src: impl LoadStore) {
+        let mut buf = [0u8; 64];
+        self.store512_slice(&mut buf, src);
+        for i in 0..8 {
+            self.load512_slice(&buf, XRow(i));
+        }
+    }
+
+    /// Copy the register row `src` to all rows of `y`.
+    #[inline]
+    fn broadcast_to_y(&mut self, src: impl LoadStore) {
+        let mut buf = [0u8; 64];
+        self.store512_slice(&mut buf, src);
+        for i in 0..8 {
+            self.load512_slice(&buf, YRow(i));
+        }
+    }
+
+    /// Capture the whole contents of `x`, `y`, and `z`, e.g., to be
+    /// restored by [`Self::restore`] after running another computation.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XBytes, YBytes, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.write_z(&[1; 4096]);
+    /// let saved = ctx.snapshot();
+    ///
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    /// assert_ne!(ctx.read_z(), [1; 4096]);
+    ///
+    /// ctx.restore(&saved);
+    /// assert_eq!(ctx.read_z(), [1; 4096]);
+    /// ```
+    ///
+    /// The returned state has the register file size of the existing
+    /// processors ([`AmxEmuGeometry::M1`]). Only this part of an
+    /// [`AmxEmuCtx`] with a larger register file is captured.
+    fn snapshot(&mut self) -> AmxState {
+        let mut state = AmxState::new(AmxEmuGeometry::M1);
+        // Safety: Writing 8, 8, and 64 rows to the register files of the
+        // same sizes
+        unsafe {
+            self.store_matrix(state.x_mut().as_mut_ptr(), 64, 8, XRow(0));
+            self.store_matrix(state.y_mut().as_mut_ptr(), 64, 8, YRow(0));
+            self.store_matrix(state.z_mut().as_mut_ptr(), 64, 64, ZRow(0));
+        }
+        state
+    }
+
+    /// Overwrite the whole contents of `x`, `y`, and `z` with `state`
+    /// captured by [`Self::snapshot`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state`'s register file size is not
+    /// [`AmxEmuGeometry::M1`].
+    #[track_caller]
+    fn restore(&mut self, state: &AmxState) {
+        assert_eq!(
+            state.geometry(),
+            AmxEmuGeometry::M1,
+            "`state` has an unsupported register file size"
+        );
+        // Safety: Reading 8, 8, and 64 rows from the register files of the
+        // same sizes
+        unsafe {
+            self.load_matrix(state.x().as_ptr(), 64, 8, XRow(0));
+            self.load_matrix(state.y().as_ptr(), 64, 8, YRow(0));
+            self.load_matrix(state.z().as_ptr(), 64, 64, ZRow(0));
+        }
+    }
+
+    /// Capture the register contents for printing. See [`AmxDump`].
+    ///
+    /// ```rust
+    /// use amx::{Amx, DumpFormat, XRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.load512_slice(&[0.5f32; 16], XRow(0));
+    /// println!("{}", ctx.dump().format(DumpFormat::F32));
+    /// ```
+    fn dump(&mut self) -> AmxDump {
+        AmxDump::new(self.snapshot())
+    }
+
+    /// Read the whole contents of `x` as rows of `T`, e.g.,
+    /// `[[f32; 16]; 8]` for `T = f32`.
+    fn read_x_as<T: AmxElement>(&mut self) -> XRegs<T> {
+        element::regs_from_bytes::<T, 8>(&self.read_x())
+    }
+
+    /// Read the whole contents of `y` as rows of `T`, e.g.,
+    /// `[[f32; 16]; 8]` for `T = f32`.
+    fn read_y_as<T: AmxElement>(&mut self) -> YRegs<T> {
+        element::regs_from_bytes::<T, 8>(&self.read_y())
+    }
+
+    /// Read the whole contents of `z` as rows of `T`, e.g.,
+    /// `[[f32; 16]; 64]` for `T = f32`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XRow, YRow, XBytes, YBytes, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let x = [2.0f32; 16];
+    /// let y = [3.0f32; 16];
+    /// unsafe {
+    ///     ctx.load512(x.as_ptr(), XRow(0));
+    ///     ctx.load512(y.as_ptr(), YRow(0));
+    /// }
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    ///
+    /// let z: [[f32; 16]; 64] = ctx.read_z_as::<f32>();
+    /// assert_eq!(z[4], [6.0; 16]);
+    /// ```
+    fn read_z_as<T: AmxElement>(&mut self) -> ZRegs<T> {
+        element::regs_from_bytes::<T, 64>(&self.read_z())
+    }
+
+    /// Read the whole contents of `z` as `[[i16; 32]; 64]`, e.g., the output
+    /// of [`Self::outer_product_i16_xy_to_z`].
+    fn read_z_i16(&mut self) -> [[i16; 32]; 64] {
+        self.read_z_as::<i16>()
+    }
+
+    /// Read the whole contents of `z` as `[[i32; 16]; 64]`, e.g., the output
+    /// of [`Self::outer_product_i16_xy_to_z_i32`].
+    fn read_z_i32(&mut self) -> [[i32; 16]; 64] {
+        self.read_z_as::<i32>()
+    }
+
+    /// Read the whole contents of `z` as `[[f32; 16]; 64]`, e.g., the output
+    /// of [`Self::outer_product_f32_xy_to_z`].
+    fn read_z_f32(&mut self) -> [[f32; 16]; 64] {
+        self.read_z_as::<f32>()
+    }
+
+    /// Read the whole contents of `z` as `[[f64; 8]; 64]`, e.g., the output
+    /// of [`Self::outer_product_f64_xy_to_z`].
+    fn read_z_f64(&mut self) -> [[f64; 8]; 64] {
+        self.read_z_as::<f64>()
+    }
+
+    /// Read the whole contents of `z` as `[[f16; 32]; 64]`, e.g., the output
+    /// of [`Self::outer_product_f16_xy_to_z`]. Use [`fp16::F16Bits::to_f32`]
+    /// to convert the elements.
+    fn read_z_f16(&mut self) -> [[fp16::F16Bits; 32]; 64] {
+        self.read_z_as::<fp16::F16Bits>()
+    }
+
+    /// Read the `plane`-th 32×32 tile of `z: [[i16; 32]; 64]`, which
+    /// consists of every second row starting at row `plane`, e.g., the output
+    /// of [`Self::outer_product_i16_xy_to_z`] with `z_index = ZRow(plane)`.
+    /// `x[i] * y[j]` is found at `[j][i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane` is not in range `0..2`.
+    #[track_caller]
+    fn read_z_plane_i16(&mut self, plane: usize) -> [[i16; 32]; 32] {
+        assert!(plane < 2, "`plane` must be in range `0..2`");
+        let z = self.read_z_i16();
+        std::array::from_fn(|j| z[j * 2 + plane])
+    }
+
+    /// Read the `plane`-th 32×32 tile of `z: [[f16; 32]; 64]`, which
+    /// consists of every second row starting at row `plane`, e.g., the output
+    /// of [`Self::outer_product_f16_xy_to_z`] with `z_index = ZRow(plane)`.
+    /// `x[i] * y[j]` is found at `[j][i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane` is not in range `0..2`.
+    #[track_caller]
+    fn read_z_plane_f16(&mut self, plane: usize) -> [[fp16::F16Bits; 32]; 32] {
+        assert!(plane < 2, "`plane` must be in range `0..2`");
+        let z = self.read_z_f16();
+        std::array::from_fn(|j| z[j * 2 + plane])
+    }
+
+    /// Read the `plane`-th 16×16 tile of `z: [[i32; 16]; 64]`, which
+    /// consists of every fourth row starting at row `plane`. This is the
+    /// layout of 32-bit outer products such as
+    /// [`Self::outer_product_f32_xy_to_z`]; use
+    /// [`Self::read_z_widened_i32`] for the output of the widening 16-bit
+    /// products instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane` is not in range `0..4`.
+    #[track_caller]
+    fn read_z_plane_i32(&mut self, plane: usize) -> [[i32; 16]; 16] {
+        assert!(plane < 4, "`plane` must be in range `0..4`");
+        let z = self.read_z_i32();
+        std::array::from_fn(|j| z[j * 4 + plane])
+    }
+
+    /// Read the `plane`-th 16×16 tile of `z: [[f32; 16]; 64]`, which
+    /// consists of every fourth row starting at row `plane`, e.g., the output
+    /// of [`Self::outer_product_f32_xy_to_z`] with `z_index = ZRow(plane)`.
+    /// `x[i] * y[j]` is found at `[j][i]`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    /// let y: [f32; 16] = std::array::from_fn(|j| (j * 100) as f32);
+    /// ctx.load512_slice(&x, XRow(0));
+    /// ctx.load512_slice(&y, YRow(0));
+    /// ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(3), false);
+    ///
+    /// let tile = ctx.read_z_plane_f32(3);
+    /// assert_eq!(tile[2][5], 5.0 * 200.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane` is not in range `0..4`.
+    #[track_caller]
+    fn read_z_plane_f32(&mut self, plane: usize) -> [[f32; 16]; 16] {
+        assert!(plane < 4, "`plane` must be in range `0..4`");
+        let z = self.read_z_f32();
+        std::array::from_fn(|j| z[j * 4 + plane])
+    }
+
+    /// Read the `plane`-th 8×8 tile of `z: [[f64; 8]; 64]`, which consists
+    /// of every eighth row starting at row `plane`, e.g., the output of
+    /// [`Self::outer_product_f64_xy_to_z`] with `z_index = ZRow(plane)`.
+    /// `x[i] * y[j]` is found at `[j][i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane` is not in range `0..8`.
+    #[track_caller]
+    fn read_z_plane_f64(&mut self, plane: usize) -> [[f64; 8]; 8] {
+        assert!(plane < 8, "`plane` must be in range `0..8`");
+        let z = self.read_z_f64();
+        std::array::from_fn(|j| z[j * 8 + plane])
+    }
+
+    /// Read the 32×32 `i32` tile written by
+    /// [`Self::outer_product_i16_xy_to_z_i32`], undoing the interleaving of
+    /// the two halves of each output row. `x[i] * y[j]` is found at `[j][i]`.
+    fn read_z_widened_i32(&mut self) -> [[i32; 32]; 32] {
+        let z = self.read_z_i32();
+        std::array::from_fn(|j| std::array::from_fn(|i| z[j * 2 + i % 2][i / 2]))
+    }
+
+    /// Read the 32×32 `f32` tile written by
+    /// [`Self::outer_product_f16_xy_to_z_f32`], undoing the interleaving of
+    /// the two halves of each output row. `x[i] * y[j]` is found at `[j][i]`.
+    fn read_z_widened_f32(&mut self) -> [[f32; 32]; 32] {
+        let z = self.read_z_f32();
+        std::array::from_fn(|j| std::array::from_fn(|i| z[j * 2 + i % 2][i / 2]))
+    }
+
+    outer_product_methods! {
+        /// Calculate the outer product of `x: [i16; 32]` and `y: [i16; 32]` and
+        /// write the output to every second row of `z: [[i16; 32]; 64]`.
+        ///
+        /// `z_index` must be in range `0..64`. Only the least significant bit
+        /// of `z_index` will be taken into consideration.
+        fn outer_product_i16_xy_to_z / outer_product_i16_xy_to_z_unchecked / try_outer_product_i16_xy_to_z
+            => mac16(Mac16Operand::new());
+
+        /// Calculate the outer product of `x: [i16; 32]` and `y: [i16; 32]` and
+        /// write the output to `z: [[i32; 16]; 64]`. `x[i] * y[j]` is written
+        /// to `z[j * 2 + i % 2][i / 2]`.
+        ///
+        /// Unlike [`Self::outer_product_i16_xy_to_z`], this accumulates in 32
+        /// bits, so long sums of products don't overflow. The result can be
+        /// read by [`Self::read_z_i32`].
+        ///
+        /// The output occupies all rows of `z`, so `z_index` should be
+        /// `ZRow(0)`.
+        fn outer_product_i16_xy_to_z_i32 / outer_product_i16_xy_to_z_i32_unchecked / try_outer_product_i16_xy_to_z_i32
+            => mac16(Mac16Operand::new().widen(true));
+
+        /// Calculate the outer product of `x: [f16; 32]` and `y: [f16; 32]` and
+        /// write the output to every second row of `z: [[f16; 32]; 64]`.
+        ///
+        /// The inputs can be prepared by [`fp16::f32_to_f16`], and the result
+        /// can be read by [`Self::read_z_f16`].
+        ///
+        /// `z_index` must be in range `0..64`. Only the least significant bit
+        /// of `z_index` will be taken into consideration.
+        fn outer_product_f16_xy_to_z / outer_product_f16_xy_to_z_unchecked / try_outer_product_f16_xy_to_z
+            => fma16(Fma16Operand::new());
+
+        /// Calculate the outer product of `x: [f16; 32]` and `y: [f16; 32]` and
+        /// write the output to `z: [[f32; 16]; 64]`. `x[i] * y[j]` is written
+        /// to `z[j * 2 + i % 2][i / 2]`.
+        ///
+        /// This accumulates in single precision, so it's suitable for long
+        /// sums. The result can be read by [`Self::read_z_f32`].
+        ///
+        /// The output occupies all rows of `z`, so `z_index` should be
+        /// `ZRow(0)`.
+        fn outer_product_f16_xy_to_z_f32 / outer_product_f16_xy_to_z_f32_unchecked / try_outer_product_f16_xy_to_z_f32
+            => fma16(Fma16Operand::new().widen(true));
+
+        /// Calculate the outer product of `x: [f32; 16]` and `y: [f32; 16]` and
+        /// write the output to every fourth row of `z: [[f32; 16]; 64]`.
+        ///
+        /// `z_index` must be in range `0..64`. Only the 2 least significant
+        /// bits of `z_index` will be taken into consideration.
+        fn outer_product_f32_xy_to_z / outer_product_f32_xy_to_z_unchecked / try_outer_product_f32_xy_to_z
+            => fma32(Fma32Operand::new());
+
+        /// Calculate the outer product of `x: [f64; 8]` and `y: [f64; 8]` and
+        /// write the output to every eighth row of `z: [[f64; 8]; 64]`.
+        ///
+        /// `z_index` must be in range `0..64`. Only the 3 least significant
+        /// bits of `z_index` will be taken into consideration.
+        fn outer_product_f64_xy_to_z / outer_product_f64_xy_to_z_unchecked / try_outer_product_f64_xy_to_z
+            => fma64(Fma64Operand::new());
+
+        /// Calculate `z[z_index][i] += x[i] * y[i]` for `x, y, z[_]: [i16; 32]`
+        /// (vector mode).
+        ///
+        /// `z_index` must be in range `0..64`.
+        fn mac_vector_i16 / mac_vector_i16_unchecked / try_mac_vector_i16
+            => mac16(Mac16Operand::new().vector(true));
+
+        /// Calculate `z[z_index + i % 2][i / 2] += x[i] * y[i]` for
+        /// `x, y: [i16; 32]` and `z[_]: [i32; 16]` (vector mode).
+        ///
+        /// `z_index` must be in range `0..63`.
+        fn mac_vector_i16_i32 / mac_vector_i16_i32_unchecked / try_mac_vector_i16_i32
+            => mac16(Mac16Operand::new().widen(true).vector(true));
+
+        /// Calculate `z[z_index][i] += x[i] * y[i]` for `x, y, z[_]: [f16; 32]`
+        /// (vector mode).
+        ///
+        /// `z_index` must be in range `0..64`.
+        fn fma_vector_f16 / fma_vector_f16_unchecked / try_fma_vector_f16
+            => fma16(Fma16Operand::new().vector(true));
+
+        /// Calculate `z[z_index + i % 2][i / 2] += x[i] * y[i]` for
+        /// `x, y: [f16; 32]` and `z[_]: [f32; 16]` (vector mode).
+        ///
+        /// `z_index` must be in range `0..63`.
+        fn fma_vector_f16_f32 / fma_vector_f16_f32_unchecked / try_fma_vector_f16_f32
+            => fma16(Fma16Operand::new().widen(true).vector(true));
+
+        /// Calculate `z[z_index][i] += x[i] * y[i]` for `x, y, z[_]: [f32; 16]`
+        /// (vector mode).
+        ///
+        /// `z_index` must be in range `0..64`.
+        fn fma_vector_f32 / fma_vector_f32_unchecked / try_fma_vector_f32
+            => fma32(Fma32Operand::new().vector(true));
+
+        /// Calculate `z[z_index][i] += x[i] * y[i]` for `x, y, z[_]: [f64; 8]`
+        /// (vector mode).
+        ///
+        /// `z_index` must be in range `0..64`.
+        fn fma_vector_f64 / fma_vector_f64_unchecked / try_fma_vector_f64
+            => fma64(Fma64Operand::new().vector(true));
+    }
+
+    /// Copy `z[z_index]` to X starting from byte offset `x_offset_bytes`
+    /// (`extrx`). The destination wraps around the end of X.
+    ///
+    /// This is the counterpart of the loads for chaining computations, e.g.,
+    /// applying [`Self::lut`] to accumulated values. A row of a matrix-mode
+    /// outer product's output can be located by [`ZRow::tile_row`].
+    ///
+    /// The row is copied bitwise, so the element size is irrelevant here;
+    /// it only enters in locating the row with [`ZRow::tile_row`]. The
+    /// column-extracting encodings of `extrx`/`extry` (bit 27 set), whose
+    /// lane width matters, are neither wrapped nor emulated.
+    #[inline(always)]
+    fn extract_z_to_x(&mut self, z_index: ZRow, x_offset_bytes: XBytes) {
+        debug_assert!(x_offset_bytes.0 < 0x200);
+        debug_assert!(z_index.0 < 64);
+        self.extrx(((x_offset_bytes.0 << 10) | (z_index.0 << 20)) as u64);
+    }
+
+    /// Copy `z[z_index]` to Y starting from byte offset `y_offset_bytes`
+    /// (`extry`). The destination wraps around the end of Y.
+    #[inline(always)]
+    fn extract_z_to_y(&mut self, z_index: ZRow, y_offset_bytes: YBytes) {
+        debug_assert!(y_offset_bytes.0 < 0x200);
+        debug_assert!(z_index.0 < 64);
+        self.extry((y_offset_bytes.0 | (z_index.0 << 20)) as u64);
+    }
+
+    /// Like [`Self::extract_z_to_x`], but returns an error instead of
+    /// panicking if any of the parameters is out of range.
+    #[inline]
+    fn try_extract_z_to_x(
+        &mut self,
+        z_index: ZRow,
+        x_offset_bytes: XBytes,
+    ) -> Result<(), AmxArgError> {
+        z_index.check()?;
+        x_offset_bytes.check()?;
+        self.extract_z_to_x(z_index, x_offset_bytes);
+        Ok(())
+    }
+
+    /// Like [`Self::extract_z_to_y`], but returns an error instead of
+    /// panicking if any of the parameters is out of range.
+    #[inline]
+    fn try_extract_z_to_y(
+        &mut self,
+        z_index: ZRow,
+        y_offset_bytes: YBytes,
+    ) -> Result<(), AmxArgError> {
+        z_index.check()?;
+        y_offset_bytes.check()?;
+        self.extract_z_to_y(z_index, y_offset_bytes);
+        Ok(())
+    }
+
+    /// Perform the elementwise integer multiply-add `op` (`vecint`) on X at
+    /// `x_offset_bytes`, Y at `y_offset_bytes`, and `z[z_index]`.
+    #[inline(always)]
+    fn vector_int(
+        &mut self,
+        op: VecIntOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) {
+        vecint::vecint(self, op, x_offset_bytes, y_offset_bytes, z_index);
+    }
+
+    /// Like [`Self::vector_int`], but returns an error instead of panicking
+    /// if any of the register indices and offsets is out of range.
+    #[inline]
+    fn try_vector_int(
+        &mut self,
+        op: VecIntOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) -> Result<(), AmxArgError> {
+        x_offset_bytes.check()?;
+        y_offset_bytes.check()?;
+        z_index.check()?;
+        self.vector_int(op, x_offset_bytes, y_offset_bytes, z_index);
+        Ok(())
+    }
+
+    /// Perform the elementwise floating-point operation `op` (`vecfp`) on
+    /// `ty` lanes of X at `x_offset_bytes`, Y at `y_offset_bytes`, and
+    /// `z[z_index]`.
+    ///
+    /// ```rust
+    /// use amx::{Amx, VecFpAluOp, VecFpTy, XBytes, XRow, YBytes, ZRow};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let x: [f32; 16] = std::array::from_fn(|i| i as f32 - 8.0);
+    /// unsafe { ctx.load512(x.as_ptr(), XRow(0)) };
+    /// unsafe { ctx.load512([0f32; 16].as_ptr(), ZRow(0)) };
+    ///
+    /// // ReLU
+    /// ctx.vector_fp(VecFpTy::F32, VecFpAluOp::Max, XBytes(0), YBytes(0), ZRow(0));
+    /// assert_eq!(ctx.read_z_f32()[0], x.map(|x| x.max(0.0)));
+    /// ```
+    #[inline(always)]
+    fn vector_fp(
+        &mut self,
+        ty: VecFpTy,
+        op: VecFpAluOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) {
+        vecfp::vecfp(self, ty, op, x_offset_bytes, y_offset_bytes, z_index);
+    }
+
+    /// Like [`Self::vector_fp`], but returns an error instead of panicking
+    /// if any of the register indices and offsets is out of range.
+    #[inline]
+    fn try_vector_fp(
+        &mut self,
+        ty: VecFpTy,
+        op: VecFpAluOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) -> Result<(), AmxArgError> {
+        x_offset_bytes.check()?;
+        y_offset_bytes.check()?;
+        z_index.check()?;
+        self.vector_fp(ty, op, x_offset_bytes, y_offset_bytes, z_index);
+        Ok(())
+    }
+
+    /// Calculate the dot product of `x: &[f32]` and `y: &[f32]`.
+    ///
+    /// See [`Self::dot_f32_batch`] for how it's computed. This overwrites
+    /// X, Y, and Z.
+    ///
+    /// ```rust
+    /// use amx::Amx;
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let x: Vec<f32> = (1..=20).map(|i| i as f32).collect();
+    /// assert_eq!(ctx.dot_f32(&x, &[1.0; 20]), 210.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    fn dot_f32(&mut self, x: &[f32], y: &[f32]) -> f32 {
+        let mut out = [0.0];
+        self.dot_f32_batch(&[(x, y)], &mut out);
+        out[0]
+    }
+
+    /// Calculate the dot products of many pairs of `&[f32]`, writing the
+    /// result for `pairs[i]` to `out[i]`. The pairs can have different
+    /// lengths.
+    ///
+    /// Up to 64 pairs are processed at once, each accumulated in a row of Z
+    /// by vector-mode `fma32`. The 16 lanes of each row are then summed on
+    /// AMX by copying the row to X by [`Self::extract_z_to_x`] and adding
+    /// its upper half to its lower half by [`Self::vector_fp`], four times.
+    /// The order of the additions thus depends only on the lengths.
+    ///
+    /// This overwrites X, Y, and Z.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pairs` and `out` have different lengths or the slices of a
+    /// pair have different lengths.
+    fn dot_f32_batch(&mut self, pairs: &[(&[f32], &[f32])], out: &mut [f32]) {
+        dot::dot_f32_batch(self, pairs, out);
+    }
+
+    /// Perform the integer outer product `op` (`matint`) of X at
+    /// `x_offset_bytes` and Y at `y_offset_bytes`, writing to the tile of Z
+    /// selected by `z_index`.
+    #[inline(always)]
+    fn matrix_int(
+        &mut self,
+        op: MatIntOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) {
+        matop::matint(self, op, x_offset_bytes, y_offset_bytes, z_index);
+    }
+
+    /// Like [`Self::matrix_int`], but returns an error instead of panicking
+    /// if any of the register indices and offsets is out of range.
+    #[inline]
+    fn try_matrix_int(
+        &mut self,
+        op: MatIntOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) -> Result<(), AmxArgError> {
+        x_offset_bytes.check()?;
+        y_offset_bytes.check()?;
+        z_index.check()?;
+        self.matrix_int(op, x_offset_bytes, y_offset_bytes, z_index);
+        Ok(())
+    }
+
+    /// Perform the floating-point outer product `op` (`matfp`) of X at
+    /// `x_offset_bytes` and Y at `y_offset_bytes`, writing to the tile of Z
+    /// selected by `z_index`.
+    ///
+    /// # Panics
+    ///
+    /// With the `amx2` feature, panics if the lane type requires a newer AMX
+    /// revision than [`AmxOps::version`].
+    #[inline(always)]
+    fn matrix_fp(
+        &mut self,
+        op: MatFpOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) {
+        matop::matfp(self, op, x_offset_bytes, y_offset_bytes, z_index);
+    }
+
+    /// Like [`Self::matrix_fp`], but returns an error instead of panicking
+    /// if any of the register indices and offsets is out of range or the
+    /// lane type is unsupported.
+    #[inline]
+    fn try_matrix_fp(
+        &mut self,
+        op: MatFpOp,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+    ) -> Result<(), AmxArgError> {
+        let required = op.ty().required_version();
+        if self.version() < required {
+            return Err(AmxArgError::Unsupported { required });
+        }
+        x_offset_bytes.check()?;
+        y_offset_bytes.check()?;
+        z_index.check()?;
+        self.matrix_fp(op, x_offset_bytes, y_offset_bytes, z_index);
+        Ok(())
+    }
+
+    /// Calculate the outer product of `x: [bf16; 32]` and `y: [bf16; 32]`
+    /// and write the output to `z: [[f32; 16]; 64]`. `x[i] * y[j]` is
+    /// written to `z[j * 2 + i % 2][i / 2]`, as in
+    /// [`Self::outer_product_f16_xy_to_z_f32`]. Requires the `amx2` feature.
+    ///
+    /// The products are exact, and each sum is rounded once to `f32`. The
+    /// inputs can be prepared by [`fp16::f32_to_bf16`] or, with the `half`
+    /// feature, loaded from `half::bf16` slices by [`Self::load512_slice`].
+    ///
+    /// This uses `matfp`, which supports `bf16` since [`AmxVersion::M2`]. On
+    /// earlier revisions, the inputs are widened to `f32` and multiplied by
+    /// four `fma32` outer products instead, with the same results. X row 0
+    /// and Y row 0 are used as scratch space and restored afterwards.
+    ///
+    /// ```rust
+    /// use amx::{Amx, AmxVersion, XBytes, XRow, YBytes, YRow, ZRow, fp16::f32_to_bf16};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// ctx.set_version(AmxVersion::M2);
+    /// ctx.load512_slice(&[f32_to_bf16(1.5); 32], XRow(0));
+    /// ctx.load512_slice(&[f32_to_bf16(-2.0); 32], YRow(0));
+    /// ctx.outer_product_bf16_xy_to_z_f32(XBytes(0), YBytes(0), ZRow(0), false);
+    /// assert_eq!(ctx.read_z_f32(), [[-3.0; 16]; 64]);
+    /// ```
+    #[cfg(feature = "amx2")]
+    #[inline(always)]
+    fn outer_product_bf16_xy_to_z_f32(
+        &mut self,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+        accumulate: bool,
+    ) {
+        if self.version() < AmxVersion::M2 {
+            matop::bf16_outer_product_by_fma32(self, x_offset_bytes, y_offset_bytes, accumulate);
+            return;
+        }
+        let op = MatFpOp::new(MatFpTy::Bf16)
+            .widen(true)
+            .accumulate(accumulate);
+        self.matrix_fp(op, x_offset_bytes, y_offset_bytes, z_index);
+    }
+
+    /// Like [`Self::outer_product_bf16_xy_to_z_f32`], but returns an error
+    /// instead of panicking if any of the parameters is out of range.
+    #[cfg(feature = "amx2")]
+    #[inline]
+    fn try_outer_product_bf16_xy_to_z_f32(
+        &mut self,
+        x_offset_bytes: XBytes,
+        y_offset_bytes: YBytes,
+        z_index: ZRow,
+        accumulate: bool,
+    ) -> Result<(), AmxArgError> {
+        x_offset_bytes.check()?;
+        y_offset_bytes.check()?;
+        z_index.check()?;
+        self.outer_product_bf16_xy_to_z_f32(x_offset_bytes, y_offset_bytes, z_index, accumulate);
+        Ok(())
+    }
+
+    /// Perform (reverse) table lookup.
+    #[inline(always)]
+    fn lut(&mut self, input: impl LutIn, table: XRow, output: impl LutOut, ty: impl LutTy) {
+        genlut::lut(self, input, table, output, ty);
+    }
+
+    /// Like [`Self::lut`], but returns an error instead of panicking if any
+    /// of the register indices and offsets is out of range.
+    #[inline]
+    fn try_lut(
+        &mut self,
+        input: impl LutIn + CheckArg,
+        table: XRow,
+        output: impl LutOut + CheckArg,
+        ty: impl LutTy,
+    ) -> Result<(), AmxArgError> {
+        input.check()?;
+        table.check()?;
+        output.check()?;
+        self.lut(input, table, output, ty);
+        Ok(())
+    }
+
+    /// Find the interval of a sorted table containing each element of
+    /// `input`.
+    ///
+    /// `table` holds the interval boundaries in ascending order. Each input
+    /// element is converted to the index of the last boundary not greater
+    /// than it, or all ones if it's smaller than every boundary, and the
+    /// indices are packed in the low bytes of `output`. Use [`lut_index`] to
+    /// extract them.
+    ///
+    /// This can be used for quantization: with the smallest representable
+    /// value followed by the midpoints between adjacent codebook entries as
+    /// the boundaries, each index is that of the nearest codebook entry.
+    ///
+    /// ```rust
+    /// use amx::{Amx, F32, Index4, LutReverseTy, Reverse, XRow, YBytes, YRow, lut_index};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let boundaries: [f32; 16] = std::array::from_fn(|i| i as f32 * 10.0);
+    /// ctx.load512_slice(&boundaries, XRow(1));
+    /// let mut input = [0.0f32; 16];
+    /// input[..4].copy_from_slice(&[-5.0, 0.0, 42.0, 1000.0]);
+    /// ctx.load512_slice(&input, YRow(0));
+    ///
+    /// let ty = (Reverse, Index4, F32);
+    /// ctx.lut_reverse(YBytes(0), XRow(1), XRow(2), ty);
+    /// let out = ctx.read_x();
+    /// let indices: Vec<u8> = (0..4)
+    ///     .map(|i| lut_index(&out[128..], <(Reverse, Index4, F32)>::INDEX_BITS, i))
+    ///     .collect();
+    /// assert_eq!(indices, [15, 0, 4, 15]);
+    /// ```
+    #[inline(always)]
+    fn lut_reverse(
+        &mut self,
+        input: impl LutIn,
+        table: XRow,
+        output: impl LutOut,
+        ty: impl LutReverseTy,
+    ) {
+        genlut::lut(self, input, table, output, ty);
+    }
+
+    /// Like [`Self::lut_reverse`], but returns an error instead of panicking
+    /// if any of the register indices and offsets is out of range.
+    #[inline]
+    fn try_lut_reverse(
+        &mut self,
+        input: impl LutIn + CheckArg,
+        table: XRow,
+        output: impl LutOut + CheckArg,
+        ty: impl LutReverseTy,
+    ) -> Result<(), AmxArgError> {
+        self.try_lut(input, table, output, ty)
+    }
+
+    /// Permute the lanes of `src` by `shuffle` and write the result to
+    /// `output`. See [`Shuffle512`] for an example.
+    ///
+    /// The packed indices of `shuffle` are loaded to `scratch`, overwriting
+    /// its contents. `output` may be the same row as `src`.
+    #[inline]
+    fn shuffle512(&mut self, shuffle: &Shuffle512, src: XRow, scratch: YRow, output: impl LutOut) {
+        self.load512_slice(shuffle.packed_indices(), scratch);
+        let input = YBytes(scratch.0 * 64);
+        genlut::lut(self, input, src, output, genlut::RawLutMode(shuffle.mode()));
+    }
+}
+
+impl<T: AmxOps + ?Sized> Amx for T {}
+
+/// An object-safe AMX backend.
+///
+/// [`Amx`] is not dyn-compatible because many of its methods are generic.
+/// Its supertrait [`AmxOps`] is, and `DynAmx` is implemented for every
+/// `AmxOps` implementation. Since `Box<dyn DynAmx>` and `&mut dyn DynAmx`
+/// implement `AmxOps` (and therefore `Amx`), a backend can be chosen at
+/// runtime and used through one pointer:
+///
+/// ```rust
+/// use amx::{Amx, DynAmx, XBytes, YBytes, ZRow};
+///
+/// fn kernel(ctx: &mut impl Amx) {
+///     ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+/// }
+///
+/// fn run(backend: &mut Box<dyn DynAmx>) {
+///     kernel(backend);
+/// }
+/// ```
+///
+/// Functions taking `&mut (impl Amx + ?Sized)` can be called with
+/// `&mut dyn DynAmx` directly, so they are instantiated once for all
+/// backends stored this way. [`AmxFallbackCtx`] is a ready-made choice
+/// between the native context and the emulator:
+///
+/// ```rust
+/// use amx::{AmxEmuCtx, AmxFallbackCtx, DynAmx, linalg::{self, MatMut, MatRef}};
+///
+/// struct Engine {
+///     ctx: Box<dyn DynAmx>,
+/// }
+///
+/// let engines = [
+///     Engine { ctx: Box::new(AmxFallbackCtx::try_new().unwrap()) },
+///     Engine { ctx: Box::new(AmxEmuCtx::default()) },
+/// ];
+/// for mut engine in engines {
+///     let (a, b) = ([1.0, 2.0], [3.0, 4.0]);
+///     let mut c = [0.0];
+///     let (a, b) = (MatRef::new(&a, 1, 2), MatRef::new(&b, 2, 1));
+///     linalg::sgemm(&mut *engine.ctx, a, b, MatMut::new(&mut c, 1, 1), false);
+///     assert_eq!(c, [11.0]);
+/// }
+/// ```
+pub trait DynAmx: AmxOps {}
+
+impl<T: AmxOps + ?Sized> DynAmx for T {}
@@ -0,0 +1,96 @@
+//! Batched inversion of small matrices
+use super::{F32_LANES, fma32_vector};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// Invert every `N`×`N` matrix in `input`, writing the results to `output`.
+///
+/// The matrices are processed 16 at a time by Gauss-Jordan elimination,
+/// with each vector lane holding one matrix of the batch (struct-of-arrays
+/// layout), so every step of the elimination is a vector-mode `fma32`/`fms32`
+/// over the whole batch. Only the pivot reciprocals are calculated by scalar
+/// code.
+///
+/// No pivoting is performed, so this is intended for well-conditioned
+/// matrices such as rigid transforms and covariance matrices. A zero pivot
+/// produces non-finite elements in the corresponding output.
+///
+/// This overwrites `x`, `y[0]`, and `z[0..2 * N]`.
+///
+/// # Panics
+///
+/// Panics if `input.len() != output.len()` or `N > 16`.
+pub fn batch_inverse<const N: usize>(
+    ctx: &mut (impl Amx + ?Sized),
+    input: &[[[f32; N]; N]],
+    output: &mut [[[f32; N]; N]],
+) {
+    assert_eq!(input.len(), output.len(), "batch size mismatch");
+    assert!(N <= 16, "matrices larger than 16x16 are not supported");
+
+    let width = N * 2;
+    // The augmented matrix `[A | I]`, `aug[r * width + c][lane]`
+    let mut aug = vec![[0f32; F32_LANES]; N * width];
+
+    for b0 in (0..input.len()).step_by(F32_LANES) {
+        let nb = (input.len() - b0).min(F32_LANES);
+        for r in 0..N {
+            for c in 0..N {
+                let identity = (r == c) as u32 as f32;
+                aug[r * width + c] = std::array::from_fn(|lane| {
+                    // Fill unused lanes with the identity matrix
+                    if lane < nb {
+                        input[b0 + lane][r][c]
+                    } else {
+                        identity
+                    }
+                });
+                aug[r * width + N + c] = [identity; F32_LANES];
+            }
+        }
+
+        for k in 0..N {
+            // Normalize the pivot row: `aug[k][..] *= 1 / aug[k][k]`
+            let recip = aug[k * width + k].map(|x| 1.0 / x);
+            // Safety: Reading 64 bytes from `[f32; 16]`
+            unsafe { ctx.load512(recip.as_ptr(), YRow(0)) };
+            for c in 0..width {
+                // Safety: Reading and writing 64 bytes from/to `[f32; 16]`
+                unsafe { ctx.load512(aug[k * width + c].as_ptr(), XRow(0)) };
+                fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(c), false, false);
+                unsafe { ctx.store512(aug[k * width + c].as_mut_ptr(), ZRow(c)) };
+            }
+
+            // Eliminate column `k` from the other rows:
+            // `aug[r][..] -= aug[r][k] * aug[k][..]`
+            let factors: Vec<[f32; F32_LANES]> = (0..N).map(|r| aug[r * width + k]).collect();
+            for c0 in (0..width).step_by(8) {
+                let num_cols = (width - c0).min(8);
+                for cc in 0..num_cols {
+                    // Safety: Reading 64 bytes from `[f32; 16]`
+                    unsafe { ctx.load512(aug[k * width + c0 + cc].as_ptr(), XRow(cc)) };
+                }
+                for (r, factor) in factors.iter().enumerate() {
+                    if r == k {
+                        continue;
+                    }
+                    // Safety: Reading and writing 64 bytes from/to `[f32; 16]`
+                    unsafe { ctx.load512(factor.as_ptr(), YRow(0)) };
+                    for cc in 0..num_cols {
+                        let c = c0 + cc;
+                        unsafe { ctx.load512(aug[r * width + c].as_ptr(), ZRow(c)) };
+                        fma32_vector(ctx, XBytes(cc * 64), YBytes(0), ZRow(c), true, true);
+                        unsafe { ctx.store512(aug[r * width + c].as_mut_ptr(), ZRow(c)) };
+                    }
+                }
+            }
+        }
+
+        for (lane, out) in output[b0..b0 + nb].iter_mut().enumerate() {
+            for (r, out) in out.iter_mut().enumerate() {
+                for (c, out) in out.iter_mut().enumerate() {
+                    *out = aug[r * width + N + c][lane];
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Direct 2D convolution for neural network inference
+use super::{F32_LANES, cgemm::read_tile_row};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// The shape of a 2D convolution computed by [`conv2d_f32`]
+///
+/// The input is `batch`×`in_channels`×`height`×`width` and the filter is
+/// `out_channels`×`in_channels`×`kernel`×`kernel`, both in the NCHW (row-major)
+/// layout. The input is implicitly surrounded by `padding` zeros on each
+/// side, and the stride is 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Conv2dShape {
+    pub batch: usize,
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub height: usize,
+    pub width: usize,
+    /// The height and width of the filter
+    pub kernel: usize,
+    pub padding: usize,
+}
+
+impl Conv2dShape {
+    /// Get the height and width of the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded input is smaller than the filter.
+    pub fn output_size(&self) -> (usize, usize) {
+        let size = |len: usize| {
+            (len + 2 * self.padding + 1)
+                .checked_sub(self.kernel)
+                .expect("the padded input is smaller than the filter")
+        };
+        (size(self.height), size(self.width))
+    }
+
+    /// Get the number of elements of the input.
+    pub fn input_len(&self) -> usize {
+        self.batch * self.in_channels * self.height * self.width
+    }
+
+    /// Get the number of elements of the filter.
+    pub fn filter_len(&self) -> usize {
+        self.out_channels * self.in_channels * self.kernel * self.kernel
+    }
+
+    /// Get the number of elements of the output
+    /// (`batch`×`out_channels`×[`Self::output_size`]).
+    pub fn output_len(&self) -> usize {
+        let (oh, ow) = self.output_size();
+        self.batch * self.out_channels * oh * ow
+    }
+}
+
+/// Calculate the 2D convolution (strictly speaking, cross-correlation, as in
+/// most deep learning frameworks)
+///
+/// ```text
+/// output[n, co, y, x] = bias[co] + sum(
+///     filter[co, ci, ky, kx] * input[n, ci, y + ky - padding, x + kx - padding]
+///     for ci, ky, kx
+/// )
+/// ```
+///
+/// of NCHW tensors with stride 1. `bias` defaults to zero.
+///
+/// ```rust
+/// use amx::linalg::{Conv2dShape, conv2d_f32};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let shape = Conv2dShape {
+///     batch: 1,
+///     in_channels: 1,
+///     out_channels: 1,
+///     height: 2,
+///     width: 3,
+///     kernel: 3,
+///     padding: 1,
+/// };
+/// let input = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+/// // A box filter
+/// let filter = [1.0; 9];
+/// let mut output = [0.0; 6];
+/// conv2d_f32(&mut ctx, shape, &input, &filter, None, &mut output);
+/// assert_eq!(output, [12.0, 21.0, 16.0, 12.0, 21.0, 16.0]);
+/// ```
+///
+/// # Execution
+///
+/// The filter is repacked once so that 16 output channels of each filter
+/// tap are contiguous, but the input is read in place, without materializing
+/// the `im2col` matrix, which is `kernel * kernel` times larger than the
+/// input. For each output row, 32 output channels × 32 pixels are accumulated
+/// in the four 16×16 `f32` tiles of Z by [`Amx::outer_product_f32_xy_to_z`],
+/// with the filter taps in Y and 16 consecutive input pixels, offset by the
+/// tap position, in X. Only the input segments overlapping the padding are
+/// copied to a buffer.
+///
+/// This is intended for small filters such as 1×1 and 3×3, where the
+/// overhead of `im2col` dominates. This overwrites `x[0..2]`, `y[0..2]`, and
+/// `z`.
+///
+/// # Panics
+///
+/// Panics if the lengths of the slices don't match `shape`.
+pub fn conv2d_f32(
+    ctx: &mut (impl Amx + ?Sized),
+    shape: Conv2dShape,
+    input: &[f32],
+    filter: &[f32],
+    bias: Option<&[f32]>,
+    output: &mut [f32],
+) {
+    let Conv2dShape {
+        batch,
+        in_channels: c_in,
+        out_channels: c_out,
+        height: h,
+        width: w,
+        kernel: k,
+        padding: pad,
+    } = shape;
+    let (oh, ow) = shape.output_size();
+    assert_eq!(input.len(), shape.input_len(), "shape mismatch in `input`");
+    assert_eq!(
+        filter.len(),
+        shape.filter_len(),
+        "shape mismatch in `filter`"
+    );
+    assert_eq!(
+        output.len(),
+        shape.output_len(),
+        "shape mismatch in `output`"
+    );
+    if let Some(bias) = bias {
+        assert_eq!(bias.len(), c_out, "shape mismatch in `bias`");
+    }
+    if output.is_empty() {
+        return;
+    }
+
+    let taps = c_in * k * k;
+    let co_blocks = c_out.div_ceil(F32_LANES);
+    // `filter_pack[cb * taps + tap]` = `filter[cb * 16..][..16, tap]`, where
+    // `tap = (ci * k + ky) * k + kx`
+    let mut filter_pack = vec![[0f32; F32_LANES]; co_blocks * taps];
+    for co in 0..c_out {
+        let src = &filter[co * taps..][..taps];
+        for (tap, &f) in src.iter().enumerate() {
+            filter_pack[co / F32_LANES * taps + tap][co % F32_LANES] = f;
+        }
+    }
+
+    let mut buf = [0f32; F32_LANES];
+    for n in 0..batch {
+        let input = &input[n * c_in * h * w..][..c_in * h * w];
+        let output = &mut output[n * c_out * oh * ow..][..c_out * oh * ow];
+
+        for cb0 in (0..co_blocks).step_by(2) {
+            let ms = (co_blocks - cb0).min(2);
+            for y in 0..oh {
+                for x0 in (0..ow).step_by(F32_LANES * 2) {
+                    let ns = (ow - x0).div_ceil(F32_LANES).min(2);
+                    let mut first = true;
+
+                    for ci in 0..c_in {
+                        for ky in 0..k {
+                            // Skip the rows in the padding
+                            let Some(iy) = (y + ky).checked_sub(pad).filter(|&iy| iy < h) else {
+                                continue;
+                            };
+                            let row = &input[(ci * h + iy) * w..][..w];
+
+                            for kx in 0..k {
+                                let tap = (ci * k + ky) * k + kx;
+                                for t in 0..ms {
+                                    let src = &filter_pack[(cb0 + t) * taps + tap];
+                                    // Safety: Reading 64 bytes from `[f32; 16]`
+                                    unsafe { ctx.load512(src.as_ptr(), YRow(t)) };
+                                }
+                                for s in 0..ns {
+                                    let ix = (x0 + s * F32_LANES + kx) as isize - pad as isize;
+                                    load_segment(ctx, row, ix, XRow(s), &mut buf);
+                                }
+
+                                for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                                    ctx.outer_product_f32_xy_to_z(
+                                        Some(XBytes(s * 64)),
+                                        Some(YBytes(t * 64)),
+                                        ZRow(t * 2 + s),
+                                        !first,
+                                    );
+                                }
+                                first = false;
+                            }
+                        }
+                    }
+
+                    // `output[n, (cb0 + t) * 16 + j, y, x0 + s * 16..]` is in
+                    // row `j` of tile `t * 2 + s`
+                    for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                        let cols = x0 + s * F32_LANES..(x0 + (s + 1) * F32_LANES).min(ow);
+                        let channels =
+                            (cb0 + t) * F32_LANES..((cb0 + t + 1) * F32_LANES).min(c_out);
+                        for (j, co) in channels.enumerate() {
+                            let b = bias.map_or(0.0, |bias| bias[co]);
+                            let dst = &mut output[(co * oh + y) * ow..][cols.clone()];
+                            if first {
+                                // Every tap was in the padding
+                                dst.fill(b);
+                                continue;
+                            }
+                            let acc = read_tile_row(ctx, t * 2 + s, j);
+                            for (d, &a) in dst.iter_mut().zip(&acc) {
+                                *d = a + b;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Load `row[ix..][..16]` to `x_row`, where the elements outside `row` are
+/// zero (padding).
+#[inline]
+fn load_segment(
+    ctx: &mut (impl Amx + ?Sized),
+    row: &[f32],
+    ix: isize,
+    x_row: XRow,
+    buf: &mut [f32; F32_LANES],
+) {
+    if ix >= 0 && ix as usize + F32_LANES <= row.len() {
+        // Safety: Reading 64 bytes from a slice with at least 16 elements
+        unsafe { ctx.load512(row[ix as usize..].as_ptr(), x_row) };
+        return;
+    }
+    for (i, dst) in buf.iter_mut().enumerate() {
+        *dst = usize::try_from(ix + i as isize)
+            .ok()
+            .and_then(|ix| row.get(ix))
+            .map_or(0.0, |&v| v);
+    }
+    // Safety: Reading 64 bytes from `[f32; 16]`
+    unsafe { ctx.load512(buf.as_ptr(), x_row) };
+}
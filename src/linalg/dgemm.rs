@@ -0,0 +1,129 @@
+//! Double-precision matrix multiplication
+use super::{MatMut, MatRef};
+use crate::{Amx, ZRow, pipeline::Pipeline};
+
+/// The number of `f64` lanes read by `fma64`
+const LANES: usize = 8;
+/// The number of strips of `B` (columns of `C`) computed at once
+const NS: usize = 4;
+/// The number of strips of `A` (rows of `C`) computed at once
+const MS: usize = 2;
+
+/// Calculate `C = A * B` (or `C += A * B` if `accumulate` is set) for `f64`
+/// matrices.
+///
+/// ```rust
+/// use amx::linalg::{MatMut, MatRef, dgemm};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [1.0, 2.0, 3.0, 4.0];
+/// let b = [1.0, 1.0, 0.5, -1.0];
+/// let mut c = [0.0; 4];
+/// dgemm(
+///     &mut ctx,
+///     MatRef::new(&a, 2, 2),
+///     MatRef::new(&b, 2, 2),
+///     MatMut::new(&mut c, 2, 2),
+///     false,
+/// );
+/// assert_eq!(c, [2.0, -1.0, 5.0, -1.0]);
+/// ```
+///
+/// # Execution
+///
+/// `C` is computed in 16×32 blocks, each accumulated in the eight 8×8 `f64`
+/// tiles of Z by [`Amx::outer_product_f64_xy_to_z`]. Each block is summed
+/// over the whole of `K` before it's added to `C`.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn dgemm(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, f64>,
+    b: MatRef<'_, f64>,
+    mut c: MatMut<'_, f64>,
+    accumulate: bool,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(b.rows(), k, "shape mismatch in `b`");
+    assert_eq!((c.rows(), c.cols()), (m, n), "shape mismatch in `c`");
+    if k == 0 {
+        if !accumulate {
+            for i in 0..m {
+                c.row_mut(i).fill(0.0);
+            }
+        }
+        return;
+    }
+
+    // `b_pack[s * k + kk]` = `B[kk, j0 + s * 8..][..8]`
+    let mut b_pack = vec![[0f64; LANES]; NS * k];
+    // `a_pack[t * k + kk]` = `A[i0 + t * 8..][..8, kk]`
+    let mut a_pack = vec![[0f64; LANES]; MS * k];
+
+    for j0 in (0..n).step_by(LANES * NS) {
+        let ns = (n - j0).div_ceil(LANES).min(NS);
+        for s in 0..ns {
+            let cols = j0 + s * LANES..(j0 + (s + 1) * LANES).min(n);
+            for kk in 0..k {
+                let src = &b.row(kk)[cols.clone()];
+                let dst = &mut b_pack[s * k + kk];
+                dst[..src.len()].copy_from_slice(src);
+                dst[src.len()..].fill(0.0);
+            }
+        }
+
+        for i0 in (0..m).step_by(LANES * MS) {
+            let ms = (m - i0).div_ceil(LANES).min(MS);
+            for t in 0..ms {
+                for kk in 0..k {
+                    for (ii, dst) in a_pack[t * k + kk].iter_mut().enumerate() {
+                        let i = i0 + t * LANES + ii;
+                        *dst = if i < m { a.row(i)[kk] } else { 0.0 };
+                    }
+                }
+            }
+
+            Pipeline::deepest(NS, MS).run(
+                ctx,
+                k,
+                |ctx, kk, slot| {
+                    // Safety: Reading 64 bytes from each `[f64; 8]`
+                    unsafe {
+                        for s in 0..ns {
+                            ctx.load512(b_pack[s * k + kk].as_ptr(), slot.x_row(s));
+                        }
+                        for t in 0..ms {
+                            ctx.load512(a_pack[t * k + kk].as_ptr(), slot.y_row(t));
+                        }
+                    }
+                },
+                |ctx, kk, slot| {
+                    for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                        ctx.outer_product_f64_xy_to_z(
+                            Some(slot.x_bytes(s)),
+                            Some(slot.y_bytes(t)),
+                            ZRow(t * NS + s),
+                            kk != 0,
+                        );
+                    }
+                },
+            );
+
+            // `C[i0 + t * 8 + ii, j0 + s * 8 + jj]` is in
+            // `z[ii * 8 + t * 4 + s][jj]`
+            let z = ctx.read_z_f64();
+            for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                let rows = i0 + t * LANES..(i0 + (t + 1) * LANES).min(m);
+                let cols = j0 + s * LANES..(j0 + (s + 1) * LANES).min(n);
+                for (ii, i) in rows.enumerate() {
+                    let src = &z[ii * 8 + t * NS + s];
+                    for (d, &s) in c.row_mut(i)[cols.clone()].iter_mut().zip(src) {
+                        *d = if accumulate { *d + s } else { s };
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Real single-precision matrix multiplication and dot product
+use super::{
+    F32_LANES, MatMut, MatRef,
+    cgemm::{pack_col, pack_row, read_tile_row},
+    fma32_vector,
+};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow, pipeline::Pipeline};
+
+/// The number of columns of `B` packed at once
+const NC: usize = 512;
+/// The number of rows of `A` and `B` packed at once
+const KC: usize = 256;
+/// The number of rows of `A` packed at once
+const MC: usize = 128;
+
+/// Calculate `C = A * B` (or `C += A * B` if `accumulate` is set).
+///
+/// This is [`sgemm_scaled`] with `alpha = 1` and `beta = accumulate as
+/// f32`.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn sgemm(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, f32>,
+    b: MatRef<'_, f32>,
+    c: MatMut<'_, f32>,
+    accumulate: bool,
+) {
+    let beta = if accumulate { 1.0 } else { 0.0 };
+    sgemm_scaled(ctx, 1.0, a, b, beta, c);
+}
+
+/// Calculate `C = alpha * A * B + beta * C`.
+///
+/// If `beta` is zero, the original contents of `C` are not read, so they
+/// may be NaN. The matrices can have any shape and stride.
+///
+/// ```rust
+/// use amx::linalg::{MatMut, MatRef, sgemm_scaled};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+/// let b = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+/// let mut c = [1.0; 4];
+/// sgemm_scaled(
+///     &mut ctx,
+///     2.0,
+///     MatRef::new(&a, 2, 3),
+///     MatRef::new(&b, 3, 2),
+///     -1.0,
+///     MatMut::new(&mut c, 2, 2),
+/// );
+/// assert_eq!(c, [7.0, 9.0, 19.0, 21.0]);
+/// ```
+///
+/// # Blocking
+///
+/// `B` is packed in panels of up to 256 rows and 512 columns, and `A` in
+/// panels of up to 128 rows and 256 columns, so that the panels stay in
+/// the cache while they are reused. The product of a pair of panels is
+/// computed in 32×32 blocks, each accumulated in the four 16×16 `f32` tiles
+/// of Z and added to `C` when the panel is done. The panels along `K` are
+/// thus summed on the CPU.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn sgemm_scaled(
+    ctx: &mut (impl Amx + ?Sized),
+    alpha: f32,
+    a: MatRef<'_, f32>,
+    b: MatRef<'_, f32>,
+    beta: f32,
+    mut c: MatMut<'_, f32>,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(b.rows(), k, "shape mismatch in `b`");
+    assert_eq!((c.rows(), c.cols()), (m, n), "shape mismatch in `c`");
+    if k == 0 || alpha == 0.0 {
+        for i in 0..m {
+            scale_row(c.row_mut(i), beta);
+        }
+        return;
+    }
+
+    // `b_pack[s * kc + kk]` = `B[p0 + kk, j0 + s * 16..][..16]`
+    let mut b_pack = vec![[0f32; F32_LANES]; NC / F32_LANES * KC.min(k)];
+    // `a_pack[t * kc + kk]` = `A[i0 + t * 16..][..16, p0 + kk]`
+    let mut a_pack = vec![[0f32; F32_LANES]; MC / F32_LANES * KC.min(k)];
+
+    for j0 in (0..n).step_by(NC) {
+        let nc = (n - j0).min(NC);
+        let n_strips = nc.div_ceil(F32_LANES);
+        for p0 in (0..k).step_by(KC) {
+            let kc = (k - p0).min(KC);
+            // `C` is scaled by `beta` only once
+            let beta = if p0 == 0 { beta } else { 1.0 };
+            for s in 0..n_strips {
+                let cols = j0 + s * F32_LANES..(j0 + (s + 1) * F32_LANES).min(n);
+                for kk in 0..kc {
+                    pack_row(&mut b_pack[s * kc + kk], &b.row(p0 + kk)[cols.clone()]);
+                }
+            }
+
+            for i0 in (0..m).step_by(MC) {
+                let m_strips = (m - i0).min(MC).div_ceil(F32_LANES);
+                for t in 0..m_strips {
+                    for kk in 0..kc {
+                        pack_col(&mut a_pack[t * kc + kk], &a, i0 + t * F32_LANES, p0 + kk);
+                    }
+                }
+
+                for s0 in (0..n_strips).step_by(2) {
+                    let ns = (n_strips - s0).min(2);
+                    for t0 in (0..m_strips).step_by(2) {
+                        let ms = (m_strips - t0).min(2);
+                        Pipeline::deepest(2, 2).run(
+                            ctx,
+                            kc,
+                            |ctx, kk, slot| {
+                                // Safety: Reading 64 bytes from each `[f32; 16]`
+                                unsafe {
+                                    for s in 0..ns {
+                                        let src = &b_pack[(s0 + s) * kc + kk];
+                                        ctx.load512(src.as_ptr(), slot.x_row(s));
+                                    }
+                                    for t in 0..ms {
+                                        let src = &a_pack[(t0 + t) * kc + kk];
+                                        ctx.load512(src.as_ptr(), slot.y_row(t));
+                                    }
+                                }
+                            },
+                            |ctx, kk, slot| {
+                                for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                                    ctx.outer_product_f32_xy_to_z(
+                                        Some(slot.x_bytes(s)),
+                                        Some(slot.y_bytes(t)),
+                                        ZRow(t * 2 + s),
+                                        kk != 0,
+                                    );
+                                }
+                            },
+                        );
+
+                        for (t, s) in (0..ms).flat_map(|t| (0..ns).map(move |s| (t, s))) {
+                            let rows =
+                                i0 + (t0 + t) * F32_LANES..(i0 + (t0 + t + 1) * F32_LANES).min(m);
+                            let cols =
+                                j0 + (s0 + s) * F32_LANES..(j0 + (s0 + s + 1) * F32_LANES).min(n);
+                            for (ii, i) in rows.enumerate() {
+                                let acc = read_tile_row(ctx, t * 2 + s, ii);
+                                update_row(&mut c.row_mut(i)[cols.clone()], &acc, alpha, beta);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Calculate `dst = beta * dst`, without reading `dst` if `beta` is zero.
+fn scale_row(dst: &mut [f32], beta: f32) {
+    if beta == 0.0 {
+        dst.fill(0.0);
+    } else if beta != 1.0 {
+        dst.iter_mut().for_each(|d| *d *= beta);
+    }
+}
+
+/// Calculate `dst = alpha * acc + beta * dst`, without reading `dst` if
+/// `beta` is zero.
+#[inline]
+fn update_row(dst: &mut [f32], acc: &[f32; F32_LANES], alpha: f32, beta: f32) {
+    for (d, &s) in dst.iter_mut().zip(acc) {
+        *d = if beta == 0.0 {
+            alpha * s
+        } else {
+            alpha * s + beta * *d
+        };
+    }
+}
+
+/// Calculate the dot product of `x` and `y`.
+///
+/// The products are accumulated in 16 lanes, which are summed at the end.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths.
+pub fn sdot(ctx: &mut (impl Amx + ?Sized), x: &[f32], y: &[f32]) -> f32 {
+    assert_eq!(x.len(), y.len(), "length mismatch");
+    if x.is_empty() {
+        return 0.0;
+    }
+
+    let mut xb = [0f32; F32_LANES];
+    let mut yb = [0f32; F32_LANES];
+    for (i, (x, y)) in x.chunks(F32_LANES).zip(y.chunks(F32_LANES)).enumerate() {
+        pack_row(&mut xb, x);
+        pack_row(&mut yb, y);
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            ctx.load512(xb.as_ptr(), XRow(0));
+            ctx.load512(yb.as_ptr(), YRow(0));
+        }
+        fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(0), i != 0, false);
+    }
+
+    let mut z = [0f32; F32_LANES];
+    // Safety: Writing 64 bytes to `[f32; 16]`
+    unsafe { ctx.store512(z.as_mut_ptr(), ZRow(0)) };
+    z.iter().sum()
+}
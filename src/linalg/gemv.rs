@@ -0,0 +1,270 @@
+//! Matrix-vector multiplication
+use super::MatRef;
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+use std::ops::Add;
+
+/// The number of rows of Z
+const Z_ROWS: usize = 64;
+
+/// A floating-point element type of the matrix-vector routines
+trait Float: Copy + Default + Add<Output = Self> {
+    /// The number of lanes in a register row
+    const LANES: usize = 64 / size_of::<Self>();
+
+    /// Calculate the outer product of `x` and `y` into the `tile`-th tile
+    /// of Z, whose first row is `z[tile]`.
+    fn outer_product(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        tile: usize,
+        accumulate: bool,
+    );
+
+    /// Calculate `z[z_index][i] += x[i] * y[i]`.
+    fn fma_vector(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        z_index: ZRow,
+        accumulate: bool,
+    );
+}
+
+impl Float for f32 {
+    #[inline]
+    fn outer_product(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        tile: usize,
+        accumulate: bool,
+    ) {
+        ctx.outer_product_f32_xy_to_z(Some(x), Some(y), ZRow(tile), accumulate);
+    }
+
+    #[inline]
+    fn fma_vector(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        z_index: ZRow,
+        accumulate: bool,
+    ) {
+        ctx.fma_vector_f32(Some(x), Some(y), z_index, accumulate);
+    }
+}
+
+impl Float for f64 {
+    #[inline]
+    fn outer_product(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        tile: usize,
+        accumulate: bool,
+    ) {
+        ctx.outer_product_f64_xy_to_z(Some(x), Some(y), ZRow(tile), accumulate);
+    }
+
+    #[inline]
+    fn fma_vector(
+        ctx: &mut (impl Amx + ?Sized),
+        x: XBytes,
+        y: YBytes,
+        z_index: ZRow,
+        accumulate: bool,
+    ) {
+        ctx.fma_vector_f64(Some(x), Some(y), z_index, accumulate);
+    }
+}
+
+/// Calculate `y = A * x` (or `y += A * x` if `accumulate` is set).
+///
+/// ```rust
+/// use amx::linalg::{MatRef, sgemv};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+/// let mut y = [0.0; 2];
+/// sgemv(&mut ctx, MatRef::new(&a, 2, 3), &[1.0, 0.0, -1.0], &mut y, false);
+/// assert_eq!(y, [-2.0, -2.0]);
+/// ```
+///
+/// # Execution
+///
+/// Unlike GEMM, each element of `A` is used only once, so the kernel is
+/// bound by loading `A`, and the best way to fill the lanes depends on the
+/// shape of `A`:
+///
+///  - If the rows of `A` have 16 or more elements, each row is multiplied
+///    by `x` by vector-mode `fma32`, with a slice of `x` kept in X and up to
+///    64 rows accumulated in Z at once. The lanes of each row are summed on
+///    the CPU.
+///
+///  - Otherwise, the columns of `A` are multiplied by the elements of `x` by
+///    [`Amx::outer_product_f32_xy_to_z`], with a slice of `x` kept in Y and
+///    each element selected by the Y offset. The first row of each of the
+///    four tiles of Z accumulates 16 elements of `y`, so no summation is
+///    needed.
+///
+/// # Panics
+///
+/// Panics if the lengths of `x` and `y` don't match the shape of `A`.
+pub fn sgemv(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, f32>,
+    x: &[f32],
+    y: &mut [f32],
+    accumulate: bool,
+) {
+    gemv(ctx, a, x, y, accumulate);
+}
+
+/// Calculate `y = A * x` (or `y += A * x` if `accumulate` is set) for `f64`.
+///
+/// This works the same way as [`sgemv`] with eight `f64` lanes per register
+/// row, so vector-mode `fma64` is used if the rows of `A` have 8 or more
+/// elements.
+///
+/// # Panics
+///
+/// Panics if the lengths of `x` and `y` don't match the shape of `A`.
+pub fn dgemv(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, f64>,
+    x: &[f64],
+    y: &mut [f64],
+    accumulate: bool,
+) {
+    gemv(ctx, a, x, y, accumulate);
+}
+
+fn gemv<T: Float>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, T>,
+    x: &[T],
+    y: &mut [T],
+    accumulate: bool,
+) {
+    let (m, n) = (a.rows(), a.cols());
+    assert_eq!(x.len(), n, "length mismatch in `x`");
+    assert_eq!(y.len(), m, "length mismatch in `y`");
+    if n == 0 {
+        if !accumulate {
+            y.fill(T::default());
+        }
+        return;
+    }
+
+    if n >= T::LANES {
+        gemv_vector(ctx, a, x, y, accumulate);
+    } else {
+        gemv_outer(ctx, a, x, y, accumulate);
+    }
+}
+
+/// A buffer holding a register row of `f32` or `f64`. Only the first
+/// `T::LANES` elements are used.
+type RowBuf<T> = [T; 16];
+
+/// Copy `src` to the start of `dst`, zero-filling the rest of the register
+/// row.
+#[inline]
+fn pack<T: Float>(dst: &mut RowBuf<T>, src: &[T]) {
+    dst[..src.len()].copy_from_slice(src);
+    dst[src.len()..T::LANES].fill(T::default());
+}
+
+/// Read `z[z_index]`.
+#[inline]
+fn read_row<T: Float>(ctx: &mut (impl Amx + ?Sized), z_index: ZRow) -> RowBuf<T> {
+    let mut out = [T::default(); 16];
+    // Safety: Writing 64 bytes to `RowBuf<T>`
+    unsafe { ctx.store512(out.as_mut_ptr(), z_index) };
+    out
+}
+
+fn gemv_vector<T: Float>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, T>,
+    x: &[T],
+    y: &mut [T],
+    accumulate: bool,
+) {
+    let (m, n) = (a.rows(), a.cols());
+    let mut buf = [T::default(); 16];
+
+    for i0 in (0..m).step_by(Z_ROWS) {
+        let mr = (m - i0).min(Z_ROWS);
+        for j0 in (0..n).step_by(T::LANES) {
+            let cols = j0..(j0 + T::LANES).min(n);
+            pack(&mut buf, &x[cols.clone()]);
+            // Safety: Reading 64 bytes from `RowBuf<T>`
+            unsafe { ctx.load512(buf.as_ptr(), XRow(0)) };
+            for ii in 0..mr {
+                let row = &a.row(i0 + ii)[cols.clone()];
+                let y_row = YRow(ii % 8);
+                if row.len() == T::LANES {
+                    // Safety: Reading 64 bytes from `row`
+                    unsafe { ctx.load512(row.as_ptr(), y_row) };
+                } else {
+                    pack(&mut buf, row);
+                    // Safety: Reading 64 bytes from `RowBuf<T>`
+                    unsafe { ctx.load512(buf.as_ptr(), y_row) };
+                }
+                T::fma_vector(ctx, XBytes(0), YBytes(y_row.0 * 64), ZRow(ii), j0 != 0);
+            }
+        }
+
+        for (ii, y) in y[i0..i0 + mr].iter_mut().enumerate() {
+            let sum = read_row::<T>(ctx, ZRow(ii))[..T::LANES]
+                .iter()
+                .fold(T::default(), |sum, &lane| sum + lane);
+            *y = if accumulate { *y + sum } else { sum };
+        }
+    }
+}
+
+fn gemv_outer<T: Float>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, T>,
+    x: &[T],
+    y: &mut [T],
+    accumulate: bool,
+) {
+    let (m, n) = (a.rows(), a.cols());
+    // The number of tiles of Z, each accumulating `T::LANES` rows
+    let tiles = Z_ROWS / T::LANES;
+    let mut buf = [T::default(); 16];
+
+    // `x` fits in a single register row
+    pack(&mut buf, x);
+    // Safety: Reading 64 bytes from `RowBuf<T>`
+    unsafe { ctx.load512(buf.as_ptr(), YRow(0)) };
+
+    for i0 in (0..m).step_by(T::LANES * tiles) {
+        let nt = (m - i0).div_ceil(T::LANES).min(tiles);
+        for j in 0..n {
+            for t in 0..nt {
+                // `buf[..]` = `A[i0 + t * LANES..][..LANES, j]`
+                for (ii, dst) in buf[..T::LANES].iter_mut().enumerate() {
+                    let i = i0 + t * T::LANES + ii;
+                    *dst = if i < m { a.row(i)[j] } else { T::default() };
+                }
+                // Safety: Reading 64 bytes from `RowBuf<T>`
+                unsafe { ctx.load512(buf.as_ptr(), XRow(t)) };
+                // The first row of the tile is `A[.., j] * x[j]`
+                T::outer_product(ctx, XBytes(t * 64), YBytes(j * size_of::<T>()), t, j != 0);
+            }
+        }
+
+        for t in 0..nt {
+            let rows = i0 + t * T::LANES..(i0 + (t + 1) * T::LANES).min(m);
+            let z = read_row::<T>(ctx, ZRow(t));
+            for (y, &sum) in y[rows].iter_mut().zip(&z) {
+                *y = if accumulate { *y + sum } else { sum };
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Givens rotations
+use super::{F32_LANES, MatMut, fma32_vector};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// A plane rotation acting on rows `i` and `k` of a matrix:
+///
+/// ```text
+/// row[i] ←  c * row[i] + s * row[k]
+/// row[k] ← -s * row[i] + c * row[k]
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Givens {
+    pub i: usize,
+    pub k: usize,
+    pub c: f32,
+    pub s: f32,
+}
+
+impl Givens {
+    /// Construct a rotation acting on rows `i` and `k` that maps `(a, b)` to
+    /// `(r, 0)`, returning the rotation and `r`.
+    ///
+    /// This is the building block of incremental QR updates: choosing `a` and
+    /// `b` from a column of the matrix makes the rotation annihilate the
+    /// element of `row[k]` in that column.
+    pub fn zeroing(i: usize, k: usize, a: f32, b: f32) -> (Self, f32) {
+        let r = a.hypot(b);
+        let (c, s) = if r == 0.0 { (1.0, 0.0) } else { (a / r, b / r) };
+        (Self { i, k, c, s }, r)
+    }
+}
+
+/// Apply `rotations` to the rows of `a` in order.
+///
+/// The rotations are applied 16 columns at a time using vector-mode
+/// `fma32`/`fms32`, so each column block stays in cache for the entire
+/// sequence.
+///
+/// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..2]`.
+///
+/// # Panics
+///
+/// Panics if a rotation refers to a row outside `a` or has `i == k`.
+pub fn apply_givens(ctx: &mut (impl Amx + ?Sized), mut a: MatMut<'_, f32>, rotations: &[Givens]) {
+    for g in rotations {
+        assert!(
+            g.i < a.rows() && g.k < a.rows() && g.i != g.k,
+            "invalid rotation rows: {:?}",
+            (g.i, g.k)
+        );
+    }
+
+    for j0 in (0..a.cols()).step_by(F32_LANES) {
+        let nr = (a.cols() - j0).min(F32_LANES);
+        for g in rotations {
+            let mut row_i = [0f32; F32_LANES];
+            let mut row_k = [0f32; F32_LANES];
+            row_i[..nr].copy_from_slice(&a.row(g.i)[j0..j0 + nr]);
+            row_k[..nr].copy_from_slice(&a.row(g.k)[j0..j0 + nr]);
+            let c = [g.c; F32_LANES];
+            let s = [g.s; F32_LANES];
+
+            // Safety: Reading 64 bytes from each `[f32; 16]`
+            unsafe {
+                ctx.load512(row_i.as_ptr(), XRow(0));
+                ctx.load512(row_k.as_ptr(), XRow(1));
+                ctx.load512(c.as_ptr(), YRow(0));
+                ctx.load512(s.as_ptr(), YRow(1));
+            }
+
+            // z[0] = c * row[i] + s * row[k]
+            fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(0), false, false);
+            fma32_vector(ctx, XBytes(64), YBytes(64), ZRow(0), true, false);
+            // z[1] = c * row[k] - s * row[i]
+            fma32_vector(ctx, XBytes(64), YBytes(0), ZRow(1), false, false);
+            fma32_vector(ctx, XBytes(0), YBytes(64), ZRow(1), true, true);
+
+            // Safety: Writing 64 bytes to each `[f32; 16]`
+            unsafe {
+                ctx.store512(row_i.as_mut_ptr(), ZRow(0));
+                ctx.store512(row_k.as_mut_ptr(), ZRow(1));
+            }
+            a.row_mut(g.i)[j0..j0 + nr].copy_from_slice(&row_i[..nr]);
+            a.row_mut(g.k)[j0..j0 + nr].copy_from_slice(&row_k[..nr]);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Half-precision matrix multiplication with single-precision accumulation
+use super::{MatMut, MatRef};
+use crate::{Amx, ZRow, fp16::F16Bits, pipeline::Pipeline};
+
+/// The number of `f16` lanes read by `fma16`
+const LANES: usize = 32;
+
+/// Calculate `C = A * B` (or `C += A * B` if `accumulate` is set) for `f16`
+/// matrices, accumulating in `f32`.
+///
+/// The elements of `A` and `B` can be [`F16Bits`] or, with the `half`
+/// feature, `half::f16`. Since the products are summed in single precision,
+/// the result is as accurate as [`sgemm`](super::sgemm) on the same
+/// inputs widened to `f32`.
+///
+/// ```rust
+/// use amx::{fp16::F16Bits, linalg::{MatMut, MatRef, hgemm}};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [1.0, 2.0, 3.0, 4.0].map(F16Bits::from_f32);
+/// let b = [0.5, 0.0, 0.25, 1.0].map(F16Bits::from_f32);
+/// let mut c = [0f32; 4];
+/// hgemm(
+///     &mut ctx,
+///     MatRef::new(&a, 2, 2),
+///     MatRef::new(&b, 2, 2),
+///     MatMut::new(&mut c, 2, 2),
+///     false,
+/// );
+/// assert_eq!(c, [1.0, 2.0, 2.5, 4.0]);
+/// ```
+///
+/// # Execution
+///
+/// `C` is computed in 32×32 blocks by
+/// [`Amx::outer_product_f16_xy_to_z_f32`], whose output occupies all of Z.
+/// Each block is summed over the whole of `K` before it's added to `C`.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn hgemm<T: Copy + Into<F16Bits>>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, T>,
+    b: MatRef<'_, T>,
+    mut c: MatMut<'_, f32>,
+    accumulate: bool,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(b.rows(), k, "shape mismatch in `b`");
+    assert_eq!((c.rows(), c.cols()), (m, n), "shape mismatch in `c`");
+    if k == 0 {
+        if !accumulate {
+            for i in 0..m {
+                c.row_mut(i).fill(0.0);
+            }
+        }
+        return;
+    }
+
+    // `a_pack[kk]` = `A[i0..][..32, kk]`
+    let mut a_pack = vec![[0u16; LANES]; k];
+    // `b_pack[kk]` = `B[kk, j0..][..32]`
+    let mut b_pack = vec![[0u16; LANES]; k];
+
+    for i0 in (0..m).step_by(LANES) {
+        let mr = (m - i0).min(LANES);
+        for (kk, dst) in a_pack.iter_mut().enumerate() {
+            for (ii, dst) in dst.iter_mut().enumerate() {
+                *dst = if ii < mr {
+                    a.row(i0 + ii)[kk].into().0
+                } else {
+                    0
+                };
+            }
+        }
+
+        for j0 in (0..n).step_by(LANES) {
+            let nr = (n - j0).min(LANES);
+            for (kk, dst) in b_pack.iter_mut().enumerate() {
+                let src = &b.row(kk)[j0..j0 + nr];
+                for (d, &s) in dst.iter_mut().zip(src) {
+                    *d = s.into().0;
+                }
+                dst[nr..].fill(0);
+            }
+
+            Pipeline::deepest(1, 1).run(
+                ctx,
+                k,
+                |ctx, kk, slot| {
+                    // Safety: Reading 64 bytes from each `[u16; 32]`
+                    unsafe {
+                        ctx.load512(a_pack[kk].as_ptr(), slot.y_row(0));
+                        ctx.load512(b_pack[kk].as_ptr(), slot.x_row(0));
+                    }
+                },
+                |ctx, kk, slot| {
+                    ctx.outer_product_f16_xy_to_z_f32(
+                        Some(slot.x_bytes(0)),
+                        Some(slot.y_bytes(0)),
+                        ZRow(0),
+                        kk != 0,
+                    );
+                },
+            );
+
+            // `C[i0 + ii, j0 + jj]` is in `z[ii * 2 + jj % 2][jj / 2]`
+            let z = ctx.read_z_f32();
+            for ii in 0..mr {
+                let dst = &mut c.row_mut(i0 + ii)[j0..j0 + nr];
+                for (jj, d) in dst.iter_mut().enumerate() {
+                    let s = z[ii * 2 + jj % 2][jj / 2];
+                    *d = if accumulate { *d + s } else { s };
+                }
+            }
+        }
+    }
+}
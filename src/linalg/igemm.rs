@@ -0,0 +1,217 @@
+//! 8-bit integer matrix multiplication for quantized inference
+use super::{MatMut, MatRef};
+use crate::{Amx, MatIntOp, MatIntTy, XBytes, YBytes, ZRow, pipeline::Pipeline};
+
+/// The number of 8-bit lanes read by `matint`
+const LANES: usize = 32;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An 8-bit input element type of the integer GEMM routines
+pub trait Int8: sealed::Sealed + Copy + Default {
+    #[doc(hidden)]
+    const TY: MatIntTy;
+    #[doc(hidden)]
+    fn to_i32(self) -> i32;
+}
+
+macro_rules! impl_int8 {
+    ($($ty:ty => $mode:ident),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl Int8 for $ty {
+            const TY: MatIntTy = MatIntTy::$mode;
+
+            #[inline]
+            fn to_i32(self) -> i32 {
+                self as i32
+            }
+        }
+    )*};
+}
+
+impl_int8!(i8 => I8, u8 => U8);
+
+/// Calculate `C = (A - a_zero) * (B - b_zero)` (or `C += ...` if
+/// `accumulate` is set) for `i8` matrices, accumulating in `i32`.
+///
+/// The sums wrap around on overflow. See [`igemm_u8`] for the handling of
+/// the zero-points.
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn igemm_i8(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, i8>,
+    a_zero: i8,
+    b: MatRef<'_, i8>,
+    b_zero: i8,
+    c: MatMut<'_, i32>,
+    accumulate: bool,
+) {
+    igemm(ctx, a, a_zero, b, b_zero, c, accumulate);
+}
+
+/// Calculate `C = (A - a_zero) * (B - b_zero)` (or `C += ...` if
+/// `accumulate` is set) for `u8` matrices, accumulating in `i32`.
+///
+/// This suits asymmetrically quantized operands, where a real value `r` is
+/// represented by `q` such that `r = scale * (q - zero)`.
+///
+/// ```rust
+/// use amx::linalg::{MatMut, MatRef, igemm_u8};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [130u8, 128, 127, 200];
+/// let b = [10u8, 12, 14, 16];
+/// let mut c = [0i32; 4];
+/// igemm_u8(
+///     &mut ctx,
+///     MatRef::new(&a, 2, 2),
+///     128,
+///     MatRef::new(&b, 2, 2),
+///     10,
+///     MatMut::new(&mut c, 2, 2),
+///     false,
+/// );
+/// // `[[2, 0], [-1, 72]] * [[0, 2], [4, 6]]`
+/// assert_eq!(c, [0, 4, 288, 430]);
+/// ```
+///
+/// # Execution
+///
+/// `A * B` is computed in 32×32 blocks by `matint` with 8-bit inputs and
+/// `i32` outputs, which occupy all of Z. Two steps along `K` are packed into
+/// each register row. The zero-points are then applied on the CPU by
+///
+/// ```text
+/// C[i, j] = (A * B)[i, j] - b_zero * sum(A[i, ..]) - a_zero * sum(B[.., j])
+///           + K * a_zero * b_zero
+/// ```
+///
+/// # Panics
+///
+/// Panics if the matrix dimensions are inconsistent.
+pub fn igemm_u8(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, u8>,
+    a_zero: u8,
+    b: MatRef<'_, u8>,
+    b_zero: u8,
+    c: MatMut<'_, i32>,
+    accumulate: bool,
+) {
+    igemm(ctx, a, a_zero, b, b_zero, c, accumulate);
+}
+
+fn igemm<T: Int8>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, T>,
+    a_zero: T,
+    b: MatRef<'_, T>,
+    b_zero: T,
+    mut c: MatMut<'_, i32>,
+    accumulate: bool,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(b.rows(), k, "shape mismatch in `b`");
+    assert_eq!((c.rows(), c.cols()), (m, n), "shape mismatch in `c`");
+    let (a_zero, b_zero) = (a_zero.to_i32(), b_zero.to_i32());
+
+    let a_sums: Vec<i32> = (0..m)
+        .map(|i| (a.row(i).iter()).fold(0i32, |sum, x| sum.wrapping_add(x.to_i32())))
+        .collect();
+    let mut b_sums = vec![0i32; n];
+    for kk in 0..k {
+        for (sum, x) in b_sums.iter_mut().zip(b.row(kk)) {
+            *sum = sum.wrapping_add(x.to_i32());
+        }
+    }
+    let offset = (k as i32).wrapping_mul(a_zero).wrapping_mul(b_zero);
+
+    if k == 0 {
+        for i in 0..m {
+            write_row(c.row_mut(i), |_| offset, accumulate);
+        }
+        return;
+    }
+
+    // `a_pack[kk / 2][kk % 2]` = `A[i0..][..32, kk]`
+    let mut a_pack = vec![[[T::default(); LANES]; 2]; k.div_ceil(2)];
+    // `b_pack[kk / 2][kk % 2]` = `B[kk, j0..][..32]`
+    let mut b_pack = vec![[[T::default(); LANES]; 2]; k.div_ceil(2)];
+    let op = MatIntOp::new(T::TY).widen(true);
+
+    for i0 in (0..m).step_by(LANES) {
+        let mr = (m - i0).min(LANES);
+        for kk in 0..k {
+            let dst = &mut a_pack[kk / 2][kk % 2];
+            for (ii, dst) in dst.iter_mut().enumerate() {
+                *dst = if ii < mr {
+                    a.row(i0 + ii)[kk]
+                } else {
+                    T::default()
+                };
+            }
+        }
+
+        for j0 in (0..n).step_by(LANES) {
+            let nr = (n - j0).min(LANES);
+            for kk in 0..k {
+                let dst = &mut b_pack[kk / 2][kk % 2];
+                dst[..nr].copy_from_slice(&b.row(kk)[j0..j0 + nr]);
+                dst[nr..].fill(T::default());
+            }
+
+            Pipeline::deepest(1, 1).run(
+                ctx,
+                k.div_ceil(2),
+                |ctx, step, slot| {
+                    // Safety: Reading 64 bytes from each `[[T; 32]; 2]`
+                    unsafe {
+                        ctx.load512(a_pack[step].as_ptr(), slot.y_row(0));
+                        ctx.load512(b_pack[step].as_ptr(), slot.x_row(0));
+                    }
+                },
+                |ctx, step, slot| {
+                    for half in 0..(k - step * 2).min(2) {
+                        let op = op.accumulate(step + half != 0);
+                        let x = XBytes(slot.x_bytes(0).0 + half * LANES);
+                        let y = YBytes(slot.y_bytes(0).0 + half * LANES);
+                        ctx.matrix_int(op, x, y, ZRow(0));
+                    }
+                },
+            );
+
+            // `(A * B)[i0 + ii, j0 + jj]` is in `z[ii * 2 + jj % 2][jj / 2]`
+            let z = ctx.read_z_as::<i32>();
+            for ii in 0..mr {
+                let i = i0 + ii;
+                let correction = offset.wrapping_sub(b_zero.wrapping_mul(a_sums[i]));
+                write_row(
+                    &mut c.row_mut(i)[j0..j0 + nr],
+                    |jj| {
+                        z[ii * 2 + jj % 2][jj / 2]
+                            .wrapping_add(correction)
+                            .wrapping_sub(a_zero.wrapping_mul(b_sums[j0 + jj]))
+                    },
+                    accumulate,
+                );
+            }
+        }
+    }
+}
+
+#[inline]
+fn write_row(dst: &mut [i32], src: impl Fn(usize) -> i32, accumulate: bool) {
+    for (jj, d) in dst.iter_mut().enumerate() {
+        *d = if accumulate {
+            d.wrapping_add(src(jj))
+        } else {
+            src(jj)
+        };
+    }
+}
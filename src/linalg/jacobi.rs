@@ -0,0 +1,163 @@
+//! One-sided (Hestenes) Jacobi method
+use super::{F32_LANES, Givens, MatMut, apply_givens, fma32_vector};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// Perform one cyclic sweep of the one-sided Jacobi method over the rows of
+/// `a`, returning the number of rotations applied.
+///
+/// For every row pair `(p, q)` whose Gram entries satisfy
+/// `|a[p]·a[q]| > tol * |a[p]| * |a[q]|`, a rotation that makes the two rows
+/// orthogonal is applied to `a` and, if given, to `v`. The Gram entries are
+/// computed with vector-mode `fma32`, and the rotations are applied by
+/// [`apply_givens`].
+///
+/// Repeating sweeps until this returns `0` orthogonalizes the rows of `a`. If
+/// `v` started as the identity matrix, it then holds the accumulated
+/// rotation `J` such that `J * a_original = a`, which yields the singular
+/// value decomposition `a_original = Jᵀ * a`. Since `a` is stored in
+/// row-major order, this is the SVD of `a_originalᵀ`; the two are the same
+/// for symmetric matrices.
+///
+/// This overwrites `x[0..2]`, `y[0..2]`, and `z[0..3]`.
+///
+/// # Panics
+///
+/// Panics if `v` does not have as many rows as `a`.
+pub fn jacobi_sweep(
+    ctx: &mut (impl Amx + ?Sized),
+    mut a: MatMut<'_, f32>,
+    mut v: Option<MatMut<'_, f32>>,
+    tol: f32,
+) -> usize {
+    if let Some(v) = &v {
+        assert_eq!(v.rows(), a.rows(), "`v` must have as many rows as `a`");
+    }
+
+    let mut num_rotations = 0;
+    for p in 0..a.rows() {
+        for q in p + 1..a.rows() {
+            let [alpha, beta, gamma] = gram_pair(ctx, &a, p, q);
+            if gamma == 0.0 || gamma.abs() <= tol as f64 * (alpha * beta).sqrt() {
+                continue;
+            }
+
+            let zeta = (beta - alpha) / (2.0 * gamma);
+            let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+            let c = 1.0 / (1.0 + t * t).sqrt();
+            let s = c * t;
+
+            // a[p] ← c * a[p] - s * a[q], a[q] ← s * a[p] + c * a[q]
+            let g = Givens {
+                i: p,
+                k: q,
+                c: c as f32,
+                s: -s as f32,
+            };
+            apply_givens(ctx, a.borrow_mut(), &[g]);
+            if let Some(v) = &mut v {
+                apply_givens(ctx, v.borrow_mut(), &[g]);
+            }
+            num_rotations += 1;
+        }
+    }
+    num_rotations
+}
+
+/// Calculate the eigenvalues and eigenvectors of the symmetric matrix `a`
+/// by running [`jacobi_sweep`] until convergence (or for at most
+/// `max_sweeps` sweeps).
+///
+/// Returns the eigenvalues in no particular order. The corresponding
+/// eigenvectors are written to the rows of `vectors`.
+///
+/// The one-sided method finds singular vectors, which are eigenvectors only
+/// if no two eigenvalues have the same magnitude and opposite signs. So the
+/// diagonal of `a` is first shifted by a bound `σ` of the spectral radius,
+/// which makes `A + σI` positive semidefinite with the same eigenvectors.
+/// Each eigenvalue `λ` is then recovered, with its sign, as the Rayleigh
+/// quotient `vᵀAv = vᵀ(A + σI)v - σ` of its eigenvector `v`. `a` is left
+/// holding the orthogonalized rows of `A + σI`.
+///
+/// # Panics
+///
+/// Panics if `a` is not square or `vectors` does not have the same shape as
+/// `a`.
+pub fn jacobi_eigh(
+    ctx: &mut (impl Amx + ?Sized),
+    mut a: MatMut<'_, f32>,
+    mut vectors: MatMut<'_, f32>,
+    tol: f32,
+    max_sweeps: usize,
+) -> Vec<f32> {
+    let n = a.rows();
+    assert_eq!(a.cols(), n, "`a` must be square");
+    assert_eq!(
+        (vectors.rows(), vectors.cols()),
+        (n, n),
+        "`vectors` must have the same shape as `a`"
+    );
+
+    // The largest absolute row sum bounds the magnitudes of the eigenvalues
+    let shift = (0..n)
+        .map(|i| a.row(i).iter().map(|&x| x.abs() as f64).sum::<f64>())
+        .fold(0.0, f64::max);
+    for i in 0..n {
+        a.row_mut(i)[i] += shift as f32;
+        let row = vectors.row_mut(i);
+        row.fill(0.0);
+        row[i] = 1.0;
+    }
+
+    for _ in 0..max_sweeps {
+        if jacobi_sweep(ctx, a.borrow_mut(), Some(vectors.borrow_mut()), tol) == 0 {
+            break;
+        }
+    }
+
+    // Each row of `a` is now `vᵀ(A + σI)` for the eigenvector `v` in the
+    // same row of `vectors`, so its projection onto `v` is `vᵀ(A + σI)v`
+    (0..n)
+        .map(|i| {
+            let rayleigh = (a.row(i).iter())
+                .zip(vectors.row(i))
+                .map(|(&x, &y)| x as f64 * y as f64)
+                .sum::<f64>();
+            (rayleigh - shift) as f32
+        })
+        .collect()
+}
+
+/// Calculate `[a[p]·a[p], a[q]·a[q], a[p]·a[q]]`.
+fn gram_pair(ctx: &mut (impl Amx + ?Sized), a: &MatMut<'_, f32>, p: usize, q: usize) -> [f64; 3] {
+    for j0 in (0..a.cols()).step_by(F32_LANES) {
+        let nr = (a.cols() - j0).min(F32_LANES);
+        let mut row_p = [0f32; F32_LANES];
+        let mut row_q = [0f32; F32_LANES];
+        row_p[..nr].copy_from_slice(&a.row(p)[j0..j0 + nr]);
+        row_q[..nr].copy_from_slice(&a.row(q)[j0..j0 + nr]);
+
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            ctx.load512(row_p.as_ptr(), XRow(0));
+            ctx.load512(row_q.as_ptr(), XRow(1));
+            ctx.load512(row_p.as_ptr(), YRow(0));
+            ctx.load512(row_q.as_ptr(), YRow(1));
+        }
+
+        let accumulate = j0 != 0;
+        fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(0), accumulate, false);
+        fma32_vector(ctx, XBytes(64), YBytes(64), ZRow(1), accumulate, false);
+        fma32_vector(ctx, XBytes(0), YBytes(64), ZRow(2), accumulate, false);
+    }
+
+    if a.cols() == 0 {
+        return [0.0; 3];
+    }
+
+    std::array::from_fn(|i| {
+        let mut lanes = [0f32; F32_LANES];
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(lanes.as_mut_ptr(), ZRow(i)) };
+        lanes.iter().map(|&x| x as f64).sum()
+    })
+}
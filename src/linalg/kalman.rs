@@ -0,0 +1,249 @@
+//! Batched Kalman filters
+use super::{F32_LANES, MatMut, MatRef, batch_inverse, sgemm};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// A linear Gaussian state-space model shared by a batch of Kalman filters
+/// with `N` state variables and `M` measured variables.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KalmanModel<const N: usize, const M: usize> {
+    /// The state transition matrix `F`
+    pub f: [[f32; N]; N],
+    /// The process noise covariance `Q`
+    pub q: [[f32; N]; N],
+    /// The observation matrix `H`
+    pub h: [[f32; N]; M],
+    /// The measurement noise covariance `R`
+    pub r: [[f32; M]; M],
+}
+
+/// The state estimate of a Kalman filter with `N` state variables.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KalmanState<const N: usize> {
+    /// The state estimate `x`
+    pub x: [f32; N],
+    /// The estimate covariance `P`
+    pub p: [[f32; N]; N],
+}
+
+/// Run the predict step of every filter in `states`:
+///
+/// ```text
+/// x ← F x
+/// P ← F P Fᵀ + Q
+/// ```
+///
+/// The filters are processed as a batch: the states are stacked along the
+/// rows of one matrix and the covariances along the rows of another, so
+/// each product with `F` is a single [`sgemm`] over the whole batch.
+///
+/// This overwrites `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if `N > 16` or `M > 16`.
+pub fn kalman_predict<const N: usize, const M: usize>(
+    ctx: &mut (impl Amx + ?Sized),
+    model: &KalmanModel<N, M>,
+    states: &mut [KalmanState<N>],
+) {
+    assert!(
+        N <= F32_LANES && M <= F32_LANES,
+        "dimensions larger than 16 are not supported"
+    );
+    let batch = states.len();
+    if batch == 0 {
+        return;
+    }
+    let f_t = transpose(&model.f);
+    let f_t = MatRef::new(f_t.as_flattened(), N, N);
+
+    // `(F x)ᵀ = xᵀ Fᵀ` for the rows `xᵀ` of the stacked states
+    let xs: Vec<f32> = states.iter().flat_map(|state| state.x).collect();
+    let mut fx = vec![0.0; batch * N];
+    sgemm(
+        ctx,
+        MatRef::new(&xs, batch, N),
+        f_t,
+        MatMut::new(&mut fx, batch, N),
+        false,
+    );
+
+    // `(F P)ᵀ = Pᵀ Fᵀ`, and then `F P Fᵀ`
+    let p_t: Vec<f32> = (states.iter())
+        .flat_map(|state| transpose(&state.p).into_iter().flatten())
+        .collect();
+    let mut fp_t = vec![0.0; batch * N * N];
+    sgemm(
+        ctx,
+        MatRef::new(&p_t, batch * N, N),
+        f_t,
+        MatMut::new(&mut fp_t, batch * N, N),
+        false,
+    );
+    let fp = transpose_blocks::<N, N>(&fp_t);
+    let mut fpf = vec![0.0; batch * N * N];
+    sgemm(
+        ctx,
+        MatRef::new(&fp, batch * N, N),
+        f_t,
+        MatMut::new(&mut fpf, batch * N, N),
+        false,
+    );
+
+    for ((state, fx), fpf) in states
+        .iter_mut()
+        .zip(fx.chunks_exact(N))
+        .zip(fpf.chunks_exact(N * N))
+    {
+        state.x.copy_from_slice(fx);
+        state.p = std::array::from_fn(|i| std::array::from_fn(|j| fpf[i * N + j] + model.q[i][j]));
+    }
+}
+
+/// Run the update step of every filter in `states` with the corresponding
+/// measurement in `measurements`:
+///
+/// ```text
+/// S ← H P Hᵀ + R
+/// K ← P Hᵀ S⁻¹
+/// x ← x + K (z - H x)
+/// P ← P - K H P
+/// ```
+///
+/// As in [`kalman_predict`], each product with `H` is a single [`sgemm`]
+/// over the whole batch, and the innovation covariances `S` of the whole
+/// batch are inverted together by [`batch_inverse`]. The products with the
+/// gains `K`, which differ between the filters, are calculated per filter
+/// by AMX outer products, with the state variables mapped to the vector
+/// lanes.
+///
+/// This overwrites `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if `states.len() != measurements.len()`, `N > 16`, or `M > 16`.
+pub fn kalman_update<const N: usize, const M: usize>(
+    ctx: &mut (impl Amx + ?Sized),
+    model: &KalmanModel<N, M>,
+    states: &mut [KalmanState<N>],
+    measurements: &[[f32; M]],
+) {
+    assert!(
+        N <= F32_LANES && M <= F32_LANES,
+        "dimensions larger than 16 are not supported"
+    );
+    assert_eq!(states.len(), measurements.len(), "batch size mismatch");
+    let batch = states.len();
+    if batch == 0 {
+        return;
+    }
+    let h_t = transpose(&model.h);
+    let h_t = MatRef::new(h_t.as_flattened(), N, M);
+
+    // `(H x)ᵀ = xᵀ Hᵀ` for the rows `xᵀ` of the stacked states
+    let xs: Vec<f32> = states.iter().flat_map(|state| state.x).collect();
+    let mut hx = vec![0.0; batch * M];
+    sgemm(
+        ctx,
+        MatRef::new(&xs, batch, N),
+        h_t,
+        MatMut::new(&mut hx, batch, M),
+        false,
+    );
+
+    // `P Hᵀ`, `H P = (P Hᵀ)ᵀ` because `P` is symmetric, and `H P Hᵀ`
+    let ps: Vec<f32> = (states.iter())
+        .flat_map(|state| state.p.into_iter().flatten())
+        .collect();
+    let mut ph_t = vec![0.0; batch * N * M];
+    sgemm(
+        ctx,
+        MatRef::new(&ps, batch * N, N),
+        h_t,
+        MatMut::new(&mut ph_t, batch * N, M),
+        false,
+    );
+    let hp = transpose_blocks::<N, M>(&ph_t);
+    let mut hph = vec![0.0; batch * M * M];
+    sgemm(
+        ctx,
+        MatRef::new(&hp, batch * M, N),
+        h_t,
+        MatMut::new(&mut hph, batch * M, M),
+        false,
+    );
+
+    let s: Vec<[[f32; M]; M]> = (hph.chunks_exact(M * M))
+        .map(|hph| std::array::from_fn(|i| std::array::from_fn(|j| hph[i * M + j] + model.r[i][j])))
+        .collect();
+    let mut s_inv = vec![[[0f32; M]; M]; batch];
+    batch_inverse(ctx, &s, &mut s_inv);
+
+    for (b, (state, z)) in states.iter_mut().zip(measurements).enumerate() {
+        let ph_t: [[f32; M]; N] = block(&ph_t, b);
+        let hp: [[f32; N]; M] = block(&hp, b);
+        let k = matmul(ctx, &ph_t, &s_inv[b]);
+
+        let innovation: [[f32; 1]; M] = std::array::from_fn(|i| [z[i] - hx[b * M + i]]);
+        let dx = matmul(ctx, &k, &innovation);
+        state.x = std::array::from_fn(|i| state.x[i] + dx[i][0]);
+
+        let khp = matmul(ctx, &k, &hp);
+        state.p = std::array::from_fn(|i| std::array::from_fn(|j| state.p[i][j] - khp[i][j]));
+    }
+}
+
+/// Get the `b`-th `R`x`C` matrix of `blocks`, which holds them one after
+/// another in row-major order.
+fn block<const R: usize, const C: usize>(blocks: &[f32], b: usize) -> [[f32; C]; R] {
+    let block = &blocks[b * R * C..][..R * C];
+    std::array::from_fn(|i| std::array::from_fn(|j| block[i * C + j]))
+}
+
+/// Transpose each `R`x`C` matrix of `blocks` (see [`block`]).
+fn transpose_blocks<const R: usize, const C: usize>(blocks: &[f32]) -> Vec<f32> {
+    (0..blocks.len() / (R * C))
+        .flat_map(|b| transpose(&block::<R, C>(blocks, b)).into_iter().flatten())
+        .collect()
+}
+
+/// Transpose a small matrix.
+fn transpose<const R: usize, const C: usize>(a: &[[f32; C]; R]) -> [[f32; R]; C] {
+    std::array::from_fn(|j| std::array::from_fn(|i| a[i][j]))
+}
+
+/// Calculate `a * b` for matrices no larger than 16x16 by accumulating outer
+/// products of the columns of `a` and the rows of `b` in the first `f32`
+/// tile of `z`.
+fn matmul<const R: usize, const K: usize, const C: usize>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: &[[f32; K]; R],
+    b: &[[f32; C]; K],
+) -> [[f32; C]; R] {
+    debug_assert!(R <= F32_LANES && C <= F32_LANES);
+    if K == 0 {
+        return [[0.0; C]; R];
+    }
+
+    for k in 0..K {
+        let mut col = [0f32; F32_LANES];
+        let mut row = [0f32; F32_LANES];
+        for (i, a) in a.iter().enumerate() {
+            col[i] = a[k];
+        }
+        row[..C].copy_from_slice(&b[k]);
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            ctx.load512(col.as_ptr(), YRow(0));
+            ctx.load512(row.as_ptr(), XRow(0));
+        }
+        ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), k != 0);
+    }
+
+    std::array::from_fn(|i| {
+        let mut row = [0f32; F32_LANES];
+        // Safety: Writing 64 bytes to `[f32; 16]`
+        unsafe { ctx.store512(row.as_mut_ptr(), ZRow(i * 4)) };
+        std::array::from_fn(|j| row[j])
+    })
+}
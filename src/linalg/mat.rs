@@ -0,0 +1,240 @@
+//! Strided matrix views
+use std::fmt;
+
+/// An immutable view of a row-major matrix whose rows are `stride` elements
+/// apart.
+#[derive(Copy, Clone)]
+pub struct MatRef<'a, T> {
+    data: &'a [T],
+    rows: usize,
+    cols: usize,
+    stride: usize,
+}
+
+/// A mutable view of a row-major matrix whose rows are `stride` elements
+/// apart.
+pub struct MatMut<'a, T> {
+    data: &'a mut [T],
+    rows: usize,
+    cols: usize,
+    stride: usize,
+}
+
+#[track_caller]
+fn check_extent(len: usize, rows: usize, cols: usize, stride: usize) {
+    assert!(
+        stride >= cols,
+        "stride ({stride}) is smaller than the column count ({cols})"
+    );
+    if rows > 0 && cols > 0 {
+        let needed = (rows - 1) * stride + cols;
+        assert!(
+            len >= needed,
+            "{rows}x{cols} matrix with stride {stride} needs {needed} elements, \
+             but only {len} were provided"
+        );
+    }
+}
+
+impl<'a, T> MatRef<'a, T> {
+    /// Construct a view of a `rows`×`cols` matrix stored contiguously in
+    /// `data`.
+    #[inline]
+    #[track_caller]
+    pub fn new(data: &'a [T], rows: usize, cols: usize) -> Self {
+        Self::with_stride(data, rows, cols, cols)
+    }
+
+    /// Construct a view of a `rows`×`cols` matrix whose rows start every
+    /// `stride` elements in `data`.
+    #[inline]
+    #[track_caller]
+    pub fn with_stride(data: &'a [T], rows: usize, cols: usize, stride: usize) -> Self {
+        check_extent(data.len(), rows, cols, stride);
+        Self {
+            data,
+            rows,
+            cols,
+            stride,
+        }
+    }
+
+    /// Get the number of rows.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns.
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get the distance between the starts of two consecutive rows, measured
+    /// in elements.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the `i`-th row.
+    #[inline]
+    #[track_caller]
+    pub fn row(&self, i: usize) -> &'a [T] {
+        assert!(i < self.rows);
+        &self.data[i * self.stride..][..self.cols]
+    }
+
+    /// Split `self` into the rows `0..i` and `i..`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > self.rows()`.
+    #[inline]
+    #[track_caller]
+    pub fn split_at_row(self, i: usize) -> (Self, Self) {
+        assert!(i <= self.rows);
+        let (top, bottom) = self.data.split_at((i * self.stride).min(self.data.len()));
+        (
+            Self {
+                data: top,
+                rows: i,
+                ..self
+            },
+            Self {
+                data: bottom,
+                rows: self.rows - i,
+                ..self
+            },
+        )
+    }
+}
+
+impl<'a, T> MatMut<'a, T> {
+    /// Construct a view of a `rows`×`cols` matrix stored contiguously in
+    /// `data`.
+    #[inline]
+    #[track_caller]
+    pub fn new(data: &'a mut [T], rows: usize, cols: usize) -> Self {
+        Self::with_stride(data, rows, cols, cols)
+    }
+
+    /// Construct a view of a `rows`×`cols` matrix whose rows start every
+    /// `stride` elements in `data`.
+    #[inline]
+    #[track_caller]
+    pub fn with_stride(data: &'a mut [T], rows: usize, cols: usize, stride: usize) -> Self {
+        check_extent(data.len(), rows, cols, stride);
+        Self {
+            data,
+            rows,
+            cols,
+            stride,
+        }
+    }
+
+    /// Get the number of rows.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns.
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get the distance between the starts of two consecutive rows, measured
+    /// in elements.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the `i`-th row.
+    #[inline]
+    #[track_caller]
+    pub fn row(&self, i: usize) -> &[T] {
+        assert!(i < self.rows);
+        &self.data[i * self.stride..][..self.cols]
+    }
+
+    /// Get the `i`-th row mutably.
+    #[inline]
+    #[track_caller]
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        assert!(i < self.rows);
+        &mut self.data[i * self.stride..][..self.cols]
+    }
+
+    /// Split `self` into the rows `0..i` and `i..`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i > self.rows()`.
+    #[inline]
+    #[track_caller]
+    pub fn split_at_row(self, i: usize) -> (Self, Self) {
+        assert!(i <= self.rows);
+        let (rows, cols, stride) = (self.rows, self.cols, self.stride);
+        let (top, bottom) = self.data.split_at_mut((i * stride).min(self.data.len()));
+        (
+            Self {
+                data: top,
+                rows: i,
+                cols,
+                stride,
+            },
+            Self {
+                data: bottom,
+                rows: rows - i,
+                cols,
+                stride,
+            },
+        )
+    }
+
+    /// Reborrow `self` as an immutable view.
+    #[inline]
+    pub fn as_ref(&self) -> MatRef<'_, T> {
+        MatRef {
+            data: self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+        }
+    }
+
+    /// Reborrow `self`, constructing a new `MatMut` with a narrower lifetime.
+    #[inline]
+    pub fn borrow_mut(&mut self) -> MatMut<'_, T> {
+        MatMut {
+            data: self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride,
+        }
+    }
+}
+
+impl<T> fmt::Debug for MatRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MatRef")
+            .field("rows", &self.rows)
+            .field("cols", &self.cols)
+            .field("stride", &self.stride)
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for MatMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MatMut")
+            .field("rows", &self.rows)
+            .field("cols", &self.cols)
+            .field("stride", &self.stride)
+            .finish()
+    }
+}
@@ -0,0 +1,112 @@
+//! Linear algebra routines built on top of [`Amx`](crate::Amx)
+//!
+//! # Determinism
+//!
+//! The instructions issued by each routine, and hence the order in which
+//! products are accumulated, depend only on the shapes of the operands. In
+//! particular, the sum for each element of a matrix product is accumulated
+//! in the same order regardless of its row stride or of how the rows are
+//! split into bands (e.g., by [`parallel_sgemm`]). Any reductions performed
+//! on the CPU (e.g., summing the lanes in [`sdot`] or the packed panels in
+//! [`sgemm`]) are done in a fixed sequential order. No separate
+//! deterministic mode is needed: every call runs in this fixed order.
+//!
+//! Since [`AmxEmuCtx`] mirrors the hardware's fused multiply-add rounding,
+//! the results are bit-identical between [`AmxCtx`] and [`AmxEmuCtx`], so
+//! golden outputs produced on one can be checked on the other. The
+//! `DualCtx` provided by the `dual` feature can be used to verify this on a
+//! particular machine.
+//!
+//! [`AmxEmuCtx`]: crate::AmxEmuCtx
+//! [`AmxCtx`]: crate::AmxCtx
+//! [`parallel_sgemm`]: crate::parallel::parallel_sgemm
+use crate::{Amx, Fma32Operand, Mac16Operand, XBytes, YBytes, ZRow};
+
+/// The number of `f32` lanes in a register row
+pub(crate) const F32_LANES: usize = 16;
+
+mod batch_inverse;
+mod cgemm;
+mod conv;
+mod dgemm;
+mod gemm;
+mod gemv;
+mod givens;
+mod hgemm;
+mod igemm;
+mod jacobi;
+mod kalman;
+mod mat;
+mod portfolio;
+mod transpose;
+mod tridiagonal;
+pub use self::{
+    batch_inverse::*, cgemm::*, conv::*, dgemm::*, gemm::*, gemv::*, givens::*, hgemm::*, igemm::*,
+    jacobi::*, kalman::*, mat::*, portfolio::*, transpose::*, tridiagonal::*,
+};
+
+/// A pair of real and imaginary parts stored separately (split-complex
+/// storage).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SplitComplex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+/// Calculate `z[z_index][i] += x[i] * y[i]` (or `-=` if `subtract` is set)
+/// for `x, y, z[_]: [f32; 16]` using vector-mode `fma32`/`fms32`.
+///
+/// If `accumulate` is `false`, `z[z_index]` is treated as zero.
+#[inline(always)]
+pub(crate) fn fma32_vector(
+    ctx: &mut (impl Amx + ?Sized),
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+    accumulate: bool,
+    subtract: bool,
+) {
+    let operand = Fma32Operand::new()
+        .x_offset(Some(x_offset_bytes))
+        .y_offset(Some(y_offset_bytes))
+        .z_row(z_index)
+        .accumulate(accumulate)
+        .vector(true)
+        .encode();
+    if subtract {
+        ctx.fms32(operand);
+    } else {
+        ctx.fma32(operand);
+    }
+}
+
+/// Calculate `z[z_index][i] += (x[i] * y[i]) >> shift` for `x, y: [i16; 32]`
+/// using vector-mode `mac16`. The product is calculated in 32 bits before
+/// the arithmetic shift.
+///
+/// If `wide` is `false`, `z[z_index]` is `[i16; 32]`. Otherwise, the sums
+/// are accumulated in 32 bits, and the even (odd) lanes are placed in
+/// `z[z_index]` (`z[z_index + 1]`) as `[i32; 16]`.
+///
+/// If `accumulate` is `false`, the existing contents of Z are treated as
+/// zero.
+#[inline(always)]
+pub(crate) fn mac16_vector(
+    ctx: &mut (impl Amx + ?Sized),
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+    accumulate: bool,
+    wide: bool,
+    shift: u32,
+) {
+    let operand = Mac16Operand::new()
+        .x_offset(Some(x_offset_bytes))
+        .y_offset(Some(y_offset_bytes))
+        .z_row(z_index)
+        .accumulate(accumulate)
+        .widen(wide)
+        .shift(shift)
+        .vector(true);
+    ctx.mac16(operand.encode());
+}
@@ -0,0 +1,139 @@
+//! Portfolio risk
+use super::{F32_LANES, MatMut, MatRef, cgemm::read_tile_row, sdot, sgemm};
+use crate::{Amx, ZRow, pipeline::Pipeline};
+
+/// Calculate the sample covariance matrix `cov` (`N`×`N`) of the asset
+/// returns `returns` (`T`×`N`, one observation per row):
+///
+/// ```text
+/// cov = (A - mean)ᵀ (A - mean) / (T - 1)
+/// ```
+///
+/// The column means are removed on the CPU, and the Gram matrix is
+/// accumulated by `f32` outer products of the observations. Only the 16×16
+/// blocks on or above the diagonal are calculated; the rest are mirrored.
+///
+/// This overwrites `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if `cov` is not `N`×`N` or if `T < 2`.
+pub fn returns_covariance(
+    ctx: &mut (impl Amx + ?Sized),
+    returns: MatRef<'_, f32>,
+    mut cov: MatMut<'_, f32>,
+) {
+    let (t, n) = (returns.rows(), returns.cols());
+    assert_eq!((cov.rows(), cov.cols()), (n, n), "shape mismatch in `cov`");
+    assert!(t >= 2, "at least two observations are required");
+
+    let mut mean = vec![0f32; n];
+    for i in 0..t {
+        for (m, &r) in mean.iter_mut().zip(returns.row(i)) {
+            *m += r;
+        }
+    }
+    for m in &mut mean {
+        *m /= t as f32;
+    }
+    let scale = 1.0 / (t - 1) as f32;
+
+    // `pack[i0 / 16][tt]` = `A[tt, i0..i0 + 16] - mean[i0..i0 + 16]`
+    let pack: Vec<Vec<[f32; F32_LANES]>> = (0..n)
+        .step_by(F32_LANES)
+        .map(|i0| {
+            let nr = (n - i0).min(F32_LANES);
+            (0..t)
+                .map(|tt| {
+                    let mut row = [0f32; F32_LANES];
+                    let centered = returns.row(tt)[i0..i0 + nr]
+                        .iter()
+                        .zip(&mean[i0..i0 + nr])
+                        .map(|(r, m)| r - m);
+                    for (dst, src) in row.iter_mut().zip(centered) {
+                        *dst = src;
+                    }
+                    row
+                })
+                .collect()
+        })
+        .collect();
+
+    for (bi, a_pack) in pack.iter().enumerate() {
+        let i0 = bi * F32_LANES;
+        let mr = (n - i0).min(F32_LANES);
+        for (bj, b_pack) in pack.iter().enumerate().skip(bi) {
+            let j0 = bj * F32_LANES;
+            let nr = (n - j0).min(F32_LANES);
+            Pipeline::deepest(1, 1).run(
+                ctx,
+                t,
+                |ctx, tt, slot| {
+                    // Safety: Reading 64 bytes from each `[f32; 16]`
+                    unsafe {
+                        ctx.load512(a_pack[tt].as_ptr(), slot.y_row(0));
+                        ctx.load512(b_pack[tt].as_ptr(), slot.x_row(0));
+                    }
+                },
+                |ctx, tt, slot| {
+                    ctx.outer_product_f32_xy_to_z(
+                        Some(slot.x_bytes(0)),
+                        Some(slot.y_bytes(0)),
+                        ZRow(0),
+                        tt != 0,
+                    );
+                },
+            );
+
+            for ii in 0..mr {
+                let row = read_tile_row(ctx, 0, ii);
+                for (jj, &value) in row[..nr].iter().enumerate() {
+                    let value = value * scale;
+                    cov.row_mut(i0 + ii)[j0 + jj] = value;
+                    cov.row_mut(j0 + jj)[i0 + ii] = value;
+                }
+            }
+        }
+    }
+}
+
+/// Calculate the variance `wᵀ Σ w` of each portfolio, where `weights` holds
+/// one portfolio's weights (`N`) per row and `cov` is `Σ` (`N`×`N`).
+///
+/// `W Σ` is calculated by [`sgemm`] and each variance by [`sdot`], so large
+/// batches of candidate portfolios (e.g., from a Monte Carlo search or an
+/// optimizer's line search) are evaluated with a single matrix product.
+///
+/// This overwrites `x`, `y`, and `z`.
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent.
+pub fn portfolio_variance(
+    ctx: &mut (impl Amx + ?Sized),
+    cov: MatRef<'_, f32>,
+    weights: MatRef<'_, f32>,
+    out: &mut [f32],
+) {
+    let n = cov.rows();
+    assert_eq!(cov.cols(), n, "shape mismatch in `cov`");
+    assert_eq!(weights.cols(), n, "shape mismatch in `weights`");
+    assert_eq!(weights.rows(), out.len(), "shape mismatch in `out`");
+
+    if n == 0 {
+        out.fill(0.0);
+        return;
+    }
+
+    let mut ws = vec![0f32; weights.rows() * n];
+    sgemm(
+        ctx,
+        weights,
+        cov,
+        MatMut::new(&mut ws, weights.rows(), n),
+        false,
+    );
+    for (p, (out, ws)) in out.iter_mut().zip(ws.chunks_exact(n)).enumerate() {
+        *out = sdot(ctx, ws, weights.row(p));
+    }
+}
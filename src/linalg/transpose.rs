@@ -0,0 +1,121 @@
+//! Matrix transposition
+use super::{MatMut, MatRef};
+use crate::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+/// The number of 16-bit lanes read by `mac16`
+const LANES: usize = 32;
+
+/// Transpose `src` into `dst`, i.e., calculate `dst = src^T`.
+///
+/// The elements are moved as bit patterns, so NaNs, infinities, and signed
+/// zeros are preserved.
+///
+/// ```rust
+/// use amx::linalg::{MatMut, MatRef, transpose_f32};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+/// let mut b = [0.0; 6];
+/// transpose_f32(&mut ctx, MatRef::new(&a, 2, 3), MatMut::new(&mut b, 3, 2));
+/// assert_eq!(b, [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+/// ```
+///
+/// # Execution
+///
+/// The matrix is transposed in 16×16 tiles staged in Z. The rows of a tile
+/// are loaded to Y one by one, and each 32-bit element is viewed as a pair
+/// of 16-bit halves. [`Amx::outer_product_i16_xy_to_z`] multiplies them by
+/// a unit vector in X, which scatters the halves to a column of Z without
+/// changing their bits. The transposed rows are then stored from Z.
+///
+/// # Panics
+///
+/// Panics if the shape of `dst` is not the transpose of that of `src`.
+pub fn transpose_f32(ctx: &mut (impl Amx + ?Sized), src: MatRef<'_, f32>, dst: MatMut<'_, f32>) {
+    transpose(ctx, src, dst);
+}
+
+/// Transpose `src` into `dst`, i.e., calculate `dst = src^T`.
+///
+/// This works the same way as [`transpose_f32`] with 32×32 tiles.
+///
+/// # Panics
+///
+/// Panics if the shape of `dst` is not the transpose of that of `src`.
+pub fn transpose_i16(ctx: &mut (impl Amx + ?Sized), src: MatRef<'_, i16>, dst: MatMut<'_, i16>) {
+    transpose(ctx, src, dst);
+}
+
+/// Transpose a matrix of 16-bit or 32-bit elements.
+fn transpose<T: Copy + Default>(
+    ctx: &mut (impl Amx + ?Sized),
+    src: MatRef<'_, T>,
+    mut dst: MatMut<'_, T>,
+) {
+    // The number of 16-bit halves of an element
+    let halves = size_of::<T>() / 2;
+    // The size of a tile
+    let tile = LANES / halves;
+    debug_assert!(matches!(halves, 1 | 2));
+
+    let (m, n) = (src.rows(), src.cols());
+    assert_eq!((dst.rows(), dst.cols()), (n, m), "shape mismatch in `dst`");
+
+    // `X` is zero except for `x[0] = 1`, so `x[(512 - 2 * l) % 512..]` is a
+    // unit vector selecting the `l`-th lane
+    let mut unit = [0i16; LANES];
+    // Safety: Reading 64 bytes from `[i16; 32]`
+    unsafe { ctx.load512(unit.as_ptr(), XRow(7)) };
+    unit[0] = 1;
+    // Safety: Reading 64 bytes from `[i16; 32]`
+    unsafe { ctx.load512(unit.as_ptr(), XRow(0)) };
+
+    // Used for the edge tiles. Only the first `tile` elements are used.
+    let mut buf = [T::default(); LANES];
+
+    for i0 in (0..m).step_by(tile) {
+        let rows = (m - i0).min(tile);
+        for j0 in (0..n).step_by(tile) {
+            let cols = (n - j0).min(tile);
+
+            for ii in 0..rows {
+                let row = &src.row(i0 + ii)[j0..j0 + cols];
+                let y_row = YRow(ii % 8);
+                if cols == tile {
+                    // Safety: Reading 64 bytes from `row`
+                    unsafe { ctx.load512(row.as_ptr(), y_row) };
+                } else {
+                    buf[..cols].copy_from_slice(row);
+                    buf[cols..tile].fill(T::default());
+                    // Safety: Reading 64 bytes from `[T; 32]`
+                    unsafe { ctx.load512(buf.as_ptr(), y_row) };
+                }
+
+                // The `h`-th half of `src[i0 + ii, j0 + jj]` is written to
+                // `z[(jj * halves) * 2][ii * halves + h]`
+                for h in 0..halves {
+                    let lane = ii * halves + h;
+                    ctx.outer_product_i16_xy_to_z(
+                        Some(XBytes((512 - 2 * lane) % 512)),
+                        Some(YBytes(y_row.0 * 64 + 2 * h)),
+                        ZRow(0),
+                        lane != 0,
+                    );
+                }
+            }
+
+            for jj in 0..cols {
+                let z_row = ZRow(jj * halves * 2);
+                let row = &mut dst.row_mut(j0 + jj)[i0..i0 + rows];
+                if rows == tile {
+                    // Safety: Writing 64 bytes to `row`
+                    unsafe { ctx.store512(row.as_mut_ptr(), z_row) };
+                } else {
+                    // Safety: Writing 64 bytes to `[T; 32]`
+                    unsafe { ctx.store512(buf.as_mut_ptr(), z_row) };
+                    row.copy_from_slice(&buf[..rows]);
+                }
+            }
+        }
+    }
+}
@@ -12,10 +12,9 @@ struct MemArgs {
 }
 
 impl MemArgs {
+    /// The caller is responsible for checking `reg_offset`.
     #[inline]
     fn encode(self) -> u64 {
-        debug_assert!(self.reg_offset < 64);
-
         // The pointer is passed by a separate parameter when using `AmxOps`
         (self.reg_offset << 56)
             // [61] - ?
@@ -33,11 +32,38 @@ enum MemSize {
     _128 = 1,
 }
 
+/// The cache behavior requested for a store, passed to
+/// [`Amx::store512_hinted`] and [`Amx::store1024_aligned_hinted`].
+///
+/// No cache-control encoding of the AMX stores is known yet, so
+/// [`Self::NonTemporal`] currently issues the same `stz` as
+/// [`Self::Normal`] and has no effect. The hint marks the stores that would
+/// use a streaming encoding if one is found. The `instructions` benchmark
+/// has a row for each (`stz` and `stz.nt`). Until an encoding is found, both
+/// rows measure the same instruction.
+///
+/// [`Amx::store512_hinted`]: crate::Amx::store512_hinted
+/// [`Amx::store1024_aligned_hinted`]: crate::Amx::store1024_aligned_hinted
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StoreHint {
+    /// The data is expected to be read again soon.
+    #[default]
+    Normal,
+    /// The data isn't expected to be read again soon, e.g., a large result
+    /// matrix that is written once.
+    NonTemporal,
+}
+
 /// Register row types supporting 512-bit and 1024-bit operations.
 ///
 /// This trait is not meant to be used directly. Please use [`Amx`]'s methods
 /// instead.
 ///
+/// The methods other than the `_unchecked` ones panic if the register index
+/// is out of range. With debug assertions enabled, they also panic if the
+/// pointer is null, doesn't fit in the 56-bit address field of the operand,
+/// or (for the 1024-bit operations) isn't aligned to 128-byte boundaries.
+///
 /// [`Amx`]: crate::Amx
 pub trait LoadStore {
     /// Load 512 bits (64 bytes) from memory to the register.
@@ -53,6 +79,51 @@ pub trait LoadStore {
     ///
     /// `ptr` must be aligned to 128-byte boundaries.
     unsafe fn store1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T);
+
+    /// Like [`Self::load512`], but without checking the register index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `load512`'s requirements, the register index must be
+    /// in range.
+    unsafe fn load512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T);
+    /// Like [`Self::store512`], but without checking the register index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `store512`'s requirements, the register index must be
+    /// in range.
+    unsafe fn store512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T);
+    /// Like [`Self::load1024_aligned`], but without checking the register
+    /// index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `load1024_aligned`'s requirements, the register index
+    /// must be in range.
+    unsafe fn load1024_aligned_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T);
+    /// Like [`Self::store1024_aligned`], but without checking the register
+    /// index.
+    ///
+    /// # Safety
+    ///
+    /// In addition to `store1024_aligned`'s requirements, the register index
+    /// must be in range.
+    unsafe fn store1024_aligned_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T);
+
+    /// Get the register file, the row index, and the number of rows in the
+    /// register file. [`LoadStoreMulti`] uses this to find adjacent rows.
+    #[doc(hidden)]
+    #[inline]
+    fn row_location(&self) -> Option<(u8, usize, usize)> {
+        None
+    }
+
+    /// Get the row `n` rows after `self`. The index may be out of range.
+    #[doc(hidden)]
+    fn nth_row(&self, n: usize) -> Self
+    where
+        Self: Sized;
 }
 
 #[cfg(feature = "either")]
@@ -96,158 +167,446 @@ impl<Left: LoadStore, Right: LoadStore> LoadStore for either::Either<Left, Right
             }
         }
     }
-}
 
-impl LoadStore for XRow {
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn load512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+    #[inline]
+    unsafe fn load512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
         unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.ldx(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_64,
-                }
-                .encode(),
-                ptr as *mut (),
-            );
+            match self {
+                either::Left(x) => x.load512_unchecked(ops, ptr),
+                either::Right(x) => x.load512_unchecked(ops, ptr),
+            }
         }
     }
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn store512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+    #[inline]
+    unsafe fn store512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
         unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.stx(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_64,
-                }
-                .encode(),
-                ptr as *mut (),
-            );
+            match self {
+                either::Left(x) => x.store512_unchecked(ops, ptr),
+                either::Right(x) => x.store512_unchecked(ops, ptr),
+            }
         }
     }
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn load1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+    #[inline]
+    unsafe fn load1024_aligned_unchecked<T>(
+        &self,
+        ops: &mut (impl AmxOps + ?Sized),
+        ptr: *const T,
+    ) {
         unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.ldx(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
-                }
-                .encode(),
-                ptr as *mut (),
-            );
+            match self {
+                either::Left(x) => x.load1024_aligned_unchecked(ops, ptr),
+                either::Right(x) => x.load1024_aligned_unchecked(ops, ptr),
+            }
         }
     }
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn store1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+    #[inline]
+    unsafe fn store1024_aligned_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
         unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.stx(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
-                }
-                .encode(),
-                ptr as *mut (),
-            );
+            match self {
+                either::Left(x) => x.store1024_aligned_unchecked(ops, ptr),
+                either::Right(x) => x.store1024_aligned_unchecked(ops, ptr),
+            }
+        }
+    }
+
+    #[inline]
+    fn row_location(&self) -> Option<(u8, usize, usize)> {
+        match self {
+            either::Left(x) => x.row_location(),
+            either::Right(x) => x.row_location(),
+        }
+    }
+
+    #[inline]
+    fn nth_row(&self, n: usize) -> Self {
+        match self {
+            either::Left(x) => either::Left(x.nth_row(n)),
+            either::Right(x) => either::Right(x.nth_row(n)),
         }
     }
 }
 
-impl LoadStore for YRow {
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn load512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.ldy(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_64,
+/// Check that the register row index `index` is in range.
+#[inline(always)]
+#[track_caller]
+fn check_row_index(index: usize, num_rows: usize) {
+    assert!(
+        index < num_rows,
+        "register row index {index} is out of range for a register file of {num_rows} rows"
+    );
+}
+
+/// Check in debug builds that `ptr` can be encoded in a load or store operand
+/// and is aligned to `align`-byte boundaries.
+#[inline(always)]
+#[track_caller]
+fn debug_check_ptr<T>(ptr: *const T, align: usize) {
+    debug_assert!(!ptr.is_null(), "the pointer is null");
+    debug_assert!(
+        (ptr as u64) >> 56 == 0,
+        "the pointer {ptr:p} doesn't fit in the 56-bit address field"
+    );
+    debug_assert!(
+        (ptr as usize).is_multiple_of(align),
+        "the pointer {ptr:p} isn't aligned to {align}-byte boundaries"
+    );
+}
+
+macro_rules! impl_load_store {
+    ($ty:ty, $file:literal, $num_rows:literal, $load:ident, $store:ident) => {
+        impl LoadStore for $ty {
+            #[inline(always)]
+            fn row_location(&self) -> Option<(u8, usize, usize)> {
+                Some(($file, self.0, $num_rows))
+            }
+
+            #[inline(always)]
+            fn nth_row(&self, n: usize) -> Self {
+                Self(self.0 + n)
+            }
+
+            #[inline(always)]
+            #[track_caller]
+            unsafe fn load512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+                check_row_index(self.0, $num_rows);
+                debug_check_ptr(ptr, 1);
+                // Safety: The index is in range
+                unsafe { self.load512_unchecked(ops, ptr) }
+            }
+
+            #[inline(always)]
+            #[track_caller]
+            unsafe fn store512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+                check_row_index(self.0, $num_rows);
+                debug_check_ptr(ptr, 1);
+                // Safety: The index is in range
+                unsafe { self.store512_unchecked(ops, ptr) }
+            }
+
+            #[inline(always)]
+            #[track_caller]
+            unsafe fn load1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+                check_row_index(self.0, $num_rows);
+                debug_check_ptr(ptr, 128);
+                // Safety: The index is in range
+                unsafe { self.load1024_aligned_unchecked(ops, ptr) }
+            }
+
+            #[inline(always)]
+            #[track_caller]
+            unsafe fn store1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+                check_row_index(self.0, $num_rows);
+                debug_check_ptr(ptr, 128);
+                // Safety: The index is in range
+                unsafe { self.store1024_aligned_unchecked(ops, ptr) }
+            }
+
+            #[inline(always)]
+            unsafe fn load512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+                unsafe {
+                    ops.$load(
+                        MemArgs {
+                            reg_offset: self.0 as u64,
+                            size: MemSize::_64,
+                        }
+                        .encode(),
+                        ptr as *mut (),
+                    );
                 }
-                .encode(),
-                ptr as *mut (),
-            );
+            }
+
+            #[inline(always)]
+            unsafe fn store512_unchecked<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+                unsafe {
+                    ops.$store(
+                        MemArgs {
+                            reg_offset: self.0 as u64,
+                            size: MemSize::_64,
+                        }
+                        .encode(),
+                        ptr as *mut (),
+                    );
+                }
+            }
+
+            #[inline(always)]
+            unsafe fn load1024_aligned_unchecked<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptr: *const T,
+            ) {
+                unsafe {
+                    ops.$load(
+                        MemArgs {
+                            reg_offset: self.0 as u64,
+                            size: MemSize::_128,
+                        }
+                        .encode(),
+                        ptr as *mut (),
+                    );
+                }
+            }
+
+            #[inline(always)]
+            unsafe fn store1024_aligned_unchecked<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptr: *mut T,
+            ) {
+                unsafe {
+                    ops.$store(
+                        MemArgs {
+                            reg_offset: self.0 as u64,
+                            size: MemSize::_128,
+                        }
+                        .encode(),
+                        ptr as *mut (),
+                    );
+                }
+            }
         }
+    };
+}
+
+/// 64 zero bytes for clearing register rows
+pub(crate) static ZEROS: [u8; 64] = [0; 64];
+
+/// Check that `rows` rows starting at `first_row` are in the register file.
+#[inline]
+#[track_caller]
+pub(crate) fn check_matrix_rows(first_row: &impl LoadStore, rows: usize) {
+    if let Some((_, index, len)) = first_row.row_location() {
+        assert!(
+            index.checked_add(rows).is_some_and(|end| end <= len),
+            "{rows} rows starting at row {index} exceed the register file of {len} rows"
+        );
     }
+}
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn store512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.sty(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_64,
-                }
-                .encode(),
-                ptr as *mut (),
-            );
+/// Get the first row and the number of rows of `rows`, checking that they
+/// are in the register file.
+#[inline]
+#[track_caller]
+pub(crate) fn row_range<R: LoadStore>(rows: &std::ops::Range<R>) -> (&R, usize) {
+    let (start, end) = (rows.start.row_location(), rows.end.row_location());
+    let n = match (start, end) {
+        (Some((file1, start, _)), Some((file2, end, _))) if file1 == file2 => {
+            assert!(start <= end, "the row range is decreasing");
+            end - start
+        }
+        _ => panic!("the ends of the row range must be in the same register file"),
+    };
+    check_matrix_rows(&rows.start, n);
+    (&rows.start, n)
+}
+
+impl_load_store!(XRow, 0, 8, ldx, stx);
+impl_load_store!(YRow, 1, 8, ldy, sty);
+impl_load_store!(ZRow, 2, 64, ldz, stz);
+
+/// A group of `N` register rows that can be loaded or stored at once by
+/// [`Amx::load512_multi`] and [`Amx::store512_multi`]. Implemented for
+/// arrays and tuples (up to 4 elements) of [`LoadStore`] types.
+///
+/// Consecutive elements are fused into one 1024-bit operation if they refer
+/// to adjacent rows of the same register file and the corresponding pointers
+/// are adjacent and 128-byte aligned.
+///
+/// [`Amx::load512_multi`]: crate::Amx::load512_multi
+/// [`Amx::store512_multi`]: crate::Amx::store512_multi
+pub trait LoadStoreMulti<const N: usize> {
+    /// Load 512 bits (64 bytes) from `ptrs[i]` to the `i`-th row.
+    ///
+    /// # Safety
+    ///
+    /// Each of `ptrs` must be valid for reading 64 bytes.
+    unsafe fn load512_multi<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptrs: [*const T; N]);
+    /// Store 512 bits (64 bytes) from the `i`-th row to `ptrs[i]`.
+    ///
+    /// # Safety
+    ///
+    /// Each of `ptrs` must be valid for writing 64 bytes.
+    unsafe fn store512_multi<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptrs: [*mut T; N]);
+}
+
+/// How an element of [`LoadStoreMulti`] is processed
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Fusion {
+    /// By a 512-bit operation
+    Single,
+    /// By a 1024-bit operation, together with the next element
+    Pair,
+    /// Together with the previous element
+    Skip,
+}
+
+/// Greedily pair consecutive elements referring to adjacent rows and
+/// adjacent, 128-byte aligned memory locations.
+#[inline]
+fn fusion_plan<const N: usize>(
+    locations: [Option<(u8, usize, usize)>; N],
+    addrs: [usize; N],
+) -> [Fusion; N] {
+    let mut plan = [Fusion::Single; N];
+    let mut i = 0;
+    while i + 1 < N {
+        let fusable = match (locations[i], locations[i + 1]) {
+            (Some((file1, index1, len)), Some((file2, index2, _))) => {
+                file1 == file2
+                    && index1 + 1 == index2
+                    && index2 < len
+                    && addrs[i].is_multiple_of(128)
+                    && addrs[i + 1] == addrs[i].wrapping_add(64)
+            }
+            _ => false,
+        };
+        if fusable {
+            plan[i] = Fusion::Pair;
+            plan[i + 1] = Fusion::Skip;
+            i += 2;
+        } else {
+            i += 1;
         }
     }
+    plan
+}
 
-    #[inline(always)]
+impl<R: LoadStore, const N: usize> LoadStoreMulti<N> for [R; N] {
+    #[inline]
     #[track_caller]
-    unsafe fn load1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.ldy(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
+    unsafe fn load512_multi<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptrs: [*const T; N]) {
+        let plan = fusion_plan(
+            std::array::from_fn(|i| self[i].row_location()),
+            ptrs.map(|p| p as usize),
+        );
+        for ((row, ptr), fusion) in self.iter().zip(ptrs).zip(plan) {
+            // Safety: Upheld by the caller
+            unsafe {
+                match fusion {
+                    Fusion::Single => row.load512(ops, ptr),
+                    Fusion::Pair => row.load1024_aligned(ops, ptr),
+                    Fusion::Skip => {}
                 }
-                .encode(),
-                ptr as *mut (),
-            );
+            }
         }
     }
 
-    #[inline(always)]
+    #[inline]
     #[track_caller]
-    unsafe fn store1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 8);
-            ops.sty(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
+    unsafe fn store512_multi<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptrs: [*mut T; N]) {
+        let plan = fusion_plan(
+            std::array::from_fn(|i| self[i].row_location()),
+            ptrs.map(|p| p as usize),
+        );
+        for ((row, ptr), fusion) in self.iter().zip(ptrs).zip(plan) {
+            // Safety: Upheld by the caller
+            unsafe {
+                match fusion {
+                    Fusion::Single => row.store512(ops, ptr),
+                    Fusion::Pair => row.store1024_aligned(ops, ptr),
+                    Fusion::Skip => {}
                 }
-                .encode(),
-                ptr as *mut (),
-            );
+            }
         }
     }
 }
 
-impl LoadStore for ZRow {
+macro_rules! impl_load_store_multi_tuple {
+    ($n:literal; $($ty:ident $i:tt),*) => {
+        impl<$($ty: LoadStore),*> LoadStoreMulti<$n> for ($($ty,)*) {
+            #[inline]
+            #[track_caller]
+            unsafe fn load512_multi<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptrs: [*const T; $n],
+            ) {
+                let plan = fusion_plan(
+                    [$(self.$i.row_location()),*],
+                    ptrs.map(|p| p as usize),
+                );
+                $(
+                    // Safety: Upheld by the caller
+                    unsafe {
+                        match plan[$i] {
+                            Fusion::Single => self.$i.load512(ops, ptrs[$i]),
+                            Fusion::Pair => self.$i.load1024_aligned(ops, ptrs[$i]),
+                            Fusion::Skip => {}
+                        }
+                    }
+                )*
+            }
+
+            #[inline]
+            #[track_caller]
+            unsafe fn store512_multi<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptrs: [*mut T; $n],
+            ) {
+                let plan = fusion_plan(
+                    [$(self.$i.row_location()),*],
+                    ptrs.map(|p| p as usize),
+                );
+                $(
+                    // Safety: Upheld by the caller
+                    unsafe {
+                        match plan[$i] {
+                            Fusion::Single => self.$i.store512(ops, ptrs[$i]),
+                            Fusion::Pair => self.$i.store1024_aligned(ops, ptrs[$i]),
+                            Fusion::Skip => {}
+                        }
+                    }
+                )*
+            }
+        }
+    };
+}
+
+impl_load_store_multi_tuple!(1; A 0);
+impl_load_store_multi_tuple!(2; A 0, B 1);
+impl_load_store_multi_tuple!(3; A 0, B 1, C 2);
+impl_load_store_multi_tuple!(4; A 0, B 1, C 2, D 3);
+
+/// Register row types supporting interleaved 512-bit operations.
+///
+/// A row `r` and its neighbor `r ^ 1` form a pair. The 64 bytes in memory
+/// are sixteen 32-bit elements alternately belonging to the two rows of the
+/// pair: element `k` corresponds to the `(8 * (r & 1) + k / 2)`-th 32-bit
+/// lane of row `(r & !1) + k % 2`. The other half of each row is left
+/// unchanged.
+///
+/// Z rows use the `ldzi` and `stzi` instructions. X and Y have no
+/// interleaved instructions, so the same access pattern is implemented by
+/// reading both rows of the pair, rearranging them on the CPU, and writing
+/// them back, which takes four or six instructions.
+///
+/// This trait is not meant to be used directly. Please use [`Amx`]'s methods
+/// instead.
+///
+/// [`Amx`]: crate::Amx
+pub trait LoadStoreInterleaved {
+    /// Load 512 bits (64 bytes) from memory to the register with
+    /// interleaving.
+    unsafe fn load512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T);
+    /// Store 512 bits (64 bytes) from the register to memory with
+    /// interleaving.
+    unsafe fn store512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T);
+}
+
+impl LoadStoreInterleaved for ZRow {
     #[inline(always)]
     #[track_caller]
-    unsafe fn load512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+    unsafe fn load512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+        check_row_index(self.0, 64);
+        debug_check_ptr(ptr, 1);
         unsafe {
-            let index = self.0;
-            assert!(index < 64);
-            ops.ldz(
+            ops.ldzi(
                 MemArgs {
-                    reg_offset: index as u64,
+                    reg_offset: self.0 as u64,
                     size: MemSize::_64,
                 }
                 .encode(),
@@ -258,13 +617,13 @@ impl LoadStore for ZRow {
 
     #[inline(always)]
     #[track_caller]
-    unsafe fn store512<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+    unsafe fn store512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+        check_row_index(self.0, 64);
+        debug_check_ptr(ptr, 1);
         unsafe {
-            let index = self.0;
-            assert!(index < 64);
-            ops.stz(
+            ops.stzi(
                 MemArgs {
-                    reg_offset: index as u64,
+                    reg_offset: self.0 as u64,
                     size: MemSize::_64,
                 }
                 .encode(),
@@ -272,84 +631,97 @@ impl LoadStore for ZRow {
             );
         }
     }
+}
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn load1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 64);
-            ops.ldz(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
+/// Get the byte range of the `k`-th element of interleaved memory in the
+/// pair of rows and which row of the pair it's in.
+#[inline]
+fn interleaved_lane(half: usize, k: usize) -> (usize, std::ops::Range<usize>) {
+    let lane = half * 8 + k / 2;
+    (k % 2, lane * 4..lane * 4 + 4)
+}
+
+macro_rules! impl_load_store_interleaved_emulated {
+    ($ty:ident) => {
+        impl LoadStoreInterleaved for $ty {
+            #[inline]
+            #[track_caller]
+            unsafe fn load512_interleaved<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptr: *const T,
+            ) {
+                check_row_index(self.0, 8);
+                debug_check_ptr(ptr, 1);
+                let (base, half) = ($ty(self.0 & !1), self.0 & 1);
+                let mut rows = [[0u8; 64]; 2];
+                // Safety: Upheld by the caller
+                let src = unsafe { ptr.cast::<[u8; 64]>().read_unaligned() };
+                unsafe {
+                    base.store512(ops, rows[0].as_mut_ptr());
+                    base.nth_row(1).store512(ops, rows[1].as_mut_ptr());
                 }
-                .encode(),
-                ptr as *mut (),
-            );
-        }
-    }
+                for (k, src) in src.chunks_exact(4).enumerate() {
+                    let (i, bytes) = interleaved_lane(half, k);
+                    rows[i][bytes].copy_from_slice(src);
+                }
+                unsafe {
+                    base.load512(ops, rows[0].as_ptr());
+                    base.nth_row(1).load512(ops, rows[1].as_ptr());
+                }
+            }
 
-    #[inline(always)]
-    #[track_caller]
-    unsafe fn store1024_aligned<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
-        unsafe {
-            let index = self.0;
-            assert!(index < 64);
-            ops.stz(
-                MemArgs {
-                    reg_offset: index as u64,
-                    size: MemSize::_128,
+            #[inline]
+            #[track_caller]
+            unsafe fn store512_interleaved<T>(
+                &self,
+                ops: &mut (impl AmxOps + ?Sized),
+                ptr: *mut T,
+            ) {
+                check_row_index(self.0, 8);
+                debug_check_ptr(ptr, 1);
+                let (base, half) = ($ty(self.0 & !1), self.0 & 1);
+                let mut rows = [[0u8; 64]; 2];
+                unsafe {
+                    base.store512(ops, rows[0].as_mut_ptr());
+                    base.nth_row(1).store512(ops, rows[1].as_mut_ptr());
                 }
-                .encode(),
-                ptr as *mut (),
-            );
+                let mut dst = [0u8; 64];
+                for (k, dst) in dst.chunks_exact_mut(4).enumerate() {
+                    let (i, bytes) = interleaved_lane(half, k);
+                    dst.copy_from_slice(&rows[i][bytes]);
+                }
+                // Safety: Upheld by the caller
+                unsafe { ptr.cast::<[u8; 64]>().write_unaligned(dst) };
+            }
         }
-    }
+    };
 }
 
-/// Load 512 bits (64 bytes) from memory to `z[index][0..64]` with interleaving.
-///
-/// `index` must be in range `0..64`.
-#[inline(always)]
-#[track_caller]
-pub(crate) unsafe fn load512_z_interleaved<T>(
-    ops: &mut (impl AmxOps + ?Sized),
-    ptr: *const T,
-    ZRow(index): ZRow,
-) {
-    unsafe {
-        assert!(index < 64);
-        ops.ldzi(
-            MemArgs {
-                reg_offset: index as u64,
-                size: MemSize::_64,
+impl_load_store_interleaved_emulated!(XRow);
+impl_load_store_interleaved_emulated!(YRow);
+
+#[cfg(feature = "either")]
+impl<Left: LoadStoreInterleaved, Right: LoadStoreInterleaved> LoadStoreInterleaved
+    for either::Either<Left, Right>
+{
+    #[inline]
+    unsafe fn load512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *const T) {
+        unsafe {
+            match self {
+                either::Left(x) => x.load512_interleaved(ops, ptr),
+                either::Right(x) => x.load512_interleaved(ops, ptr),
             }
-            .encode(),
-            ptr as *mut (),
-        );
+        }
     }
-}
 
-/// Store 512 bits (64 bytes) `z[index][0..64]` to memory with interleaving.
-///
-/// `index` must be in range `0..64`.
-#[inline(always)]
-#[track_caller]
-pub(crate) unsafe fn store512_z_interleaved<T>(
-    ops: &mut (impl AmxOps + ?Sized),
-    ptr: *mut T,
-    ZRow(index): ZRow,
-) {
-    unsafe {
-        assert!(index < 64);
-        ops.stzi(
-            MemArgs {
-                reg_offset: index as u64,
-                size: MemSize::_64,
+    #[inline]
+    unsafe fn store512_interleaved<T>(&self, ops: &mut (impl AmxOps + ?Sized), ptr: *mut T) {
+        unsafe {
+            match self {
+                either::Left(x) => x.store512_interleaved(ops, ptr),
+                either::Right(x) => x.store512_interleaved(ops, ptr),
             }
-            .encode(),
-            ptr as *mut (),
-        );
+        }
     }
 }
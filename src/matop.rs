@@ -0,0 +1,478 @@
+//! Wrappers for the `matint` and `matfp` instructions
+//!
+//! # Operand encoding
+//!
+//! | Bits  | Field                                                         |
+//! |-------|---------------------------------------------------------------|
+//! | 0–8   | Y byte offset                                                 |
+//! | 10–18 | X byte offset                                                 |
+//! | 20–25 | Z row                                                         |
+//! | 27    | Treat Z as zero (i.e., don't accumulate)                      |
+//! | 32–37 | Number of enabled X lanes (`0` = all)                         |
+//! | 42–45 | Lane type (see [`MatIntTy`] and [`MatFpTy`])                  |
+//! | 47–52 | ALU mode: `0` = `z + x * y`, `1` = `z - x * y`                |
+//! | 53–58 | Number of enabled Y lanes (`0` = all)                         |
+//! | 62    | Widen the output                                              |
+//!
+//! The lane type is `0` = `i16`, `1` = `u16`, `2` = `i8`, `3` = `u8` for
+//! `matint` and `4` = `f32`, `7` = `f64`, otherwise `f16` for `matfp`. Since
+//! M2, `1` = `bf16` for `matfp`.
+//!
+//! These encodings are taken from the reverse-engineered descriptions of
+//! [`matint`] and [`matfp`].
+//!
+//! M2 also adds `matint` lane types, but their encodings haven't been
+//! confirmed on the hardware, so they are left out: the other `matint` lane
+//! types don't decode to a [`MatIntTy`], and [`AmxEmuCtx`] panics when
+//! executing them instead of guessing a layout.
+//!
+//! [`AmxEmuCtx`]: crate::AmxEmuCtx
+//! [`matint`]: https://github.com/corsix/amx/blob/main/matint.md
+//! [`matfp`]: https://github.com/corsix/amx/blob/main/matfp.md
+#[cfg(feature = "amx2")]
+use crate::{Amx, XRow, YRow};
+use crate::{AmxOps, AmxVersion, MatFpOperand, MatIntOperand, XBytes, YBytes, ZRow};
+
+/// The input lane type of a [`MatIntOp`]
+///
+/// 8-bit inputs are read from the first 32 bytes of the X and Y operands
+/// and extended to 16 bits, so they produce the same output layout as the
+/// 16-bit inputs.
+///
+/// These are the M1 lane types, which later revisions also support. The
+/// lane types added by M2 aren't provided because their encodings haven't
+/// been confirmed on the hardware.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MatIntTy {
+    I8,
+    U8,
+    I16,
+    U16,
+}
+
+impl MatIntTy {
+    /// Get the size of an input lane in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+        }
+    }
+
+    /// Get the raw lane type.
+    const fn mode(self) -> u64 {
+        match self {
+            Self::I16 => 0,
+            Self::U16 => 1,
+            Self::I8 => 2,
+            Self::U8 => 3,
+        }
+    }
+
+    /// Decode the raw lane type.
+    const fn from_mode(mode: u64) -> Option<Self> {
+        match mode {
+            0 => Some(Self::I16),
+            1 => Some(Self::U16),
+            2 => Some(Self::I8),
+            3 => Some(Self::U8),
+            _ => None,
+        }
+    }
+}
+
+/// The input lane type of a [`MatFpOp`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MatFpTy {
+    /// `[f16; 32]`
+    F16,
+    /// `[f32; 16]`
+    F32,
+    /// `[f64; 8]`
+    F64,
+    /// `[bf16; 32]`, supported since [`AmxVersion::M2`] (requires the `amx2`
+    /// feature)
+    #[cfg(feature = "amx2")]
+    Bf16,
+}
+
+impl MatFpTy {
+    /// Get the size of an input lane in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::F16 => 2,
+            Self::F32 => 4,
+            Self::F64 => 8,
+            #[cfg(feature = "amx2")]
+            Self::Bf16 => 2,
+        }
+    }
+
+    /// Get the oldest AMX revision supporting the lane type.
+    pub const fn required_version(self) -> AmxVersion {
+        match self {
+            #[cfg(feature = "amx2")]
+            Self::Bf16 => AmxVersion::M2,
+            _ => AmxVersion::M1,
+        }
+    }
+
+    /// Get the raw lane type.
+    const fn mode(self) -> u64 {
+        match self {
+            Self::F16 => 0,
+            Self::F32 => 4,
+            Self::F64 => 7,
+            #[cfg(feature = "amx2")]
+            Self::Bf16 => 1,
+        }
+    }
+
+    /// Decode the raw lane type. Returns `None` for `bf16` without the
+    /// `amx2` feature.
+    const fn from_mode(mode: u64) -> Option<Self> {
+        match mode {
+            4 => Some(Self::F32),
+            7 => Some(Self::F64),
+            #[cfg(feature = "amx2")]
+            1 => Some(Self::Bf16),
+            #[cfg(not(feature = "amx2"))]
+            1 => None,
+            _ => Some(Self::F16),
+        }
+    }
+}
+
+/// The options shared by [`MatIntOp`] and [`MatFpOp`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct MatOptions {
+    pub(crate) widen: bool,
+    pub(crate) accumulate: bool,
+    pub(crate) subtract: bool,
+    pub(crate) x_lanes: usize,
+    pub(crate) y_lanes: usize,
+}
+
+impl MatOptions {
+    const DEFAULT: Self = Self {
+        widen: false,
+        accumulate: true,
+        subtract: false,
+        x_lanes: 0,
+        y_lanes: 0,
+    };
+
+    /// Encode the options along with the raw lane type `ty_mode`.
+    #[inline(always)]
+    const fn bits(&self, ty_mode: u64) -> u64 {
+        ((!self.accumulate as u64) << 27)
+            | ((self.x_lanes as u64) << 32)
+            | (ty_mode << 42)
+            | ((self.subtract as u64) << 47)
+            | ((self.y_lanes as u64) << 53)
+            | ((self.widen as u64) << 62)
+    }
+
+    /// Decode the fields encoded by [`Self::bits`]. Returns `None` if the
+    /// ALU mode is not supported.
+    const fn from_bits(operand: u64) -> Option<Self> {
+        let subtract = match (operand >> 47) & 0x3f {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        Some(Self {
+            widen: operand & (1 << 62) != 0,
+            accumulate: operand & (1 << 27) == 0,
+            subtract,
+            x_lanes: ((operand >> 32) & 0x3f) as usize,
+            y_lanes: ((operand >> 53) & 0x3f) as usize,
+        })
+    }
+}
+
+/// Define the builder methods shared by [`MatIntOp`] and [`MatFpOp`].
+macro_rules! mat_options {
+    ($ty:ident) => {
+        impl $ty {
+            /// Set whether the products are added to the existing contents
+            /// of Z. If `false`, Z is treated as zero.
+            pub const fn accumulate(mut self, accumulate: bool) -> Self {
+                self.options.accumulate = accumulate;
+                self
+            }
+
+            /// Set whether the products are subtracted instead of added.
+            pub const fn subtract(mut self, subtract: bool) -> Self {
+                self.options.subtract = subtract;
+                self
+            }
+
+            /// Enable only the first `lanes` lanes of X. The output elements
+            /// of the disabled lanes are left unchanged.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `lanes` is zero or exceeds the number of lanes.
+            #[track_caller]
+            pub const fn x_lanes(mut self, lanes: usize) -> Self {
+                assert!(
+                    lanes != 0 && lanes <= self.lanes(),
+                    "`lanes` is out of range"
+                );
+                self.options.x_lanes = lanes % self.lanes();
+                self
+            }
+
+            /// Enable only the first `lanes` lanes of Y. The output elements
+            /// of the disabled lanes are left unchanged.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `lanes` is zero or exceeds the number of lanes.
+            #[track_caller]
+            pub const fn y_lanes(mut self, lanes: usize) -> Self {
+                assert!(
+                    lanes != 0 && lanes <= self.lanes(),
+                    "`lanes` is out of range"
+                );
+                self.options.y_lanes = lanes % self.lanes();
+                self
+            }
+        }
+    };
+}
+
+/// An integer outer product executed by
+/// [`Amx::matrix_int`](crate::Amx::matrix_int).
+///
+/// The output layout is that of
+/// [`Amx::outer_product_i16_xy_to_z`](crate::Amx::outer_product_i16_xy_to_z)
+/// (`[[i16; 32]; 64]`, every second row) or, if widened,
+/// [`Amx::outer_product_i16_xy_to_z_i32`](crate::Amx::outer_product_i16_xy_to_z_i32)
+/// (`[[i32; 16]; 64]`). The sums wrap around.
+///
+/// ```rust
+/// use amx::{Amx, MatIntOp, MatIntTy, XBytes, XRow, YBytes, YRow, ZRow};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let x = [200u8; 64];
+/// let y = [100u8; 64];
+/// unsafe {
+///     ctx.load512(x.as_ptr(), XRow(0));
+///     ctx.load512(y.as_ptr(), YRow(0));
+/// }
+///
+/// let op = MatIntOp::new(MatIntTy::U8).widen(true).accumulate(false);
+/// ctx.matrix_int(op, XBytes(0), YBytes(0), ZRow(0));
+/// assert_eq!(ctx.read_z_i32()[0], [20_000; 16]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MatIntOp {
+    ty: MatIntTy,
+    pub(crate) options: MatOptions,
+}
+
+impl MatIntOp {
+    /// Construct a `MatIntOp` calculating `z[j][i] += x[i] * y[j]` (in the
+    /// output layout) without widening.
+    pub const fn new(ty: MatIntTy) -> Self {
+        Self {
+            ty,
+            options: MatOptions::DEFAULT,
+        }
+    }
+
+    /// Set whether the output is `i32` instead of `i16`.
+    pub const fn widen(mut self, widen: bool) -> Self {
+        self.options.widen = widen;
+        self
+    }
+
+    /// Get the input lane type.
+    pub const fn ty(&self) -> MatIntTy {
+        self.ty
+    }
+
+    /// Get the number of lanes.
+    const fn lanes(&self) -> usize {
+        32
+    }
+
+    /// Encode the fields of the `matint` operand other than the register
+    /// offsets.
+    #[inline(always)]
+    pub(crate) const fn bits(&self) -> u64 {
+        self.options.bits(self.ty.mode())
+    }
+
+    /// Decode the fields encoded by [`Self::bits`]. Returns `None` if the
+    /// lane type or the ALU mode is not supported.
+    pub(crate) const fn from_bits(operand: u64) -> Option<Self> {
+        let Some(ty) = MatIntTy::from_mode((operand >> 42) & 0xf) else {
+            return None;
+        };
+        let Some(options) = MatOptions::from_bits(operand) else {
+            return None;
+        };
+        Some(Self { ty, options })
+    }
+}
+
+mat_options!(MatIntOp);
+
+/// A floating-point outer product executed by
+/// [`Amx::matrix_fp`](crate::Amx::matrix_fp).
+///
+/// The output layout is that of the `outer_product_{f16,f32,f64}_xy_to_z`
+/// methods of [`Amx`](crate::Amx), or
+/// [`Amx::outer_product_f16_xy_to_z_f32`](crate::Amx::outer_product_f16_xy_to_z_f32)
+/// if widened.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MatFpOp {
+    ty: MatFpTy,
+    pub(crate) options: MatOptions,
+}
+
+impl MatFpOp {
+    /// Construct a `MatFpOp` calculating `z[j][i] += x[i] * y[j]` (in the
+    /// output layout) without widening.
+    pub const fn new(ty: MatFpTy) -> Self {
+        Self {
+            ty,
+            options: MatOptions::DEFAULT,
+        }
+    }
+
+    /// Set whether the output is `f32` instead of `f16` (or `bf16`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `widen` is `true` and the input lane type is not 16 bits
+    /// wide.
+    #[track_caller]
+    pub const fn widen(mut self, widen: bool) -> Self {
+        assert!(
+            !widen || self.ty.size() == 2,
+            "only 16-bit lane types can be widened"
+        );
+        self.options.widen = widen;
+        self
+    }
+
+    /// Get the input lane type.
+    pub const fn ty(&self) -> MatFpTy {
+        self.ty
+    }
+
+    /// Get the number of lanes.
+    const fn lanes(&self) -> usize {
+        64 / self.ty.size()
+    }
+
+    /// Encode the fields of the `matfp` operand other than the register
+    /// offsets.
+    #[inline(always)]
+    pub(crate) const fn bits(&self) -> u64 {
+        self.options.bits(self.ty.mode())
+    }
+
+    /// Decode the fields encoded by [`Self::bits`]. Returns `None` if the
+    /// lane type or the ALU mode is not supported.
+    pub(crate) const fn from_bits(operand: u64) -> Option<Self> {
+        let Some(ty) = MatFpTy::from_mode((operand >> 42) & 0xf) else {
+            return None;
+        };
+        let Some(options) = MatOptions::from_bits(operand) else {
+            return None;
+        };
+        Some(Self { ty, options })
+    }
+}
+
+mat_options!(MatFpOp);
+
+#[inline(always)]
+pub(crate) fn matint(
+    ops: &mut (impl AmxOps + ?Sized),
+    op: MatIntOp,
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+) {
+    let operand = MatIntOperand::new(op)
+        .x_offset(x_offset_bytes)
+        .y_offset(y_offset_bytes)
+        .z_row(z_index);
+    ops.matint(operand.encode());
+}
+
+#[inline(always)]
+pub(crate) fn matfp(
+    ops: &mut (impl AmxOps + ?Sized),
+    op: MatFpOp,
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+) {
+    #[cfg(feature = "amx2")]
+    {
+        let required = op.ty.required_version();
+        assert!(
+            ops.version() >= required,
+            "`{:?}` requires AMX revision {required:?} or later",
+            op.ty
+        );
+    }
+    let operand = MatFpOperand::new(op)
+        .x_offset(x_offset_bytes)
+        .y_offset(y_offset_bytes)
+        .z_row(z_index);
+    ops.matfp(operand.encode());
+}
+
+/// Calculate the outer product of [`Amx::outer_product_bf16_xy_to_z_f32`]
+/// by `fma32` on AMX revisions without `bf16` support.
+///
+/// Widening a `bf16` to `f32` only shifts its bits, and the products of the
+/// widened values are exact, so this produces the same results as `matfp`.
+/// The lanes of each parity are widened into X row 0 and Y row 0, which are
+/// restored afterwards.
+#[cfg(feature = "amx2")]
+pub(crate) fn bf16_outer_product_by_fma32(
+    ctx: &mut (impl Amx + ?Sized),
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    accumulate: bool,
+) {
+    let (x, y) = (ctx.read_x(), ctx.read_y());
+    // The operand wraps around the register file, as on the hardware
+    let lanes = |regs: &[u8; 512], offset: usize| -> [u16; 32] {
+        std::array::from_fn(|i| {
+            let at = |k: usize| regs[(offset + i * 2 + k) % 512];
+            u16::from_le_bytes([at(0), at(1)])
+        })
+    };
+    let (x_lanes, y_lanes) = (lanes(&x, x_offset_bytes.0), lanes(&y, y_offset_bytes.0));
+    let widen = |lanes: &[u16; 32], parity: usize| -> [u32; 16] {
+        std::array::from_fn(|i| u32::from(lanes[i * 2 + parity]) << 16)
+    };
+
+    // `x[i] * y[j]` goes to `z[j * 2 + i % 2][i / 2]`, i.e., the `f32` outer
+    // product of the lanes `i % 2 == p` and `j % 2 == q` goes to the rows
+    // `4 * n + 2 * q + p`
+    for q in 0..2 {
+        ctx.load512_slice(&widen(&y_lanes, q), YRow(0));
+        for p in 0..2 {
+            ctx.load512_slice(&widen(&x_lanes, p), XRow(0));
+            ctx.outer_product_f32_xy_to_z(
+                Some(XBytes(0)),
+                Some(YBytes(0)),
+                ZRow(q * 2 + p),
+                accumulate,
+            );
+        }
+    }
+    ctx.load512_slice(&x[..64], XRow(0));
+    ctx.load512_slice(&y[..64], YRow(0));
+}
@@ -0,0 +1,189 @@
+//! Minimization of divergences between two backends
+//!
+//! When a kernel produces different results on two backends (typically
+//! [`AmxCtx`](crate::AmxCtx) and [`AmxEmuCtx`](crate::AmxEmuCtx)), the
+//! instruction trace recorded by an [`AmxEmuCtx`](crate::AmxEmuCtx) hook can
+//! be passed to [`find_divergence`] to locate the first instruction after
+//! which the register contents differ. The prefixes of the trace are
+//! re-executed by [`replay`].
+use crate::{Amx, XRow, YRow, ZRow, emu::Instr, jit::JitOp};
+
+/// A register file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RegFile {
+    /// The X registers, indexed by [`XRow`]
+    X,
+    /// The Y registers, indexed by [`YRow`]
+    Y,
+    /// The Z registers, indexed by [`ZRow`]
+    Z,
+}
+
+/// A register row whose contents differ between two backends
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowDiff {
+    /// The register file containing the row
+    pub file: RegFile,
+    /// The index of the row within `file`
+    pub row: usize,
+    /// The row's contents on the first backend
+    pub left: [u8; 64],
+    /// The row's contents on the second backend
+    pub right: [u8; 64],
+}
+
+impl RowDiff {
+    /// Get the byte offsets within the row at which the contents differ.
+    pub fn differing_bytes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..64).filter(|&i| self.left[i] != self.right[i])
+    }
+}
+
+/// The result of [`find_divergence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The length of the shortest prefix of the trace found to reproduce the
+    /// divergence. The last instruction of the prefix (`trace[len - 1]`) is
+    /// the one that introduces it.
+    pub len: usize,
+    /// The register rows that differ after executing the prefix
+    pub diffs: Vec<RowDiff>,
+}
+
+/// Replay `instrs` on `ctx`, skipping store instructions.
+///
+/// Stores are skipped because they don't affect the register contents, and
+/// replaying them would modify the memory read by subsequent replays.
+///
+/// # Safety
+///
+/// The addresses accessed by the load instructions must be valid for
+/// reading.
+pub unsafe fn replay(ctx: &mut (impl Amx + ?Sized), instrs: &[Instr]) {
+    for instr in instrs {
+        let ptr = instr.addr.unwrap_or_default() as *mut ();
+        let x = instr.operand;
+        // Safety: Upheld by the caller
+        unsafe {
+            match instr.op {
+                JitOp::Ldx => ctx.ldx(x, ptr),
+                JitOp::Ldy => ctx.ldy(x, ptr),
+                JitOp::Ldz => ctx.ldz(x, ptr),
+                JitOp::Ldzi => ctx.ldzi(x, ptr),
+                JitOp::Stx | JitOp::Sty | JitOp::Stz | JitOp::Stzi => {}
+                JitOp::Extrx => ctx.extrx(x),
+                JitOp::Extry => ctx.extry(x),
+                JitOp::Fma64 => ctx.fma64(x),
+                JitOp::Fms64 => ctx.fms64(x),
+                JitOp::Fma32 => ctx.fma32(x),
+                JitOp::Fms32 => ctx.fms32(x),
+                JitOp::Mac16 => ctx.mac16(x),
+                JitOp::Fma16 => ctx.fma16(x),
+                JitOp::Fms16 => ctx.fms16(x),
+                JitOp::Vecint => ctx.vecint(x),
+                JitOp::Vecfp => ctx.vecfp(x),
+                JitOp::Matint => ctx.matint(x),
+                JitOp::Matfp => ctx.matfp(x),
+                JitOp::Genlut => ctx.genlut(x),
+            }
+        }
+    }
+}
+
+/// Clear all registers.
+fn reset(ctx: &mut (impl Amx + ?Sized)) {
+    let zero = [0u8; 64];
+    // Safety: Reading 64 bytes from `[u8; 64]`
+    unsafe {
+        for i in 0..8 {
+            ctx.load512(zero.as_ptr(), XRow(i));
+            ctx.load512(zero.as_ptr(), YRow(i));
+        }
+        for i in 0..64 {
+            ctx.load512(zero.as_ptr(), ZRow(i));
+        }
+    }
+}
+
+/// Reset the registers, replay `instrs`, and compare the register contents.
+///
+/// # Safety
+///
+/// See [`replay`].
+unsafe fn compare(
+    a: &mut (impl Amx + ?Sized),
+    b: &mut (impl Amx + ?Sized),
+    instrs: &[Instr],
+) -> Vec<RowDiff> {
+    reset(a);
+    reset(b);
+    // Safety: Upheld by the caller
+    unsafe {
+        replay(a, instrs);
+        replay(b, instrs);
+    }
+
+    diff_registers(a, b)
+}
+
+/// Compare the register contents of `a` and `b`.
+pub(crate) fn diff_registers(
+    a: &mut (impl Amx + ?Sized),
+    b: &mut (impl Amx + ?Sized),
+) -> Vec<RowDiff> {
+    let rows = |file: RegFile, left: &[u8], right: &[u8]| {
+        left.chunks_exact(64)
+            .zip(right.chunks_exact(64))
+            .enumerate()
+            .filter(|(_, (l, r))| l != r)
+            .map(|(row, (l, r))| RowDiff {
+                file,
+                row,
+                left: l.try_into().unwrap(),
+                right: r.try_into().unwrap(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut diffs = rows(RegFile::X, &a.read_x(), &b.read_x());
+    diffs.extend(rows(RegFile::Y, &a.read_y(), &b.read_y()));
+    diffs.extend(rows(RegFile::Z, &a.read_z(), &b.read_z()));
+    diffs
+}
+
+/// Find the shortest prefix of `trace` after which the register contents of
+/// `a` and `b` differ, starting from cleared registers.
+///
+/// This bisects the trace, so it replays the trace `O(log trace.len())`
+/// times. Returns `None` if the register contents don't differ after the
+/// entire trace. If they differ only at some intermediate points, the
+/// divergence might not be found.
+///
+/// # Safety
+///
+/// See [`replay`].
+pub unsafe fn find_divergence(
+    a: &mut (impl Amx + ?Sized),
+    b: &mut (impl Amx + ?Sized),
+    trace: &[Instr],
+) -> Option<Divergence> {
+    // Safety: Upheld by the caller
+    let mut diffs = unsafe { compare(a, b, trace) };
+    if diffs.is_empty() {
+        return None;
+    }
+
+    // Invariant: `trace[..lo]` agrees, and `trace[..hi]` diverges
+    let (mut lo, mut hi) = (0, trace.len());
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        // Safety: Upheld by the caller
+        let mid_diffs = unsafe { compare(a, b, &trace[..mid]) };
+        if mid_diffs.is_empty() {
+            lo = mid;
+        } else {
+            (hi, diffs) = (mid, mid_diffs);
+        }
+    }
+
+    Some(Divergence { len: hi, diffs })
+}
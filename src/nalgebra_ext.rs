@@ -0,0 +1,194 @@
+//! Interoperability with [`nalgebra`]
+//!
+//! This mirrors `ndarray_ext` (the `ndarray` feature) for `nalgebra`'s
+//! matrix types. Since `nalgebra` stores matrices in
+//! column-major order, [`amx_mul`] computes the transposed product on the
+//! row-major routines of [`linalg`] without copying the operands.
+//!
+//! [`AmxNalgebraExt`] adds the counterparts of [`Amx::load_tile`] and
+//! [`Amx::store_tile`] to every [`Amx`] implementation. They are named
+//! differently so that both features can be enabled at the same time.
+use crate::{
+    Amx, ZRow,
+    linalg::{self, MatMut, MatRef},
+};
+use nalgebra::{DMatrix, Dim, Matrix, RawStorage, RawStorageMut};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An element type of [`AmxNalgebraExt`] and [`amx_mul`]
+///
+/// This trait is sealed and implemented for `f32` (four 16×16 tiles) and
+/// `f64` (eight 8×8 tiles).
+pub trait Scalar: sealed::Sealed + nalgebra::Scalar + Copy {
+    /// The number of rows and columns of a tile
+    const TILE: usize;
+
+    #[doc(hidden)]
+    const ZERO: Self;
+
+    #[doc(hidden)]
+    fn gemm(
+        ctx: &mut (impl Amx + ?Sized),
+        a: MatRef<'_, Self>,
+        b: MatRef<'_, Self>,
+        c: MatMut<'_, Self>,
+    );
+}
+
+impl sealed::Sealed for f32 {}
+
+impl Scalar for f32 {
+    const TILE: usize = 16;
+    const ZERO: Self = 0.0;
+
+    fn gemm(
+        ctx: &mut (impl Amx + ?Sized),
+        a: MatRef<'_, Self>,
+        b: MatRef<'_, Self>,
+        c: MatMut<'_, Self>,
+    ) {
+        linalg::sgemm(ctx, a, b, c, false);
+    }
+}
+
+impl sealed::Sealed for f64 {}
+
+impl Scalar for f64 {
+    const TILE: usize = 8;
+    const ZERO: Self = 0.0;
+
+    fn gemm(
+        ctx: &mut (impl Amx + ?Sized),
+        a: MatRef<'_, Self>,
+        b: MatRef<'_, Self>,
+        c: MatMut<'_, Self>,
+    ) {
+        linalg::dgemm(ctx, a, b, c, false);
+    }
+}
+
+/// Calculate the matrix product `a * b` by [`linalg::sgemm`] or
+/// [`linalg::dgemm`].
+///
+/// ```rust
+/// use nalgebra::DMatrix;
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = DMatrix::from_row_slice(3, 2, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let b = DMatrix::from_row_slice(2, 3, &[1.0, 0.0, -1.0, 0.5, 1.0, 2.0]);
+/// assert_eq!(amx::nalgebra_ext::amx_mul(&mut ctx, &a, &b), &a * &b);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the number of columns of `a` doesn't match the number of rows
+/// of `b`.
+pub fn amx_mul<T: Scalar>(
+    ctx: &mut (impl Amx + ?Sized),
+    a: &DMatrix<T>,
+    b: &DMatrix<T>,
+) -> DMatrix<T> {
+    let (m, k, n) = (a.nrows(), a.ncols(), b.ncols());
+    assert_eq!(b.nrows(), k, "shape mismatch in `b`");
+    let mut c = DMatrix::from_element(m, n, T::ZERO);
+    // The column-major storage of an `r`×`c` matrix is the row-major
+    // storage of its `c`×`r` transpose, and `C^T = B^T * A^T`
+    T::gemm(
+        ctx,
+        MatRef::new(b.as_slice(), n, k),
+        MatRef::new(a.as_slice(), k, m),
+        MatMut::new(c.as_mut_slice(), n, m),
+    );
+    c
+}
+
+/// An extension trait for transferring tiles between `nalgebra` matrices
+/// and Z, implemented for every [`Amx`]
+pub trait AmxNalgebraExt: Amx {
+    /// Load `src` (up to `T::TILE`×`T::TILE`) to the `tile`-th tile of Z in
+    /// the layout of [`Amx::outer_product_f32_xy_to_z`] (for `f32`) or
+    /// [`Amx::outer_product_f64_xy_to_z`] (for `f64`), filling the elements
+    /// outside `src` with zeros. Row `i` of `src` goes to row `i` of the
+    /// tile.
+    ///
+    /// ```rust
+    /// use amx::nalgebra_ext::AmxNalgebraExt;
+    /// use nalgebra::{DMatrix, Matrix2};
+    ///
+    /// let mut ctx = amx::AmxEmuCtx::default();
+    /// let a = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+    /// ctx.load_matrix_tile(&a, 5);
+    ///
+    /// let mut out = DMatrix::from_element(3, 3, f64::NAN);
+    /// ctx.store_matrix_tile(5, &mut out);
+    /// let expected = [1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0];
+    /// assert_eq!(out, DMatrix::from_row_slice(3, 3, &expected));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is larger than a tile or `tile` is not in range
+    /// `0..64 / T::TILE`.
+    #[track_caller]
+    fn load_matrix_tile<T: Scalar, R: Dim, C: Dim, S: RawStorage<T, R, C>>(
+        &mut self,
+        src: &Matrix<T, R, C, S>,
+        tile: usize,
+    ) {
+        check_tile::<T>(src.shape(), tile);
+        let mut row = [T::ZERO; 16];
+        for i in 0..T::TILE {
+            row.fill(T::ZERO);
+            if i < src.nrows() {
+                for (j, dst) in row[..src.ncols()].iter_mut().enumerate() {
+                    *dst = src[(i, j)];
+                }
+            }
+            // Safety: Reading 64 bytes from `[T; 16]`
+            unsafe { self.load512(row.as_ptr(), tile_row::<T>(tile, i)) };
+        }
+    }
+
+    /// Store the top-left corner of the `tile`-th tile of Z, in the layout
+    /// described in [`Self::load_matrix_tile`], to `dst` (up to
+    /// `T::TILE`×`T::TILE`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is larger than a tile or `tile` is not in range
+    /// `0..64 / T::TILE`.
+    #[track_caller]
+    fn store_matrix_tile<T: Scalar, R: Dim, C: Dim, S: RawStorageMut<T, R, C>>(
+        &mut self,
+        tile: usize,
+        dst: &mut Matrix<T, R, C, S>,
+    ) {
+        check_tile::<T>(dst.shape(), tile);
+        let mut row = [T::ZERO; 16];
+        for i in 0..dst.nrows() {
+            // Safety: Writing 64 bytes to `[T; 16]`
+            unsafe { self.store512(row.as_mut_ptr(), tile_row::<T>(tile, i)) };
+            for (j, &s) in row[..dst.ncols()].iter().enumerate() {
+                dst[(i, j)] = s;
+            }
+        }
+    }
+}
+
+impl<A: Amx + ?Sized> AmxNalgebraExt for A {}
+
+fn tile_row<T: Scalar>(tile: usize, row: usize) -> ZRow {
+    ZRow::tile_row(64 / T::TILE, tile, row)
+}
+
+#[track_caller]
+fn check_tile<T: Scalar>((rows, cols): (usize, usize), tile: usize) {
+    assert!(tile < 64 / T::TILE, "`tile` is out of range");
+    assert!(
+        rows <= T::TILE && cols <= T::TILE,
+        "the matrix is larger than a tile"
+    );
+}
@@ -4,24 +4,17 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use crate::nativeops::AmxOps;
+use crate::{AmxFallbackCtx, NewAmxCtxError, nativeops::AmxOps};
 
 /// Represents the current thread's AMX context.
+///
+/// AMX is enabled per thread, so this type is neither `Send` nor `Sync`. Use
+/// [`SendableAmxState`](crate::SendableAmxState) to continue a computation on
+/// another thread.
 pub struct AmxCtx {
     ops: AmxOps<'static>,
 }
 
-/// The error type for [`AmxCtx::new`]
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum NewAmxCtxError {
-    /// The current thread already has an active `AmxCtx`.
-    AlreadyActive,
-    /// AMX is not supported by the target system.
-    ///
-    /// TODO: Report this error if it's unsupported
-    Unsupported,
-}
-
 thread_local! {
     static CTX_ACTIVE: Cell<bool> = Cell::new(false);
 }
@@ -29,15 +22,19 @@ thread_local! {
 impl AmxCtx {
     /// Construct a brand new instance of `AmxCtx` by enabling AMX for the
     /// current thread.
+    ///
+    /// Fails with [`NewAmxCtxError::AlreadyActive`] if the current thread
+    /// already has an active `AmxCtx`, whose state would be clobbered.
     pub fn new() -> Result<Self, NewAmxCtxError> {
-        if CTX_ACTIVE.with(|x| x.get()) {
+        if !crate::is_available() {
+            Err(NewAmxCtxError::Unsupported)
+        } else if CTX_ACTIVE.with(|x| x.get()) {
             Err(NewAmxCtxError::AlreadyActive)
         } else {
-            // TODO: Don't assume AMX is always supported
-
             // Enable AMX for the current thread
             // Safety: AMX is supported
             unsafe { crate::nativeops::set() };
+            CTX_ACTIVE.with(|x| x.set(true));
 
             Ok(Self {
                 // Safety: AMX is supported
@@ -45,6 +42,20 @@ impl AmxCtx {
             })
         }
     }
+
+    /// Enable AMX for the current thread, call `f` with the context, and
+    /// disable AMX again, even if `f` panics. Returns the result of `f`.
+    pub fn with<R>(f: impl FnOnce(&mut Self) -> R) -> Result<R, NewAmxCtxError> {
+        // `Drop` disables AMX when `ctx` goes out of scope or unwinds
+        let mut ctx = Self::new()?;
+        Ok(f(&mut ctx))
+    }
+
+    /// Like [`Self::new`], but falls back to [`AmxEmuCtx`](crate::AmxEmuCtx)
+    /// if AMX is not supported. See [`AmxFallbackCtx::try_new`].
+    pub fn try_new_with_fallback() -> Result<AmxFallbackCtx, NewAmxCtxError> {
+        AmxFallbackCtx::try_new()
+    }
 }
 
 impl Drop for AmxCtx {
@@ -70,3 +81,9 @@ impl DerefMut for AmxCtx {
         &mut self.ops
     }
 }
+
+// Safety: Just forwarding the calls. This allows `AmxCtx` to be used as
+// `dyn DynAmx`.
+unsafe impl crate::ops::AmxOps for AmxCtx {
+    forward_amx_ops!();
+}
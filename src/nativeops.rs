@@ -1,9 +1,27 @@
 //! Low-level operations (modeled after [Apple compiler intrinsics])
 //!
+//! The free functions emit one instruction each and are
+//! `#[inline(always)]`, so a call compiles to exactly one `.word` directive,
+//! with the operand taken directly from the register holding it. Load and
+//! store operands must include the address (bits 0–55). They can be used to
+//! audit the code generated for a kernel (see `examples/asm_audit.rs`) or to
+//! bypass the [`AmxOps`](crate::AmxOps) trait altogether.
+//!
+//! # Safety
+//!
+//! The target processor must support AMX, and AMX must be enabled for the
+//! current thread (by [`set`] or [`AmxCtx`](crate::AmxCtx)) when calling the
+//! functions other than `set`. The load and store operations must access
+//! valid memory regions as described in [`Amx`](crate::Amx).
+//!
 //! [Apple compiler intrinsics]: https://www.realworldtech.com/forum/?threadid=187087&curpostid=187120
 use std::{arch::asm, marker::PhantomData};
 
 /// Emit an AMX instruction with an input register.
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn op_in<const OP: u8>(operand: u64) {
     unsafe {
@@ -29,6 +47,10 @@ pub unsafe fn op_in<const OP: u8>(operand: u64) {
 }
 
 /// Emit an AMX instruction with a 5-bit immediate.
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn op_imm<const OP: u8, const OPERAND: u8>() {
     unsafe {
@@ -41,6 +63,11 @@ pub unsafe fn op_imm<const OP: u8, const OPERAND: u8>() {
     }
 }
 
+/// Load a row or a pair of rows to X (`ldx`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn ldx(x: u64) {
     unsafe {
@@ -48,6 +75,11 @@ pub unsafe fn ldx(x: u64) {
     }
 }
 
+/// Load a row or a pair of rows to Y (`ldy`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn ldy(x: u64) {
     unsafe {
@@ -55,6 +87,11 @@ pub unsafe fn ldy(x: u64) {
     }
 }
 
+/// Store a row or a pair of rows from X (`stx`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn stx(x: u64) {
     unsafe {
@@ -62,6 +99,11 @@ pub unsafe fn stx(x: u64) {
     }
 }
 
+/// Store a row or a pair of rows from Y (`sty`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn sty(x: u64) {
     unsafe {
@@ -69,6 +111,11 @@ pub unsafe fn sty(x: u64) {
     }
 }
 
+/// Load a row or a pair of rows to Z (`ldz`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn ldz(x: u64) {
     unsafe {
@@ -76,6 +123,11 @@ pub unsafe fn ldz(x: u64) {
     }
 }
 
+/// Store a row or a pair of rows from Z (`stz`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn stz(x: u64) {
     unsafe {
@@ -83,6 +135,11 @@ pub unsafe fn stz(x: u64) {
     }
 }
 
+/// Load a row to Z in the interleaved layout (`ldzi`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn ldzi(x: u64) {
     unsafe {
@@ -90,6 +147,11 @@ pub unsafe fn ldzi(x: u64) {
     }
 }
 
+/// Store a row from Z in the interleaved layout (`stzi`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn stzi(x: u64) {
     unsafe {
@@ -97,6 +159,11 @@ pub unsafe fn stzi(x: u64) {
     }
 }
 
+/// Move a row of Z to X (`extrx`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn extrx(x: u64) {
     unsafe {
@@ -104,6 +171,11 @@ pub unsafe fn extrx(x: u64) {
     }
 }
 
+/// Move a row of Z to Y (`extry`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn extry(x: u64) {
     unsafe {
@@ -111,6 +183,11 @@ pub unsafe fn extry(x: u64) {
     }
 }
 
+/// Multiply and accumulate `f64` values (`fma64`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fma64(x: u64) {
     unsafe {
@@ -118,6 +195,11 @@ pub unsafe fn fma64(x: u64) {
     }
 }
 
+/// Multiply and subtract `f64` values (`fms64`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fms64(x: u64) {
     unsafe {
@@ -125,6 +207,11 @@ pub unsafe fn fms64(x: u64) {
     }
 }
 
+/// Multiply and accumulate `f32` values (`fma32`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fma32(x: u64) {
     unsafe {
@@ -132,6 +219,11 @@ pub unsafe fn fma32(x: u64) {
     }
 }
 
+/// Multiply and subtract `f32` values (`fms32`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fms32(x: u64) {
     unsafe {
@@ -139,6 +231,11 @@ pub unsafe fn fms32(x: u64) {
     }
 }
 
+/// Multiply and accumulate 16-bit integers (`mac16`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn mac16(x: u64) {
     unsafe {
@@ -146,6 +243,11 @@ pub unsafe fn mac16(x: u64) {
     }
 }
 
+/// Multiply and accumulate `f16` values (`fma16`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fma16(x: u64) {
     unsafe {
@@ -153,6 +255,11 @@ pub unsafe fn fma16(x: u64) {
     }
 }
 
+/// Multiply and subtract `f16` values (`fms16`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn fms16(x: u64) {
     unsafe {
@@ -160,6 +267,11 @@ pub unsafe fn fms16(x: u64) {
     }
 }
 
+/// Enable AMX for the current thread (`set`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn set() {
     unsafe {
@@ -167,6 +279,11 @@ pub unsafe fn set() {
     }
 }
 
+/// Disable AMX for the current thread (`clr`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn clr() {
     unsafe {
@@ -174,6 +291,11 @@ pub unsafe fn clr() {
     }
 }
 
+/// Perform an integer vector operation (`vecint`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn vecint(x: u64) {
     unsafe {
@@ -181,6 +303,11 @@ pub unsafe fn vecint(x: u64) {
     }
 }
 
+/// Perform a floating-point vector operation (`vecfp`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn vecfp(x: u64) {
     unsafe {
@@ -188,6 +315,11 @@ pub unsafe fn vecfp(x: u64) {
     }
 }
 
+/// Perform an integer matrix operation (`matint`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn matint(x: u64) {
     unsafe {
@@ -195,6 +327,11 @@ pub unsafe fn matint(x: u64) {
     }
 }
 
+/// Perform a floating-point matrix operation (`matfp`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn matfp(x: u64) {
     unsafe {
@@ -202,6 +339,11 @@ pub unsafe fn matfp(x: u64) {
     }
 }
 
+/// Look up or generate table indices (`genlut`).
+///
+/// # Safety
+///
+/// See the [module-level documentation](self#safety).
 #[inline(always)]
 pub unsafe fn genlut(x: u64) {
     unsafe {
@@ -350,4 +492,13 @@ unsafe impl crate::ops::AmxOps for AmxOps<'_> {
     fn genlut(&mut self, x: u64) {
         unsafe { genlut(x) };
     }
+
+    /// Get the detected AMX revision, or [`AmxVersion::M1`] if it's not
+    /// recognized.
+    ///
+    /// [`AmxVersion::M1`]: crate::AmxVersion::M1
+    #[inline]
+    fn version(&self) -> crate::AmxVersion {
+        crate::version().unwrap_or(crate::AmxVersion::M1)
+    }
 }
@@ -0,0 +1,92 @@
+//! Interoperability with [`ndarray`]
+//!
+//! Besides [`amx_dot`], [`Amx::load_tile`] and [`Amx::store_tile`] transfer
+//! `f32` tiles between arrays and Z.
+use crate::{
+    Amx, ZRow,
+    linalg::{self, MatMut, MatRef},
+};
+use ndarray::{Array2, ArrayView2, ArrayViewMut2};
+
+/// The number of rows and columns of an `f32` tile
+const TILE: usize = 16;
+
+/// Calculate the matrix product `a * b` by [`linalg::sgemm`].
+///
+/// The arrays can have any memory layout. Arrays that are not in the
+/// standard (row-major) layout are copied first.
+///
+/// ```rust
+/// use ndarray::array;
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let a = array![[1.0, 2.0], [3.0, 4.0]];
+/// let b = array![[5.0, 6.0], [7.0, 8.0]];
+/// let c = amx::ndarray_ext::amx_dot(&mut ctx, &a.view(), &b.t());
+/// assert_eq!(c, a.dot(&b.t()));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the number of columns of `a` doesn't match the number of rows
+/// of `b`.
+pub fn amx_dot(
+    ctx: &mut (impl Amx + ?Sized),
+    a: &ArrayView2<'_, f32>,
+    b: &ArrayView2<'_, f32>,
+) -> Array2<f32> {
+    let mut c = Array2::zeros((a.nrows(), b.ncols()));
+    let (a, b) = (a.as_standard_layout(), b.as_standard_layout());
+    let (m, k, n) = (a.nrows(), a.ncols(), b.ncols());
+    assert_eq!(b.nrows(), k, "shape mismatch in `b`");
+    linalg::sgemm(
+        ctx,
+        MatRef::new(a.as_slice().unwrap(), m, k),
+        MatRef::new(b.as_slice().unwrap(), k, n),
+        MatMut::new(c.as_slice_mut().unwrap(), m, n),
+        false,
+    );
+    c
+}
+
+/// See [`Amx::load_tile`].
+#[track_caller]
+pub(crate) fn load_tile(ctx: &mut (impl Amx + ?Sized), src: &ArrayView2<'_, f32>, tile: usize) {
+    check_tile(src.dim(), tile);
+    let mut row = [0f32; TILE];
+    for i in 0..TILE {
+        row.fill(0.0);
+        if i < src.nrows() {
+            for (dst, &s) in row.iter_mut().zip(src.row(i)) {
+                *dst = s;
+            }
+        }
+        ctx.load512_slice(&row, ZRow::tile_row(4, tile, i));
+    }
+}
+
+/// See [`Amx::store_tile`].
+#[track_caller]
+pub(crate) fn store_tile(
+    ctx: &mut (impl Amx + ?Sized),
+    tile: usize,
+    dst: &mut ArrayViewMut2<'_, f32>,
+) {
+    check_tile(dst.dim(), tile);
+    let mut row = [0f32; TILE];
+    for (i, mut dst) in dst.rows_mut().into_iter().enumerate() {
+        ctx.store512_slice(&mut row, ZRow::tile_row(4, tile, i));
+        for (d, &s) in dst.iter_mut().zip(&row) {
+            *d = s;
+        }
+    }
+}
+
+#[track_caller]
+fn check_tile((rows, cols): (usize, usize), tile: usize) {
+    assert!(tile < 4, "`tile` is out of range");
+    assert!(
+        rows <= TILE && cols <= TILE,
+        "the array is larger than a tile"
+    );
+}
@@ -0,0 +1,114 @@
+//! Look-up-table-based activation functions
+use crate::{
+    Amx, F32, Index4, Normal, Reverse, X32, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, fma32_vector},
+};
+
+/// A piecewise quadratic approximation of a function with 16 segments,
+/// evaluated by `genlut`.
+///
+/// Segment `0` is `(-∞, -limit)`, segment `15` is `[limit, ∞)`, and the
+/// segments in between divide `[-limit, limit)` uniformly. The outermost
+/// segments are constant.
+struct PiecewiseQuadratic {
+    /// The lower edge of each segment
+    edges: [f32; F32_LANES],
+    /// `coefs[k][s]` is the coefficient of `x^k` in segment `s`
+    coefs: [[f32; F32_LANES]; 3],
+}
+
+impl PiecewiseQuadratic {
+    fn new(f: impl Fn(f64) -> f64, limit: f64) -> Self {
+        let width = 2.0 * limit / (F32_LANES - 2) as f64;
+        let edge = |s: usize| -limit + (s - 1) as f64 * width;
+
+        let edges = std::array::from_fn(|s| {
+            if s == 0 {
+                f32::NEG_INFINITY
+            } else {
+                edge(s) as f32
+            }
+        });
+        // `[c0, c1, c2]` of each segment
+        let segments: [[f64; 3]; F32_LANES] = std::array::from_fn(|s| {
+            if s == 0 {
+                return [f(-limit), 0.0, 0.0];
+            } else if s == F32_LANES - 1 {
+                return [f(limit), 0.0, 0.0];
+            }
+            // Interpolate `f` at both ends and the midpoint
+            let (x0, x2) = (edge(s), edge(s + 1));
+            let x1 = (x0 + x2) / 2.0;
+            let (y0, y1, y2) = (f(x0), f(x1), f(x2));
+            // Newton's divided differences
+            let d01 = (y1 - y0) / (x1 - x0);
+            let d12 = (y2 - y1) / (x2 - x1);
+            let c2 = (d12 - d01) / (x2 - x0);
+            [y0 - d01 * x0 + c2 * x0 * x1, d01 - c2 * (x0 + x1), c2]
+        });
+        let coefs = std::array::from_fn(|k| std::array::from_fn(|s| segments[s][k] as f32));
+        Self { edges, coefs }
+    }
+
+    /// Apply the approximation to `data` in place.
+    ///
+    /// This overwrites the entire contents of `x`, `y[0..3]`, and `z[0]`.
+    fn apply(&self, ctx: &mut (impl Amx + ?Sized), data: &mut [f32]) {
+        const ONES: [f32; F32_LANES] = [1.0; F32_LANES];
+        // Safety: Reading 64 bytes from each `[f32; 16]`
+        unsafe {
+            ctx.load512(ONES.as_ptr(), XRow(2));
+            ctx.load512(self.edges.as_ptr(), XRow(3));
+            for (k, coefs) in self.coefs.iter().enumerate() {
+                ctx.load512(coefs.as_ptr(), XRow(4 + k));
+            }
+        }
+
+        for chunk in data.chunks_mut(F32_LANES) {
+            let mut v = [0f32; F32_LANES];
+            v[..chunk.len()].copy_from_slice(chunk);
+            let v2 = v.map(|x| x * x);
+            // Safety: Reading 64 bytes from each `[f32; 16]`
+            unsafe {
+                ctx.load512(v.as_ptr(), XRow(0));
+                ctx.load512(v2.as_ptr(), XRow(1));
+            }
+
+            // Find the segment of each element, and look up its coefficients
+            ctx.lut(XBytes(0), XRow(3), XRow(7), (Reverse, Index4, F32));
+            for k in 0..3 {
+                ctx.lut(XBytes(7 * 64), XRow(4 + k), YRow(k), (Normal, Index4, X32));
+            }
+
+            // c0 + c1 * v + c2 * v^2
+            fma32_vector(ctx, XBytes(2 * 64), YBytes(0), ZRow(0), false, false);
+            fma32_vector(ctx, XBytes(0), YBytes(64), ZRow(0), true, false);
+            fma32_vector(ctx, XBytes(64), YBytes(2 * 64), ZRow(0), true, false);
+
+            // Safety: Writing 64 bytes to `[f32; 16]`
+            unsafe { ctx.store512(v.as_mut_ptr(), ZRow(0)) };
+            chunk.copy_from_slice(&v[..chunk.len()]);
+        }
+    }
+}
+
+/// Apply the logistic sigmoid function `1 / (1 + exp(-x))` to `data` in
+/// place.
+///
+/// The function is approximated by 16 quadratic segments selected by
+/// `genlut`, with an absolute error below `2e-3`.
+///
+/// This overwrites the entire contents of `x`, `y[0..3]`, and `z[0]`.
+pub fn sigmoid_f32(ctx: &mut (impl Amx + ?Sized), data: &mut [f32]) {
+    PiecewiseQuadratic::new(|x| 1.0 / (1.0 + (-x).exp()), 8.0).apply(ctx, data);
+}
+
+/// Apply the hyperbolic tangent function to `data` in place.
+///
+/// The function is approximated by 16 quadratic segments selected by
+/// `genlut`, with an absolute error below `4e-3`.
+///
+/// This overwrites the entire contents of `x`, `y[0..3]`, and `z[0]`.
+pub fn tanh_f32(ctx: &mut (impl Amx + ?Sized), data: &mut [f32]) {
+    PiecewiseQuadratic::new(f64::tanh, 4.0).apply(ctx, data);
+}
@@ -0,0 +1,120 @@
+//! Embedding bags
+use crate::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    linalg::{F32_LANES, MatMut, MatRef, fma32_vector},
+};
+
+/// The number of Z rows, i.e., the number of 16-column blocks accumulated at
+/// once
+const Z_ROWS: usize = 64;
+
+/// Specifies how the embeddings in a bag are reduced by [`embedding_bag`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BagMode {
+    /// The (optionally weighted) sum of the embeddings
+    Sum,
+    /// The mean of the embeddings
+    Mean,
+}
+
+/// Reduce bags of embeddings gathered from `table` (`V`×`D`), like
+/// `torch.nn.EmbeddingBag`.
+///
+/// Bag `b` consists of the rows `indices[offsets[b]..offsets[b + 1]]` of
+/// `table` (the last bag extends to the end of `indices`), and its reduction
+/// is written to row `b` of `out` (`offsets.len()`×`D`). Empty bags produce
+/// zeros. If `per_sample_weights` is given, each embedding is scaled by the
+/// weight at the same position in `indices` before summation.
+///
+/// Each embedding is accumulated by vector-mode `fma32` with its weight
+/// (`1 / len` for [`BagMode::Mean`]) broadcast in Y, keeping up to 1024
+/// columns of the bag in Z at once.
+///
+/// This overwrites `x[0]`, `y[0]`, and `z`.
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent, `offsets` is not
+/// non-decreasing or exceeds `indices.len()`, an index is out of range, or
+/// `per_sample_weights` is used with [`BagMode::Mean`].
+#[track_caller]
+pub fn embedding_bag(
+    ctx: &mut (impl Amx + ?Sized),
+    table: MatRef<'_, f32>,
+    indices: &[usize],
+    offsets: &[usize],
+    per_sample_weights: Option<&[f32]>,
+    mode: BagMode,
+    mut out: MatMut<'_, f32>,
+) {
+    let d = table.cols();
+    assert_eq!(
+        (out.rows(), out.cols()),
+        (offsets.len(), d),
+        "shape mismatch in `out`"
+    );
+    assert!(
+        offsets.windows(2).all(|w| w[0] <= w[1])
+            && offsets.last().is_none_or(|&o| o <= indices.len()),
+        "`offsets` must be non-decreasing and not exceed `indices.len()`"
+    );
+    if let Some(weights) = per_sample_weights {
+        assert_eq!(
+            weights.len(),
+            indices.len(),
+            "shape mismatch in `per_sample_weights`"
+        );
+        assert_eq!(
+            mode,
+            BagMode::Sum,
+            "`per_sample_weights` is only supported by `BagMode::Sum`"
+        );
+    }
+    assert!(
+        indices.iter().all(|&i| i < table.rows()),
+        "an index is out of range"
+    );
+
+    for (b, &start) in offsets.iter().enumerate() {
+        let end = offsets.get(b + 1).copied().unwrap_or(indices.len());
+        let bag = start..end;
+        let scale = match mode {
+            BagMode::Sum => 1.0,
+            BagMode::Mean => 1.0 / bag.len().max(1) as f32,
+        };
+
+        for c0 in (0..d).step_by(F32_LANES * Z_ROWS) {
+            let num_blocks = (d - c0).div_ceil(F32_LANES).min(Z_ROWS);
+
+            for (k, n) in bag.clone().enumerate() {
+                let weight = per_sample_weights.map_or(1.0, |w| w[n]) * scale;
+                let weight = [weight; F32_LANES];
+                // Safety: Reading 64 bytes from `[f32; 16]`
+                unsafe { ctx.load512(weight.as_ptr(), YRow(0)) };
+
+                let row = table.row(indices[n]);
+                for block in 0..num_blocks {
+                    let col = c0 + block * F32_LANES;
+                    let mut x = [0f32; F32_LANES];
+                    let len = (d - col).min(F32_LANES);
+                    x[..len].copy_from_slice(&row[col..col + len]);
+                    // Safety: Reading 64 bytes from `[f32; 16]`
+                    unsafe { ctx.load512(x.as_ptr(), XRow(0)) };
+                    fma32_vector(ctx, XBytes(0), YBytes(0), ZRow(block), k != 0, false);
+                }
+            }
+
+            let out = out.row_mut(b);
+            for block in 0..num_blocks {
+                let col = c0 + block * F32_LANES;
+                let len = (d - col).min(F32_LANES);
+                let mut z = [0f32; F32_LANES];
+                if !bag.is_empty() {
+                    // Safety: Writing 64 bytes to `[f32; 16]`
+                    unsafe { ctx.store512(z.as_mut_ptr(), ZRow(block)) };
+                }
+                out[col..col + len].copy_from_slice(&z[..len]);
+            }
+        }
+    }
+}
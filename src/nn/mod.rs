@@ -0,0 +1,5 @@
+//! Neural network kernels built on top of [`Amx`](crate::Amx)
+mod activation;
+mod embedding;
+mod rnn;
+pub use self::{activation::*, embedding::*, rnn::*};
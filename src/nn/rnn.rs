@@ -0,0 +1,191 @@
+//! Recurrent neural network cells
+use super::{sigmoid_f32, tanh_f32};
+use crate::{
+    Amx,
+    linalg::{MatMut, MatRef, sgemm},
+};
+
+/// The parameters of an LSTM cell with `I` inputs and `H` hidden units.
+///
+/// The gates are ordered as input, forget, cell, and output (`i, f, g, o`)
+/// along the columns, each occupying `H` consecutive columns.
+#[derive(Debug, Clone, Copy)]
+pub struct LstmWeights<'a> {
+    /// The input weights (`I`×`4H`)
+    pub w_x: MatRef<'a, f32>,
+    /// The recurrent weights (`H`×`4H`)
+    pub w_h: MatRef<'a, f32>,
+    /// The bias (`4H`)
+    pub bias: &'a [f32],
+}
+
+/// The parameters of a GRU cell with `I` inputs and `H` hidden units.
+///
+/// The gates are ordered as reset, update, and new (`r, z, n`) along the
+/// columns, each occupying `H` consecutive columns.
+#[derive(Debug, Clone, Copy)]
+pub struct GruWeights<'a> {
+    /// The input weights (`I`×`3H`)
+    pub w_x: MatRef<'a, f32>,
+    /// The recurrent weights (`H`×`3H`)
+    pub w_h: MatRef<'a, f32>,
+    /// The bias added to the input projection (`3H`)
+    pub bias_x: &'a [f32],
+    /// The bias added to the recurrent projection (`3H`)
+    pub bias_h: &'a [f32],
+}
+
+/// Calculate `out = a * w + bias` (or `out += a * w` if `bias` is `None`),
+/// where `out` is a contiguous `a.rows()`×`w.cols()` matrix.
+fn project(
+    ctx: &mut (impl Amx + ?Sized),
+    a: MatRef<'_, f32>,
+    w: MatRef<'_, f32>,
+    bias: Option<&[f32]>,
+    out: &mut [f32],
+) {
+    let (rows, cols) = (a.rows(), w.cols());
+    if let Some(bias) = bias {
+        for row in out.chunks_exact_mut(cols) {
+            row.copy_from_slice(bias);
+        }
+    }
+    sgemm(ctx, a, w, MatMut::new(out, rows, cols), true);
+}
+
+/// Advance a batch of `B` LSTM cells by one time step, updating the hidden
+/// state `h` and the cell state `c` (both `B`×`H`) from the input `x`
+/// (`B`×`I`):
+///
+/// ```text
+/// [i, f, g, o] = [σ, σ, tanh, σ](x * w_x + h * w_h + bias)
+/// c' = f ⊙ c + i ⊙ g
+/// h' = o ⊙ tanh(c')
+/// ```
+///
+/// The gate matrix multiplications are calculated by [`sgemm`], and the
+/// activations by [`sigmoid_f32`] and [`tanh_f32`].
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent.
+pub fn lstm_step(
+    ctx: &mut (impl Amx + ?Sized),
+    weights: &LstmWeights<'_>,
+    x: MatRef<'_, f32>,
+    mut h: MatMut<'_, f32>,
+    mut c: MatMut<'_, f32>,
+) {
+    let (batch, hidden) = (h.rows(), h.cols());
+    let gates = 4 * hidden;
+    assert_eq!(x.rows(), batch, "shape mismatch in `x`");
+    assert_eq!(
+        (c.rows(), c.cols()),
+        (batch, hidden),
+        "shape mismatch in `c`"
+    );
+    assert_eq!(
+        (weights.w_x.rows(), weights.w_x.cols()),
+        (x.cols(), gates),
+        "shape mismatch in `w_x`"
+    );
+    assert_eq!(
+        (weights.w_h.rows(), weights.w_h.cols()),
+        (hidden, gates),
+        "shape mismatch in `w_h`"
+    );
+    assert_eq!(weights.bias.len(), gates, "shape mismatch in `bias`");
+
+    let mut g = vec![0f32; batch * gates];
+    project(ctx, x, weights.w_x, Some(weights.bias), &mut g);
+    project(ctx, h.as_ref(), weights.w_h, None, &mut g);
+
+    for row in g.chunks_exact_mut(gates) {
+        let (ifg, o) = row.split_at_mut(3 * hidden);
+        let (i_f, g_) = ifg.split_at_mut(2 * hidden);
+        sigmoid_f32(ctx, i_f);
+        tanh_f32(ctx, g_);
+        sigmoid_f32(ctx, o);
+    }
+
+    // `c' = f * c + i * g`, staged in the `i` columns for the final `tanh`
+    for (b, row) in g.chunks_exact_mut(gates).enumerate() {
+        let c_row = c.row_mut(b);
+        for j in 0..hidden {
+            c_row[j] = row[hidden + j] * c_row[j] + row[j] * row[2 * hidden + j];
+            row[j] = c_row[j];
+        }
+        tanh_f32(ctx, &mut row[..hidden]);
+        let h_row = h.row_mut(b);
+        for j in 0..hidden {
+            h_row[j] = row[3 * hidden + j] * row[j];
+        }
+    }
+}
+
+/// Advance a batch of `B` GRU cells by one time step, updating the hidden
+/// state `h` (`B`×`H`) from the input `x` (`B`×`I`):
+///
+/// ```text
+/// [xr, xz, xn] = x * w_x + bias_x
+/// [hr, hz, hn] = h * w_h + bias_h
+/// r = σ(xr + hr), z = σ(xz + hz)
+/// n = tanh(xn + r ⊙ hn)
+/// h' = (1 - z) ⊙ n + z ⊙ h
+/// ```
+///
+/// The gate matrix multiplications are calculated by [`sgemm`], and the
+/// activations by [`sigmoid_f32`] and [`tanh_f32`].
+///
+/// # Panics
+///
+/// Panics if the dimensions are inconsistent.
+pub fn gru_step(
+    ctx: &mut (impl Amx + ?Sized),
+    weights: &GruWeights<'_>,
+    x: MatRef<'_, f32>,
+    mut h: MatMut<'_, f32>,
+) {
+    let (batch, hidden) = (h.rows(), h.cols());
+    let gates = 3 * hidden;
+    assert_eq!(x.rows(), batch, "shape mismatch in `x`");
+    assert_eq!(
+        (weights.w_x.rows(), weights.w_x.cols()),
+        (x.cols(), gates),
+        "shape mismatch in `w_x`"
+    );
+    assert_eq!(
+        (weights.w_h.rows(), weights.w_h.cols()),
+        (hidden, gates),
+        "shape mismatch in `w_h`"
+    );
+    assert_eq!(weights.bias_x.len(), gates, "shape mismatch in `bias_x`");
+    assert_eq!(weights.bias_h.len(), gates, "shape mismatch in `bias_h`");
+
+    let mut gx = vec![0f32; batch * gates];
+    let mut gh = vec![0f32; batch * gates];
+    project(ctx, x, weights.w_x, Some(weights.bias_x), &mut gx);
+    project(ctx, h.as_ref(), weights.w_h, Some(weights.bias_h), &mut gh);
+
+    for (b, (gx, gh)) in gx
+        .chunks_exact_mut(gates)
+        .zip(gh.chunks_exact(gates))
+        .enumerate()
+    {
+        let (rz, n) = gx.split_at_mut(2 * hidden);
+        for (rz, &hrz) in rz.iter_mut().zip(&gh[..2 * hidden]) {
+            *rz += hrz;
+        }
+        sigmoid_f32(ctx, rz);
+        for j in 0..hidden {
+            n[j] += rz[j] * gh[2 * hidden + j];
+        }
+        tanh_f32(ctx, n);
+
+        let h_row = h.row_mut(b);
+        for j in 0..hidden {
+            let z = rz[hidden + j];
+            h_row[j] = (1.0 - z) * n[j] + z * h_row[j];
+        }
+    }
+}
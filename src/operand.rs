@@ -0,0 +1,543 @@
+//! Bitfield builders for the operands of the computational instructions
+//!
+//! The high-level methods of [`Amx`](crate::Amx) encode their operands with
+//! these types, and they can also be used to drive the low-level
+//! [`AmxOps`](crate::AmxOps) methods directly without shifting bits by
+//! hand:
+//!
+//! ```rust
+//! use amx::{Amx, AmxOps, CheckArg, Fma32Operand, XBytes, XRow, YRow, ZRow};
+//!
+//! let mut ctx = amx::AmxEmuCtx::default();
+//! unsafe {
+//!     ctx.load512([2.0f32; 16].as_ptr(), XRow(1));
+//!     ctx.load512([3.0f32; 16].as_ptr(), YRow(0));
+//! }
+//!
+//! let operand = Fma32Operand::new()
+//!     .x_offset(Some(XBytes(64)))
+//!     .z_row(ZRow(1))
+//!     .accumulate(false);
+//! operand.check().unwrap();
+//! ctx.fma32(operand.encode());
+//! assert_eq!(ctx.read_z_f32()[1], [6.0; 16]);
+//!
+//! // Decoding recovers the fields for debugging
+//! assert_eq!(Fma32Operand::decode(operand.encode()), operand);
+//! ```
+//!
+//! The setters don't check the register offsets and rows, so that the
+//! builders can be used in hot loops. [`CheckArg::check`] validates them,
+//! and [`encode`](Fma32Operand::encode) asserts their validity in debug
+//! builds. The bit layouts are described in the documentation of each
+//! type.
+use crate::{
+    AmxArgError, CheckArg, MatFpOp, MatIntOp, VecFpAluOp, VecFpTy, VecIntOp, XBytes, YBytes, ZRow,
+    vecfp,
+};
+
+/// Encode the register fields shared by every operand: the Y byte offset
+/// (bits 0–8), the X byte offset (bits 10–18), and the Z row (bits 20–25).
+#[inline(always)]
+const fn address(x_offset: XBytes, y_offset: YBytes, z_row: ZRow) -> u64 {
+    debug_assert!(x_offset.0 < 0x200);
+    debug_assert!(y_offset.0 < 0x200);
+    debug_assert!(z_row.0 < 64);
+    (y_offset.0 as u64 & 0x1ff)
+        | ((x_offset.0 as u64 & 0x1ff) << 10)
+        | ((z_row.0 as u64 & 0x3f) << 20)
+}
+
+/// Decode the fields encoded by [`address`].
+const fn decode_address(operand: u64) -> (XBytes, YBytes, ZRow) {
+    (
+        XBytes(((operand >> 10) & 0x1ff) as usize),
+        YBytes((operand & 0x1ff) as usize),
+        ZRow(((operand >> 20) & 0x3f) as usize),
+    )
+}
+
+/// Define the operand builders of the `fma`/`fms`/`mac16` instructions.
+/// Each entry lists the optional fields (`widen` and `shift`) supported by
+/// the instruction.
+macro_rules! mul_operand {
+    (@ty widen) => { bool };
+    (@ty shift) => { u32 };
+    (@default widen) => { false };
+    (@default shift) => { 0 };
+    (@encode widen, $value:expr) => { (($value as u64) << 62) };
+    (@encode shift, $value:expr) => { (($value as u64) << 55) };
+    (@decode widen, $operand:expr) => { $operand & (1 << 62) != 0 };
+    (@decode shift, $operand:expr) => { (($operand >> 55) & 0x1f) as u32 };
+    (@setter widen) => {
+        /// Set whether the output lanes are twice as wide as the input
+        /// lanes.
+        pub const fn widen(mut self, widen: bool) -> Self {
+            self.widen = widen;
+            self
+        }
+    };
+    (@setter shift) => {
+        /// Set the right shift applied to the products.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `shift` is not in range `0..32`.
+        #[track_caller]
+        pub const fn shift(mut self, shift: u32) -> Self {
+            assert!(shift < 32, "`shift` must be in range `0..32`");
+            self.shift = shift;
+            self
+        }
+    };
+    ($(
+        $(#[$meta:meta])*
+        struct $name:ident { $($extra:ident),* }
+    )*) => {$(
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        pub struct $name {
+            pub(crate) x_offset: Option<XBytes>,
+            pub(crate) y_offset: Option<YBytes>,
+            pub(crate) z_row: ZRow,
+            pub(crate) accumulate: bool,
+            pub(crate) vector: bool,
+            $(pub(crate) $extra: mul_operand!(@ty $extra),)*
+        }
+
+        impl $name {
+            /// Construct an operand calculating the outer product of
+            /// `x[0..]` and `y[0..]` and adding it to Z starting from row 0.
+            pub const fn new() -> Self {
+                Self {
+                    x_offset: Some(XBytes(0)),
+                    y_offset: Some(YBytes(0)),
+                    z_row: ZRow(0),
+                    accumulate: true,
+                    vector: false,
+                    $($extra: mul_operand!(@default $extra),)*
+                }
+            }
+
+            /// Set the byte offset of the X input. If `None`, X is excluded
+            /// from the operation (not performing multiplication).
+            pub const fn x_offset(mut self, x_offset: Option<XBytes>) -> Self {
+                self.x_offset = x_offset;
+                self
+            }
+
+            /// Set the byte offset of the Y input. If `None`, Y is excluded
+            /// from the operation (not performing multiplication).
+            pub const fn y_offset(mut self, y_offset: Option<YBytes>) -> Self {
+                self.y_offset = y_offset;
+                self
+            }
+
+            /// Set the output row of Z (in the vector mode) or the row
+            /// selecting the output tile (in the outer product mode).
+            pub const fn z_row(mut self, z_row: ZRow) -> Self {
+                self.z_row = z_row;
+                self
+            }
+
+            /// Set whether the products are added to the existing contents
+            /// of Z. If `false`, Z is treated as zero.
+            pub const fn accumulate(mut self, accumulate: bool) -> Self {
+                self.accumulate = accumulate;
+                self
+            }
+
+            /// Set whether X and Y are multiplied elementwise (the vector
+            /// mode) instead of calculating their outer product.
+            pub const fn vector(mut self, vector: bool) -> Self {
+                self.vector = vector;
+                self
+            }
+
+            $(mul_operand!(@setter $extra);)*
+
+            /// Encode the operand.
+            ///
+            /// Debug builds panic if any of the fields is out of range (see
+            /// [`CheckArg::check`]).
+            #[inline(always)]
+            pub const fn encode(&self) -> u64 {
+                let x_offset = match self.x_offset {
+                    Some(x_offset) => x_offset,
+                    None => XBytes(0),
+                };
+                let y_offset = match self.y_offset {
+                    Some(y_offset) => y_offset,
+                    None => YBytes(0),
+                };
+                address(x_offset, y_offset, self.z_row)
+                    | ((!self.accumulate as u64) << 27)
+                    | ((self.x_offset.is_none() as u64) << 28)
+                    | ((self.y_offset.is_none() as u64) << 29)
+                    | ((self.vector as u64) << 63)
+                    $(| mul_operand!(@encode $extra, self.$extra))*
+            }
+
+            /// Decode `operand`. The bits not covered by the fields are
+            /// ignored.
+            pub const fn decode(operand: u64) -> Self {
+                let (x_offset, y_offset, z_row) = decode_address(operand);
+                Self {
+                    x_offset: if operand & (1 << 28) != 0 { None } else { Some(x_offset) },
+                    y_offset: if operand & (1 << 29) != 0 { None } else { Some(y_offset) },
+                    z_row,
+                    accumulate: operand & (1 << 27) == 0,
+                    vector: operand & (1 << 63) != 0,
+                    $($extra: mul_operand!(@decode $extra, operand),)*
+                }
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl CheckArg for $name {
+            #[inline]
+            fn check(&self) -> Result<(), AmxArgError> {
+                self.x_offset.check()?;
+                self.y_offset.check()?;
+                self.z_row.check()
+            }
+        }
+    )*};
+}
+
+mul_operand! {
+    /// The operand of `mac16` (16-bit integer multiply-accumulate)
+    ///
+    /// | Bits  | Field                                            |
+    /// |-------|--------------------------------------------------|
+    /// | 0–8   | Y byte offset                                    |
+    /// | 10–18 | X byte offset                                    |
+    /// | 20–25 | Z row                                            |
+    /// | 27    | Treat Z as zero (i.e., don't accumulate)         |
+    /// | 28    | Exclude X                                        |
+    /// | 29    | Exclude Y                                        |
+    /// | 55–59 | Right shift applied to the products              |
+    /// | 62    | Widen the output to 32 bits                      |
+    /// | 63    | Vector mode                                      |
+    struct Mac16Operand { widen, shift }
+
+    /// The operand of `fma16` and `fms16` (`f16` multiply-add)
+    ///
+    /// The layout is that of [`Mac16Operand`] without the shift. If widened,
+    /// the output is `f32`.
+    struct Fma16Operand { widen }
+
+    /// The operand of `fma32` and `fms32` (`f32` multiply-add)
+    ///
+    /// The layout is that of [`Mac16Operand`] without the shift and the
+    /// widening mode.
+    struct Fma32Operand {}
+
+    /// The operand of `fma64` and `fms64` (`f64` multiply-add)
+    ///
+    /// The layout is that of [`Mac16Operand`] without the shift and the
+    /// widening mode.
+    struct Fma64Operand {}
+}
+
+/// Define the register setters and the validation of an operand builder
+/// holding an operation and the register fields.
+macro_rules! reg_operand {
+    ($($name:ident),*) => {$(
+        impl $name {
+            /// Set the byte offset of the X input.
+            pub const fn x_offset(mut self, x_offset: XBytes) -> Self {
+                self.x_offset = x_offset;
+                self
+            }
+
+            /// Set the byte offset of the Y input.
+            pub const fn y_offset(mut self, y_offset: YBytes) -> Self {
+                self.y_offset = y_offset;
+                self
+            }
+
+            /// Set the row of Z. For outer products, this selects the
+            /// output tile.
+            pub const fn z_row(mut self, z_row: ZRow) -> Self {
+                self.z_row = z_row;
+                self
+            }
+        }
+
+        impl CheckArg for $name {
+            #[inline]
+            fn check(&self) -> Result<(), AmxArgError> {
+                self.x_offset.check()?;
+                self.y_offset.check()?;
+                self.z_row.check()
+            }
+        }
+    )*};
+}
+
+/// The operand of `vecint`, consisting of a [`VecIntOp`] and the register
+/// fields
+///
+/// | Bits  | Field                                                        |
+/// |-------|--------------------------------------------------------------|
+/// | 0–8   | Y byte offset                                                |
+/// | 10–18 | X byte offset                                                |
+/// | 20–25 | Z row                                                        |
+/// | 27    | Treat Z as zero (i.e., don't accumulate)                     |
+/// | 42–45 | Lane width: `4` = 32 bits, `10` = 8 bits, otherwise 16 bits  |
+/// | 47–52 | ALU mode: `0` = `z + p`, `1` = `z - p`                       |
+/// | 53    | Saturate instead of wrapping around                          |
+/// | 58–62 | Right shift applied to the product `p = x * y`               |
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VecIntOperand {
+    pub(crate) op: VecIntOp,
+    pub(crate) x_offset: XBytes,
+    pub(crate) y_offset: YBytes,
+    pub(crate) z_row: ZRow,
+}
+
+impl VecIntOperand {
+    /// Construct an operand performing `op` on `x[0..]`, `y[0..]`, and
+    /// `z[0]`.
+    pub const fn new(op: VecIntOp) -> Self {
+        Self {
+            op,
+            x_offset: XBytes(0),
+            y_offset: YBytes(0),
+            z_row: ZRow(0),
+        }
+    }
+
+    /// Get the operation.
+    pub const fn op(&self) -> VecIntOp {
+        self.op
+    }
+
+    /// Encode the operand.
+    ///
+    /// Debug builds panic if any of the fields is out of range (see
+    /// [`CheckArg::check`]).
+    #[inline(always)]
+    pub const fn encode(&self) -> u64 {
+        address(self.x_offset, self.y_offset, self.z_row) | self.op.bits()
+    }
+
+    /// Decode `operand`. Returns `None` if the ALU mode is not supported by
+    /// [`VecIntOp`]. The bits not covered by the fields are ignored.
+    pub const fn decode(operand: u64) -> Option<Self> {
+        let Some(op) = VecIntOp::from_bits(operand) else {
+            return None;
+        };
+        let (x_offset, y_offset, z_row) = decode_address(operand);
+        Some(Self {
+            op,
+            x_offset,
+            y_offset,
+            z_row,
+        })
+    }
+}
+
+/// The operand of `vecfp`, consisting of a lane type, an ALU operation, and
+/// the register fields
+///
+/// | Bits  | Field                                                 |
+/// |-------|-------------------------------------------------------|
+/// | 0–8   | Y byte offset                                         |
+/// | 10–18 | X byte offset                                         |
+/// | 20–25 | Z row                                                 |
+/// | 29    | Treat Y as ones                                       |
+/// | 42–45 | Lane width: `4` = `f32`, `7` = `f64`, otherwise `f16` |
+/// | 47–52 | ALU mode (see [`VecFpAluOp`])                         |
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VecFpOperand {
+    pub(crate) ty: VecFpTy,
+    pub(crate) op: VecFpAluOp,
+    pub(crate) x_offset: XBytes,
+    pub(crate) y_offset: YBytes,
+    pub(crate) z_row: ZRow,
+}
+
+impl VecFpOperand {
+    /// Construct an operand performing `op` on `x[0..]`, `y[0..]`, and
+    /// `z[0]`.
+    pub const fn new(ty: VecFpTy, op: VecFpAluOp) -> Self {
+        Self {
+            ty,
+            op,
+            x_offset: XBytes(0),
+            y_offset: YBytes(0),
+            z_row: ZRow(0),
+        }
+    }
+
+    /// Get the lane type.
+    pub const fn ty(&self) -> VecFpTy {
+        self.ty
+    }
+
+    /// Get the ALU operation.
+    pub const fn op(&self) -> VecFpAluOp {
+        self.op
+    }
+
+    /// Encode the operand.
+    ///
+    /// Debug builds panic if any of the fields is out of range (see
+    /// [`CheckArg::check`]).
+    #[inline(always)]
+    pub const fn encode(&self) -> u64 {
+        address(self.x_offset, self.y_offset, self.z_row) | vecfp::bits(self.ty, self.op)
+    }
+
+    /// Decode `operand`. Returns `None` if the ALU mode is not supported by
+    /// [`VecFpAluOp`]. The bits not covered by the fields are ignored.
+    pub const fn decode(operand: u64) -> Option<Self> {
+        let Some((ty, op)) = vecfp::from_bits(operand) else {
+            return None;
+        };
+        let (x_offset, y_offset, z_row) = decode_address(operand);
+        Some(Self {
+            ty,
+            op,
+            x_offset,
+            y_offset,
+            z_row,
+        })
+    }
+}
+
+/// The operand of `matint`, consisting of a [`MatIntOp`] and the register
+/// fields
+///
+/// | Bits  | Field                                                         |
+/// |-------|---------------------------------------------------------------|
+/// | 0–8   | Y byte offset                                                 |
+/// | 10–18 | X byte offset                                                 |
+/// | 20–25 | Z row                                                         |
+/// | 27    | Treat Z as zero (i.e., don't accumulate)                      |
+/// | 32–37 | Number of enabled X lanes (`0` = all)                         |
+/// | 42–45 | Lane type                                                     |
+/// | 47–52 | ALU mode: `0` = `z + x * y`, `1` = `z - x * y`                |
+/// | 53–58 | Number of enabled Y lanes (`0` = all)                         |
+/// | 62    | Widen the output                                              |
+///
+/// The lane type is `0` = `i16`, `1` = `u16`, `2` = `i8`, `3` = `u8`.
+///
+/// The lane types and ALU modes follow the reverse-engineered description
+/// of `matint` in <https://github.com/corsix/amx/blob/main/matint.md>.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MatIntOperand {
+    pub(crate) op: MatIntOp,
+    pub(crate) x_offset: XBytes,
+    pub(crate) y_offset: YBytes,
+    pub(crate) z_row: ZRow,
+}
+
+impl MatIntOperand {
+    /// Construct an operand performing `op` on `x[0..]` and `y[0..]`,
+    /// writing to the first tile of Z.
+    pub const fn new(op: MatIntOp) -> Self {
+        Self {
+            op,
+            x_offset: XBytes(0),
+            y_offset: YBytes(0),
+            z_row: ZRow(0),
+        }
+    }
+
+    /// Get the operation.
+    pub const fn op(&self) -> MatIntOp {
+        self.op
+    }
+
+    /// Encode the operand.
+    ///
+    /// Debug builds panic if any of the fields is out of range (see
+    /// [`CheckArg::check`]).
+    #[inline(always)]
+    pub const fn encode(&self) -> u64 {
+        address(self.x_offset, self.y_offset, self.z_row) | self.op.bits()
+    }
+
+    /// Decode `operand`. Returns `None` if the lane type or the ALU mode is
+    /// not supported by [`MatIntOp`]. The bits not covered by the fields are
+    /// ignored.
+    pub const fn decode(operand: u64) -> Option<Self> {
+        let Some(op) = MatIntOp::from_bits(operand) else {
+            return None;
+        };
+        let (x_offset, y_offset, z_row) = decode_address(operand);
+        Some(Self {
+            op,
+            x_offset,
+            y_offset,
+            z_row,
+        })
+    }
+}
+
+/// The operand of `matfp`, consisting of a [`MatFpOp`] and the register
+/// fields
+///
+/// The layout is that of [`MatIntOperand`], except that the lane type is
+/// `4` = `f32`, `7` = `f64`, otherwise `f16`. Since M2, `1` = `bf16`. See
+/// <https://github.com/corsix/amx/blob/main/matfp.md>.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MatFpOperand {
+    pub(crate) op: MatFpOp,
+    pub(crate) x_offset: XBytes,
+    pub(crate) y_offset: YBytes,
+    pub(crate) z_row: ZRow,
+}
+
+impl MatFpOperand {
+    /// Construct an operand performing `op` on `x[0..]` and `y[0..]`,
+    /// writing to the first tile of Z.
+    pub const fn new(op: MatFpOp) -> Self {
+        Self {
+            op,
+            x_offset: XBytes(0),
+            y_offset: YBytes(0),
+            z_row: ZRow(0),
+        }
+    }
+
+    /// Get the operation.
+    pub const fn op(&self) -> MatFpOp {
+        self.op
+    }
+
+    /// Encode the operand.
+    ///
+    /// Debug builds panic if any of the fields is out of range (see
+    /// [`CheckArg::check`]).
+    #[inline(always)]
+    pub const fn encode(&self) -> u64 {
+        address(self.x_offset, self.y_offset, self.z_row) | self.op.bits()
+    }
+
+    /// Decode `operand`. Returns `None` if the lane type or the ALU mode is
+    /// not supported by [`MatFpOp`] (e.g., `bf16` without the `amx2`
+    /// feature). The bits not covered by the fields are ignored.
+    pub const fn decode(operand: u64) -> Option<Self> {
+        let Some(op) = MatFpOp::from_bits(operand) else {
+            return None;
+        };
+        let (x_offset, y_offset, z_row) = decode_address(operand);
+        Some(Self {
+            op,
+            x_offset,
+            y_offset,
+            z_row,
+        })
+    }
+}
+
+reg_operand!(VecIntOperand, VecFpOperand, MatIntOperand, MatFpOperand);
@@ -1,3 +1,5 @@
+use crate::AmxVersion;
+
 /// Exposes all AMX instructions except `set` and `clr` as trait methods.
 ///
 /// Load and store operations receive a pointer by the additional parameter to
@@ -25,74 +27,118 @@ pub unsafe trait AmxOps {
     fn matint(&mut self, x: u64);
     fn matfp(&mut self, x: u64);
     fn genlut(&mut self, x: u64);
+
+    /// Get the AMX revision whose operations are supported. The default
+    /// implementation returns [`AmxVersion::M1`].
+    fn version(&self) -> AmxVersion {
+        AmxVersion::M1
+    }
+}
+
+/// Implement [`AmxOps`] by forwarding the calls to `**self`.
+macro_rules! forward_amx_ops {
+    () => {
+        #[inline(always)]
+        unsafe fn ldx(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).ldx(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn ldy(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).ldy(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn stx(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).stx(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn sty(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).sty(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn ldz(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).ldz(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn stz(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).stz(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn ldzi(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).ldzi(x, ptr) }
+        }
+        #[inline(always)]
+        unsafe fn stzi(&mut self, x: u64, ptr: *mut ()) {
+            unsafe { (**self).stzi(x, ptr) }
+        }
+        #[inline(always)]
+        fn extrx(&mut self, x: u64) {
+            (**self).extrx(x)
+        }
+        #[inline(always)]
+        fn extry(&mut self, x: u64) {
+            (**self).extry(x)
+        }
+        #[inline(always)]
+        fn fma64(&mut self, x: u64) {
+            (**self).fma64(x)
+        }
+        #[inline(always)]
+        fn fms64(&mut self, x: u64) {
+            (**self).fms64(x)
+        }
+        #[inline(always)]
+        fn fma32(&mut self, x: u64) {
+            (**self).fma32(x)
+        }
+        #[inline(always)]
+        fn fms32(&mut self, x: u64) {
+            (**self).fms32(x)
+        }
+        #[inline(always)]
+        fn mac16(&mut self, x: u64) {
+            (**self).mac16(x)
+        }
+        #[inline(always)]
+        fn fma16(&mut self, x: u64) {
+            (**self).fma16(x)
+        }
+        #[inline(always)]
+        fn fms16(&mut self, x: u64) {
+            (**self).fms16(x)
+        }
+        #[inline(always)]
+        fn vecint(&mut self, x: u64) {
+            (**self).vecint(x)
+        }
+        #[inline(always)]
+        fn vecfp(&mut self, x: u64) {
+            (**self).vecfp(x)
+        }
+        #[inline(always)]
+        fn matint(&mut self, x: u64) {
+            (**self).matint(x)
+        }
+        #[inline(always)]
+        fn matfp(&mut self, x: u64) {
+            (**self).matfp(x)
+        }
+        #[inline(always)]
+        fn genlut(&mut self, x: u64) {
+            (**self).genlut(x)
+        }
+        #[inline(always)]
+        fn version(&self) -> $crate::AmxVersion {
+            (**self).version()
+        }
+    };
 }
 
 // Safety: Just forwarding the calls
 unsafe impl<T: ?Sized + AmxOps> AmxOps for &'_ mut T {
-    unsafe fn ldx(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).ldx(x, ptr) }
-    }
-    unsafe fn ldy(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).ldy(x, ptr) }
-    }
-    unsafe fn stx(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).stx(x, ptr) }
-    }
-    unsafe fn sty(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).sty(x, ptr) }
-    }
-    unsafe fn ldz(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).ldz(x, ptr) }
-    }
-    unsafe fn stz(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).stz(x, ptr) }
-    }
-    unsafe fn ldzi(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).ldzi(x, ptr) }
-    }
-    unsafe fn stzi(&mut self, x: u64, ptr: *mut ()) {
-        unsafe { (**self).stzi(x, ptr) }
-    }
-    fn extrx(&mut self, x: u64) {
-        (**self).extrx(x)
-    }
-    fn extry(&mut self, x: u64) {
-        (**self).extry(x)
-    }
-    fn fma64(&mut self, x: u64) {
-        (**self).fma64(x)
-    }
-    fn fms64(&mut self, x: u64) {
-        (**self).fms64(x)
-    }
-    fn fma32(&mut self, x: u64) {
-        (**self).fma32(x)
-    }
-    fn fms32(&mut self, x: u64) {
-        (**self).fms32(x)
-    }
-    fn mac16(&mut self, x: u64) {
-        (**self).mac16(x)
-    }
-    fn fma16(&mut self, x: u64) {
-        (**self).fma16(x)
-    }
-    fn fms16(&mut self, x: u64) {
-        (**self).fms16(x)
-    }
-    fn vecint(&mut self, x: u64) {
-        (**self).vecint(x)
-    }
-    fn vecfp(&mut self, x: u64) {
-        (**self).vecfp(x)
-    }
-    fn matint(&mut self, x: u64) {
-        (**self).matint(x)
-    }
-    fn matfp(&mut self, x: u64) {
-        (**self).matfp(x)
-    }
-    fn genlut(&mut self, x: u64) {
-        (**self).genlut(x)
-    }
+    forward_amx_ops!();
+}
+
+// Safety: Just forwarding the calls
+unsafe impl<T: ?Sized + AmxOps> AmxOps for Box<T> {
+    forward_amx_ops!();
 }
@@ -0,0 +1,84 @@
+//! The table of outer product and vector multiply-add methods provided by
+//! [`Amx`](crate::Amx)
+//!
+//! Every variant shares the same operand encoding (see
+//! [`Mac16Operand`](crate::Mac16Operand)) and only differs in the
+//! instruction and whether the widening mode and the vector mode are used,
+//! so they are generated from a single table by `outer_product_methods!`.
+//! Outer product methods are named `outer_product_{input}_xy_to_z` if the
+//! output type is the same as the input type and
+//! `outer_product_{input}_xy_to_z_{output}` otherwise. Vector methods are
+//! named `{fma,mac}_vector_{input}[_{output}]` likewise.
+
+/// Expand to the outer product and vector methods of [`Amx`](crate::Amx).
+/// Each entry specifies the method's name, the names of its unchecked and
+/// fallible variants, the underlying instruction, and the operand builder
+/// with the widening and vector modes set.
+macro_rules! outer_product_methods {
+    ($(
+        $(#[$meta:meta])*
+        fn $name:ident / $name_unchecked:ident / $try_name:ident
+            => $op:ident($operand:expr);
+    )*) => {$(
+        $(#[$meta])*
+        ///
+        /// If `x_offset_bytes` and/or `y_offset_bytes` are `None`, the
+        /// respective registers will be excluded from the operation (not
+        /// performing multiplication).
+        #[inline(always)]
+        fn $name(
+            &mut self,
+            x_offset_bytes: Option<XBytes>,
+            y_offset_bytes: Option<YBytes>,
+            z_index: ZRow,
+            accumulate: bool,
+        ) {
+            let operand = $operand
+                .x_offset(x_offset_bytes)
+                .y_offset(y_offset_bytes)
+                .z_row(z_index)
+                .accumulate(accumulate);
+            self.$op(operand.encode());
+        }
+
+        #[doc = concat!("Like [`Self::", stringify!($name), "`], but without checking the")]
+        /// parameters. Both X and Y are always included in the operation.
+        ///
+        /// # Safety
+        ///
+        /// `x_offset_bytes` and `y_offset_bytes` must be in range `0..0x200`,
+        /// and `z_index` must be in range `0..64`.
+        #[inline(always)]
+        unsafe fn $name_unchecked(
+            &mut self,
+            x_offset_bytes: XBytes,
+            y_offset_bytes: YBytes,
+            z_index: ZRow,
+            accumulate: bool,
+        ) {
+            let operand = $operand
+                .x_offset(Some(x_offset_bytes))
+                .y_offset(Some(y_offset_bytes))
+                .z_row(z_index)
+                .accumulate(accumulate);
+            self.$op(operand.encode());
+        }
+
+        #[doc = concat!("Like [`Self::", stringify!($name), "`], but returns an error instead")]
+        /// of panicking if any of the parameters is out of range.
+        #[inline(always)]
+        fn $try_name(
+            &mut self,
+            x_offset_bytes: Option<XBytes>,
+            y_offset_bytes: Option<YBytes>,
+            z_index: ZRow,
+            accumulate: bool,
+        ) -> Result<(), AmxArgError> {
+            x_offset_bytes.check()?;
+            y_offset_bytes.check()?;
+            z_index.check()?;
+            self.$name(x_offset_bytes, y_offset_bytes, z_index, accumulate);
+            Ok(())
+        }
+    )*};
+}
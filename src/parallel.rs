@@ -0,0 +1,152 @@
+//! Data-parallel execution on multiple threads
+//!
+//! AMX is enabled per thread, so a data-parallel kernel needs one context
+//! per worker thread. The functions in this module spawn scoped worker
+//! threads, each owning an [`AmxFallbackCtx`] (the native context on Apple
+//! Silicon and the emulator elsewhere), and hand each worker a contiguous
+//! part of the work.
+use std::ops::Range;
+
+use crate::{
+    AmxFallbackCtx, NewAmxCtxError,
+    linalg::{self, MatMut, MatRef},
+};
+
+/// The granularity of the row bands of [`parallel_sgemm`], matching the
+/// block size of [`linalg::sgemm_scaled`]
+const SGEMM_BAND: usize = 32;
+
+/// Split `0..num_tiles` into `num_threads` contiguous ranges, call `f` for
+/// each range on a separate thread with its own context, and wait for them
+/// to finish.
+///
+/// The ranges differ in length by at most one. No thread is spawned for an
+/// empty range.
+///
+/// ```rust
+/// use amx::{Amx, XBytes, XRow, YBytes, ZRow};
+/// use std::sync::Mutex;
+///
+/// let sums = Mutex::new(vec![0.0f32; 10]);
+/// amx::parallel::parallel_for_tiles(3, 10, |ctx, tiles| {
+///     for tile in tiles {
+///         ctx.load512_slice(&[tile as f32; 16], XRow(0));
+///         ctx.fma_vector_f32(Some(XBytes(0)), None, ZRow(0), false);
+///         sums.lock().unwrap()[tile] = ctx.read_z_f32()[0][0];
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(*sums.lock().unwrap(), (0..10).map(|i| i as f32).collect::<Vec<_>>());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if a worker thread fails to construct its context by
+/// [`AmxFallbackCtx::try_new`]. The other workers still run their ranges.
+///
+/// # Panics
+///
+/// Panics if `num_threads` is zero or if `f` panics.
+#[track_caller]
+pub fn parallel_for_tiles<F>(
+    num_threads: usize,
+    num_tiles: usize,
+    f: F,
+) -> Result<(), NewAmxCtxError>
+where
+    F: Fn(&mut AmxFallbackCtx, Range<usize>) + Sync,
+{
+    assert!(num_threads > 0, "`num_threads` must be non-zero");
+    let ranges = (0..num_threads).map(|i| {
+        let start = num_tiles * i / num_threads;
+        let end = num_tiles * (i + 1) / num_threads;
+        start..end
+    });
+    spawn_workers(ranges.filter(|range| !range.is_empty()), f)
+}
+
+/// Calculate `C = A * B` (or `C += A * B` if `accumulate` is set) by
+/// [`linalg::sgemm`] on up to `num_threads` threads.
+///
+/// `C` and `A` are divided into bands of rows, whose heights are multiples of
+/// 32, and each thread computes one band.
+///
+/// ```rust
+/// use amx::linalg::{MatMut, MatRef};
+///
+/// let a: Vec<f32> = (0..100 * 3).map(|i| i as f32).collect();
+/// let b = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+/// let mut c = vec![0.0; 100 * 2];
+/// amx::parallel::parallel_sgemm(
+///     4,
+///     MatRef::new(&a, 100, 3),
+///     MatRef::new(&b, 3, 2),
+///     MatMut::new(&mut c, 100, 2),
+///     false,
+/// )
+/// .unwrap();
+/// assert_eq!(c[198..], [297.0 + 299.0, 298.0 + 299.0]);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if a worker thread fails to construct its context. See
+/// [`parallel_for_tiles`].
+///
+/// # Panics
+///
+/// Panics if `num_threads` is zero or the matrix dimensions are
+/// inconsistent.
+#[track_caller]
+pub fn parallel_sgemm(
+    num_threads: usize,
+    a: MatRef<'_, f32>,
+    b: MatRef<'_, f32>,
+    c: MatMut<'_, f32>,
+    accumulate: bool,
+) -> Result<(), NewAmxCtxError> {
+    assert!(num_threads > 0, "`num_threads` must be non-zero");
+    assert_eq!(a.rows(), c.rows(), "shape mismatch in `c`");
+    let band = c.rows().div_ceil(num_threads).next_multiple_of(SGEMM_BAND);
+
+    let mut bands = Vec::with_capacity(num_threads);
+    let (mut a, mut c) = (a, c);
+    while c.rows() > 0 {
+        let rows = band.min(c.rows());
+        let (a_band, a_rest) = a.split_at_row(rows);
+        let (c_band, c_rest) = c.split_at_row(rows);
+        bands.push((a_band, c_band));
+        (a, c) = (a_rest, c_rest);
+    }
+
+    spawn_workers(bands, |ctx, (a, c)| linalg::sgemm(ctx, a, b, c, accumulate))
+}
+
+/// Call `f` for each item of `work` on a separate thread with its own
+/// context.
+fn spawn_workers<T, F>(work: impl IntoIterator<Item = T>, f: F) -> Result<(), NewAmxCtxError>
+where
+    T: Send,
+    F: Fn(&mut AmxFallbackCtx, T) + Sync,
+{
+    let f = &f;
+    std::thread::scope(|s| {
+        let workers: Vec<_> = work
+            .into_iter()
+            .map(|item| {
+                s.spawn(move || {
+                    let mut ctx = AmxFallbackCtx::try_new()?;
+                    f(&mut ctx, item);
+                    Ok(())
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| match worker.join() {
+                Ok(result) => result,
+                Err(payload) => std::panic::resume_unwind(payload),
+            })
+            .fold(Ok(()), Result::and)
+    })
+}
@@ -0,0 +1,166 @@
+//! Software pipelining of load/compute streams
+//!
+//! A naïve kernel loop loads its operands for step `k` and immediately
+//! issues the computation that consumes them, stalling on the load latency
+//! every iteration. [`Pipeline`] instead splits X and Y into `depth` slots
+//! and issues the loads for step `k + depth - 1` before the computation for
+//! step `k`, so that the loads are overlapped with the preceding
+//! computations. `depth = 2` corresponds to classic double buffering.
+//!
+//! # Example
+//!
+//! ```rust
+//! use amx::{Amx, pipeline::Pipeline, ZRow};
+//!
+//! /// `z[0..64] = sum_k x[k] ⊗ y[k]` (`f32`)
+//! fn dot(ctx: &mut impl Amx, x: &[[f32; 16]], y: &[[f32; 16]]) {
+//!     Pipeline::new(2, 1, 1).run(
+//!         ctx,
+//!         x.len(),
+//!         |ctx, k, slot| unsafe {
+//!             ctx.load512(x[k].as_ptr(), slot.x_row(0));
+//!             ctx.load512(y[k].as_ptr(), slot.y_row(0));
+//!         },
+//!         |ctx, k, slot| {
+//!             ctx.outer_product_f32_xy_to_z(
+//!                 Some(slot.x_bytes(0)),
+//!                 Some(slot.y_bytes(0)),
+//!                 ZRow(0),
+//!                 k != 0,
+//!             );
+//!         },
+//!     );
+//! }
+//! ```
+use crate::{XBytes, XRow, YBytes, YRow};
+
+/// The number of rows in X and Y
+const NUM_ROWS: usize = 8;
+
+/// Describes how X and Y are divided into pipeline slots. See the
+/// [module-level documentation](self) for details.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    depth: usize,
+    x_rows: usize,
+    y_rows: usize,
+}
+
+/// A set of X and Y rows assigned to one step of a [`Pipeline`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PipelineSlot {
+    index: usize,
+    x_rows: usize,
+    y_rows: usize,
+}
+
+impl Pipeline {
+    /// Construct a `Pipeline` with `depth` slots, each consisting of `x_rows`
+    /// X rows and `y_rows` Y rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is zero or the slots don't fit in the register file
+    /// (`depth * x_rows > 8` or `depth * y_rows > 8`).
+    #[track_caller]
+    pub fn new(depth: usize, x_rows: usize, y_rows: usize) -> Self {
+        assert!(depth > 0, "`depth` must be non-zero");
+        assert!(
+            depth * x_rows <= NUM_ROWS && depth * y_rows <= NUM_ROWS,
+            "{depth} slots of {x_rows} X rows and {y_rows} Y rows don't fit in the register file"
+        );
+        Self {
+            depth,
+            x_rows,
+            y_rows,
+        }
+    }
+
+    /// Construct a `Pipeline` with the largest depth that fits in the
+    /// register file.
+    #[track_caller]
+    pub fn deepest(x_rows: usize, y_rows: usize) -> Self {
+        let depth = NUM_ROWS / x_rows.max(y_rows).max(1);
+        Self::new(depth, x_rows, y_rows)
+    }
+
+    /// Get the number of slots.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the slot used by the `step`-th step.
+    #[inline]
+    pub fn slot(&self, step: usize) -> PipelineSlot {
+        PipelineSlot {
+            index: step % self.depth,
+            x_rows: self.x_rows,
+            y_rows: self.y_rows,
+        }
+    }
+
+    /// Run `steps` steps, calling `load` and `compute` for each step in the
+    /// pipelined order.
+    ///
+    /// `load(ctx, step, slot)` should load the operands of `step` into
+    /// `slot`, and `compute(ctx, step, slot)` should consume them. `load` is
+    /// called for `step + depth - 1` right before `compute` is called for
+    /// `step`, and is not called for a slot until its previous `compute`
+    /// has returned.
+    pub fn run<C: ?Sized>(
+        &self,
+        ctx: &mut C,
+        steps: usize,
+        mut load: impl FnMut(&mut C, usize, PipelineSlot),
+        mut compute: impl FnMut(&mut C, usize, PipelineSlot),
+    ) {
+        let lead = self.depth - 1;
+
+        // Prologue
+        for step in 0..steps.min(lead) {
+            load(ctx, step, self.slot(step));
+        }
+
+        for step in 0..steps {
+            if step + lead < steps {
+                load(ctx, step + lead, self.slot(step + lead));
+            }
+            compute(ctx, step, self.slot(step));
+        }
+    }
+}
+
+impl PipelineSlot {
+    /// Get the index of the slot in range `0..depth`.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the `i`-th X row of the slot.
+    #[inline]
+    pub fn x_row(&self, i: usize) -> XRow {
+        debug_assert!(i < self.x_rows);
+        XRow(self.index * self.x_rows + i)
+    }
+
+    /// Get the `i`-th Y row of the slot.
+    #[inline]
+    pub fn y_row(&self, i: usize) -> YRow {
+        debug_assert!(i < self.y_rows);
+        YRow(self.index * self.y_rows + i)
+    }
+
+    /// Get the byte offset of the `i`-th X row of the slot.
+    #[inline]
+    pub fn x_bytes(&self, i: usize) -> XBytes {
+        XBytes(self.x_row(i).0 * 64)
+    }
+
+    /// Get the byte offset of the `i`-th Y row of the slot.
+    #[inline]
+    pub fn y_bytes(&self, i: usize) -> YBytes {
+        YBytes(self.y_row(i).0 * 64)
+    }
+}
@@ -1,31 +1,147 @@
 //! AMX registers
+use crate::AmxArgError;
 
 /// Refers to a row (register) in the `x` register set.
 ///
-/// The row index must be in range `0..8`.
+/// The row index must be in range `0..8`. [`XRow::new`] and [`XRow::try_new`]
+/// check it at compile time and at runtime, respectively.
 #[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct XRow(pub usize);
 
 /// Refers to a row (register) in the `y` register set.
 ///
-/// The row index must be in range `0..8`.
+/// The row index must be in range `0..8`. [`YRow::new`] and [`YRow::try_new`]
+/// check it at compile time and at runtime, respectively.
 #[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct YRow(pub usize);
 
 /// Refers to a row (register) in the `z` register set.
 ///
-/// The row index must be in range `0..64`.
+/// The row index must be in range `0..64`. [`ZRow::new`] and [`ZRow::try_new`]
+/// check it at compile time and at runtime, respectively.
 #[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct ZRow(pub usize);
 
+impl ZRow {
+    /// Get the row holding `z[row]` of tile `tile` in the interleaved layout
+    /// written by the matrix-mode outer products on `elem_size`-byte inputs
+    /// (e.g., [`Amx::outer_product_f32_xy_to_z`] for `elem_size = 4`), where
+    /// Z is divided into `elem_size` tiles of `64 / elem_size` rows each.
+    ///
+    /// The widening outer products use the layout of their input size, with
+    /// tile `1` holding the odd lanes.
+    ///
+    /// `elem_size` must be `2`, `4`, or `8`, `tile` must be in range
+    /// `0..elem_size`, and `row` must be in range `0..64 / elem_size`.
+    ///
+    /// [`Amx::outer_product_f32_xy_to_z`]: crate::Amx::outer_product_f32_xy_to_z
+    #[inline]
+    #[track_caller]
+    pub const fn tile_row(elem_size: usize, tile: usize, row: usize) -> Self {
+        assert!(
+            matches!(elem_size, 2 | 4 | 8),
+            "`elem_size` must be 2, 4, or 8"
+        );
+        assert!(tile < elem_size, "`tile` is out of range");
+        assert!(row < 64 / elem_size, "`row` is out of range");
+        Self(row * elem_size + tile)
+    }
+}
+
 /// A byte offset in `x` register set.
 ///
-/// The byte offset must be in range `0..512`.
+/// The byte offset must be in range `0..512`. [`XBytes::new`] and [`XBytes::try_new`]
+/// check it at compile time and at runtime, respectively.
 #[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct XBytes(pub usize);
 
 /// A byte offset in `y` register set.
 ///
-/// The byte offset must be in range `0..512`.
+/// The byte offset must be in range `0..512`. [`YBytes::new`] and [`YBytes::try_new`]
+/// check it at compile time and at runtime, respectively.
 #[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct YBytes(pub usize);
+
+/// Define the range-checked constructors of a register index type.
+macro_rules! impl_index {
+    ($($ty:ident: $len:literal, $what:literal, $variant:ident { $field:ident }),* $(,)?) => {$(
+        impl $ty {
+            #[doc = concat!("The number of valid ", $what, "s")]
+            pub const LEN: usize = $len;
+
+            #[doc = concat!("Construct a `", stringify!($ty), "` with the ", $what, " `INDEX`, which")]
+            /// is checked at compile time.
+            ///
+            /// ```rust
+            #[doc = concat!("const INDEX: amx::", stringify!($ty), " = amx::", stringify!($ty), "::new::<1>();")]
+            /// assert_eq!(INDEX.0, 1);
+            /// ```
+            ///
+            /// An out-of-range index fails to compile:
+            ///
+            /// ```rust,compile_fail
+            #[doc = concat!("let index = amx::", stringify!($ty), "::new::<", stringify!($len), ">();")]
+            /// ```
+            #[inline]
+            pub const fn new<const INDEX: usize>() -> Self {
+                const {
+                    assert!(INDEX < $len, concat!("the ", $what, " is out of range"));
+                }
+                Self(INDEX)
+            }
+
+            #[doc = concat!("Construct a `", stringify!($ty), "` with the ", $what, " `index`, or")]
+            /// return an error if it's out of range.
+            #[inline]
+            pub const fn try_new(index: usize) -> Result<Self, AmxArgError> {
+                if index < $len {
+                    Ok(Self(index))
+                } else {
+                    Err(AmxArgError::$variant { $field: index, len: $len })
+                }
+            }
+        }
+    )*};
+}
+
+impl_index! {
+    XRow: 8, "row index", RowOutOfRange { index },
+    YRow: 8, "row index", RowOutOfRange { index },
+    ZRow: 64, "row index", RowOutOfRange { index },
+    XBytes: 0x200, "byte offset", OffsetOutOfRange { offset },
+    YBytes: 0x200, "byte offset", OffsetOutOfRange { offset },
+}
+
+/// Named constants for every register row, e.g., `X3` for `XRow(3)`.
+///
+/// These are also exported by [the prelude](crate::prelude).
+pub mod consts {
+    use super::{XRow, YRow, ZRow};
+
+    macro_rules! define_row_consts {
+        ($ty:ident: $($name:ident = $index:literal),* $(,)?) => {$(
+            #[doc = concat!("`", stringify!($ty), "(", stringify!($index), ")`")]
+            pub const $name: $ty = $ty($index);
+        )*};
+    }
+
+    define_row_consts! {
+        XRow: X0 = 0, X1 = 1, X2 = 2, X3 = 3, X4 = 4, X5 = 5, X6 = 6, X7 = 7,
+    }
+
+    define_row_consts! {
+        YRow: Y0 = 0, Y1 = 1, Y2 = 2, Y3 = 3, Y4 = 4, Y5 = 5, Y6 = 6, Y7 = 7,
+    }
+
+    define_row_consts! {
+        ZRow:
+        Z0 = 0, Z1 = 1, Z2 = 2, Z3 = 3, Z4 = 4, Z5 = 5, Z6 = 6, Z7 = 7,
+        Z8 = 8, Z9 = 9, Z10 = 10, Z11 = 11, Z12 = 12, Z13 = 13, Z14 = 14, Z15 = 15,
+        Z16 = 16, Z17 = 17, Z18 = 18, Z19 = 19, Z20 = 20, Z21 = 21, Z22 = 22, Z23 = 23,
+        Z24 = 24, Z25 = 25, Z26 = 26, Z27 = 27, Z28 = 28, Z29 = 29, Z30 = 30, Z31 = 31,
+        Z32 = 32, Z33 = 33, Z34 = 34, Z35 = 35, Z36 = 36, Z37 = 37, Z38 = 38, Z39 = 39,
+        Z40 = 40, Z41 = 41, Z42 = 42, Z43 = 43, Z44 = 44, Z45 = 45, Z46 = 46, Z47 = 47,
+        Z48 = 48, Z49 = 49, Z50 = 50, Z51 = 51, Z52 = 52, Z53 = 53, Z54 = 54, Z55 = 55,
+        Z56 = 56, Z57 = 57, Z58 = 58, Z59 = 59, Z60 = 60, Z61 = 61, Z62 = 62, Z63 = 63,
+    }
+}
@@ -0,0 +1,139 @@
+//! Capability reporting for bug reports and support requests
+use std::{fmt::Write as _, time::Instant};
+
+use crate::{
+    Amx, AmxCtx, AmxVersion, NewAmxCtxError, XBytes, YBytes, ZRow,
+    chrome_trace::{escape_json, write_json_f64},
+    detect::chip_name,
+};
+
+/// A summary of the AMX capabilities of the current system, collected by
+/// [`AmxReport::collect`].
+///
+/// The report can be rendered as JSON by [`Self::to_json`] for attaching to
+/// an issue. See `examples/amx_probe.rs` for a command-line tool printing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmxReport {
+    /// The processor's brand string (e.g., `Apple M1`), if available
+    pub chip: Option<String>,
+    /// The AMX revision, numbered after the chip generation that introduced
+    /// it (e.g., `2` for M2), if recognized from [`Self::chip`]
+    pub amx_version: Option<u32>,
+    /// The result of [`AmxCtx::new`]
+    pub ctx_status: Result<(), NewAmxCtxError>,
+    /// The measured peak throughput of the outer product instructions. Empty
+    /// if `ctx_status` is an error.
+    pub throughput: Vec<Throughput>,
+}
+
+/// The measured throughput of an outer product instruction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throughput {
+    /// The input data type (`f16`, `f32`, `f64`, or `i16`)
+    pub dtype: &'static str,
+    /// Billions of arithmetic operations (multiplications and additions) per
+    /// second
+    pub gops: f64,
+}
+
+/// The number of instructions issued per throughput measurement
+const NUM_ITERATIONS: usize = 1 << 20;
+
+impl AmxReport {
+    /// Collect a report. This takes a fraction of a second to measure the
+    /// throughput.
+    ///
+    /// The calling thread must not have an active [`AmxCtx`]; otherwise,
+    /// `ctx_status` will be `Err(AlreadyActive)`.
+    pub fn collect() -> Self {
+        let chip = chip_name();
+        let amx_version = (chip.as_deref())
+            .and_then(AmxVersion::from_brand_string)
+            .map(AmxVersion::generation);
+        let (ctx_status, throughput) = match AmxCtx::new() {
+            Ok(mut ctx) => (Ok(()), measure_throughput(&mut ctx)),
+            Err(e) => (Err(e), Vec::new()),
+        };
+        Self {
+            chip,
+            amx_version,
+            ctx_status,
+            throughput,
+        }
+    }
+
+    /// Render the report as a JSON string.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        match &self.chip {
+            Some(chip) => {
+                out.push_str(r#""chip":""#);
+                escape_json(chip, &mut out);
+                out.push_str(r#"","#);
+            }
+            None => out.push_str(r#""chip":null,"#),
+        }
+        match self.amx_version {
+            Some(version) => write!(out, r#""amx_version":{version},"#).unwrap(),
+            None => out.push_str(r#""amx_version":null,"#),
+        }
+        match self.ctx_status {
+            Ok(()) => out.push_str(r#""ctx_status":"ok","#),
+            Err(e) => write!(out, r#""ctx_status":"{e:?}","#).unwrap(),
+        }
+        out.push_str(r#""throughput":{"#);
+        for (i, t) in self.throughput.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write!(out, r#""{}":"#, t.dtype).unwrap();
+            // Rounded to 0.1 GOPS
+            write_json_f64((t.gops * 10.0).round() / 10.0, &mut out);
+        }
+        out.push_str("}}");
+        out
+    }
+}
+
+fn measure_throughput(ctx: &mut AmxCtx) -> Vec<Throughput> {
+    type Op = fn(&mut AmxCtx, Option<XBytes>, Option<YBytes>, ZRow, bool);
+    // (dtype, lanes, op)
+    let ops: [(&str, usize, Op); 4] = [
+        ("f16", 32, |ctx, x, y, z, a| {
+            ctx.outer_product_f16_xy_to_z(x, y, z, a)
+        }),
+        ("f32", 16, |ctx, x, y, z, a| {
+            ctx.outer_product_f32_xy_to_z(x, y, z, a)
+        }),
+        ("f64", 8, |ctx, x, y, z, a| {
+            ctx.outer_product_f64_xy_to_z(x, y, z, a)
+        }),
+        ("i16", 32, |ctx, x, y, z, a| {
+            ctx.outer_product_i16_xy_to_z(x, y, z, a)
+        }),
+    ];
+
+    ops.iter()
+        .map(|&(dtype, lanes, op)| {
+            // Rotate through the independent tiles to avoid stalling on the
+            // accumulation dependency
+            let num_tiles = 64 / lanes;
+            let start = Instant::now();
+            for i in 0..NUM_ITERATIONS {
+                op(
+                    ctx,
+                    Some(XBytes(0)),
+                    Some(YBytes(0)),
+                    ZRow(i % num_tiles),
+                    true,
+                );
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+            let num_ops = (NUM_ITERATIONS * lanes * lanes * 2) as f64;
+            Throughput {
+                dtype,
+                gops: num_ops / elapsed / 1e9,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Lane permutation by `genlut`
+use crate::AmxElement;
+
+/// A permutation of the lanes of a register row, applied by
+/// [`Amx::shuffle512`](crate::Amx::shuffle512)
+///
+/// The permutation is stored as the packed index row of a `genlut`
+/// lookup whose table is the source row. Since `genlut` indices are at most
+/// five bits wide, the indices for 8-bit lanes must be smaller than 32;
+/// 16-, 32-, and 64-bit lanes can be permuted freely.
+///
+/// [`Self::new`] is a `const fn`, so a fixed permutation can be built at
+/// compile time:
+///
+/// ```rust
+/// use amx::{Amx, Shuffle512, XRow, YRow};
+///
+/// /// Swap adjacent `u32` lanes
+/// const SWAP_PAIRS: Shuffle512 =
+///     Shuffle512::new::<u32>(&[1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10, 13, 12, 15, 14]);
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let src: [u32; 16] = std::array::from_fn(|i| i as u32 * 10);
+/// ctx.load512_slice(&src, XRow(0));
+/// ctx.shuffle512(&SWAP_PAIRS, XRow(0), YRow(7), XRow(1));
+/// assert_eq!(ctx.read_x_as::<u32>()[1][..4], [10, 0, 30, 20]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Shuffle512 {
+    packed: [u8; 64],
+    mode: u64,
+}
+
+impl Shuffle512 {
+    /// Construct a `Shuffle512` that sets lane `i` of the output to lane
+    /// `indices[i]` of the source, where each lane is a `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indices.len()` isn't `64 / size_of::<T>()` or any index is
+    /// out of range (`>= 64 / size_of::<T>()`, or `>= 32` for 8-bit lanes).
+    #[track_caller]
+    pub const fn new<T: AmxElement>(indices: &[u8]) -> Self {
+        let lane_size = size_of::<T>();
+        let lanes = 64 / lane_size;
+        // (`genlut` mode, index width)
+        let (mode, bits) = match lane_size {
+            1 => (15, 5),
+            2 => (14, 5),
+            4 => (11, 4),
+            _ => (10, 4),
+        };
+        assert!(
+            indices.len() == lanes,
+            "`indices` must have one index per lane"
+        );
+
+        let mut packed = [0u8; 64];
+        let mut i = 0;
+        while i < lanes {
+            let index = indices[i] as usize;
+            assert!(
+                index < lanes && index < 1 << bits,
+                "lane index is out of range"
+            );
+            let mut b = 0;
+            while b < bits {
+                let bit = i * bits + b;
+                packed[bit / 8] |= (((index >> b) & 1) as u8) << (bit % 8);
+                b += 1;
+            }
+            i += 1;
+        }
+        Self { packed, mode }
+    }
+
+    /// Get the packed index row, which is loaded to the scratch row by
+    /// [`Amx::shuffle512`](crate::Amx::shuffle512).
+    #[inline]
+    pub const fn packed_indices(&self) -> &[u8; 64] {
+        &self.packed
+    }
+
+    /// Get the `genlut` mode number.
+    #[inline]
+    pub(crate) const fn mode(&self) -> u64 {
+        self.mode
+    }
+}
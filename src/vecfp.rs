@@ -0,0 +1,120 @@
+//! Wrapper for the `vecfp` instruction
+//!
+//! # Operand encoding
+//!
+//! | Bits  | Field                                                 |
+//! |-------|-------------------------------------------------------|
+//! | 0–8   | Y byte offset                                         |
+//! | 10–18 | X byte offset                                         |
+//! | 20–25 | Z row                                                 |
+//! | 29    | Treat Y as ones                                       |
+//! | 42–45 | Lane width: `4` = `f32`, `7` = `f64`, otherwise `f16` |
+//! | 47–52 | ALU mode (see [`VecFpAluOp`])                         |
+use crate::{AmxOps, VecFpOperand, XBytes, YBytes, ZRow};
+
+/// The lane type of [`Amx::vector_fp`](crate::Amx::vector_fp)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VecFpTy {
+    /// `[f16; 32]`
+    F16,
+    /// `[f32; 16]`
+    F32,
+    /// `[f64; 8]`
+    F64,
+}
+
+impl VecFpTy {
+    /// Get the size of a lane in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::F16 => 2,
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Get the raw lane width mode.
+    const fn lane_width_mode(self) -> u64 {
+        match self {
+            Self::F16 => 0,
+            Self::F32 => 4,
+            Self::F64 => 7,
+        }
+    }
+}
+
+/// The elementwise operation performed by
+/// [`Amx::vector_fp`](crate::Amx::vector_fp) for each lane `i`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VecFpAluOp {
+    /// `z[i] = z[i] + x[i] * y[i]`, rounded once
+    MulAdd,
+    /// `z[i] = z[i] - x[i] * y[i]`, rounded once
+    MulSub,
+    /// `z[i] = z[i] + x[i]`. Y is not read.
+    Add,
+    /// `z[i] = z[i] - x[i]`. Y is not read.
+    Sub,
+    /// `z[i] = min(x[i], z[i])`. Y is not read.
+    Min,
+    /// `z[i] = max(x[i], z[i])`. Y is not read.
+    Max,
+}
+
+impl VecFpAluOp {
+    /// Get the raw ALU mode and whether Y is treated as ones.
+    const fn alu_mode(self) -> (u64, bool) {
+        match self {
+            Self::MulAdd => (0, false),
+            Self::MulSub => (1, false),
+            Self::Add => (0, true),
+            Self::Sub => (1, true),
+            Self::Min => (5, false),
+            Self::Max => (7, false),
+        }
+    }
+}
+
+/// Encode the fields of the `vecfp` operand other than the register offsets.
+#[inline(always)]
+pub(crate) const fn bits(ty: VecFpTy, op: VecFpAluOp) -> u64 {
+    let (alu, skip_y) = op.alu_mode();
+    ((skip_y as u64) << 29) | (ty.lane_width_mode() << 42) | (alu << 47)
+}
+
+/// Decode the fields encoded by [`bits`]. Returns `None` if the ALU mode is
+/// not supported.
+pub(crate) const fn from_bits(operand: u64) -> Option<(VecFpTy, VecFpAluOp)> {
+    let skip_y = operand & (1 << 29) != 0;
+    let op = match ((operand >> 47) & 0x3f, skip_y) {
+        (0, false) => VecFpAluOp::MulAdd,
+        (1, false) => VecFpAluOp::MulSub,
+        (0, true) => VecFpAluOp::Add,
+        (1, true) => VecFpAluOp::Sub,
+        (5, _) => VecFpAluOp::Min,
+        (7, _) => VecFpAluOp::Max,
+        _ => return None,
+    };
+    let ty = match (operand >> 42) & 0xf {
+        4 => VecFpTy::F32,
+        7 => VecFpTy::F64,
+        _ => VecFpTy::F16,
+    };
+    Some((ty, op))
+}
+
+#[inline(always)]
+pub(crate) fn vecfp(
+    ops: &mut (impl AmxOps + ?Sized),
+    ty: VecFpTy,
+    op: VecFpAluOp,
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+) {
+    let operand = VecFpOperand::new(ty, op)
+        .x_offset(x_offset_bytes)
+        .y_offset(y_offset_bytes)
+        .z_row(z_index);
+    ops.vecfp(operand.encode());
+}
@@ -0,0 +1,177 @@
+//! Wrapper for the `vecint` instruction
+//!
+//! # Operand encoding
+//!
+//! | Bits  | Field                                                        |
+//! |-------|--------------------------------------------------------------|
+//! | 0–8   | Y byte offset                                                |
+//! | 10–18 | X byte offset                                                |
+//! | 20–25 | Z row                                                        |
+//! | 27    | Treat Z as zero (i.e., don't accumulate)                     |
+//! | 42–45 | Lane width: `4` = 32 bits, `10` = 8 bits, otherwise 16 bits  |
+//! | 47–52 | ALU mode: `0` = `z + p`, `1` = `z - p`                       |
+//! | 53    | Saturate instead of wrapping around                          |
+//! | 58–62 | Right shift applied to the product `p = x * y`               |
+use crate::{AmxOps, VecIntOperand, XBytes, YBytes, ZRow};
+
+/// The lane type of a [`VecIntOp`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VecIntTy {
+    /// `[i8; 64]`
+    I8,
+    /// `[i16; 32]`
+    I16,
+    /// `[i32; 16]`
+    I32,
+}
+
+impl VecIntTy {
+    /// Get the size of a lane in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::I8 => 1,
+            Self::I16 => 2,
+            Self::I32 => 4,
+        }
+    }
+
+    /// Get the raw lane width mode.
+    const fn lane_width_mode(self) -> u64 {
+        match self {
+            Self::I8 => 10,
+            Self::I16 => 0,
+            Self::I32 => 4,
+        }
+    }
+}
+
+/// An elementwise integer multiply-add executed by
+/// [`Amx::vector_int`](crate::Amx::vector_int).
+///
+/// For each lane `i`, this calculates
+/// `z[i] = z[i] ± ((x[i] * y[i]) >> shift)`, where the product is calculated
+/// at full precision before the arithmetic right shift, and the sum wraps
+/// around or saturates to the lane type's range.
+///
+/// ```rust
+/// use amx::{Amx, VecIntOp, VecIntTy, XBytes, XRow, YBytes, YRow, ZRow};
+///
+/// let mut ctx = amx::AmxEmuCtx::default();
+/// let x = [20_000i16; 32];
+/// let y = [3i16; 32];
+/// unsafe {
+///     ctx.load512(x.as_ptr(), XRow(0));
+///     ctx.load512(y.as_ptr(), YRow(0));
+/// }
+///
+/// let op = VecIntOp::new(VecIntTy::I16).accumulate(false).saturate(true);
+/// ctx.vector_int(op, XBytes(0), YBytes(0), ZRow(0));
+/// assert_eq!(ctx.read_z_i16()[0], [i16::MAX; 32]);
+///
+/// let op = VecIntOp::new(VecIntTy::I16).accumulate(false).shift(4);
+/// ctx.vector_int(op, XBytes(0), YBytes(0), ZRow(0));
+/// assert_eq!(ctx.read_z_i16()[0], [3750; 32]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VecIntOp {
+    ty: VecIntTy,
+    pub(crate) shift: u32,
+    pub(crate) saturate: bool,
+    pub(crate) accumulate: bool,
+    pub(crate) subtract: bool,
+}
+
+impl VecIntOp {
+    /// Construct a `VecIntOp` calculating `z[i] += x[i] * y[i]` with
+    /// wrapping arithmetic.
+    pub const fn new(ty: VecIntTy) -> Self {
+        Self {
+            ty,
+            shift: 0,
+            saturate: false,
+            accumulate: true,
+            subtract: false,
+        }
+    }
+
+    /// Set the right shift applied to the products.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shift` is not in range `0..32`.
+    #[track_caller]
+    pub const fn shift(self, shift: u32) -> Self {
+        assert!(shift < 32, "`shift` must be in range `0..32`");
+        Self { shift, ..self }
+    }
+
+    /// Set whether the results saturate to the lane type's range instead of
+    /// wrapping around.
+    pub const fn saturate(self, saturate: bool) -> Self {
+        Self { saturate, ..self }
+    }
+
+    /// Set whether the products are added to the existing contents of Z. If
+    /// `false`, Z is treated as zero.
+    pub const fn accumulate(self, accumulate: bool) -> Self {
+        Self { accumulate, ..self }
+    }
+
+    /// Set whether the products are subtracted instead of added.
+    pub const fn subtract(self, subtract: bool) -> Self {
+        Self { subtract, ..self }
+    }
+
+    /// Get the lane type.
+    pub const fn ty(&self) -> VecIntTy {
+        self.ty
+    }
+
+    /// Encode the fields of the `vecint` operand other than the register
+    /// offsets.
+    #[inline(always)]
+    pub(crate) const fn bits(&self) -> u64 {
+        ((!self.accumulate as u64) << 27)
+            | (self.ty.lane_width_mode() << 42)
+            | ((self.subtract as u64) << 47)
+            | ((self.saturate as u64) << 53)
+            | ((self.shift as u64) << 58)
+    }
+
+    /// Decode the fields encoded by [`Self::bits`]. Returns `None` if the
+    /// ALU mode is not supported.
+    pub(crate) const fn from_bits(operand: u64) -> Option<Self> {
+        let subtract = match (operand >> 47) & 0x3f {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let ty = match (operand >> 42) & 0xf {
+            4 => VecIntTy::I32,
+            10 => VecIntTy::I8,
+            _ => VecIntTy::I16,
+        };
+        Some(Self {
+            ty,
+            shift: ((operand >> 58) & 0x1f) as u32,
+            saturate: operand & (1 << 53) != 0,
+            accumulate: operand & (1 << 27) == 0,
+            subtract,
+        })
+    }
+}
+
+#[inline(always)]
+pub(crate) fn vecint(
+    ops: &mut (impl AmxOps + ?Sized),
+    op: VecIntOp,
+    x_offset_bytes: XBytes,
+    y_offset_bytes: YBytes,
+    z_index: ZRow,
+) {
+    let operand = VecIntOperand::new(op)
+        .x_offset(x_offset_bytes)
+        .y_offset(y_offset_bytes)
+        .z_row(z_index);
+    ops.vecint(operand.encode());
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use amx::{
+    algo::{self, TopK},
+    linalg::{MatMut, MatRef},
+};
+use common::{Xorshift32, assert_close};
+
+fn prefix_sum_naive(data: &[f32], exclusive: bool) -> Vec<f32> {
+    let mut acc = 0.0;
+    data.iter()
+        .map(|&x| {
+            let prev = acc;
+            acc += x;
+            if exclusive { prev } else { acc }
+        })
+        .collect()
+}
+
+fn topk_naive(scores: &[f32], k: usize) -> Vec<(f32, usize)> {
+    let mut entries: Vec<(f32, usize)> = scores
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, s)| !s.is_nan())
+        .map(|(i, s)| (s, i))
+        .collect();
+    entries.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    entries.truncate(k);
+    entries
+}
+
+#[test]
+fn topk_stream() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7070);
+
+    for k in [0, 1, 5, 40] {
+        let mut scores = rng.vec_f32(300);
+        // Ties, NaNs, and infinities
+        scores[17] = scores[3];
+        scores[50] = f32::NAN;
+        scores[99] = f32::INFINITY;
+        scores[120] = f32::NEG_INFINITY;
+
+        let mut topk = TopK::new(k);
+        for row in scores.chunks(37) {
+            topk.push_row(&mut ctx, row);
+        }
+        assert_eq!(topk.as_slice(), &topk_naive(&scores, k)[..], "k = {k}");
+    }
+}
+
+#[test]
+fn topk_rows() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7171);
+
+    let (m, n, k) = (7, 45, 10);
+    let mut scores = rng.vec_f32(m * n);
+    // A row with fewer than `k` valid scores
+    for s in &mut scores[n..n + n - 3] {
+        *s = f32::NAN;
+    }
+
+    let mut values = vec![0f32; m * k];
+    let mut indices = vec![0usize; m * k];
+    algo::topk_rows(
+        &mut ctx,
+        MatRef::new(&scores, m, n),
+        MatMut::new(&mut values, m, k),
+        MatMut::new(&mut indices, m, k),
+    );
+
+    for i in 0..m {
+        let expected = topk_naive(&scores[i * n..][..n], k);
+        for j in 0..k {
+            let (value, index) = (values[i * k + j], indices[i * k + j]);
+            match expected.get(j) {
+                Some(&e) => assert_eq!((value, index), e, "({i}, {j})"),
+                None => assert!(value.is_nan() && index == usize::MAX, "({i}, {j})"),
+            }
+        }
+    }
+}
+
+#[test]
+fn sort_rows_f32x16() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5050);
+
+    let mut rows: Vec<[f32; 16]> = (0..20)
+        .map(|_| std::array::from_fn(|_| rng.next_f32()))
+        .collect();
+    rows[0] = std::array::from_fn(|i| i as f32);
+    rows[1] = std::array::from_fn(|i| -(i as f32));
+    rows[2] = [0.5; 16];
+    rows[3][5] = f32::INFINITY;
+    rows[3][9] = f32::NEG_INFINITY;
+    rows[4][0..8].copy_from_slice(&[0.25; 8]);
+
+    let mut expected = rows.clone();
+    for row in &mut expected {
+        row.sort_by(f32::total_cmp);
+    }
+    algo::sort_rows_f32x16(&mut ctx, &mut rows);
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn sort_rows_i16x32() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5151);
+
+    let mut rows: Vec<[i16; 32]> = (0..20)
+        .map(|_| std::array::from_fn(|_| rng.next() as i16))
+        .collect();
+    rows[0] = std::array::from_fn(|i| 31 - i as i16);
+    rows[1] = std::array::from_fn(|i| if i % 2 == 0 { i16::MIN } else { i16::MAX });
+    rows[2] = std::array::from_fn(|i| (i % 3) as i16);
+
+    let mut expected = rows.clone();
+    for row in &mut expected {
+        row.sort();
+    }
+    algo::sort_rows_i16x32(&mut ctx, &mut rows);
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn prefix_sum() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x3030);
+
+    for len in [0, 1, 15, 16, 17, 100, 1000] {
+        let input = rng.vec_f32(len);
+        for exclusive in [false, true] {
+            let mut data = input.clone();
+            algo::prefix_sum_f32(&mut ctx, &mut data, exclusive);
+            assert_close(&data, &prefix_sum_naive(&input, exclusive), 1e-4);
+        }
+    }
+
+    // Integers are summed exactly
+    let mut data: Vec<f32> = (1..=40).map(|i| i as f32).collect();
+    algo::prefix_sum_f32(&mut ctx, &mut data, false);
+    assert_eq!(
+        data,
+        (1..=40)
+            .map(|i| (i * (i + 1) / 2) as f32)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn prefix_sum_rows() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x3131);
+
+    let (m, n, stride) = (5, 37, 40);
+    let input = rng.vec_f32(m * stride);
+    for exclusive in [false, true] {
+        let mut data = input.clone();
+        algo::prefix_sum_rows_f32(
+            &mut ctx,
+            MatMut::with_stride(&mut data, m, n, stride),
+            exclusive,
+        );
+        for i in 0..m {
+            let row = &input[i * stride..][..n];
+            assert_close(
+                &data[i * stride..][..n],
+                &prefix_sum_naive(row, exclusive),
+                1e-4,
+            );
+            // The padding is left untouched
+            assert_eq!(
+                data[i * stride + n..][..stride - n],
+                input[i * stride + n..][..stride - n]
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Check the assembly generated for `examples/asm_audit.rs`
+#![cfg(target_arch = "aarch64")]
+use std::{fs, path::Path, process::Command};
+
+/// The number of `audit_*` functions defined in `examples/asm_audit.rs`
+const NUM_FUNCTIONS: usize = 22 + 8 + 14;
+
+/// The maximum number of instructions computing the operand. Loads and
+/// stores through `AmxOps` combine the operand and the pointer.
+const MAX_SETUP: usize = 2;
+
+/// Split the assembly into the instructions of each `audit_*` function.
+fn audit_functions(asm: &str) -> Vec<(&str, Vec<&str>)> {
+    let mut functions: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut in_function = false;
+    for line in asm.lines().map(str::trim) {
+        if let Some(label) = line.strip_suffix(':') {
+            let name = label.trim_start_matches('_');
+            in_function = name.starts_with("audit_");
+            if in_function {
+                functions.push((name, Vec::new()));
+            }
+        } else if in_function {
+            // Skip comments and directives except the instruction itself
+            if line.is_empty()
+                || line.starts_with("//")
+                || line.starts_with(';')
+                || (line.starts_with('.') && !line.starts_with(".word"))
+            {
+                continue;
+            }
+            let instrs = &mut functions.last_mut().unwrap().1;
+            instrs.push(line);
+            if line == "ret" {
+                in_function = false;
+            }
+        }
+    }
+    functions
+}
+
+#[test]
+fn one_word_per_instruction() {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("asm_audit");
+    let status = Command::new(env!("CARGO"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .args(["rustc", "--release", "--example", "asm_audit", "--"])
+        .args(["--emit", "asm", "-C", "codegen-units=1"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let examples = target_dir.join("release/examples");
+    let asm_path = fs::read_dir(&examples)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("asm_audit-") && name.ends_with(".s")
+        })
+        .expect("assembly output not found");
+    let asm = fs::read_to_string(asm_path).unwrap();
+
+    let functions = audit_functions(&asm);
+    assert_eq!(functions.len(), NUM_FUNCTIONS);
+    for (name, instrs) in functions {
+        let num_words = instrs.iter().filter(|i| i.starts_with(".word")).count();
+        assert_eq!(num_words, 1, "{name}: {instrs:#?}");
+        assert_eq!(instrs.last(), Some(&"ret"), "{name}: {instrs:#?}");
+        assert!(
+            instrs.len() <= MAX_SETUP + 2,
+            "{name} has extra instructions: {instrs:#?}"
+        );
+        assert!(
+            !instrs
+                .iter()
+                .any(|i| i.starts_with("bl") || i.starts_with("b ")),
+            "{name} calls a function: {instrs:#?}"
+        );
+    }
+}
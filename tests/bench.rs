@@ -0,0 +1,105 @@
+use amx::bench::{self, Access, Baseline, Delta, Pattern, Status};
+
+#[test]
+fn compare() {
+    let mut baseline = Baseline::new();
+    baseline.record("Apple M1", "sgemm", 100.0);
+    baseline.record("Apple M1", "hgemm", 200.0);
+    baseline.record("Apple M1", "dgemm", 50.0);
+    baseline.record("Apple M2", "sgemm", 150.0);
+
+    let mut current = Baseline::new();
+    current.record("Apple M1", "sgemm", 96.0);
+    current.record("Apple M1", "hgemm", 150.0);
+    current.record("Apple M1", "dgemm", 60.0);
+    current.record("Apple M1", "igemm", 10.0);
+
+    let deltas = baseline.compare(&current, 0.05);
+    let statuses: Vec<_> = deltas
+        .iter()
+        .map(|d| (d.kernel.as_str(), d.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("dgemm", Status::Improved),
+            ("hgemm", Status::Regressed),
+            ("igemm", Status::New),
+            ("sgemm", Status::Unchanged),
+        ]
+    );
+    assert_eq!(deltas[1].ratio(), Some(0.75));
+    assert_eq!(
+        deltas[2],
+        Delta {
+            chip: "Apple M1".to_owned(),
+            kernel: "igemm".to_owned(),
+            baseline: None,
+            current: 10.0,
+            status: Status::New,
+        }
+    );
+
+    baseline.merge(&current);
+    assert_eq!(baseline.len(), 5);
+    assert_eq!(baseline.get("Apple M1", "hgemm"), Some(150.0));
+    assert_eq!(baseline.get("Apple M2", "sgemm"), Some(150.0));
+}
+
+#[test]
+fn save_load() {
+    let mut baseline = Baseline::new();
+    baseline.record("Apple M1 Max", "sgemm 256x256", 1234.5678);
+    baseline.record("Apple M1 Max", "conv", 0.1 + 0.2);
+
+    let path = std::env::temp_dir().join(format!("amx-baseline-{}.tsv", std::process::id()));
+    baseline.save(&path).unwrap();
+    let loaded = Baseline::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), baseline);
+}
+
+#[test]
+fn parse() {
+    let baseline = Baseline::parse("# comment\n\nM1\tk\t1.5\n").unwrap();
+    assert_eq!(baseline.iter().collect::<Vec<_>>(), [("M1", "k", 1.5)]);
+
+    let err = Baseline::parse("M1\tk\t1.5\nM1\tk\n").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("line 2"), "{err}");
+    assert!(Baseline::parse("M1\tk\tfast").is_err());
+}
+
+#[test]
+#[should_panic]
+fn record_rejects_tabs() {
+    Baseline::new().record("M1", "a\tb", 1.0);
+}
+
+#[test]
+fn measure_throughput() {
+    let mut calls = 0;
+    let t = bench::measure_throughput(1000, 2.0, || calls += 1);
+    assert_eq!(calls, 1000);
+    assert!(t > 0.0);
+}
+
+#[test]
+fn bandwidth() {
+    let mut ctx = amx::AmxEmuCtx::default();
+    for bw in bench::probe_bandwidth(&mut ctx, 8192, 3) {
+        assert_eq!(bw.bytes, 3 * 8192, "{bw:?}");
+        assert!(bw.gbps() > 0.0, "{bw:?}");
+    }
+
+    let bw = bench::measure_bandwidth(&mut ctx, Access::LoadY, Pattern::Strided(192), 1024, 1);
+    assert_eq!(bw.bytes, 1024);
+    assert!(bw.to_string().starts_with("ldy strided(192): "));
+}
+
+#[test]
+#[should_panic]
+fn bandwidth_interleaved_load() {
+    let mut ctx = amx::AmxEmuCtx::default();
+    bench::measure_bandwidth(&mut ctx, Access::LoadX, Pattern::InterleavedZ, 1024, 1);
+}
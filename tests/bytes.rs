@@ -0,0 +1,207 @@
+mod common;
+
+use amx::bytes::{self, GatherPlan, Match, ShiftOr};
+use common::Xorshift32;
+
+impl Xorshift32 {
+    fn vec_u8(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+#[test]
+fn adler32() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1234);
+
+    assert_eq!(
+        bytes::adler32(&mut ctx, 1, b"Wikipedia"),
+        0x11e6_0398,
+        "known answer"
+    );
+
+    for len in [0, 1, 63, 64, 65, 2047, 2048, 2049, 10000] {
+        let mut data = rng.vec_u8(len);
+        if len == 10000 {
+            data.fill(0xff);
+        }
+
+        let (mut a, mut b) = (1u32, 0u32);
+        for &d in &data {
+            a = (a + d as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        let expected = b << 16 | a;
+
+        assert_eq!(bytes::adler32(&mut ctx, 1, &data), expected, "len = {len}");
+
+        // Streaming in two parts
+        let (head, tail) = data.split_at(len / 3);
+        let partial = bytes::adler32(&mut ctx, 1, head);
+        assert_eq!(
+            bytes::adler32(&mut ctx, partial, tail),
+            expected,
+            "len = {len}"
+        );
+    }
+}
+
+#[test]
+fn fletcher64() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5678);
+
+    for len in [0, 4, 60, 64, 68, 2044, 2048, 2052, 10000] {
+        let mut data = rng.vec_u8(len);
+        if len == 10000 {
+            data.fill(0xff);
+        }
+
+        let (mut sum1, mut sum2) = (0u64, 0u64);
+        for word in data.chunks_exact(4) {
+            let word = u32::from_le_bytes(word.try_into().unwrap()) as u64;
+            sum1 = (sum1 + word) % 0xffff_ffff;
+            sum2 = (sum2 + sum1) % 0xffff_ffff;
+        }
+        let expected = sum2 << 32 | sum1;
+
+        assert_eq!(
+            bytes::fletcher64(&mut ctx, 0, &data),
+            expected,
+            "len = {len}"
+        );
+
+        // Streaming in two parts
+        let (head, tail) = data.split_at(len / 8 * 4);
+        let partial = bytes::fletcher64(&mut ctx, 0, head);
+        assert_eq!(
+            bytes::fletcher64(&mut ctx, partial, tail),
+            expected,
+            "len = {len}"
+        );
+    }
+}
+
+#[test]
+fn shift_or() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x9abc);
+
+    let find_naive = |patterns: &[&[u8]], haystack: &[u8]| -> Vec<Match> {
+        let mut matches = Vec::new();
+        for start in 0..haystack.len() {
+            for (pattern, p) in patterns.iter().enumerate() {
+                if haystack[start..].starts_with(p) {
+                    matches.push(Match { start, pattern });
+                }
+            }
+        }
+        matches
+    };
+
+    let log = b"INFO ok\nWARN disk\nERROR: failed\nINFO ok\nERROR: timeout\nFATAL";
+    let patterns: [&[u8]; 3] = [b"ERROR", b"WARN", b"FATAL"];
+    let matcher = ShiftOr::new(&patterns);
+    assert_eq!(matcher.len(), 3);
+    let got = matcher.find_all(&mut ctx, log);
+    assert_eq!(got, find_naive(&patterns, log));
+    assert_eq!(got.len(), 4);
+
+    // Many patterns sharing buckets, overlapping matches, and a small
+    // alphabet to provoke false positives in the filter
+    let patterns: Vec<Vec<u8>> = (0..20)
+        .map(|i| {
+            let len = 2 + i % 5;
+            (0..len)
+                .map(|_| b"abcAB"[rng.next() as usize % 5])
+                .collect()
+        })
+        .chain([b"aa".to_vec(), b"aaa".to_vec()])
+        .collect();
+    let pattern_refs: Vec<&[u8]> = patterns.iter().map(Vec::as_slice).collect();
+    let matcher = ShiftOr::new(&patterns);
+    for len in [0, 1, 2, 63, 64, 65, 200, 1000] {
+        let haystack: Vec<u8> = (0..len)
+            .map(|_| b"abcAB"[rng.next() as usize % 5])
+            .collect();
+        assert_eq!(
+            matcher.find_all(&mut ctx, &haystack),
+            find_naive(&pattern_refs, &haystack),
+            "len = {len}"
+        );
+    }
+}
+
+#[test]
+fn unpack_bits() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xdef0);
+
+    for bits in 1..=16u32 {
+        for len in [0, 1, 31, 32, 33, 100, 257] {
+            let values: Vec<u32> = (0..len).map(|_| rng.next() & ((1 << bits) - 1)).collect();
+
+            // Pack LSB-first
+            let mut packed = vec![0u8; (len * bits as usize).div_ceil(8)];
+            for (i, &v) in values.iter().enumerate() {
+                for b in 0..bits as usize {
+                    let bit = i * bits as usize + b;
+                    packed[bit / 8] |= ((v >> b & 1) as u8) << (bit % 8);
+                }
+            }
+
+            let mut got_i16 = vec![-1i16; len];
+            bytes::unpack_bits_i16(&mut ctx, &packed, bits, &mut got_i16);
+            let expected_i16: Vec<i16> = values.iter().map(|&v| v as u16 as i16).collect();
+            assert_eq!(got_i16, expected_i16, "bits = {bits}, len = {len}");
+
+            let mut got_i32 = vec![-1i32; len];
+            bytes::unpack_bits_i32(&mut ctx, &packed, bits, &mut got_i32);
+            let expected_i32: Vec<i32> = values.iter().map(|&v| v as i32).collect();
+            assert_eq!(got_i32, expected_i32, "bits = {bits}, len = {len}");
+        }
+    }
+}
+
+#[test]
+fn gather_plan() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1357);
+    let sources: Vec<Vec<u8>> = [3, 40, 64, 200, 1000]
+        .into_iter()
+        .map(|len| rng.vec_u8(len))
+        .collect();
+    let sources: Vec<&[u8]> = sources.iter().map(Vec::as_slice).collect();
+
+    for _ in 0..200 {
+        let mut plan = GatherPlan::new();
+        let mut expected = Vec::new();
+        while expected.len() < 64 {
+            let len = (rng.next() as usize % 20).min(64 - expected.len());
+            if rng.next().is_multiple_of(4) {
+                let byte = rng.next() as u8;
+                plan.fill(byte, len);
+                expected.extend(std::iter::repeat_n(byte, len));
+            } else {
+                let source = rng.next() as usize % sources.len();
+                let len = len.min(sources[source].len());
+                let start = rng.next() as usize % (sources[source].len() - len + 1);
+                plan.copy(source, start..start + len);
+                expected.extend_from_slice(&sources[source][start..start + len]);
+            }
+            if rng.next().is_multiple_of(8) {
+                break;
+            }
+        }
+        assert_eq!(plan.len(), expected.len());
+        expected.resize(64, 0);
+
+        let got = plan.gather(&mut ctx, &sources);
+        assert_eq!(got[..], expected[..], "{plan:?}");
+    }
+
+    // Adjacent ranges of a source share a load
+    let mut plan = GatherPlan::new();
+    plan.copy(4, 0..10).fill(1, 4).copy(4, 30..60).copy(3, 0..8);
+    assert_eq!(plan.num_loads(), 2);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "capi")]
+use amx::capi::{AmxStatus, amx_quantize_i16, amx_sconv, amx_sdot, amx_sgemm};
+
+#[test]
+fn sgemm() {
+    // A: 2x3 (lda = 4), B: 3x2, C: 2x2
+    let a = [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0];
+    let b = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let mut c = [1.0f32; 4];
+    let status = unsafe {
+        amx_sgemm(
+            2,
+            2,
+            3,
+            a.as_ptr(),
+            4,
+            b.as_ptr(),
+            2,
+            c.as_mut_ptr(),
+            2,
+            true,
+        )
+    };
+    assert_eq!(status, AmxStatus::Ok);
+    assert_eq!(c, [23.0, 29.0, 50.0, 65.0]);
+
+    // `lda < k`
+    let status = unsafe {
+        amx_sgemm(
+            2,
+            2,
+            3,
+            a.as_ptr(),
+            2,
+            b.as_ptr(),
+            2,
+            c.as_mut_ptr(),
+            2,
+            true,
+        )
+    };
+    assert_eq!(status, AmxStatus::InvalidArgument);
+}
+
+#[test]
+fn sdot() {
+    let x: Vec<f32> = (0..20).map(|i| i as f32).collect();
+    let mut out = 0.0;
+    let status = unsafe { amx_sdot(20, x.as_ptr(), x.as_ptr(), &mut out) };
+    assert_eq!(status, AmxStatus::Ok);
+    assert_eq!(out, 2470.0);
+
+    let status = unsafe { amx_sdot(20, x.as_ptr(), std::ptr::null(), &mut out) };
+    assert_eq!(status, AmxStatus::InvalidArgument);
+}
+
+#[test]
+fn sconv() {
+    let x = [1.0, 2.0, 3.0, 4.0];
+    let h = [1.0, 10.0];
+    let mut out = [0.0f32; 3];
+    let status = unsafe { amx_sconv(x.as_ptr(), 4, h.as_ptr(), 2, out.as_mut_ptr()) };
+    assert_eq!(status, AmxStatus::Ok);
+    assert_eq!(out, [12.0, 23.0, 34.0]);
+
+    let status = unsafe { amx_sconv(x.as_ptr(), 4, h.as_ptr(), 0, out.as_mut_ptr()) };
+    assert_eq!(status, AmxStatus::InvalidArgument);
+}
+
+#[test]
+fn quantize_i16() {
+    let x = [0.5, -0.25, 2.0];
+    let mut out = [0i16; 3];
+    let status = unsafe { amx_quantize_i16(x.as_ptr(), 3, 1.0 / 256.0, out.as_mut_ptr()) };
+    assert_eq!(status, AmxStatus::Ok);
+    assert_eq!(out, [128, -64, 512]);
+}
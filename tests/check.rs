@@ -0,0 +1,123 @@
+mod common;
+
+use amx::{Amx, AmxArgError, Index4, Normal, X8, XBytes, XRow, YBytes, YRow, ZRow};
+
+#[test]
+fn try_load_store() {
+    let mut ctx = common::ctx();
+    let input: [u8; 64] = std::array::from_fn(|i| i as u8);
+    let mut output = [0u8; 64];
+    unsafe {
+        assert_eq!(ctx.try_load512(input.as_ptr(), XRow(7)), Ok(()));
+        assert_eq!(ctx.try_store512(output.as_mut_ptr(), XRow(7)), Ok(()));
+        assert_eq!(output, input);
+
+        assert_eq!(
+            ctx.try_load512(input.as_ptr(), YRow(8)),
+            Err(AmxArgError::RowOutOfRange { index: 8, len: 8 })
+        );
+        assert_eq!(
+            ctx.try_store512(output.as_mut_ptr(), ZRow(64)),
+            Err(AmxArgError::RowOutOfRange { index: 64, len: 64 })
+        );
+        assert_eq!(
+            ctx.try_load512_interleaved(input.as_ptr(), ZRow(100)),
+            Err(AmxArgError::RowOutOfRange {
+                index: 100,
+                len: 64
+            })
+        );
+    }
+}
+
+#[test]
+fn try_outer_product() {
+    let mut ctx = common::ctx();
+    assert_eq!(
+        ctx.try_outer_product_f32_xy_to_z(Some(XBytes(0x1c0)), None, ZRow(3), false),
+        Ok(())
+    );
+    assert_eq!(
+        ctx.try_outer_product_f32_xy_to_z(Some(XBytes(0x200)), None, ZRow(0), false),
+        Err(AmxArgError::OffsetOutOfRange {
+            offset: 0x200,
+            len: 0x200
+        })
+    );
+    assert_eq!(
+        ctx.try_outer_product_i16_xy_to_z(None, Some(YBytes(0)), ZRow(64), true),
+        Err(AmxArgError::RowOutOfRange { index: 64, len: 64 })
+    );
+}
+
+#[test]
+fn try_lut() {
+    let mut ctx = common::ctx();
+    assert_eq!(
+        ctx.try_lut(XBytes(0), XRow(1), ZRow(2), (Normal, Index4, X8)),
+        Ok(())
+    );
+    assert_eq!(
+        ctx.try_lut(YBytes(512), XRow(1), ZRow(2), (Normal, Index4, X8)),
+        Err(AmxArgError::OffsetOutOfRange {
+            offset: 512,
+            len: 512
+        })
+    );
+    assert_eq!(
+        ctx.try_lut(XBytes(0), XRow(8), ZRow(2), (Normal, Index4, X8)),
+        Err(AmxArgError::RowOutOfRange { index: 8, len: 8 })
+    );
+    assert_eq!(
+        ctx.try_lut(XBytes(0), XRow(0), YRow(9), (Normal, Index4, X8)),
+        Err(AmxArgError::RowOutOfRange { index: 9, len: 8 })
+    );
+}
+
+#[test]
+fn amx_arg_error_display() {
+    assert_eq!(
+        AmxArgError::RowOutOfRange { index: 9, len: 8 }.to_string(),
+        "register row index 9 is out of range 0..8"
+    );
+}
+
+#[test]
+fn try_extract() {
+    let mut ctx = common::ctx();
+    assert_eq!(ctx.try_extract_z_to_x(ZRow(63), XBytes(0x1ff)), Ok(()));
+    assert_eq!(
+        ctx.try_extract_z_to_y(ZRow(0), YBytes(0x200)),
+        Err(AmxArgError::OffsetOutOfRange {
+            offset: 0x200,
+            len: 0x200
+        })
+    );
+    assert_eq!(
+        ctx.try_extract_z_to_x(ZRow(64), XBytes(0)),
+        Err(AmxArgError::RowOutOfRange { index: 64, len: 64 })
+    );
+}
+
+#[test]
+fn checked_constructors() {
+    assert_eq!(XRow::new::<7>(), XRow(7));
+    assert_eq!(ZRow::new::<63>(), ZRow(63));
+    assert_eq!(YBytes::new::<0x1ff>(), YBytes(0x1ff));
+    const Z: ZRow = ZRow::new::<5>();
+    assert_eq!(Z, ZRow(5));
+
+    assert_eq!(YRow::try_new(7), Ok(YRow(7)));
+    assert_eq!(
+        YRow::try_new(8),
+        Err(AmxArgError::RowOutOfRange { index: 8, len: 8 })
+    );
+    assert_eq!(
+        XBytes::try_new(0x200),
+        Err(AmxArgError::OffsetOutOfRange {
+            offset: 0x200,
+            len: XBytes::LEN
+        })
+    );
+    assert_eq!(ZRow::LEN, 64);
+}
@@ -0,0 +1,63 @@
+//! Helpers shared by the integration tests
+
+// Each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+/// Construct the context the tests run on: [`amx::AmxCtx`] on AArch64 and
+/// [`amx::AmxEmuCtx`] elsewhere, so that the tests run on any system.
+#[cfg(target_arch = "aarch64")]
+pub fn ctx() -> amx::AmxCtx {
+    amx::AmxCtx::new().unwrap()
+}
+
+/// Construct the context the tests run on: [`amx::AmxCtx`] on AArch64 and
+/// [`amx::AmxEmuCtx`] elsewhere, so that the tests run on any system.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn ctx() -> amx::AmxEmuCtx {
+    amx::AmxEmuCtx::default()
+}
+
+/// A xorshift PRNG generating reproducible test inputs
+pub struct Xorshift32(pub u32);
+
+impl Xorshift32 {
+    pub fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Generate a multiple of 0.001 in range `-1.0..=1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next() % 2001) as f32 / 1000.0 - 1.0
+    }
+
+    pub fn vec_f32(&mut self, len: usize) -> Vec<f32> {
+        (0..len).map(|_| self.next_f32()).collect()
+    }
+}
+
+/// Assert that `got` matches `expected` within a tolerance of `tol` relative
+/// to `1 + |expected|`.
+#[track_caller]
+pub fn assert_close(got: &[f32], expected: &[f32], tol: f32) {
+    for (i, (&g, &e)) in got.iter().zip(expected).enumerate() {
+        assert!(
+            (g - e).abs() <= tol * (1.0 + e.abs()),
+            "mismatch at {i}: got = {g}, expected = {e}"
+        );
+    }
+}
+
+/// Assert that `got` matches `expected` within an absolute tolerance of
+/// `tol`.
+#[track_caller]
+pub fn assert_close_abs(got: &[f32], expected: &[f32], tol: f32) {
+    for (i, (&g, &e)) in got.iter().zip(expected).enumerate() {
+        assert!(
+            (g - e).abs() <= tol,
+            "mismatch at {i}: got = {g}, expected = {e}"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use amx::AmxVersion;
+
+#[test]
+fn version_from_brand_string() {
+    for (chip, version) in [
+        ("Apple M1", Some(AmxVersion::M1)),
+        ("Apple M1 Max", Some(AmxVersion::M1)),
+        ("Apple M2 Pro", Some(AmxVersion::M2)),
+        ("Apple M3", Some(AmxVersion::M3)),
+        ("Apple M4 Max", Some(AmxVersion::M3)),
+        ("Apple M10", Some(AmxVersion::M3)),
+        ("Apple M", None),
+        ("Apple A17 Pro", None),
+        ("Intel(R) Core(TM) i9-9880H CPU @ 2.30GHz", None),
+    ] {
+        assert_eq!(AmxVersion::from_brand_string(chip), version, "{chip}");
+    }
+    assert!(AmxVersion::M1 < AmxVersion::M3);
+}
+
+#[test]
+fn detection_is_consistent() {
+    if amx::version().is_some() {
+        assert!(amx::is_available());
+    }
+    if !cfg!(target_arch = "aarch64") {
+        assert!(!amx::is_available());
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::{Arc, Mutex};
+
+use amx::{
+    Amx, AmxEmuCtx, AmxOps, Fma64Operand, Index4, MatIntOp, MatIntTy, Normal, RegFile, VecFpAluOp,
+    VecFpTy, VecIntOp, VecIntTy, X16, XBytes, XRow, YBytes, ZRow,
+    disasm::{DecodedInstr, decode},
+    jit::JitOp,
+};
+
+#[test]
+fn decode_trace() {
+    let mut ctx = AmxEmuCtx::default();
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    ctx.set_hook({
+        let trace = Arc::clone(&trace);
+        move |instr, _| trace.lock().unwrap().push(*instr)
+    });
+
+    let operand = Fma64Operand::new()
+        .x_offset(Some(XBytes(8)))
+        .y_offset(None)
+        .z_row(ZRow(9))
+        .vector(true);
+    ctx.fms64(operand.encode());
+    ctx.vector_int(
+        VecIntOp::new(VecIntTy::I8).accumulate(false).shift(3),
+        XBytes(1),
+        YBytes(2),
+        ZRow(3),
+    );
+    ctx.vector_fp(
+        VecFpTy::F16,
+        VecFpAluOp::Max,
+        XBytes(64),
+        YBytes(0),
+        ZRow(4),
+    );
+    ctx.matrix_int(
+        MatIntOp::new(MatIntTy::U16).widen(true).x_lanes(8),
+        XBytes(0),
+        YBytes(128),
+        ZRow(1),
+    );
+    ctx.lut(YBytes(32), XRow(2), ZRow(40), (Normal, Index4, X16));
+    ctx.extract_z_to_y(ZRow(7), YBytes(448));
+
+    let text: Vec<String> = trace
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|instr| decode(instr.op as u8, instr.operand).to_string())
+        .collect();
+    assert_eq!(
+        text,
+        [
+            "fms64 z9 -= x[8..72] (vector)",
+            "vecint.i8 z3 = (x[1..65] * y[2..66]) >> 3",
+            "vecfp.f16 z4 = max(z4, x[64..128])",
+            "matint.u16 z1 += x[0..64] * y[128..192] (wide) (x_lanes=8)",
+            "genlut z40 = lut(x2, y[32..96], mode 12)",
+            "extry y[448..512] = z7",
+        ]
+    );
+}
+
+#[test]
+fn decode_fields() {
+    let DecodedInstr::Mem {
+        op,
+        row,
+        pair,
+        addr,
+    } = decode(JitOp::Ldy as u8, (1 << 62) | (6 << 56) | 0x1234)
+    else {
+        panic!("not a memory instruction");
+    };
+    assert_eq!((op, row, pair, addr), (JitOp::Ldy, 6, true, 0x1234));
+
+    let DecodedInstr::Genlut {
+        input,
+        output,
+        output_row,
+        ..
+    } = decode(JitOp::Genlut as u8, (1 << 25) | (5 << 20))
+    else {
+        panic!("not `genlut`");
+    };
+    assert_eq!((input, output, output_row), (RegFile::X, RegFile::Y, 5));
+
+    // Unsupported ALU modes and unknown opcodes are kept as raw operands
+    assert_eq!(
+        decode(JitOp::Vecint as u8, 9 << 47),
+        DecodedInstr::Unknown {
+            opcode: 18,
+            operand: 9 << 47
+        }
+    );
+    assert_eq!(decode(18, 9 << 47).to_string(), "vecint 0x4800000000000");
+
+    // `bf16` is only decoded with the `amx2` feature
+    let bf16 = decode(JitOp::Matfp as u8, 1 << 42);
+    if cfg!(feature = "amx2") {
+        assert!(matches!(bf16, DecodedInstr::MatFp(_)), "{bf16:?}");
+    } else {
+        assert_eq!(
+            bf16,
+            DecodedInstr::Unknown {
+                opcode: JitOp::Matfp as u8,
+                operand: 1 << 42
+            }
+        );
+    }
+    assert_eq!(decode(31, 1).to_string(), "amx31 0x1");
+}
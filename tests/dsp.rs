@@ -0,0 +1,418 @@
+mod common;
+
+use amx::{
+    dsp::{self, Cq15, Rounding},
+    linalg::{MatMut, MatRef, SplitComplex},
+};
+use common::Xorshift32;
+
+impl Xorshift32 {
+    fn next_cq15(&mut self) -> Cq15 {
+        let x = self.next();
+        Cq15::new(x as i16, (x >> 16) as i16)
+    }
+}
+
+fn narrow(x: i64, shift: u32, rounding: Rounding) -> i16 {
+    let x = match rounding {
+        Rounding::Truncate => x >> shift,
+        Rounding::Nearest if shift > 0 => (x + (1 << (shift - 1))) >> shift,
+        Rounding::Nearest => x,
+    };
+    x.clamp(i16::MIN as i64, i16::MAX as i64) as i16
+}
+
+fn cmul_wide(x: Cq15, y: Cq15, conj_y: bool) -> (i64, i64) {
+    let (xr, xi) = (x.re as i64, x.im as i64);
+    let (yr, yi) = (
+        y.re as i64,
+        if conj_y { -(y.im as i64) } else { y.im as i64 },
+    );
+    (xr * yr - xi * yi, xr * yi + xi * yr)
+}
+
+#[test]
+fn cq15_outer_product() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x114514);
+
+    for &(m, n) in &[(1, 1), (16, 16), (17, 5), (40, 33)] {
+        let mut x: Vec<Cq15> = (0..m).map(|_| rng.next_cq15()).collect();
+        let y: Vec<Cq15> = (0..n).map(|_| rng.next_cq15()).collect();
+        // Full-scale corner case
+        x[0] = Cq15::new(i16::MIN, i16::MIN);
+
+        for &conj_y in &[false, true] {
+            for &rounding in &[Rounding::Truncate, Rounding::Nearest] {
+                let mut got = vec![Cq15::default(); m * n];
+                dsp::cq15_outer_product(
+                    &mut ctx,
+                    &x,
+                    &y,
+                    MatMut::new(&mut got, n, m),
+                    conj_y,
+                    rounding,
+                );
+
+                for j in 0..n {
+                    for i in 0..m {
+                        let (re, im) = cmul_wide(x[i], y[j], conj_y);
+                        let expected =
+                            Cq15::new(narrow(re, 15, rounding), narrow(im, 15, rounding));
+                        assert_eq!(got[j * m + i], expected, "(j, i) = {:?}", (j, i));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cq15_xcorr() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1919);
+
+    for &(num_lags, num_taps) in &[(1usize, 1usize), (16, 3), (37, 16), (5, 100)] {
+        let x: Vec<Cq15> = (0..num_lags + num_taps - 1)
+            .map(|_| rng.next_cq15())
+            .collect();
+        let h: Vec<Cq15> = (0..num_taps).map(|_| rng.next_cq15()).collect();
+        let headroom = num_taps.next_power_of_two().trailing_zeros();
+
+        for &conj_h in &[false, true] {
+            for &rounding in &[Rounding::Truncate, Rounding::Nearest] {
+                let mut got = vec![Cq15::default(); num_lags];
+                dsp::cq15_xcorr(&mut ctx, &x, &h, &mut got, conj_h, rounding);
+
+                for (l, &got) in got.iter().enumerate() {
+                    // The products are individually pre-shifted by `headroom`
+                    let (mut rr, mut ii, mut ri, mut ir) = (0i64, 0i64, 0i64, 0i64);
+                    for (k, &h) in h.iter().enumerate() {
+                        let x = x[l + k];
+                        rr += (x.re as i64 * h.re as i64) >> headroom;
+                        ii += (x.im as i64 * h.im as i64) >> headroom;
+                        ri += (x.re as i64 * h.im as i64) >> headroom;
+                        ir += (x.im as i64 * h.re as i64) >> headroom;
+                    }
+                    let (re, im) = if conj_h {
+                        (rr + ii, ir - ri)
+                    } else {
+                        (rr - ii, ir + ri)
+                    };
+                    let shift = 15 - headroom;
+                    let expected =
+                        Cq15::new(narrow(re, shift, rounding), narrow(im, shift, rounding));
+                    assert_eq!(got, expected, "lag = {}", l);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn conv_f32() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x114514);
+    let mut next_f32 = || (rng.next() % 2001) as f32 / 1000.0 - 1.0;
+
+    for &(num_out, num_taps) in &[(1usize, 1usize), (16, 3), (37, 16), (5, 100)] {
+        let x: Vec<f32> = (0..num_out + num_taps - 1).map(|_| next_f32()).collect();
+        let h: Vec<f32> = (0..num_taps).map(|_| next_f32()).collect();
+
+        let mut got = vec![0.0; num_out];
+        dsp::conv_f32(&mut ctx, &x, &h, &mut got);
+
+        for (l, &got) in got.iter().enumerate() {
+            let expected: f32 = (0..num_taps).map(|k| x[l + k] * h[num_taps - 1 - k]).sum();
+            assert!(
+                (got - expected).abs() <= 1e-4 * (1.0 + expected.abs()),
+                "output {l}: got = {got}, expected = {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn quantize_i16() {
+    let x = [0.0, 0.49, 0.5, -0.5, -1.26, 1e9, -1e9, f32::NAN];
+    let mut out = [0i16; 8];
+    dsp::quantize_i16(&x, 0.25, &mut out);
+    assert_eq!(out, [0, 2, 2, -2, -5, i16::MAX, i16::MIN, 0]);
+}
+
+#[test]
+fn resampler_sine() {
+    let mut ctx = common::ctx();
+    let (from_rate, to_rate) = (44_100, 48_000);
+    let freq = 1000.0;
+    let input: Vec<f32> = (0..4410)
+        .map(|n| (2.0 * std::f64::consts::PI * freq * n as f64 / from_rate as f64).sin() as f32)
+        .collect();
+
+    let mut resampler = dsp::Resampler::new(from_rate, to_rate, 32);
+    assert_eq!(resampler.ratio(), (160, 147));
+    assert_eq!(resampler.taps_per_phase(), 32);
+    let mut out = Vec::new();
+    resampler.process(&mut ctx, &input, &mut out);
+    assert_eq!(out.len(), 4800);
+
+    // Skip the filter's warm-up period
+    let delay = resampler.delay();
+    for (o, &got) in out.iter().enumerate().skip(100) {
+        let t = o as f64 / to_rate as f64 - delay / from_rate as f64;
+        let expected = (2.0 * std::f64::consts::PI * freq * t).sin();
+        assert!(
+            (got as f64 - expected).abs() < 1e-3,
+            "output {o}: got = {got}, expected = {expected}"
+        );
+    }
+}
+
+#[test]
+fn resampler_chunked() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1234);
+    let input: Vec<f32> = (0..1000)
+        .map(|_| (rng.next() % 2001) as f32 / 1000.0 - 1.0)
+        .collect();
+
+    for &(from_rate, to_rate, taps) in &[(48_000, 44_100, 24), (3, 1, 16), (1, 2, 48)] {
+        let mut resampler = dsp::Resampler::new(from_rate, to_rate, taps);
+        let mut expected = Vec::new();
+        resampler.process(&mut ctx, &input, &mut expected);
+
+        resampler.reset();
+        let mut got = Vec::new();
+        for chunk in input.chunks(37) {
+            resampler.process(&mut ctx, chunk, &mut got);
+        }
+        assert_eq!(got, expected);
+        let (up, down) = resampler.ratio();
+        assert_eq!(got.len(), (input.len() * up).div_ceil(down));
+    }
+}
+
+#[test]
+fn resampler_large_decimation() {
+    let mut ctx = common::ctx();
+    let input: Vec<f32> = (0..3000).map(|i| (i % 17) as f32).collect();
+
+    let mut resampler = dsp::Resampler::new(1000, 1, 16);
+    let mut expected = Vec::new();
+    resampler.process(&mut ctx, &input, &mut expected);
+    assert_eq!(expected.len(), 3);
+
+    // Each window steps past the end of the 20-sample chunks
+    resampler.reset();
+    let mut got = Vec::new();
+    for chunk in input.chunks(20) {
+        resampler.process(&mut ctx, chunk, &mut got);
+    }
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn partitioned_convolver() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xbeef);
+    let mut next_f32 = || (rng.next() % 2001) as f32 / 1000.0 - 1.0;
+
+    for &(ir_len, block_size) in &[(1usize, 1usize), (100, 16), (1000, 64), (300, 256)] {
+        let ir: Vec<f32> = (0..ir_len).map(|_| next_f32()).collect();
+        let x: Vec<f32> = (0..2000).map(|_| next_f32()).collect();
+
+        let mut conv = dsp::PartitionedConvolver::new(&ir, block_size);
+        assert_eq!(conv.num_partitions(), ir_len.div_ceil(block_size));
+        let latency = conv.latency();
+        let mut got = vec![0.0; x.len()];
+        for (x, got) in x.chunks(37).zip(got.chunks_mut(37)) {
+            conv.process(&mut ctx, x, got);
+        }
+
+        for (n, &got) in got.iter().enumerate() {
+            let expected: f32 = (0..ir_len)
+                .filter(|&k| n >= latency + k)
+                .map(|k| ir[k] * x[n - latency - k])
+                .sum();
+            assert!(
+                (got - expected).abs() <= 1e-3 * (1.0 + expected.abs()),
+                "ir_len = {ir_len}, block_size = {block_size}, output {n}: \
+                 got = {got}, expected = {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn spatial_covariance() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5eed);
+    let mut next_f32 = || (rng.next() % 2001) as f32 / 1000.0 - 1.0;
+
+    for &(t, m) in &[(1usize, 1usize), (37, 20), (5, 40)] {
+        let xr: Vec<f32> = (0..t * m).map(|_| next_f32()).collect();
+        let xi: Vec<f32> = (0..t * m).map(|_| next_f32()).collect();
+        for accumulate in [false, true] {
+            let mut rr = vec![1.0; m * m];
+            let mut ri = vec![1.0; m * m];
+            dsp::spatial_covariance(
+                &mut ctx,
+                SplitComplex {
+                    re: MatRef::new(&xr, t, m),
+                    im: MatRef::new(&xi, t, m),
+                },
+                SplitComplex {
+                    re: MatMut::new(&mut rr, m, m),
+                    im: MatMut::new(&mut ri, m, m),
+                },
+                accumulate,
+            );
+
+            let init = if accumulate { 1.0 } else { 0.0 };
+            for i in 0..m {
+                for j in 0..m {
+                    let (mut er, mut ei) = (init, init);
+                    for tt in 0..t {
+                        let (ar, ai) = (xr[tt * m + i], xi[tt * m + i]);
+                        let (br, bi) = (xr[tt * m + j], -xi[tt * m + j]);
+                        er += ar * br - ai * bi;
+                        ei += ar * bi + ai * br;
+                    }
+                    let (gr, gi) = (rr[i * m + j], ri[i * m + j]);
+                    assert!(
+                        (gr - er).abs() < 1e-3 && (gi - ei).abs() < 1e-3,
+                        "(t, m) = {:?}, r[{i}][{j}]: got = {:?}, expected = {:?}",
+                        (t, m),
+                        (gr, gi),
+                        (er, ei)
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn apply_beamforming_weights() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xfeed);
+    let mut next_f32 = || (rng.next() % 2001) as f32 / 1000.0 - 1.0;
+
+    let (t, m, b) = (50, 18, 3);
+    let xr: Vec<f32> = (0..t * m).map(|_| next_f32()).collect();
+    let xi: Vec<f32> = (0..t * m).map(|_| next_f32()).collect();
+    let wr: Vec<f32> = (0..m * b).map(|_| next_f32()).collect();
+    let wi: Vec<f32> = (0..m * b).map(|_| next_f32()).collect();
+    let mut yr = vec![0.0; t * b];
+    let mut yi = vec![0.0; t * b];
+    dsp::apply_beamforming_weights(
+        &mut ctx,
+        SplitComplex {
+            re: MatRef::new(&xr, t, m),
+            im: MatRef::new(&xi, t, m),
+        },
+        SplitComplex {
+            re: MatRef::new(&wr, m, b),
+            im: MatRef::new(&wi, m, b),
+        },
+        SplitComplex {
+            re: MatMut::new(&mut yr, t, b),
+            im: MatMut::new(&mut yi, t, b),
+        },
+        false,
+    );
+
+    for tt in 0..t {
+        for bb in 0..b {
+            let (mut er, mut ei) = (0.0, 0.0);
+            for i in 0..m {
+                // conj(w) * x
+                let (ar, ai) = (wr[i * b + bb], -wi[i * b + bb]);
+                let (br, bi) = (xr[tt * m + i], xi[tt * m + i]);
+                er += ar * br - ai * bi;
+                ei += ar * bi + ai * br;
+            }
+            let (gr, gi) = (yr[tt * b + bb], yi[tt * b + bb]);
+            assert!(
+                (gr - er).abs() < 1e-3 && (gi - ei).abs() < 1e-3,
+                "out[{tt}][{bb}]: got = {:?}, expected = {:?}",
+                (gr, gi),
+                (er, ei)
+            );
+        }
+    }
+}
+
+#[test]
+fn ofdm_equalize() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x0fd3);
+    let mut next_f32 = || (rng.next() % 2001) as f32 / 1000.0 - 1.0;
+
+    let (s, k) = (11, 70);
+    let xr: Vec<f32> = (0..s * k).map(|_| next_f32()).collect();
+    let xi: Vec<f32> = (0..s * k).map(|_| next_f32()).collect();
+    let hr: Vec<f32> = (0..k).map(|_| next_f32()).collect();
+    let hi: Vec<f32> = (0..k).map(|_| next_f32()).collect();
+    let mut yr = vec![0.0; s * k];
+    let mut yi = vec![0.0; s * k];
+    dsp::ofdm_equalize(
+        &mut ctx,
+        SplitComplex {
+            re: MatRef::new(&xr, s, k),
+            im: MatRef::new(&xi, s, k),
+        },
+        SplitComplex { re: &hr, im: &hi },
+        SplitComplex {
+            re: MatMut::new(&mut yr, s, k),
+            im: MatMut::new(&mut yi, s, k),
+        },
+    );
+
+    for i in 0..s * k {
+        let (er, ei) = (
+            xr[i] * hr[i % k] - xi[i] * hi[i % k],
+            xr[i] * hi[i % k] + xi[i] * hr[i % k],
+        );
+        assert!(
+            (yr[i] - er).abs() < 1e-5 && (yi[i] - ei).abs() < 1e-5,
+            "element {i}: got = {:?}, expected = {:?}",
+            (yr[i], yi[i]),
+            (er, ei)
+        );
+    }
+}
+
+#[test]
+fn branch_metrics() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7e7b1);
+
+    for &(rate, steps) in &[(1usize, 5usize), (2, 70), (3, 32), (5, 33)] {
+        let soft: Vec<i16> = (0..steps * rate).map(|_| rng.next() as i16).collect();
+        let num_symbols = 1 << rate;
+        let mut metrics = vec![0i32; steps * num_symbols];
+        dsp::branch_metrics(
+            &mut ctx,
+            &soft,
+            rate,
+            MatMut::new(&mut metrics, steps, num_symbols),
+        );
+
+        for t in 0..steps {
+            for c in 0..num_symbols {
+                let expected: i32 = (0..rate)
+                    .map(|j| {
+                        let s = soft[t * rate + j] as i32;
+                        if c >> j & 1 != 0 { -s } else { s }
+                    })
+                    .sum();
+                assert_eq!(
+                    metrics[t * num_symbols + c],
+                    expected,
+                    "rate = {rate}, t = {t}, c = {c}"
+                );
+            }
+        }
+    }
+}
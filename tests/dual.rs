@@ -0,0 +1,88 @@
+#![cfg(feature = "dual")]
+use amx::{Amx, AmxEmuCtx, DualCtx, OnDivergence, RegFile, XBytes, XRow, YBytes, YRow, ZRow};
+
+#[test]
+#[cfg(target_arch = "aarch64")]
+fn native_matches_emulator() {
+    let mut ctx = DualCtx::new().unwrap();
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 * 0.75 - 3.0);
+    let y: [f32; 16] = std::array::from_fn(|i| 1.0 / (i as f32 + 1.0));
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), true);
+    ctx.extract_z_to_x(ZRow(4), XBytes(64));
+}
+
+#[test]
+#[cfg(target_arch = "aarch64")]
+fn native_sgemm_matches_emulator() {
+    use amx::linalg::{self, MatMut, MatRef};
+
+    let (m, n, k) = (40, 36, 300);
+    let a: Vec<f32> = (0..m * k).map(|i| (i as f32 * 0.37).sin()).collect();
+    let b: Vec<f32> = (0..k * n).map(|i| (i as f32 * 0.11).cos()).collect();
+    let mut c = vec![0.0; m * n];
+    // Panics on the first instruction after which the register files differ
+    linalg::sgemm(
+        &mut DualCtx::new().unwrap(),
+        MatRef::new(&a, m, k),
+        MatRef::new(&b, k, n),
+        MatMut::new(&mut c, m, n),
+        false,
+    );
+}
+
+#[test]
+fn backends_agree() {
+    let mut ctx = DualCtx::with_backends(AmxEmuCtx::default(), AmxEmuCtx::default());
+    let x: [i16; 32] = std::array::from_fn(|i| i as i16 - 16);
+    ctx.load512_slice(&x, XRow(2));
+    ctx.load512_slice(&x, YRow(5));
+    ctx.outer_product_i16_xy_to_z(Some(XBytes(128)), Some(YBytes(320)), ZRow(1), false);
+
+    let mut out = [0i16; 32];
+    ctx.store512_slice(&mut out, ZRow(5));
+    assert_eq!(out, x.map(|v| v * x[2]));
+    assert!(ctx.take_divergences().is_empty());
+}
+
+#[test]
+fn record_divergences() {
+    let mut ctx = DualCtx::with_backends(AmxEmuCtx::default(), AmxEmuCtx::default())
+        .on_divergence(OnDivergence::Record);
+    ctx.load512_slice(&[1.0f32; 16], XRow(0));
+    ctx.load512_slice(&[2.0f32; 16], YRow(0));
+    ctx.right_mut().y_mut()[4..8].copy_from_slice(&3.0f32.to_ne_bytes());
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+
+    // The divergence in Y is reported once, along with the Z row it causes
+    // to differ, and the backends are resynchronized afterwards
+    let divergences = ctx.take_divergences();
+    assert_eq!(divergences.len(), 1);
+    let diffs = &divergences[0].diffs;
+    assert_eq!(
+        diffs.iter().map(|d| (d.file, d.row)).collect::<Vec<_>>(),
+        [(RegFile::Y, 0), (RegFile::Z, 4)]
+    );
+    assert_eq!(diffs[1].left, bytes_of(&[2.0f32; 16]));
+    assert_eq!(diffs[1].right, bytes_of(&[3.0f32; 16]));
+    assert_eq!(ctx.left().y(), ctx.right().y());
+}
+
+#[test]
+#[should_panic = "`fma32 z0 = x[0..64] * y[0..64]` diverged in y0 (bytes [6, 7])"]
+fn panic_on_divergence() {
+    let mut ctx = DualCtx::with_backends(AmxEmuCtx::default(), AmxEmuCtx::default());
+    ctx.right_mut().y_mut()[4..8].copy_from_slice(&3.0f32.to_ne_bytes());
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+}
+
+fn bytes_of(values: &[f32; 16]) -> [u8; 64] {
+    let mut out = [0; 64];
+    for (chunk, v) in out.chunks_exact_mut(4).zip(values) {
+        chunk.copy_from_slice(&v.to_ne_bytes());
+    }
+    out
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use amx::{Amx, DumpFormat, RegFile, XRow, ZRow};
+
+#[test]
+fn dump_layout() {
+    let mut ctx = common::ctx();
+    ctx.write_x(&[0; 512]);
+    ctx.write_y(&[0; 512]);
+    ctx.write_z(&[0; 4096]);
+    ctx.load512_slice(&[0xabu8; 64], XRow(7));
+
+    let text = ctx.dump().to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    // 8 + 8 + 64 rows and two separating blank lines
+    assert_eq!(lines.len(), 8 + 1 + 8 + 1 + 64);
+    assert_eq!(lines[0], format!("x0:{}", " 00".repeat(64)));
+    assert_eq!(lines[7], format!("x7:{}", " ab".repeat(64)));
+    assert_eq!(lines[8], "");
+    assert!(lines[9].starts_with("y0: 00"));
+    assert!(lines[18].starts_with("z0:  00"));
+    assert!(lines[81].starts_with("z63: 00"));
+}
+
+#[test]
+fn dump_formats() {
+    let mut ctx = common::ctx();
+    ctx.write_z(&[0; 4096]);
+    let mut row = [0.0f32; 16];
+    row[0] = 1.5;
+    row[15] = -256.0;
+    ctx.load512_slice(&row, ZRow(2));
+
+    let dump = ctx.dump();
+    let z = dump
+        .clone()
+        .format(DumpFormat::F32)
+        .file(RegFile::Z)
+        .to_string();
+    let line = z.lines().nth(2).unwrap();
+    let cells: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!(cells[0], "z2:");
+    assert_eq!(cells[1], "1.5");
+    assert_eq!(cells[16], "-256.0");
+    // Aligned to the widest cell
+    assert!(line.starts_with("z2:     1.5    0.0"), "{line}");
+
+    let z = dump
+        .clone()
+        .format(DumpFormat::BF16)
+        .file(RegFile::Z)
+        .to_string();
+    let cells: Vec<&str> = z.lines().nth(2).unwrap().split_whitespace().collect();
+    // The upper half of 1.5f32 is 1.5bf16
+    assert_eq!(cells[2], "1.5");
+
+    let z = dump.format(DumpFormat::U32).file(RegFile::Z).to_string();
+    let cells: Vec<&str> = z.lines().nth(2).unwrap().split_whitespace().collect();
+    assert_eq!(cells[1], 1.5f32.to_bits().to_string());
+}
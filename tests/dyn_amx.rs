@@ -0,0 +1,144 @@
+mod common;
+
+use amx::{
+    Amx, AmxFallbackCtx, DynAmx, NewAmxCtxError, SendableAmxState, XBytes, XRow, YBytes, YRow,
+    ZRow,
+    linalg::{self, MatMut, MatRef},
+};
+
+fn outer_product(ctx: &mut impl Amx, x: &[f32; 16], y: &[f32; 16]) -> [[f32; 16]; 16] {
+    let mut out = [[0.0; 16]; 16];
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(3));
+        ctx.load512(y.as_ptr(), YRow(5));
+    }
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(3 * 64)), Some(YBytes(5 * 64)), ZRow(2), false);
+    for (j, row) in out.iter_mut().enumerate() {
+        unsafe { ctx.store512(row.as_mut_ptr(), ZRow(j * 4 + 2)) };
+    }
+    out
+}
+
+#[test]
+fn dyn_amx_boxed_backend() {
+    let mut backend: Box<dyn DynAmx> = Box::new(common::ctx());
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let y: [f32; 16] = std::array::from_fn(|i| 2.0 - i as f32);
+    let z = outer_product(&mut backend, &x, &y);
+    for (j, row) in z.iter().enumerate() {
+        for (i, &z) in row.iter().enumerate() {
+            assert_eq!(z, x[i] * y[j]);
+        }
+    }
+
+    // Dropping the backend releases the AMX context
+    drop(backend);
+    let _ctx = common::ctx();
+}
+
+#[test]
+fn dyn_amx_borrowed_backend() {
+    let mut ctx = common::ctx();
+    let mut backend: &mut dyn DynAmx = &mut ctx;
+    let x = [1.5; 16];
+    let y = [-2.0; 16];
+    let z = outer_product(&mut backend, &x, &y);
+    assert_eq!(z, [[-3.0; 16]; 16]);
+}
+
+#[test]
+fn fallback_ctx() {
+    let mut ctx = AmxFallbackCtx::try_new().unwrap();
+    assert_eq!(ctx.is_native(), amx::is_available());
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 - 4.0);
+    let y: [f32; 16] = std::array::from_fn(|i| 0.5 * i as f32);
+    let z = outer_product(&mut ctx, &x, &y);
+    for (j, row) in z.iter().enumerate() {
+        for (i, &z) in row.iter().enumerate() {
+            assert_eq!(z, x[i] * y[j]);
+        }
+    }
+
+    // Can be stored along with other backends
+    let backends: Vec<Box<dyn DynAmx>> = vec![Box::new(ctx), Box::new(amx::AmxEmuCtx::default())];
+    assert_eq!(backends.len(), 2);
+}
+
+#[test]
+fn dyn_amx_struct_field() {
+    struct Engine {
+        ctx: Box<dyn DynAmx>,
+    }
+
+    let mut engines = [
+        Engine {
+            ctx: Box::new(AmxFallbackCtx::try_new().unwrap()),
+        },
+        Engine {
+            ctx: Box::new(amx::AmxEmuCtx::default()),
+        },
+    ];
+    let a: [f32; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let b: [f32; 6] = [1.0, 0.0, -1.0, 0.5, 1.0, 2.0];
+    for engine in &mut engines {
+        let mut c = [0.0f32; 9];
+        linalg::sgemm(
+            &mut *engine.ctx,
+            MatRef::new(&a, 3, 2),
+            MatRef::new(&b, 2, 3),
+            MatMut::new(&mut c, 3, 3),
+            false,
+        );
+        assert_eq!(c, [2.0, 2.0, 3.0, 5.0, 4.0, 5.0, 8.0, 6.0, 7.0]);
+    }
+}
+
+#[test]
+fn scoped_ctx() {
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let z = AmxFallbackCtx::with(|ctx| outer_product(ctx, &x, &[2.0; 16])).unwrap();
+    assert_eq!(z[0][15], 30.0);
+
+    // The context is released even if the closure panics
+    let result = std::panic::catch_unwind(|| {
+        AmxFallbackCtx::with(|_| panic!("oops")).unwrap();
+    });
+    assert!(result.is_err());
+    assert!(AmxFallbackCtx::with(|ctx| ctx.read_z()[0]).is_ok());
+}
+
+#[test]
+fn nested_scoped_ctx() {
+    let nested = AmxFallbackCtx::with(|_| AmxFallbackCtx::with(|_| ()));
+    if amx::is_available() {
+        // A second native context would clobber the first one's state
+        assert_eq!(nested, Ok(Err(NewAmxCtxError::AlreadyActive)));
+    } else {
+        assert_eq!(nested, Ok(Ok(())));
+    }
+    // Released when the outer scope ends
+    assert!(AmxFallbackCtx::with(|_| ()).is_ok());
+}
+
+#[test]
+fn sendable_state() {
+    let mut ctx = common::ctx();
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let z = outer_product(&mut ctx, &x, &[-1.0; 16]);
+    let state = SendableAmxState::take(ctx);
+
+    let state = std::thread::spawn(move || {
+        let mut ctx = state.into_fallback_ctx().unwrap();
+        let mut row = [0.0f32; 16];
+        ctx.store512_slice(&mut row, ZRow(2));
+        assert_eq!(row, z[0]);
+        SendableAmxState::take(ctx)
+    })
+    .join()
+    .unwrap();
+
+    // The source thread can create a new context
+    let mut ctx = common::ctx();
+    ctx.restore(state.state());
+    assert_eq!(ctx.snapshot(), state.into_state());
+}
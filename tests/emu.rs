@@ -0,0 +1,621 @@
+use std::sync::{Arc, Mutex};
+
+use amx::{
+    Amx, AmxEmuCtx, AmxEmuGeometry, AmxOps, AmxVersion, ChromeTrace, Disassembly, FpSemantics,
+    Instr, RegFile, VecFpAluOp, VecFpTy, XBytes, XRow, YBytes, YRow, ZRow, find_divergence,
+    jit::JitOp,
+};
+
+#[test]
+fn default_geometry() {
+    let ctx = AmxEmuCtx::default();
+    assert_eq!(ctx.geometry(), AmxEmuGeometry::M1);
+    assert_eq!(ctx.x().len(), 512);
+    assert_eq!(ctx.y().len(), 512);
+    assert_eq!(ctx.z().len(), 64);
+}
+
+#[test]
+fn outer_product_f32_z128() {
+    let mut ctx = AmxEmuCtx::new(AmxEmuGeometry {
+        z_rows: 128,
+        ..AmxEmuGeometry::M1
+    });
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let y: [f32; 16] = std::array::from_fn(|i| (i + 100) as f32);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(5), false);
+
+    // Z is divided into eight interleaved 16x16 tiles
+    for (row, z) in ctx.z().iter().enumerate() {
+        let z: Vec<f32> = z
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let expected: Vec<f32> = if row % 8 == 5 {
+            x.iter().map(|&x| x * y[row / 8]).collect()
+        } else {
+            vec![0.0; 16]
+        };
+        assert_eq!(z, expected, "row {row}");
+    }
+}
+
+#[test]
+fn load_store_wraps_small_x() {
+    let mut ctx = AmxEmuCtx::new(AmxEmuGeometry {
+        x_rows: 4,
+        ..AmxEmuGeometry::M1
+    });
+    let input: [u8; 64] = std::array::from_fn(|i| i as u8);
+    let mut output = [0u8; 64];
+    unsafe {
+        ctx.load512(input.as_ptr(), XRow(6));
+        ctx.store512(output.as_mut_ptr(), XRow(2));
+    }
+    assert_eq!(output, input);
+    assert_eq!(ctx.x()[128..192], input);
+}
+
+#[test]
+fn hook() {
+    let mut ctx = AmxEmuCtx::default();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    ctx.set_hook({
+        let log = Arc::clone(&log);
+        move |instr, state| log.lock().unwrap().push((*instr, state.z()[0][0]))
+    });
+
+    let x = [2u8; 64];
+    unsafe { ctx.load512(x.as_ptr(), XRow(1)) };
+    ctx.outer_product_i16_xy_to_z(None, None, ZRow(0), false);
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            (
+                Instr {
+                    op: JitOp::Ldx,
+                    operand: 1 << 56,
+                    addr: Some(x.as_ptr() as usize),
+                },
+                0,
+            ),
+            (
+                Instr {
+                    op: JitOp::Mac16,
+                    operand: (1 << 27) | (1 << 28) | (1 << 29),
+                    addr: None,
+                },
+                1,
+            ),
+        ]
+    );
+
+    // The hook is no longer called after it's removed
+    assert!(ctx.take_hook().is_some());
+    ctx.outer_product_i16_xy_to_z(None, None, ZRow(0), false);
+    assert_eq!(log.lock().unwrap().len(), 2);
+}
+
+#[test]
+#[should_panic]
+fn bad_geometry() {
+    AmxEmuCtx::new(AmxEmuGeometry {
+        z_rows: 96,
+        ..AmxEmuGeometry::M1
+    });
+}
+
+#[test]
+fn sandbox_allows_registered_buffers() {
+    let mut ctx = AmxEmuCtx::default();
+    let input = [1u8; 128];
+    let mut output = [0u8; 128];
+    ctx.register_buffer(&input);
+    ctx.register_buffer(&output);
+    unsafe {
+        ctx.load512(input[64..].as_ptr(), XRow(0));
+        ctx.load512(input.as_ptr(), XRow(1));
+        ctx.store512(output[64..].as_mut_ptr(), XRow(0));
+    }
+    assert_eq!(output[64..], input[64..]);
+
+    ctx.disable_sandbox();
+    let other = [0u8; 64];
+    unsafe { ctx.load512(other.as_ptr(), XRow(0)) };
+}
+
+#[test]
+#[should_panic = "outside of the registered buffers"]
+fn sandbox_rejects_overrun() {
+    let mut ctx = AmxEmuCtx::default();
+    let input = [1u8; 128];
+    ctx.register_buffer(&input[..96]);
+    unsafe { ctx.load512(input[64..].as_ptr(), YRow(0)) };
+}
+
+#[test]
+fn disassemble_trace() {
+    let mut ctx = AmxEmuCtx::default();
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    ctx.set_hook({
+        let trace = Arc::clone(&trace);
+        move |instr, _| trace.lock().unwrap().push(*instr)
+    });
+
+    #[repr(align(128))]
+    struct Aligned([u8; 256]);
+    let Aligned(buf) = &Aligned([0; 256]);
+    unsafe {
+        ctx.load512(buf[192..].as_ptr(), XRow(3));
+        ctx.load1024_aligned(buf.as_ptr(), YRow(0));
+    }
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(192)), Some(YBytes(64)), ZRow(2), true);
+    ctx.outer_product_i16_xy_to_z(None, Some(YBytes(0)), ZRow(1), false);
+
+    let trace = trace.lock().unwrap();
+    let text = Disassembly::new(&trace)
+        .base("p", buf.as_ptr() as usize)
+        .to_string();
+    assert_eq!(
+        text,
+        "ldx x3, [p+0xc0]\n\
+         ldy y0, y1, [p+0x0]\n\
+         fma32 z2 += x[192..256] * y[64..128]\n\
+         mac16 z1 = y[0..64]\n"
+    );
+    assert_eq!(
+        trace[0].to_string(),
+        format!("ldx x3, [{:#x}]", buf.as_ptr() as usize + 192)
+    );
+}
+
+#[test]
+fn trace_log() {
+    let mut ctx = AmxEmuCtx::default();
+    let x: [i16; 32] = std::array::from_fn(|i| i as i16);
+    ctx.load512_slice(&[1u8; 64], XRow(1));
+    ctx.enable_trace();
+    ctx.load512_slice(&x, XRow(1));
+    ctx.load512_slice(&[2i16; 32], YRow(0));
+    ctx.outer_product_i16_xy_to_z(Some(XBytes(64)), Some(YBytes(0)), ZRow(1), false);
+    ctx.outer_product_i16_xy_to_z(Some(XBytes(64)), Some(YBytes(0)), ZRow(1), false);
+
+    let trace = ctx.take_trace();
+    assert_eq!(trace.len(), 4);
+    assert_eq!(trace[0].instr.op, JitOp::Ldx);
+    assert_eq!(trace[0].fields, [("row", 1), ("pair", 0)]);
+    assert_eq!(trace[0].changes.len(), 1);
+    assert_eq!(trace[0].changes[0].file, RegFile::X);
+    assert_eq!(trace[0].changes[0].row, 1);
+    assert_eq!(trace[0].changes[0].before, [1; 64]);
+    // Only the low byte of lane 1 keeps its value
+    assert_eq!(trace[0].changes[0].changed_bytes().count(), 63);
+
+    let fields = &trace[2].fields;
+    assert!(fields.contains(&("x_offset", 64)));
+    assert!(fields.contains(&("z_row", 1)));
+    assert!(fields.contains(&("skip_z", 1)));
+    let rows: Vec<usize> = trace[2].changes.iter().map(|c| c.row).collect();
+    assert_eq!(rows, (0..32).map(|j| j * 2 + 1).collect::<Vec<_>>());
+    assert_eq!(
+        trace[2].to_string().lines().next(),
+        Some("mac16 z1 = x[64..128] * y[0..64]")
+    );
+    assert!(trace[2].to_string().ends_with("z63: 31 bytes changed"));
+
+    // Overwriting with the same values changes nothing
+    assert!(trace[3].changes.is_empty());
+
+    assert!(ctx.take_trace().is_empty());
+    ctx.disable_trace();
+    ctx.load512_slice(&x, XRow(0));
+    assert!(ctx.take_trace().is_empty());
+}
+
+#[test]
+fn chrome_trace_json() {
+    let trace = [
+        Instr {
+            op: JitOp::Ldx,
+            operand: 3 << 56,
+            addr: Some(0x1000),
+        },
+        Instr {
+            op: JitOp::Fma32,
+            operand: 1 << 27,
+            addr: None,
+        },
+    ];
+    let mut chrome =
+        ChromeTrace::from_cost_model(&trace, |instr| if instr.op.is_mem() { 0.5 } else { 0.25 });
+    chrome.push(&trace[1], 1, 10.0, 2.0);
+    assert_eq!(chrome.len(), 3);
+    assert_eq!(
+        chrome.to_json(),
+        concat!(
+            r#"{"traceEvents":["#,
+            r#"{"name":"ldx","cat":"amx","ph":"X","ts":0,"dur":0.5,"pid":0,"tid":0,"args":{"asm":"ldx x3, [0x1000]"}},"#,
+            r#"{"name":"fma32","cat":"amx","ph":"X","ts":0.5,"dur":0.25,"pid":0,"tid":0,"args":{"asm":"fma32 z0 = x[0..64] * y[0..64]"}},"#,
+            r#"{"name":"fma32","cat":"amx","ph":"X","ts":10,"dur":2,"pid":0,"tid":1,"args":{"asm":"fma32 z0 = x[0..64] * y[0..64]"}}"#,
+            r#"],"displayTimeUnit":"ns"}"#,
+        )
+    );
+}
+
+#[test]
+fn chrome_trace_json_non_finite() {
+    let instr = Instr {
+        op: JitOp::Fma32,
+        operand: 0,
+        addr: None,
+    };
+    let mut chrome = ChromeTrace::new();
+    chrome
+        .push(&instr, 0, 0.0, f64::NAN)
+        .push(&instr, 0, f64::INFINITY, f64::NEG_INFINITY)
+        .push(&instr, 0, 1.5, 0.25);
+
+    let json: serde_json::Value = serde_json::from_str(&chrome.to_json()).unwrap();
+    let times: Vec<_> = json["traceEvents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| (event["ts"].as_f64(), event["dur"].as_f64()))
+        .collect();
+    assert_eq!(
+        times,
+        [(Some(0.0), None), (None, None), (Some(1.5), Some(0.25))]
+    );
+    assert!(json["traceEvents"][1]["dur"].is_null());
+}
+
+#[test]
+fn fma16_rounds_once() {
+    let mut ctx = AmxEmuCtx::default();
+    // Rounding `x * y + z` to `f32` first would produce 0x4266
+    let (x, y, z) = ([0x3493u16; 32], [0x3665u16; 32], [0x422bu16; 32]);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+        ctx.load512(z.as_ptr(), ZRow(0));
+    }
+    ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), true);
+    assert_eq!(ctx.z()[0][..2], 0x4265u16.to_le_bytes());
+}
+
+#[test]
+fn vecfp_min_max_f32() {
+    let mut ctx = AmxEmuCtx::default();
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let z: [f32; 16] = std::array::from_fn(|i| 15.0 - i as f32);
+    for (alu, f) in [(5u64, f32::min as fn(f32, f32) -> f32), (7, f32::max)] {
+        unsafe {
+            ctx.load512(x.as_ptr(), XRow(1));
+            ctx.load512(z.as_ptr(), ZRow(3));
+        }
+        // `z[3] = min/max(x[1], z[3])` with `f32` lanes
+        ctx.vecfp((64 << 10) | (3 << 20) | (4 << 42) | (alu << 47));
+        let mut got = [0f32; 16];
+        unsafe { ctx.store512(got.as_mut_ptr(), ZRow(3)) };
+        let expected: [f32; 16] = std::array::from_fn(|i| f(x[i], z[i]));
+        assert_eq!(got, expected, "ALU mode {alu}");
+    }
+}
+
+/// An ALU mode and its scalar reference `f(x, y, z)`
+type AluMode = (u64, fn(i64, i64, i64) -> i64);
+
+/// The ALU modes emulated for every `vecint`/`vecfp`/`matint`/`matfp`
+/// instruction on M2
+const ALU_MODES: [AluMode; 6] = [
+    (0, |x, y, z| z + x * y),
+    (1, |x, y, z| z - x * y),
+    (4, |x, y, _| if x > 0 { y } else { 0 }),
+    (10, |x, y, _| x * y),
+    (11, |x, _, z| z + x),
+    (12, |_, y, z| z + y),
+];
+
+/// Small operands (covering the sign cases) whose products are exact in
+/// every lane type
+fn alu_inputs(lanes: usize) -> (Vec<i64>, Vec<i64>, Vec<i64>) {
+    let x = (0..lanes).map(|i| i as i64 % 7 - 3).collect();
+    let y = (0..lanes).map(|i| 5 - (i as i64 * 3) % 11).collect();
+    let z = (0..lanes).map(|i| (i as i64 * 5) % 13 - 6).collect();
+    (x, y, z)
+}
+
+#[test]
+fn vecint_alu_modes() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    let (x, y, z) = alu_inputs(32);
+    let to_i16 = |v: &[i64]| v.iter().map(|&v| v as i16).collect::<Vec<_>>();
+    let min_max: [AluMode; 2] = [(5, |x, _, z| x.min(z)), (7, |x, _, z| x.max(z))];
+    for (mode, f) in ALU_MODES.into_iter().chain(min_max) {
+        for skip_z in [false, true] {
+            ctx.load512_slice(&to_i16(&x), XRow(2));
+            ctx.load512_slice(&to_i16(&y), YRow(5));
+            ctx.load512_slice(&to_i16(&z), ZRow(9));
+            // i16 lanes
+            ctx.vecint((128 << 10) | 320 | (9 << 20) | ((skip_z as u64) << 27) | (mode << 47));
+            let expected: Vec<i16> = (0..32)
+                .map(|i| f(x[i], y[i], if skip_z { 0 } else { z[i] }) as i16)
+                .collect();
+            assert_eq!(
+                ctx.read_z_i16()[9][..],
+                expected,
+                "mode {mode}, skip_z {skip_z}"
+            );
+        }
+    }
+}
+
+#[test]
+fn vecfp_alu_modes() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    let (x, y, z) = alu_inputs(16);
+    let to_f32 = |v: &[i64]| v.iter().map(|&v| v as f32).collect::<Vec<_>>();
+    for (mode, f) in ALU_MODES {
+        for skip_z in [false, true] {
+            ctx.load512_slice(&to_f32(&x), XRow(0));
+            ctx.load512_slice(&to_f32(&y), YRow(0));
+            ctx.load512_slice(&to_f32(&z), ZRow(7));
+            // f32 lanes
+            ctx.vecfp((7 << 20) | ((skip_z as u64) << 27) | (4 << 42) | (mode << 47));
+            let expected: Vec<f32> = (0..16)
+                .map(|i| f(x[i], y[i], if skip_z { 0 } else { z[i] }) as f32)
+                .collect();
+            assert_eq!(
+                ctx.read_z_f32()[7][..],
+                expected,
+                "mode {mode}, skip_z {skip_z}"
+            );
+        }
+    }
+}
+
+#[test]
+fn vecfp_min_max_nan() {
+    let mut ctx = AmxEmuCtx::default();
+    let mut x: [u32; 16] = std::array::from_fn(|i| (i as f32).to_bits());
+    let mut z: [u32; 16] = std::array::from_fn(|i| (16.0 - i as f32).to_bits());
+    // Quiet NaN in X, quiet NaN in Z, zeros of opposite signs, signaling NaN
+    // in X with quiet NaN in Z, quiet NaNs in both
+    x[..6].copy_from_slice(&[
+        0x7fc0_0001,
+        0x3f80_0000,
+        0x8000_0000,
+        0,
+        0x7f80_0005,
+        0xffc0_0006,
+    ]);
+    z[..6].copy_from_slice(&[
+        0x4000_0000,
+        0x7fc0_0002,
+        0,
+        0x8000_0000,
+        0x7fc0_0003,
+        0x7fc0_0007,
+    ]);
+    let nans = [0x7fc0_0001, 0x7fc0_0002, 0x7fc0_0005, 0x7fc0_0007];
+
+    for (mode, default_nan, zeros) in [
+        (5, false, [0x8000_0000; 2]),
+        (7, false, [0; 2]),
+        (5, true, [0x8000_0000; 2]),
+    ] {
+        ctx.set_fp_semantics(FpSemantics {
+            default_nan,
+            ..FpSemantics::IEEE
+        });
+        ctx.load512_slice(&x, XRow(0));
+        ctx.load512_slice(&z, ZRow(0));
+        ctx.vecfp((4 << 42) | (mode << 47));
+        let got = ctx.read_z_f32()[0].map(f32::to_bits);
+
+        let nans = if default_nan { [0x7fc0_0000; 4] } else { nans };
+        assert_eq!(
+            got[..6],
+            [nans[0], nans[1], zeros[0], zeros[1], nans[2], nans[3]]
+        );
+        for (i, &got) in got.iter().enumerate().skip(6) {
+            let (a, b) = (i as f32, 16.0 - i as f32);
+            let expected = if mode == 5 { a.min(b) } else { a.max(b) };
+            assert_eq!(got, expected.to_bits(), "mode {mode}, lane {i}");
+        }
+    }
+}
+
+#[test]
+fn matint_alu_modes() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    let (x, y, z) = alu_inputs(32);
+    let to_i16 = |v: &[i64]| v.iter().map(|&v| v as i16).collect::<Vec<_>>();
+    for (mode, f) in ALU_MODES {
+        ctx.load512_slice(&to_i16(&x), XRow(0));
+        ctx.load512_slice(&to_i16(&y), YRow(0));
+        for j in 0..32 {
+            ctx.load512_slice(&to_i16(&z), ZRow(j * 2 + 1));
+        }
+        // i16 lanes, writing the odd rows
+        ctx.matint((1 << 20) | (mode << 47));
+        let got = ctx.read_z_plane_i16(1);
+        for (j, i) in itertools::iproduct!(0..32, 0..32) {
+            assert_eq!(
+                got[j][i],
+                f(x[i], y[j], z[i]) as i16,
+                "mode {mode}, i = {i}, j = {j}"
+            );
+        }
+    }
+}
+
+#[test]
+fn matfp_alu_modes() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    let (x, y, z) = alu_inputs(16);
+    let to_f32 = |v: &[i64]| v.iter().map(|&v| v as f32).collect::<Vec<_>>();
+    for (mode, f) in ALU_MODES {
+        ctx.load512_slice(&to_f32(&x), XRow(0));
+        ctx.load512_slice(&to_f32(&y), YRow(0));
+        for j in 0..16 {
+            ctx.load512_slice(&to_f32(&z), ZRow(j * 4 + 2));
+        }
+        // f32 lanes, writing every fourth row from row 2
+        ctx.matfp((2 << 20) | (4 << 42) | (mode << 47));
+        let got = ctx.read_z_plane_f32(2);
+        for (j, i) in itertools::iproduct!(0..16, 0..16) {
+            assert_eq!(
+                got[j][i],
+                f(x[i], y[j], z[i]) as f32,
+                "mode {mode}, i = {i}, j = {j}"
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic = "`vecfp` ALU mode 10 is reserved on M1"]
+fn alu_mode_requires_m2() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.vecfp((4 << 42) | (10 << 47));
+}
+
+#[test]
+#[should_panic = "`matint` lane type 10 is not emulated"]
+fn matint_lane_type_not_emulated() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    ctx.matint(10 << 42);
+}
+
+#[test]
+#[should_panic = "`matfp` ALU mode 5 is reserved"]
+fn alu_mode_reserved() {
+    let mut ctx = AmxEmuCtx::default();
+    ctx.matfp((4 << 42) | (5 << 47));
+}
+
+/// Calculate `z + x * y` for `f32` lanes with `vecfp` and return the bit
+/// patterns.
+fn vecfp_f32_bits(ctx: &mut AmxEmuCtx, x: u32, y: u32, z: u32) -> u32 {
+    ctx.load512_slice(&[x; 16], XRow(0));
+    ctx.load512_slice(&[y; 16], YRow(0));
+    ctx.load512_slice(&[z; 16], ZRow(0));
+    ctx.vector_fp(
+        VecFpTy::F32,
+        VecFpAluOp::MulAdd,
+        XBytes(0),
+        YBytes(0),
+        ZRow(0),
+    );
+    let mut out = [0u32; 16];
+    ctx.store512_slice(&mut out, ZRow(0));
+    assert!(out.iter().all(|&b| b == out[0]));
+    out[0]
+}
+
+#[test]
+fn fp_subnormals() {
+    let mut ctx = AmxEmuCtx::default();
+    let (tiny, one, zero) = (1u32, 1f32.to_bits(), 0u32);
+    let neg_zero = (-0f32).to_bits();
+    assert_eq!(ctx.fp_semantics(), FpSemantics::IEEE);
+    assert_eq!(vecfp_f32_bits(&mut ctx, tiny, one, zero), tiny);
+
+    ctx.set_fp_semantics(FpSemantics {
+        flush_subnormals: true,
+        ..FpSemantics::IEEE
+    });
+    // Subnormal inputs are zero
+    assert_eq!(vecfp_f32_bits(&mut ctx, tiny, one, one), one);
+    assert_eq!(
+        vecfp_f32_bits(&mut ctx, one | 1 << 31, tiny, neg_zero),
+        neg_zero
+    );
+    // Subnormal results are zero of the same sign
+    let (min, neg_half) = (f32::MIN_POSITIVE.to_bits(), (-0.5f32).to_bits());
+    assert_eq!(vecfp_f32_bits(&mut ctx, min, neg_half, zero), neg_zero);
+}
+
+#[test]
+fn fp_nan_propagation() {
+    let mut ctx = AmxEmuCtx::default();
+    let one = 1f32.to_bits();
+    let (snan, qnan) = (0xff80_0123u32, 0x7fc0_0456u32);
+    let default_nan = f32::NAN.to_bits();
+
+    // Signaling NaNs are quietened, keeping the payload and the sign
+    assert_eq!(vecfp_f32_bits(&mut ctx, snan, one, one), 0xffc0_0123);
+    // Signaling NaNs take precedence over quiet NaNs
+    assert_eq!(vecfp_f32_bits(&mut ctx, snan, one, qnan), 0xffc0_0123);
+    // Otherwise, Z takes precedence over X
+    assert_eq!(vecfp_f32_bits(&mut ctx, qnan | 1, one, qnan), qnan);
+    // `0 * inf` is invalid
+    let inf = f32::INFINITY.to_bits();
+    assert_eq!(vecfp_f32_bits(&mut ctx, 0, inf, one), default_nan);
+    assert_eq!(vecfp_f32_bits(&mut ctx, 0, inf, qnan), default_nan);
+
+    // `f16` payloads are kept through widening
+    ctx.load512_slice(&[0x7d01u16; 32], XRow(0));
+    ctx.outer_product_f16_xy_to_z_f32(Some(XBytes(0)), None, ZRow(0), true);
+    assert_eq!(ctx.read_z_f32()[0][0].to_bits(), 0x7fe0_2000);
+
+    ctx.set_fp_semantics(FpSemantics {
+        default_nan: true,
+        ..FpSemantics::IEEE
+    });
+    assert_eq!(vecfp_f32_bits(&mut ctx, snan, one, qnan), default_nan);
+}
+
+#[test]
+fn minimize_divergence() {
+    // Record a trace
+    let mut ctx = AmxEmuCtx::default();
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    ctx.set_hook({
+        let trace = Arc::clone(&trace);
+        move |instr, _| trace.lock().unwrap().push(*instr)
+    });
+    let a = [1u8; 64];
+    let b = [2u8; 64];
+    unsafe {
+        ctx.load512(a.as_ptr(), YRow(0));
+        ctx.outer_product_i16_xy_to_z(None, Some(YBytes(0)), ZRow(0), false);
+        ctx.load512(b.as_ptr(), XRow(0));
+        ctx.outer_product_i16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), false);
+    }
+    ctx.take_hook();
+    let trace = trace.lock().unwrap().clone();
+    assert_eq!(trace.len(), 4);
+
+    // A backend with the same geometry agrees
+    let mut same = AmxEmuCtx::default();
+    assert_eq!(
+        unsafe { find_divergence(&mut ctx, &mut same, &trace) },
+        None
+    );
+
+    // A backend with fewer X rows diverges at the first load to X, as
+    // `XRow(4)` aliases `XRow(0)` there
+    let mut small = AmxEmuCtx::new(AmxEmuGeometry {
+        x_rows: 4,
+        ..AmxEmuGeometry::M1
+    });
+    let divergence = unsafe { find_divergence(&mut ctx, &mut small, &trace) }.unwrap();
+    assert_eq!(divergence.len, 3);
+    assert_eq!(trace[divergence.len - 1].op, JitOp::Ldx);
+    assert_eq!(divergence.diffs.len(), 1);
+    let diff = &divergence.diffs[0];
+    assert_eq!((diff.file, diff.row), (RegFile::X, 4));
+    assert_eq!((diff.left, diff.right), ([0; 64], b));
+    assert_eq!(diff.differing_bytes().count(), 64);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use amx::{Amx, XBytes, XRow, YBytes, YRow, ZRow};
+
+#[test]
+fn extract_tile_rows() {
+    let mut ctx = common::ctx();
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 + 1.0);
+    let y: [f32; 16] = std::array::from_fn(|j| j as f32 * 0.5);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(3), false);
+
+    for j in [0, 7, 15] {
+        let expected = x.map(|x| x * y[j]);
+
+        ctx.extract_z_to_x(ZRow::tile_row(4, 3, j), XBytes(64 * 5));
+        let mut got = [0f32; 16];
+        unsafe { ctx.store512(got.as_mut_ptr(), XRow(5)) };
+        assert_eq!(got, expected, "j = {j}");
+
+        ctx.extract_z_to_y(ZRow::tile_row(4, 3, j), YBytes(64 * 2));
+        unsafe { ctx.store512(got.as_mut_ptr(), YRow(2)) };
+        assert_eq!(got, expected, "j = {j}");
+    }
+}
+
+#[test]
+fn extract_wraps_around() {
+    let mut ctx = common::ctx();
+    let row: [u8; 64] = std::array::from_fn(|i| i as u8 + 1);
+    unsafe { ctx.load512(row.as_ptr(), ZRow(9)) };
+
+    ctx.extract_z_to_y(ZRow(9), YBytes(512 - 16));
+    let y = ctx.read_y();
+    assert_eq!(y[512 - 16..], row[..16]);
+    assert_eq!(y[..48], row[16..]);
+}
@@ -1,4 +1,9 @@
-use amx::{Index4, Normal, X8, XBytes, XRow, YBytes, YRow, prelude::*};
+mod common;
+
+use amx::{
+    F16, F32, F64, I16, I32, Index2, Index4, Index5, LutReverseTy, LutTy, Normal, Reverse, U16,
+    U32, X8, X16, X32, X64, XBytes, XRow, YBytes, YRow, ZRow, fp16::F16Bits, prelude::*,
+};
 use either::{Left, Right};
 use quickcheck::TestResult;
 
@@ -31,7 +36,7 @@ fn qc_genlut_lut8x16(
     }
 
     let mut got = [0u8; 64];
-    let mut ctx = amx::AmxCtx::new().unwrap();
+    let mut ctx = common::ctx();
     unsafe {
         indices.resize_with(64, u8::default);
 
@@ -67,7 +72,6 @@ fn qc_genlut_lut8x16(
 
     // Read the result
     unsafe { ctx.store512(got.as_mut_ptr(), XRow(out_row)) };
-    let all_x = unsafe { std::mem::transmute::<_, [[u64; 8]; 8]>(ctx.read_x()) };
 
     let expected: Vec<u8> = (0..64)
         .map(|i| {
@@ -86,3 +90,212 @@ fn qc_genlut_lut8x16(
 
     TestResult::passed()
 }
+
+/// Run `genlut` with `input` in Y and `table` in X, writing the result to X,
+/// Y, or Z depending on `out_file`.
+fn run_lut(input: &[u8; 64], table: &[u8; 64], out_file: u8, mode: impl LutTy) -> [u8; 64] {
+    let mut ctx = common::ctx();
+    ctx.load512_slice(input, YRow(2));
+    ctx.load512_slice(table, XRow(3));
+    let mut got = [0u8; 64];
+    match out_file % 3 {
+        0 => {
+            ctx.lut(YBytes(128), XRow(3), XRow(6), mode);
+            ctx.store512_slice(&mut got, XRow(6));
+        }
+        1 => {
+            ctx.lut(YBytes(128), XRow(3), YRow(1), mode);
+            ctx.store512_slice(&mut got, YRow(1));
+        }
+        _ => {
+            ctx.lut(YBytes(128), XRow(3), ZRow(41), mode);
+            ctx.store512_slice(&mut got, ZRow(41));
+        }
+    }
+    got
+}
+
+fn to_row(mut bytes: Vec<u8>) -> [u8; 64] {
+    bytes.resize(64, 0);
+    bytes.try_into().unwrap()
+}
+
+/// Get the `i`-th `bits`-bit field of `bytes`.
+fn bit_field(bytes: &[u8], bits: usize, i: usize) -> usize {
+    (0..bits)
+        .map(|b| {
+            let bit = i * bits + b;
+            ((bytes[bit / 8] >> (bit % 8)) as usize & 1) << b
+        })
+        .sum()
+}
+
+/// Check a normal mode, which replaces each `index_bits`-bit index with the
+/// `esize`-byte table entry.
+fn check_lookup(
+    mode: impl LutTy,
+    index_bits: usize,
+    esize: usize,
+    input: Vec<u8>,
+    table: Vec<u8>,
+    out_file: u8,
+) {
+    let (input, table) = (to_row(input), to_row(table));
+    let got = run_lut(&input, &table, out_file, mode);
+
+    let entries = 64 / esize;
+    let mut expected = [0u8; 64];
+    for (i, out) in expected.chunks_exact_mut(esize).enumerate() {
+        let index = bit_field(&input, index_bits, i) % entries;
+        out.copy_from_slice(&table[index * esize..][..esize]);
+    }
+    assert_eq!(got, expected, "index_bits = {index_bits}, esize = {esize}");
+}
+
+macro_rules! qc_lookup {
+    ($($name:ident: ($index:ident, $value:ident) => ($index_bits:expr, $esize:expr)),* $(,)?) => {$(
+        #[quickcheck_macros::quickcheck]
+        fn $name(input: Vec<u8>, table: Vec<u8>, out_file: u8) {
+            check_lookup((Normal, $index, $value), $index_bits, $esize, input, table, out_file);
+        }
+    )*};
+}
+
+qc_lookup! {
+    qc_lookup_index2_x32: (Index2, X32) => (2, 4),
+    qc_lookup_index2_x16: (Index2, X16) => (2, 2),
+    qc_lookup_index2_x8: (Index2, X8) => (2, 1),
+    qc_lookup_index4_x64: (Index4, X64) => (4, 8),
+    qc_lookup_index4_x32: (Index4, X32) => (4, 4),
+    qc_lookup_index4_x16: (Index4, X16) => (4, 2),
+    qc_lookup_index4_x8: (Index4, X8) => (4, 1),
+    qc_lookup_index5_x16: (Index5, X16) => (5, 2),
+    qc_lookup_index5_x8: (Index5, X8) => (5, 1),
+}
+
+/// Converts an element to bytes and from an integer
+type Codec<T> = (fn(T) -> Vec<u8>, fn(i32) -> T);
+
+/// Check a reverse mode, which finds the interval of a sorted table
+/// containing each input element and outputs the packed `index_bits`-bit
+/// interval indices.
+///
+/// The table is `base` followed by increments of `steps`, and the inputs
+/// are spread slightly beyond the table's range.
+fn check_reverse<T: Copy + PartialOrd>(
+    mode: impl LutTy,
+    index_bits: usize,
+    (to_bytes, from_int): Codec<T>,
+    base: i32,
+    steps: Vec<u8>,
+    inputs: Vec<u16>,
+    out_file: u8,
+) {
+    let esize = to_bytes(from_int(0)).len();
+    let (entries, lanes) = ((64 / esize).min(1 << index_bits), 64 / esize);
+    let keys: Vec<i32> = (0..entries)
+        .scan(base, |key, k| {
+            *key += steps.get(k).map_or(1, |&s| s as i32 % 30 + 1);
+            Some(*key)
+        })
+        .collect();
+    let (lo, hi) = (keys[0] - 40, keys[entries - 1] + 40);
+    let values: Vec<i32> = (0..lanes)
+        .map(|i| lo + inputs.get(i).map_or(0, |&x| x as i32) % (hi - lo + 1))
+        .collect();
+
+    let table = to_row(keys.iter().flat_map(|&k| to_bytes(from_int(k))).collect());
+    let input = to_row(values.iter().flat_map(|&v| to_bytes(from_int(v))).collect());
+    let got = run_lut(&input, &table, out_file, mode);
+
+    let mut expected = [0u8; 64];
+    for (i, &v) in values.iter().enumerate() {
+        let v = from_int(v);
+        let num_le = keys.iter().filter(|&&k| from_int(k) <= v).count();
+        let index = num_le.wrapping_sub(1) & ((1 << index_bits) - 1);
+        for b in 0..index_bits {
+            let bit = i * index_bits + b;
+            expected[bit / 8] |= (((index >> b) & 1) as u8) << (bit % 8);
+        }
+    }
+    assert_eq!(got, expected, "keys = {keys:?}, values = {values:?}");
+}
+
+macro_rules! qc_reverse {
+    ($(
+        $name:ident: ($index:ident, $value:ident) => ($index_bits:expr, $ty:ty, $signed:expr)
+    ),* $(,)?) => {$(
+        #[quickcheck_macros::quickcheck]
+        fn $name(base: i16, steps: Vec<u8>, inputs: Vec<u16>, out_file: u8) {
+            let base = if $signed { base as i32 % 500 } else { (base as i32 % 500).abs() + 50 };
+            check_reverse::<$ty>(
+                (Reverse, $index, $value),
+                $index_bits,
+                (|x| x.to_le_bytes().to_vec(), |x| x as $ty),
+                base,
+                steps,
+                inputs,
+                out_file,
+            );
+        }
+    )*};
+}
+
+qc_reverse! {
+    qc_reverse_index4_f32: (Index4, F32) => (4, f32, true),
+    qc_reverse_index4_f64: (Index4, F64) => (4, f64, true),
+    qc_reverse_index4_i32: (Index4, I32) => (4, i32, true),
+    qc_reverse_index4_u32: (Index4, U32) => (4, u32, false),
+    qc_reverse_index5_i16: (Index5, I16) => (5, i16, true),
+    qc_reverse_index5_u16: (Index5, U16) => (5, u16, false),
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_reverse_index5_f16(base: i16, steps: Vec<u8>, inputs: Vec<u16>, out_file: u8) {
+    check_reverse::<f32>(
+        (Reverse, Index5, F16),
+        5,
+        (
+            |x| F16Bits::from_f32(x).0.to_le_bytes().to_vec(),
+            |x| x as f32,
+        ),
+        base as i32 % 500,
+        steps,
+        inputs,
+        out_file,
+    );
+}
+
+#[test]
+fn lut_reverse_quantize() {
+    // Quantize to the nearest of 32 codebook entries
+    let codebook: [i16; 32] = std::array::from_fn(|i| (i as i16 - 16) * 20);
+    let mut boundaries = [i16::MIN; 32];
+    for k in 1..32 {
+        boundaries[k] = (codebook[k - 1] + codebook[k]) / 2;
+    }
+    let input: [i16; 32] = std::array::from_fn(|i| (i as i16 * 37 % 700) - 350);
+
+    let mut ctx = common::ctx();
+    ctx.load512_slice(&boundaries, XRow(5));
+    ctx.load512_slice(&input, XRow(0));
+    ctx.try_lut_reverse(XBytes(0), XRow(5), ZRow(9), (Reverse, Index5, I16))
+        .unwrap();
+    let mut out = [0u8; 64];
+    ctx.store512_slice(&mut out, ZRow(9));
+
+    let bits = <(Reverse, Index5, I16)>::INDEX_BITS;
+    for (i, &x) in input.iter().enumerate() {
+        let got = codebook[amx::lut_index(&out, bits, i) as usize];
+        let nearest = *codebook
+            .iter()
+            .min_by_key(|&&c| ((c - x).abs(), -c))
+            .unwrap();
+        assert_eq!(got, nearest, "x = {x}");
+    }
+
+    assert_eq!(
+        ctx.try_lut_reverse(XBytes(0), XRow(8), ZRow(9), (Reverse, Index5, I16)),
+        Err(amx::AmxArgError::RowOutOfRange { index: 8, len: 8 })
+    );
+}
@@ -0,0 +1,277 @@
+mod common;
+
+use amx::{
+    geom::{self, Aabb, AffineTransform, Mat3, Ray, RigidTransform, Triangle},
+    linalg::MatMut,
+};
+use common::Xorshift32;
+
+impl Xorshift32 {
+    fn vec3(&mut self) -> [f32; 3] {
+        [self.next_f32(), self.next_f32(), self.next_f32()]
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// The textbook Möller–Trumbore test, returning `(t, u, v)`
+fn moller_trumbore(ray: &Ray, [v0, v1, v2]: &Triangle) -> Option<(f32, f32, f32)> {
+    let (e1, e2) = (sub(*v1, *v0), sub(*v2, *v0));
+    let p = cross(ray.dir, e2);
+    let det = dot(e1, p);
+    if det == 0.0 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = sub(ray.origin, *v0);
+    let u = dot(s, p) * inv_det;
+    let q = cross(s, e1);
+    let v = dot(ray.dir, q) * inv_det;
+    let t = dot(e2, q) * inv_det;
+    Some((t, u, v))
+}
+
+#[test]
+fn ray_triangle_intersect() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7a1);
+
+    let triangles: Vec<Triangle> = (0..37)
+        .map(|_| [rng.vec3(), rng.vec3(), rng.vec3()])
+        .collect();
+    // Aim the rays at points near the triangles' centroids
+    let rays: Vec<Ray> = (0..40)
+        .map(|i| {
+            let [v0, v1, v2] = triangles[i * 7 % triangles.len()];
+            let origin = rng.vec3().map(|x| x * 4.0);
+            let target: [f32; 3] =
+                std::array::from_fn(|a| (v0[a] + v1[a] + v2[a]) / 3.0 + rng.next_f32() * 0.2);
+            Ray {
+                origin,
+                dir: sub(target, origin),
+            }
+        })
+        .collect();
+
+    let mut t = vec![0.0; rays.len() * triangles.len()];
+    geom::ray_triangle_intersect(
+        &mut ctx,
+        &rays,
+        &triangles,
+        MatMut::new(&mut t, rays.len(), triangles.len()),
+    );
+
+    let mut num_hits = 0;
+    for (r, ray) in rays.iter().enumerate() {
+        for (k, tri) in triangles.iter().enumerate() {
+            let got = t[r * triangles.len() + k];
+            let Some((et, u, v)) = moller_trumbore(ray, tri) else {
+                assert_eq!(got, f32::INFINITY, "ray {r}, triangle {k}");
+                continue;
+            };
+            // Skip the pairs too close to an edge to classify reliably
+            let margin = u.min(v).min(1.0 - u - v).abs().min(et.abs());
+            if margin < 1e-3 {
+                continue;
+            }
+            if u >= 0.0 && v >= 0.0 && u + v <= 1.0 && et > 0.0 {
+                num_hits += 1;
+                assert!(
+                    (got - et).abs() <= 1e-3 * (1.0 + et.abs()),
+                    "ray {r}, triangle {k}: got = {got}, expected = {et}"
+                );
+            } else {
+                assert_eq!(got, f32::INFINITY, "ray {r}, triangle {k}");
+            }
+        }
+    }
+    assert!(num_hits > 10);
+}
+
+#[test]
+fn ray_aabb_intersect() {
+    let mut ctx = common::ctx();
+    let aabb = Aabb {
+        min: [-1.0, -2.0, -0.5],
+        max: [1.0, 0.5, 3.0],
+    };
+    let rays = [
+        // From outside, hitting the -x face at t = 2
+        Ray {
+            origin: [-3.0, 0.0, 0.0],
+            dir: [1.0, 0.0, 0.0],
+        },
+        // Pointing away
+        Ray {
+            origin: [-3.0, 0.0, 0.0],
+            dir: [-1.0, 0.0, 0.0],
+        },
+        // From inside
+        Ray {
+            origin: [0.0, 0.0, 0.0],
+            dir: [0.3, -0.2, 1.0],
+        },
+        // Parallel to a slab and outside of it
+        Ray {
+            origin: [-3.0, 1.0, 0.0],
+            dir: [1.0, 0.0, 0.0],
+        },
+        // Diagonal, entering through the +z face at t = 1
+        Ray {
+            origin: [0.0, 0.0, 5.0],
+            dir: [0.5, -0.5, -2.0],
+        },
+    ];
+    let expected = [2.0, f32::INFINITY, 0.0, f32::INFINITY, 1.0];
+
+    // Repeat the rays to cover multiple steps
+    let rays: Vec<Ray> = rays.iter().cycle().take(37).copied().collect();
+    let mut t = vec![0.0; rays.len()];
+    geom::ray_aabb_intersect(&mut ctx, &rays, &aabb, &mut t);
+    for (i, &got) in t.iter().enumerate() {
+        let expected = expected[i % 5];
+        assert!(
+            got == expected || (got - expected).abs() < 1e-5,
+            "ray {i}: got = {got}, expected = {expected}"
+        );
+    }
+}
+
+fn mat_vec(m: &Mat3, v: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|i| dot(m[i], v))
+}
+
+fn rotation(rng: &mut Xorshift32) -> Mat3 {
+    // Rotation about a random axis by a random angle (Rodrigues' formula)
+    let axis = rng.vec3();
+    let norm = dot(axis, axis).sqrt();
+    let [x, y, z] = axis.map(|a| a / norm);
+    let (s, c) = (rng.next_f32() * 3.0).sin_cos();
+    let t = 1.0 - c;
+    [
+        [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+        [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+        [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+    ]
+}
+
+#[test]
+fn transform_points() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x819d);
+    let transforms: Vec<RigidTransform> = (0..21)
+        .map(|_| RigidTransform {
+            rotation: rotation(&mut rng),
+            translation: rng.vec3(),
+        })
+        .collect();
+    let points: Vec<[f32; 3]> = (0..21).map(|_| rng.vec3()).collect();
+
+    let mut got = vec![[0.0; 3]; 21];
+    geom::transform_points(&mut ctx, &transforms, &points, &mut got);
+    for (i, got) in got.iter().enumerate() {
+        let rotated = mat_vec(&transforms[i].rotation, points[i]);
+        for a in 0..3 {
+            let expected = rotated[a] + transforms[i].translation[a];
+            assert!((got[a] - expected).abs() < 1e-5, "point {i}");
+        }
+    }
+
+    let mut got = vec![[0.0; 3]; 21];
+    let matrices: Vec<Mat3> = transforms.iter().map(|t| t.rotation).collect();
+    geom::apply_mat3(&mut ctx, &matrices, &points, &mut got);
+    for (i, got) in got.iter().enumerate() {
+        let expected = mat_vec(&matrices[i], points[i]);
+        for a in 0..3 {
+            assert!((got[a] - expected[a]).abs() < 1e-5, "vector {i}");
+        }
+    }
+}
+
+#[test]
+fn world_inertia() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1e47);
+    let rotations: Vec<Mat3> = (0..19).map(|_| rotation(&mut rng)).collect();
+    let inertia: Vec<Mat3> = (0..19)
+        .map(|_| {
+            let d = rng.vec3().map(|x| x.abs() + 0.5);
+            [[d[0], 0.0, 0.0], [0.0, d[1], 0.0], [0.0, 0.0, d[2]]]
+        })
+        .collect();
+
+    let mut got = vec![[[0.0; 3]; 3]; 19];
+    geom::world_inertia(&mut ctx, &rotations, &inertia, &mut got);
+    for (b, got) in got.iter().enumerate() {
+        let (r, ib) = (&rotations[b], &inertia[b]);
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected: f32 = (0..3)
+                    .flat_map(|k| (0..3).map(move |l| (k, l)))
+                    .map(|(k, l)| r[i][k] * ib[k][l] * r[j][l])
+                    .sum();
+                assert!(
+                    (got[i][j] - expected).abs() < 1e-5,
+                    "body {b}, ({i}, {j}): got = {}, expected = {expected}",
+                    got[i][j]
+                );
+            }
+        }
+        // A rotation preserves the trace
+        let trace = |m: &Mat3| m[0][0] + m[1][1] + m[2][2];
+        assert!((trace(got) - trace(ib)).abs() < 1e-4);
+    }
+}
+
+fn dot64(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[test]
+fn transform_coords() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x6e0d);
+    // An EPSG-style Helmert transformation, with the rotations converted from
+    // arcseconds
+    let arcsec = std::f64::consts::PI / (180.0 * 3600.0);
+    let transform = AffineTransform::helmert(
+        [-446.448, 125.157, -542.06],
+        [-0.1502 * arcsec, -0.247 * arcsec, -0.8421 * arcsec],
+        20.4894,
+    );
+    // Points near the Earth's surface in ECEF coordinates
+    let points: Vec<[f64; 3]> = (0..150)
+        .map(|_| rng.vec3().map(|x| x as f64 * 6.4e6))
+        .collect();
+
+    let mut got = vec![[0.0; 3]; points.len()];
+    geom::transform_coords(&mut ctx, &transform, &points, &mut got);
+    for (i, (p, got)) in points.iter().zip(&got).enumerate() {
+        for (j, &got) in got.iter().enumerate() {
+            let row = transform.matrix[j];
+            let expected = transform.offset[j] + dot64(row, *p);
+            assert!(
+                (got - expected).abs() < 1e-6,
+                "point {i}, axis {j}: got = {got}, expected = {expected}"
+            );
+        }
+    }
+
+    // The identity leaves the points unchanged
+    geom::transform_coords(&mut ctx, &AffineTransform::default(), &points, &mut got);
+    assert_eq!(got, points);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use amx::jit::{JitBuilder, JitOp};
+
+/// Build a kernel computing the f32 outer product of `args[0]` and `args[1]`
+/// and storing the result to `args[2]`.
+fn outer_product_kernel() -> JitBuilder {
+    let mut b = JitBuilder::new();
+    b.mem(JitOp::Ldx, 0, 0, 0);
+    b.mem(JitOp::Ldy, 1, 0, 0);
+    b.op(JitOp::Fma32, 1 << 27);
+    for j in 0..16 {
+        b.mem(JitOp::Stz, 2, j * 64, ((j * 4) as u64) << 56);
+    }
+    b
+}
+
+fn inputs() -> ([f32; 16], [f32; 16]) {
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 + 1.0);
+    let y: [f32; 16] = std::array::from_fn(|i| (i as f32) * 0.5 - 3.0);
+    (x, y)
+}
+
+fn check(x: &[f32; 16], y: &[f32; 16], z: &[[f32; 16]; 16]) {
+    for (j, z_row) in z.iter().enumerate() {
+        for (i, &z) in z_row.iter().enumerate() {
+            assert_eq!(z, x[i] * y[j], "(i, j) = ({i}, {j})");
+        }
+    }
+}
+
+#[test]
+fn jit_encoding() {
+    let mut b = JitBuilder::new();
+    b.op(JitOp::Fma32, 0x0012_0000_0000_0040);
+    b.mem(JitOp::Ldx, 1, 0x80, 3 << 56);
+    b.op(JitOp::Mac16, 0);
+    assert_eq!(b.len(), 3);
+    assert_eq!(
+        b.assemble(),
+        [
+            0xd280_0809, // movz x9, #0x40
+            0xf2e0_0249, // movk x9, #0x12, lsl #48
+            0x0020_1189, // fma32 x9
+            0xd280_1009, // movz x9, #0x80
+            0xf2e0_6009, // movk x9, #0x300, lsl #48
+            0xf940_040a, // ldr x10, [x0, #8]
+            0x8b0a_0129, // add x9, x9, x10
+            0x0020_1009, // ldx x9
+            0xd2e0_0009, // movz x9, #0, lsl #48
+            0x0020_11c9, // mac16 x9
+            0xd65f_03c0, // ret
+        ]
+    );
+}
+
+#[test]
+fn jit_replay() {
+    let mut ctx = common::ctx();
+    let (x, y) = inputs();
+    let mut z = [[0.0f32; 16]; 16];
+    let args = [x.as_ptr() as u64, y.as_ptr() as u64, z.as_mut_ptr() as u64];
+    unsafe { outer_product_kernel().replay(&mut ctx, &args) };
+    check(&x, &y, &z);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn jit_native() {
+    let mut ctx = common::ctx();
+    let kernel = outer_product_kernel().finish().unwrap();
+    assert_eq!(kernel.num_args(), 3);
+    let (x, y) = inputs();
+    let mut z = [[0.0f32; 16]; 16];
+    let args = [x.as_ptr() as u64, y.as_ptr() as u64, z.as_mut_ptr() as u64];
+    unsafe { kernel.call(&mut ctx, &args) };
+    check(&x, &y, &z);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use amx::{XBytes, XRow, YBytes, YRow, ZRow, prelude::*};
+
+#[test]
+fn amx_kernel_matches_hand_written() {
+    let x: Vec<i16> = (0..64).map(|i| i * 3 - 50).collect();
+    let y: Vec<i16> = (0..64).map(|i| 7 - i * 2).collect();
+    let xf: Vec<f32> = (0..16).map(|i| i as f32 * 0.5).collect();
+    let yf: Vec<f32> = (0..16).map(|i| 2.0 - i as f32).collect();
+
+    let expected = {
+        let mut ctx = common::ctx();
+        unsafe {
+            ctx.load512(x.as_ptr(), XRow(0));
+            ctx.load512(x[32..].as_ptr(), XRow(1));
+            ctx.load512(y.as_ptr(), YRow(2));
+            ctx.load512(xf.as_ptr(), XRow(3));
+            ctx.load512(yf.as_ptr(), YRow(3));
+        }
+        ctx.outer_product_i16_xy_to_z(Some(XBytes(0)), Some(YBytes(128)), ZRow(0), false);
+        for _ in 0..5 {
+            ctx.outer_product_i16_xy_to_z(Some(XBytes(64)), Some(YBytes(128)), ZRow(0), true);
+            ctx.outer_product_i16_xy_to_z(Some(XBytes(2)), Some(YBytes(130)), ZRow(1), true);
+        }
+        ctx.outer_product_f32_xy_to_z(Some(XBytes(192)), Some(YBytes(192)), ZRow(2), false);
+        ctx.read_z()
+    };
+
+    let mut ctx = common::ctx();
+    let mut got = [0u8; 4096];
+    unsafe {
+        amx::amx_kernel!(ctx => {
+            load x[0] <- x.as_ptr();
+            load x[1] <- x[32..].as_ptr();
+            load y[2] <- y.as_ptr();
+            load x[3] <- xf.as_ptr();
+            load y[3] <- yf.as_ptr();
+            i16 z[0] = x(0) * y(128);
+            repeat 5 {
+                i16 z[0] += x(64) * y(128);
+                i16 z[1] += x(2) * y(130);
+            }
+            f32 z[2] = x(192) * y(192);
+        });
+        for i in 0..64 {
+            amx::amx_kernel!(ctx => {
+                store z[i] -> got[i * 64..].as_mut_ptr();
+            });
+        }
+    }
+
+    assert_eq!(got[..], expected[..]);
+}
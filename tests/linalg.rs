@@ -0,0 +1,922 @@
+mod common;
+
+use amx::{
+    fp16::F16Bits,
+    linalg::{self, MatMut, MatRef, SplitComplex},
+};
+use common::{Xorshift32, assert_close};
+
+#[test]
+fn cgemm() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x114514);
+
+    for &(m, n, k) in &[
+        (1, 1, 1),
+        (16, 16, 16),
+        (17, 33, 5),
+        (40, 7, 31),
+        (3, 20, 0),
+    ] {
+        for &karatsuba in &[false, true] {
+            for &accumulate in &[false, true] {
+                // Use padded strides to exercise strided access
+                let (lda, ldb, ldc) = (k + 3, n + 1, n + 2);
+                let (a_re, a_im) = (rng.vec_f32(m * lda), rng.vec_f32(m * lda));
+                let (b_re, b_im) = (rng.vec_f32(k * ldb), rng.vec_f32(k * ldb));
+                let (mut c_re, mut c_im) = (rng.vec_f32(m * ldc), rng.vec_f32(m * ldc));
+
+                let mut expected_re = c_re.clone();
+                let mut expected_im = c_im.clone();
+                for i in 0..m {
+                    for j in 0..n {
+                        let (mut re, mut im) = (0.0, 0.0);
+                        for kk in 0..k {
+                            let (ar, ai) = (a_re[i * lda + kk], a_im[i * lda + kk]);
+                            let (br, bi) = (b_re[kk * ldb + j], b_im[kk * ldb + j]);
+                            re += ar * br - ai * bi;
+                            im += ar * bi + ai * br;
+                        }
+                        if accumulate {
+                            expected_re[i * ldc + j] += re;
+                            expected_im[i * ldc + j] += im;
+                        } else {
+                            expected_re[i * ldc + j] = re;
+                            expected_im[i * ldc + j] = im;
+                        }
+                    }
+                }
+
+                let a = SplitComplex {
+                    re: MatRef::with_stride(&a_re, m, k, lda),
+                    im: MatRef::with_stride(&a_im, m, k, lda),
+                };
+                let b = SplitComplex {
+                    re: MatRef::with_stride(&b_re, k, n, ldb),
+                    im: MatRef::with_stride(&b_im, k, n, ldb),
+                };
+                let c = SplitComplex {
+                    re: MatMut::with_stride(&mut c_re, m, n, ldc),
+                    im: MatMut::with_stride(&mut c_im, m, n, ldc),
+                };
+                if karatsuba {
+                    linalg::cgemm_3m(&mut ctx, a, b, c, accumulate);
+                } else {
+                    linalg::cgemm(&mut ctx, a, b, c, accumulate);
+                }
+
+                assert_close(&c_re, &expected_re, 1e-4);
+                assert_close(&c_im, &expected_im, 1e-4);
+            }
+        }
+    }
+}
+
+#[test]
+fn sgemm() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1919);
+
+    for &(m, n, k) in &[
+        (1, 1, 1),
+        (16, 16, 16),
+        (17, 33, 5),
+        (40, 7, 31),
+        (3, 20, 0),
+    ] {
+        for &accumulate in &[false, true] {
+            let (lda, ldb, ldc) = (k + 3, n + 1, n + 2);
+            let a = rng.vec_f32(m * lda);
+            let b = rng.vec_f32(k * ldb);
+            let mut c = rng.vec_f32(m * ldc);
+
+            let mut expected = c.clone();
+            for i in 0..m {
+                for j in 0..n {
+                    let sum: f32 = (0..k).map(|kk| a[i * lda + kk] * b[kk * ldb + j]).sum();
+                    if accumulate {
+                        expected[i * ldc + j] += sum;
+                    } else {
+                        expected[i * ldc + j] = sum;
+                    }
+                }
+            }
+
+            linalg::sgemm(
+                &mut ctx,
+                MatRef::with_stride(&a, m, k, lda),
+                MatRef::with_stride(&b, k, n, ldb),
+                MatMut::with_stride(&mut c, m, n, ldc),
+                accumulate,
+            );
+            assert_close(&c, &expected, 1e-4);
+        }
+    }
+}
+
+#[test]
+fn sgemm_deterministic() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5eed);
+    // Cross the packed panel boundaries
+    let (m, n, k) = (140, 50, 520);
+    let a = rng.vec_f32(m * k);
+    let b = rng.vec_f32(k * n);
+
+    let mut expected = vec![0.0; m * n];
+    linalg::sgemm(
+        &mut ctx,
+        MatRef::new(&a, m, k),
+        MatRef::new(&b, k, n),
+        MatMut::new(&mut expected, m, n),
+        false,
+    );
+
+    // The same bits for repeated calls and for padded operands
+    for pad in [0, 0, 1, 7] {
+        let (lda, ldb, ldc) = (k + pad, n + 2 * pad, n + 3 * pad);
+        let a_padded: Vec<f32> = a
+            .chunks(k)
+            .flat_map(|row| row.iter().copied().chain(std::iter::repeat_n(9.0, pad)))
+            .collect();
+        let b_padded: Vec<f32> = b
+            .chunks(n)
+            .flat_map(|row| row.iter().copied().chain(std::iter::repeat_n(9.0, 2 * pad)))
+            .collect();
+        let mut c = vec![0.0; m * ldc];
+        linalg::sgemm(
+            &mut ctx,
+            MatRef::with_stride(&a_padded, m, k, lda),
+            MatRef::with_stride(&b_padded, k, n, ldb),
+            MatMut::with_stride(&mut c, m, n, ldc),
+            false,
+        );
+        for i in 0..m {
+            let got = c[i * ldc..][..n].iter().map(|x| x.to_bits());
+            let expected = expected[i * n..][..n].iter().map(|x| x.to_bits());
+            assert!(got.eq(expected), "row {i}, pad = {pad}");
+        }
+    }
+}
+
+#[test]
+fn sgemm_scaled() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x2e9);
+
+    // Cross the boundaries of the packed panels
+    for &(m, n, k, alpha, beta) in &[
+        (33, 35, 300, 1.5, -0.5),
+        (130, 20, 40, -1.0, 0.0),
+        (5, 530, 3, 0.25, 1.0),
+        (4, 4, 0, 2.0, 3.0),
+        (4, 4, 2, 0.0, 3.0),
+    ] {
+        let (lda, ldb, ldc) = (k + 1, n, n + 5);
+        let a = rng.vec_f32(m * lda);
+        let b = rng.vec_f32(k * ldb);
+        let mut c = rng.vec_f32(m * ldc);
+
+        let mut expected = c.clone();
+        for i in 0..m {
+            for j in 0..n {
+                let sum: f32 = (0..k).map(|kk| a[i * lda + kk] * b[kk * ldb + j]).sum();
+                let e = &mut expected[i * ldc + j];
+                *e = alpha * sum + beta * *e;
+            }
+        }
+        if beta == 0.0 {
+            // Not read
+            for i in 0..m {
+                c[i * ldc..][..n].fill(f32::NAN);
+            }
+        }
+
+        linalg::sgemm_scaled(
+            &mut ctx,
+            alpha,
+            MatRef::with_stride(&a, m, k, lda),
+            MatRef::with_stride(&b, k, n, ldb),
+            beta,
+            MatMut::with_stride(&mut c, m, n, ldc),
+        );
+        assert_close(&c, &expected, 1e-3);
+    }
+}
+
+#[test]
+fn hgemm() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5eed);
+
+    for &(m, n, k) in &[(1, 1, 1), (32, 32, 8), (33, 70, 5), (40, 7, 31), (3, 20, 0)] {
+        for &accumulate in &[false, true] {
+            let (lda, ldb, ldc) = (k + 3, n + 1, n + 2);
+            // Round to `f16` so that the reference sees the same inputs
+            let a: Vec<F16Bits> = rng
+                .vec_f32(m * lda)
+                .into_iter()
+                .map(F16Bits::from_f32)
+                .collect();
+            let b: Vec<F16Bits> = rng
+                .vec_f32(k * ldb)
+                .into_iter()
+                .map(F16Bits::from_f32)
+                .collect();
+            let mut c = rng.vec_f32(m * ldc);
+
+            let mut expected = c.clone();
+            for i in 0..m {
+                for j in 0..n {
+                    let sum: f32 = (0..k)
+                        .map(|kk| a[i * lda + kk].to_f32() * b[kk * ldb + j].to_f32())
+                        .sum();
+                    if accumulate {
+                        expected[i * ldc + j] += sum;
+                    } else {
+                        expected[i * ldc + j] = sum;
+                    }
+                }
+            }
+
+            linalg::hgemm(
+                &mut ctx,
+                MatRef::with_stride(&a, m, k, lda),
+                MatRef::with_stride(&b, k, n, ldb),
+                MatMut::with_stride(&mut c, m, n, ldc),
+                accumulate,
+            );
+            assert_close(&c, &expected, 1e-4);
+        }
+    }
+}
+
+#[test]
+fn dgemm() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xd6e);
+
+    for &(m, n, k) in &[(1, 1, 1), (16, 32, 8), (17, 35, 5), (40, 7, 31), (3, 20, 0)] {
+        for &accumulate in &[false, true] {
+            let (lda, ldb, ldc) = (k + 3, n + 1, n + 2);
+            let mut vec_f64 =
+                |len| -> Vec<f64> { (0..len).map(|_| rng.next_f32() as f64).collect() };
+            let a = vec_f64(m * lda);
+            let b = vec_f64(k * ldb);
+            let mut c = vec_f64(m * ldc);
+
+            let mut expected = c.clone();
+            for i in 0..m {
+                for j in 0..n {
+                    let sum: f64 = (0..k).map(|kk| a[i * lda + kk] * b[kk * ldb + j]).sum();
+                    if accumulate {
+                        expected[i * ldc + j] += sum;
+                    } else {
+                        expected[i * ldc + j] = sum;
+                    }
+                }
+            }
+
+            linalg::dgemm(
+                &mut ctx,
+                MatRef::with_stride(&a, m, k, lda),
+                MatRef::with_stride(&b, k, n, ldb),
+                MatMut::with_stride(&mut c, m, n, ldc),
+                accumulate,
+            );
+            for (i, (&g, &e)) in c.iter().zip(&expected).enumerate() {
+                assert!(
+                    (g - e).abs() <= 1e-12 * (1.0 + e.abs()),
+                    "mismatch at {i}: got = {g}, expected = {e}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn igemm_fixed_vector() {
+    let mut ctx = common::ctx();
+    let a: [i8; 6] = [1, -2, 3, 127, -128, 0];
+    let b: [i8; 6] = [4, 5, -6, 7, 8, -9];
+    let mut c = [0i32; 4];
+    linalg::igemm_i8(
+        &mut ctx,
+        MatRef::new(&a, 2, 3),
+        -1,
+        MatRef::new(&b, 3, 2),
+        2,
+        MatMut::new(&mut c, 2, 2),
+        false,
+    );
+    assert_eq!(c, [36, -43, 1278, -262]);
+}
+
+#[test]
+fn igemm() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7a11);
+
+    for &(m, n, k) in &[(1, 1, 1), (32, 32, 2), (33, 40, 7), (5, 70, 64), (3, 4, 0)] {
+        for &accumulate in &[false, true] {
+            let (lda, ldb, ldc) = (k + 1, n + 3, n + 2);
+            let a: Vec<u8> = (0..m * lda).map(|_| rng.next() as u8).collect();
+            let b: Vec<u8> = (0..k * ldb).map(|_| rng.next() as u8).collect();
+            let c: Vec<i32> = (0..m * ldc).map(|_| rng.next() as i32 >> 8).collect();
+            let (a_zero, b_zero) = (rng.next() as u8, rng.next() as u8);
+
+            let expected = |signed: bool| {
+                let get = |x: u8| if signed { x as i8 as i32 } else { x as i32 };
+                let mut expected = c.clone();
+                for i in 0..m {
+                    for j in 0..n {
+                        let sum: i32 = (0..k)
+                            .map(|kk| {
+                                (get(a[i * lda + kk]) - get(a_zero))
+                                    * (get(b[kk * ldb + j]) - get(b_zero))
+                            })
+                            .sum();
+                        let e = &mut expected[i * ldc + j];
+                        *e = if accumulate { *e + sum } else { sum };
+                    }
+                }
+                expected
+            };
+
+            let mut got = c.clone();
+            linalg::igemm_u8(
+                &mut ctx,
+                MatRef::with_stride(&a, m, k, lda),
+                a_zero,
+                MatRef::with_stride(&b, k, n, ldb),
+                b_zero,
+                MatMut::with_stride(&mut got, m, n, ldc),
+                accumulate,
+            );
+            assert_eq!(got, expected(false), "u8 {m}x{n}x{k}");
+
+            let (a, b) = (a.iter().map(|&x| x as i8), b.iter().map(|&x| x as i8));
+            let (a, b): (Vec<i8>, Vec<i8>) = (a.collect(), b.collect());
+            let mut got = c.clone();
+            linalg::igemm_i8(
+                &mut ctx,
+                MatRef::with_stride(&a, m, k, lda),
+                a_zero as i8,
+                MatRef::with_stride(&b, k, n, ldb),
+                b_zero as i8,
+                MatMut::with_stride(&mut got, m, n, ldc),
+                accumulate,
+            );
+            assert_eq!(got, expected(true), "i8 {m}x{n}x{k}");
+        }
+    }
+}
+
+#[test]
+fn gemv() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x9e37);
+
+    // Cover both the vector-mode and outer-product paths
+    for &(m, n) in &[
+        (1, 1),
+        (5, 3),
+        (70, 15),
+        (130, 7),
+        (3, 16),
+        (70, 37),
+        (2, 0),
+    ] {
+        for &accumulate in &[false, true] {
+            let lda = n + 2;
+            let a = rng.vec_f32(m * lda);
+            let x = rng.vec_f32(n);
+            let mut y = rng.vec_f32(m);
+
+            let mut expected = y.clone();
+            for (i, e) in expected.iter_mut().enumerate() {
+                let sum: f32 = (0..n).map(|j| a[i * lda + j] * x[j]).sum();
+                *e = if accumulate { *e + sum } else { sum };
+            }
+            linalg::sgemv(
+                &mut ctx,
+                MatRef::with_stride(&a, m, n, lda),
+                &x,
+                &mut y,
+                accumulate,
+            );
+            assert_close(&y, &expected, 1e-4);
+
+            let a: Vec<f64> = a.iter().map(|&v| v as f64).collect();
+            let x: Vec<f64> = x.iter().map(|&v| v as f64).collect();
+            let mut y: Vec<f64> = rng.vec_f32(m).into_iter().map(|v| v as f64).collect();
+            let mut expected = y.clone();
+            for (i, e) in expected.iter_mut().enumerate() {
+                let sum: f64 = (0..n).map(|j| a[i * lda + j] * x[j]).sum();
+                *e = if accumulate { *e + sum } else { sum };
+            }
+            linalg::dgemv(
+                &mut ctx,
+                MatRef::with_stride(&a, m, n, lda),
+                &x,
+                &mut y,
+                accumulate,
+            );
+            for (i, (&g, &e)) in y.iter().zip(&expected).enumerate() {
+                assert!(
+                    (g - e).abs() <= 1e-12 * (1.0 + e.abs()),
+                    "mismatch at {i}: got = {g}, expected = {e}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn transpose() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7a5);
+
+    for &(m, n) in &[(1, 1), (16, 16), (32, 32), (17, 40), (70, 3), (5, 0)] {
+        let (lda, ldb) = (n + 3, m + 1);
+        let mut a: Vec<f32> = rng.vec_f32(m * lda);
+        // Special values must be moved as they are
+        if m * lda >= 4 {
+            a[..4].copy_from_slice(&[f32::from_bits(0x7fa0_0001), f32::INFINITY, -0.0, -1e-40]);
+        }
+        let mut b = vec![0f32; n * ldb];
+        linalg::transpose_f32(
+            &mut ctx,
+            MatRef::with_stride(&a, m, n, lda),
+            MatMut::with_stride(&mut b, n, m, ldb),
+        );
+        for i in 0..m {
+            for j in 0..n {
+                assert_eq!(
+                    b[j * ldb + i].to_bits(),
+                    a[i * lda + j].to_bits(),
+                    "({i}, {j})"
+                );
+            }
+        }
+
+        let a: Vec<i16> = (0..m * lda).map(|_| rng.next() as i16).collect();
+        let mut b = vec![0i16; n * ldb];
+        linalg::transpose_i16(
+            &mut ctx,
+            MatRef::with_stride(&a, m, n, lda),
+            MatMut::with_stride(&mut b, n, m, ldb),
+        );
+        for i in 0..m {
+            for j in 0..n {
+                assert_eq!(b[j * ldb + i], a[i * lda + j], "({i}, {j})");
+            }
+        }
+    }
+}
+
+#[test]
+fn conv2d() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xc0417);
+
+    // (batch, in_channels, out_channels, height, width, kernel, padding)
+    for &(batch, c_in, c_out, h, w, k, padding) in &[
+        (1, 1, 1, 1, 1, 1, 0),
+        (2, 3, 5, 4, 7, 3, 1),
+        (1, 2, 40, 3, 37, 3, 1),
+        (1, 17, 18, 5, 20, 1, 0),
+        (1, 2, 3, 6, 18, 3, 0),
+        (1, 1, 2, 2, 2, 3, 2),
+    ] {
+        let shape = linalg::Conv2dShape {
+            batch,
+            in_channels: c_in,
+            out_channels: c_out,
+            height: h,
+            width: w,
+            kernel: k,
+            padding,
+        };
+        let (oh, ow) = shape.output_size();
+        let input = rng.vec_f32(shape.input_len());
+        let filter = rng.vec_f32(shape.filter_len());
+        let bias = rng.vec_f32(c_out);
+
+        for bias in [None, Some(&bias[..])] {
+            let mut expected = vec![0f32; shape.output_len()];
+            for n in 0..batch {
+                for co in 0..c_out {
+                    for y in 0..oh {
+                        for x in 0..ow {
+                            let mut sum = bias.map_or(0.0, |b| b[co]);
+                            for ci in 0..c_in {
+                                for ky in 0..k {
+                                    for kx in 0..k {
+                                        let (iy, ix) = (y + ky, x + kx);
+                                        if iy < padding
+                                            || ix < padding
+                                            || iy - padding >= h
+                                            || ix - padding >= w
+                                        {
+                                            continue;
+                                        }
+                                        let (iy, ix) = (iy - padding, ix - padding);
+                                        sum += filter[((co * c_in + ci) * k + ky) * k + kx]
+                                            * input[((n * c_in + ci) * h + iy) * w + ix];
+                                    }
+                                }
+                            }
+                            expected[((n * c_out + co) * oh + y) * ow + x] = sum;
+                        }
+                    }
+                }
+            }
+
+            let mut output = vec![f32::NAN; shape.output_len()];
+            linalg::conv2d_f32(&mut ctx, shape, &input, &filter, bias, &mut output);
+            assert_close(&output, &expected, 1e-4);
+        }
+    }
+}
+
+#[test]
+fn sdot() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x810);
+
+    for len in [0, 1, 15, 16, 17, 100] {
+        let x = rng.vec_f32(len);
+        let y = rng.vec_f32(len);
+        let expected: f32 = x.iter().zip(&y).map(|(x, y)| x * y).sum();
+        let got = linalg::sdot(&mut ctx, &x, &y);
+        assert_close(&[got], &[expected], 1e-4);
+    }
+}
+
+#[test]
+fn apply_givens() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1919);
+
+    for &(rows, cols) in &[(2, 1), (5, 16), (8, 37)] {
+        let stride = cols + 3;
+        let mut a = rng.vec_f32(rows * stride);
+
+        // Triangularize the first column, as a QR update would
+        let mut rotations = Vec::new();
+        let mut pivot = a[0];
+        for k in 1..rows {
+            let (g, r) = linalg::Givens::zeroing(0, k, pivot, a[k * stride]);
+            rotations.push(g);
+            pivot = r;
+        }
+        rotations.push(linalg::Givens {
+            i: rows - 1,
+            k: 0,
+            c: 0.6,
+            s: -0.8,
+        });
+
+        let mut expected = a.clone();
+        for g in &rotations {
+            for j in 0..cols {
+                let (x, y) = (expected[g.i * stride + j], expected[g.k * stride + j]);
+                expected[g.i * stride + j] = g.c * x + g.s * y;
+                expected[g.k * stride + j] = g.c * y - g.s * x;
+            }
+        }
+
+        linalg::apply_givens(
+            &mut ctx,
+            MatMut::with_stride(&mut a, rows, cols, stride),
+            &rotations,
+        );
+
+        assert_close(&a, &expected, 1e-5);
+        if rows > 2 {
+            // The rotations annihilated the first column below the pivot
+            // (except for the row touched by the final rotation)
+            for k in 1..rows - 1 {
+                assert!(a[k * stride].abs() < 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn jacobi_eigh() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x8101);
+
+    for &n in &[1, 2, 5, 20] {
+        let mut a = rng.vec_f32(n * n);
+        for i in 0..n {
+            for j in 0..i {
+                a[i * n + j] = a[j * n + i];
+            }
+        }
+        let original = a.clone();
+
+        let mut vectors = vec![0.0; n * n];
+        let values = linalg::jacobi_eigh(
+            &mut ctx,
+            MatMut::new(&mut a, n, n),
+            MatMut::new(&mut vectors, n, n),
+            1e-6,
+            30,
+        );
+
+        for (i, &lambda) in values.iter().enumerate() {
+            let v = &vectors[i * n..][..n];
+            // `A v = λ v`
+            let av: Vec<f32> = (0..n)
+                .map(|r| (0..n).map(|c| original[r * n + c] * v[c]).sum())
+                .collect();
+            let lv: Vec<f32> = v.iter().map(|&x| x * lambda).collect();
+            assert_close(&av, &lv, 1e-3);
+
+            // Orthonormality
+            for (j, w) in vectors.chunks(n).enumerate() {
+                let dot: f32 = v.iter().zip(w).map(|(x, y)| x * y).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-4, "v{} · v{} = {}", i, j, dot);
+            }
+        }
+    }
+}
+
+#[test]
+fn jacobi_eigh_opposite_signs() {
+    let mut ctx = common::ctx();
+
+    // `A = Q diag(λ) Qᵀ` with a Householder reflection `Q = I - 2 u uᵀ / uᵀu`
+    let lambdas = [3.0, -3.0, 1.0, -1.0, 0.5];
+    let n = lambdas.len();
+    let u = [1.0f32, -2.0, 0.5, 3.0, 1.5];
+    let uu: f32 = u.iter().map(|x| x * x).sum();
+    let q = |r: usize, c: usize| (r == c) as u8 as f32 - 2.0 * u[r] * u[c] / uu;
+    let original: Vec<f32> = itertools::iproduct!(0..n, 0..n)
+        .map(|(r, c)| (0..n).map(|k| q(r, k) * lambdas[k] * q(c, k)).sum())
+        .collect();
+
+    for (n, original, expected) in [
+        (2, vec![0.0, 1.0, 1.0, 0.0], vec![-1.0, 1.0]),
+        (n, original, vec![-3.0, -1.0, 0.5, 1.0, 3.0]),
+    ] {
+        let mut a = original.clone();
+        let mut vectors = vec![0.0; n * n];
+        let mut values = linalg::jacobi_eigh(
+            &mut ctx,
+            MatMut::new(&mut a, n, n),
+            MatMut::new(&mut vectors, n, n),
+            1e-6,
+            30,
+        );
+
+        for (i, &lambda) in values.iter().enumerate() {
+            let v = &vectors[i * n..][..n];
+            let av: Vec<f32> = (0..n)
+                .map(|r| (0..n).map(|c| original[r * n + c] * v[c]).sum())
+                .collect();
+            let lv: Vec<f32> = v.iter().map(|&x| x * lambda).collect();
+            assert_close(&av, &lv, 1e-4);
+        }
+        values.sort_by(f32::total_cmp);
+        assert_close(&values, &expected, 1e-5);
+    }
+}
+
+fn check_batch_inverse<const N: usize>(
+    ctx: &mut impl amx::Amx,
+    rng: &mut Xorshift32,
+    count: usize,
+) {
+    // Diagonally dominant matrices are safe to invert without pivoting
+    let input: Vec<[[f32; N]; N]> = (0..count)
+        .map(|_| {
+            std::array::from_fn(|r| {
+                std::array::from_fn(|c| rng.next_f32() + if r == c { N as f32 } else { 0.0 })
+            })
+        })
+        .collect();
+    let mut output = vec![[[0f32; N]; N]; count];
+
+    linalg::batch_inverse(ctx, &input, &mut output);
+
+    for (a, inv) in input.iter().zip(&output) {
+        for (r, a_row) in a.iter().enumerate() {
+            let got: Vec<f32> = (0..N)
+                .map(|c| a_row.iter().zip(inv).map(|(&x, inv)| x * inv[c]).sum())
+                .collect();
+            let expected: Vec<f32> = (0..N).map(|c| (r == c) as u32 as f32).collect();
+            assert_close(&got, &expected, 1e-4);
+        }
+    }
+}
+
+#[test]
+fn batch_inverse() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x2525);
+    for &count in &[0, 1, 16, 37] {
+        check_batch_inverse::<1>(&mut ctx, &mut rng, count);
+        check_batch_inverse::<4>(&mut ctx, &mut rng, count);
+        check_batch_inverse::<8>(&mut ctx, &mut rng, count);
+        check_batch_inverse::<16>(&mut ctx, &mut rng, count);
+    }
+}
+
+#[test]
+fn batch_tridiagonal() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x3131);
+
+    for &(n, batch) in &[(1, 1), (5, 16), (12, 37)] {
+        let stride = batch + 1;
+        let sub = rng.vec_f32(n * stride);
+        let sup = rng.vec_f32(n * stride);
+        let diag: Vec<f32> = rng.vec_f32(n * stride).iter().map(|x| x + 3.0).collect();
+        let x: Vec<f32> = rng.vec_f32(n * stride);
+
+        // Construct the right-hand side from the known solution `x`
+        let mut rhs = vec![0.0; n * stride];
+        for i in 0..n {
+            for s in 0..batch {
+                let at = |i: usize| i * stride + s;
+                let mut d = diag[at(i)] * x[at(i)];
+                if i > 0 {
+                    d += sub[at(i)] * x[at(i - 1)];
+                }
+                if i + 1 < n {
+                    d += sup[at(i)] * x[at(i + 1)];
+                }
+                rhs[at(i)] = d;
+            }
+        }
+
+        linalg::batch_tridiagonal(
+            &mut ctx,
+            MatRef::with_stride(&sub, n, batch, stride),
+            MatRef::with_stride(&diag, n, batch, stride),
+            MatRef::with_stride(&sup, n, batch, stride),
+            MatMut::with_stride(&mut rhs, n, batch, stride),
+        );
+
+        for i in 0..n {
+            assert_close(&rhs[i * stride..][..batch], &x[i * stride..][..batch], 1e-4);
+        }
+    }
+}
+
+#[test]
+fn kalman() {
+    use linalg::{KalmanModel, KalmanState};
+
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x4545);
+
+    // Constant-velocity model in 2D: state = [px, py, vx, vy]
+    let dt = 0.1;
+    let model = KalmanModel::<4, 2> {
+        f: [
+            [1.0, 0.0, dt, 0.0],
+            [0.0, 1.0, 0.0, dt],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        q: std::array::from_fn(|i| std::array::from_fn(|j| if i == j { 0.01 } else { 0.0 })),
+        h: [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]],
+        r: [[0.5, 0.1], [0.1, 0.4]],
+    };
+
+    let mut states: Vec<KalmanState<4>> = (0..37)
+        .map(|_| KalmanState {
+            x: std::array::from_fn(|_| rng.next_f32()),
+            p: std::array::from_fn(|i| {
+                std::array::from_fn(|j| {
+                    if i == j {
+                        1.0 + rng.next_f32().abs()
+                    } else {
+                        0.0
+                    }
+                })
+            }),
+        })
+        .collect();
+    let measurements: Vec<[f32; 2]> = (0..states.len())
+        .map(|_| [rng.next_f32(), rng.next_f32()])
+        .collect();
+
+    // Scalar reference implementation
+    type Mat = Vec<Vec<f64>>;
+    fn mul(a: &Mat, b: &Mat) -> Mat {
+        (0..a.len())
+            .map(|i| {
+                (0..b[0].len())
+                    .map(|j| (0..b.len()).map(|k| a[i][k] * b[k][j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+    fn t(a: &Mat) -> Mat {
+        (0..a[0].len())
+            .map(|j| (0..a.len()).map(|i| a[i][j]).collect())
+            .collect()
+    }
+    fn add(a: &Mat, b: &Mat, sign: f64) -> Mat {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.iter().zip(b).map(|(a, b)| a + sign * b).collect())
+            .collect()
+    }
+    fn mat<const R: usize, const C: usize>(a: &[[f32; C]; R]) -> Mat {
+        a.iter()
+            .map(|r| r.iter().map(|&x| x as f64).collect())
+            .collect()
+    }
+    let (f, q, h, r) = (mat(&model.f), mat(&model.q), mat(&model.h), mat(&model.r));
+    // The second round starts from non-diagonal covariances
+    for _ in 0..2 {
+        let expected: Vec<(Mat, Mat)> = states
+            .iter()
+            .zip(&measurements)
+            .map(|(s, z)| {
+                let x = t(&mat(&[s.x]));
+                let x = mul(&f, &x);
+                let p = add(&mul(&mul(&f, &mat(&s.p)), &t(&f)), &q, 1.0);
+                let s_mat = add(&mul(&mul(&h, &p), &t(&h)), &r, 1.0);
+                let det = s_mat[0][0] * s_mat[1][1] - s_mat[0][1] * s_mat[1][0];
+                let s_inv = vec![
+                    vec![s_mat[1][1] / det, -s_mat[0][1] / det],
+                    vec![-s_mat[1][0] / det, s_mat[0][0] / det],
+                ];
+                let k = mul(&mul(&p, &t(&h)), &s_inv);
+                let innovation = add(&t(&mat(&[*z])), &mul(&h, &x), -1.0);
+                let x = add(&x, &mul(&k, &innovation), 1.0);
+                let p = add(&p, &mul(&mul(&k, &h), &p), -1.0);
+                (x, p)
+            })
+            .collect();
+
+        linalg::kalman_predict(&mut ctx, &model, &mut states);
+        linalg::kalman_update(&mut ctx, &model, &mut states, &measurements);
+
+        for (s, (x, p)) in states.iter().zip(&expected) {
+            let x: Vec<f32> = x.iter().map(|r| r[0] as f32).collect();
+            assert_close(&s.x, &x, 1e-4);
+            for (got, expected) in s.p.iter().zip(p) {
+                let expected: Vec<f32> = expected.iter().map(|&x| x as f32).collect();
+                assert_close(got, &expected, 1e-4);
+            }
+        }
+    }
+}
+
+#[test]
+fn portfolio() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x9f0);
+
+    for (t, n) in [(2, 1), (50, 20), (33, 37)] {
+        let returns: Vec<f32> = rng.vec_f32(t * n).iter().map(|r| r * 0.05).collect();
+        let mut cov = vec![f32::NAN; n * n];
+        linalg::returns_covariance(
+            &mut ctx,
+            MatRef::new(&returns, t, n),
+            MatMut::new(&mut cov, n, n),
+        );
+
+        let mean: Vec<f32> = (0..n)
+            .map(|j| (0..t).map(|i| returns[i * n + j]).sum::<f32>() / t as f32)
+            .collect();
+        let mut expected = vec![0f32; n * n];
+        for i in 0..n {
+            for j in 0..n {
+                expected[i * n + j] = (0..t)
+                    .map(|k| (returns[k * n + i] - mean[i]) * (returns[k * n + j] - mean[j]))
+                    .sum::<f32>()
+                    / (t - 1) as f32;
+            }
+        }
+        assert_close(&cov, &expected, 1e-5);
+
+        let p = 21;
+        let weights = rng.vec_f32(p * n);
+        let mut variance = vec![0f32; p];
+        linalg::portfolio_variance(
+            &mut ctx,
+            MatRef::new(&cov, n, n),
+            MatRef::new(&weights, p, n),
+            &mut variance,
+        );
+        for (k, &got) in variance.iter().enumerate() {
+            let w = &weights[k * n..][..n];
+            let expected: f32 = (0..n)
+                .map(|i| (0..n).map(|j| w[i] * cov[i * n + j] * w[j]).sum::<f32>())
+                .sum();
+            assert_close(&[got], &[expected], 1e-4);
+            assert!(got >= -1e-6, "variance {k} is negative: {got}");
+        }
+    }
+}
@@ -1,5 +1,7 @@
+mod common;
+
 use aligned_box::AlignedBox;
-use amx::{AmxOps, XRow, YRow, ZRow, prelude::*};
+use amx::{Aligned64, Aligned128, AmxOps, StoreHint, XRow, YRow, ZRow, prelude::*};
 use itertools::iproduct;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -94,7 +96,7 @@ unsafe fn store_generic<T>(
 
 #[test]
 fn copy_and_check_memory() {
-    let mut ctx = amx::AmxCtx::new().unwrap();
+    let mut ctx = common::ctx();
 
     let mut src: AlignedBox<[u16]> = AlignedBox::slice_from_default(0x80, 4096).unwrap();
     for (i, src) in src.iter_mut().enumerate() {
@@ -126,9 +128,9 @@ fn copy_and_check_memory() {
             .collect();
 
         unsafe {
-            load_generic(&mut *ctx, src.as_ptr(), reg_offset, size, reg, interleaved);
+            load_generic(&mut ctx, src.as_ptr(), reg_offset, size, reg, interleaved);
             store_generic(
-                &mut *ctx,
+                &mut ctx,
                 got.as_mut_ptr(),
                 reg_offset,
                 size,
@@ -143,7 +145,7 @@ fn copy_and_check_memory() {
 
 #[test]
 fn load_and_check_register() {
-    let mut ctx = amx::AmxCtx::new().unwrap();
+    let mut ctx = common::ctx();
 
     let mut pat1: AlignedBox<[u64]> = AlignedBox::slice_from_default(0x80, 16).unwrap();
     for (i, pat1) in pat1.iter_mut().enumerate() {
@@ -172,7 +174,7 @@ fn load_and_check_register() {
         for i in 0..reg_size / 8 {
             unsafe {
                 load_generic(
-                    &mut *ctx,
+                    &mut ctx,
                     pat2[i * 8..].as_ptr() as *mut (),
                     i,
                     MemSize::_64,
@@ -184,7 +186,7 @@ fn load_and_check_register() {
 
         // Load `pat1` to somewhere in the register
         unsafe {
-            load_generic(&mut *ctx, pat1.as_ptr(), reg_offset, size, reg, interleaved);
+            load_generic(&mut ctx, pat1.as_ptr(), reg_offset, size, reg, interleaved);
         }
 
         // Read the whole register set
@@ -216,7 +218,7 @@ fn load_and_check_register() {
             // the resultant low parts go to
             // `z[reg_index][second_half * 4..][..4]`. The high parts go to
             // `z[reg_index + 1][second_half * 4..][..4]`
-            let reg_start = (reg_offset as usize % 2) * 4 + (reg_offset as usize / 2) * 16;
+            let reg_start = (reg_offset % 2) * 4 + (reg_offset / 2) * 16;
             for i in (0..size.num_bytes() / 8).step_by(2) {
                 let low1 = pat1[i] & 0xffff_ffff;
                 let low2 = pat1[i + 1] & 0xffff_ffff;
@@ -228,7 +230,7 @@ fn load_and_check_register() {
         } else {
             // Simple copy with register index wrap-around
             for i in 0..size.num_bytes() / 8 {
-                expected[(reg_offset as usize * 8 + i) % reg_size] = pat1[i];
+                expected[(reg_offset * 8 + i) % reg_size] = pat1[i];
             }
         }
 
@@ -239,3 +241,565 @@ fn load_and_check_register() {
         );
     }
 }
+
+#[test]
+fn load_store_unchecked() {
+    #[repr(align(128))]
+    struct Aligned([u8; 128]);
+
+    let mut ctx = common::ctx();
+    let input = Aligned(std::array::from_fn(|i| i as u8 ^ 0x5a));
+
+    unsafe {
+        for (i, row) in [(0, X0), (6, X6)] {
+            let mut out = Aligned([0; 128]);
+            ctx.load512_unchecked(input.0.as_ptr(), row);
+            ctx.store512(out.0.as_mut_ptr(), XRow(i));
+            assert_eq!(out.0[..64], input.0[..64]);
+
+            ctx.load1024_aligned(input.0.as_ptr(), YRow(i));
+            ctx.store1024_aligned_unchecked(out.0.as_mut_ptr(), YRow(i));
+            assert_eq!(out.0, input.0);
+        }
+
+        for i in [0, 31, 62] {
+            let mut out = Aligned([0; 128]);
+            ctx.load1024_aligned_unchecked(input.0.as_ptr(), ZRow(i));
+            ctx.store512_unchecked(out.0.as_mut_ptr(), ZRow(i + 1));
+            assert_eq!(out.0[..64], input.0[64..]);
+        }
+    }
+}
+
+/// Forwards the calls to `inner`, recording the sizes of memory operations.
+struct MemRecorder<'a, T: ?Sized> {
+    inner: &'a mut T,
+    sizes: Vec<MemSize>,
+}
+
+impl<T: ?Sized> MemRecorder<'_, T> {
+    fn record(&mut self, x: u64) {
+        self.sizes.push(if x & (1 << 62) != 0 {
+            MemSize::_128
+        } else {
+            MemSize::_64
+        });
+    }
+}
+
+unsafe impl<T: AmxOps + ?Sized> AmxOps for MemRecorder<'_, T> {
+    unsafe fn ldx(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.ldx(x, ptr) }
+    }
+    unsafe fn ldy(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.ldy(x, ptr) }
+    }
+    unsafe fn stx(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.stx(x, ptr) }
+    }
+    unsafe fn sty(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.sty(x, ptr) }
+    }
+    unsafe fn ldz(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.ldz(x, ptr) }
+    }
+    unsafe fn stz(&mut self, x: u64, ptr: *mut ()) {
+        self.record(x);
+        unsafe { self.inner.stz(x, ptr) }
+    }
+    unsafe fn ldzi(&mut self, x: u64, ptr: *mut ()) {
+        unsafe { self.inner.ldzi(x, ptr) }
+    }
+    unsafe fn stzi(&mut self, x: u64, ptr: *mut ()) {
+        unsafe { self.inner.stzi(x, ptr) }
+    }
+    fn extrx(&mut self, x: u64) {
+        self.inner.extrx(x)
+    }
+    fn extry(&mut self, x: u64) {
+        self.inner.extry(x)
+    }
+    fn fma64(&mut self, x: u64) {
+        self.inner.fma64(x)
+    }
+    fn fms64(&mut self, x: u64) {
+        self.inner.fms64(x)
+    }
+    fn fma32(&mut self, x: u64) {
+        self.inner.fma32(x)
+    }
+    fn fms32(&mut self, x: u64) {
+        self.inner.fms32(x)
+    }
+    fn mac16(&mut self, x: u64) {
+        self.inner.mac16(x)
+    }
+    fn fma16(&mut self, x: u64) {
+        self.inner.fma16(x)
+    }
+    fn fms16(&mut self, x: u64) {
+        self.inner.fms16(x)
+    }
+    fn vecint(&mut self, x: u64) {
+        self.inner.vecint(x)
+    }
+    fn vecfp(&mut self, x: u64) {
+        self.inner.vecfp(x)
+    }
+    fn matint(&mut self, x: u64) {
+        self.inner.matint(x)
+    }
+    fn matfp(&mut self, x: u64) {
+        self.inner.matfp(x)
+    }
+    fn genlut(&mut self, x: u64) {
+        self.inner.genlut(x)
+    }
+}
+
+#[test]
+fn load_store_multi() {
+    #[repr(align(128))]
+    struct Aligned([u8; 256]);
+
+    let mut ctx = common::ctx();
+    let mut ctx = MemRecorder {
+        inner: &mut ctx,
+        sizes: Vec::new(),
+    };
+    let input = Aligned(std::array::from_fn(|i| (i * 7) as u8));
+    let p = |i: usize| input.0[i * 64..].as_ptr();
+
+    unsafe {
+        // Adjacent rows and aligned, adjacent pointers are fused
+        ctx.load512_multi(
+            [p(0), p(1), p(2), p(3)],
+            [XRow(2), XRow(3), XRow(4), XRow(5)],
+        );
+        assert_eq!(ctx.sizes, [MemSize::_128, MemSize::_128]);
+        ctx.sizes.clear();
+
+        // Misaligned pairs are not fused
+        ctx.load512_multi([p(1), p(2), p(3)], (YRow(0), YRow(1), YRow(2)));
+        assert_eq!(ctx.sizes, [MemSize::_64, MemSize::_128]);
+        ctx.sizes.clear();
+
+        // Different register files are not fused
+        ctx.load512_multi([p(0), p(1)], (ZRow(10), XRow(1)));
+        assert_eq!(ctx.sizes, [MemSize::_64, MemSize::_64]);
+        ctx.sizes.clear();
+
+        let mut output = Aligned([0; 256]);
+        let base = output.0.as_mut_ptr();
+        let ptrs = [0, 1, 2, 3].map(|i| base.add(i * 64));
+        // Rows in descending order are not fused
+        ctx.store512_multi(ptrs, (XRow(2), XRow(3), YRow(2), YRow(1)));
+        assert_eq!(ctx.sizes, [MemSize::_128, MemSize::_64, MemSize::_64]);
+        assert_eq!(output.0[..128], input.0[..128]);
+        assert_eq!(output.0[128..192], input.0[192..]);
+        assert_eq!(output.0[192..], input.0[128..192]);
+        ctx.sizes.clear();
+
+        ctx.store512_multi(ptrs, [ZRow(10), ZRow(11), ZRow(63), ZRow(0)]);
+        assert_eq!(ctx.sizes, [MemSize::_128, MemSize::_64, MemSize::_64]);
+        assert_eq!(output.0[..64], input.0[..64]);
+    }
+}
+
+#[test]
+fn read_typed_views() {
+    let mut ctx = common::ctx();
+    let bytes: [u8; 64] = std::array::from_fn(|i| i as u8 * 3 + 1);
+    unsafe {
+        ctx.load512(bytes.as_ptr(), XRow(2));
+        ctx.load512(bytes.as_ptr(), YRow(7));
+        ctx.load512(bytes.as_ptr(), ZRow(40));
+    }
+
+    let x = ctx.read_x_as::<u8>();
+    assert_eq!(x[2], bytes);
+    let y = ctx.read_y_as::<i16>();
+    assert_eq!(y[7][5], i16::from_ne_bytes([bytes[10], bytes[11]]));
+    let z = ctx.read_z_as::<u64>();
+    assert_eq!(
+        z[40][3],
+        u64::from_ne_bytes(bytes[24..32].try_into().unwrap())
+    );
+
+    let values: [f64; 8] = std::array::from_fn(|i| i as f64 * -1.5);
+    unsafe { ctx.load512(values.as_ptr(), ZRow(63)) };
+    assert_eq!(ctx.read_z_as::<f64>()[63], values);
+
+    let halves = [0x3c00u16, 0xc000, 0x3800, 0x7c00].repeat(8);
+    unsafe { ctx.load512(halves.as_ptr(), XRow(0)) };
+    let x = ctx.read_x_as::<amx::fp16::F16Bits>();
+    assert_eq!(
+        x[0][..4].iter().map(|v| v.to_f32()).collect::<Vec<_>>(),
+        [1.0, -2.0, 0.5, f32::INFINITY]
+    );
+}
+
+#[test]
+fn load_store_matrix() {
+    let mut ctx = common::ctx();
+    // A 12×40 `u16` matrix (80 bytes per row)
+    let a: Vec<u16> = (0..12 * 40).collect();
+    unsafe { ctx.load_matrix(a[3 * 40 + 8..].as_ptr(), 80, 8, ZRow(50)) };
+    let z = ctx.read_z_as::<u16>();
+    for i in 0..8 {
+        assert_eq!(z[50 + i][..], a[(3 + i) * 40 + 8..][..32], "i = {i}");
+    }
+
+    let mut b = vec![0u16; 12 * 40];
+    unsafe { ctx.store_matrix(b[40 + 4..].as_mut_ptr(), 80, 8, ZRow(50)) };
+    for (i, row) in b.chunks(40).enumerate() {
+        if (1..9).contains(&i) {
+            assert_eq!(row[4..36], a[(i + 2) * 40 + 8..][..32], "i = {i}");
+            assert_eq!(row[..4], [0; 4]);
+            assert_eq!(row[36..], [0; 4]);
+        } else {
+            assert_eq!(row, [0; 40]);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn load_matrix_out_of_range() {
+    let mut ctx = common::ctx();
+    let a = [0u8; 64 * 3];
+    unsafe { ctx.load_matrix(a.as_ptr(), 64, 3, YRow(6)) };
+}
+
+#[test]
+fn load_store_rows() {
+    #[repr(align(128))]
+    struct Aligned([u8; 64 * 9]);
+
+    let mut ctx = common::ctx();
+    let input = Aligned(std::array::from_fn(|i| (i * 13) as u8));
+    // Aligned, then offset by one row so that the pairs straddle 128-byte
+    // boundaries
+    for offset in [0, 64] {
+        let src = &input.0[offset..];
+        ctx.load_rows_slice(src, XRow(0)..XRow(8));
+        assert_eq!(ctx.read_x()[..], src[..512], "offset = {offset}");
+        ctx.load_rows_slice(src, YRow(3)..YRow(6));
+        assert_eq!(ctx.read_y()[3 * 64..6 * 64], src[..3 * 64]);
+        ctx.load_rows_slice(src, ZRow(61)..ZRow(64));
+        assert_eq!(ctx.read_z()[61 * 64..], src[..3 * 64]);
+
+        let mut output = Aligned([0; 64 * 9]);
+        let dst = &mut output.0[offset..];
+        ctx.store_rows_slice(dst, XRow(1)..XRow(6));
+        assert_eq!(dst[..5 * 64], input.0[offset + 64..][..5 * 64]);
+        assert!(dst[5 * 64..].iter().all(|&b| b == 0));
+    }
+
+    // An empty range does nothing
+    ctx.store_rows_slice(&mut [0u8; 0], ZRow(5)..ZRow(5));
+}
+
+#[test]
+#[should_panic = "`src` is shorter than 3 rows"]
+fn load_rows_too_short() {
+    let mut ctx = common::ctx();
+    ctx.load_rows_slice(&[0u8; 64 * 2], YRow(0)..YRow(3));
+}
+
+#[test]
+#[should_panic = "exceed the register file"]
+fn load_rows_out_of_range() {
+    let mut ctx = common::ctx();
+    ctx.load_rows_slice(&[0u8; 64 * 3], XRow(6)..XRow(9));
+}
+
+#[test]
+fn store_hinted() {
+    #[repr(align(128))]
+    struct Aligned([u8; 128]);
+
+    let mut ctx = common::ctx();
+    let input = Aligned(std::array::from_fn(|i| i as u8 ^ 0xa5));
+    unsafe { ctx.load1024_aligned(input.0.as_ptr(), ZRow(20)) };
+    for hint in [StoreHint::Normal, StoreHint::NonTemporal] {
+        let mut out = Aligned([0; 128]);
+        unsafe { ctx.store1024_aligned_hinted(out.0.as_mut_ptr(), ZRow(20), hint) };
+        assert_eq!(out.0, input.0, "{hint:?}");
+
+        let mut out = [0u8; 64];
+        unsafe { ctx.store512_hinted(out.as_mut_ptr(), ZRow(21), hint) };
+        assert_eq!(out, input.0[64..], "{hint:?}");
+    }
+}
+
+#[test]
+fn load_store_aligned_buffers() {
+    let mut ctx = common::ctx();
+    let src = Aligned128(std::array::from_fn::<u16, 64, _>(|i| i as u16 * 3));
+    assert_eq!(src.as_ptr() as usize % 128, 0);
+    ctx.load1024(&src, YRow(2));
+    assert_eq!(ctx.read_y_as::<u16>()[2..4].as_flattened(), &src[..]);
+
+    let mut dst = Aligned128([0u16; 64]);
+    ctx.store1024(&mut dst, YRow(2));
+    assert_eq!(dst, src);
+
+    let src = Aligned64([7i8; 80]);
+    ctx.load512_aligned(&src, ZRow(63));
+    let mut dst = Aligned64([0i8; 64]);
+    ctx.store512_aligned(&mut dst, ZRow(63));
+    assert_eq!(*dst, [7; 64]);
+}
+
+#[test]
+#[should_panic = "register row index 8 is out of range for a register file of 8 rows"]
+fn load_row_out_of_range() {
+    let mut ctx = common::ctx();
+    ctx.load512_slice(&[0u8; 64], XRow(8));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "isn't aligned to 128-byte boundaries"]
+fn load1024_misaligned() {
+    let mut ctx = common::ctx();
+    let src = Aligned128([0u8; 256]);
+    unsafe { ctx.load1024_aligned(src[64..].as_ptr(), ZRow(0)) };
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "the pointer is null"]
+fn store_null() {
+    let mut ctx = common::ctx();
+    unsafe { ctx.store512(std::ptr::null_mut::<u8>(), YRow(0)) };
+}
+
+#[test]
+fn load_store_slice() {
+    let mut ctx = common::ctx();
+    let src: Vec<i32> = (0..20).collect();
+    ctx.load512_slice(&src, ZRow(9));
+    ctx.load512_slice(&src[4..], YRow(1));
+
+    let mut dst = [0i32; 16];
+    ctx.store512_slice(&mut dst, ZRow(9));
+    assert_eq!(dst[..], src[..16]);
+
+    let mut dst = [0u8; 65];
+    ctx.store512_slice(&mut dst, YRow(1));
+    assert_eq!(dst[..4], 4i32.to_ne_bytes());
+    assert_eq!(dst[64], 0);
+}
+
+#[test]
+#[should_panic = "`src` is shorter than 64 bytes"]
+fn load_slice_too_short() {
+    let mut ctx = common::ctx();
+    ctx.load512_slice(&[0u64; 7], XRow(0));
+}
+
+#[test]
+fn write_register_files() {
+    let mut ctx = common::ctx();
+    let x: [u8; 512] = std::array::from_fn(|i| (i * 7) as u8);
+    let y: [u8; 512] = std::array::from_fn(|i| (i * 11 + 3) as u8);
+    let z: [u8; 4096] = std::array::from_fn(|i| (i * 13 + 5) as u8);
+    ctx.write_x(&x);
+    ctx.write_y(&y);
+    ctx.write_z(&z);
+    assert_eq!(ctx.read_x(), x);
+    assert_eq!(ctx.read_y(), y);
+    assert_eq!(ctx.read_z(), z);
+}
+
+#[test]
+fn snapshot_restore() {
+    let mut ctx = common::ctx();
+    let x: [u8; 512] = std::array::from_fn(|i| (i * 3) as u8);
+    let y: [u8; 512] = std::array::from_fn(|i| (i * 5 + 1) as u8);
+    let z: [u8; 4096] = std::array::from_fn(|i| (i * 7 + 2) as u8);
+    ctx.write_x(&x);
+    ctx.write_y(&y);
+    ctx.write_z(&z);
+
+    let state = ctx.snapshot();
+    assert_eq!(state.x(), x);
+    assert_eq!(state.y(), y);
+    assert_eq!(state.z().as_flattened(), z);
+
+    ctx.write_x(&[0; 512]);
+    ctx.write_y(&[0; 512]);
+    ctx.write_z(&[0; 4096]);
+    ctx.restore(&state);
+    assert_eq!(ctx.read_x(), x);
+    assert_eq!(ctx.read_y(), y);
+    assert_eq!(ctx.read_z(), z);
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn half_elements() {
+    use half::{bf16, f16};
+
+    let mut ctx = common::ctx();
+    let x: [f16; 32] = std::array::from_fn(|i| f16::from_f32(i as f32 - 16.0));
+    let y: [f16; 32] = std::array::from_fn(|i| f16::from_f32(0.5 + i as f32 / 64.0));
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+    assert_eq!(ctx.read_x_as::<f16>()[0], x);
+    assert_eq!(ctx.read_y_as::<f16>()[0], y);
+
+    ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    let z = ctx.read_z_as::<f16>();
+    for (i, j) in itertools::iproduct!(0..32, 0..32) {
+        assert_eq!(z[j * 2][i], x[i] * y[j], "x[{i}] * y[{j}]");
+    }
+    assert_eq!(amx::fp16::F16Bits::from(z[0][1]).to_f32(), z[0][1].to_f32());
+
+    let b: [bf16; 32] = std::array::from_fn(|i| bf16::from_f32(i as f32 * 1e30));
+    ctx.load512_slice(&b, ZRow(5));
+    assert_eq!(ctx.read_z_as::<bf16>()[5], b);
+}
+
+#[test]
+fn clear() {
+    let mut ctx = common::ctx();
+    let x: [u8; 512] = std::array::from_fn(|i| i as u8 | 1);
+    let y: [u8; 512] = std::array::from_fn(|i| !(i as u8) | 1);
+    // Includes NaNs and infinities in every lane type
+    let z = [0xff; 4096];
+
+    ctx.write_x(&x);
+    ctx.write_y(&y);
+    ctx.write_z(&z);
+    ctx.clear_z();
+    assert_eq!(ctx.read_z(), [0; 4096]);
+    assert_eq!((ctx.read_x(), ctx.read_y()), (x, y));
+
+    ctx.clear_x();
+    assert_eq!((ctx.read_x(), ctx.read_y()), ([0; 512], y));
+    ctx.clear_y();
+    assert_eq!(ctx.read_y(), [0; 512]);
+
+    ctx.write_x(&x);
+    ctx.write_y(&y);
+    ctx.write_z(&z);
+    ctx.clear_all();
+    assert_eq!(
+        (ctx.read_x(), ctx.read_y(), ctx.read_z()),
+        ([0; 512], [0; 512], [0; 4096])
+    );
+}
+
+#[test]
+fn fill_and_broadcast() {
+    let mut ctx = common::ctx();
+
+    ctx.fill_x_row(0xa5u8, XRow(3));
+    assert_eq!(ctx.read_x_as::<u8>()[3], [0xa5; 64]);
+    ctx.fill_y_row(-3i16, YRow(7));
+    assert_eq!(ctx.read_y_as::<i16>()[7], [-3; 32]);
+    ctx.fill_z_row(1.25f64, ZRow(63));
+    assert_eq!(ctx.read_z_as::<f64>()[63], [1.25; 8]);
+    ctx.fill_x_row(amx::fp16::F16Bits::from_f32(0.5), XRow(0));
+    assert_eq!(ctx.read_x_as::<u16>()[0], [0x3800; 32]);
+
+    let row: [u32; 16] = std::array::from_fn(|i| i as u32 * 0x0101_0101);
+    ctx.load512_slice(&row, YRow(2));
+    ctx.broadcast_to_x(YRow(2));
+    assert_eq!(ctx.read_x_as::<u32>(), [row; 8]);
+    ctx.load512_slice(&row, ZRow(17));
+    ctx.clear_y();
+    ctx.broadcast_to_y(ZRow(17));
+    assert_eq!(ctx.read_y_as::<u32>(), [row; 8]);
+    // The source can be one of the destination rows
+    ctx.fill_x_row(7u32, XRow(4));
+    ctx.broadcast_to_x(XRow(4));
+    assert_eq!(ctx.read_x_as::<u32>(), [[7; 16]; 8]);
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_gather_scatter_f32(indices: Vec<u32>, to_z: bool) {
+    let src: Vec<f32> = (0..37).map(|i| i as f32 * 1.5 - 9.0).collect();
+    let indices: [u32; 16] = std::array::from_fn(|i| indices.get(i).map_or(0, |&x| x % 37));
+
+    let mut ctx = common::ctx();
+    let mut gathered = [0.0f32; 16];
+    let mut scattered = [f32::NAN; 37];
+    let mut expected = [f32::NAN; 37];
+    for &index in &indices {
+        expected[index as usize] = src[index as usize];
+    }
+    if to_z {
+        unsafe { ctx.gather_f32(src.as_ptr(), &indices, ZRow(33)) };
+        ctx.store512_slice(&mut gathered, ZRow(33));
+        unsafe { ctx.scatter_f32(ZRow(33), scattered.as_mut_ptr(), &indices) };
+    } else {
+        ctx.gather_f32_slice(&src, &indices, YRow(6));
+        ctx.store512_slice(&mut gathered, YRow(6));
+        ctx.scatter_f32_slice(YRow(6), &mut scattered, &indices);
+    }
+
+    assert_eq!(gathered, indices.map(|i| src[i as usize]));
+    assert_eq!(
+        scattered.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+        expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic = "index out of bounds of `dst`"]
+fn scatter_f32_slice_out_of_bounds() {
+    let mut ctx = common::ctx();
+    let mut dst = [0.0f32; 16];
+    let mut indices = [0; 16];
+    indices[3] = 16;
+    ctx.scatter_f32_slice(XRow(0), &mut dst, &indices);
+}
+
+#[test]
+fn interleaved_xy_matches_z() {
+    use either::{Either, Left, Right};
+
+    let mut ctx = common::ctx();
+    let init: Vec<u8> = (0..512).map(|i| (i * 7 % 251) as u8).collect();
+    for row in 0..8 {
+        let data: [u8; 64] = std::array::from_fn(|i| (i + row * 64) as u8 ^ 0x5a);
+        for in_y in [false, true] {
+            let xy = |i: usize| -> Either<XRow, YRow> {
+                if in_y { Right(YRow(i)) } else { Left(XRow(i)) }
+            };
+            // Start from the same contents in X/Y and Z
+            for i in 0..8 {
+                ctx.load512_slice(&init[i * 64..], ZRow(i));
+                ctx.load512_slice(&init[i * 64..], xy(i));
+            }
+            unsafe {
+                ctx.load512_interleaved(data.as_ptr(), ZRow(row));
+                ctx.load512_interleaved(data.as_ptr(), xy(row));
+            }
+            for i in 0..8 {
+                let (mut from_z, mut from_xy) = ([0u8; 64], [0u8; 64]);
+                ctx.store512_slice(&mut from_z, ZRow(i));
+                ctx.store512_slice(&mut from_xy, xy(i));
+                assert_eq!(from_xy, from_z, "in_y = {in_y}, row {row}, {i}");
+            }
+
+            let (mut from_z, mut from_xy) = ([0u8; 64], [0u8; 64]);
+            unsafe {
+                ctx.store512_interleaved(from_z.as_mut_ptr(), ZRow(row ^ 1));
+                ctx.store512_interleaved(from_xy.as_mut_ptr(), xy(row ^ 1));
+            }
+            assert_eq!(from_xy, from_z, "in_y = {in_y}, row {row}");
+        }
+    }
+}
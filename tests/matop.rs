@@ -0,0 +1,335 @@
+mod common;
+
+use amx::{
+    Amx, AmxOps, MatFpOp, MatFpTy, MatIntOp, MatIntOperand, MatIntTy, XBytes, XRow, YBytes, YRow,
+    ZRow, fp16,
+};
+use common::Xorshift32;
+use itertools::iproduct;
+
+/// Get the Z row and byte offset of `x[i] * y[j]` in the output layout of
+/// an outer product on `lanes`-lane inputs.
+fn output_position(
+    lanes: usize,
+    widen: bool,
+    z_index: usize,
+    i: usize,
+    j: usize,
+    out_size: usize,
+) -> (usize, usize) {
+    let tiles = 64 / lanes;
+    if widen {
+        (
+            j * tiles + i % 2 * (tiles / 2) + z_index % (tiles / 2),
+            i / 2 * out_size,
+        )
+    } else {
+        (j * tiles + z_index % tiles, i * out_size)
+    }
+}
+
+#[test]
+fn matrix_int_fixed_vector() {
+    // Widening `i8` outer product of X at 0 and Y at 64 into Z, without
+    // accumulating
+    const OPERAND: u64 = 0x4000_0800_0800_0040;
+    let op = MatIntOp::new(MatIntTy::I8).widen(true).accumulate(false);
+    let operand = MatIntOperand::new(op).y_offset(YBytes(64));
+    assert_eq!(operand.encode(), OPERAND);
+
+    let mut ctx = common::ctx();
+    let x: [i8; 64] = std::array::from_fn(|i| (i as i32 * 8 - 128) as i8);
+    let mut y: [i8; 64] = std::array::from_fn(|j| j as i8);
+    (y[0], y[31]) = (-3, 127);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(1));
+    ctx.matint(OPERAND);
+
+    let z = ctx.read_z_as::<i32>();
+    #[rustfmt::skip]
+    let expected: [[i32; 16]; 4] = [
+        [384, 336, 288, 240, 192, 144, 96, 48, 0, -48, -96, -144, -192, -240, -288, -336],
+        [360, 312, 264, 216, 168, 120, 72, 24, -24, -72, -120, -168, -216, -264, -312, -360],
+        [
+            -16256, -14224, -12192, -10160, -8128, -6096, -4064, -2032,
+            0, 2032, 4064, 6096, 8128, 10160, 12192, 14224,
+        ],
+        [
+            -15240, -13208, -11176, -9144, -7112, -5080, -3048, -1016,
+            1016, 3048, 5080, 7112, 9144, 11176, 13208, 15240,
+        ],
+    ];
+    assert_eq!([z[0], z[1], z[62], z[63]], expected);
+}
+
+#[test]
+fn matrix_int() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x5555);
+    let x: [u8; 64] = std::array::from_fn(|_| rng.next() as u8);
+    let y: [u8; 64] = std::array::from_fn(|_| rng.next() as u8);
+    let z: Vec<[u8; 64]> = (0..64)
+        .map(|_| std::array::from_fn(|_| rng.next() as u8))
+        .collect();
+
+    for (ty, widen, accumulate, subtract, (x_lanes, y_lanes), z_index) in iproduct!(
+        [MatIntTy::I8, MatIntTy::U8, MatIntTy::I16, MatIntTy::U16],
+        [false, true],
+        [false, true],
+        [false, true],
+        [(32, 32), (5, 32), (32, 17), (1, 1)],
+        [0, 1]
+    ) {
+        if widen && z_index != 0 {
+            continue;
+        }
+        unsafe {
+            ctx.load512(x.as_ptr(), XRow(3));
+            ctx.load512(y.as_ptr(), YRow(4));
+            for (i, z) in z.iter().enumerate() {
+                ctx.load512(z.as_ptr(), ZRow(i));
+            }
+        }
+        let op = MatIntOp::new(ty)
+            .widen(widen)
+            .accumulate(accumulate)
+            .subtract(subtract)
+            .x_lanes(x_lanes)
+            .y_lanes(y_lanes);
+        ctx.matrix_int(op, XBytes(3 * 64), YBytes(4 * 64), ZRow(z_index));
+
+        let lane = |a: &[u8; 64], i: usize| -> i64 {
+            match ty {
+                MatIntTy::I8 => a[i] as i8 as i64,
+                MatIntTy::U8 => a[i] as i64,
+                MatIntTy::I16 => i16::from_le_bytes([a[i * 2], a[i * 2 + 1]]) as i64,
+                MatIntTy::U16 => u16::from_le_bytes([a[i * 2], a[i * 2 + 1]]) as i64,
+            }
+        };
+        let out_size = if widen { 4 } else { 2 };
+        let mut expected = z.clone();
+        for (i, j) in iproduct!(0..x_lanes, 0..y_lanes) {
+            let (row, offset) = output_position(32, widen, z_index, i, j, out_size);
+            let out = &mut expected[row][offset..][..out_size];
+            let mut base = [0u8; 8];
+            base[..out_size].copy_from_slice(out);
+            let base = if !accumulate {
+                0
+            } else if widen {
+                i32::from_le_bytes(base[..4].try_into().unwrap()) as i64
+            } else {
+                i16::from_le_bytes(base[..2].try_into().unwrap()) as i64
+            };
+            let p = lane(&x, i) * lane(&y, j);
+            let value = if subtract { base - p } else { base + p };
+            out.copy_from_slice(&value.to_le_bytes()[..out_size]);
+        }
+
+        let got = ctx.read_z();
+        for (row, expected) in expected.iter().enumerate() {
+            assert_eq!(
+                got[row * 64..][..64],
+                expected[..],
+                "{op:?}, z_index = {z_index}, row = {row}"
+            );
+        }
+    }
+}
+
+#[test]
+fn matrix_fp() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x7777);
+    // Small integers are represented exactly in every type
+    let mut gen_values =
+        |n: usize| -> Vec<f64> { (0..n).map(|_| (rng.next() % 17) as f64 - 8.0).collect() };
+    let (x, y) = (gen_values(32), gen_values(32));
+    let z: Vec<Vec<f64>> = (0..64).map(|_| gen_values(32)).collect();
+
+    let encode = |size: usize, values: &[f64]| -> [u8; 64] {
+        let mut out = [0u8; 64];
+        for (chunk, &v) in out.chunks_exact_mut(size).zip(values) {
+            match size {
+                2 => chunk.copy_from_slice(&fp16::f32_to_f16(v as f32).to_le_bytes()),
+                4 => chunk.copy_from_slice(&(v as f32).to_le_bytes()),
+                _ => chunk.copy_from_slice(&v.to_le_bytes()),
+            }
+        }
+        out
+    };
+    let decode = |b: &[u8]| -> f64 {
+        match b.len() {
+            2 => fp16::f16_to_f32(u16::from_le_bytes(b.try_into().unwrap())) as f64,
+            4 => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+            _ => f64::from_le_bytes(b.try_into().unwrap()),
+        }
+    };
+
+    for (ty, widen, accumulate, subtract, masked, z_index) in iproduct!(
+        [MatFpTy::F16, MatFpTy::F32, MatFpTy::F64],
+        [false, true],
+        [false, true],
+        [false, true],
+        [false, true],
+        [0, 1, 3]
+    ) {
+        if widen && (ty != MatFpTy::F16 || z_index != 0) {
+            continue;
+        }
+        let size = ty.size();
+        let lanes = 64 / size;
+        let out_size = if widen { 4 } else { size };
+        let (x_lanes, y_lanes) = if masked {
+            (lanes - 1, 3)
+        } else {
+            (lanes, lanes)
+        };
+
+        unsafe {
+            ctx.load512(encode(size, &x).as_ptr(), XRow(0));
+            ctx.load512(encode(size, &y).as_ptr(), YRow(7));
+            for (i, z) in z.iter().enumerate() {
+                ctx.load512(encode(out_size, z).as_ptr(), ZRow(i));
+            }
+        }
+        let op = MatFpOp::new(ty)
+            .widen(widen)
+            .accumulate(accumulate)
+            .subtract(subtract)
+            .x_lanes(x_lanes)
+            .y_lanes(y_lanes);
+        ctx.matrix_fp(op, XBytes(0), YBytes(7 * 64), ZRow(z_index));
+
+        let mut expected: Vec<Vec<f64>> = z.iter().map(|z| z[..64 / out_size].to_vec()).collect();
+        for (i, j) in iproduct!(0..x_lanes, 0..y_lanes) {
+            let (row, offset) = output_position(lanes, widen, z_index, i, j, out_size);
+            let out = &mut expected[row][offset / out_size];
+            let base = if accumulate { *out } else { 0.0 };
+            *out = if subtract {
+                base - x[i] * y[j]
+            } else {
+                base + x[i] * y[j]
+            };
+        }
+
+        let got = ctx.read_z();
+        for (row, expected) in expected.iter().enumerate() {
+            let got: Vec<f64> = got[row * 64..][..64]
+                .chunks_exact(out_size)
+                .map(decode)
+                .collect();
+            assert_eq!(&got, expected, "{op:?}, z_index = {z_index}, row = {row}");
+        }
+    }
+}
+
+#[cfg(feature = "amx2")]
+#[test]
+fn matrix_fp_bf16() {
+    use amx::{AmxArgError, AmxEmuCtx, AmxOps, AmxVersion};
+
+    let mut ctx = AmxEmuCtx::default();
+    let x: [u16; 32] = std::array::from_fn(|i| fp16::f32_to_bf16(i as f32 + 0.5));
+    let y: [u16; 32] = std::array::from_fn(|i| fp16::f32_to_bf16(-(i as f32) / 4.0));
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+
+    let op = MatFpOp::new(MatFpTy::Bf16).widen(true).accumulate(false);
+    assert_eq!(ctx.version(), AmxVersion::M1);
+    assert_eq!(
+        ctx.try_matrix_fp(op, XBytes(0), YBytes(0), ZRow(0)),
+        Err(AmxArgError::Unsupported {
+            required: AmxVersion::M2
+        })
+    );
+
+    ctx.set_version(AmxVersion::M2);
+    ctx.matrix_fp(op, XBytes(0), YBytes(0), ZRow(0));
+    let z = ctx.read_z_f32();
+    for (i, j) in iproduct!(0..32, 0..32) {
+        let (row, offset) = output_position(32, true, 0, i, j, 4);
+        let expected = fp16::bf16_to_f32(x[i]) * fp16::bf16_to_f32(y[j]);
+        assert_eq!(z[row][offset / 4], expected, "x[{i}] * y[{j}]");
+    }
+
+    // Without widening, the sums are rounded to `bf16`
+    let op = MatFpOp::new(MatFpTy::Bf16);
+    ctx.load512_slice(&[fp16::f32_to_bf16(1.0); 32], XRow(0));
+    ctx.load512_slice(&[fp16::f32_to_bf16(1.0 + 1.0 / 128.0); 32], YRow(0));
+    ctx.load512_slice(&[fp16::f32_to_bf16(1.0 / 256.0); 32], ZRow(0));
+    ctx.matrix_fp(op, XBytes(0), YBytes(0), ZRow(0));
+    // `1 + 2^-7 + 2^-8` is a tie, which is rounded to even
+    assert_eq!(
+        ctx.read_z_as::<u16>()[0][0],
+        fp16::f32_to_bf16(1.0 + 1.0 / 64.0)
+    );
+}
+
+#[cfg(feature = "amx2")]
+#[test]
+#[should_panic = "requires AMX revision M2 or later"]
+fn matrix_fp_bf16_unsupported() {
+    let mut ctx = amx::AmxEmuCtx::default();
+    let op = MatFpOp::new(MatFpTy::Bf16);
+    ctx.matrix_fp(op, XBytes(0), YBytes(0), ZRow(0));
+}
+
+#[cfg(all(feature = "amx2", feature = "half"))]
+#[test]
+fn outer_product_bf16_half() {
+    use amx::AmxVersion;
+    use half::bf16;
+
+    let mut ctx = amx::AmxEmuCtx::default();
+    ctx.set_version(AmxVersion::M2);
+    let x: [bf16; 32] = std::array::from_fn(|i| bf16::from_f32(i as f32 * 0.25 - 3.0));
+    let y: [bf16; 32] = std::array::from_fn(|i| bf16::from_f32(1.0 / (i as f32 + 1.0)));
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    assert_eq!(ctx.read_x_as::<bf16>()[0], x);
+
+    ctx.outer_product_bf16_xy_to_z_f32(XBytes(0), YBytes(0), ZRow(0), false);
+    ctx.outer_product_bf16_xy_to_z_f32(XBytes(0), YBytes(0), ZRow(0), true);
+    let z = ctx.read_z_f32();
+    for (i, j) in iproduct!(0..32, 0..32) {
+        let expected = x[i].to_f32() * y[j].to_f32() * 2.0;
+        assert_eq!(z[j * 2 + i % 2][i / 2], expected, "x[{i}] * y[{j}]");
+    }
+}
+
+#[cfg(feature = "amx2")]
+#[test]
+fn outer_product_bf16_fallback() {
+    use amx::{AmxEmuCtx, AmxVersion};
+
+    let mut m1 = AmxEmuCtx::default();
+    let mut m2 = AmxEmuCtx::default();
+    m2.set_version(AmxVersion::M2);
+    let x: [u16; 256] = std::array::from_fn(|i| fp16::f32_to_bf16((i as f32 * 0.37).sin()));
+    let y: [u16; 256] = std::array::from_fn(|i| fp16::f32_to_bf16((i as f32 * 0.71).cos()));
+    let z: Vec<f32> = (0..1024).map(|i| (i as f32 * 0.13).sin() / 3.0).collect();
+    for ctx in [&mut m1, &mut m2] {
+        for row in 0..8 {
+            ctx.load512_slice(&x[row * 32..], XRow(row));
+            ctx.load512_slice(&y[row * 32..], YRow(row));
+        }
+        for row in 0..64 {
+            ctx.load512_slice(&z[row * 16..], ZRow(row));
+        }
+    }
+
+    // The X operand wraps around the register file
+    for (x_offset, y_offset, accumulate) in [(0, 0, false), (486, 70, true), (2, 448, true)] {
+        for ctx in [&mut m1, &mut m2] {
+            ctx.outer_product_bf16_xy_to_z_f32(
+                XBytes(x_offset),
+                YBytes(y_offset),
+                ZRow(0),
+                accumulate,
+            );
+        }
+        assert_eq!(m1.read_z(), m2.read_z(), "{x_offset}, {y_offset}");
+    }
+    assert_eq!(m1.read_x(), m2.read_x());
+    assert_eq!(m1.read_y(), m2.read_y());
+}
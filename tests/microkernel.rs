@@ -0,0 +1,123 @@
+#![cfg(feature = "macros")]
+mod common;
+
+use amx::{Amx, ZRow};
+use common::Xorshift32;
+
+amx::gemm_microkernel!(fn kernel_f32_2x2(f32, mr = 2, nr = 2, unroll = 2));
+amx::gemm_microkernel!(fn kernel_f32_1x4(f32, mr = 1, nr = 4));
+amx::gemm_microkernel!(fn kernel_i16_2x1(i16, mr = 2, nr = 1, unroll = 3));
+
+/// Read the `lanes × lanes` tile `tile` out of `tiles` from Z.
+fn read_block<T: Copy>(
+    ctx: &mut (impl Amx + ?Sized),
+    tile: usize,
+    tiles: usize,
+    lanes: usize,
+) -> Vec<Vec<T>> {
+    (0..lanes)
+        .map(|r| {
+            let mut row = [0u8; 64];
+            unsafe { ctx.store512(row.as_mut_ptr(), ZRow(r * tiles + tile)) };
+            let row: Vec<T> = (0..lanes)
+                .map(|c| unsafe { row.as_ptr().cast::<T>().add(c).read_unaligned() })
+                .collect();
+            row
+        })
+        .collect()
+}
+
+fn check_f32<C: Amx + ?Sized>(
+    ctx: &mut C,
+    mr: usize,
+    nr: usize,
+    k: usize,
+    kernel: impl Fn(&mut C, usize, *const f32, *const f32, bool),
+) {
+    let mut rng = Xorshift32(0x1234567 + k as u32);
+    let mut gen_vec = |len: usize| -> Vec<f32> {
+        (0..len)
+            .map(|_| (rng.next() % 64) as f32 / 8.0 - 4.0)
+            .collect()
+    };
+    let (m, n) = (mr * 16, nr * 16);
+    let a = gen_vec(k * m);
+    let b = gen_vec(k * n);
+    let c = gen_vec(m * n);
+
+    for accumulate in [false, true] {
+        // Preload the initial value of C
+        for i in 0..mr {
+            for j in 0..nr {
+                for r in 0..16 {
+                    let row: Vec<f32> = (0..16)
+                        .map(|cc| c[(i * 16 + r) * n + j * 16 + cc])
+                        .collect();
+                    unsafe { ctx.load512(row.as_ptr(), ZRow(r * 4 + i * nr + j)) };
+                }
+            }
+        }
+
+        kernel(ctx, k, a.as_ptr(), b.as_ptr(), accumulate);
+
+        for i in 0..mr {
+            for j in 0..nr {
+                let got = read_block::<f32>(ctx, i * nr + j, 4, 16);
+                for (r, got_row) in got.iter().enumerate() {
+                    for (cc, &got) in got_row.iter().enumerate() {
+                        let (row, col) = (i * 16 + r, j * 16 + cc);
+                        let mut expected = if accumulate || k == 0 {
+                            c[row * n + col]
+                        } else {
+                            0.0
+                        };
+                        for kk in 0..k {
+                            expected += a[kk * m + row] * b[kk * n + col];
+                        }
+                        assert_eq!(
+                            got, expected,
+                            "k = {k}, accumulate = {accumulate}, (row, col) = ({row}, {col})"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn microkernel_f32() {
+    let mut ctx = common::ctx();
+    for k in [0, 1, 2, 3, 4, 7, 12] {
+        check_f32(&mut ctx, 2, 2, k, |ctx, k, a, b, acc| unsafe {
+            kernel_f32_2x2(ctx, k, a, b, acc)
+        });
+        check_f32(&mut ctx, 1, 4, k, |ctx, k, a, b, acc| unsafe {
+            kernel_f32_1x4(ctx, k, a, b, acc)
+        });
+    }
+}
+
+#[test]
+fn microkernel_i16() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xdeadbeef);
+    for k in [1, 2, 4, 5, 10] {
+        let a: Vec<i16> = (0..k * 64).map(|_| rng.next() as i16 % 16).collect();
+        let b: Vec<i16> = (0..k * 32).map(|_| rng.next() as i16 % 16).collect();
+
+        unsafe { kernel_i16_2x1(&mut ctx, k, a.as_ptr(), b.as_ptr(), false) };
+
+        for i in 0..2 {
+            let got = read_block::<i16>(&mut ctx, i, 2, 32);
+            for r in 0..32 {
+                for c in 0..32 {
+                    let expected = (0..k)
+                        .map(|kk| a[kk * 64 + i * 32 + r].wrapping_mul(b[kk * 32 + c]))
+                        .fold(0i16, i16::wrapping_add);
+                    assert_eq!(got[r][c], expected, "k = {k}, (i, r, c) = ({i}, {r}, {c})");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "nalgebra")]
+mod common;
+
+use amx::{
+    Amx, XBytes, XRow, YBytes, YRow, ZRow,
+    nalgebra_ext::{AmxNalgebraExt, amx_mul},
+};
+use nalgebra::{DMatrix, SMatrix};
+
+#[test]
+fn tile_round_trip() {
+    let mut ctx = common::ctx();
+
+    let a = DMatrix::from_fn(20, 20, |i, j| (i * 20 + j) as f32);
+    let src = a.view((2, 3), (9, 16));
+    ctx.load_matrix_tile(&src, 3);
+    let mut out = DMatrix::from_element(16, 16, f32::NAN);
+    ctx.store_matrix_tile(3, &mut out);
+    assert_eq!(out.view((0, 0), (9, 16)), src);
+    assert!(out.rows(9, 7).iter().all(|&x| x == 0.0));
+
+    // The tile is laid out like the outer product's output
+    let x: [f64; 8] = std::array::from_fn(|i| i as f64);
+    let y: [f64; 8] = std::array::from_fn(|i| 1.0 - i as f64);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    ctx.outer_product_f64_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(6), false);
+    let mut out = SMatrix::<f64, 8, 8>::zeros();
+    ctx.store_matrix_tile(6, &mut out);
+    assert_eq!(out, SMatrix::<f64, 8, 8>::from_fn(|j, i| x[i] * y[j]));
+}
+
+#[test]
+fn mul() {
+    let mut ctx = common::ctx();
+
+    // Small integers make the results exact
+    let a = DMatrix::from_fn(37, 21, |i, j| ((i * 7 + j * 3) % 11) as f32 - 5.0);
+    let b = DMatrix::from_fn(21, 40, |i, j| ((i * 5 + j) % 13) as f32 - 6.0);
+    assert_eq!(amx_mul(&mut ctx, &a, &b), &a * &b);
+
+    let (a, b) = (a.cast::<f64>(), b.cast::<f64>());
+    assert_eq!(amx_mul(&mut ctx, &a, &b), &a * &b);
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "ndarray")]
+mod common;
+
+use amx::{Amx, XBytes, XRow, YBytes, YRow, ZRow, ndarray_ext::amx_dot};
+use ndarray::{Array2, s};
+
+#[test]
+fn tile_round_trip() {
+    let mut ctx = common::ctx();
+    let a = Array2::from_shape_fn((20, 20), |(i, j)| (i * 20 + j) as f32);
+
+    // A non-contiguous view
+    let src = a.slice(s![1..15;2, 3..19]);
+    ctx.load_tile(&src, 2);
+    let mut out = Array2::from_elem((16, 16), f32::NAN);
+    ctx.store_tile(2, &mut out.view_mut());
+    assert_eq!(out.slice(s![..7, ..]), src);
+    assert!(out.slice(s![7.., ..]).iter().all(|&x| x == 0.0));
+
+    // The tile is laid out like the outer product's output
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32);
+    let y: [f32; 16] = std::array::from_fn(|i| 1.0 - i as f32);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), false);
+    let mut out = Array2::zeros((16, 16));
+    ctx.store_tile(1, &mut out.view_mut());
+    assert_eq!(out, Array2::from_shape_fn((16, 16), |(j, i)| x[i] * y[j]));
+}
+
+#[test]
+fn dot() {
+    let mut ctx = common::ctx();
+    let a = Array2::from_shape_fn((37, 21), |(i, j)| ((i * 7 + j * 3) % 11) as f32 - 5.0);
+    let b = Array2::from_shape_fn((40, 21), |(i, j)| ((i * 5 + j) % 13) as f32 - 6.0);
+
+    // Small integers make the result exact
+    assert_eq!(amx_dot(&mut ctx, &a.view(), &b.t()), a.dot(&b.t()));
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use amx::{
+    linalg::{MatMut, MatRef},
+    nn::{self, BagMode, GruWeights, LstmWeights},
+};
+use common::{Xorshift32, assert_close_abs};
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// `a (m×k) * b (k×n)`
+fn matmul(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    let mut out = vec![0.0; m * n];
+    for i in 0..m {
+        for j in 0..n {
+            out[i * n + j] = (0..k).map(|kk| a[i * k + kk] * b[kk * n + j]).sum();
+        }
+    }
+    out
+}
+
+#[test]
+fn activations() {
+    let mut ctx = common::ctx();
+    let x: Vec<f32> = (-2000..=2000).map(|i| i as f32 / 100.0).collect();
+
+    let mut got = x.clone();
+    nn::sigmoid_f32(&mut ctx, &mut got);
+    let expected: Vec<f32> = x.iter().map(|&x| sigmoid(x)).collect();
+    assert_close_abs(&got, &expected, 2e-3);
+
+    let mut got = x.clone();
+    nn::tanh_f32(&mut ctx, &mut got);
+    let expected: Vec<f32> = x.iter().map(|&x| x.tanh()).collect();
+    assert_close_abs(&got, &expected, 4e-3);
+}
+
+#[test]
+fn lstm_step() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x1517);
+    let (batch, input, hidden) = (3, 10, 20);
+
+    let w_x = rng.vec_f32(input * 4 * hidden);
+    let w_h = rng.vec_f32(hidden * 4 * hidden);
+    let bias = rng.vec_f32(4 * hidden);
+    let x = rng.vec_f32(batch * input);
+    let mut h = rng.vec_f32(batch * hidden);
+    let mut c = rng.vec_f32(batch * hidden);
+
+    // Reference
+    let gx = matmul(&x, &w_x, batch, input, 4 * hidden);
+    let gh = matmul(&h, &w_h, batch, hidden, 4 * hidden);
+    let mut expected_h = vec![0.0; batch * hidden];
+    let mut expected_c = vec![0.0; batch * hidden];
+    for b in 0..batch {
+        for j in 0..hidden {
+            let gate = |k: usize| {
+                let idx = b * 4 * hidden + k * hidden + j;
+                gx[idx] + gh[idx] + bias[k * hidden + j]
+            };
+            let (i, f, g, o) = (
+                sigmoid(gate(0)),
+                sigmoid(gate(1)),
+                gate(2).tanh(),
+                sigmoid(gate(3)),
+            );
+            let c_new = f * c[b * hidden + j] + i * g;
+            expected_c[b * hidden + j] = c_new;
+            expected_h[b * hidden + j] = o * c_new.tanh();
+        }
+    }
+
+    nn::lstm_step(
+        &mut ctx,
+        &LstmWeights {
+            w_x: MatRef::new(&w_x, input, 4 * hidden),
+            w_h: MatRef::new(&w_h, hidden, 4 * hidden),
+            bias: &bias,
+        },
+        MatRef::new(&x, batch, input),
+        MatMut::new(&mut h, batch, hidden),
+        MatMut::new(&mut c, batch, hidden),
+    );
+    assert_close_abs(&c, &expected_c, 1e-2);
+    assert_close_abs(&h, &expected_h, 1e-2);
+}
+
+#[test]
+fn gru_step() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x6e0);
+    let (batch, input, hidden) = (5, 7, 17);
+
+    let w_x = rng.vec_f32(input * 3 * hidden);
+    let w_h = rng.vec_f32(hidden * 3 * hidden);
+    let bias_x = rng.vec_f32(3 * hidden);
+    let bias_h = rng.vec_f32(3 * hidden);
+    let x = rng.vec_f32(batch * input);
+    let mut h = rng.vec_f32(batch * hidden);
+
+    // Reference
+    let gx = matmul(&x, &w_x, batch, input, 3 * hidden);
+    let gh = matmul(&h, &w_h, batch, hidden, 3 * hidden);
+    let mut expected = vec![0.0; batch * hidden];
+    for b in 0..batch {
+        for j in 0..hidden {
+            let xg = |k: usize| gx[b * 3 * hidden + k * hidden + j] + bias_x[k * hidden + j];
+            let hg = |k: usize| gh[b * 3 * hidden + k * hidden + j] + bias_h[k * hidden + j];
+            let r = sigmoid(xg(0) + hg(0));
+            let z = sigmoid(xg(1) + hg(1));
+            let n = (xg(2) + r * hg(2)).tanh();
+            expected[b * hidden + j] = (1.0 - z) * n + z * h[b * hidden + j];
+        }
+    }
+
+    nn::gru_step(
+        &mut ctx,
+        &GruWeights {
+            w_x: MatRef::new(&w_x, input, 3 * hidden),
+            w_h: MatRef::new(&w_h, hidden, 3 * hidden),
+            bias_x: &bias_x,
+            bias_h: &bias_h,
+        },
+        MatRef::new(&x, batch, input),
+        MatMut::new(&mut h, batch, hidden),
+    );
+    assert_close_abs(&h, &expected, 1e-2);
+}
+
+#[test]
+fn embedding_bag() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0xe0e0);
+
+    for d in [1, 16, 37, 1100] {
+        let v = 50;
+        let table = rng.vec_f32(v * d);
+        let indices: Vec<usize> = (0..20).map(|_| rng.next() as usize % v).collect();
+        // Includes empty bags in the middle and at the end
+        let offsets = [0, 3, 3, 10, 11, 20];
+        let weights = rng.vec_f32(indices.len());
+
+        for (mode, per_sample_weights) in [
+            (BagMode::Sum, None),
+            (BagMode::Sum, Some(&weights[..])),
+            (BagMode::Mean, None),
+        ] {
+            let mut out = vec![f32::NAN; offsets.len() * d];
+            nn::embedding_bag(
+                &mut ctx,
+                MatRef::new(&table, v, d),
+                &indices,
+                &offsets,
+                per_sample_weights,
+                mode,
+                MatMut::new(&mut out, offsets.len(), d),
+            );
+
+            for (b, &start) in offsets.iter().enumerate() {
+                let end = offsets.get(b + 1).copied().unwrap_or(indices.len());
+                let mut expected = vec![0f32; d];
+                for n in start..end {
+                    let w = per_sample_weights.map_or(1.0, |w| w[n]);
+                    for (e, &t) in expected.iter_mut().zip(&table[indices[n] * d..][..d]) {
+                        *e += w * t;
+                    }
+                }
+                if mode == BagMode::Mean && end > start {
+                    for e in &mut expected {
+                        *e /= (end - start) as f32;
+                    }
+                }
+                assert_close_abs(&out[b * d..][..d], &expected, 1e-5);
+            }
+        }
+    }
+}
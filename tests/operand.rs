@@ -0,0 +1,126 @@
+mod common;
+
+use amx::{
+    Amx, AmxArgError, AmxOps, CheckArg, Fma16Operand, Fma32Operand, Fma64Operand, Mac16Operand,
+    MatFpOp, MatFpOperand, MatFpTy, MatIntOp, MatIntOperand, MatIntTy, VecFpAluOp, VecFpOperand,
+    VecFpTy, VecIntOp, VecIntOperand, VecIntTy, XBytes, XRow, YBytes, YRow, ZRow,
+};
+
+#[test]
+fn mul_operand_encoding() {
+    let operand = Mac16Operand::new()
+        .x_offset(Some(XBytes(0x1c0)))
+        .y_offset(Some(YBytes(0x42)))
+        .z_row(ZRow(63))
+        .accumulate(false)
+        .shift(31)
+        .widen(true)
+        .vector(true);
+    let bits = 0x42 | (0x1c0 << 10) | (63 << 20) | (1 << 27) | (31 << 55) | (1 << 62) | (1 << 63);
+    assert_eq!(operand.encode(), bits);
+    assert_eq!(Mac16Operand::decode(bits), operand);
+
+    let operand = Fma16Operand::new()
+        .x_offset(None)
+        .y_offset(None)
+        .z_row(ZRow(1));
+    assert_eq!(operand.encode(), (1 << 20) | (1 << 28) | (1 << 29));
+    assert_eq!(Fma16Operand::decode(operand.encode()), operand);
+
+    assert_eq!(Fma32Operand::new().encode(), 0);
+    assert_eq!(Fma32Operand::default(), Fma32Operand::new());
+    // Unrelated bits are ignored
+    assert_eq!(Fma64Operand::decode(1 << 40), Fma64Operand::new());
+}
+
+#[test]
+fn reg_operand_encoding() {
+    let op = VecIntOp::new(VecIntTy::I8)
+        .shift(5)
+        .saturate(true)
+        .subtract(true)
+        .accumulate(false);
+    let operand = VecIntOperand::new(op)
+        .x_offset(XBytes(3))
+        .y_offset(YBytes(0x1ff))
+        .z_row(ZRow(7));
+    let bits =
+        0x1ff | (3 << 10) | (7 << 20) | (1 << 27) | (10 << 42) | (1 << 47) | (1 << 53) | (5 << 58);
+    assert_eq!(operand.encode(), bits);
+    assert_eq!(VecIntOperand::decode(bits), Some(operand));
+    assert_eq!(VecIntOperand::decode(2 << 47), None);
+
+    for op in [
+        VecFpAluOp::MulAdd,
+        VecFpAluOp::MulSub,
+        VecFpAluOp::Add,
+        VecFpAluOp::Sub,
+        VecFpAluOp::Min,
+        VecFpAluOp::Max,
+    ] {
+        for ty in [VecFpTy::F16, VecFpTy::F32, VecFpTy::F64] {
+            let operand = VecFpOperand::new(ty, op).x_offset(XBytes(8)).z_row(ZRow(2));
+            assert_eq!(VecFpOperand::decode(operand.encode()), Some(operand));
+        }
+    }
+    assert_eq!(VecFpOperand::decode(3 << 47), None);
+
+    let op = MatIntOp::new(MatIntTy::U8)
+        .widen(true)
+        .x_lanes(4)
+        .y_lanes(32);
+    let operand = MatIntOperand::new(op).y_offset(YBytes(64));
+    let bits = 64 | (4 << 32) | (3 << 42) | (1 << 62);
+    assert_eq!(operand.encode(), bits);
+    assert_eq!(MatIntOperand::decode(bits), Some(operand));
+    assert_eq!(MatIntOperand::decode(5 << 42), None);
+
+    let op = MatFpOp::new(MatFpTy::F64).subtract(true).accumulate(false);
+    let operand = MatFpOperand::new(op).z_row(ZRow(5));
+    assert_eq!(
+        operand.encode(),
+        (5 << 20) | (1 << 27) | (7 << 42) | (1 << 47)
+    );
+    assert_eq!(MatFpOperand::decode(operand.encode()), Some(operand));
+}
+
+#[test]
+fn operand_check() {
+    assert_eq!(Mac16Operand::new().x_offset(None).check(), Ok(()));
+    assert_eq!(
+        Fma32Operand::new().y_offset(Some(YBytes(0x200))).check(),
+        Err(AmxArgError::OffsetOutOfRange {
+            offset: 0x200,
+            len: 0x200
+        })
+    );
+    assert_eq!(
+        VecIntOperand::new(VecIntOp::new(VecIntTy::I16))
+            .z_row(ZRow(64))
+            .check(),
+        Err(AmxArgError::RowOutOfRange { index: 64, len: 64 })
+    );
+}
+
+#[test]
+fn operand_matches_wrapper() {
+    let mut ctx = common::ctx();
+    let x: [i16; 32] = std::array::from_fn(|i| i as i16 - 7);
+    let y: [i16; 32] = std::array::from_fn(|i| 3 * i as i16 + 1);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(2));
+        ctx.load512(y.as_ptr(), YRow(5));
+    }
+
+    ctx.outer_product_i16_xy_to_z_i32(Some(XBytes(128)), Some(YBytes(320)), ZRow(0), false);
+    let expected = ctx.read_z();
+
+    ctx.clear_z();
+    let operand = Mac16Operand::new()
+        .x_offset(Some(XBytes(128)))
+        .y_offset(Some(YBytes(320)))
+        .accumulate(false)
+        .widen(true);
+    ctx.mac16(operand.encode());
+    assert_eq!(ctx.read_z(), expected);
+}
@@ -1,3 +1,5 @@
+mod common;
+
 use amx::{XBytes, XRow, YBytes, YRow, ZRow, prelude::*};
 use itertools::iproduct;
 
@@ -24,7 +26,7 @@ fn read_array_wrapping<T: Copy, const N: usize>(a: &[T], i: usize) -> [T; N] {
 #[test]
 fn outer_product_i16_xy_to_z() {
     unsafe {
-        let mut ctx = amx::AmxCtx::new().unwrap();
+        let mut ctx = common::ctx();
 
         let mut rng = Xorshift32(0x114514);
         let in_x: Vec<u8> = (0..512).map(|_| rng.next() as u8).collect();
@@ -65,9 +67,419 @@ fn outer_product_i16_xy_to_z() {
             let got_z = ctx.read_z();
 
             assert_eq!(
-                std::mem::transmute::<_, [[u16; 32]; 64]>(got_z),
-                std::mem::transmute::<_, [[u16; 32]; 64]>(expected_z)
+                std::mem::transmute::<[u8; 4096], [[u16; 32]; 64]>(got_z),
+                std::mem::transmute::<[u8; 4096], [[u16; 32]; 64]>(expected_z)
+            );
+        }
+    }
+}
+
+/// An element type of the shared outer product tests. The tests only use
+/// small integers, which are represented exactly in every type.
+#[derive(Debug, Clone, Copy)]
+enum Ty {
+    I16,
+    I32,
+    F16,
+    F32,
+    F64,
+}
+
+impl Ty {
+    fn size(self) -> usize {
+        match self {
+            Ty::I16 | Ty::F16 => 2,
+            Ty::I32 | Ty::F32 => 4,
+            Ty::F64 => 8,
+        }
+    }
+
+    fn encode(self, v: i32, out: &mut [u8]) {
+        match self {
+            Ty::I16 => out.copy_from_slice(&(v as i16).to_le_bytes()),
+            Ty::I32 => out.copy_from_slice(&v.to_le_bytes()),
+            Ty::F16 => out.copy_from_slice(&f32_to_f16(v as f32).to_le_bytes()),
+            Ty::F32 => out.copy_from_slice(&(v as f32).to_le_bytes()),
+            Ty::F64 => out.copy_from_slice(&(v as f64).to_le_bytes()),
+        }
+    }
+
+    fn decode(self, b: &[u8]) -> f64 {
+        match self {
+            Ty::I16 => i16::from_le_bytes(b.try_into().unwrap()) as f64,
+            Ty::I32 => i32::from_le_bytes(b.try_into().unwrap()) as f64,
+            Ty::F16 => f16_to_f32(u16::from_le_bytes(b.try_into().unwrap())) as f64,
+            Ty::F32 => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+            Ty::F64 => f64::from_le_bytes(b.try_into().unwrap()),
+        }
+    }
+}
+
+/// Convert a normal or zero `f32` with a short mantissa to `f16`.
+fn f32_to_f16(x: f32) -> u16 {
+    if x == 0.0 {
+        return 0;
+    }
+    let bits = x.to_bits();
+    let sign = (bits >> 16) & 0x8000;
+    let exp = ((bits >> 23) & 0xff) - 127 + 15;
+    (sign | (exp << 10) | ((bits >> 13) & 0x3ff)) as u16
+}
+
+/// Convert a normal or zero `f16` to `f32`.
+fn f16_to_f32(x: u16) -> f32 {
+    if x & 0x7fff == 0 {
+        return 0.0;
+    }
+    let x = x as u32;
+    let sign = (x & 0x8000) << 16;
+    let exp = ((x >> 10) & 0x1f) + 127 - 15;
+    f32::from_bits(sign | (exp << 23) | ((x & 0x3ff) << 13))
+}
+
+/// Test an outer product (or vector if `vector` is set) method `op` whose
+/// input and output types are `input` and `output`, respectively.
+fn check_outer_product<C: amx::Amx + ?Sized>(
+    ctx: &mut C,
+    input: Ty,
+    output: Ty,
+    widen: bool,
+    vector: bool,
+    op: impl Fn(&mut C, Option<XBytes>, Option<YBytes>, ZRow, bool),
+    supports_skip: bool,
+) {
+    let lanes_in = 64 / input.size();
+    let lanes_out = 64 / output.size();
+    let mut rng = Xorshift32(0xc0ffee);
+    let mut gen_value = || (rng.next() % 17) as i32 - 8;
+
+    let x: Vec<i32> = (0..lanes_in).map(|_| gen_value()).collect();
+    let y: Vec<i32> = (0..lanes_in).map(|_| gen_value()).collect();
+    let z: Vec<Vec<i32>> = (0..64)
+        .map(|_| (0..lanes_out).map(|_| gen_value()).collect())
+        .collect();
+
+    let encode = |ty: Ty, values: &[i32]| -> [u8; 64] {
+        let mut out = [0u8; 64];
+        for (chunk, &v) in out.chunks_exact_mut(ty.size()).zip(values) {
+            ty.encode(v, chunk);
+        }
+        out
+    };
+    unsafe {
+        ctx.load512(encode(input, &x).as_ptr(), XRow(1));
+        ctx.load512(encode(input, &y).as_ptr(), YRow(2));
+    }
+
+    let z_indices: &[usize] = match (vector, widen) {
+        (false, false) => &[0, 1, 2, 3, 6, 7],
+        (false, true) => &[0],
+        (true, false) => &[0, 1, 37, 63],
+        (true, true) => &[0, 1, 62],
+    };
+    for (&z_index, accumulate, skip_x) in iproduct!(z_indices, [false, true], [false, supports_skip]) {
+        for (i, z) in z.iter().enumerate() {
+            unsafe { ctx.load512(encode(output, z).as_ptr(), ZRow(i)) };
+        }
+
+        let x_offset = if skip_x { None } else { Some(XBytes(64)) };
+        op(ctx, x_offset, Some(YBytes(128)), ZRow(z_index), accumulate);
+
+        // Calculate the expected answer
+        let mut expected: Vec<Vec<f64>> = z
+            .iter()
+            .map(|row| row.iter().map(|&v| v as f64).collect())
+            .collect();
+        let tiles = 64 / lanes_in;
+        for (j, &y) in y.iter().enumerate() {
+            for (i, &x) in x.iter().enumerate() {
+                if vector && i != j {
+                    continue;
+                }
+                let (row, lane) = if vector && widen {
+                    (z_index + i % 2, i / 2)
+                } else if vector {
+                    (z_index, i)
+                } else if widen {
+                    (j * 2 + i % 2, i / 2)
+                } else {
+                    (j * tiles + z_index % tiles, i)
+                };
+                let x = if skip_x { 1 } else { x };
+                let base = if accumulate { z[row][lane] } else { 0 };
+                expected[row][lane] = (base + x * y) as f64;
+            }
+        }
+
+        // Get the actual answer
+        for (row, expected) in expected.iter().enumerate() {
+            let mut got = [0u8; 64];
+            unsafe { ctx.store512(got.as_mut_ptr(), ZRow(row)) };
+            let got: Vec<f64> = got
+                .chunks_exact(output.size())
+                .map(|b| output.decode(b))
+                .collect();
+            assert_eq!(
+                &got, expected,
+                "{input:?} -> {output:?}, z_index = {z_index}, accumulate = {accumulate}, \
+                 skip_x = {skip_x}, row = {row}"
+            );
+        }
+    }
+}
+
+macro_rules! outer_product_tests {
+    ($(
+        $method:ident / $method_unchecked:ident:
+            $input:ident => $output:ident, widen: $widen:literal, vector: $vector:literal;
+    )*) => {$(
+        #[test]
+        fn $method() {
+            let mut ctx = common::ctx();
+            check_outer_product(
+                &mut ctx,
+                Ty::$input,
+                Ty::$output,
+                $widen,
+                $vector,
+                |ctx, x, y, z, accumulate| ctx.$method(x, y, z, accumulate),
+                true,
+            );
+        }
+
+        #[test]
+        fn $method_unchecked() {
+            let mut ctx = common::ctx();
+            check_outer_product(
+                &mut ctx,
+                Ty::$input,
+                Ty::$output,
+                $widen,
+                $vector,
+                |ctx, x, y, z, accumulate| unsafe {
+                    ctx.$method_unchecked(x.unwrap(), y.unwrap(), z, accumulate)
+                },
+                false,
+            );
+        }
+    )*};
+}
+
+mod shared {
+    use super::*;
+
+    outer_product_tests! {
+        outer_product_i16_xy_to_z / outer_product_i16_xy_to_z_unchecked:
+            I16 => I16, widen: false, vector: false;
+        outer_product_i16_xy_to_z_i32 / outer_product_i16_xy_to_z_i32_unchecked:
+            I16 => I32, widen: true, vector: false;
+        outer_product_f16_xy_to_z / outer_product_f16_xy_to_z_unchecked:
+            F16 => F16, widen: false, vector: false;
+        outer_product_f16_xy_to_z_f32 / outer_product_f16_xy_to_z_f32_unchecked:
+            F16 => F32, widen: true, vector: false;
+        outer_product_f32_xy_to_z / outer_product_f32_xy_to_z_unchecked:
+            F32 => F32, widen: false, vector: false;
+        outer_product_f64_xy_to_z / outer_product_f64_xy_to_z_unchecked:
+            F64 => F64, widen: false, vector: false;
+        mac_vector_i16 / mac_vector_i16_unchecked:
+            I16 => I16, widen: false, vector: true;
+        mac_vector_i16_i32 / mac_vector_i16_i32_unchecked:
+            I16 => I32, widen: true, vector: true;
+        fma_vector_f16 / fma_vector_f16_unchecked:
+            F16 => F16, widen: false, vector: true;
+        fma_vector_f16_f32 / fma_vector_f16_f32_unchecked:
+            F16 => F32, widen: true, vector: true;
+        fma_vector_f32 / fma_vector_f32_unchecked:
+            F32 => F32, widen: false, vector: true;
+        fma_vector_f64 / fma_vector_f64_unchecked:
+            F64 => F64, widen: false, vector: true;
+    }
+}
+
+#[test]
+fn outer_product_i16_xy_to_z_i32_accumulate() {
+    let mut ctx = common::ctx();
+    let x: [i16; 32] = std::array::from_fn(|i| 1000 + i as i16 * 300);
+    let y: [i16; 32] = std::array::from_fn(|j| -2000 + j as i16 * 150);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+
+    // The sums overflow `i16`
+    for k in 0..4 {
+        ctx.outer_product_i16_xy_to_z_i32(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), k != 0);
+    }
+
+    let z = ctx.read_z_i32();
+    for (j, &y) in y.iter().enumerate() {
+        for (i, &x) in x.iter().enumerate() {
+            assert_eq!(
+                z[j * 2 + i % 2][i / 2],
+                4 * x as i32 * y as i32,
+                "i = {i}, j = {j}"
             );
         }
     }
+
+    ctx.outer_product_i16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), false);
+    let z = ctx.read_z_i16();
+    assert_eq!(z[1][3], x[3].wrapping_mul(y[0]));
+}
+
+#[test]
+fn outer_product_f64_xy_to_z_accumulate() {
+    let mut ctx = common::ctx();
+    let x: [f64; 8] = std::array::from_fn(|i| 0.1 + i as f64 * 1e-9);
+    let y: [f64; 8] = std::array::from_fn(|j| -3.5 + j as f64 / 7.0);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+
+    for z_index in [0, 5] {
+        for k in 0..3 {
+            ctx.outer_product_f64_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(z_index), k != 0);
+        }
+    }
+
+    let z = ctx.read_z_f64();
+    for z_index in [0, 5] {
+        for (j, &y) in y.iter().enumerate() {
+            for (i, &x) in x.iter().enumerate() {
+                let expected = x.mul_add(y, x.mul_add(y, x * y));
+                assert_eq!(z[j * 8 + z_index][i], expected, "i = {i}, j = {j}");
+            }
+        }
+    }
+
+    let x: [f32; 16] = std::array::from_fn(|i| 0.3 + i as f32);
+    let y: [f32; 16] = std::array::from_fn(|j| 1.7 - j as f32);
+    unsafe {
+        ctx.load512(x.as_ptr(), XRow(0));
+        ctx.load512(y.as_ptr(), YRow(0));
+    }
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(2), false);
+    let z = ctx.read_z_f32();
+    assert_eq!(z[4 * 3 + 2][5], x[5] * y[3]);
+}
+
+#[test]
+fn outer_product_f16_xy_to_z_read() {
+    use amx::fp16::{f16_to_f32, f32_to_f16};
+
+    let mut ctx = common::ctx();
+    let x: [f32; 32] = std::array::from_fn(|i| i as f32 * 0.25 - 4.0);
+    let y: [f32; 32] = std::array::from_fn(|j| 1.5 - j as f32 * 0.125);
+    unsafe {
+        ctx.load512(x.map(f32_to_f16).as_ptr(), XRow(0));
+        ctx.load512(y.map(f32_to_f16).as_ptr(), YRow(0));
+    }
+
+    // Half-precision accumulation
+    ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), false);
+    ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), true);
+    let z = ctx.read_z_f16();
+    for (j, &y) in y.iter().enumerate() {
+        for (i, &x) in x.iter().enumerate() {
+            let p = f16_to_f32(f32_to_f16(x * y));
+            let expected = f16_to_f32(f32_to_f16(p + p));
+            assert_eq!(z[j * 2 + 1][i].to_f32(), expected, "i = {i}, j = {j}");
+        }
+    }
+
+    // Widening accumulation
+    for k in 0..3 {
+        ctx.outer_product_f16_xy_to_z_f32(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), k != 0);
+    }
+    let z = ctx.read_z_f32();
+    for (j, &y) in y.iter().enumerate() {
+        for (i, &x) in x.iter().enumerate() {
+            assert_eq!(z[j * 2 + i % 2][i / 2], 3.0 * x * y, "i = {i}, j = {j}");
+        }
+    }
+}
+
+#[test]
+fn fp16_conversion() {
+    use amx::fp16::{f16_to_f32, f32_to_f16};
+
+    for bits in 0..=u16::MAX {
+        let x = f16_to_f32(bits);
+        if x.is_nan() {
+            assert!(f16_to_f32(f32_to_f16(x)).is_nan());
+        } else {
+            assert_eq!(f32_to_f16(x), bits, "{bits:#06x}");
+        }
+    }
+    assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0), 0x3c00); // ties to even
+    assert_eq!(f32_to_f16(1e6), 0x7c00);
+}
+
+#[test]
+fn read_z_planes() {
+    let mut ctx = common::ctx();
+    ctx.write_z(&[0; 4096]);
+
+    let x: [f64; 8] = std::array::from_fn(|i| i as f64 + 1.0);
+    let y: [f64; 8] = std::array::from_fn(|j| j as f64 * 10.0);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    for plane in 0..8 {
+        ctx.outer_product_f64_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(plane), false);
+        let tile = ctx.read_z_plane_f64(plane);
+        for (j, i) in iproduct!(0..8, 0..8) {
+            assert_eq!(tile[j][i], x[i] * y[j], "plane = {plane}, i = {i}, j = {j}");
+        }
+    }
+
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 - 3.0);
+    let y: [f32; 16] = std::array::from_fn(|j| j as f32 * 0.5);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(2), false);
+    let tile = ctx.read_z_plane_f32(2);
+    for (j, i) in iproduct!(0..16, 0..16) {
+        assert_eq!(tile[j][i], x[i] * y[j], "i = {i}, j = {j}");
+    }
+    // The same rows viewed as integers
+    let tile = ctx.read_z_plane_i32(2);
+    assert_eq!(tile[3][5], (x[5] * y[3]).to_bits() as i32);
+
+    let x: [i16; 32] = std::array::from_fn(|i| i as i16 - 7);
+    let y: [i16; 32] = std::array::from_fn(|j| j as i16 * 3);
+    ctx.load512_slice(&x, XRow(0));
+    ctx.load512_slice(&y, YRow(0));
+    ctx.outer_product_i16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(1), false);
+    let tile = ctx.read_z_plane_i16(1);
+    for (j, i) in iproduct!(0..32, 0..32) {
+        assert_eq!(tile[j][i], x[i] * y[j], "i = {i}, j = {j}");
+    }
+
+    ctx.outer_product_i16_xy_to_z_i32(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    let tile = ctx.read_z_widened_i32();
+    for (j, i) in iproduct!(0..32, 0..32) {
+        assert_eq!(tile[j][i], x[i] as i32 * y[j] as i32, "i = {i}, j = {j}");
+    }
+
+    let x: [f32; 32] = std::array::from_fn(|i| i as f32 * 0.25);
+    let y: [f32; 32] = std::array::from_fn(|j| 8.0 - j as f32);
+    ctx.load512_slice(&x.map(amx::fp16::F16Bits::from_f32), XRow(0));
+    ctx.load512_slice(&y.map(amx::fp16::F16Bits::from_f32), YRow(0));
+    ctx.outer_product_f16_xy_to_z(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    let tile = ctx.read_z_plane_f16(0);
+    assert_eq!(tile[4][6].to_f32(), x[6] * y[4]);
+
+    ctx.outer_product_f16_xy_to_z_f32(Some(XBytes(0)), Some(YBytes(0)), ZRow(0), false);
+    let tile = ctx.read_z_widened_f32();
+    for (j, i) in iproduct!(0..32, 0..32) {
+        assert_eq!(tile[j][i], x[i] * y[j], "i = {i}, j = {j}");
+    }
+}
+
+#[test]
+#[should_panic = "`plane` must be in range `0..4`"]
+fn read_z_plane_out_of_range() {
+    let mut ctx = common::ctx();
+    ctx.read_z_plane_f32(4);
 }
@@ -0,0 +1,107 @@
+mod common;
+
+use amx::{
+    linalg::{self, MatMut, MatRef},
+    parallel::{parallel_for_tiles, parallel_sgemm},
+};
+use std::sync::Mutex;
+
+#[test]
+fn for_tiles_covers_each_tile_once() {
+    for (num_threads, num_tiles) in [(1, 5), (3, 10), (8, 3), (4, 0)] {
+        let ranges = Mutex::new(Vec::new());
+        parallel_for_tiles(num_threads, num_tiles, |_, tiles| {
+            ranges.lock().unwrap().push(tiles)
+        })
+        .unwrap();
+
+        let mut ranges = ranges.into_inner().unwrap();
+        ranges.sort_by_key(|range| range.start);
+        assert!(ranges.len() <= num_threads);
+        assert_eq!(
+            ranges.into_iter().flatten().collect::<Vec<_>>(),
+            (0..num_tiles).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn sgemm_matches_single_thread() {
+    let (m, k, n) = (150, 70, 45);
+    let a: Vec<f32> = (0..m * k).map(|i| (i % 13) as f32 - 6.0).collect();
+    let b: Vec<f32> = (0..k * n).map(|i| (i % 7) as f32 * 0.5).collect();
+
+    let mut expected = vec![1.0; m * n];
+    linalg::sgemm(
+        &mut common::ctx(),
+        MatRef::new(&a, m, k),
+        MatRef::new(&b, k, n),
+        MatMut::new(&mut expected, m, n),
+        true,
+    );
+
+    for num_threads in [1, 3, 16] {
+        let mut c = vec![1.0; m * n];
+        parallel_sgemm(
+            num_threads,
+            MatRef::new(&a, m, k),
+            MatRef::new(&b, k, n),
+            MatMut::new(&mut c, m, n),
+            true,
+        )
+        .unwrap();
+        assert_eq!(c, expected);
+    }
+}
+
+#[test]
+fn split_at_row() {
+    let data: Vec<u32> = (0..12).collect();
+    let (top, bottom) = MatRef::with_stride(&data, 3, 3, 4).split_at_row(1);
+    assert_eq!((top.rows(), bottom.rows()), (1, 2));
+    assert_eq!(top.row(0), [0, 1, 2]);
+    assert_eq!(bottom.row(1), [8, 9, 10]);
+
+    let mut data = data;
+    let (mut top, bottom) = MatMut::new(&mut data, 4, 3).split_at_row(4);
+    assert_eq!(bottom.rows(), 0);
+    top.row_mut(3).fill(0);
+    assert_eq!(data[9..], [0, 0, 0]);
+}
+
+#[test]
+fn sgemm_bit_identical_across_blockings() {
+    // Cross the packed panel boundaries with values whose sums round
+    let (m, k, n) = (300, 600, 40);
+    let a: Vec<f32> = (0..m * k).map(|i| ((i * 37 % 101) as f32).sin()).collect();
+    let b: Vec<f32> = (0..k * n)
+        .map(|i| ((i * 11 % 97) as f32).cos() / 3.0)
+        .collect();
+
+    let mut expected = vec![0.0; m * n];
+    linalg::sgemm(
+        &mut common::ctx(),
+        MatRef::new(&a, m, k),
+        MatRef::new(&b, k, n),
+        MatMut::new(&mut expected, m, n),
+        false,
+    );
+
+    for num_threads in [1, 2, 3, 4] {
+        let mut c = vec![0.0; m * n];
+        parallel_sgemm(
+            num_threads,
+            MatRef::new(&a, m, k),
+            MatRef::new(&b, k, n),
+            MatMut::new(&mut c, m, n),
+            false,
+        )
+        .unwrap();
+        assert!(
+            c.iter()
+                .zip(&expected)
+                .all(|(x, y)| x.to_bits() == y.to_bits()),
+            "{num_threads} threads"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use amx::pipeline::Pipeline;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Load(usize, usize),
+    Compute(usize, usize),
+}
+
+fn trace(pipeline: Pipeline, steps: usize) -> Vec<Event> {
+    let mut events = Vec::new();
+    pipeline.run(
+        &mut events,
+        steps,
+        |events, step, slot| events.push(Event::Load(step, slot.index())),
+        |events, step, slot| events.push(Event::Compute(step, slot.index())),
+    );
+    events
+}
+
+#[test]
+fn pipeline_double_buffered_order() {
+    use Event::*;
+    assert_eq!(
+        trace(Pipeline::new(2, 1, 1), 3),
+        [
+            Load(0, 0),
+            Load(1, 1),
+            Compute(0, 0),
+            Load(2, 0),
+            Compute(1, 1),
+            Compute(2, 0),
+        ]
+    );
+}
+
+#[test]
+fn pipeline_slots_are_not_clobbered() {
+    for depth in 1..=8 {
+        for steps in 0..20 {
+            let events = trace(Pipeline::new(depth, 1, 1), steps);
+
+            // `slots[i]` = the step whose operands are currently in slot `i`
+            let mut slots = vec![None; depth];
+            let mut computed = 0;
+            for event in events {
+                match event {
+                    Event::Load(step, slot) => {
+                        assert_eq!(slot, step % depth);
+                        assert_eq!(slots[slot], None, "slot {slot} is still in use");
+                        slots[slot] = Some(step);
+                    }
+                    Event::Compute(step, slot) => {
+                        assert_eq!(step, computed);
+                        assert_eq!(slots[slot].take(), Some(step));
+                        computed += 1;
+                    }
+                }
+            }
+            assert_eq!(computed, steps);
+        }
+    }
+}
+
+#[test]
+fn pipeline_rows() {
+    let p = Pipeline::new(2, 3, 4);
+    assert_eq!(p.slot(1).x_row(2), amx::XRow(5));
+    assert_eq!(p.slot(1).y_row(3), amx::YRow(7));
+    assert_eq!(p.slot(3).y_bytes(0), amx::YBytes(4 * 64));
+    assert_eq!(Pipeline::deepest(2, 1).depth(), 4);
+}
+
+#[test]
+#[should_panic]
+fn pipeline_too_deep() {
+    Pipeline::new(3, 3, 1);
+}
@@ -0,0 +1,20 @@
+#![cfg(target_arch = "aarch64")]
+mod common;
+
+use amx::AmxReport;
+
+#[test]
+fn collect() {
+    let report = AmxReport::collect();
+    assert_eq!(report.ctx_status, Ok(()));
+    let dtypes: Vec<_> = report.throughput.iter().map(|t| t.dtype).collect();
+    assert_eq!(dtypes, ["f16", "f32", "f64", "i16"]);
+    assert!(report.throughput.iter().all(|t| t.gops > 0.0));
+
+    let json = report.to_json();
+    assert!(json.starts_with(r#"{"chip":"#), "{json}");
+    assert!(json.contains(r#""ctx_status":"ok""#), "{json}");
+
+    // The context has been released
+    let _ctx = common::ctx();
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+mod common;
+
+use amx::{Amx, AmxEmuGeometry, AmxState, XBytes, XRow, YBytes, YRow, ZRow};
+
+#[test]
+fn state_round_trip() {
+    let mut ctx = common::ctx();
+    let x: [f32; 16] = std::array::from_fn(|i| i as f32 - 4.5);
+    let y: [f32; 16] = std::array::from_fn(|i| i as f32 * 0.25);
+    ctx.load512_slice(&x, XRow(1));
+    ctx.load512_slice(&y, YRow(2));
+    ctx.outer_product_f32_xy_to_z(Some(XBytes(64)), Some(YBytes(128)), ZRow(0), false);
+
+    let state = ctx.snapshot();
+    let json = serde_json::to_string(&state).unwrap();
+    let decoded: AmxState = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, state);
+
+    let mut emu = amx::AmxEmuCtx::default();
+    emu.restore(&decoded);
+    assert_eq!(emu.read_z(), ctx.read_z());
+}
+
+#[test]
+fn emu_state_with_custom_geometry() {
+    let geometry = AmxEmuGeometry {
+        x_rows: 3,
+        y_rows: 16,
+        z_rows: 128,
+    };
+    let mut ctx = amx::AmxEmuCtx::new(geometry);
+    ctx.z_mut()[100] = [7; 64];
+    ctx.x_mut()[150] = 1;
+
+    let json = serde_json::to_vec(&*ctx).unwrap();
+    let decoded: AmxState = serde_json::from_slice(&json).unwrap();
+    assert_eq!(decoded.geometry(), geometry);
+    assert_eq!(decoded, *ctx);
+}
+
+#[test]
+fn reject_inconsistent_state() {
+    let mut value = serde_json::to_value(AmxState::new(AmxEmuGeometry::M1)).unwrap();
+    value["z"].as_array_mut().unwrap().pop();
+    let err = serde_json::from_value::<AmxState>(value).unwrap_err();
+    assert!(err.to_string().contains("`z` has 4095 bytes"), "{err}");
+
+    let mut value = serde_json::to_value(AmxState::new(AmxEmuGeometry::M1)).unwrap();
+    value["geometry"]["z_rows"] = 32.into();
+    assert!(serde_json::from_value::<AmxState>(value).is_err());
+}
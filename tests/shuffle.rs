@@ -0,0 +1,66 @@
+mod common;
+
+use amx::{Amx, AmxElement, Shuffle512, XRow, YRow, ZRow};
+
+/// Shuffle `src` by `indices` (each reduced modulo `limit`) on the context
+/// and compare the result against a scalar permutation.
+fn check<T: AmxElement + PartialEq + std::fmt::Debug>(src: &[T], indices: &[u8], limit: u8) {
+    let lanes = src.len();
+    let indices: Vec<u8> = (0..lanes)
+        .map(|i| indices.get(i).map_or(0, |&x| x % limit))
+        .collect();
+    let shuffle = Shuffle512::new::<T>(&indices);
+
+    let mut ctx = common::ctx();
+    ctx.load512_slice(src, XRow(4));
+    ctx.shuffle512(&shuffle, XRow(4), YRow(3), ZRow(17));
+    let mut got = src.to_vec();
+    ctx.store512_slice(&mut got, ZRow(17));
+
+    let expected: Vec<T> = indices.iter().map(|&i| src[i as usize]).collect();
+    assert_eq!(got, expected, "indices = {indices:?}");
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_shuffle_u8(src: Vec<u8>, indices: Vec<u8>) {
+    let src: Vec<u8> = (0..64).map(|i| src.get(i).copied().unwrap_or(0)).collect();
+    check(&src, &indices, 32);
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_shuffle_u16(src: Vec<u16>, indices: Vec<u8>) {
+    let src: Vec<u16> = (0..32).map(|i| src.get(i).copied().unwrap_or(0)).collect();
+    check(&src, &indices, 32);
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_shuffle_u32(src: Vec<u32>, indices: Vec<u8>) {
+    let src: Vec<u32> = (0..16).map(|i| src.get(i).copied().unwrap_or(0)).collect();
+    check(&src, &indices, 16);
+}
+
+#[quickcheck_macros::quickcheck]
+fn qc_shuffle_u64(src: Vec<u64>, indices: Vec<u8>) {
+    let src: Vec<u64> = (0..8).map(|i| src.get(i).copied().unwrap_or(0)).collect();
+    check(&src, &indices, 8);
+}
+
+#[test]
+fn shuffle_in_place() {
+    const REVERSE: Shuffle512 = Shuffle512::new::<f64>(&[7, 6, 5, 4, 3, 2, 1, 0]);
+    let mut ctx = common::ctx();
+    let src: [f64; 8] = std::array::from_fn(|i| i as f64 + 0.5);
+    ctx.load512_slice(&src, XRow(0));
+    ctx.shuffle512(&REVERSE, XRow(0), YRow(0), XRow(0));
+    let mut got = [0.0f64; 8];
+    ctx.store512_slice(&mut got, XRow(0));
+    assert_eq!(got, [7.5, 6.5, 5.5, 4.5, 3.5, 2.5, 1.5, 0.5]);
+}
+
+#[test]
+#[should_panic = "lane index is out of range"]
+fn shuffle_u8_out_of_range() {
+    let mut indices = [0u8; 64];
+    indices[5] = 32;
+    Shuffle512::new::<u8>(&indices);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use amx::{Amx, VecFpAluOp, VecFpTy, XBytes, XRow, YBytes, YRow, ZRow, fp16};
+use common::Xorshift32;
+use itertools::iproduct;
+
+fn encode(ty: VecFpTy, values: &[f64]) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    for (chunk, &v) in out.chunks_exact_mut(ty.size()).zip(values) {
+        match ty {
+            VecFpTy::F16 => chunk.copy_from_slice(&fp16::f32_to_f16(v as f32).to_le_bytes()),
+            VecFpTy::F32 => chunk.copy_from_slice(&(v as f32).to_le_bytes()),
+            VecFpTy::F64 => chunk.copy_from_slice(&v.to_le_bytes()),
+        }
+    }
+    out
+}
+
+fn decode(ty: VecFpTy, b: &[u8]) -> f64 {
+    match ty {
+        VecFpTy::F16 => fp16::f16_to_f32(u16::from_le_bytes(b.try_into().unwrap())) as f64,
+        VecFpTy::F32 => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+        VecFpTy::F64 => f64::from_le_bytes(b.try_into().unwrap()),
+    }
+}
+
+#[test]
+fn vector_fp() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x9876);
+    // Small integers are represented exactly in every type, and so are
+    // their sums and products
+    let mut gen_values =
+        || -> Vec<f64> { (0..32).map(|_| (rng.next() % 33) as f64 - 16.0).collect() };
+    let (x, y, z) = (gen_values(), gen_values(), gen_values());
+
+    for (ty, op) in iproduct!(
+        [VecFpTy::F16, VecFpTy::F32, VecFpTy::F64],
+        [
+            VecFpAluOp::MulAdd,
+            VecFpAluOp::MulSub,
+            VecFpAluOp::Add,
+            VecFpAluOp::Sub,
+            VecFpAluOp::Min,
+            VecFpAluOp::Max,
+        ]
+    ) {
+        unsafe {
+            ctx.load512(encode(ty, &x).as_ptr(), XRow(6));
+            ctx.load512(encode(ty, &y).as_ptr(), YRow(1));
+            ctx.load512(encode(ty, &z).as_ptr(), ZRow(40));
+        }
+        ctx.vector_fp(ty, op, XBytes(6 * 64), YBytes(64), ZRow(40));
+
+        let mut got = [0u8; 64];
+        unsafe { ctx.store512(got.as_mut_ptr(), ZRow(40)) };
+        for (i, got) in got.chunks_exact(ty.size()).enumerate() {
+            let expected = match op {
+                VecFpAluOp::MulAdd => z[i] + x[i] * y[i],
+                VecFpAluOp::MulSub => z[i] - x[i] * y[i],
+                VecFpAluOp::Add => z[i] + x[i],
+                VecFpAluOp::Sub => z[i] - x[i],
+                VecFpAluOp::Min => x[i].min(z[i]),
+                VecFpAluOp::Max => x[i].max(z[i]),
+            };
+            assert_eq!(decode(ty, got), expected, "{ty:?}, {op:?}, lane = {i}");
+        }
+    }
+}
+
+#[test]
+fn dot_f32() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x2468);
+    // Small integers keep every partial sum exact, so the result doesn't
+    // depend on the order of the additions
+    let mut gen_values =
+        |len: usize| -> Vec<f32> { (0..len).map(|_| (rng.next() % 17) as f32 - 8.0).collect() };
+
+    // More than 64 pairs with various lengths
+    let lens: Vec<usize> = (0..70).map(|i| (i * 7) % 40).collect();
+    let xs: Vec<Vec<f32>> = lens.iter().map(|&len| gen_values(len)).collect();
+    let ys: Vec<Vec<f32>> = lens.iter().map(|&len| gen_values(len)).collect();
+    let pairs: Vec<(&[f32], &[f32])> = xs.iter().zip(&ys).map(|(x, y)| (&x[..], &y[..])).collect();
+
+    let mut out = vec![f32::NAN; pairs.len()];
+    ctx.dot_f32_batch(&pairs, &mut out);
+    for (i, (x, y)) in pairs.iter().enumerate() {
+        let expected: f32 = x.iter().zip(*y).map(|(x, y)| x * y).sum();
+        assert_eq!(out[i], expected, "pair {i}");
+        assert_eq!(ctx.dot_f32(x, y), expected, "pair {i}");
+    }
+}
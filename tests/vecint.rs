@@ -0,0 +1,59 @@
+mod common;
+
+use amx::{Amx, VecIntOp, VecIntTy, XBytes, XRow, YBytes, YRow, ZRow};
+use common::Xorshift32;
+use itertools::iproduct;
+
+fn read(ty: VecIntTy, b: &[u8]) -> i64 {
+    match ty {
+        VecIntTy::I8 => b[0] as i8 as i64,
+        VecIntTy::I16 => i16::from_le_bytes(b.try_into().unwrap()) as i64,
+        VecIntTy::I32 => i32::from_le_bytes(b.try_into().unwrap()) as i64,
+    }
+}
+
+#[test]
+fn vector_int() {
+    let mut ctx = common::ctx();
+    let mut rng = Xorshift32(0x2468);
+    let x: [u8; 64] = std::array::from_fn(|_| rng.next() as u8);
+    let y: [u8; 64] = std::array::from_fn(|_| rng.next() as u8);
+    let z: [u8; 64] = std::array::from_fn(|_| rng.next() as u8);
+
+    for (ty, shift, saturate, accumulate, subtract) in iproduct!(
+        [VecIntTy::I8, VecIntTy::I16, VecIntTy::I32],
+        [0, 1, 7, 16, 31],
+        [false, true],
+        [false, true],
+        [false, true]
+    ) {
+        unsafe {
+            ctx.load512(x.as_ptr(), XRow(2));
+            ctx.load512(y.as_ptr(), YRow(5));
+            ctx.load512(z.as_ptr(), ZRow(13));
+        }
+        let op = VecIntOp::new(ty)
+            .shift(shift)
+            .saturate(saturate)
+            .accumulate(accumulate)
+            .subtract(subtract);
+        ctx.vector_int(op, XBytes(128), YBytes(320), ZRow(13));
+
+        let mut got = [0u8; 64];
+        unsafe { ctx.store512(got.as_mut_ptr(), ZRow(13)) };
+
+        let size = ty.size();
+        let bits = size as u32 * 8;
+        for i in 0..64 / size {
+            let lane = |a: &[u8; 64]| read(ty, &a[i * size..][..size]);
+            let p = (lane(&x) * lane(&y)) >> shift;
+            let base = if accumulate { lane(&z) } else { 0 };
+            let mut expected = if subtract { base - p } else { base + p };
+            if saturate {
+                expected = expected.clamp(-1 << (bits - 1), (1 << (bits - 1)) - 1);
+            }
+            let expected = read(ty, &expected.to_le_bytes()[..size]);
+            assert_eq!(lane(&got), expected, "{op:?}, lane = {i}");
+        }
+    }
+}